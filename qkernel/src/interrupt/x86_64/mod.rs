@@ -19,6 +19,7 @@ use super::super::asm::*;
 use super::super::qlib::addr::*;
 use super::super::qlib::backtracer;
 use super::super::qlib::common::*;
+use super::super::qlib::kernel::memmgr::oom::CheckOOM;
 use super::super::qlib::kernel::TSC;
 use super::super::qlib::linux_def::*;
 use super::super::qlib::singleton::*;
@@ -580,6 +581,8 @@ pub extern "C" fn PageFaultHandler(ptRegs: &mut PtRegs, errorCode: u64) {
             }
 
             if fromUser {
+                drop(_ml);
+                CheckOOM(currTask);
                 //PerfGoto(PerfType::User);
                 currTask.AccountTaskEnter(SchedState::RunningApp);
                 /*if SHARESPACE.config.read().KernelPagetable {
@@ -606,6 +609,8 @@ pub extern "C" fn PageFaultHandler(ptRegs: &mut PtRegs, errorCode: u64) {
             currTask.mm.CopyOnWriteLocked(pageAddr, &vma);
             currTask.mm.TlbShootdown();
             if fromUser {
+                drop(_ml);
+                CheckOOM(currTask);
                 //PerfGoto(PerfType::User);
                 currTask.AccountTaskEnter(SchedState::RunningApp);
 
@@ -332,6 +332,8 @@ pub extern "C" fn syscall_handler(
     //currTask.DoStop();
 
     let state = SysCall(currTask, nr, &args);
+    // the memory charged by the syscall, e.g. mmap populate, might go over the memcg limit
+    memmgr::oom::CheckPendingOOM();
     MainRun(currTask, state);
     res = currTask.Return();
     currTask.DoStop();
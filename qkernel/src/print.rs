@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use super::qlib::config::{LogFormat, LogRecord};
 use super::qlib::kernel::Timestamp;
 use super::qlib::vcpu_mgr::*;
 use super::task::*;
@@ -45,12 +46,12 @@ impl log::Log for SimpleLogger {
 
     fn log(&self, record: &Record) {
         if self.enabled(record.metadata()) {
+            let level = format!("{}", record.level());
+            let str = FormatLog(&level, &format!("{}", record.args()));
             if crate::SHARESPACE.config.read().SyncPrint() {
-                let str = format!("[{}] {}", record.level(), record.args());
                 crate::Kernel::HostSpace::SyncPrint(crate::qlib::config::DebugLevel::Error, &str);
             } else {
-                let str = format!("[{}] {}", record.level(), record.args());
-                crate::Kernel::HostSpace::Kprint(&str);
+                crate::Kernel::HostSpace::Kprint(&format!("{}\n", str));
             }
         }
     }
@@ -72,6 +73,41 @@ pub fn PrintPrefix() -> String {
     );
 }
 
+// FormatLog formats one log record in the log format of the sandbox
+pub fn FormatLog(level: &str, msg: &str) -> String {
+    if super::SHARESPACE.config.read().LogFormat != LogFormat::Json {
+        return format!("[{}] {} {}", level, PrintPrefix(), msg);
+    }
+
+    // the log might be printed with the thread or the sandbox id locked, skip the
+    // field instead of deadlock
+    let task = Task::Current();
+    let container = match &task.thread {
+        None => String::new(),
+        Some(thread) => match thread.try_lock() {
+            None => String::new(),
+            Some(t) => t.containerID.clone(),
+        },
+    };
+    let sandbox = match super::SHARESPACE.sandboxId.try_lock() {
+        None => String::new(),
+        Some(id) => id.clone(),
+    };
+
+    let record = LogRecord {
+        ts: Timestamp(),
+        level: level,
+        sandbox: &sandbox,
+        container: &container,
+        vcpu: Some(CPULocal::CpuId()),
+        task: Some(Task::TaskId().Addr()),
+        thread: None,
+        msg: msg,
+    };
+
+    return record.ToJson();
+}
+
 #[macro_export]
 macro_rules! raw {
     // macth like arm for macro
@@ -99,9 +135,8 @@ macro_rules! print {
     ($($arg:tt)*) => ({
         if $crate::SHARESPACE.config.read().DebugLevel >= $crate::qlib::config::DebugLevel::Error {
             //$crate::qlib::perf_tunning::PerfGoto($crate::qlib::perf_tunning::PerfType::Print);
-            let prefix = $crate::print::PrintPrefix();
             let s = &format!($($arg)*);
-            let str = format!("[Print] {} {}", prefix, s);
+            let str = $crate::print::FormatLog("Print", s);
 
             $crate::Kernel::HostSpace::SyncPrint($crate::qlib::config::DebugLevel::Error, &str);
             //$crate::qlib::perf_tunning::PerfGofrom($crate::qlib::perf_tunning::PerfType::Print);
@@ -114,15 +149,13 @@ macro_rules! error {
     ($($arg:tt)*) => ({
         if $crate::SHARESPACE.config.read().DebugLevel >= $crate::qlib::config::DebugLevel::Error {
             //$crate::qlib::perf_tunning::PerfGoto($crate::qlib::perf_tunning::PerfType::Print);
-            let prefix = $crate::print::PrintPrefix();
             let s = &format!($($arg)*);
+            let str = $crate::print::FormatLog("ERROR", s);
 
             if $crate::SHARESPACE.config.read().SyncPrint() {
-                let str = format!("[ERROR] {} {}", prefix, s);
                 $crate::Kernel::HostSpace::SyncPrint($crate::qlib::config::DebugLevel::Error, &str);
             } else {
-                let str = format!("[ERROR] {} {}\n", prefix, s);
-                $crate::Kernel::HostSpace::Kprint(&str);
+                $crate::Kernel::HostSpace::Kprint(&format!("{}\n", str));
             }
 
             //$crate::qlib::perf_tunning::PerfGofrom($crate::qlib::perf_tunning::PerfType::Print);
@@ -135,15 +168,13 @@ macro_rules! info {
     ($($arg:tt)*) => ({
         if $crate::SHARESPACE.config.read().DebugLevel >= $crate::qlib::config::DebugLevel::Info {
             //$crate::qlib::perf_tunning::PerfGoto($crate::qlib::perf_tunning::PerfType::Print);
-            let prefix = $crate::print::PrintPrefix();
             let s = &format!($($arg)*);
+            let str = $crate::print::FormatLog("INFO", s);
 
             if $crate::SHARESPACE.config.read().SyncPrint() {
-                let str = format!("[INFO] {} {}", prefix, s);
                 $crate::Kernel::HostSpace::SyncPrint($crate::qlib::config::DebugLevel::Error, &str);
             } else {
-                 let str = format!("[INFO] {} {}\n", prefix, s);
-                 $crate::Kernel::HostSpace::Kprint(&str);
+                $crate::Kernel::HostSpace::Kprint(&format!("{}\n", str));
             }
             //$crate::qlib::perf_tunning::PerfGofrom($crate::qlib::perf_tunning::PerfType::Print);
         }
@@ -155,15 +186,13 @@ macro_rules! warn {
     ($($arg:tt)*) => ({
         if $crate::SHARESPACE.config.read().DebugLevel >= $crate::qlib::config::DebugLevel::Info {
             //$crate::qlib::perf_tunning::PerfGoto($crate::qlib::perf_tunning::PerfType::Print);
-            let prefix = $crate::print::PrintPrefix();
             let s = &format!($($arg)*);
+            let str = $crate::print::FormatLog("WARN", s);
 
             if $crate::SHARESPACE.config.read().SyncPrint() {
-                let str = format!("[WARN] {} {}", prefix, s);
                 $crate::Kernel::HostSpace::SyncPrint($crate::qlib::config::DebugLevel::Error, &str);
             } else {
-                 let str = format!("[WARN] {} {}\n", prefix, s);
-                 $crate::Kernel::HostSpace::Kprint(&str);
+                $crate::Kernel::HostSpace::Kprint(&format!("{}\n", str));
             }
             //$crate::qlib::perf_tunning::PerfGofrom($crate::qlib::perf_tunning::PerfType::Print);
         }
@@ -175,15 +204,13 @@ macro_rules! debug {
     ($($arg:tt)*) => ({
         if $crate::SHARESPACE.config.read().DebugLevel >= $crate::qlib::config::DebugLevel::Debug {
             //$crate::qlib::perf_tunning::PerfGoto($crate::qlib::perf_tunning::PerfType::Print);
-            let prefix = $crate::print::PrintPrefix();
             let s = &format!($($arg)*);
+            let str = $crate::print::FormatLog("DEBUG", s);
 
             if $crate::SHARESPACE.config.read().SyncPrint() {
-                let str = format!("[DEBUG] {} {}", prefix, s);
                 $crate::Kernel::HostSpace::SyncPrint($crate::qlib::config::DebugLevel::Error, &str);
             } else {
-                let str = format!("[DEBUG] {} {}\n", prefix, s);
-                $crate::Kernel::HostSpace::Kprint(&str);
+                $crate::Kernel::HostSpace::Kprint(&format!("{}\n", str));
             }
             //$crate::qlib::perf_tunning::PerfGofrom($crate::qlib::perf_tunning::PerfType::Print);
         }
@@ -336,6 +336,7 @@ pub fn Execvat(
             let newMM = MemoryManager::Init(false);
             let oldMM = task.mm.clone();
             *newMM.metadata.lock() = oldMM.metadata.lock().Fork();
+            newMM.SetMemCgroup(oldMM.MemCgroup());
             newMM.SetVcpu(GetVcpuId());
             task.mm = newMM.clone();
             task.futexMgr = task.futexMgr.Fork();
@@ -757,7 +758,15 @@ pub fn SysSchedGetaffinity(task: &mut Task, args: &SyscallArguments) -> Result<i
         }
     };
 
-    let mask = t.CPUMask();
+    let mut mask = t.CPUMask();
+    // hide the parked vcpus, vcpu#0 is the io thread which is not an application core
+    let onlineCores = SHARESPACE.scheduler.OnlineVcpuCnt() - 1;
+    let mut online = mask.Copy();
+    online.ClearAbove(onlineCores);
+    if online.NumCPUs() > 0 {
+        mask = online;
+    }
+
     // The buffer needs to be big enough to hold a cpumask with
     // all possible cpus.
     if size < mask.Size() {
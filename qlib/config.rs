@@ -18,6 +18,9 @@ pub struct Config {
     pub KernelMemSize: u64,
     pub LogType: LogType,
     pub LogLevel: LogLevel,
+    // LogFormat is the format of the log records, text lines or one json object per line
+    #[serde(default)]
+    pub LogFormat: LogFormat,
     pub UringIO: bool,
     pub UringFixedFile: bool,
     pub EnableAIO: bool,
@@ -44,6 +47,14 @@ pub struct Config {
     pub Realtime: bool,
     pub EnableIOBuf: bool,
     pub EnableTsot: bool,
+    // create vcpus for all the host cpus at sandbox start and park the ones above the cpu quota,
+    // so that the sandbox vcpus can be changed when the cpu quota is updated
+    #[serde(default)]
+    pub VcpuHotplug: bool,
+    // the memory pressure (psi "some avg10" percent) of the sandbox cgroup above which the
+    // memory balloon inflates, 0 means the balloon isn't driven by the memory pressure
+    #[serde(default)]
+    pub BalloonPressure: u32,
 }
 
 impl Config {
@@ -65,6 +76,7 @@ impl Default for Config {
             KernelMemSize: 16, // GB
             LogType: LogType::Sync,
             LogLevel: LogLevel::Simple,
+            LogFormat: LogFormat::Text,
             UringIO: true,
             UringFixedFile: false,
             EnableAIO: false,
@@ -90,6 +102,8 @@ impl Default for Config {
             Realtime: false,
             EnableIOBuf: false,
             EnableTsot: false,
+            VcpuHotplug: false,
+            BalloonPressure: 0,
         };
     }
 }
@@ -130,3 +144,38 @@ pub enum LogType {
     Sync,
     Async,
 }
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+impl Default for LogFormat {
+    fn default() -> Self {
+        return Self::Text;
+    }
+}
+
+// LogRecord is one log line in the json log format
+#[derive(Debug, Serialize)]
+pub struct LogRecord<'a> {
+    pub ts: i64,
+    pub level: &'a str,
+    pub sandbox: &'a str,
+    #[serde(skip_serializing_if = "str::is_empty")]
+    pub container: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vcpu: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thread: Option<i32>,
+    pub msg: &'a str,
+}
+
+impl<'a> LogRecord<'a> {
+    pub fn ToJson(&self) -> alloc::string::String {
+        return serde_json::to_string(self).unwrap_or_default();
+    }
+}
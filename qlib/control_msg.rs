@@ -18,6 +18,7 @@ use core::sync::atomic::AtomicU64;
 use core::sync::atomic::Ordering;
 
 use super::auth::id::*;
+use super::config::{DebugLevel, LogFormat};
 use super::loader::*;
 use super::singleton::*;
 
@@ -103,6 +104,56 @@ pub struct StartArgs {
     pub process: Process,
 }
 
+/// MemBalloonArgs is payload for MemBalloon control msg to quark sandbox,
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MemBalloonArgs {
+    // TargetSize is the guest heap size in bytes the sandbox should shrink or grow to.
+    // The guest rounds it to 2MB chunks and stops early when it can't reclaim more memory.
+    // 0 doesn't resize the guest heap, it only returns the balloon info.
+    pub TargetSize: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct MemBalloonInfo {
+    // TotalSize is the guest heap size usable by the sandbox after the resize
+    pub TotalSize: u64,
+    // BalloonSize is the memory currently returned to the host by the balloon
+    pub BalloonSize: u64,
+    // FreeSize is the free guest heap memory after the resize
+    pub FreeSize: u64,
+}
+
+/// VcpuResizeArgs is payload for VcpuResize control msg to quark sandbox,
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VcpuResizeArgs {
+    // Count is the number of vcpus which should take tasks, including the io vcpu.
+    // The vcpus above it are parked, it is bounded by the vcpus created at sandbox start.
+    pub Count: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct VcpuInfo {
+    // Online is the count of the vcpus taking tasks after the resize
+    pub Online: usize,
+    // Total is the count of the vcpus created at sandbox start
+    pub Total: usize,
+}
+
+/// LogLevelArgs is payload for SetLogLevel control msg to quark sandbox,
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LogLevelArgs {
+    // Level is the new debug level of the sandbox, it is not changed when None
+    pub Level: Option<DebugLevel>,
+    // Format is the new log format of the sandbox, it is not changed when None
+    pub Format: Option<LogFormat>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LogLevelInfo {
+    pub Level: DebugLevel,
+    pub Format: LogFormat,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub enum Payload {
     RootContainerStart(RootProcessStart),
@@ -117,6 +168,9 @@ pub enum Payload {
     CreateSubContainer(CreateArgs),
     StartSubContainer(StartArgs),
     WaitAll,
+    MemBalloon(MemBalloonArgs),
+    VcpuResize(VcpuResizeArgs),
+    SetLogLevel(LogLevelArgs),
 }
 
 impl Default for Payload {
@@ -150,6 +204,9 @@ pub enum UCallResp {
     CreateSubContainerResp,
     StartSubContainerResp,
     WaitAllResp(WaitAllResp),
+    MemBalloonResp(MemBalloonInfo),
+    VcpuResizeResp(VcpuInfo),
+    SetLogLevelResp(LogLevelInfo),
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -173,4 +230,7 @@ pub struct WaitAllResp {
     pub cid: String,
     pub execId: String,
     pub status: i32,
+    // whether the process or the container is killed by the guest oom killer
+    #[serde(default)]
+    pub oomKilled: bool,
 }
@@ -679,6 +679,16 @@ impl HostSpace {
         return ret;
     }
 
+    pub fn RemoveGuestMemRange(start: u64, len: u64) -> i64 {
+        let mut msg = Msg::RemoveGuestMemRange(RemoveGuestMemRange {
+            start: start,
+            len: len,
+        });
+
+        let ret = Self::Call(&mut msg, false) as i64;
+        return ret;
+    }
+
     pub fn NividiaDriverVersion(version: &RMAPIVersion) -> i64 {
         let mut msg = Msg::NividiaDriverVersion(NividiaDriverVersion {
             ioctlParamsAddr: version as * const _ as u64
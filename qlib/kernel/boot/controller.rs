@@ -26,6 +26,7 @@ use super::super::super::super::kernel_def::{
 use super::super::super::common::*;
 use super::super::super::control_msg::*;
 use super::super::super::vcpu_mgr::*;
+use super::super::memmgr::balloon::*;
 use super::super::task::*;
 use super::super::taskMgr;
 use super::super::Kernel;
@@ -238,6 +239,47 @@ pub fn ControlMsgHandler(fd: *const u8) {
         Payload::WaitAll => {
             SetWaitContainerfd(fd);
         }
+        Payload::MemBalloon(args) => match MEM_BALLOON.Resize(args.TargetSize) {
+            Ok(info) => {
+                WriteControlMsgResp(fd, &UCallResp::MemBalloonResp(info), true);
+            }
+            Err(e) => {
+                WriteControlMsgResp(fd, &UCallResp::UCallRespErr(format!("{:?}", e)), true);
+            }
+        },
+        Payload::VcpuResize(args) => {
+            let online = SHARESPACE.scheduler.SetOnlineVcpuCnt(args.Count);
+            info!("VcpuResize: {} vcpus online", online);
+            // kick the new online vcpus to take tasks
+            SHARESPACE.scheduler.WakeAll();
+            let info = VcpuInfo {
+                Online: online,
+                Total: SHARESPACE.scheduler.vcpuCnt,
+            };
+            WriteControlMsgResp(fd, &UCallResp::VcpuResizeResp(info), true);
+        }
+        Payload::SetLogLevel(args) => {
+            let info = {
+                let mut config = SHARESPACE.config.write();
+                if let Some(level) = args.Level {
+                    config.DebugLevel = level;
+                }
+                if let Some(format) = args.Format {
+                    config.LogFormat = format;
+                }
+                LogLevelInfo {
+                    Level: config.DebugLevel,
+                    Format: config.LogFormat,
+                }
+            };
+
+            // the log macros read the config, print after it is unlocked
+            info!(
+                "SetLogLevel: debug level {:?}, log format {:?}",
+                info.Level, info.Format
+            );
+            WriteControlMsgResp(fd, &UCallResp::SetLogLevelResp(info), true);
+        }
     }
 
     // free curent task in the waitfn context
@@ -245,7 +287,7 @@ pub fn ControlMsgHandler(fd: *const u8) {
     super::super::taskMgr::SwitchToNewTask();
 }
 
-pub fn WriteWaitAllResponse(cid: String, execId: String, status: i32) {
+pub fn WriteWaitAllResponse(cid: String, execId: String, status: i32, oomKilled: bool) {
     let fd = WaitContainerfd();
     WriteControlMsgResp(
         fd,
@@ -253,6 +295,7 @@ pub fn WriteWaitAllResponse(cid: String, execId: String, status: i32) {
             cid,
             execId,
             status,
+            oomKilled,
         }),
         false,
     );
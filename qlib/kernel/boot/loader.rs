@@ -38,6 +38,7 @@ use super::super::kernel::ipc_namespace::*;
 use super::super::kernel::kernel::*;
 use super::super::kernel::uts_namespace::*;
 use super::super::kernel::waiter::qlock::*;
+use super::super::memmgr::oom::MEM_CGROUPS;
 use super::super::task::*;
 use super::super::threadmgr::thread::*;
 use super::super::threadmgr::thread_group::*;
@@ -362,6 +363,7 @@ impl Loader {
             .mounts
             .write()
             .insert(processSpec.ID.clone(), rootMounts);
+        MEM_CGROUPS.SetLimit(&processSpec.ID, processSpec.MemoryLimit);
 
         //todo: investigate PID namespace and whether we need it.
         let mut createProcessArgs = NewProcess(processSpec, &creds, &kernel);
@@ -492,6 +494,7 @@ impl LoaderInternal {
         let rootMounts =
             InitRootFs(Task::Current(), &process.Root).expect("in loader::New, InitRootfs fail");
         kernel.mounts.write().insert(sandboxID.clone(), rootMounts);
+        MEM_CGROUPS.SetLimit(&sandboxID, process.MemoryLimit);
 
         let processArgs = NewProcess(process, &creds, &kernel);
         self.kernel = kernel;
@@ -712,6 +715,7 @@ pub fn NewProcess(process: Process, creds: &auth::Credentials, k: &Kernel) -> Cr
         Stdiofds: stdiofds,
         Terminal: process.Terminal,
         ExecId: process.ExecId.clone(),
+        OOMScoreAdj: process.OOMScoreAdj,
         ..Default::default()
     };
 }
@@ -13,37 +13,239 @@
 // limitations under the License.
 
 use alloc::collections::btree_map::BTreeMap;
+use alloc::string::String;
+use alloc::string::ToString;
 use alloc::sync::Arc;
+use alloc::vec::Vec;
 use core::ops::Deref;
 use spin::Mutex;
 
 use crate::qlib::common::*;
+use crate::qlib::kernel::kernel::timer::MonotonicNow;
 use crate::qlib::kernel::socket::hostinet::tsot_mgr::TsotSocketMgr;
+use crate::qlib::linux::time::SECOND;
 use crate::qlib::linux_def::*;
 use crate::qlib::kernel::tcpip::tcpip::SockAddrInet;
 use crate::qlib::kernel::quring::uring_async::DNSSend;
 use crate::qlib::kernel::IOURING;
-use crate::qlib::tsot_msg::DnsResp;
+use crate::qlib::tsot_msg::*;
 
 use super::*;
 
+// the maximum names of one DnsReq to the node agent. The search domains are expanded by
+// the resolver of the guest with the pod resolv.conf, so the names are looked up as is
+pub const DNS_MAX_LOOKUPS: usize = 4;
+pub const DNS_CACHE_SIZE: usize = 1024;
+
+#[derive(Debug, Clone)]
+pub struct DnsCacheEntry {
+    pub rcode: ResultCode,
+    // 0 for no A answer
+    pub ipv4: u32,
+    // all zero for no AAAA answer
+    pub ipv6: [u8; 16],
+    // the CNAME chain from the requested name to the canonical name
+    pub cnames: Vec<String>,
+    // the monotonic time when the entry expires
+    pub expire: i64,
+    // the monotonic time when the entry is looked up last, the least recently used entry is
+    // evicted when the cache is full
+    pub lastUse: i64,
+}
+
+impl DnsCacheEntry {
+    pub fn Ttl(&self, now: i64) -> u32 {
+        if self.expire <= now {
+            return 0;
+        }
+
+        return ((self.expire - now) / SECOND) as u32;
+    }
+}
+
 #[derive(Debug)]
 pub struct DnsReqContext {
     pub peerIp: [u8; 4],
     pub peerPort: u16,
+    pub header: DnsHeader,
     pub reqs: Vec<DnsQuestion>,
+    // the names which are being looked up by the node agent
+    pub lookups: Vec<String>,
+    // name --> result, a missing name is answered with SERVFAIL
+    pub results: BTreeMap<String, DnsCacheEntry>,
 }
 
 #[derive(Debug)]
 pub struct DnsSvcInner {
     pub fd: i32,
+    pub nextReqId: u16,
     pub pendingReqs: BTreeMap<u16, DnsReqContext>,
+    pub cache: BTreeMap<String, DnsCacheEntry>,
     pub recvBuf: BytePacketBuffer,
     pub iov: IoVec,
     pub peerAddr: SockAddrInet,
     pub msg: MsgHdr,
 }
 
+impl DnsSvcInner {
+    pub fn NewReqId(&mut self) -> u16 {
+        loop {
+            let reqId = self.nextReqId;
+            self.nextReqId = self.nextReqId.wrapping_add(1);
+            if !self.pendingReqs.contains_key(&reqId) {
+                return reqId;
+            }
+        }
+    }
+
+    pub fn CacheLookup(&mut self, name: &str, now: i64) -> Option<DnsCacheEntry> {
+        match self.cache.get_mut(name) {
+            Some(entry) if entry.expire > now => {
+                entry.lastUse = now;
+                return Some(entry.clone());
+            }
+            _ => return None,
+        }
+    }
+
+    pub fn CacheInsert(&mut self, name: &str, entry: &DnsCacheEntry, now: i64) {
+        if self.cache.len() >= DNS_CACHE_SIZE {
+            self.cache.retain(|_, e| e.expire > now);
+        }
+
+        if self.cache.len() >= DNS_CACHE_SIZE {
+            let lru = self
+                .cache
+                .iter()
+                .min_by_key(|(_, e)| e.lastUse)
+                .map(|(name, _)| name.clone());
+            if let Some(lru) = lru {
+                self.cache.remove(&lru);
+            }
+        }
+
+        let mut entry = entry.clone();
+        entry.lastUse = now;
+        self.cache.insert(name.to_string(), entry);
+    }
+
+    pub fn SendLookups(&mut self, context: DnsReqContext) -> Result<()> {
+        let reqId = self.NewReqId();
+        let domains = context.lookups.clone();
+        self.pendingReqs.insert(reqId, context);
+
+        match TsotSocketMgr::DnsReq(reqId, &domains) {
+            Ok(()) => return Ok(()),
+            Err(e) => {
+                error!("DnsSvc::SendLookups fail with error {:?}", e);
+                // answer the lookups with SERVFAIL
+                let mut context = self.pendingReqs.remove(&reqId).unwrap();
+                context.lookups.clear();
+                return self.SendResp(&context);
+            }
+        }
+    }
+
+    pub fn SendResp(&self, context: &DnsReqContext) -> Result<()> {
+        let now = MonotonicNow();
+        let mut p = DnsPacket::new();
+
+        p.header.id = context.header.id;
+        p.header.opcode = context.header.opcode;
+        p.header.response = true;
+        p.header.recursion_desired = context.header.recursion_desired;
+        p.header.recursion_available = true;
+
+        let mut answered = false;
+        let mut nxdomain = false;
+        let mut servfail = false;
+        for req in &context.reqs {
+            if !IsAddressQuery(req.qtype) {
+                continue;
+            }
+
+            let entry = match context.results.get(&req.name) {
+                None => {
+                    servfail = true;
+                    continue;
+                }
+                Some(e) => e,
+            };
+
+            match entry.rcode {
+                ResultCode::NOERROR => answered = true,
+                ResultCode::NXDOMAIN => {
+                    nxdomain = true;
+                    continue;
+                }
+                _ => {
+                    servfail = true;
+                    continue;
+                }
+            }
+
+            let ttl = entry.Ttl(now);
+            let mut domain = req.name.clone();
+            for cname in &entry.cnames {
+                p.answers.push(DnsRecord::CNAME {
+                    domain: domain,
+                    host: cname.clone(),
+                    ttl: ttl,
+                });
+                domain = cname.clone();
+            }
+
+            if req.qtype == QueryType::A && entry.ipv4 != 0 {
+                p.answers.push(DnsRecord::A {
+                    domain: domain,
+                    addr: Ipv4Addr::fromU32(entry.ipv4),
+                    ttl: ttl,
+                });
+            } else if req.qtype == QueryType::AAAA && entry.ipv6 != [0; 16] {
+                p.answers.push(DnsRecord::AAAA {
+                    domain: domain,
+                    addr: Ipv6Addr::fromBytes(&entry.ipv6),
+                    ttl: ttl,
+                });
+            }
+        }
+
+        // NXDOMAIN only when none of the requested names exists
+        p.header.rescode = if answered {
+            ResultCode::NOERROR
+        } else if servfail {
+            ResultCode::SERVFAIL
+        } else if nxdomain {
+            ResultCode::NXDOMAIN
+        } else {
+            ResultCode::NOERROR
+        };
+
+        for req in &context.reqs {
+            p.questions.push(req.clone());
+        }
+
+        let mut buf = BytePacketBuffer::new();
+        p.write(&mut buf)?;
+
+        let peerAddr = SockAddrInet::New(context.peerPort, &context.peerIp);
+        let mut dataBuf = DataBuff::New(buf.pos);
+        for i in 0..buf.pos {
+            dataBuf.buf[i] = buf.buf[i];
+        };
+
+        let dnsSend = DNSSend::New(self.fd, dataBuf, peerAddr);
+        IOURING.SendDns(dnsSend);
+        return Ok(())
+    }
+}
+
+// the questions answered with the node agent lookup, the CNAME is answered with the canonical
+// name of the A/AAAA lookup
+pub fn IsAddressQuery(qtype: QueryType) -> bool {
+    return qtype == QueryType::A || qtype == QueryType::AAAA || qtype == QueryType::CNAME;
+}
+
 #[derive(Debug, Clone)]
 pub struct DnsSvc(pub Arc<Mutex<DnsSvcInner>>);
 
@@ -55,11 +257,6 @@ impl Deref for DnsSvc {
     }
 }
 
-pub struct DnsAnswer {
-    addr: Ipv4Addr,
-    ttl: u32,
-}
-
 pub const DNS_SVC_ADDR: [u8; 4] = [127, 0, 0, 53];
 pub const DNS_SVC_PORT: u16 = 53;
 
@@ -77,7 +274,9 @@ impl DnsSvc {
 
         let inner = DnsSvcInner {
             fd: sock,
+            nextReqId: 0,
             pendingReqs: BTreeMap::new(),
+            cache: BTreeMap::new(),
             recvBuf: recvBuf,
             iov: IoVec::default(),
             peerAddr: SockAddrInet::default(),
@@ -121,88 +320,133 @@ impl DnsSvc {
             }
         };
 
-        let mut domains = Vec::new();
-        for req in &p.questions {
-            if req.qtype == QueryType::A {
-                domains.push(req.name.clone());
-            } 
-        }
-
-        if domains.len() == 0 {
-            let mut resp = DnsPacket::new();
+        let mut context = DnsReqContext {
+            peerIp: inner.peerAddr.Addr.clone(),
+            peerPort: inner.peerAddr.Ipv4Port(),
+            header: p.header,
+            reqs: p.questions,
+            lookups: Vec::new(),
+            results: BTreeMap::new(),
+        };
 
-            //let mut header = DnsHeader::new();
-            resp.header.id = p.header.id;
-            for req in p.questions {
-                resp.questions.push(req);
+        let now = MonotonicNow();
+        for req in &context.reqs {
+            if !IsAddressQuery(req.qtype) 
+                || context.results.contains_key(&req.name) 
+                || context.lookups.contains(&req.name) {
+                continue;
             }
 
-            let mut buf = BytePacketBuffer::new();
-            resp.write(&mut buf)?;
-            let peerAddr = SockAddrInet::New(inner.peerAddr.Ipv4Port(), &inner.peerAddr.Addr.clone());
-            let mut dataBuf = DataBuff::New(buf.pos);
-            for i in 0..buf.pos {
-                dataBuf.buf[i] = buf.buf[i];
-            };
-    
-            let dnsSend = DNSSend::New(inner.fd, dataBuf, peerAddr);
-            IOURING.SendDns(dnsSend);
-            return Ok(())
+            match inner.CacheLookup(&req.name, now) {
+                Some(entry) => {
+                    context.results.insert(req.name.clone(), entry);
+                }
+                None => {
+                    context.lookups.push(req.name.clone());
+                }
+            }
         }
 
-        let context = DnsReqContext {
-            peerIp: inner.peerAddr.Addr.clone(),
-            peerPort: inner.peerAddr.Ipv4Port(),
-            reqs: p.questions,
-        };
+        // the names over the limit are answered with SERVFAIL
+        context.lookups.truncate(DNS_MAX_LOOKUPS);
 
-        inner.pendingReqs.insert(p.header.id, context);
+        if context.lookups.len() == 0 {
+            return inner.SendResp(&context);
+        }
 
-        TsotSocketMgr::DnsReq(p.header.id, &domains)?;
-        return Ok(())
+        return inner.SendLookups(context);
     }
 
     pub fn ProcessDnsResp(&self, resp: DnsResp) -> Result<()> {
-        let context = match self.lock().pendingReqs.remove(&resp.reqId) {
+        let mut inner = self.lock();
+        let mut context = match inner.pendingReqs.remove(&resp.reqId) {
             None => return Err(Error::Common(format!("DnsSvc::ProcessDnsResp get nonexisit reqid {}", resp.reqId))),
             Some(c) => c
         };
 
-        assert!(context.reqs.len() == resp.count);
+        // the lookups are sent to the node agent in order, a broken response is answered
+        // with SERVFAIL
+        if context.lookups.len() != resp.count {
+            let count = context.lookups.len();
+            context.lookups.clear();
+            inner.SendResp(&context)?;
+            return Err(Error::Common(format!(
+                "DnsSvc::ProcessDnsResp reqid {} get {} answers for {} lookups",
+                resp.reqId, resp.count, count
+            )));
+        }
 
-        let mut p = DnsPacket::new();
+        let now = MonotonicNow();
+        let cnames = resp.GetCnames();
+        for (i, name) in core::mem::take(&mut context.lookups).into_iter().enumerate() {
+            let rcode = ResultCode::from_num(resp.rcodes[i]);
+            let mut entry = DnsCacheEntry {
+                rcode: rcode,
+                ipv4: resp.ips[i],
+                ipv6: resp.ipv6s[i],
+                cnames: Vec::new(),
+                expire: now + resp.ttls[i] as i64 * SECOND,
+                lastUse: now,
+            };
 
-        //let mut header = DnsHeader::new();
-        p.header.id = resp.reqId;
-
-        for i in 0..resp.count {
-            let addr = resp.ips[i];
-            if addr != 0 {
-                let answer = DnsRecord::A {
-                    domain: context.reqs[i].name.clone(),
-                    addr: Ipv4Addr::fromU32(addr),
-                    ttl: 100,
-                };
-                p.answers.push(answer)
+            if rcode == ResultCode::NOERROR && cnames[i].len() > 0 {
+                entry.cnames.push(cnames[i].clone());
+            }
+
+            // SERVFAIL has no ttl and is not cached
+            if resp.ttls[i] > 0 {
+                inner.CacheInsert(&name, &entry, now);
             }
-        }
 
-        for req in context.reqs {
-            p.questions.push(req);
+            context.results.insert(name, entry);
         }
 
-        let mut buf = BytePacketBuffer::new();
-        p.write(&mut buf)?;
+        return inner.SendResp(&context);
+    }
+}
 
-        let peerAddr = SockAddrInet::New(context.peerPort, &context.peerIp);
-        let mut dataBuf = DataBuff::New(buf.pos);
-        for i in 0..buf.pos {
-            dataBuf.buf[i] = buf.buf[i];
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn Entry(expire: i64) -> DnsCacheEntry {
+        return DnsCacheEntry {
+            rcode: ResultCode::NOERROR,
+            ipv4: 0x0a000001,
+            ipv6: [0; 16],
+            cnames: Vec::new(),
+            expire: expire,
+            lastUse: 0,
         };
+    }
 
-        let dnsSend = DNSSend::New(self.lock().fd, dataBuf, peerAddr);
-        IOURING.SendDns(dnsSend);
-        
-        return Ok(())
+    #[test]
+    fn TestDnsCacheTtl() {
+        assert_eq!(Entry(10 * SECOND).Ttl(0), 10);
+        assert_eq!(Entry(10 * SECOND).Ttl(10 * SECOND), 0);
+        assert_eq!(Entry(10 * SECOND).Ttl(20 * SECOND), 0);
+    }
+
+    #[test]
+    fn TestDnsCacheEvict() {
+        let svc = DnsSvc::New().unwrap();
+        let mut inner = svc.lock();
+        let expire = 100 * SECOND;
+        for i in 0..DNS_CACHE_SIZE {
+            inner.CacheInsert(&format!("n{}", i), &Entry(expire), i as i64 + 1);
+        }
+        let now = DNS_CACHE_SIZE as i64 + 1;
+        assert!(inner.CacheLookup("n0", now).is_some());
+        assert!(inner.CacheLookup("n1", expire).is_none());
+
+        // n1 is the least recently used one after n0 is looked up
+        inner.CacheInsert("new", &Entry(expire), now + 1);
+        assert_eq!(inner.cache.len(), DNS_CACHE_SIZE);
+        assert!(inner.cache.contains_key("n0"));
+        assert!(!inner.cache.contains_key("n1"));
+
+        // the expired entries are dropped first
+        inner.CacheInsert("late", &Entry(expire + 1), expire);
+        assert_eq!(inner.cache.len(), 1);
     }
 }
@@ -76,6 +76,16 @@ impl Ipv6Addr {
         }
     }
 
+    pub fn fromBytes(bytes: &[u8; 16]) -> Self {
+        let mut addr = [0; 8];
+        for i in 0..8 {
+            addr[i] = ((bytes[2 * i] as u16) << 8) | (bytes[2 * i + 1] as u16);
+        }
+        return Self {
+            addr: addr
+        }
+    }
+
     pub fn segments(&self) -> [u16; 8] {
         return [self.addr[0], self.addr[1], self.addr[2], self.addr[3], self.addr[4], self.addr[5], self.addr[6], self.addr[7]]
     }
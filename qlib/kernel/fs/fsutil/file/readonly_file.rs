@@ -35,6 +35,7 @@ use crate::qlib::kernel::fs::procfs::net::NetUnixReadonlyFileNode;
 use crate::qlib::kernel::fs::procfs::task::auxvec::AUXVecReadonlyFileNode;
 use crate::qlib::kernel::fs::procfs::task::comm::CommReadonlyFileNode;
 use crate::qlib::kernel::fs::procfs::task::exec_args::ExecArgReadonlyFileNode;
+use crate::qlib::kernel::fs::procfs::task::oom_score_adj::OOMScoreAdjFileNode;
 use crate::qlib::kernel::fs::procfs::task::uid_pid_map::IdMapReadonlyFileNode;
 use crate::qlib::kernel::fs::procfs::uptime::UptimeFileNode;
 
@@ -87,6 +88,7 @@ pub enum ReadonlyFileNode {
     ExecArgReadonlyFileNode(ExecArgReadonlyFileNode),
     IdMapReadonlyFileNode(IdMapReadonlyFileNode),
     UptimeFileNode(UptimeFileNode),
+    OOMScoreAdjFileNode(OOMScoreAdjFileNode),
 }
 
 #[enum_dispatch(ReadonlyFileNode)]
@@ -101,6 +103,18 @@ pub trait ReadonlyFileNodeTrait: Send + Sync {
     ) -> Result<i64> {
         return Err(Error::SysError(SysErr::EINVAL));
     }
+
+    // only the few writable proc files implement it
+    fn WriteAt(
+        &self,
+        _task: &Task,
+        _f: &File,
+        _srcs: &[IoVec],
+        _offset: i64,
+        _blocking: bool,
+    ) -> Result<i64> {
+        return Err(Error::SysError(SysErr::EINVAL));
+    }
 }
 
 #[derive(Clone)]
@@ -160,13 +174,13 @@ impl FileOperations for ReadonlyFileOperations {
 
     fn WriteAt(
         &self,
-        _task: &Task,
-        _f: &File,
-        _srcs: &[IoVec],
-        _offset: i64,
-        _blocking: bool,
+        task: &Task,
+        f: &File,
+        srcs: &[IoVec],
+        offset: i64,
+        blocking: bool,
     ) -> Result<i64> {
-        return Err(Error::SysError(SysErr::EINVAL));
+        return self.node.WriteAt(task, f, srcs, offset, blocking);
     }
 
     fn Append(&self, task: &Task, f: &File, srcs: &[IoVec]) -> Result<(i64, i64)> {
@@ -37,6 +37,7 @@ use super::super::super::mount::*;
 use crate::qlib::kernel::fs::procfs::filesystems::FileSystemData;
 use crate::qlib::kernel::fs::procfs::loadavg::LoadAvgData;
 use crate::qlib::kernel::fs::procfs::meminfo::MeminfoInode;
+use crate::qlib::kernel::fs::procfs::net::NetBandwidth;
 use crate::qlib::kernel::fs::procfs::net::NetTCP;
 use crate::qlib::kernel::fs::procfs::net::NetUDP;
 use crate::qlib::kernel::fs::procfs::net::NetUnix;
@@ -48,11 +49,13 @@ use crate::qlib::kernel::fs::procfs::task::io::IOData;
 use crate::qlib::kernel::fs::procfs::task::maps::MapsData;
 use crate::qlib::kernel::fs::procfs::task::mounts::MountInfoFile;
 use crate::qlib::kernel::fs::procfs::task::mounts::MountsFile;
+use crate::qlib::kernel::fs::procfs::task::oom_score_adj::OOMScoreAdjSimpleFileTrait;
 use crate::qlib::kernel::fs::procfs::task::stat::TaskStatData;
 use crate::qlib::kernel::fs::procfs::task::statm::StatmData;
 use crate::qlib::kernel::fs::procfs::task::status::StatusData;
 use crate::qlib::kernel::fs::procfs::task::uid_pid_map::IdMapSimpleFileTrait;
 use crate::qlib::kernel::fs::procfs::uptime::UptimeInode;
+use crate::qlib::kernel::fs::sys::devices::OnlineData;
 use crate::qlib::kernel::fs::sys::devices::PossibleData;
 use crate::qlib::kernel::socket::unix::unix::Dummy;
 
@@ -75,6 +78,7 @@ pub enum SimpleFileImpl {
     FileSystemData(FileSystemData),
     LoadAvgData(LoadAvgData),
     MeminfoInode(MeminfoInode),
+    NetBandwidth(NetBandwidth),
     NetTCP(NetTCP),
     NetUDP(NetUDP),
     NetUnix(NetUnix),
@@ -91,7 +95,9 @@ pub enum SimpleFileImpl {
     StatmData(StatmData),
     StatusData(StatusData),
     IdMapSimpleFileTrait(IdMapSimpleFileTrait),
+    OOMScoreAdjSimpleFileTrait(OOMScoreAdjSimpleFileTrait),
     PossibleData(PossibleData),
+    OnlineData(OnlineData),
     Dummy(Dummy),
 }
 
@@ -26,6 +26,7 @@ use super::super::mount::*;
 use super::super::super::super::auth::*;
 use super::super::super::super::common::*;
 use super::super::super::super::linux_def::*;
+use super::super::super::memmgr::balloon::*;
 use super::super::super::task::*;
 use super::super::super::Kernel::HostSpace;
 use super::super::fsutil::file::readonly_file::*;
@@ -55,6 +56,11 @@ impl ReadonlyFileNodeTrait for MeminfoFileNode {
             return Err(Error::SysError(-ret as i32));
         }
 
+        // the memory held by balloon has been given back to the host
+        let balloon = MEM_BALLOON.Size();
+        info.totalram = info.totalram.saturating_sub(balloon);
+        info.freeram = info.freeram.saturating_sub(balloon);
+
         let mut s = "".to_string();
         // this is just fake meminfo
         // todo: fix this.
@@ -28,6 +28,7 @@ use super::super::super::super::linux::time::*;
 use super::super::super::super::linux_def::*;
 use super::super::super::socket::unix::transport::unix::*;
 use super::super::super::task::*;
+use super::super::super::SHARESPACE;
 use super::super::super::tcpip::tcpip::*;
 use super::super::attr::*;
 use super::super::dirent::*;
@@ -120,6 +121,7 @@ pub fn NewNetDir(task: &Task, msrc: &Arc<QMutex<MountSource>>) -> Inode {
         NewStaticProcInode(task, msrc, &Arc::new(ptype.as_bytes().to_vec())),
     );
 
+    contents.insert("bandwidth".to_string(), NewNetBandwidth(task, msrc));
     contents.insert("tcp".to_string(), NewNetTCP(task, msrc));
     contents.insert("udp".to_string(), NewNetUDP(task, msrc));
     contents.insert("unix".to_string(), NewNetUnix(task, msrc));
@@ -167,6 +169,37 @@ pub fn WriteInetAddr(addr: &SockAddr) -> String {
     }
 }
 
+// the pod bandwidth limit and the throttled counters of the host and tsot sockets
+pub struct NetBandwidth {}
+
+impl SimpleFileTrait for NetBandwidth {
+    fn GetFile(
+        &self,
+        _task: &Task,
+        _dir: &Inode,
+        dirent: &Dirent,
+        flags: FileFlags,
+    ) -> Result<File> {
+        let data = SHARESPACE.podBandwidth.Report().as_bytes().to_vec();
+        let fops = NewSnapshotReadonlyFileOperations(data);
+        let file = File::New(dirent, &flags, fops.into());
+        return Ok(file);
+    }
+}
+
+pub fn NewNetBandwidth(task: &Task, msrc: &Arc<QMutex<MountSource>>) -> Inode {
+    let node = SimpleFileInode::New(
+        task,
+        &ROOT_OWNER,
+        &FilePermissions::FromMode(FileMode(0o444)),
+        FSMagic::PROC_SUPER_MAGIC,
+        false,
+        NetBandwidth {}.into(),
+    );
+
+    return NewProcInode(node.into(), msrc, InodeType::SpecialFile, None);
+}
+
 pub struct NetTCP {}
 
 impl SimpleFileTrait for NetTCP {
@@ -20,6 +20,7 @@ pub mod fds;
 pub mod io;
 pub mod maps;
 pub mod mounts;
+pub mod oom_score_adj;
 pub mod stat;
 pub mod statm;
 pub mod status;
@@ -0,0 +1,164 @@
+// Copyright (c) 2021 Quark Container Authors / 2018 The gVisor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::qlib::mutex::*;
+use alloc::string::String;
+use alloc::sync::Arc;
+
+use super::super::super::super::super::auth::*;
+use super::super::super::super::super::common::*;
+use super::super::super::super::super::linux_def::*;
+use super::super::super::super::memmgr::oom::*;
+use super::super::super::super::task::*;
+use super::super::super::super::threadmgr::thread::*;
+use super::super::super::attr::*;
+use super::super::super::dirent::*;
+use super::super::super::file::*;
+use super::super::super::flags::*;
+use super::super::super::fsutil::file::readonly_file::*;
+use super::super::super::fsutil::inode::simple_file_inode::*;
+use super::super::super::inode::*;
+use super::super::super::mount::*;
+use super::super::inode::*;
+
+pub fn NewOOMScoreAdj(task: &Task, thread: &Thread, msrc: &Arc<QMutex<MountSource>>) -> Inode {
+    let v = NewOOMScoreAdjSimpleFileInode(
+        task,
+        thread,
+        &ROOT_OWNER,
+        &FilePermissions::FromMode(FileMode(0o644)),
+        FSMagic::PROC_SUPER_MAGIC,
+    );
+    return NewProcInode(v.into(), msrc, InodeType::SpecialFile, Some(thread.clone()));
+}
+
+pub fn NewOOMScoreAdjSimpleFileInode(
+    task: &Task,
+    thread: &Thread,
+    owner: &FileOwner,
+    perms: &FilePermissions,
+    typ: u64,
+) -> SimpleFileInode {
+    return SimpleFileInode::New(
+        task,
+        owner,
+        perms,
+        typ,
+        false,
+        OOMScoreAdjSimpleFileTrait {
+            thread: thread.clone(),
+        }
+        .into(),
+    );
+}
+
+pub struct OOMScoreAdjSimpleFileTrait {
+    pub thread: Thread,
+}
+
+impl SimpleFileTrait for OOMScoreAdjSimpleFileTrait {
+    fn GetFile(
+        &self,
+        _task: &Task,
+        _dir: &Inode,
+        dirent: &Dirent,
+        flags: FileFlags,
+    ) -> Result<File> {
+        let fops = NewOOMScoreAdjFileOperations(&self.thread);
+        let file = File::New(dirent, &flags, fops.into());
+        return Ok(file);
+    }
+}
+
+pub fn NewOOMScoreAdjFileOperations(thread: &Thread) -> ReadonlyFileOperations {
+    return ReadonlyFileOperations {
+        node: OOMScoreAdjFileNode {
+            thread: thread.clone(),
+        }
+        .into(),
+    };
+}
+
+#[derive(Clone)]
+pub struct OOMScoreAdjFileNode {
+    pub thread: Thread,
+}
+
+impl ReadonlyFileNodeTrait for OOMScoreAdjFileNode {
+    fn ReadAt(
+        &self,
+        task: &Task,
+        _f: &File,
+        dsts: &mut [IoVec],
+        offset: i64,
+        _blocking: bool,
+    ) -> Result<i64> {
+        if offset < 0 {
+            return Err(Error::SysError(SysErr::EINVAL));
+        }
+
+        let adj = self.thread.ThreadGroup().lock().oomScoreAdj;
+        let buf = format!("{}\n", adj);
+        if offset as usize >= buf.len() {
+            return Ok(0);
+        }
+
+        let n = task.CopyDataOutToIovs(&buf.as_bytes()[offset as usize..], dsts, true)?;
+
+        return Ok(n as i64);
+    }
+
+    fn WriteAt(
+        &self,
+        task: &Task,
+        _f: &File,
+        srcs: &[IoVec],
+        _offset: i64,
+        _blocking: bool,
+    ) -> Result<i64> {
+        let size = IoVec::NumBytes(srcs);
+        if size == 0 {
+            return Ok(0);
+        }
+
+        // the value is at most "-1000\n"
+        let mut buf = DataBuff::New(if size > 16 { 16 } else { size });
+        let len = task.CopyDataInFromIovs(&mut buf.buf, srcs, true)?;
+        let str = match String::from_utf8(buf.buf[..len].to_vec()) {
+            Err(_) => return Err(Error::SysError(SysErr::EINVAL)),
+            Ok(s) => s,
+        };
+
+        let adj = match str.trim().parse::<i32>() {
+            Err(_) => return Err(Error::SysError(SysErr::EINVAL)),
+            Ok(adj) => adj,
+        };
+
+        if adj < OOM_SCORE_ADJ_MIN || adj > OOM_SCORE_ADJ_MAX {
+            return Err(Error::SysError(SysErr::EINVAL));
+        }
+
+        let tg = self.thread.ThreadGroup();
+        let mut tglock = tg.lock();
+        // lowering the score needs CAP_SYS_RESOURCE
+        if adj < tglock.oomScoreAdj
+            && !task.Creds().HasCapability(Capability::CAP_SYS_RESOURCE)
+        {
+            return Err(Error::SysError(SysErr::EACCES));
+        }
+
+        tglock.oomScoreAdj = adj;
+        return Ok(size as i64);
+    }
+}
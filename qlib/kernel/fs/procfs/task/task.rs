@@ -41,6 +41,7 @@ use super::fds::*;
 use super::io::*;
 use super::maps::*;
 use super::mounts::*;
+use super::oom_score_adj::*;
 use super::stat::*;
 use super::statm::*;
 use super::status::*;
@@ -100,6 +101,7 @@ impl ProcNode {
             NewMountInfoFile(task, thread, msrc),
         );
         contents.insert("mounts".to_string(), NewMountsFile(task, thread, msrc));
+        contents.insert("oom_score_adj".to_string(), NewOOMScoreAdj(task, thread, msrc));
         contents.insert(
             "stat".to_string(),
             NewStat(task, thread, showSubtasks, self.lock().pidns.clone(), msrc),
@@ -23,6 +23,7 @@ use super::super::super::super::common::*;
 use super::super::super::super::linux_def::*;
 use super::super::super::kernel::kernel::*;
 use super::super::super::task::*;
+use super::super::super::SHARESPACE;
 use super::super::dirent::*;
 use super::super::file::*;
 use super::super::flags::*;
@@ -78,10 +79,51 @@ impl SimpleFileTrait for PossibleData {
     }
 }
 
+pub fn NewOnline(task: &Task, msrc: &Arc<QMutex<MountSource>>) -> Inode {
+    let fs = OnlineData {};
+    let v = SimpleFileInode::New(
+        task,
+        &ROOT_OWNER,
+        &FilePermissions::FromMode(FileMode(0o400)),
+        FSMagic::PROC_SUPER_MAGIC,
+        false,
+        fs.into(),
+    );
+    return NewFile(v.into(), msrc);
+}
+
+pub struct OnlineData {}
+
+impl OnlineData {
+    pub fn GenSnapshot(&self, _task: &Task) -> Vec<u8> {
+        // vcpu#0 is the io thread, the parked vcpus are offline
+        let kernel = GetKernel();
+        let onlineCores = SHARESPACE.scheduler.OnlineVcpuCnt() - 1;
+        let maxCore = onlineCores.min(kernel.applicationCores) - 1;
+
+        let ret = format!("0-{}\n", maxCore);
+        return ret.as_bytes().to_vec();
+    }
+}
+
+impl SimpleFileTrait for OnlineData {
+    fn GetFile(
+        &self,
+        task: &Task,
+        _dir: &Inode,
+        dirent: &Dirent,
+        flags: FileFlags,
+    ) -> Result<File> {
+        let fops = NewSnapshotReadonlyFileOperations(self.GenSnapshot(task));
+        let file = File::New(dirent, &flags, fops.into());
+        return Ok(file);
+    }
+}
+
 pub fn NewCPU(task: &Task, msrc: &Arc<QMutex<MountSource>>) -> Inode {
     let mut m = BTreeMap::new();
 
-    m.insert("online".to_string(), NewPossible(task, msrc));
+    m.insert("online".to_string(), NewOnline(task, msrc));
     m.insert("possible".to_string(), NewPossible(task, msrc));
     m.insert("present".to_string(), NewPossible(task, msrc));
 
@@ -36,6 +36,7 @@ use super::super::super::singleton::*;
 use super::super::fs::dirent::*;
 use super::super::fs::mount::*;
 use super::super::loader::loader::*;
+use super::super::memmgr::oom::MEM_CGROUPS;
 use super::super::task::*;
 use super::super::threadmgr::pid_namespace::*;
 use super::super::threadmgr::task_sched::*;
@@ -319,6 +320,7 @@ impl Kernel {
             let mut tglock = tg.lock();
             tglock.liveThreads.Add(1);
             tglock.root = true;
+            tglock.oomScoreAdj = args.OOMScoreAdj;
         }
 
         if args.Filename.as_str() == "" {
@@ -354,6 +356,7 @@ impl Kernel {
             )
             .expect("can't get cwd dirent");
         task.fsContext.SetWorkDirectory(&cwdDir);
+        task.mm.SetMemCgroup(Some(MEM_CGROUPS.GetOrCreate(&args.ContainerID)));
 
         let config = TaskConfig {
             TaskId: task.taskId,
@@ -629,4 +632,7 @@ pub struct CreateProcessArgs {
     pub Stdiofds: [i32; 3],
     pub Terminal: bool,
     pub ExecId: Option<String>,
+
+    // OOMScoreAdj is the initial oom_score_adj of the process.
+    pub OOMScoreAdj: i32,
 }
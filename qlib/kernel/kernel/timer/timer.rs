@@ -22,6 +22,7 @@ use super::super::super::super::common::*;
 use super::super::super::super::linux::time::*;
 use super::super::super::super::linux_def::*;
 use super::super::super::fs::timerfd::*;
+use super::super::super::socket::hostinet::bandwidth::ThrottleWaiters;
 use super::super::super::task::*;
 use super::super::super::threadmgr::task_sched::*;
 use super::super::super::threadmgr::thread_group::*;
@@ -107,6 +108,7 @@ pub enum TimerListener {
     WaitEntryListener(WaitEntryListener),
     ITimerRealListener(Arc<ITimerRealListener>),
     KernelCPUClockTicker(Arc<KernelCPUClockTicker>),
+    ThrottleWaiters(ThrottleWaiters),
 }
 
 impl fmt::Debug for TimerListener {
@@ -119,6 +121,7 @@ impl fmt::Debug for TimerListener {
             Self::WaitEntryListener(_) => f.debug_struct("WaitEntryListener").finish(),
             Self::ITimerRealListener(_) => f.debug_struct("ITimerRealListener").finish(),
             Self::KernelCPUClockTicker(_) => f.debug_struct("KernelCPUClockTicker").finish(),
+            Self::ThrottleWaiters(_) => f.debug_struct("ThrottleWaiters").finish(),
         }
     }
 }
@@ -140,6 +143,7 @@ impl TimerListener {
             Self::WaitEntryListener(tl) => tl.Notify(exp),
             Self::ITimerRealListener(tl) => tl.Notify(exp),
             Self::KernelCPUClockTicker(tl) => tl.Notify(exp),
+            Self::ThrottleWaiters(tl) => tl.Notify(exp),
         }
     }
 
@@ -152,6 +156,7 @@ impl TimerListener {
             Self::WaitEntryListener(tl) => tl.Destroy(),
             Self::ITimerRealListener(tl) => tl.Destroy(),
             Self::KernelCPUClockTicker(tl) => tl.Destroy(),
+            Self::ThrottleWaiters(tl) => tl.Destroy(),
         }
     }
 }
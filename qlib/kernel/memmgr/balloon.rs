@@ -0,0 +1,151 @@
+// Copyright (c) 2021 Quark Container Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::qlib::mutex::*;
+use alloc::vec::Vec;
+use core::ops::Deref;
+use core::sync::atomic::Ordering;
+
+use super::super::super::common::*;
+use super::super::super::control_msg::*;
+use super::super::super::linux_def::*;
+use super::super::Kernel::HostSpace;
+use crate::GLOBAL_ALLOCATOR;
+
+// the balloon inflates/deflates the guest heap in 2MB chunks
+pub const BALLOON_CHUNK_SIZE: u64 = MemoryDef::PAGE_SIZE_2M;
+
+lazy_static! {
+    pub static ref MEM_BALLOON: MemBalloon = MemBalloon::default();
+}
+
+#[derive(Debug, Default)]
+pub struct MemBalloonIntern {
+    // the guest heap chunks which have been returned to the host
+    pub chunks: Vec<u64>,
+}
+
+impl MemBalloonIntern {
+    pub fn Size(&self) -> u64 {
+        return self.chunks.len() as u64 * BALLOON_CHUNK_SIZE;
+    }
+
+    // take free chunks out of the guest heap and free their host memory, the host mapping
+    // is kept. return the size of memory really reclaimed
+    pub fn Inflate(&mut self, size: u64) -> u64 {
+        let allocator = GLOBAL_ALLOCATOR.Allocator();
+
+        // return the cached free blocks to the heap so that they can be merged to 2MB chunks
+        allocator.FreeAll();
+
+        let mut reclaimed = 0;
+        while reclaimed < size {
+            let addr = match allocator.TakeBlock(BALLOON_CHUNK_SIZE as usize) {
+                None => break,
+                Some(addr) => addr,
+            };
+
+            let ret = HostSpace::RemoveGuestMemRange(addr, BALLOON_CHUNK_SIZE);
+            if ret < 0 {
+                error!(
+                    "MemBalloon::Inflate remove {:x} fail with error {}",
+                    addr, ret
+                );
+                allocator.ReturnBlock(addr, BALLOON_CHUNK_SIZE as usize);
+                break;
+            }
+
+            self.chunks.push(addr);
+            reclaimed += BALLOON_CHUNK_SIZE;
+        }
+
+        return reclaimed;
+    }
+
+    // give the chunks back to the guest heap, the host allocates their memory again when
+    // they are touched. return the size of memory really given back
+    pub fn Deflate(&mut self, size: u64) -> u64 {
+        let allocator = GLOBAL_ALLOCATOR.Allocator();
+
+        let mut released = 0;
+        while released < size {
+            let addr = match self.chunks.pop() {
+                None => break,
+                Some(addr) => addr,
+            };
+
+            allocator.ReturnBlock(addr, BALLOON_CHUNK_SIZE as usize);
+            released += BALLOON_CHUNK_SIZE;
+        }
+
+        return released;
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct MemBalloon(QMutex<MemBalloonIntern>);
+
+impl Deref for MemBalloon {
+    type Target = QMutex<MemBalloonIntern>;
+
+    fn deref(&self) -> &QMutex<MemBalloonIntern> {
+        &self.0
+    }
+}
+
+impl MemBalloon {
+    pub fn Size(&self) -> u64 {
+        return self.lock().Size();
+    }
+
+    pub fn Info(&self) -> MemBalloonInfo {
+        let allocator = GLOBAL_ALLOCATOR.Allocator();
+        return MemBalloonInfo {
+            TotalSize: allocator.total.load(Ordering::Acquire) as u64,
+            BalloonSize: self.Size(),
+            FreeSize: allocator.free.load(Ordering::Acquire) as u64,
+        };
+    }
+
+    // Resize shrinks or grows the guest heap to targetSize.
+    // Shrinking stops when there is no free 2MB chunk in the heap anymore,
+    // growing stops when all the chunks held by the balloon are given back.
+    // targetSize 0 only returns the balloon info.
+    pub fn Resize(&self, targetSize: u64) -> Result<MemBalloonInfo> {
+        if targetSize == 0 {
+            return Ok(self.Info());
+        }
+
+        let mut intern = self.lock();
+        let total = GLOBAL_ALLOCATOR.Allocator().total.load(Ordering::Acquire) as u64;
+        if targetSize < total {
+            let size = total - targetSize;
+            let reclaimed = intern.Inflate(size);
+            info!(
+                "MemBalloon inflate {:x} bytes, expect {:x} bytes",
+                reclaimed, size
+            );
+        } else if targetSize > total {
+            let size = targetSize - total;
+            let released = intern.Deflate(size);
+            info!(
+                "MemBalloon deflate {:x} bytes, expect {:x} bytes",
+                released, size
+            );
+        }
+
+        drop(intern);
+        return Ok(self.Info());
+    }
+}
@@ -34,6 +34,7 @@ use super::super::super::linux_def::*;
 use super::super::super::mem::areaset::*;
 use super::super::super::pagetable::*;
 use super::super::super::range::*;
+use super::super::super::usage::memory::*;
 use super::super::super::vcpu_mgr::CPULocal;
 use super::super::arch::x86_64::context::*;
 use super::super::asm::*;
@@ -149,6 +150,45 @@ pub struct MMPagetable {
     //
     // maxRSS should be modified only via insertRSS, not directly.
     pub maxRSS: u64,
+
+    // memcg is the memory accounting of the container which owns the MemoryManager.
+    // The memory allocated for the MemoryManager is charged to it.
+    pub memcg: Option<MemCgroup>,
+
+    // charged is the memory charged to memcg by the MemoryManager. It differs from
+    // curRSS as the pages shared with the parent after fork are charged only when
+    // the copy on write allocates them.
+    pub charged: u64,
+}
+
+impl MMPagetable {
+    pub fn AddRss(&mut self, len: u64) {
+        self.curRSS += len;
+        if self.curRSS > self.maxRSS {
+            self.maxRSS = self.curRSS;
+        }
+
+        self.Charge(len);
+    }
+
+    pub fn RemoveRss(&mut self, len: u64) {
+        self.curRSS -= len;
+
+        // the unmapped range might include the pages shared by fork which were not charged
+        let len = core::cmp::min(len, self.charged);
+        self.charged -= len;
+        if let Some(memcg) = &self.memcg {
+            memcg.Uncharge(len);
+        }
+    }
+
+    pub fn Charge(&mut self, len: u64) {
+        self.charged += len;
+        if let Some(memcg) = &self.memcg {
+            memcg.Charge(len);
+            super::oom::OnCharged(memcg);
+        }
+    }
 }
 
 #[derive(Default)]
@@ -190,6 +230,8 @@ impl Drop for MemoryManager {
             SHARESPACE.hiberMgr.RemoveMemMgr(self);
             let _ml = self.MappingWriteLock();
             self.CleanVMAs().unwrap();
+            // return the remaining charge to the container
+            self.SetMemCgroup(None);
         }
     }
 }
@@ -270,6 +312,8 @@ impl MemoryManager {
             sharedLoadsOffset: MemoryDef::SHARED_START,
             curRSS: 0,
             maxRSS: 0,
+            memcg: None,
+            charged: 0,
         };
 
         let layout = MmapLayout {
@@ -450,7 +494,7 @@ impl MemoryManager {
                 let mut pt = self.pagetable.write();
 
                 pt.pt.MUnmap(r.Start(), r.Len())?;
-                pt.curRSS -= r.Len();
+                pt.RemoveRss(r.Len());
             }
             //let vgap = mapping.vmas.Remove(&vseg);
             vseg = vgap.NextSeg();
@@ -484,7 +528,7 @@ impl MemoryManager {
                 let mut pt = self.pagetable.write();
 
                 pt.pt.MUnmap(r.Start(), r.Len())?;
-                pt.curRSS -= r.Len();
+                pt.RemoveRss(r.Len());
             }
             let vgap = mapping.vmas.Remove(&vseg);
             vseg = vgap.NextSeg();
@@ -532,15 +576,31 @@ impl MemoryManager {
 
     pub fn AddRssLock(&self, ar: &Range) {
         let mut pt = self.pagetable.write();
-        pt.curRSS += ar.Len();
-        if pt.curRSS > pt.maxRSS {
-            pt.maxRSS = pt.curRSS;
-        }
+        pt.AddRss(ar.Len());
     }
 
     pub fn RemoveRssLock(&self, ar: &Range) {
         let mut pt = self.pagetable.write();
-        pt.curRSS -= ar.Len();
+        pt.RemoveRss(ar.Len());
+    }
+
+    pub fn MemCgroup(&self) -> Option<MemCgroup> {
+        return self.pagetable.read().memcg.clone();
+    }
+
+    // SetMemCgroup moves the memory manager's charge to memcg
+    pub fn SetMemCgroup(&self, memcg: Option<MemCgroup>) {
+        let mut pt = self.pagetable.write();
+        let charged = pt.charged;
+        if let Some(old) = &pt.memcg {
+            old.Uncharge(charged);
+        }
+
+        if let Some(new) = &memcg {
+            new.Charge(charged);
+        }
+
+        pt.memcg = memcg;
     }
 
     pub fn GenStatmSnapshot(&self, _task: &Task) -> Vec<u8> {
@@ -1149,6 +1209,7 @@ impl MemoryManager {
         let page = { super::super::PAGE_MGR.AllocPage(false).unwrap() };
         CopyPage(page, phyAddr);
         self.MapPageWriteLocked(pageAddr, page, exec);
+        self.pagetable.write().Charge(MemoryDef::PAGE_SIZE);
     }
 
     pub fn CopyOnWrite(&self, pageAddr: u64, vma: &VMA) {
@@ -1604,6 +1665,9 @@ impl MemoryManager {
             ptInternal2.sharedLoadsOffset = ptInternal1.sharedLoadsOffset;
             ptInternal2.curRSS = ptInternal1.curRSS;
             ptInternal2.maxRSS = ptInternal1.maxRSS;
+            // the pages shared with the parent are charged when the copy on write allocates them
+            ptInternal2.memcg = ptInternal1.memcg.clone();
+            ptInternal2.charged = 0;
             ptInternal2.pt = ptInternal1.pt.Fork(&*PAGE_MGR)?;
 
             let mut srcvseg = mappingInternal1.vmas.FirstSeg();
@@ -13,11 +13,13 @@
 // limitations under the License.
 
 pub mod arch;
+pub mod balloon;
 mod mapping;
 pub mod mapping_set;
 pub mod memmap;
 pub mod metadata;
 pub mod mm;
+pub mod oom;
 pub mod pma;
 pub mod pmamgr;
 pub mod syscalls;
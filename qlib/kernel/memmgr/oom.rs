@@ -0,0 +1,196 @@
+// Copyright (c) 2021 Quark Container Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::vec::Vec;
+use core::sync::atomic::AtomicBool;
+use core::sync::atomic::Ordering;
+
+use super::super::super::linux_def::*;
+use super::super::super::usage::memory::*;
+use super::super::kernel::kernel::GetKernel;
+use super::super::task::*;
+use super::super::threadmgr::thread::*;
+use super::super::threadmgr::thread_group::*;
+use super::super::SignalDef::*;
+use crate::GLOBAL_ALLOCATOR;
+
+pub const OOM_SCORE_ADJ_MIN: i32 = -1000;
+pub const OOM_SCORE_ADJ_MAX: i32 = 1000;
+
+// part of the guest heap kept for the kernel itself, in 1/OOM_KERNEL_RESERVE_RATIO.
+// The application memory can't grow over the rest.
+pub const OOM_KERNEL_RESERVE_RATIO: u64 = 8;
+
+lazy_static! {
+    pub static ref MEM_CGROUPS: MemCgroups = MemCgroups::New();
+}
+
+// OOM_PENDING is set by the charge path when a memcg goes over its limit. The charge
+// path holds the mm locks, the oom killer runs later in CheckPendingOOM.
+pub static OOM_PENDING: AtomicBool = AtomicBool::new(false);
+
+// the application memory limit of the whole sandbox
+pub fn RootLimit() -> u64 {
+    let total = GLOBAL_ALLOCATOR.Allocator().total.load(Ordering::Acquire) as u64;
+    return total - total / OOM_KERNEL_RESERVE_RATIO;
+}
+
+// OnCharged is called after memory is charged to memcg
+pub fn OnCharged(memcg: &MemCgroup) {
+    if memcg.OverLimit() || MEM_CGROUPS.Root().Usage() > RootLimit() {
+        OOM_PENDING.store(true, Ordering::Release);
+    }
+}
+
+// CheckOOM is called after the resident memory of the task grows. It kills a victim
+// when the container of the task is over its memory limit or the guest memory is
+// about to be exhausted. It must be called without holding any mm or task lock.
+pub fn CheckOOM(task: &Task) {
+    if let Some(memcg) = task.mm.MemCgroup() {
+        if memcg.OverLimit() {
+            OOMKill(Some(&memcg), memcg.Limit());
+            return;
+        }
+    }
+
+    let limit = RootLimit();
+    if MEM_CGROUPS.Root().Usage() > limit {
+        OOMKill(None, limit);
+    }
+}
+
+// CheckPendingOOM kills the victims of the memcgs which went over their limit in the
+// charge path, e.g. mmap populate or the copy on write. It must be called without
+// holding any mm or task lock.
+pub fn CheckPendingOOM() {
+    if !OOM_PENDING.load(Ordering::Acquire) || !OOM_PENDING.swap(false, Ordering::AcqRel) {
+        return;
+    }
+
+    let memcgs: Vec<MemCgroup> = MEM_CGROUPS.lock().values().cloned().collect();
+    for memcg in memcgs {
+        if memcg.OverLimit() {
+            OOMKill(Some(&memcg), memcg.Limit());
+        }
+    }
+
+    let limit = RootLimit();
+    if MEM_CGROUPS.Root().Usage() > limit {
+        OOMKill(None, limit);
+    }
+}
+
+// OOMBadness returns the score of the thread group used to select the oom victim,
+// None means the thread group can't be killed
+pub fn OOMBadness(tg: &ThreadGroup, leader: &Thread, totalMem: u64) -> Option<i64> {
+    let adj = {
+        let tglock = tg.lock();
+        if tglock.liveTasks == 0 || tglock.exiting {
+            return None;
+        }
+
+        tglock.oomScoreAdj
+    };
+
+    if adj == OOM_SCORE_ADJ_MIN {
+        return None;
+    }
+
+    let rss = leader.MemoryManager().ResidentSetSize() as i64;
+    return Some(rss + adj as i64 * (totalMem / 1000) as i64);
+}
+
+// OOMKill kills the thread group with the largest badness score in the container of
+// memcg, or in the whole sandbox when memcg is None.
+pub fn OOMKill(memcg: Option<&MemCgroup>, totalMem: u64) {
+    let kernel = GetKernel();
+    let mut candidates = Vec::new();
+    {
+        let _r = kernel.tasks.ReadLock();
+        let tasks = kernel.tasks.read();
+        let root = tasks.root.as_ref().unwrap().clone();
+        for (tg, tgid) in root.lock().tgids.iter() {
+            let leader = match tg.lock().leader.Upgrade() {
+                None => continue,
+                Some(l) => l,
+            };
+
+            if let Some(memcg) = memcg {
+                if leader.ContainerID() != memcg.cid {
+                    continue;
+                }
+            }
+
+            candidates.push((tg.clone(), leader, *tgid));
+        }
+    }
+
+    let mut victim = None;
+    let mut maxPoints = 0;
+    for (tg, leader, tgid) in candidates {
+        // the last victim is still exiting, wait for it to release the memory
+        {
+            let tglock = tg.lock();
+            if tglock.oomKilled && tglock.liveTasks > 0 {
+                return;
+            }
+        }
+
+        let points = match OOMBadness(&tg, &leader, totalMem) {
+            None => continue,
+            Some(p) => p,
+        };
+
+        if victim.is_none() || points > maxPoints {
+            maxPoints = points;
+            victim = Some((tg, leader, tgid));
+        }
+    }
+
+    let (tg, leader, tgid) = match victim {
+        None => {
+            error!("OOMKill: no killable process, memory limit {:x}", totalMem);
+            return;
+        }
+        Some(v) => v,
+    };
+
+    let cid = leader.ContainerID();
+    error!(
+        "OOMKill: memory limit {:x} exceeded, kill process {} of container {}, score {}",
+        totalMem, tgid, &cid, maxPoints
+    );
+
+    tg.lock().oomKilled = true;
+    MEM_CGROUPS.GetOrCreate(&cid).IncOOMKills();
+    tg.SendSignal(&SignalInfo::SignalInfoPriv(Signal(Signal::SIGKILL)))
+        .unwrap_or_else(|e| error!("OOMKill: kill process {} fail {:?}", tgid, e));
+}
+
+// OOMKilled returns whether the process of the thread group or any process of the
+// container is killed by the oom killer
+pub fn OOMKilled(tg: &ThreadGroup, cid: &str, containerInit: bool) -> bool {
+    if tg.lock().oomKilled {
+        return true;
+    }
+
+    if !containerInit {
+        return false;
+    }
+
+    return match MEM_CGROUPS.Get(cid) {
+        None => false,
+        Some(memcg) => memcg.OOMKills() > 0,
+    };
+}
@@ -0,0 +1,355 @@
+// Copyright (c) 2021 Quark Container Authors / 2018 The gVisor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::ops::Deref;
+use core::sync::atomic::AtomicU64;
+use core::sync::atomic::Ordering;
+use spin::Mutex;
+
+use super::super::super::super::common::*;
+use super::super::super::super::linux::time::SECOND;
+use super::super::super::super::linux_def::*;
+use super::super::super::kernel::timer::timer::Setting;
+use super::super::super::kernel::timer::timer::Timer;
+use super::super::super::kernel::timer::timer::TimerListener;
+use super::super::super::kernel::timer::timer::TimerListenerTrait;
+use super::super::super::kernel::timer::MonotonicNow;
+use super::super::super::kernel::timer::MONOTONIC_CLOCK;
+use super::super::super::kernel::waiter::queue::Queue;
+
+// the bucket holds 100ms of the rate, and at least one socket buffer transfer
+pub const BANDWIDTH_MIN_BURST: u64 = 64 * 1024;
+// the throttled sockets are notified when the bucket has the tokens of this transfer size,
+// so that they don't wake up for a few bytes
+pub const BANDWIDTH_MIN_TRANSFER: i64 = 4 * 1024;
+
+lazy_static! {
+    // the throttled sockets waiting for the tokens of the pod ingress and egress buckets
+    pub static ref INGRESS_WAITERS: ThrottleWaiters = ThrottleWaiters::New(READABLE_EVENT);
+    pub static ref EGRESS_WAITERS: ThrottleWaiters = ThrottleWaiters::New(WRITEABLE_EVENT);
+}
+
+#[derive(Debug, Default)]
+pub struct TokenBucketInner {
+    // the bytes can be transferred, negative when the transfers are more than the tokens
+    pub tokens: i64,
+    pub last: i64,
+}
+
+impl TokenBucketInner {
+    pub fn Refill(&mut self, rate: u64, now: i64) {
+        let burst = TokenBucket::Burst(rate);
+        let elapsed = now - self.last;
+        if elapsed <= 0 {
+            return;
+        }
+
+        let add = (elapsed as i128 * rate as i128 / SECOND as i128) as i64;
+        self.tokens = (self.tokens + add).min(burst);
+        if self.tokens == burst {
+            self.last = now;
+        } else {
+            // keep the remainder of the elapsed time for the next refill
+            self.last += (add as i128 * SECOND as i128 / rate as i128) as i64;
+        }
+    }
+
+    // the bytes of a transfer of want bytes allowed now, or the ns to wait for the tokens
+    pub fn Take(&self, rate: u64, want: usize) -> core::result::Result<usize, i64> {
+        if self.tokens > 0 {
+            return Ok(want.min(self.tokens as usize));
+        }
+
+        let need = BANDWIDTH_MIN_TRANSFER.min(TokenBucket::Burst(rate)) - self.tokens;
+        return Err((need as i128 * SECOND as i128 / rate as i128) as i64 + 1);
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct TokenBucket {
+    // bytes per second, 0 for no limit
+    pub rate: AtomicU64,
+    pub inner: Mutex<TokenBucketInner>,
+
+    pub bytes: AtomicU64,
+    // the count of the throttled transfers and the total ns the bucket is empty
+    pub throttledCount: AtomicU64,
+    pub throttledTime: AtomicU64,
+}
+
+impl TokenBucket {
+    pub fn Burst(rate: u64) -> i64 {
+        return (rate / 10).max(BANDWIDTH_MIN_BURST) as i64;
+    }
+
+    // it is called by the host before the guest starts, the bucket is filled at the first refill
+    pub fn SetRate(&self, rate: u64) {
+        let mut inner = self.inner.lock();
+        inner.tokens = Self::Burst(rate);
+        inner.last = 0;
+        self.rate.store(rate, Ordering::SeqCst);
+    }
+
+    pub fn Rate(&self) -> u64 {
+        return self.rate.load(Ordering::Relaxed);
+    }
+
+    // the bytes of a transfer of want bytes allowed now. When the bucket is empty, the
+    // transfer gets EWOULDBLOCK and the queue is notified once the bucket is refilled, so
+    // the blocking callers wait on the queue and the non-blocking callers return EAGAIN
+    pub fn Take(&self, queue: &Queue, waiters: &ThrottleWaiters, want: usize) -> Result<usize> {
+        let rate = self.Rate();
+        if rate == 0 {
+            return Ok(want);
+        }
+
+        let ret = {
+            let mut inner = self.inner.lock();
+            inner.Refill(rate, MonotonicNow());
+            inner.Take(rate, want)
+        };
+
+        match ret {
+            Ok(n) => return Ok(n),
+            Err(wait) => {
+                self.throttledCount.fetch_add(1, Ordering::Relaxed);
+                if waiters.Arm(queue, wait) {
+                    self.throttledTime.fetch_add(wait as u64, Ordering::Relaxed);
+                }
+                return Err(Error::SysError(SysErr::EWOULDBLOCK));
+            }
+        }
+    }
+
+    // whether the bucket is empty, the queue is notified once it is refilled
+    pub fn Throttled(&self, queue: &Queue, waiters: &ThrottleWaiters) -> bool {
+        let rate = self.Rate();
+        if rate == 0 {
+            return false;
+        }
+
+        let ret = {
+            let mut inner = self.inner.lock();
+            inner.Refill(rate, MonotonicNow());
+            inner.Take(rate, 1)
+        };
+
+        match ret {
+            Ok(_) => return false,
+            Err(wait) => {
+                waiters.Arm(queue, wait);
+                return true;
+            }
+        }
+    }
+
+    // take the tokens of the transferred bytes
+    pub fn Consume(&self, count: i64) {
+        if count <= 0 {
+            return;
+        }
+
+        self.bytes.fetch_add(count as u64, Ordering::Relaxed);
+        if self.Rate() == 0 {
+            return;
+        }
+
+        self.inner.lock().tokens -= count;
+    }
+}
+
+#[derive(Default)]
+pub struct ThrottleWaitersInner {
+    pub queues: Vec<Queue>,
+    pub timer: Option<Timer>,
+    pub armed: bool,
+}
+
+// the queues of the throttled sockets, notified with the event by a timer when the bucket
+// is refilled
+#[derive(Clone)]
+pub struct ThrottleWaiters {
+    pub event: EventMask,
+    pub inner: Arc<Mutex<ThrottleWaitersInner>>,
+}
+
+impl ThrottleWaiters {
+    pub fn New(event: EventMask) -> Self {
+        return Self {
+            event: event,
+            inner: Arc::new(Mutex::new(ThrottleWaitersInner::default())),
+        };
+    }
+
+    // add the queue and start the timer if it is not running, return whether it is started
+    pub fn Arm(&self, queue: &Queue, wait: i64) -> bool {
+        let timer = {
+            let mut inner = self.inner.lock();
+            if !inner
+                .queues
+                .iter()
+                .any(|q| Arc::ptr_eq(q.deref(), queue.deref()))
+            {
+                inner.queues.push(queue.clone());
+            }
+
+            if inner.armed {
+                return false;
+            }
+
+            inner.armed = true;
+            let waiters = self.clone();
+            inner
+                .timer
+                .get_or_insert_with(|| {
+                    Timer::New(&MONOTONIC_CLOCK, TimerListener::ThrottleWaiters(waiters))
+                })
+                .clone()
+        };
+
+        timer.Swap(&Setting {
+            Enabled: true,
+            Next: MONOTONIC_CLOCK.Now().Add(wait),
+            Period: 0,
+        });
+        return true;
+    }
+}
+
+impl TimerListenerTrait for ThrottleWaiters {
+    fn Notify(&self, _exp: u64) {
+        let queues = {
+            let mut inner = self.inner.lock();
+            inner.armed = false;
+            core::mem::take(&mut inner.queues)
+        };
+
+        for queue in queues {
+            queue.Notify(self.event);
+        }
+    }
+
+    fn Destroy(&self) {}
+}
+
+// the pod level ingress/egress limit of the host and tsot sockets, from the kubernetes
+// "kubernetes.io/ingress-bandwidth" and "kubernetes.io/egress-bandwidth" pod annotations
+#[derive(Debug, Default)]
+pub struct PodBandwidth {
+    pub ingress: TokenBucket,
+    pub egress: TokenBucket,
+}
+
+impl PodBandwidth {
+    // the rates are in bytes per second, 0 for no limit
+    pub fn SetLimit(&self, ingress: u64, egress: u64) {
+        self.ingress.SetRate(ingress);
+        self.egress.SetRate(egress);
+    }
+
+    pub fn TakeIngress(&self, queue: &Queue, want: usize) -> Result<usize> {
+        return self.ingress.Take(queue, &INGRESS_WAITERS, want);
+    }
+
+    pub fn TakeEgress(&self, queue: &Queue, want: usize) -> Result<usize> {
+        return self.egress.Take(queue, &EGRESS_WAITERS, want);
+    }
+
+    // the socket isn't readable or writable while the bucket of the direction is empty
+    pub fn Readiness(&self, queue: &Queue, mask: EventMask) -> EventMask {
+        let mut mask = mask;
+        if mask & READABLE_EVENT != 0 && self.ingress.Throttled(queue, &INGRESS_WAITERS) {
+            mask &= !READABLE_EVENT;
+        }
+
+        if mask & WRITEABLE_EVENT != 0 && self.egress.Throttled(queue, &EGRESS_WAITERS) {
+            mask &= !WRITEABLE_EVENT;
+        }
+
+        return mask;
+    }
+
+    pub fn Report(&self) -> String {
+        let mut buf = format!(
+            "{:<8} {:>12} {:>16} {:>10} {:>14}\n",
+            "dir", "rate", "bytes", "throttled", "throttled_ns"
+        );
+        for (dir, bucket) in [("ingress", &self.ingress), ("egress", &self.egress)].iter() {
+            buf += &format!(
+                "{:<8} {:>12} {:>16} {:>10} {:>14}\n",
+                dir,
+                bucket.Rate(),
+                bucket.bytes.load(Ordering::Relaxed),
+                bucket.throttledCount.load(Ordering::Relaxed),
+                bucket.throttledTime.load(Ordering::Relaxed)
+            );
+        }
+
+        return buf;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u64 = 1_000_000;
+
+    #[test]
+    fn TestRefill() {
+        let mut inner = TokenBucketInner::default();
+        inner.Refill(RATE, 10_000_000);
+        assert_eq!(inner.tokens, 10_000);
+        assert_eq!(inner.last, 10_000_000);
+
+        // the tokens are capped at the burst
+        inner.Refill(RATE, SECOND);
+        assert_eq!(inner.tokens, TokenBucket::Burst(RATE));
+        assert_eq!(inner.last, SECOND);
+
+        // the time goes back
+        inner.Refill(RATE, SECOND / 2);
+        assert_eq!(inner.tokens, TokenBucket::Burst(RATE));
+    }
+
+    #[test]
+    fn TestRefillRemainder() {
+        let mut inner = TokenBucketInner::default();
+        inner.Refill(3, SECOND / 2);
+        assert_eq!(inner.tokens, 1);
+        assert_eq!(inner.last, SECOND / 3);
+
+        // the ns which are less than a token are kept for the next refill
+        inner.Refill(3, SECOND);
+        assert_eq!(inner.tokens, 3);
+        assert_eq!(inner.last, SECOND - 1);
+    }
+
+    #[test]
+    fn TestTake() {
+        let mut inner = TokenBucketInner::default();
+        inner.tokens = 100;
+        assert_eq!(inner.Take(RATE, 1000), Ok(100));
+        assert_eq!(inner.Take(RATE, 10), Ok(10));
+
+        inner.tokens = 0;
+        assert_eq!(inner.Take(RATE, 10), Err(4_096_001));
+
+        inner.tokens = -50;
+        assert_eq!(inner.Take(RATE, 10), Err(4_146_001));
+    }
+}
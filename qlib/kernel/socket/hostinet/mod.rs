@@ -13,6 +13,7 @@
 // limitations under the License.
 
 pub mod asyncsocket;
+pub mod bandwidth;
 pub mod hostsocket;
 pub mod loopbacksocket;
 pub mod rdma_socket;
@@ -51,6 +51,7 @@ use super::super::super::kernel::waiter::*;
 use super::super::super::quring::QUring;
 use super::tsotsocket::NewTsotSocketFile;
 use super::tsotsocket::TsotSocketType;
+use super::tsotsocket::TsotUdpRelay;
 use crate::qlib::rdmasocket::RDMAServerSock;
 // use super::super::super::rdmasocket::*;
 use super::super::super::task::*;
@@ -2414,8 +2415,12 @@ impl Provider for SocketProvider {
         let nonblocking = stype & SocketFlags::SOCK_NONBLOCK != 0;
         let stype = stype & SocketType::SOCK_TYPE_MASK;
 
+        let mut udpRelay = None;
         let fd = if SHARESPACE.config.read().EnableTsot && stype == SockType::SOCK_STREAM {
-            if self.family == AFType::AF_INET && stype == SockType::SOCK_STREAM {
+            // the AF_INET6 sockets are served by the ipv4 tsot with the translated addresses
+            if (self.family == AFType::AF_INET || self.family == AFType::AF_INET6)
+                && stype == SockType::SOCK_STREAM
+            {
                 let general = task.blocker.generalEntry.clone();
                 SHARESPACE.tsotSocketMgr.CreateSocket()?;
                 
@@ -2441,9 +2446,48 @@ impl Provider for SocketProvider {
 
                 fd
             } else {
-                // tsot only support IPv4 tcp
+                // tsot only support IPv4/IPv6 tcp
                 return Err(Error::SysError(SysErr::ESOCKTNOSUPPORT));
             }
+        } else if SHARESPACE.config.read().EnableTsot
+            && (self.family == AFType::AF_INET || self.family == AFType::AF_INET6)
+            && stype == SockType::SOCK_DGRAM
+        {
+            // the pod network datagrams are relayed by the node agent,
+            // the host socket serves the loopback datagrams such as the dns service
+            let general = task.blocker.generalEntry.clone();
+            SHARESPACE.tsotSocketMgr.EventRegister(task, &general, EVENT_IN);
+            defer!(SHARESPACE.tsotSocketMgr.EventUnregister(task, &general));
+            SHARESPACE.tsotSocketMgr.CreateUdpSocket()?;
+
+            loop {
+                match SHARESPACE.tsotSocketMgr.GetUdpSocket() {
+                    None => {
+                        match task.blocker.BlockWithMonoTimer(true, None) {
+                            Err(e) => {
+                                return Err(e);
+                            }
+                            _ => (),
+                        }
+                    },
+                    Some((socket, socketId)) => {
+                        udpRelay = Some(TsotUdpRelay {
+                            fd: socket,
+                            socketId: socketId,
+                        });
+                        break;
+                    }
+                }
+            }
+
+            // the loopback addresses of the AF_INET6 socket are translated to ipv4 too
+            let res = Kernel::HostSpace::Socket(AFType::AF_INET, stype | SocketFlags::SOCK_CLOEXEC, protocol);
+            if res < 0 {
+                Kernel::HostSpace::Close(udpRelay.unwrap().fd);
+                return Err(Error::SysError(-res as i32));
+            }
+
+            res as i32
         } else {
             let res = Kernel::HostSpace::Socket(self.family, stype | SocketFlags::SOCK_CLOEXEC, protocol);
             if res < 0 {
@@ -2482,6 +2526,17 @@ impl Provider for SocketProvider {
                 socketType,
                 None,
             )?;
+        } else if let Some(relay) = udpRelay {
+            file = NewTsotSocketFile(
+                task,
+                self.family,
+                fd,
+                stype,
+                nonblocking,
+                Queue::default(),
+                TsotSocketType::Udp(relay),
+                None,
+            )?;
         } else if tcpRDMA || udpRDMA {
             let socketType = SocketBufType::TCPInit;
 
@@ -16,6 +16,7 @@ use core::sync::atomic::AtomicU32;
 use core::sync::atomic::{AtomicU64, Ordering};
 
 use alloc::collections::BTreeMap;
+use alloc::collections::BTreeSet;
 use alloc::sync::Arc;
 use spin::Mutex;
 use alloc::collections::VecDeque;
@@ -147,7 +148,7 @@ impl TsotBindings {
         return Ok(true)
     }
 
-    pub fn NewConnection(&self, fd: i32, addr: QIPv4Addr, port: u16, sockBuf: AcceptSocket, queue: Queue) -> Result<()> {
+    pub fn NewConnection(&self, fd: i32, addr: TsotAddr, port: u16, sockBuf: AcceptSocket, queue: Queue) -> Result<()> {
         match self.listeningSockets.lock().get(&port) {
             None => return Err(Error::SysError(SysErr::EADDRINUSE)),
             Some(sock) => {
@@ -219,24 +220,39 @@ impl QIPv4Addr {
 }
 
 #[derive(Debug, Clone, Copy)]
-pub struct QIPv4Endpoint {
-    pub ip: QIPv4Addr,
+pub struct QIPEndpoint {
+    pub ip: TsotAddr,
     pub port: u16,
 }
 
-impl QIPv4Endpoint {
-    pub fn New(ip: QIPv4Addr, port: u16) -> Self {
+impl QIPEndpoint {
+    pub fn New(ip: TsotAddr, port: u16) -> Self {
         return Self {
             ip: ip,
             port: port,
         }
     }
 
+    pub fn FromIpv4(ip: QIPv4Addr, port: u16) -> Self {
+        return Self::New(TsotAddr::FromIpv4(ip.0), port)
+    }
+
+    // none when the endpoint is ipv6
+    pub fn Ipv4(&self) -> Option<QIPv4Addr> {
+        return self.ip.Ipv4().map(|ip| QIPv4Addr(ip))
+    }
+
+    pub fn IsLoopback(&self) -> bool {
+        return self.ip.IsLoopback();
+    }
+
+    // the AF_INET address, the ipv6 endpoint can't be presented and is reported as 0.0.0.0
     pub fn ToSockAddr(&self) -> SockAddr {
+        let ip = self.Ipv4().unwrap_or(QIPv4Addr(0));
         let addr = SockAddrInet {
             Family: AFType::AF_INET as u16,
             Port: self.port,
-            Addr: self.ip.ToBytes(),
+            Addr: ip.ToBytes(),
             Zero: [0; 8],
         };
 
@@ -246,6 +262,10 @@ impl QIPv4Endpoint {
 
 pub const SOCKET_POOL_SIZE: usize = 5;
 
+// the ephemeral port range used for the implicit bind of udp sockets
+pub const UDP_EPHEMERAL_PORT_START: u16 = 32768;
+pub const UDP_EPHEMERAL_PORT_END: u16 = 60999;
+
 impl DnsReq {
     pub fn New(reqId: u16, domains: &[String]) -> Result<Self> {
         let mut names = [0; 256];
@@ -259,7 +279,7 @@ impl DnsReq {
             for i in 0..bytes.len() {
                 names[offset + i] = bytes[i];
             }
-            names[offset + bytes.len() + 1] = ':' as u8; // leave another ':' as splitter
+            names[offset + bytes.len()] = ':' as u8; // the ':' splitter
             offset += bytes.len() + 1; 
         }
 
@@ -285,13 +305,22 @@ pub struct TsotSocketMgr {
     pub queue: Queue,
 
     pub localIpAddr: AtomicU32,
+    // the pod ipv6 address, unspecified when the pod network is ipv4 only
+    pub localIpv6Addr: Mutex<TsotAddr>,
 
     pub bindAddrs: Mutex<BTreeMap<QIPv4Addr, TsotBindings>>,
+
+    // (hostfd, socketId) of the udp sockets created by the node agent
+    pub udpSocketPool: Mutex<VecDeque<(i32, u32)>>,
+
+    // the udp ports bound by the pod, udp and tcp have separated port spaces
+    pub udpPorts: Mutex<BTreeSet<u16>>,
+    pub nextUdpPort: Mutex<u16>,
 }
 
 impl Waitable for TsotSocketMgr {
     fn Readiness(&self, _task: &Task, mask: EventMask) -> EventMask {
-        if self.socketPool.lock().len() > 0 {
+        if self.socketPool.lock().len() > 0 || self.udpSocketPool.lock().len() > 0 {
             return EVENT_IN & mask;
         }
 
@@ -315,7 +344,11 @@ impl Default for TsotSocketMgr {
             socketPool: Mutex::new(VecDeque::with_capacity(SOCKET_POOL_SIZE)),
             queue: Queue::default(),
             localIpAddr: AtomicU32::new(0),
+            localIpv6Addr: Mutex::new(TsotAddr::default()),
             bindAddrs: Mutex::new(BTreeMap::new()),
+            udpSocketPool: Mutex::new(VecDeque::new()),
+            udpPorts: Mutex::new(BTreeSet::new()),
+            nextUdpPort: Mutex::new(UDP_EPHEMERAL_PORT_START),
         }
     }
 }
@@ -329,6 +362,14 @@ impl TsotSocketMgr {
         return self.localIpAddr.load(Ordering::Relaxed).into();
     }
     
+    pub fn SetLocalIpv6Addr(&self, addr: [u8; 16]) {
+        *self.localIpv6Addr.lock() = TsotAddr::FromIpv6(addr);
+    }
+
+    pub fn LocalIpv6Addr(&self) -> TsotAddr {
+        return *self.localIpv6Addr.lock();
+    }
+
     pub fn NextReqId(&self) -> u32 {
         return self.currReqId.fetch_add(1, Ordering::SeqCst) as u32;
     }
@@ -374,6 +415,86 @@ impl TsotSocketMgr {
         }
     }
 
+    pub fn CreateUdpSocket(&self) -> Result<()> {
+        let msg = TsotMsg::CreateUdpSocketReq(CreateUdpSocketReq {}).into();
+
+        Self::SendMsg(&msg)?;
+        return Ok(())
+    }
+
+    pub fn GetUdpSocket(&self) -> Option<(i32, u32)> {
+        return self.udpSocketPool.lock().pop_front();
+    }
+
+    pub fn NewUdpSocket(&self, socket: i32, socketId: u32) {
+        let mut udpSocketPool = self.udpSocketPool.lock();
+        udpSocketPool.push_back((socket, socketId));
+        if udpSocketPool.len() == 1 {
+            self.queue.Notify(EVENT_IN);
+        }
+    }
+
+    // bind the udp socket to the port, port 0 means allocating an ephemeral port
+    // return: the bound port
+    pub fn UdpBind(&self, socketId: u32, port: u16) -> Result<u16> {
+        let port = {
+            let mut udpPorts = self.udpPorts.lock();
+            let port = if port == 0 {
+                let mut nextPort = self.nextUdpPort.lock();
+                let count = UDP_EPHEMERAL_PORT_END - UDP_EPHEMERAL_PORT_START + 1;
+                let mut found = None;
+                for _i in 0..count {
+                    let candidate = *nextPort;
+                    *nextPort = if candidate == UDP_EPHEMERAL_PORT_END {
+                        UDP_EPHEMERAL_PORT_START
+                    } else {
+                        candidate + 1
+                    };
+
+                    if !udpPorts.contains(&candidate) {
+                        found = Some(candidate);
+                        break;
+                    }
+                }
+
+                match found {
+                    None => return Err(Error::SysError(SysErr::EADDRINUSE)),
+                    Some(p) => p,
+                }
+            } else {
+                if udpPorts.contains(&port) {
+                    return Err(Error::SysError(SysErr::EADDRINUSE));
+                }
+                port
+            };
+
+            udpPorts.insert(port);
+            port
+        };
+
+        let msg = TsotMsg::UdpBindReq(UdpBindReq {
+            socketId: socketId,
+            port: port,
+        }).into();
+
+        match Self::SendMsg(&msg) {
+            Ok(()) => (),
+            Err(e) => {
+                self.udpPorts.lock().remove(&port);
+                return Err(e);
+            }
+        }
+
+        return Ok(port)
+    }
+
+    // the node agent releases its binding when the pod closes the socket
+    pub fn UdpRemoveBind(&self, port: u16) {
+        if !self.udpPorts.lock().remove(&port) {
+            error!("TsotSocketMgr::UdpRemoveBind the port {} doesn't exist", port);
+        }
+    }
+
     pub fn Bind(&self, ip: QIPv4Addr, port: u16, reusePort: bool) -> Result<()> {
         self.ValidAddr(ip)?;
         let mut bindingAddrs = self.bindAddrs.lock();
@@ -484,11 +605,11 @@ impl TsotSocketMgr {
         return Ok(())
     }
 
-    pub fn Connect(&self, dstIp: QIPv4Addr, dstPort: u16, srcPort: u16, socket: i32, ops: &TsotSocketOperations) -> Result<()> {
+    pub fn Connect(&self, dstIp: TsotAddr, dstPort: u16, srcPort: u16, socket: i32, ops: &TsotSocketOperations) -> Result<()> {
         let reqId = self.NextReqId();
         let connectReq = PodConnectReq {
             reqId: reqId,
-            dstIp: dstIp.0,
+            dstIp: dstIp,
             dstPort: dstPort,
             srcPort: srcPort,
         };
@@ -513,7 +634,7 @@ impl TsotSocketMgr {
         return Ok(())
     }
 
-    pub fn NewPeerConnection(&self, fd: i32, peerAddr: QIPv4Addr, port: u16, sockBuf: SocketBuff) -> Result<()> {
+    pub fn NewPeerConnection(&self, fd: i32, peerAddr: TsotAddr, port: u16, sockBuf: SocketBuff) -> Result<()> {
         let sockBuf = AcceptSocket::SocketBuff(sockBuf);
         let listens = self.bindAddrs.lock();
         match listens.get(&self.LocalIpAddr()) { 
@@ -543,7 +664,7 @@ impl TsotSocketMgr {
                 return Err(Error::SysError(SysErr::ECONNREFUSED))
             }
             Some(addr) => {
-                addr.NewConnection(fd, peerAddr, port, sockBuf, serverQueue)?;
+                addr.NewConnection(fd, TsotAddr::FromIpv4(peerAddr.0), port, sockBuf, serverQueue)?;
                 return Ok(())
             }
         }
@@ -567,7 +688,7 @@ impl TsotSocketMgr {
 
                     self.NewPeerConnection(
                         fd, 
-                        m.peerIp.into(), 
+                        m.peerIp, 
                         m.localPort, 
                         sockBuf
                     )?;
@@ -588,7 +709,12 @@ impl TsotSocketMgr {
                     connectingSocket.queue.Notify(EVENT_OUT)
                 }
                 TsotMsg::DnsResp(m) => {
-                    SHARESPACE.dnsSvc.ProcessDnsResp(m).unwrap();
+                    if let Err(e) = SHARESPACE.dnsSvc.ProcessDnsResp(m) {
+                        error!("TsotSocketMgr::DnsResp fail with error {:?}", e);
+                    }
+                }
+                TsotMsg::CreateUdpSocketResp(m) => {
+                    self.NewUdpSocket(fd, m.socketId);
                 }
                 _ => ()
             };
@@ -609,12 +735,12 @@ impl TsotListenSocket {
     pub fn NewConnection(
         &self, 
         fd: i32, 
-        addr: QIPv4Addr, 
+        addr: TsotAddr, 
         port: u16, 
         sockBuf: AcceptSocket, 
         queue: Queue
     ) -> Result<()> {
-        let trigger = self.acceptQueue.EnqSocket(fd, addr.0, port, sockBuf, queue);
+        let trigger = self.acceptQueue.EnqSocket(fd, addr, port, sockBuf, queue);
         if trigger {
             for (_, (queue, _)) in &self.queues {
                 queue.Notify(EVENT_READ);
@@ -640,7 +766,7 @@ impl TsotListenSocket {
 #[derive(Default, Debug)]
 pub struct TsotAcceptItem {
     pub fd: i32,
-    pub addr: u32,
+    pub addr: TsotAddr,
     pub port: u16,
     pub sockBuf: AcceptSocket,
     pub queue: Queue
@@ -671,7 +797,7 @@ impl TsotAcceptQueue {
     pub fn EnqSocket(
         &self,
         fd: i32,
-        addr: u32,
+        addr: TsotAddr,
         port: u16,
         sockBuf: AcceptSocket,
         queue: Queue
@@ -41,6 +41,7 @@ use super::super::super::fs::dirent::*;
 use super::super::super::fs::file::*;
 use super::super::super::fs::flags::*;
 use super::super::super::fs::host::hostinodeop::*;
+use super::super::super::guestfdnotifier::*;
 use super::super::super::kernel::fd_table::*;
 use super::super::super::kernel::kernel::GetKernel;
 use super::super::super::kernel::time::*;
@@ -53,10 +54,11 @@ use super::super::super::Kernel::HostSpace;
 use super::super::super::SHARESPACE;
 use super::super::control::*;
 use super::super::socket::*;
-use super::tsot_mgr::QIPv4Endpoint;
+use super::tsot_mgr::QIPEndpoint;
 use super::tsot_mgr::TsotAcceptItem;
 use super::tsot_mgr::TsotAcceptQueue;
 use crate::qlib::bytestream::*;
+use crate::qlib::tsot_msg::{TsotAddr, UdpDatagramHdr, UDP_DATAGRAM_HDR_SIZE, UDP_MAX_PAYLOAD_SIZE};
 use crate::qlib::kernel::kernel::waiter::Queue;
 use crate::qlib::kernel::socket::hostinet::loopbacksocket::*;
 use crate::qlib::kernel::socket::hostinet::socket::HostIoctlIFConf;
@@ -70,10 +72,10 @@ pub fn NewTsotSocketFile(
     nonblock: bool,
     queue: Queue,
     socketType: TsotSocketType,
-    remoteAddr: Option<QIPv4Endpoint>,
+    remoteAddr: Option<QIPEndpoint>,
 ) -> Result<File> {
-    if family != AFType::AF_INET {
-        error!("Tsot only support IPV4");
+    if family != AFType::AF_INET && family != AFType::AF_INET6 {
+        error!("Tsot only support IPV4 and IPV6");
         return Err(Error::SysError(SysErr::EINVAL));
     }
 
@@ -114,6 +116,14 @@ pub enum TsotSocketType {
     Server(Vec<TsotAcceptQueue>),       // TCP Server socket, when socket start to listen
     Uring(SocketBuff),
     Loopback(LoopbackSocket),
+    Udp(TsotUdpRelay),         // UDP socket, the loopback datagrams go through the host socket
+}
+
+// the node agent side of a udp socket, the pod network datagrams are relayed through it
+#[derive(Debug, Clone, Copy)]
+pub struct TsotUdpRelay {
+    pub fd: i32,
+    pub socketId: u32,
 }
 
 impl fmt::Debug for TsotSocketType {
@@ -124,6 +134,7 @@ impl fmt::Debug for TsotSocketType {
             Self::Server(_) => write!(f, "TsotSocketType::TCPUringlServer"),
             Self::Uring(_) => write!(f, "TsotSocketType::Uring"),
             Self::Loopback(_) => write!(f, "TsotSocketType::Loopback"),
+            Self::Udp(_) => write!(f, "TsotSocketType::Udp"),
         }
     }
 }
@@ -179,13 +190,15 @@ pub struct TsotSocketOperationsIntern {
     pub fd: i32,
     pub connErrNo: AtomicI32,
     pub queue: Queue,
-    pub remoteAddr: QMutex<Option<QIPv4Endpoint>>,
+    pub remoteAddr: QMutex<Option<QIPEndpoint>>,
     pub socketType: QMutex<TsotSocketType>,
     pub bindIp: AtomicU32,
     pub bindPort: AtomicU16,
     pub listening: AtomicBool, // is listening?
     pub hostops: HostInodeOp,
     pub reusePort: AtomicBool,
+    // IPV6_V6ONLY of the AF_INET6 socket, the host socket is AF_INET
+    pub v6Only: AtomicBool,
     passInq: AtomicBool,
 }
 
@@ -228,14 +241,14 @@ impl TsotSocketOperations {
         return self.stype == SockType::SOCK_DGRAM;
     }
 
-    pub fn RemoteAddr(&self) -> Result<QIPv4Endpoint> {
+    pub fn RemoteAddr(&self) -> Result<QIPEndpoint> {
         match *self.remoteAddr.lock() {
             None => return Err(Error::SysError(SysErr::EINVAL)),
             Some(a) => return Ok(a.clone())
         }
     }
 
-    pub fn SetRemoteAddr(&self, addr: QIPv4Endpoint) {
+    pub fn SetRemoteAddr(&self, addr: QIPEndpoint) {
         *self.remoteAddr.lock() = Some(addr);
     }
 
@@ -244,6 +257,16 @@ impl TsotSocketOperations {
         let bindPort = self.bindPort.load(Ordering::Relaxed);
         let localAddr = SHARESPACE.tsotSocketMgr.LocalIpAddr();
 
+        if let TsotSocketType::Udp(relay) = self.SocketType() {
+            if bindPort != 0 && !bindIp.IsLoopback() {
+                SHARESPACE.tsotSocketMgr.UdpRemoveBind(bindPort);
+            }
+
+            // the node agent releases the socket when it sees the close
+            HostSpace::Close(relay.fd);
+            return Ok(())
+        }
+
         if bindPort != 0 {
             if bindIp.IsLoopback() {
                 SHARESPACE.tsotSocketMgr.RemoveBind(bindIp, bindPort)?;
@@ -284,12 +307,17 @@ impl TsotSocketOperations {
         queue: Queue,
         hostops: HostInodeOp,
         socketBuf: TsotSocketType,
-        remoteAddr: Option<QIPv4Endpoint>,
+        remoteAddr: Option<QIPEndpoint>,
     ) -> Result<Self> {
         match &socketBuf {
             TsotSocketType::Uring(ref buf) => {
                 QUring::BufSockInit(fd, queue.clone(), buf.clone(), true).unwrap();
             }
+            TsotSocketType::Udp(ref relay) => {
+                // both the host socket and the relay socket notify the socket queue
+                SetWaitInfo(fd, queue.clone());
+                SetWaitInfo(relay.fd, queue.clone());
+            }
             _ => (),
         }
 
@@ -309,6 +337,7 @@ impl TsotSocketOperations {
             hostops: hostops,
             passInq: AtomicBool::new(false),
             reusePort: AtomicBool::new(false),
+            v6Only: AtomicBool::new(false),
         };
 
         let ret = Self(Arc::new(ret));
@@ -323,7 +352,9 @@ impl TsotSocketOperations {
                     return Err(Error::SysError(SysErr::EPIPE));
                 }
 
-                return QUring::TsotSocketProduce(
+                // the produced or consumed bytes are already in the socket buffer, they are only
+                // accounted so that the following reads and writes are throttled
+                QUring::TsotSocketProduce(
                     task,
                     self.fd,
                     self.queue.clone(),
@@ -331,7 +362,9 @@ impl TsotSocketOperations {
                     count as usize,
                     self,
                     iovs,
-                );
+                )?;
+                SHARESPACE.podBandwidth.egress.Consume(count as i64);
+                return Ok(());
             }
             _ => {
                 return Err(Error::SysError(SysErr::EPIPE));
@@ -347,14 +380,18 @@ impl TsotSocketOperations {
                     return Err(Error::SysError(SysErr::EPIPE));
                 }
 
-                return QUring::SocketConsume(
+                // the produced or consumed bytes are already in the socket buffer, they are only
+                // accounted so that the following reads and writes are throttled
+                QUring::SocketConsume(
                     task,
                     self.fd,
                     self.queue.clone(),
                     buf,
                     count as usize,
                     iovs,
-                );
+                )?;
+                SHARESPACE.podBandwidth.ingress.Consume(count as i64);
+                return Ok(());
             }
             _ => {
                 return Err(Error::SysError(SysErr::EPIPE));
@@ -445,15 +482,31 @@ impl TsotSocketOperations {
         peek: bool,
     ) -> Result<i64> {
         let ret = match buf {
-            TsotSocketType::Uring(buf) => QUring::RingFileRead(
-                task,
-                self.fd,
-                self.queue.clone(),
-                buf.clone(),
-                dsts,
-                true,
-                peek,
-            )?,
+            TsotSocketType::Uring(buf) => {
+                let mut limited;
+                let dsts = if peek {
+                    dsts
+                } else {
+                    let allowed = SHARESPACE
+                        .podBandwidth
+                        .TakeIngress(&self.queue, IoVec::NumBytes(dsts))?;
+                    limited = Iovs(dsts).First(allowed);
+                    &mut limited[..]
+                };
+                let count = QUring::RingFileRead(
+                    task,
+                    self.fd,
+                    self.queue.clone(),
+                    buf.clone(),
+                    dsts,
+                    true,
+                    peek,
+                )?;
+                if !peek {
+                    SHARESPACE.podBandwidth.ingress.Consume(count);
+                }
+                count
+            }
             TsotSocketType::Loopback(loopback) => loopback.Readv(task, dsts, peek)?,
             _ => {
                 //return Err(Error::SysError(SysErr::ECONNREFUSED));
@@ -467,7 +520,21 @@ impl TsotSocketOperations {
     pub fn WriteToBuf(&self, task: &Task, buf: &TsotSocketType, srcs: &[IoVec]) -> Result<i64> {
         let ret = match buf {
             TsotSocketType::Uring(buf) => {
-                QUring::TsotSocketSend(task, self.fd, self.queue.clone(), buf.clone(), srcs, self)?
+                let allowed = SHARESPACE
+                    .podBandwidth
+                    .TakeEgress(&self.queue, IoVec::NumBytes(srcs))?;
+                let limited = Iovs(srcs).First(allowed);
+                let srcs = &limited[..];
+                let count = QUring::TsotSocketSend(
+                    task,
+                    self.fd,
+                    self.queue.clone(),
+                    buf.clone(),
+                    srcs,
+                    self,
+                )?;
+                SHARESPACE.podBandwidth.egress.Consume(count);
+                count
             }
             TsotSocketType::Loopback(loopback) => loopback.Writev(task, srcs)?,
             _ => {
@@ -478,6 +545,370 @@ impl TsotSocketOperations {
 
         return Ok(ret);
     }
+
+    // the AF_INET6 socket reaches the pod ipv6 addresses by tsot, the loopback and the any
+    // addresses are served by the ipv4 bindings and the v4 mapped addresses are ipv4 endpoints
+    pub fn ParseSockAddr(&self, sockaddr: &[u8]) -> Result<QIPEndpoint> {
+        if sockaddr.len() < 2 {
+            return Err(Error::SysError(SysErr::EINVAL));
+        }
+
+        let family = unsafe { *(&sockaddr[0] as *const _ as u64 as *const u16) };
+        if family == AFType::AF_INET as u16 {
+            if self.v6Only.load(Ordering::Relaxed) {
+                return Err(Error::SysError(SysErr::EAFNOSUPPORT));
+            }
+
+            if sockaddr.len() < SocketSize::SIZEOF_SOCKADDR_INET4 {
+                return Err(Error::SysError(SysErr::EINVAL));
+            }
+
+            let addr = unsafe { &*(&sockaddr[0] as *const _ as u64 as *const SockAddrInet) };
+            return Ok(QIPEndpoint::FromIpv4(QIPv4Addr::from(&addr.Addr), addr.Ipv4Port()));
+        }
+
+        if family == AFType::AF_INET6 as u16 && self.family == AFType::AF_INET6 {
+            if sockaddr.len() < SocketSize::SIZEOF_SOCKADDR_INET6 {
+                return Err(Error::SysError(SysErr::EINVAL));
+            }
+
+            let addr = unsafe { &*(&sockaddr[0] as *const _ as u64 as *const SocketAddrInet6) };
+            let port = RevertU16(addr.Port);
+            let ip = TsotAddr::FromIpv6(addr.Addr);
+            if ip.IsIpv4() {
+                if self.v6Only.load(Ordering::Relaxed) {
+                    return Err(Error::SysError(SysErr::ENETUNREACH));
+                }
+
+                return Ok(QIPEndpoint::New(ip, port));
+            }
+
+            if ip.IsUnspecified() {
+                return Ok(QIPEndpoint::FromIpv4(QIPv4Addr(0), port));
+            }
+
+            if ip.IsLoopback() {
+                return Ok(QIPEndpoint::FromIpv4(QIPv4Addr::Loopback(), port));
+            }
+
+            return Ok(QIPEndpoint::New(ip, port));
+        }
+
+        return Err(Error::SysError(SysErr::EAFNOSUPPORT));
+    }
+
+    // the ipv4 endpoints are presented as the v4 mapped addresses except the loopback and
+    // the any addresses
+    pub fn Inet6SockAddr(endpoint: &QIPEndpoint) -> SockAddr {
+        let mut ip = endpoint.ip.0;
+        match endpoint.Ipv4() {
+            Some(addr) if addr.IsAny() => ip = [0; 16],
+            Some(addr) if addr == QIPv4Addr::Loopback() => {
+                ip = [0; 16];
+                ip[15] = 1;
+            }
+            _ => (),
+        }
+
+        return SockAddr::Inet6(SocketAddrInet6 {
+            Family: AFType::AF_INET6 as u16,
+            Port: RevertU16(endpoint.port),
+            Flowinfo: 0,
+            Addr: ip,
+            Scope_id: 0,
+        });
+    }
+
+    // the peer address of the stream socket in the socket family
+    pub fn PeerSockAddr(&self, endpoint: &QIPEndpoint) -> SockAddr {
+        if self.family == AFType::AF_INET6 {
+            return Self::Inet6SockAddr(endpoint);
+        }
+
+        return endpoint.ToSockAddr();
+    }
+
+    pub fn UdpSockAddr(&self, endpoint: &QIPEndpoint) -> SockAddr {
+        if self.family == AFType::AF_INET6 {
+            return Self::Inet6SockAddr(endpoint);
+        }
+
+        let ip = endpoint.Ipv4().unwrap_or(QIPv4Addr(0));
+        return SockAddr::Inet(SockAddrInet::New(endpoint.port, &ip.ToBytes()));
+    }
+
+    pub fn UdpDstAddr(&self, msgHdr: &MsgHdr) -> Result<QIPEndpoint> {
+        let sockaddr = unsafe {
+            core::slice::from_raw_parts(msgHdr.msgName as *const u8, msgHdr.nameLen as usize)
+        };
+        return self.ParseSockAddr(sockaddr);
+    }
+
+    // the pod network port is bound in the node agent, the loopback port is bound in the host socket
+    pub fn UdpBind(&self, relay: &TsotUdpRelay, ip: QIPv4Addr, port: u16) -> Result<()> {
+        if self.bindPort.load(Ordering::Relaxed) != 0 {
+            return Err(Error::SysError(SysErr::EINVAL));
+        }
+
+        let port = if ip.IsLoopback() {
+            port
+        } else {
+            SHARESPACE.tsotSocketMgr.UdpBind(relay.socketId, port)?
+        };
+
+        // the pod ip is not a host address, the host socket binds the loopback instead
+        let hostIp = if ip.IsLoopback() || ip.IsAny() {
+            ip
+        } else {
+            QIPv4Addr::Loopback()
+        };
+
+        let addr = SockAddrInet::New(port, &hostIp.ToBytes());
+        let res = HostSpace::Bind(self.fd, &addr as *const _ as u64, addr.Len() as u32, 0);
+        if res < 0 {
+            if !ip.IsLoopback() {
+                SHARESPACE.tsotSocketMgr.UdpRemoveBind(port);
+            }
+            return Err(Error::SysError(-res as i32));
+        }
+
+        let port = if port == 0 {
+            // the host socket allocates the loopback port
+            let mut addr = SockAddrInet::default();
+            let len = addr.Len() as u32;
+            let res = HostSpace::GetSockName(
+                self.fd,
+                &mut addr as *mut _ as u64,
+                &len as *const _ as u64,
+            );
+            if res < 0 {
+                return Err(Error::SysError(-res as i32));
+            }
+            addr.Ipv4Port()
+        } else {
+            port
+        };
+
+        self.bindPort.store(port, Ordering::SeqCst);
+        self.bindIp.store(ip.0, Ordering::SeqCst);
+        return Ok(())
+    }
+
+    pub fn UdpConnect(&self, relay: &TsotUdpRelay, sockaddr: &[u8]) -> Result<i64> {
+        if sockaddr.len() < 2 {
+            return Err(Error::SysError(SysErr::EINVAL));
+        }
+
+        let family = unsafe { *(&sockaddr[0] as *const _ as u64 as *const u16) };
+        if family == AFType::AF_UNSPEC as u16 {
+            *self.remoteAddr.lock() = None;
+            return Ok(0)
+        }
+
+        let remote = self.ParseSockAddr(sockaddr)?;
+        if self.bindPort.load(Ordering::Relaxed) == 0 {
+            self.UdpBind(relay, QIPv4Addr(0), 0)?;
+        }
+
+        self.SetRemoteAddr(remote);
+        return Ok(0)
+    }
+
+    // send one datagram without blocking, the loopback datagram goes to the host socket
+    // and the others go to the node agent with the UdpDatagramHdr
+    fn UdpTrySend(&self, relay: &TsotUdpRelay, dst: &QIPEndpoint, buf: &DataBuff) -> i64 {
+        if let Some(ip) = dst.Ipv4().filter(|ip| ip.IsLoopback()) {
+            let addr = SockAddrInet::New(dst.port, &ip.ToBytes());
+            return HostSpace::IOSendto(
+                self.fd,
+                buf.Ptr() + UDP_DATAGRAM_HDR_SIZE as u64,
+                buf.Len() - UDP_DATAGRAM_HDR_SIZE,
+                MsgType::MSG_DONTWAIT,
+                &addr as *const _ as u64,
+                addr.Len() as u32,
+            );
+        }
+
+        return HostSpace::IOSendto(relay.fd, buf.Ptr(), buf.Len(), MsgType::MSG_DONTWAIT, 0, 0);
+    }
+
+    pub fn UdpSend(
+        &self,
+        task: &Task,
+        relay: &TsotUdpRelay,
+        srcs: &[IoVec],
+        flags: i32,
+        dst: Option<QIPEndpoint>,
+        deadline: Option<Time>,
+    ) -> Result<i64> {
+        let dst = match dst {
+            Some(dst) => dst,
+            None => match *self.remoteAddr.lock() {
+                None => return Err(Error::SysError(SysErr::EDESTADDRREQ)),
+                Some(addr) => addr,
+            },
+        };
+
+        let size = IoVec::NumBytes(srcs);
+        if size > UDP_MAX_PAYLOAD_SIZE {
+            return Err(Error::SysError(SysErr::EMSGSIZE));
+        }
+
+        if self.bindPort.load(Ordering::Relaxed) == 0 {
+            self.UdpBind(relay, QIPv4Addr(0), 0)?;
+        }
+
+        let hdr = UdpDatagramHdr {
+            peerIp: dst.ip,
+            peerPort: dst.port,
+            localPort: self.bindPort.load(Ordering::Relaxed),
+        };
+
+        let mut buf = DataBuff::New(UDP_DATAGRAM_HDR_SIZE + size);
+        buf.buf[..UDP_DATAGRAM_HDR_SIZE].copy_from_slice(hdr.AsBytes());
+        task.CopyDataInFromIovs(&mut buf.buf[UDP_DATAGRAM_HDR_SIZE..], srcs, false)?;
+
+        let res = self.UdpTrySend(relay, &dst, &buf);
+        if res >= 0 {
+            return Ok(size as i64);
+        }
+
+        if res != -SysErr::EWOULDBLOCK as i64 || flags & MsgType::MSG_DONTWAIT != 0 {
+            return Err(Error::SysError(-res as i32));
+        }
+
+        let general = task.blocker.generalEntry.clone();
+        self.EventRegister(task, &general, EVENT_WRITE);
+        defer!(self.EventUnregister(task, &general));
+
+        loop {
+            let res = self.UdpTrySend(relay, &dst, &buf);
+            if res >= 0 {
+                return Ok(size as i64);
+            }
+
+            if res != -SysErr::EWOULDBLOCK as i64 {
+                return Err(Error::SysError(-res as i32));
+            }
+
+            match task.blocker.BlockWithMonoTimer(true, deadline) {
+                Err(Error::SysError(SysErr::ETIMEDOUT)) => {
+                    return Err(Error::SysError(SysErr::EWOULDBLOCK));
+                }
+                Err(Error::ErrInterrupted) => {
+                    return Err(Error::SysError(SysErr::ERESTARTSYS));
+                }
+                Err(e) => {
+                    return Err(e);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    // receive one datagram without blocking, the node agent datagrams are checked first
+    // return: (datagram len, peer)
+    fn UdpTryRecv(
+        &self,
+        relay: &TsotUdpRelay,
+        buf: &mut DataBuff,
+        flags: i32,
+    ) -> Result<(usize, QIPEndpoint)> {
+        let flags = (flags & MsgType::MSG_PEEK) | MsgType::MSG_TRUNC | MsgType::MSG_DONTWAIT;
+        let res = HostSpace::IORecvfrom(relay.fd, buf.Ptr(), buf.Len(), flags, 0, 0);
+        if res >= UDP_DATAGRAM_HDR_SIZE as i64 {
+            let hdr = UdpDatagramHdr::FromBytes(&buf.buf);
+            let peer = QIPEndpoint::New(hdr.peerIp, hdr.peerPort);
+            return Ok((res as usize - UDP_DATAGRAM_HDR_SIZE, peer));
+        }
+
+        if res >= 0 {
+            // the node agent has closed the relay socket
+            return Err(Error::SysError(SysErr::ECONNREFUSED));
+        }
+
+        if res != -SysErr::EWOULDBLOCK as i64 {
+            return Err(Error::SysError(-res as i32));
+        }
+
+        let mut addr = SockAddrInet::default();
+        let len = addr.Len() as u32;
+        let res = HostSpace::IORecvfrom(
+            self.fd,
+            buf.Ptr() + UDP_DATAGRAM_HDR_SIZE as u64,
+            buf.Len() - UDP_DATAGRAM_HDR_SIZE,
+            flags,
+            &mut addr as *mut _ as u64,
+            &len as *const _ as u64,
+        );
+        if res < 0 {
+            return Err(Error::SysError(-res as i32));
+        }
+
+        let peer = QIPEndpoint::FromIpv4(QIPv4Addr::from(&addr.Addr), addr.Ipv4Port());
+        return Ok((res as usize, peer));
+    }
+
+    pub fn UdpRecv(
+        &self,
+        task: &Task,
+        relay: &TsotUdpRelay,
+        dsts: &mut [IoVec],
+        flags: i32,
+        deadline: Option<Time>,
+        senderRequested: bool,
+    ) -> Result<(i64, i32, Option<(SockAddr, usize)>)> {
+        let size = IoVec::NumBytes(dsts);
+        let mut buf = DataBuff::New(UDP_DATAGRAM_HDR_SIZE + size);
+
+        let (len, peer) = match self.UdpTryRecv(relay, &mut buf, flags) {
+            Err(Error::SysError(SysErr::EWOULDBLOCK)) if flags & MsgType::MSG_DONTWAIT == 0 => {
+                let general = task.blocker.generalEntry.clone();
+                self.EventRegister(task, &general, EVENT_READ);
+                defer!(self.EventUnregister(task, &general));
+
+                loop {
+                    match self.UdpTryRecv(relay, &mut buf, flags) {
+                        Err(Error::SysError(SysErr::EWOULDBLOCK)) => (),
+                        Err(e) => return Err(e),
+                        Ok(ret) => break ret,
+                    }
+
+                    match task.blocker.BlockWithMonoTimer(true, deadline) {
+                        Err(Error::SysError(SysErr::ETIMEDOUT)) => {
+                            return Err(Error::SysError(SysErr::EAGAIN));
+                        }
+                        Err(Error::ErrInterrupted) => {
+                            return Err(Error::SysError(SysErr::ERESTARTSYS));
+                        }
+                        Err(e) => {
+                            return Err(e);
+                        }
+                        _ => (),
+                    }
+                }
+            }
+            Err(e) => return Err(e),
+            Ok(ret) => ret,
+        };
+
+        // the datagram is truncated to the user buffer
+        let count = len.min(size);
+        task.CopyDataOutToIovs(&buf.buf[UDP_DATAGRAM_HDR_SIZE..UDP_DATAGRAM_HDR_SIZE + count], dsts, false)?;
+
+        let retFlags = if len > size { MsgType::MSG_TRUNC } else { 0 };
+        let ret = if flags & MsgType::MSG_TRUNC != 0 { len } else { count };
+
+        let senderAddr = if senderRequested {
+            let addr = self.UdpSockAddr(&peer);
+            let l = addr.Len();
+            Some((addr, l))
+        } else {
+            None
+        };
+
+        return Ok((ret as i64, retFlags, senderAddr));
+    }
 }
 
 impl Deref for TsotSocketOperations {
@@ -508,11 +939,16 @@ impl Waitable for TsotSocketOperations {
                  return event & mask;
             }
             TsotSocketType::Uring(buf) => {
-                return buf.Events() & mask;
+                return SHARESPACE
+                    .podBandwidth
+                    .Readiness(&self.queue, buf.Events() & mask);
             }
             TsotSocketType::Loopback(loopback) => {
                 return loopback.Events() & mask;
             }
+            TsotSocketType::Udp(relay) => {
+                return (NonBlockingPoll(self.fd, mask) | NonBlockingPoll(relay.fd, mask)) & mask;
+            }
             TsotSocketType::Init => {
                 return 0;
             },
@@ -528,6 +964,10 @@ impl Waitable for TsotSocketOperations {
             TsotSocketType::Server(_q) => (),
             TsotSocketType::Uring(_buf) => (),
             TsotSocketType::Loopback(_) => (),
+            TsotSocketType::Udp(relay) => {
+                UpdateFD(self.fd).unwrap();
+                UpdateFD(relay.fd).unwrap();
+            }
             TsotSocketType::Init => {}
         }
     }
@@ -540,6 +980,10 @@ impl Waitable for TsotSocketOperations {
             TsotSocketType::Server(_q) => (),
             TsotSocketType::Uring(_buf) => (),
             TsotSocketType::Loopback(_) => (),
+            TsotSocketType::Udp(relay) => {
+                UpdateFD(self.fd).unwrap();
+                UpdateFD(relay.fd).unwrap();
+            }
             TsotSocketType::Init => {}
         }
     }
@@ -595,6 +1039,11 @@ impl FileOperations for TsotSocketOperations {
                 /*if self.SocketBuf().RClosed() {
                     return Err(Error::SysError(SysErr::ESPIPE))
                 }*/
+                let allowed = SHARESPACE
+                    .podBandwidth
+                    .TakeIngress(&self.queue, IoVec::NumBytes(dsts))?;
+                let mut limited = Iovs(dsts).First(allowed);
+                let dsts = &mut limited[..];
                 let ret = QUring::RingFileRead(
                     task,
                     self.fd,
@@ -604,12 +1053,18 @@ impl FileOperations for TsotSocketOperations {
                     true,
                     false,
                 )?;
+                SHARESPACE.podBandwidth.ingress.Consume(ret);
                 return Ok(ret);
             }
             TsotSocketType::Loopback(loopback) => {
                 let count = loopback.Readv(task, dsts, false)?;
                 return Ok(count);
             }
+            TsotSocketType::Udp(relay) => {
+                let (count, _, _) =
+                    self.UdpRecv(task, &relay, dsts, MsgType::MSG_DONTWAIT, None, false)?;
+                return Ok(count);
+            }
             _ => {
                 return Ok(0);
             }
@@ -636,12 +1091,23 @@ impl FileOperations for TsotSocketOperations {
                     return Err(Error::SysError(SysErr::EPIPE));
                 }
 
-                return QUring::TsotSocketSend(task, self.fd, self.queue.clone(), buf, srcs, self);
+                let allowed = SHARESPACE
+                    .podBandwidth
+                    .TakeEgress(&self.queue, IoVec::NumBytes(srcs))?;
+                let limited = Iovs(srcs).First(allowed);
+                let srcs = &limited[..];
+                let count =
+                    QUring::TsotSocketSend(task, self.fd, self.queue.clone(), buf, srcs, self)?;
+                SHARESPACE.podBandwidth.egress.Consume(count);
+                return Ok(count);
             }
             TsotSocketType::Loopback(loopback) => {
                 let count = loopback.Writev(task, srcs)?;
                 return Ok(count);
             }
+            TsotSocketType::Udp(relay) => {
+                return self.UdpSend(task, &relay, srcs, MsgType::MSG_DONTWAIT, None, None);
+            }
             _ => {
                 return Err(Error::SysError(SysErr::EPIPE));
             }
@@ -750,22 +1216,16 @@ impl FileOperations for TsotSocketOperations {
 impl SockOperations for TsotSocketOperations {
     fn Connect(&self, task: &Task, sockaddr: &[u8], blocking: bool) -> Result<i64> {
         let sockType = self.SocketType();
+        if let TsotSocketType::Udp(relay) = &sockType {
+            return self.UdpConnect(relay, sockaddr);
+        }
+
         match sockType {
             TsotSocketType::Init => {
-                let addr = unsafe { &*(&sockaddr[0] as *const _ as u64 as *const SockAddrInet) };
-
-                if addr.Family != AFType::AF_INET as u16 {
-                    // only support Ipv4 so far;
-                    return Err(Error::SysError(SysErr::EAFNOSUPPORT));
-                }
-
-                let addrPort = addr.Ipv4Port();
-                let ipAddr = QIPv4Addr::from(&addr.Addr);
-                self.SetRemoteAddr(QIPv4Endpoint {
-                    ip: ipAddr,
-                    port: addrPort
-                } );
-                if ipAddr.IsLoopback() {
+                let remote = self.ParseSockAddr(sockaddr)?;
+                let addrPort = remote.port;
+                self.SetRemoteAddr(remote);
+                if let Some(ipAddr) = remote.Ipv4().filter(|ip| ip.IsLoopback()) {
                     let serverQueue = Queue::default();
                     let (clientSock, serverSock) =
                         LoopbackSocketPair(self.queue.clone(), serverQueue.clone());
@@ -787,7 +1247,7 @@ impl SockOperations for TsotSocketOperations {
                     return Ok(0);
                 }
 
-                SHARESPACE.tsotSocketMgr.Connect(ipAddr, addrPort, 123, self.fd, self)?;
+                SHARESPACE.tsotSocketMgr.Connect(remote.ip, addrPort, 123, self.fd, self)?;
                 
                 *self.socketType.lock() = TsotSocketType::Connecting;
                 if !blocking {
@@ -878,14 +1338,13 @@ impl SockOperations for TsotSocketOperations {
             }
         }
 
-        let peerIp : QIPv4Addr = acceptItem.addr.into();
-        if !peerIp.IsLoopback() {
+        if !acceptItem.addr.IsLoopback() {
             SHARESPACE.tsotSocketMgr.Accept(acceptItem.port)?;
         }
 
         if addr.len() > 0 {
-            let peerAddr = QIPv4Endpoint::New(acceptItem.addr.into(), acceptItem.port);
-            let peerSockAddr = peerAddr.ToSockAddr();
+            let peerAddr = QIPEndpoint::New(acceptItem.addr, acceptItem.port);
+            let peerSockAddr = self.PeerSockAddr(&peerAddr);
             let vec = peerSockAddr.ToVec()?;
             let len = addr.len().min(vec.len());
             for i in 0..len {
@@ -907,7 +1366,7 @@ impl SockOperations for TsotSocketOperations {
             flags & SocketFlags::SOCK_NONBLOCK != 0,
             acceptItem.queue.clone(),
             sockBuf,
-            Some(QIPv4Endpoint::New(acceptItem.addr.into(), acceptItem.port)),
+            Some(QIPEndpoint::New(acceptItem.addr, acceptItem.port)),
         )?;
 
         let fdFlags = FDFlags {
@@ -921,20 +1380,32 @@ impl SockOperations for TsotSocketOperations {
     fn Bind(&self, _task: &Task, sockaddr: &[u8]) -> Result<i64> {
         let socketaddr = sockaddr;
 
-        let addr = unsafe { &*(&socketaddr[0] as *const _ as u64 as *const SockAddrInet) };
+        let addr = match self.ParseSockAddr(socketaddr) {
+            // the v4 mapped address of the v6 only socket
+            Err(Error::SysError(SysErr::ENETUNREACH)) => {
+                return Err(Error::SysError(SysErr::EADDRNOTAVAIL));
+            }
+            Err(e) => return Err(e),
+            Ok(addr) => addr,
+        };
         // info!(
         //     "hostinet socket bind {:?}, addr is {:?}/{:?}",
         //     self.family, addr, socketaddr
         // );
 
-        if self.family != AFType::AF_INET {
-            // doesn't support ipv6 so far
-            return Err(Error::SysError(SysErr::EAFNOSUPPORT)); 
-        }
-
-        let ip = QIPv4Addr::from(&addr.Addr);
-
         let localAddr = SHARESPACE.tsotSocketMgr.LocalIpAddr();
+
+        // the pod ipv6 address shares the bindings of the pod ipv4 address
+        let ip = match addr.Ipv4() {
+            Some(ip) => ip,
+            None => {
+                let localIpv6Addr = SHARESPACE.tsotSocketMgr.LocalIpv6Addr();
+                if localIpv6Addr.IsUnspecified() || addr.ip != localIpv6Addr {
+                    return Err(Error::SysError(SysErr::EADDRNOTAVAIL));
+                }
+                localAddr
+            }
+        };
         if !ip.IsLoopback() && !ip.IsAny() {
             if ip != localAddr {
                 // can't bind non-local address
@@ -942,7 +1413,12 @@ impl SockOperations for TsotSocketOperations {
             }
         }
         
-        let addrPort = addr.Ipv4Port();
+        let addrPort = addr.port;
+        if let TsotSocketType::Udp(relay) = self.SocketType() {
+            self.UdpBind(&relay, ip, addrPort)?;
+            return Ok(0);
+        }
+
         let reusePort = self.reusePort.load(Ordering::Relaxed);
         if ip.IsLoopback() {
             SHARESPACE.tsotSocketMgr.Bind(ip, addrPort, reusePort)?;
@@ -1002,6 +1478,7 @@ impl SockOperations for TsotSocketOperations {
                 qs.push(q);
                 qs
             }
+            TsotSocketType::Udp(_) => return Err(Error::SysError(SysErr::EOPNOTSUPP)),
             _ => panic!("uring socket listen on wrong type {:?}", socketBuf), // panic?
         };
 
@@ -1033,6 +1510,7 @@ impl SockOperations for TsotSocketOperations {
             }
             TsotSocketType::Loopback(_) => (),
             TsotSocketType::Server(_) => (),
+            TsotSocketType::Udp(_) => (),
         }
 
         if how == LibcConst::SHUT_RD || how == LibcConst::SHUT_WR || how == LibcConst::SHUT_RDWR {
@@ -1063,6 +1541,7 @@ impl SockOperations for TsotSocketOperations {
                     }
                 }
                 TsotSocketType::Server(_) => (),
+                TsotSocketType::Udp(_) => (),
             }
 
             self.queue.Notify(EventMaskFromLinux(EVENT_HUP as u32));
@@ -1168,6 +1647,22 @@ impl SockOperations for TsotSocketOperations {
             _ => (),
         };
 
+        if self.family == AFType::AF_INET6 && (level as u64) == LibcConst::SOL_IPV6 {
+            // the host socket is AF_INET, the ipv6 options are kept by the socket
+            match name as u64 {
+                LibcConst::IPV6_V6ONLY => {
+                    if opt.len() < SIZEOF_I32 {
+                        return Err(Error::SysError(SysErr::EINVAL));
+                    }
+
+                    let val: i32 = if self.v6Only.load(Ordering::Relaxed) { 1 } else { 0 };
+                    task.CopyOutObj(&val, &mut opt[0] as *mut _ as u64)?;
+                    return Ok(SIZEOF_I32 as i64);
+                }
+                _ => return Err(Error::SysError(SysErr::ENOPROTOOPT)),
+            }
+        }
+
         if (level as u64) == LibcConst::SOL_TCP {
             match name as u64 {                
                 LibcConst::TCP_INQ => {
@@ -1283,6 +1778,21 @@ impl SockOperations for TsotSocketOperations {
             }
         }
 
+        if self.family == AFType::AF_INET6 && (level as u64) == LibcConst::SOL_IPV6 {
+            match name as u64 {
+                LibcConst::IPV6_V6ONLY => {
+                    if opt.len() < SIZEOF_I32 {
+                        return Err(Error::SysError(SysErr::EINVAL));
+                    }
+
+                    let val: i32 = task.CopyInObj::<i32>(&opt[0] as *const _ as u64)?;
+                    self.v6Only.store(val != 0, Ordering::Relaxed);
+                    return Ok(0);
+                }
+                _ => return Err(Error::SysError(SysErr::ENOPROTOOPT)),
+            }
+        }
+
         if (level as u64) == LibcConst::SOL_TCP {
             match name as u64 {                
                 LibcConst::TCP_INQ => {
@@ -1335,6 +1845,50 @@ impl SockOperations for TsotSocketOperations {
     }
 
     fn GetSockName(&self, _task: &Task, socketaddr: &mut [u8]) -> Result<i64> {
+        if let TsotSocketType::Udp(_) = self.SocketType() {
+            let ip: QIPv4Addr = self.bindIp.load(Ordering::Relaxed).into();
+            let port = self.bindPort.load(Ordering::Relaxed);
+            let addr = self.UdpSockAddr(&QIPEndpoint::FromIpv4(ip, port));
+            let v = addr.ToVec()?;
+            let len = addr.Len().min(socketaddr.len());
+            for i in 0..len {
+                socketaddr[i] = v[i];
+            }
+
+            return Ok(len as i64);
+        }
+
+        if self.family == AFType::AF_INET6 {
+            // the host socket is AF_INET, translate its address
+            let mut addr = SockAddrInet::default();
+            let len = addr.Len() as u32;
+            let res = Kernel::HostSpace::GetSockName(
+                self.fd,
+                &mut addr as *mut _ as u64,
+                &len as *const _ as u64,
+            );
+            if res < 0 {
+                return Err(Error::SysError(-res as i32));
+            }
+
+            let mut endpoint = QIPEndpoint::FromIpv4(QIPv4Addr::from(&addr.Addr), addr.Ipv4Port());
+            // the connection to an ipv6 peer is from the pod ipv6 address
+            if let Ok(remote) = self.RemoteAddr() {
+                let localIpv6Addr = SHARESPACE.tsotSocketMgr.LocalIpv6Addr();
+                if !remote.ip.IsIpv4() && !localIpv6Addr.IsUnspecified() {
+                    endpoint.ip = localIpv6Addr;
+                }
+            }
+            let addr = Self::Inet6SockAddr(&endpoint);
+            let v = addr.ToVec()?;
+            let len = addr.Len().min(socketaddr.len());
+            for i in 0..len {
+                socketaddr[i] = v[i];
+            }
+
+            return Ok(len as i64);
+        }
+
         let len = socketaddr.len() as i32;
 
         let res = Kernel::HostSpace::GetSockName(
@@ -1350,7 +1904,13 @@ impl SockOperations for TsotSocketOperations {
     }
 
     fn GetPeerName(&self, _task: &Task, socketaddr: &mut [u8]) -> Result<i64> {
-        let addr = self.RemoteAddr()?.ToSockAddr();
+        let addr = match self.SocketType() {
+            TsotSocketType::Udp(_) => match *self.remoteAddr.lock() {
+                None => return Err(Error::SysError(SysErr::ENOTCONN)),
+                Some(remote) => self.UdpSockAddr(&remote),
+            },
+            _ => self.PeerSockAddr(&self.RemoteAddr()?),
+        };
         let v = addr.ToVec()?;
         let len = addr.Len().min(socketaddr.len());
         for i in 0..len {
@@ -1388,6 +1948,12 @@ impl SockOperations for TsotSocketOperations {
             return Err(Error::SysError(SysErr::EINVAL));
         }
 
+        if let TsotSocketType::Udp(relay) = &buf {
+            let (count, retFlags, senderAddr) =
+                self.UdpRecv(task, relay, dsts, flags, deadline, senderRequested)?;
+            return Ok((count, retFlags, senderAddr, Vec::new()));
+        }
+
         let waitall = (flags & MsgType::MSG_WAITALL) != 0;
         let dontwait = (flags & MsgType::MSG_DONTWAIT) != 0;
         let trunc = (flags & MsgType::MSG_TRUNC) != 0;
@@ -1395,7 +1961,7 @@ impl SockOperations for TsotSocketOperations {
 
         if buf.RClosed() {
             let senderAddr = if senderRequested {
-                let addr = self.RemoteAddr()?.ToSockAddr();
+                let addr = self.PeerSockAddr(&self.RemoteAddr()?);
                 let l = addr.Len();
                 Some((addr, l))
             } else {
@@ -1419,7 +1985,7 @@ impl SockOperations for TsotSocketOperations {
                 Err(e) => return Err(e),
                 Ok(count) => {
                     let senderAddr = if senderRequested {
-                        let addr = self.RemoteAddr()?.ToSockAddr();
+                        let addr = self.PeerSockAddr(&self.RemoteAddr()?);
                         let l = addr.Len();
                         Some((addr, l))
                     } else {
@@ -1500,7 +2066,7 @@ impl SockOperations for TsotSocketOperations {
         }
 
         let senderAddr = if senderRequested {
-            let addr = self.RemoteAddr()?.ToSockAddr();
+            let addr = self.PeerSockAddr(&self.RemoteAddr()?);
             let l = addr.Len();
             Some((addr, l))
         } else {
@@ -1521,11 +2087,20 @@ impl SockOperations for TsotSocketOperations {
         task: &Task,
         srcs: &[IoVec],
         flags: i32,
-        _msgHdr: &mut MsgHdr,
+        msgHdr: &mut MsgHdr,
         deadline: Option<Time>,
     ) -> Result<i64> {
         let buf = self.SocketType();
 
+        if let TsotSocketType::Udp(relay) = &buf {
+            let dst = if msgHdr.msgName != 0 {
+                Some(self.UdpDstAddr(msgHdr)?)
+            } else {
+                None
+            };
+            return self.UdpSend(task, relay, srcs, flags, dst, deadline);
+        }
+
         if buf.WClosed() {
             return Err(Error::SysError(SysErr::EPIPE));
         }
@@ -1619,6 +2194,10 @@ impl SockOperations for TsotSocketOperations {
     }
 
     fn State(&self) -> u32 {
+        if self.IsUdp() {
+            return 0;
+        }
+
         let mut info = TCPInfo::default();
         let mut len = SocketSize::SIZEOF_TCPINFO;
 
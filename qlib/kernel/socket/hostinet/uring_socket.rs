@@ -296,7 +296,9 @@ impl UringSocketOperations {
                     return Err(Error::SysError(SysErr::EPIPE));
                 }
 
-                return QUring::SocketProduce(
+                // the produced or consumed bytes are already in the socket buffer, they are only
+                // accounted so that the following reads and writes are throttled
+                QUring::SocketProduce(
                     task,
                     self.fd,
                     self.queue.clone(),
@@ -304,7 +306,9 @@ impl UringSocketOperations {
                     count as usize,
                     self,
                     iovs,
-                );
+                )?;
+                SHARESPACE.podBandwidth.egress.Consume(count as i64);
+                return Ok(());
             }
             _ => {
                 return Err(Error::SysError(SysErr::EPIPE));
@@ -320,14 +324,18 @@ impl UringSocketOperations {
                     return Err(Error::SysError(SysErr::EPIPE));
                 }
 
-                return QUring::SocketConsume(
+                // the produced or consumed bytes are already in the socket buffer, they are only
+                // accounted so that the following reads and writes are throttled
+                QUring::SocketConsume(
                     task,
                     self.fd,
                     self.queue.clone(),
                     buf,
                     count as usize,
                     iovs,
-                );
+                )?;
+                SHARESPACE.podBandwidth.ingress.Consume(count as i64);
+                return Ok(());
             }
             _ => {
                 return Err(Error::SysError(SysErr::EPIPE));
@@ -430,15 +438,31 @@ impl UringSocketOperations {
         peek: bool,
     ) -> Result<i64> {
         let ret = match buf {
-            UringSocketType::Uring(buf) => QUring::RingFileRead(
-                task,
-                self.fd,
-                self.queue.clone(),
-                buf.clone(),
-                dsts,
-                true,
-                peek,
-            )?,
+            UringSocketType::Uring(buf) => {
+                let mut limited;
+                let dsts = if peek {
+                    dsts
+                } else {
+                    let allowed = SHARESPACE
+                        .podBandwidth
+                        .TakeIngress(&self.queue, IoVec::NumBytes(dsts))?;
+                    limited = Iovs(dsts).First(allowed);
+                    &mut limited[..]
+                };
+                let count = QUring::RingFileRead(
+                    task,
+                    self.fd,
+                    self.queue.clone(),
+                    buf.clone(),
+                    dsts,
+                    true,
+                    peek,
+                )?;
+                if !peek {
+                    SHARESPACE.podBandwidth.ingress.Consume(count);
+                }
+                count
+            }
             UringSocketType::Loopback(loopback) => loopback.Readv(task, dsts, peek)?,
             _ => {
                 //return Err(Error::SysError(SysErr::ECONNREFUSED));
@@ -452,7 +476,15 @@ impl UringSocketOperations {
     pub fn WriteToBuf(&self, task: &Task, buf: &UringSocketType, srcs: &[IoVec]) -> Result<i64> {
         let ret = match buf {
             UringSocketType::Uring(buf) => {
-                QUring::SocketSend(task, self.fd, self.queue.clone(), buf.clone(), srcs, self)?
+                let allowed = SHARESPACE
+                    .podBandwidth
+                    .TakeEgress(&self.queue, IoVec::NumBytes(srcs))?;
+                let limited = Iovs(srcs).First(allowed);
+                let srcs = &limited[..];
+                let count =
+                    QUring::SocketSend(task, self.fd, self.queue.clone(), buf.clone(), srcs, self)?;
+                SHARESPACE.podBandwidth.egress.Consume(count);
+                count
             }
             UringSocketType::Loopback(loopback) => loopback.Writev(task, srcs)?,
             _ => {
@@ -505,7 +537,9 @@ impl Waitable for UringSocketOperations {
                 return q.lock().Events() & mask;
             }
             UringSocketType::Uring(buf) => {
-                return buf.Events() & mask;
+                return SHARESPACE
+                    .podBandwidth
+                    .Readiness(&self.queue, buf.Events() & mask);
             }
             UringSocketType::Loopback(loopback) => {
                 return loopback.Events() & mask;
@@ -603,6 +637,11 @@ impl FileOperations for UringSocketOperations {
                 /*if self.SocketBuf().RClosed() {
                     return Err(Error::SysError(SysErr::ESPIPE))
                 }*/
+                let allowed = SHARESPACE
+                    .podBandwidth
+                    .TakeIngress(&self.queue, IoVec::NumBytes(dsts))?;
+                let mut limited = Iovs(dsts).First(allowed);
+                let dsts = &mut limited[..];
                 let ret = QUring::RingFileRead(
                     task,
                     self.fd,
@@ -612,6 +651,7 @@ impl FileOperations for UringSocketOperations {
                     true,
                     false,
                 )?;
+                SHARESPACE.podBandwidth.ingress.Consume(ret);
                 return Ok(ret);
             }
             UringSocketType::Loopback(loopback) => {
@@ -644,7 +684,14 @@ impl FileOperations for UringSocketOperations {
                     return Err(Error::SysError(SysErr::EPIPE));
                 }
 
-                return QUring::SocketSend(task, self.fd, self.queue.clone(), buf, srcs, self);
+                let allowed = SHARESPACE
+                    .podBandwidth
+                    .TakeEgress(&self.queue, IoVec::NumBytes(srcs))?;
+                let limited = Iovs(srcs).First(allowed);
+                let srcs = &limited[..];
+                let count = QUring::SocketSend(task, self.fd, self.queue.clone(), buf, srcs, self)?;
+                SHARESPACE.podBandwidth.egress.Consume(count);
+                return Ok(count);
             }
             UringSocketType::Loopback(loopback) => {
                 let count = loopback.Writev(task, srcs)?;
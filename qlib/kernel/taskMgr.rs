@@ -279,6 +279,14 @@ impl Scheduler {
     pub fn GetNext(&self) -> Option<TaskId> {
         let vcpuId = CPULocal::CpuId() as usize;
 
+        if !self.IsVcpuOnline(vcpuId) {
+            // the vcpu is parked, hand over the pending working task and take no more task
+            if let Some(t) = self.queue[vcpuId].ResetWorkingTask() {
+                self.ScheduleQ(t, 0, false);
+            }
+            return None;
+        }
+
         match self.queue[vcpuId].Next() {
             None => (),
             Some((t, global)) => {
@@ -336,7 +344,11 @@ impl Scheduler {
 }
 
 pub fn Yield() {
-    if SHARESPACE.scheduler.GlobalReadyTaskCnt() == 0 {
+    // the task on a parked vcpu has to move to online vcpu even there is no other ready task
+    let parked = !SHARESPACE
+        .scheduler
+        .IsVcpuOnline(CPULocal::CpuId() as usize);
+    if SHARESPACE.scheduler.GlobalReadyTaskCnt() == 0 && !parked {
         return;
     }
     SHARESPACE.scheduler.Schedule(Task::TaskId(), false);
@@ -304,6 +304,7 @@ impl Thread {
             let kernel = t.k.clone();
             let limit = tg.lock().limits.clone();
             let cid = tg.lock().containerID.clone();
+            let oomScoreAdj = tg.lock().oomScoreAdj;
             tg = kernel.newThreadGroup(
                 &pidns,
                 &sh,
@@ -312,6 +313,7 @@ impl Thread {
                 &cid,
                 &None,
             );
+            tg.lock().oomScoreAdj = oomScoreAdj;
         }
 
         let mut cfg = TaskConfig {
@@ -23,6 +23,7 @@ use super::super::super::auth::id::*;
 use super::super::super::common::*;
 use super::super::super::linux_def::*;
 use super::super::boot::controller::WriteWaitAllResponse;
+use super::super::memmgr::oom::{OOMKilled, MEM_CGROUPS};
 use super::super::threadmgr::pid_namespace::*;
 use super::super::threadmgr::thread::*;
 use super::super::threadmgr::thread_group::*;
@@ -1021,7 +1022,17 @@ impl Thread {
                 " sending exit notification for CID:{}, execID:{}",
                 &cid, &execId
             );
-            WriteWaitAllResponse(cid.clone(), execId.clone(), tg.ExitStatus().Status() as i32);
+            let containerInit = execId.len() == 0;
+            let oomKilled = OOMKilled(&tg, &cid, containerInit);
+            if containerInit {
+                MEM_CGROUPS.Remove(&cid);
+            }
+            WriteWaitAllResponse(
+                cid.clone(),
+                execId.clone(),
+                tg.ExitStatus().Status() as i32,
+                oomKilled,
+            );
             let curr = Task::Current();
             LOADER
                 .Lock(curr)
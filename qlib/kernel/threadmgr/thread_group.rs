@@ -278,6 +278,11 @@ pub struct ThreadGroupInternal {
 
     // root track whether this threadgroup is directly started by container provisioning
     pub root: bool,
+
+    // oomScoreAdj is the value of /proc/[pid]/oom_score_adj, in [-1000, 1000]
+    pub oomScoreAdj: i32,
+    // oomKilled is set when the thread group is killed by the oom killer
+    pub oomKilled: bool,
     pub timerMu: Arc<QMutex<()>>,
     // todo: handle tty
     //pub tty: Option<TTY>
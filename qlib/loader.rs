@@ -46,4 +46,8 @@ pub struct Process {
     pub Root: String,
     pub Stdiofds: [i32; 3],
     pub ExecId: Option<String>,
+
+    // memory limit of the container in bytes, 0 means no limit
+    pub MemoryLimit: u64,
+    pub OOMScoreAdj: i32,
 }
@@ -539,6 +539,30 @@ impl ListAllocator {
 
         return count > 0;
     }
+
+    /// take a size aligned block out of the heap for good, e.g. for memory balloon.
+    /// different with alloc, it won't go to the OOM handler when the heap is exhausted
+    pub fn TakeBlock(&self, size: usize) -> Option<u64> {
+        let layout = Layout::from_size_align(size, size).ok()?;
+        let ret = self.heap.lock().alloc(layout).ok()?;
+
+        self.total.fetch_sub(size, Ordering::Release);
+        self.free.fetch_sub(size, Ordering::Release);
+        return Some(ret.as_ptr() as u64);
+    }
+
+    /// give back the block taken by TakeBlock
+    pub fn ReturnBlock(&self, addr: u64, size: usize) {
+        let layout = Layout::from_size_align(size, size).unwrap();
+        unsafe {
+            self.heap
+                .lock()
+                .dealloc(NonNull::new_unchecked(addr as *mut u8), layout);
+        }
+
+        self.total.fetch_add(size, Ordering::Release);
+        self.free.fetch_add(size, Ordering::Release);
+    }
 }
 
 pub const PRINT_CLASS: usize = 0;
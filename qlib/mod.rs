@@ -121,6 +121,7 @@ use self::qmsg::*;
 use self::rdma_svc_cli::*;
 use self::ringbuf::*;
 use self::task_mgr::*;
+use self::kernel::socket::hostinet::bandwidth::PodBandwidth;
 use self::kernel::socket::hostinet::tsot_mgr::TsotSocketMgr;
 use self::kernel::quring::uring_async::UringEntry;
 
@@ -1214,9 +1215,12 @@ pub struct ShareSpace {
 
     pub tsotSocketMgr: TsotSocketMgr,
     pub dnsSvc: DnsSvc,
+    pub podBandwidth: PodBandwidth,
     pub uringQueue: UringQueue,
 
     pub bootId: QMutex<String>,
+    // sandboxId tags the log records of the sandbox
+    pub sandboxId: QMutex<String>,
     pub values: Vec<[AtomicU64; 2]>,
 }
 
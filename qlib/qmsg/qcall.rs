@@ -127,6 +127,7 @@ pub enum Msg {
     Proxy(Proxy),
     RemapGuestMemRanges(RemapGuestMemRanges),
     UnmapGuestMemRange(UnmapGuestMemRange),
+    RemoveGuestMemRange(RemoveGuestMemRange),
     NividiaDriverVersion(NividiaDriverVersion),
     NvidiaMMap(NvidiaMMap),
     HostUnixConnect(HostUnixConnect),
@@ -217,6 +218,12 @@ pub struct UnmapGuestMemRange {
     pub len: u64,
 }
 
+#[derive(Clone, Default, Debug)]
+pub struct RemoveGuestMemRange {
+    pub start: u64,
+    pub len: u64,
+}
+
 #[derive(Clone, Default, Debug)]
 pub struct Proxy {
     pub cmd: ProxyCommand,
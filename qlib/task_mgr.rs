@@ -127,6 +127,9 @@ pub struct Scheduler {
 
     pub vcpuWaitMask: AtomicU64,
     pub VcpuArr: Vec<CPULocal>,
+
+    // the vcpus [0, onlineVcpuCnt) take tasks, the others are parked
+    pub onlineVcpuCnt: AtomicUsize,
 }
 
 impl Scheduler {
@@ -142,6 +145,7 @@ impl Scheduler {
             VcpuArr: vcpuArr,
             queue: queue,
             vcpuCnt: vcpuCount,
+            onlineVcpuCnt: AtomicUsize::new(vcpuCount),
             ..Default::default()
         };
     }
@@ -172,6 +176,32 @@ impl Scheduler {
         return ret;
     }
 
+    pub fn OnlineVcpuCnt(&self) -> usize {
+        return self.onlineVcpuCnt.load(Ordering::Acquire);
+    }
+
+    pub fn IsVcpuOnline(&self, vcpuId: usize) -> bool {
+        return vcpuId < self.OnlineVcpuCnt();
+    }
+
+    pub fn OnlineVcpuMask(&self) -> u64 {
+        let cnt = self.OnlineVcpuCnt();
+        if cnt >= 64 {
+            return !0;
+        }
+
+        return (1u64 << cnt) - 1;
+    }
+
+    // change the count of the vcpus which take tasks, return the count really set.
+    // vcpu#0 is the io thread, so at least 2 vcpus stay online.
+    // the caller needs to WakeAll so that the new online vcpus start to take tasks.
+    pub fn SetOnlineVcpuCnt(&self, cnt: usize) -> usize {
+        let cnt = cnt.max(2).min(self.vcpuCnt);
+        self.onlineVcpuCnt.store(cnt, Ordering::SeqCst);
+        return cnt;
+    }
+
     pub fn ReadyTaskCnt(&self, vcpuId: usize) -> u64 {
         //return self.readyTaskCnt.load(Ordering::SeqCst) as u64
         return self.queue[vcpuId].Len();
@@ -199,6 +229,13 @@ impl Scheduler {
     }
 
     pub fn ScheduleQ(&self, task: TaskId, vcpuId: u64, cpuAff: bool) {
+        // the vcpu of the task has been parked, hand over the task to the global queue
+        let (vcpuId, cpuAff) = if self.IsVcpuOnline(vcpuId as usize) {
+            (vcpuId, cpuAff)
+        } else {
+            (0, false)
+        };
+
         if self.queue[vcpuId as usize].Enqueue(task, cpuAff) {
             self.IncReadyTaskCount();
         }
@@ -220,7 +257,8 @@ impl Scheduler {
 
     pub fn WakeOne(&self) -> i64 {
         loop {
-            let mask = self.vcpuWaitMask.load(Ordering::Acquire);
+            // don't wake up parked vcpu
+            let mask = self.vcpuWaitMask.load(Ordering::Acquire) & self.OnlineVcpuMask();
 
             let vcpuId = mask.trailing_zeros() as usize;
             if vcpuId >= 64 {
@@ -389,3 +427,24 @@ impl TaskQueue {
         return self.queueSize.load(Ordering::Acquire) as u64;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn TestSetOnlineVcpuCnt() {
+        let scheduler = Scheduler::New(8);
+        assert_eq!(scheduler.OnlineVcpuCnt(), 8);
+
+        assert_eq!(scheduler.SetOnlineVcpuCnt(3), 3);
+        assert_eq!(scheduler.OnlineVcpuMask(), 0b111);
+        assert!(scheduler.IsVcpuOnline(2));
+        assert!(!scheduler.IsVcpuOnline(3));
+
+        // the io thread and one vcpu stay online, no more than the vcpus are online
+        assert_eq!(scheduler.SetOnlineVcpuCnt(0), 2);
+        assert_eq!(scheduler.SetOnlineVcpuCnt(16), 8);
+        assert_eq!(scheduler.OnlineVcpuMask(), 0xff);
+    }
+}
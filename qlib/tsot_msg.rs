@@ -15,6 +15,7 @@
 use alloc::borrow::ToOwned;
 use alloc::string::String;
 use alloc::vec::Vec;
+use core::fmt;
 use core::mem::size_of;
 
 pub static TSOT_SOCKET_PATH: &'static str = "/var/run/quark/tsot-socket";
@@ -28,6 +29,80 @@ pub enum ErrCode {
     ECONNREFUSED = 111, //
 }
 
+// the 128 bits address of the dual stack pod network. the ipv4 address is carried as the ipv4
+// mapped ipv6 address (::ffff:a.b.c.d), so that one address type serves both families
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TsotAddr(pub [u8; 16]);
+
+impl From<u32> for TsotAddr {
+    fn from(addr: u32) -> Self {
+        return Self::FromIpv4(addr);
+    }
+}
+
+impl TsotAddr {
+    // the addr is the ipv4 address in host order, e.g. 0x0a010102 for 10.1.1.2
+    pub fn FromIpv4(addr: u32) -> Self {
+        let mut bytes = [0; 16];
+        bytes[10] = 0xff;
+        bytes[11] = 0xff;
+        bytes[12..].copy_from_slice(&addr.to_be_bytes());
+        return Self(bytes);
+    }
+
+    pub fn FromIpv6(addr: [u8; 16]) -> Self {
+        return Self(addr);
+    }
+
+    pub fn IsIpv4(&self) -> bool {
+        return self.0[..10] == [0; 10] && self.0[10] == 0xff && self.0[11] == 0xff;
+    }
+
+    pub fn Ipv4(&self) -> Option<u32> {
+        if !self.IsIpv4() {
+            return None;
+        }
+
+        let bytes = [self.0[12], self.0[13], self.0[14], self.0[15]];
+        return Some(u32::from_be_bytes(bytes));
+    }
+
+    // 0.0.0.0 or ::
+    pub fn IsUnspecified(&self) -> bool {
+        return self.Ipv4() == Some(0) || self.0 == [0; 16];
+    }
+
+    // 127.0.0.0/8 or ::1
+    pub fn IsLoopback(&self) -> bool {
+        match self.Ipv4() {
+            Some(addr) => return addr >> 24 == 127,
+            None => {
+                let mut loopback = [0; 16];
+                loopback[15] = 1;
+                return self.0 == loopback;
+            }
+        }
+    }
+}
+
+impl fmt::Display for TsotAddr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(addr) = self.Ipv4() {
+            let b = addr.to_be_bytes();
+            return write!(f, "{}.{}.{}.{}", b[0], b[1], b[2], b[3]);
+        }
+
+        for i in 0..8 {
+            if i > 0 {
+                write!(f, ":")?;
+            }
+            write!(f, "{:x}", u16::from_be_bytes([self.0[2 * i], self.0[2 * i + 1]]))?;
+        }
+        return Ok(());
+    }
+}
+
 #[repr(C)]
 #[derive(Debug)]
 pub struct TsotMessage {
@@ -70,6 +145,8 @@ pub enum TsotMsg {
     PodConnectReq(PodConnectReq),
     GatewayConnectReq(GatewayConnectReq),
     DnsReq(DnsReq),
+    CreateUdpSocketReq(CreateUdpSocketReq),
+    UdpBindReq(UdpBindReq),
 
     //////////////////////////////////////////////////////
     // from nodeagent to pod
@@ -80,6 +157,7 @@ pub enum TsotMsg {
     PodConnectResp(PodConnectResp),
     GatewayConnectResp(GatewayConnectResp),
     DnsResp(DnsResp),
+    CreateUdpSocketResp(CreateUdpSocketResp),
 }
 
 pub const BUFF_SIZE: usize = core::mem::size_of::<TsotMsg>();
@@ -147,7 +225,7 @@ pub enum ConnectReq {
 #[derive(Debug, Clone, Copy)]
 pub struct PodConnectReq {
     pub reqId: u32,
-    pub dstIp: u32,
+    pub dstIp: TsotAddr,
     pub dstPort: u16,
     pub srcPort: u16,
 }
@@ -158,7 +236,7 @@ pub struct PodConnectReq {
 pub struct GatewayConnectReq {
     pub reqId: u32,
     pub podNamespace: [u8; 64],
-    pub dstIp: u32,
+    pub dstIp: TsotAddr,
     pub dstPort: u16,
     pub srcPort: u16,
 }
@@ -171,6 +249,18 @@ pub struct DnsReq {
     pub names: [u8; 256],
 }
 
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CreateUdpSocketReq {}
+
+// bind the udp socket to the pod port, the port has been allocated by the pod
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct UdpBindReq {
+    pub socketId: u32,
+    pub port: u16,
+}
+
 impl DnsReq {
     pub fn GetDomains(&self) -> Vec<String> {
         let namesStr =
@@ -193,6 +283,9 @@ impl DnsReq {
 pub struct PodRegisterResp {
     // the pod's container IP addr
     pub containerIp: u32,
+    // the pod's ipv6 address allocated from the ipv6 pod cidr of the node, all zero when the pod
+    // network is ipv4 only
+    pub containerIpv6: [u8; 16],
     pub errorCode: u32,
 }
 
@@ -212,23 +305,11 @@ pub struct CreateSocketResp {}
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
 pub struct PeerConnectNotify {
-    pub peerIp: u32,
+    pub peerIp: TsotAddr,
     pub peerPort: u16,
     pub localPort: u16,
 }
 
-impl PeerConnectNotify {
-    pub fn PeerAddrBytes(&self) -> [u8; 4] {
-        let addr = &self.peerIp as *const _ as u64 as *const u8;
-        let bytes = unsafe { core::slice::from_raw_parts(addr, 4) };
-
-        let mut ret: [u8; 4] = [0; 4];
-
-        ret.copy_from_slice(bytes);
-        return ret;
-    }
-}
-
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
 pub struct PodConnectResp {
@@ -243,11 +324,143 @@ pub struct GatewayConnectResp {
     pub errorCode: i32,
 }
 
+// send with the pod side of a SOCK_SEQPACKET socket pair, the node agent keeps the other side.
+// each packet on the socket pair is one datagram: UdpDatagramHdr followed by the payload.
+// the socket is released by the node agent when the pod closes its side
 #[repr(C)]
 #[derive(Debug, Clone, Copy, Default)]
+pub struct CreateUdpSocketResp {
+    pub socketId: u32,
+}
+
+// from pod to node agent: the peer is the destination of the datagram
+// from node agent to pod: the peer is the source of the datagram
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UdpDatagramHdr {
+    pub peerIp: TsotAddr,
+    pub peerPort: u16,
+    pub localPort: u16,
+}
+
+pub const UDP_DATAGRAM_HDR_SIZE: usize = size_of::<UdpDatagramHdr>();
+pub const UDP_MAX_PAYLOAD_SIZE: usize = 65507;
+
+impl UdpDatagramHdr {
+    pub fn AsBytes(&self) -> &[u8] {
+        let addr = self as *const _ as u64 as *const u8;
+        return unsafe { core::slice::from_raw_parts(addr, UDP_DATAGRAM_HDR_SIZE) };
+    }
+
+    pub fn FromBytes(bytes: &[u8]) -> Self {
+        assert!(bytes.len() >= UDP_DATAGRAM_HDR_SIZE);
+        let addr = &bytes[0] as *const _ as u64;
+        let ret = unsafe { *(addr as *const Self) };
+        return ret;
+    }
+}
+
+// the result code of a dns request, same as the dns header RCODE
+pub const DNS_RCODE_NOERROR: u8 = 0;
+pub const DNS_RCODE_SERVFAIL: u8 = 2;
+pub const DNS_RCODE_NXDOMAIN: u8 = 3;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
 pub struct DnsResp {
     pub reqId: u16,
     // maxinum 4 request and response
     pub ips: [u32; 4],
+    // the AAAA answers of the requests, all zero for no ipv6 address. only the pods have AAAA
+    // answers, as tsot only routes the pod ipv6 addresses
+    pub ipv6s: [[u8; 16]; 4],
+    // DNS_RCODE_* of the requests
+    pub rcodes: [u8; 4],
+    // the time to live of the answers in seconds
+    pub ttls: [u32; 4],
+    // the canonical names of the requests separated by ':', empty for the canonical request name
+    pub cnameslen: u16,
+    pub cnames: [u8; 256],
     pub count: usize,
 }
+
+impl Default for DnsResp {
+    fn default() -> Self {
+        return Self {
+            reqId: 0,
+            ips: [0; 4],
+            ipv6s: [[0; 16]; 4],
+            rcodes: [DNS_RCODE_NOERROR; 4],
+            ttls: [0; 4],
+            cnameslen: 0,
+            cnames: [0; 256],
+            count: 0,
+        };
+    }
+}
+
+impl DnsResp {
+    pub fn SetCnames(&mut self, cnames: &[String]) -> bool {
+        let joined = cnames.join(":");
+        if joined.len() > self.cnames.len() {
+            return false;
+        }
+
+        self.cnames[0..joined.len()].copy_from_slice(joined.as_bytes());
+        self.cnameslen = joined.len() as u16;
+        return true;
+    }
+
+    pub fn GetCnames(&self) -> Vec<String> {
+        let namesStr = String::from_utf8_lossy(&self.cnames[0..self.cnameslen as usize]);
+        let mut cnames: Vec<String> = namesStr.split(":").map(|n| n.to_owned()).collect();
+        cnames.resize(self.count, String::new());
+        return cnames;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn TestGetCnames() {
+        let mut resp = DnsResp::default();
+        resp.count = 3;
+        let cnames = vec!["a.example.com".to_owned(), "".to_owned()];
+        assert!(resp.SetCnames(&cnames));
+        assert_eq!(resp.GetCnames(), vec!["a.example.com", "", ""]);
+
+        let long = vec!["a".repeat(200), "b".repeat(100)];
+        assert!(!resp.SetCnames(&long));
+    }
+
+    #[test]
+    fn TestTsotAddr() {
+        let addr = TsotAddr::FromIpv4(0x0a010102);
+        assert!(addr.IsIpv4());
+        assert_eq!(addr.Ipv4(), Some(0x0a010102));
+        assert_eq!(
+            addr.0,
+            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xff, 0xff, 10, 1, 1, 2]
+        );
+        assert_eq!(format!("{}", addr), "10.1.1.2");
+        assert!(!addr.IsLoopback());
+        assert!(TsotAddr::FromIpv4(0x7f000001).IsLoopback());
+        assert!(TsotAddr::FromIpv4(0).IsUnspecified());
+
+        let mut bytes = [0; 16];
+        bytes[0] = 0xfd;
+        bytes[3] = 0x10;
+        bytes[15] = 2;
+        let addr = TsotAddr::FromIpv6(bytes);
+        assert!(!addr.IsIpv4());
+        assert_eq!(addr.Ipv4(), None);
+        assert_eq!(format!("{}", addr), "fd00:10:0:0:0:0:0:2");
+        assert!(TsotAddr::default().IsUnspecified());
+
+        let mut loopback = [0; 16];
+        loopback[15] = 1;
+        assert!(TsotAddr::FromIpv6(loopback).IsLoopback());
+    }
+}
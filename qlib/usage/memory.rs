@@ -13,7 +13,13 @@
 // limitations under the License.
 
 use super::super::mutex::*;
+use alloc::collections::btree_map::BTreeMap;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::sync::Arc;
 use core::ops::Deref;
+use core::sync::atomic::AtomicU64;
+use core::sync::atomic::Ordering;
 //use core::sync::atomic::AtomicU64;
 //use core::sync::atomic::Ordering;
 
@@ -152,6 +158,143 @@ impl MemoryAccounting {
     }
 }
 
+// MemCgroupIntern tracks the resident memory charged to one container and
+// the memory limit enforced on it. A limit of 0 means unlimited.
+#[derive(Debug, Default)]
+pub struct MemCgroupIntern {
+    pub cid: String,
+    // the charge is propagated to the parent, i.e. the sandbox level accounting
+    pub parent: Option<MemCgroup>,
+    pub limit: AtomicU64,
+    pub usage: AtomicU64,
+    pub maxUsage: AtomicU64,
+    pub oomKills: AtomicU64,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct MemCgroup(Arc<MemCgroupIntern>);
+
+impl Deref for MemCgroup {
+    type Target = Arc<MemCgroupIntern>;
+
+    fn deref(&self) -> &Arc<MemCgroupIntern> {
+        &self.0
+    }
+}
+
+impl MemCgroup {
+    pub fn New(cid: &str, parent: Option<MemCgroup>) -> Self {
+        let intern = MemCgroupIntern {
+            cid: cid.to_string(),
+            parent: parent,
+            ..Default::default()
+        };
+
+        return Self(Arc::new(intern));
+    }
+
+    pub fn Charge(&self, val: u64) {
+        let usage = self.usage.fetch_add(val, Ordering::SeqCst) + val;
+        self.maxUsage.fetch_max(usage, Ordering::SeqCst);
+        if let Some(parent) = &self.parent {
+            parent.Charge(val);
+        }
+    }
+
+    pub fn Uncharge(&self, val: u64) {
+        // the rss accounting of the memory manager is not exact, don't underflow
+        let _ = self
+            .usage
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |usage| {
+                Some(usage.saturating_sub(val))
+            });
+        if let Some(parent) = &self.parent {
+            parent.Uncharge(val);
+        }
+    }
+
+    pub fn Usage(&self) -> u64 {
+        return self.usage.load(Ordering::SeqCst);
+    }
+
+    pub fn MaxUsage(&self) -> u64 {
+        return self.maxUsage.load(Ordering::SeqCst);
+    }
+
+    pub fn Limit(&self) -> u64 {
+        return self.limit.load(Ordering::SeqCst);
+    }
+
+    pub fn SetLimit(&self, limit: u64) {
+        self.limit.store(limit, Ordering::SeqCst);
+    }
+
+    pub fn OverLimit(&self) -> bool {
+        let limit = self.Limit();
+        return limit != 0 && self.Usage() > limit;
+    }
+
+    pub fn IncOOMKills(&self) {
+        self.oomKills.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub fn OOMKills(&self) -> u64 {
+        return self.oomKills.load(Ordering::SeqCst);
+    }
+}
+
+// MemCgroups is the per container memory accounting table, indexed by container id.
+// All the containers are charged to the root cgroup as well.
+pub struct MemCgroups {
+    pub root: MemCgroup,
+    pub cgroups: QMutex<BTreeMap<String, MemCgroup>>,
+}
+
+impl Deref for MemCgroups {
+    type Target = QMutex<BTreeMap<String, MemCgroup>>;
+
+    fn deref(&self) -> &QMutex<BTreeMap<String, MemCgroup>> {
+        &self.cgroups
+    }
+}
+
+impl MemCgroups {
+    pub fn New() -> Self {
+        return Self {
+            root: MemCgroup::New("", None),
+            cgroups: QMutex::new(BTreeMap::new()),
+        };
+    }
+
+    pub fn Root(&self) -> MemCgroup {
+        return self.root.clone();
+    }
+
+    pub fn GetOrCreate(&self, cid: &str) -> MemCgroup {
+        let mut map = self.lock();
+        match map.get(cid) {
+            Some(cg) => return cg.clone(),
+            None => (),
+        }
+
+        let cg = MemCgroup::New(cid, Some(self.Root()));
+        map.insert(cid.to_string(), cg.clone());
+        return cg;
+    }
+
+    pub fn Get(&self, cid: &str) -> Option<MemCgroup> {
+        return self.lock().get(cid).cloned();
+    }
+
+    pub fn SetLimit(&self, cid: &str, limit: u64) {
+        self.GetOrCreate(cid).SetLimit(limit);
+    }
+
+    pub fn Remove(&self, cid: &str) -> Option<MemCgroup> {
+        return self.lock().remove(cid);
+    }
+}
+
 // MinimumTotalMemoryBytes is the minimum reported total system memory.
 pub const MINIMUM_TOTAL_MEMORY_BYTES: u64 = 1 << 30; // 2GB
 
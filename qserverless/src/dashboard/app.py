@@ -13,7 +13,7 @@
 # limitations under the License.
 
 import os
-import psycopg2
+import datetime
 from flask import Flask, render_template, request, render_template_string
 import grpc
 import json
@@ -23,13 +23,6 @@ import qobjs_pb2_grpc
 
 app = Flask(__name__)
 
-def get_db_connection():
-    conn = psycopg2.connect(host='localhost',
-                            database='auditdb',
-                            user='audit_user',
-                            password='123456')
-    return conn
-
 def ReadFuncLog(namespace: str, funcId: str) -> str: 
     req = qobjs_pb2.ReadFuncLogReq (
         namespace = namespace,
@@ -41,6 +34,29 @@ def ReadFuncLog(namespace: str, funcId: str) -> str:
     res = stub.ReadFuncLog(req)
     return res.content
 
+def FormatTime(millis: int):
+    if millis == 0:
+        return None
+    return datetime.datetime.fromtimestamp(millis / 1000.0)
+
+def ReadFuncAudit(namespace: str, jobId: str): 
+    req = qobjs_pb2.ReadFuncAuditReq (
+        namespace = namespace,
+        jobId = jobId if jobId is not None else "",
+    )
+    
+    channel = grpc.insecure_channel("127.0.0.1:8890")
+    stub = qobjs_pb2_grpc.QMetaServiceStub(channel)
+    res = stub.ReadFuncAudit(req)
+    
+    audits = []
+    for a in res.audits:
+        audits.append((a.id, a.jobId, a.namespace, a.packageName, a.revision, a.funcName, 
+            a.callerFuncId, a.funcState, a.nodeId, 
+            FormatTime(a.createTime), FormatTime(a.assignedTime), FormatTime(a.finishTime)))
+    
+    return audits
+
 def listpackages(namespace: str): 
     req = qobjs_pb2.GetRequestMessage (
         obj_type = "package",
@@ -114,19 +130,9 @@ def getnode(name: str):
 
 @app.route('/')
 def index():
-    conn = get_db_connection()
-    cur = conn.cursor()
     namespace = request.args.get('namespace')
     jobid = request.args.get('jobid')
-    query = "SELECT * FROM FuncAudit where namespace='{}' order by createTime desc;".format(namespace)
-    if jobid is not None:
-        query = "SELECT * FROM FuncAudit where namespace='{}' and jobId = '{}' order by createTime desc;".format(namespace, jobid)
-    
-    cur.execute(query)
-    audits = cur.fetchall()
-    
-    cur.close()
-    conn.close()
+    audits = ReadFuncAudit(namespace, jobid)
     hosturl = request.host_url
     return render_template('index.html', audits=audits, hosturl=hosturl, namespace=namespace)
 
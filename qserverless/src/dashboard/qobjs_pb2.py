@@ -13,7 +13,7 @@ _sym_db = _symbol_database.Default()
 
 
 
-DESCRIPTOR = _descriptor_pool.Default().AddSerializedFile(b'\n\x0bqobjs.proto\x12\x05qmeta\"R\n\x0eReadFuncLogReq\x12\x11\n\tnamespace\x18\x01 \x01(\t\x12\x10\n\x08\x66uncName\x18\x02 \x01(\t\x12\x0e\n\x06offset\x18\x03 \x01(\x04\x12\x0b\n\x03len\x18\x04 \x01(\r\"1\n\x0fReadFuncLogResp\x12\r\n\x05\x65rror\x18\x01 \x01(\t\x12\x0f\n\x07\x63ontent\x18\x02 \x01(\t\":\n\tPutObjReq\x12\x11\n\tnamespace\x18\x01 \x01(\t\x12\x0c\n\x04name\x18\x02 \x01(\t\x12\x0c\n\x04\x64\x61ta\x18\x03 \x01(\x0c\"\x1b\n\nPutObjResp\x12\r\n\x05\x65rror\x18\x01 \x01(\t\"/\n\x0c\x44\x65leteObjReq\x12\x11\n\tnamespace\x18\x01 \x01(\t\x12\x0c\n\x04name\x18\x02 \x01(\t\"\x1e\n\rDeleteObjResp\x12\r\n\x05\x65rror\x18\x01 \x01(\t\"-\n\nReadObjReq\x12\x11\n\tnamespace\x18\x01 \x01(\t\x12\x0c\n\x04name\x18\x02 \x01(\t\"*\n\x0bReadObjResp\x12\r\n\x05\x65rror\x18\x01 \x01(\t\x12\x0c\n\x04\x64\x61ta\x18\x02 \x01(\x0c\"%\n\x07ObjMeta\x12\x0c\n\x04name\x18\x01 \x01(\t\x12\x0c\n\x04size\x18\x02 \x01(\x05\"/\n\nListObjReq\x12\x11\n\tnamespace\x18\x01 \x01(\t\x12\x0e\n\x06prefix\x18\x02 \x01(\t\":\n\x0bListObjResp\x12\r\n\x05\x65rror\x18\x01 \x01(\t\x12\x1c\n\x04objs\x18\x02 \x03(\x0b\x32\x0e.qmeta.ObjMeta\"\x17\n\x15VersionRequestMessage\")\n\x16VersionResponseMessage\x12\x0f\n\x07version\x18\x01 \x01(\t\"\x1e\n\x02KV\x12\x0b\n\x03key\x18\x01 \x01(\t\x12\x0b\n\x03val\x18\x02 \x01(\t\"\x80\x01\n\x06Object\x12\x0c\n\x04kind\x18\x01 \x01(\t\x12\x11\n\tnamespace\x18\x02 \x01(\t\x12\x0c\n\x04name\x18\x03 \x01(\t\x12\x19\n\x06labels\x18\x05 \x03(\x0b\x32\t.qmeta.KV\x12\x1e\n\x0b\x61nnotations\x18\x06 \x03(\x0b\x32\t.qmeta.KV\x12\x0c\n\x04\x64\x61ta\x18\x07 \x01(\t\"\x8f\x01\n\x03Obj\x12\x0c\n\x04kind\x18\x01 \x01(\t\x12\x11\n\tnamespace\x18\x02 \x01(\t\x12\x0c\n\x04name\x18\x03 \x01(\t\x12\x10\n\x08revision\x18\x04 \x01(\x03\x12\x19\n\x06labels\x18\x05 \x03(\x0b\x32\t.qmeta.KV\x12\x1e\n\x0b\x61nnotations\x18\x06 \x03(\x0b\x32\t.qmeta.KV\x12\x0c\n\x04\x64\x61ta\x18\x07 \x01(\t\"C\n\x11PutRequestMessage\x12\x12\n\nObjectType\x18\x01 \x01(\t\x12\x1a\n\x03obj\x18\x02 \x01(\x0b\x32\r.qmeta.Object\"5\n\x12PutResponseMessage\x12\r\n\x05\x65rror\x18\x01 \x01(\t\x12\x10\n\x08revision\x18\x02 \x01(\x03\"2\n\x0eResponseHeader\x12\r\n\x05\x65rror\x18\x01 \x01(\t\x12\x11\n\tserver_id\x18\x02 \x01(\x04\"A\n\x14\x43reateRequestMessage\x12\x10\n\x08obj_type\x18\x01 \x01(\t\x12\x17\n\x03obj\x18\x02 \x01(\x0b\x32\n.qmeta.Obj\"8\n\x15\x43reateResponseMessage\x12\r\n\x05\x65rror\x18\x01 \x01(\t\x12\x10\n\x08revision\x18\x02 \x01(\x03\"X\n\x11GetRequestMessage\x12\x10\n\x08obj_type\x18\x01 \x01(\t\x12\x11\n\tnamespace\x18\x02 \x01(\t\x12\x0c\n\x04name\x18\x03 \x01(\t\x12\x10\n\x08revision\x18\x04 \x01(\x03\"<\n\x12GetResponseMessage\x12\r\n\x05\x65rror\x18\x01 \x01(\t\x12\x17\n\x03obj\x18\x02 \x01(\x0b\x32\n.qmeta.Obj\"I\n\x14\x44\x65leteRequestMessage\x12\x10\n\x08obj_type\x18\x01 \x01(\t\x12\x11\n\tnamespace\x18\x02 \x01(\t\x12\x0c\n\x04name\x18\x03 \x01(\t\"8\n\x15\x44\x65leteResponseMessage\x12\r\n\x05\x65rror\x18\x01 \x01(\t\x12\x10\n\x08revision\x18\x02 \x01(\x03\"A\n\x14UpdateRequestMessage\x12\x10\n\x08obj_type\x18\x01 \x01(\t\x12\x17\n\x03obj\x18\x02 \x01(\x0b\x32\n.qmeta.Obj\"8\n\x15UpdateResponseMessage\x12\r\n\x05\x65rror\x18\x01 \x01(\t\x12\x10\n\x08revision\x18\x02 \x01(\x03\"{\n\x12ListRequestMessage\x12\x10\n\x08obj_type\x18\x01 \x01(\t\x12\x11\n\tnamespace\x18\x02 \x01(\t\x12\x10\n\x08revision\x18\x03 \x01(\x03\x12\x16\n\x0elabel_selector\x18\x04 \x01(\t\x12\x16\n\x0e\x66ield_selector\x18\x05 \x01(\t\"P\n\x13ListResponseMessage\x12\r\n\x05\x65rror\x18\x01 \x01(\t\x12\x10\n\x08revision\x18\x02 \x01(\x03\x12\x18\n\x04objs\x18\x03 \x03(\x0b\x32\n.qmeta.Obj\"|\n\x13WatchRequestMessage\x12\x10\n\x08obj_type\x18\x01 \x01(\t\x12\x11\n\tnamespace\x18\x02 \x01(\t\x12\x10\n\x08revision\x18\x03 \x01(\x03\x12\x16\n\x0elabel_selector\x18\x04 \x01(\t\x12\x16\n\x0e\x66ield_selector\x18\x05 \x01(\t\"5\n\x06WEvent\x12\x12\n\nevent_type\x18\x02 \x01(\x03\x12\x17\n\x03obj\x18\x03 \x01(\x0b\x32\n.qmeta.Obj2\xf7\x05\n\x0cQMetaService\x12H\n\x07Version\x12\x1c.qmeta.VersionRequestMessage\x1a\x1d.qmeta.VersionResponseMessage\"\x00\x12\x45\n\x06\x43reate\x12\x1b.qmeta.CreateRequestMessage\x1a\x1c.qmeta.CreateResponseMessage\"\x00\x12<\n\x03Get\x12\x18.qmeta.GetRequestMessage\x1a\x19.qmeta.GetResponseMessage\"\x00\x12\x45\n\x06\x44\x65lete\x12\x1b.qmeta.DeleteRequestMessage\x1a\x1c.qmeta.DeleteResponseMessage\"\x00\x12\x45\n\x06Update\x12\x1b.qmeta.UpdateRequestMessage\x1a\x1c.qmeta.UpdateResponseMessage\"\x00\x12?\n\x04List\x12\x19.qmeta.ListRequestMessage\x1a\x1a.qmeta.ListResponseMessage\"\x00\x12\x36\n\x05Watch\x12\x1a.qmeta.WatchRequestMessage\x1a\r.qmeta.WEvent\"\x00\x30\x01\x12/\n\x06PutObj\x12\x10.qmeta.PutObjReq\x1a\x11.qmeta.PutObjResp\"\x00\x12\x38\n\tDeleteObj\x12\x13.qmeta.DeleteObjReq\x1a\x14.qmeta.DeleteObjResp\"\x00\x12\x32\n\x07ReadObj\x12\x11.qmeta.ReadObjReq\x1a\x12.qmeta.ReadObjResp\"\x00\x12\x32\n\x07ListObj\x12\x11.qmeta.ListObjReq\x1a\x12.qmeta.ListObjResp\"\x00\x12>\n\x0bReadFuncLog\x12\x15.qmeta.ReadFuncLogReq\x1a\x16.qmeta.ReadFuncLogResp\"\x00\x62\x06proto3')
+DESCRIPTOR = _descriptor_pool.Default().AddSerializedFile(b'\n\x0bqobjs.proto\x12\x05qmeta\"R\n\x0eReadFuncLogReq\x12\x11\n\tnamespace\x18\x01 \x01(\t\x12\x10\n\x08\x66uncName\x18\x02 \x01(\t\x12\x0e\n\x06offset\x18\x03 \x01(\x04\x12\x0b\n\x03len\x18\x04 \x01(\r\"B\n\x0fReadFuncLogResp\x12\r\n\x05\x65rror\x18\x01 \x01(\t\x12\x0f\n\x07\x63ontent\x18\x02 \x01(\t\x12\x0f\n\x07readLen\x18\x03 \x01(\x04\"g\n\x10ReadFuncAuditReq\x12\x11\n\tnamespace\x18\x01 \x01(\t\x12\r\n\x05jobId\x18\x02 \x01(\t\x12\x11\n\tstartTime\x18\x03 \x01(\x03\x12\x0f\n\x07\x65ndTime\x18\x04 \x01(\x03\x12\r\n\x05limit\x18\x05 \x01(\r\"\xe9\x01\n\tFuncAudit\x12\n\n\x02id\x18\x01 \x01(\t\x12\r\n\x05jobId\x18\x02 \x01(\t\x12\x11\n\tnamespace\x18\x03 \x01(\t\x12\x13\n\x0bpackageName\x18\x04 \x01(\t\x12\x10\n\x08revision\x18\x05 \x01(\x03\x12\x10\n\x08\x66uncName\x18\x06 \x01(\t\x12\x14\n\x0c\x63\x61llerFuncId\x18\x07 \x01(\t\x12\x11\n\tfuncState\x18\x08 \x01(\t\x12\x0e\n\x06nodeId\x18\t \x01(\t\x12\x12\n\ncreateTime\x18\n \x01(\x03\x12\x14\n\x0c\x61ssignedTime\x18\x0b \x01(\x03\x12\x12\n\nfinishTime\x18\x0c \x01(\x03\"D\n\x11ReadFuncAuditResp\x12\r\n\x05\x65rror\x18\x01 \x01(\t\x12 \n\x06\x61udits\x18\x02 \x03(\x0b\x32\x10.qmeta.FuncAudit\":\n\tPutObjReq\x12\x11\n\tnamespace\x18\x01 \x01(\t\x12\x0c\n\x04name\x18\x02 \x01(\t\x12\x0c\n\x04\x64\x61ta\x18\x03 \x01(\x0c\"\x1b\n\nPutObjResp\x12\r\n\x05\x65rror\x18\x01 \x01(\t\"/\n\x0c\x44\x65leteObjReq\x12\x11\n\tnamespace\x18\x01 \x01(\t\x12\x0c\n\x04name\x18\x02 \x01(\t\"\x1e\n\rDeleteObjResp\x12\r\n\x05\x65rror\x18\x01 \x01(\t\"-\n\nReadObjReq\x12\x11\n\tnamespace\x18\x01 \x01(\t\x12\x0c\n\x04name\x18\x02 \x01(\t\"*\n\x0bReadObjResp\x12\r\n\x05\x65rror\x18\x01 \x01(\t\x12\x0c\n\x04\x64\x61ta\x18\x02 \x01(\x0c\"%\n\x07ObjMeta\x12\x0c\n\x04name\x18\x01 \x01(\t\x12\x0c\n\x04size\x18\x02 \x01(\x05\"/\n\nListObjReq\x12\x11\n\tnamespace\x18\x01 \x01(\t\x12\x0e\n\x06prefix\x18\x02 \x01(\t\":\n\x0bListObjResp\x12\r\n\x05\x65rror\x18\x01 \x01(\t\x12\x1c\n\x04objs\x18\x02 \x03(\x0b\x32\x0e.qmeta.ObjMeta\"\x17\n\x15VersionRequestMessage\")\n\x16VersionResponseMessage\x12\x0f\n\x07version\x18\x01 \x01(\t\"\x1e\n\x02KV\x12\x0b\n\x03key\x18\x01 \x01(\t\x12\x0b\n\x03val\x18\x02 \x01(\t\"\x80\x01\n\x06Object\x12\x0c\n\x04kind\x18\x01 \x01(\t\x12\x11\n\tnamespace\x18\x02 \x01(\t\x12\x0c\n\x04name\x18\x03 \x01(\t\x12\x19\n\x06labels\x18\x05 \x03(\x0b\x32\t.qmeta.KV\x12\x1e\n\x0b\x61nnotations\x18\x06 \x03(\x0b\x32\t.qmeta.KV\x12\x0c\n\x04\x64\x61ta\x18\x07 \x01(\t\"\x8f\x01\n\x03Obj\x12\x0c\n\x04kind\x18\x01 \x01(\t\x12\x11\n\tnamespace\x18\x02 \x01(\t\x12\x0c\n\x04name\x18\x03 \x01(\t\x12\x10\n\x08revision\x18\x04 \x01(\x03\x12\x19\n\x06labels\x18\x05 \x03(\x0b\x32\t.qmeta.KV\x12\x1e\n\x0b\x61nnotations\x18\x06 \x03(\x0b\x32\t.qmeta.KV\x12\x0c\n\x04\x64\x61ta\x18\x07 \x01(\t\"C\n\x11PutRequestMessage\x12\x12\n\nObjectType\x18\x01 \x01(\t\x12\x1a\n\x03obj\x18\x02 \x01(\x0b\x32\r.qmeta.Object\"5\n\x12PutResponseMessage\x12\r\n\x05\x65rror\x18\x01 \x01(\t\x12\x10\n\x08revision\x18\x02 \x01(\x03\"2\n\x0eResponseHeader\x12\r\n\x05\x65rror\x18\x01 \x01(\t\x12\x11\n\tserver_id\x18\x02 \x01(\x04\"A\n\x14\x43reateRequestMessage\x12\x10\n\x08obj_type\x18\x01 \x01(\t\x12\x17\n\x03obj\x18\x02 \x01(\x0b\x32\n.qmeta.Obj\"8\n\x15\x43reateResponseMessage\x12\r\n\x05\x65rror\x18\x01 \x01(\t\x12\x10\n\x08revision\x18\x02 \x01(\x03\"X\n\x11GetRequestMessage\x12\x10\n\x08obj_type\x18\x01 \x01(\t\x12\x11\n\tnamespace\x18\x02 \x01(\t\x12\x0c\n\x04name\x18\x03 \x01(\t\x12\x10\n\x08revision\x18\x04 \x01(\x03\"<\n\x12GetResponseMessage\x12\r\n\x05\x65rror\x18\x01 \x01(\t\x12\x17\n\x03obj\x18\x02 \x01(\x0b\x32\n.qmeta.Obj\"I\n\x14\x44\x65leteRequestMessage\x12\x10\n\x08obj_type\x18\x01 \x01(\t\x12\x11\n\tnamespace\x18\x02 \x01(\t\x12\x0c\n\x04name\x18\x03 \x01(\t\"8\n\x15\x44\x65leteResponseMessage\x12\r\n\x05\x65rror\x18\x01 \x01(\t\x12\x10\n\x08revision\x18\x02 \x01(\x03\"U\n\x14UpdateRequestMessage\x12\x10\n\x08obj_type\x18\x01 \x01(\t\x12\x17\n\x03obj\x18\x02 \x01(\x0b\x32\n.qmeta.Obj\x12\x12\n\nexpect_rev\x18\x03 \x01(\x03\"8\n\x15UpdateResponseMessage\x12\r\n\x05\x65rror\x18\x01 \x01(\t\x12\x10\n\x08revision\x18\x02 \x01(\x03\"{\n\x12ListRequestMessage\x12\x10\n\x08obj_type\x18\x01 \x01(\t\x12\x11\n\tnamespace\x18\x02 \x01(\t\x12\x10\n\x08revision\x18\x03 \x01(\x03\x12\x16\n\x0elabel_selector\x18\x04 \x01(\t\x12\x16\n\x0e\x66ield_selector\x18\x05 \x01(\t\"P\n\x13ListResponseMessage\x12\r\n\x05\x65rror\x18\x01 \x01(\t\x12\x10\n\x08revision\x18\x02 \x01(\x03\x12\x18\n\x04objs\x18\x03 \x03(\x0b\x32\n.qmeta.Obj\"|\n\x13WatchRequestMessage\x12\x10\n\x08obj_type\x18\x01 \x01(\t\x12\x11\n\tnamespace\x18\x02 \x01(\t\x12\x10\n\x08revision\x18\x03 \x01(\x03\x12\x16\n\x0elabel_selector\x18\x04 \x01(\t\x12\x16\n\x0e\x66ield_selector\x18\x05 \x01(\t\"5\n\x06WEvent\x12\x12\n\nevent_type\x18\x02 \x01(\x03\x12\x17\n\x03obj\x18\x03 \x01(\x0b\x32\n.qmeta.Obj2\xbd\x06\n\x0cQMetaService\x12H\n\x07Version\x12\x1c.qmeta.VersionRequestMessage\x1a\x1d.qmeta.VersionResponseMessage\"\x00\x12\x45\n\x06\x43reate\x12\x1b.qmeta.CreateRequestMessage\x1a\x1c.qmeta.CreateResponseMessage\"\x00\x12<\n\x03Get\x12\x18.qmeta.GetRequestMessage\x1a\x19.qmeta.GetResponseMessage\"\x00\x12\x45\n\x06\x44\x65lete\x12\x1b.qmeta.DeleteRequestMessage\x1a\x1c.qmeta.DeleteResponseMessage\"\x00\x12\x45\n\x06Update\x12\x1b.qmeta.UpdateRequestMessage\x1a\x1c.qmeta.UpdateResponseMessage\"\x00\x12?\n\x04List\x12\x19.qmeta.ListRequestMessage\x1a\x1a.qmeta.ListResponseMessage\"\x00\x12\x36\n\x05Watch\x12\x1a.qmeta.WatchRequestMessage\x1a\r.qmeta.WEvent\"\x00\x30\x01\x12/\n\x06PutObj\x12\x10.qmeta.PutObjReq\x1a\x11.qmeta.PutObjResp\"\x00\x12\x38\n\tDeleteObj\x12\x13.qmeta.DeleteObjReq\x1a\x14.qmeta.DeleteObjResp\"\x00\x12\x32\n\x07ReadObj\x12\x11.qmeta.ReadObjReq\x1a\x12.qmeta.ReadObjResp\"\x00\x12\x32\n\x07ListObj\x12\x11.qmeta.ListObjReq\x1a\x12.qmeta.ListObjResp\"\x00\x12>\n\x0bReadFuncLog\x12\x15.qmeta.ReadFuncLogReq\x1a\x16.qmeta.ReadFuncLogResp\"\x00\x12\x44\n\rReadFuncAudit\x12\x17.qmeta.ReadFuncAuditReq\x1a\x18.qmeta.ReadFuncAuditResp\"\x00\x62\x06proto3')
 
 _builder.BuildMessageAndEnumDescriptors(DESCRIPTOR, globals())
 _builder.BuildTopDescriptorsAndMessages(DESCRIPTOR, 'qobjs_pb2', globals())
@@ -23,65 +23,71 @@ if _descriptor._USE_C_DESCRIPTORS == False:
   _READFUNCLOGREQ._serialized_start=22
   _READFUNCLOGREQ._serialized_end=104
   _READFUNCLOGRESP._serialized_start=106
-  _READFUNCLOGRESP._serialized_end=155
-  _PUTOBJREQ._serialized_start=157
-  _PUTOBJREQ._serialized_end=215
-  _PUTOBJRESP._serialized_start=217
-  _PUTOBJRESP._serialized_end=244
-  _DELETEOBJREQ._serialized_start=246
-  _DELETEOBJREQ._serialized_end=293
-  _DELETEOBJRESP._serialized_start=295
-  _DELETEOBJRESP._serialized_end=325
-  _READOBJREQ._serialized_start=327
-  _READOBJREQ._serialized_end=372
-  _READOBJRESP._serialized_start=374
-  _READOBJRESP._serialized_end=416
-  _OBJMETA._serialized_start=418
-  _OBJMETA._serialized_end=455
-  _LISTOBJREQ._serialized_start=457
-  _LISTOBJREQ._serialized_end=504
-  _LISTOBJRESP._serialized_start=506
-  _LISTOBJRESP._serialized_end=564
-  _VERSIONREQUESTMESSAGE._serialized_start=566
-  _VERSIONREQUESTMESSAGE._serialized_end=589
-  _VERSIONRESPONSEMESSAGE._serialized_start=591
-  _VERSIONRESPONSEMESSAGE._serialized_end=632
-  _KV._serialized_start=634
-  _KV._serialized_end=664
-  _OBJECT._serialized_start=667
-  _OBJECT._serialized_end=795
-  _OBJ._serialized_start=798
-  _OBJ._serialized_end=941
-  _PUTREQUESTMESSAGE._serialized_start=943
-  _PUTREQUESTMESSAGE._serialized_end=1010
-  _PUTRESPONSEMESSAGE._serialized_start=1012
-  _PUTRESPONSEMESSAGE._serialized_end=1065
-  _RESPONSEHEADER._serialized_start=1067
-  _RESPONSEHEADER._serialized_end=1117
-  _CREATEREQUESTMESSAGE._serialized_start=1119
-  _CREATEREQUESTMESSAGE._serialized_end=1184
-  _CREATERESPONSEMESSAGE._serialized_start=1186
-  _CREATERESPONSEMESSAGE._serialized_end=1242
-  _GETREQUESTMESSAGE._serialized_start=1244
-  _GETREQUESTMESSAGE._serialized_end=1332
-  _GETRESPONSEMESSAGE._serialized_start=1334
-  _GETRESPONSEMESSAGE._serialized_end=1394
-  _DELETEREQUESTMESSAGE._serialized_start=1396
-  _DELETEREQUESTMESSAGE._serialized_end=1469
-  _DELETERESPONSEMESSAGE._serialized_start=1471
-  _DELETERESPONSEMESSAGE._serialized_end=1527
-  _UPDATEREQUESTMESSAGE._serialized_start=1529
-  _UPDATEREQUESTMESSAGE._serialized_end=1594
-  _UPDATERESPONSEMESSAGE._serialized_start=1596
-  _UPDATERESPONSEMESSAGE._serialized_end=1652
-  _LISTREQUESTMESSAGE._serialized_start=1654
-  _LISTREQUESTMESSAGE._serialized_end=1777
-  _LISTRESPONSEMESSAGE._serialized_start=1779
-  _LISTRESPONSEMESSAGE._serialized_end=1859
-  _WATCHREQUESTMESSAGE._serialized_start=1861
-  _WATCHREQUESTMESSAGE._serialized_end=1985
-  _WEVENT._serialized_start=1987
-  _WEVENT._serialized_end=2040
-  _QMETASERVICE._serialized_start=2043
-  _QMETASERVICE._serialized_end=2802
+  _READFUNCLOGRESP._serialized_end=172
+  _READFUNCAUDITREQ._serialized_start=174
+  _READFUNCAUDITREQ._serialized_end=277
+  _FUNCAUDIT._serialized_start=280
+  _FUNCAUDIT._serialized_end=513
+  _READFUNCAUDITRESP._serialized_start=515
+  _READFUNCAUDITRESP._serialized_end=583
+  _PUTOBJREQ._serialized_start=585
+  _PUTOBJREQ._serialized_end=643
+  _PUTOBJRESP._serialized_start=645
+  _PUTOBJRESP._serialized_end=672
+  _DELETEOBJREQ._serialized_start=674
+  _DELETEOBJREQ._serialized_end=721
+  _DELETEOBJRESP._serialized_start=723
+  _DELETEOBJRESP._serialized_end=753
+  _READOBJREQ._serialized_start=755
+  _READOBJREQ._serialized_end=800
+  _READOBJRESP._serialized_start=802
+  _READOBJRESP._serialized_end=844
+  _OBJMETA._serialized_start=846
+  _OBJMETA._serialized_end=883
+  _LISTOBJREQ._serialized_start=885
+  _LISTOBJREQ._serialized_end=932
+  _LISTOBJRESP._serialized_start=934
+  _LISTOBJRESP._serialized_end=992
+  _VERSIONREQUESTMESSAGE._serialized_start=994
+  _VERSIONREQUESTMESSAGE._serialized_end=1017
+  _VERSIONRESPONSEMESSAGE._serialized_start=1019
+  _VERSIONRESPONSEMESSAGE._serialized_end=1060
+  _KV._serialized_start=1062
+  _KV._serialized_end=1092
+  _OBJECT._serialized_start=1095
+  _OBJECT._serialized_end=1223
+  _OBJ._serialized_start=1226
+  _OBJ._serialized_end=1369
+  _PUTREQUESTMESSAGE._serialized_start=1371
+  _PUTREQUESTMESSAGE._serialized_end=1438
+  _PUTRESPONSEMESSAGE._serialized_start=1440
+  _PUTRESPONSEMESSAGE._serialized_end=1493
+  _RESPONSEHEADER._serialized_start=1495
+  _RESPONSEHEADER._serialized_end=1545
+  _CREATEREQUESTMESSAGE._serialized_start=1547
+  _CREATEREQUESTMESSAGE._serialized_end=1612
+  _CREATERESPONSEMESSAGE._serialized_start=1614
+  _CREATERESPONSEMESSAGE._serialized_end=1670
+  _GETREQUESTMESSAGE._serialized_start=1672
+  _GETREQUESTMESSAGE._serialized_end=1760
+  _GETRESPONSEMESSAGE._serialized_start=1762
+  _GETRESPONSEMESSAGE._serialized_end=1822
+  _DELETEREQUESTMESSAGE._serialized_start=1824
+  _DELETEREQUESTMESSAGE._serialized_end=1897
+  _DELETERESPONSEMESSAGE._serialized_start=1899
+  _DELETERESPONSEMESSAGE._serialized_end=1955
+  _UPDATEREQUESTMESSAGE._serialized_start=1957
+  _UPDATEREQUESTMESSAGE._serialized_end=2042
+  _UPDATERESPONSEMESSAGE._serialized_start=2044
+  _UPDATERESPONSEMESSAGE._serialized_end=2100
+  _LISTREQUESTMESSAGE._serialized_start=2102
+  _LISTREQUESTMESSAGE._serialized_end=2225
+  _LISTRESPONSEMESSAGE._serialized_start=2227
+  _LISTRESPONSEMESSAGE._serialized_end=2307
+  _WATCHREQUESTMESSAGE._serialized_start=2309
+  _WATCHREQUESTMESSAGE._serialized_end=2433
+  _WEVENT._serialized_start=2435
+  _WEVENT._serialized_end=2488
+  _QMETASERVICE._serialized_start=2491
+  _QMETASERVICE._serialized_end=3320
 # @@protoc_insertion_point(module_scope)
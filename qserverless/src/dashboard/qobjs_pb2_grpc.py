@@ -74,6 +74,11 @@ class QMetaServiceStub(object):
                 request_serializer=qobjs__pb2.ReadFuncLogReq.SerializeToString,
                 response_deserializer=qobjs__pb2.ReadFuncLogResp.FromString,
                 )
+        self.ReadFuncAudit = channel.unary_unary(
+                '/qmeta.QMetaService/ReadFuncAudit',
+                request_serializer=qobjs__pb2.ReadFuncAuditReq.SerializeToString,
+                response_deserializer=qobjs__pb2.ReadFuncAuditResp.FromString,
+                )
 
 
 class QMetaServiceServicer(object):
@@ -151,6 +156,12 @@ class QMetaServiceServicer(object):
         context.set_details('Method not implemented!')
         raise NotImplementedError('Method not implemented!')
 
+    def ReadFuncAudit(self, request, context):
+        """Missing associated documentation comment in .proto file."""
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
 
 def add_QMetaServiceServicer_to_server(servicer, server):
     rpc_method_handlers = {
@@ -214,6 +225,11 @@ def add_QMetaServiceServicer_to_server(servicer, server):
                     request_deserializer=qobjs__pb2.ReadFuncLogReq.FromString,
                     response_serializer=qobjs__pb2.ReadFuncLogResp.SerializeToString,
             ),
+            'ReadFuncAudit': grpc.unary_unary_rpc_method_handler(
+                    servicer.ReadFuncAudit,
+                    request_deserializer=qobjs__pb2.ReadFuncAuditReq.FromString,
+                    response_serializer=qobjs__pb2.ReadFuncAuditResp.SerializeToString,
+            ),
     }
     generic_handler = grpc.method_handlers_generic_handler(
             'qmeta.QMetaService', rpc_method_handlers)
@@ -427,3 +443,20 @@ class QMetaService(object):
             qobjs__pb2.ReadFuncLogResp.FromString,
             options, channel_credentials,
             insecure, call_credentials, compression, wait_for_ready, timeout, metadata)
+
+    @staticmethod
+    def ReadFuncAudit(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(request, target, '/qmeta.QMetaService/ReadFuncAudit',
+            qobjs__pb2.ReadFuncAuditReq.SerializeToString,
+            qobjs__pb2.ReadFuncAuditResp.FromString,
+            options, channel_credentials,
+            insecure, call_credentials, compression, wait_for_ready, timeout, metadata)
@@ -14,14 +14,26 @@
 
 use core::ops::Deref;
 use std::sync::{Arc, Mutex, Weak}; 
-use std::time::SystemTime;
-use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime};
+use std::collections::{BTreeMap, BTreeSet};
 
 use qobjs::common::*;
 use qobjs::types::*;
+use qobjs::func;
+use qobjs::system_types::FuncCallRecord;
+use qobjs::utility::SystemTimeProto;
 
+use crate::FUNC_CALL_STORE;
+use crate::WORKFLOW_MGR;
 use crate::package::*;
 
+// the backoff before requeueing the func call whose callee pod fails, doubled for each retry
+pub const RETRY_BACKOFF_BASE: Duration = Duration::from_millis(500);
+pub const RETRY_BACKOFF_MAX: Duration = Duration::from_secs(30);
+// a job finishes when it has no func call for the timeout, so that the sequential top level
+// func calls of a job share the blobs of the job
+pub const JOB_IDLE_TIMEOUT: Duration = Duration::from_secs(600);
+
 #[derive(Debug, Clone)]
 pub struct FuncCallId {
     pub packageId: PackageId,
@@ -72,6 +84,10 @@ pub enum FuncCallState {
     PendingCallerWithResult((SystemTime, FuncCallResult)),
     //
     Cancelling,
+    // the callee pod failed, waiting for the backoff to requeue the func call
+    Retrying(SystemTime),
+    // the func call is cancelled and the caller has got the error
+    Cancelled,
 }
 
 impl FuncCallState {
@@ -88,6 +104,20 @@ impl FuncCallState {
             _ => return false,
         }
     }
+
+    pub fn IsRetrying(&self) -> bool {
+        match self {
+            FuncCallState::Retrying(_) => return true,
+            _ => return false,
+        }
+    }
+
+    pub fn IsCancelled(&self) -> bool {
+        match self {
+            FuncCallState::Cancelled => return true,
+            _ => return false,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -108,6 +138,11 @@ pub struct FuncCallInner {
     pub priority: usize,
     pub createTime: SystemTime,
     pub callType: i32,
+
+    // the timeout in milliseconds from createTime, 0 means no deadline
+    pub timeout: u64,
+    pub maxRetry: u32,
+    pub retryCount: Mutex<u32>,
 }
 
 #[derive(Debug, Clone)]
@@ -122,6 +157,52 @@ impl Deref for FuncCall {
 }
 
 impl FuncCall {
+    pub fn NewFromGrpc(req: &func::FuncSvcCallReq, package: &Package, state: FuncCallState) -> Self {
+        let inner = FuncCallInner {
+            jobId: req.job_id.clone(),
+            id: req.id.clone(),
+            package: package.clone(),
+            funcName: req.func_name.clone(),
+            callerNodeId: req.caller_node_id.clone(),
+            callerFuncPodId: req.caller_pod_id.clone(),
+            calleeNodeId: Mutex::new(req.callee_node_id.clone()),
+            calleeFuncPodId: Mutex::new(req.callee_pod_id.clone()),
+            state: Mutex::new(state),
+            parameters: req.parameters.clone(),
+            callerFuncId: req.caller_func_id.clone(),
+            priority: req.priority as usize,
+            createTime: SystemTimeProto::FromTimestamp(req.createtime.as_ref().unwrap()).ToSystemTime(),
+            callType: req.call_type,
+            timeout: req.timeout,
+            maxRetry: req.max_retry,
+            retryCount: Mutex::new(req.retry_count),
+        };
+
+        return Self(Arc::new(inner));
+    }
+
+    pub fn ToGrpcType(&self) -> func::FuncSvcCallReq {
+        return func::FuncSvcCallReq {
+            job_id: self.jobId.clone(),
+            id: self.id.clone(),
+            namespace: self.package.Namespace(),
+            package_name: self.package.Name(),
+            func_name: self.funcName.clone(),
+            parameters: self.parameters.clone(),
+            caller_func_id: self.callerFuncId.clone(),
+            priority: self.priority as u64,
+            createtime: Some(SystemTimeProto::FromSystemTime(self.createTime).ToTimeStamp()),
+            caller_node_id: self.callerNodeId.clone(),
+            caller_pod_id: self.callerFuncPodId.clone(),
+            callee_node_id: self.calleeNodeId.lock().unwrap().clone(),
+            callee_pod_id: self.calleeFuncPodId.lock().unwrap().clone(),
+            call_type: self.callType,
+            timeout: self.timeout,
+            max_retry: self.maxRetry,
+            retry_count: *self.retryCount.lock().unwrap(),
+        };
+    }
+
     pub fn Downgrade(&self) -> FuncCallWeak {
         return FuncCallWeak(Arc::downgrade(&self.0));
     }
@@ -142,6 +223,27 @@ impl FuncCall {
         *self.state.lock().unwrap() = state;
     }
 
+    pub fn Deadline(&self) -> Option<SystemTime> {
+        if self.timeout == 0 {
+            return None;
+        }
+
+        return Some(self.createTime + Duration::from_millis(self.timeout));
+    }
+
+    // count a retry for the callee pod failure, return the backoff before requeue
+    // or None if the func call has run out of retries
+    pub fn NextRetry(&self) -> Option<Duration> {
+        let mut count = self.retryCount.lock().unwrap();
+        if *count >= self.maxRetry {
+            return None;
+        }
+
+        let backoff = RETRY_BACKOFF_BASE * 2u32.pow((*count).min(8));
+        *count += 1;
+        return Some(backoff.min(RETRY_BACKOFF_MAX));
+    }
+
     pub fn Match(&self, _other: &FuncCall) -> bool {
         // todo:
         return true;
@@ -180,6 +282,54 @@ pub struct FuncCallMgrInner {
     pub pendingCallerFuncCalls: BTreeMap<SystemTime, String>,
     // the function apppears in caller and shows running, but callee node is not online
     pub pendingCalleeFuncCalls: BTreeMap<SystemTime, String>,
+    // the func calls persisted by last func service instance which have not been reconciled
+    pub recovered: BTreeMap<String, FuncCallRecord>,
+    // the count of tracked func calls of each job
+    pub jobs: BTreeMap<String, usize>,
+    // the jobs which have no tracked func call and the time their last func call finished
+    pub idleJobs: BTreeMap<String, SystemTime>,
+    // caller func call id to the ids of the func calls started by it
+    pub callees: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl FuncCallMgrInner {
+    pub fn Track(&mut self, funcCall: &FuncCall) {
+        if self.funcCalls.insert(funcCall.id.clone(), funcCall.clone()).is_some() {
+            return;
+        }
+
+        if funcCall.jobId.len() > 0 {
+            *self.jobs.entry(funcCall.jobId.clone()).or_insert(0) += 1;
+            self.idleJobs.remove(&funcCall.jobId);
+        }
+
+        if funcCall.callerFuncId.len() > 0 {
+            self.callees.entry(funcCall.callerFuncId.clone()).or_default().insert(funcCall.id.clone());
+        }
+    }
+
+    pub fn Untrack(&mut self, funcCall: &FuncCall) {
+        if let Some(callees) = self.callees.get_mut(&funcCall.callerFuncId) {
+            callees.remove(&funcCall.id);
+            if callees.len() == 0 {
+                self.callees.remove(&funcCall.callerFuncId);
+            }
+        }
+
+        let count = match self.jobs.get_mut(&funcCall.jobId) {
+            None => return,
+            Some(c) => c,
+        };
+
+        *count -= 1;
+        if *count > 0 {
+            return;
+        }
+
+        // the job might start more func calls, it finishes after JOB_IDLE_TIMEOUT
+        self.jobs.remove(&funcCall.jobId);
+        self.idleJobs.insert(funcCall.jobId.clone(), SystemTime::now());
+    }
 }
 
 #[derive(Debug, Default)]
@@ -194,6 +344,157 @@ impl Deref for FuncCallMgr {
 }
 
 impl FuncCallMgr {
+    // load the func calls persisted before the func service restart
+    pub fn Recover(&self, records: Vec<FuncCallRecord>) {
+        let mut inner = self.lock().unwrap();
+        for r in records {
+            inner.recovered.insert(r.Id(), r);
+        }
+    }
+
+    pub fn TakeRecovered(&self, id: &str) -> Option<FuncCallRecord> {
+        return self.lock().unwrap().recovered.remove(id);
+    }
+
+    pub fn Get(&self, id: &str) -> Option<FuncCall> {
+        return self.lock().unwrap().funcCalls.get(id).cloned();
+    }
+
+    // all the func calls started by the func call directly or indirectly
+    pub fn Descendants(&self, id: &str) -> Vec<FuncCall> {
+        let inner = self.lock().unwrap();
+        let mut descendants = Vec::new();
+        let mut ids = vec![id.to_owned()];
+        while let Some(id) = ids.pop() {
+            let callees = match inner.callees.get(&id) {
+                None => continue,
+                Some(c) => c,
+            };
+
+            for calleeId in callees {
+                if let Some(funcCall) = inner.funcCalls.get(calleeId) {
+                    descendants.push(funcCall.clone());
+                }
+                ids.push(calleeId.clone());
+            }
+        }
+
+        return descendants;
+    }
+
+    // the func calls which have passed the deadline without result
+    pub fn Expired(&self) -> Vec<FuncCall> {
+        let now = SystemTime::now();
+        let inner = self.lock().unwrap();
+        let mut expired = Vec::new();
+        for funcCall in inner.funcCalls.values() {
+            match funcCall.Deadline() {
+                Some(deadline) if deadline <= now => (),
+                _ => continue,
+            }
+
+            match &*funcCall.state.lock().unwrap() {
+                FuncCallState::Cancelled => continue,
+                FuncCallState::PendingCallerWithResult(_) => continue,
+                _ => (),
+            }
+
+            expired.push(funcCall.clone());
+        }
+
+        return expired;
+    }
+
+    // the caller has got the result
+    pub fn Remove(&self, id: &str) {
+        let mut inner = self.lock().unwrap();
+        let funcCall = match inner.funcCalls.remove(id) {
+            None => return,
+            Some(c) => c,
+        };
+
+        let state = funcCall.state.lock().unwrap().clone();
+        match state {
+            FuncCallState::PendingCaller(time) => {
+                inner.pendingCallerFuncCalls.remove(&time);
+            }
+            FuncCallState::PendingCallee(time) => {
+                inner.pendingCalleeFuncCalls.remove(&time);
+            }
+            FuncCallState::PendingCallerWithResult((time, _)) => {
+                inner.pendingResultFuncCalls.remove(&time);
+            }
+            _ => (),
+        }
+
+        inner.Untrack(&funcCall);
+    }
+
+    // the jobs which have been idle for JOB_IDLE_TIMEOUT are finished and their blobs are removed
+    pub fn FinishIdleJobs(&self) {
+        let now = SystemTime::now();
+        let mut finished = Vec::new();
+        {
+            let mut inner = self.lock().unwrap();
+            for (jobId, idleTime) in &inner.idleJobs {
+                if now.duration_since(*idleTime).unwrap_or_default() >= JOB_IDLE_TIMEOUT {
+                    finished.push(jobId.clone());
+                }
+            }
+
+            for jobId in &finished {
+                inner.idleJobs.remove(jobId);
+            }
+        }
+
+        for jobId in finished {
+            // the workflow removes the blobs of its job when the workflow finishes
+            if WORKFLOW_MGR.HasJob(&jobId) {
+                continue;
+            }
+
+            if let Err(e) = FUNC_CALL_STORE.JobFinished(&jobId) {
+                error!("FuncCallMgr notify job {} finished fail with error {:?}", &jobId, e);
+            }
+        }
+    }
+
+    // the callee node has registered but doesn't report the func calls assigned to it,
+    // the func calls are lost and need to be requeued
+    pub fn TakeLostCallees(&self, nodeId: &str) -> Vec<FuncCall> {
+        let mut inner = self.lock().unwrap();
+        let mut lost = Vec::new();
+        let mut times = Vec::new();
+        for (time, id) in &inner.pendingCalleeFuncCalls {
+            if let Some(funcCall) = inner.funcCalls.get(id) {
+                if &*funcCall.calleeNodeId.lock().unwrap() == nodeId {
+                    times.push(*time);
+                    lost.push(funcCall.clone());
+                }
+            }
+        }
+
+        for time in times {
+            inner.pendingCalleeFuncCalls.remove(&time);
+        }
+
+        return lost;
+    }
+
+    // track a new func call until the caller gets the result
+    pub fn Add(&self, funcCall: &FuncCall) {
+        self.lock().unwrap().Track(funcCall);
+    }
+
+    // the callee has finished but the caller node is not online
+    pub fn PendingResult(&self, funcCall: &FuncCall, result: FuncCallResult) {
+        let mut inner = self.lock().unwrap();
+        let now = SystemTime::now();
+        funcCall.SetState(FuncCallState::PendingCallerWithResult((now, result)));
+        inner.pendingResultFuncCalls.insert(now, funcCall.id.clone());
+        inner.Track(funcCall);
+    }
+
     // when a node register in func service, register the list of funccall it is working on
     pub fn RegisteCallee(&self, funcCall: &FuncCall) -> Result<()> {
         let mut inner = self.lock().unwrap();
@@ -202,7 +503,7 @@ impl FuncCallMgr {
             None => {
                 funcCall.SetState(FuncCallState::PendingCaller(now));
                 inner.pendingCallerFuncCalls.insert(now, funcCall.id.clone());
-                inner.funcCalls.insert(funcCall.id.clone(), funcCall.clone());
+                inner.Track(funcCall);
                 return Ok(())
             }
             Some(curr) => {
@@ -240,7 +541,7 @@ impl FuncCallMgr {
             None => {
                 funcCall.SetState(FuncCallState::PendingCallee(now));
                 inner.pendingCalleeFuncCalls.insert(now, funcCall.id.clone());
-                inner.funcCalls.insert(funcCall.id.clone(), funcCall.clone());
+                inner.Track(funcCall);
                 return Ok(None)
             }
             Some(curr) => {
@@ -274,4 +575,66 @@ impl FuncCallMgr {
 
         return Ok(None)
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn NewFuncCall(id: &str, callerFuncId: &str, maxRetry: u32) -> FuncCall {
+        let req = func::FuncSvcCallReq {
+            id: id.to_string(),
+            caller_func_id: callerFuncId.to_string(),
+            createtime: Some(SystemTimeProto::FromSystemTime(SystemTime::now()).ToTimeStamp()),
+            max_retry: maxRetry,
+            ..Default::default()
+        };
+        let package = Package::New("ns1", "package1");
+        return FuncCall::NewFromGrpc(&req, &package, FuncCallState::Scheduled);
+    }
+
+    #[test]
+    fn TestNextRetry() {
+        let funcCall = NewFuncCall("call1", "", 3);
+        assert_eq!(funcCall.NextRetry(), Some(RETRY_BACKOFF_BASE));
+        assert_eq!(funcCall.NextRetry(), Some(RETRY_BACKOFF_BASE * 2));
+        assert_eq!(funcCall.NextRetry(), Some(RETRY_BACKOFF_BASE * 4));
+        // the retry budget is used up
+        assert_eq!(funcCall.NextRetry(), None);
+        assert_eq!(*funcCall.retryCount.lock().unwrap(), 3);
+
+        // no retry by default
+        let funcCall = NewFuncCall("call2", "", 0);
+        assert_eq!(funcCall.NextRetry(), None);
+
+        // the backoff is capped
+        let funcCall = NewFuncCall("call3", "", 20);
+        *funcCall.retryCount.lock().unwrap() = 10;
+        assert_eq!(funcCall.NextRetry(), Some(RETRY_BACKOFF_MAX));
+    }
+
+    #[test]
+    fn TestDescendants() {
+        let mgr = FuncCallMgr::default();
+        let calls = [
+            NewFuncCall("root", "", 0),
+            NewFuncCall("child1", "root", 0),
+            NewFuncCall("child2", "root", 0),
+            NewFuncCall("grandchild", "child1", 0),
+            NewFuncCall("other", "", 0),
+            NewFuncCall("otherchild", "other", 0),
+        ];
+        for funcCall in &calls {
+            mgr.lock().unwrap().Track(funcCall);
+        }
+
+        let ids = |calls: Vec<FuncCall>| -> BTreeSet<String> {
+            return calls.iter().map(|c| c.id.clone()).collect();
+        };
+
+        // the cancellation of root cascades to its whole call tree only
+        let expect: BTreeSet<String> = ["child1", "child2", "grandchild"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(ids(mgr.Descendants("root")), expect);
+        assert_eq!(ids(mgr.Descendants("child1")), ["grandchild".to_string()].into_iter().collect());
+        assert!(mgr.Descendants("grandchild").is_empty());
+    }
+}
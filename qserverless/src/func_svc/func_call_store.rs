@@ -0,0 +1,308 @@
+// Copyright (c) 2021 Quark Container Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+use core::ops::Deref;
+use tokio::sync::Notify;
+use tokio::sync::mpsc;
+
+use qobjs::cacher_client::CacherClient;
+use qobjs::selection_predicate::ListOption;
+use qobjs::selector::Selector;
+use qobjs::system_types::*;
+use qobjs::types::*;
+use qobjs::common::*;
+use qobjs::func;
+
+use crate::func_call::FuncCall;
+
+// a message failing to write is retried until it is persisted, the retry interval
+// doubles from 1 second up to the max
+pub const FUNC_CALL_STORE_MAX_BACKOFF: Duration = Duration::from_secs(32);
+
+// the interval before the retry after count failed writes
+pub fn RetryBackoff(count: usize) -> Duration {
+    let mut backoff = Duration::from_secs(1);
+    for _ in 1..count {
+        backoff = backoff * 2;
+        if backoff >= FUNC_CALL_STORE_MAX_BACKOFF {
+            return FUNC_CALL_STORE_MAX_BACKOFF;
+        }
+    }
+
+    return backoff;
+}
+
+// the list option selecting the blobs created by the job
+pub fn JobBlobListOption(jobId: &str) -> Result<ListOption> {
+    let mut opts = ListOption::default();
+    opts.predicate.label = Selector::Parse(&format!("{}={}", LabelBlobJobId, jobId))?;
+    return Ok(opts);
+}
+
+// the store object of the workflow, it carries the workflow status
+pub fn WorkflowObject(workflow: &Workflow) -> Result<DataObject> {
+    let data = serde_json::to_string(workflow)?;
+    return Ok(DataObject::NewFromK8sObj(QUARK_WORKFLOW, &workflow.metadata, data));
+}
+
+pub enum FuncCallStoreMsg {
+    Put(FuncCallRecord),
+    Remove(String, String), // namespace, funccall id
+    PutWorkflow(Workflow),
+    JobFinished(String), // job id
+}
+
+#[derive(Debug)]
+pub struct FuncCallStoreInner {
+    pub closeNotify: Arc<Notify>,
+    pub stop: AtomicBool,
+
+    // unbounded as the messages are sent with the func svc lock held, a full channel
+    // would lose the func call stage
+    pub agentChann: mpsc::UnboundedSender<FuncCallStoreMsg>,
+    pub qmetaSvcAddr: String,
+}
+
+// persist the func call state in the qobjs store so that the func call
+// can be recovered after the func service restarts
+#[derive(Debug, Clone)]
+pub struct FuncCallStore(Arc<FuncCallStoreInner>);
+
+impl Deref for FuncCallStore {
+    type Target = Arc<FuncCallStoreInner>;
+
+    fn deref(&self) -> &Arc<FuncCallStoreInner> {
+        &self.0
+    }
+}
+
+impl FuncCallStore {
+    pub fn New(qmetaSvcAddr: &str) -> Self {
+        let qmetaSvcAddr = &format!("http://{}", qmetaSvcAddr);
+        let (tx, rx) = mpsc::unbounded_channel();
+        let inner = FuncCallStoreInner {
+            closeNotify: Arc::new(Notify::new()),
+            stop: AtomicBool::new(false),
+            agentChann: tx,
+            qmetaSvcAddr: qmetaSvcAddr.to_string(),
+        };
+        let ret = FuncCallStore(Arc::new(inner));
+        let clone = ret.clone();
+        tokio::spawn(async move {
+            clone.Process(rx).await.unwrap();
+        });
+
+        return ret;
+    }
+
+    pub fn Close(&self){
+        self.closeNotify.notify_waiters();
+        self.stop.store(true, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    pub fn Send(&self, msg: FuncCallStoreMsg) -> Result<()> {
+        match self.agentChann.send(msg) {
+            Ok(()) => return Ok(()),
+            Err(_) => return Err(Error::MpscSendFail),
+        }
+    }
+
+    pub fn Put(&self, funcCall: &FuncCall, stage: FuncCallStage) -> Result<()> {
+        let record = FuncCallRecord::NewFromGrpc(&funcCall.ToGrpcType(), stage);
+        return self.Send(FuncCallStoreMsg::Put(record));
+    }
+
+    pub fn Done(&self, funcCall: &FuncCall, res: &func::FuncRes) -> Result<()> {
+        let mut record = FuncCallRecord::NewFromGrpc(&funcCall.ToGrpcType(), FuncCallStage::Done);
+        record.SetResult(res);
+        return self.Send(FuncCallStoreMsg::Put(record));
+    }
+
+    pub fn Remove(&self, namespace: &str, id: &str) -> Result<()> {
+        return self.Send(FuncCallStoreMsg::Remove(namespace.to_owned(), id.to_owned()));
+    }
+
+    // update the workflow status
+    pub fn PutWorkflow(&self, workflow: &Workflow) -> Result<()> {
+        return self.Send(FuncCallStoreMsg::PutWorkflow(workflow.clone()));
+    }
+
+    // remove the blobs which are created by the finished job
+    pub fn JobFinished(&self, jobId: &str) -> Result<()> {
+        return self.Send(FuncCallStoreMsg::JobFinished(jobId.to_owned()));
+    }
+
+    // load all the func calls persisted by last func service instance
+    pub async fn Load(&self) -> Result<Vec<FuncCallRecord>> {
+        let client = CacherClient::New(self.qmetaSvcAddr.clone()).await?;
+        let list = client.List(QUARK_FUNCCALL, "", &ListOption::default()).await?;
+        let mut records = Vec::new();
+        for obj in &list.objs {
+            match serde_json::from_str::<FuncCallRecord>(&obj.data) {
+                Ok(r) => records.push(r),
+                Err(e) => {
+                    error!("FuncCallStore::Load get invalid funccall {} with error {:?}", obj.Key(), e);
+                }
+            }
+        }
+
+        return Ok(records);
+    }
+
+    pub async fn ProcessMsg(&self, client: &CacherClient, msg: &FuncCallStoreMsg) -> Result<()> {
+        match msg {
+            FuncCallStoreMsg::Put(record) => {
+                let data = serde_json::to_string(record)?;
+                let dataObj = DataObject::NewFromK8sObj(QUARK_FUNCCALL, &record.metadata, data);
+                client.Update(QUARK_FUNCCALL, &dataObj).await?;
+            }
+            FuncCallStoreMsg::Remove(namespace, id) => {
+                // the record might not be persisted, e.g. the funccall fails before queued
+                if let Err(e) = client.Delete(QUARK_FUNCCALL, namespace, id).await {
+                    info!("FuncCallStore remove funccall {}/{} fail with error {:?}", namespace, id, e);
+                }
+            }
+            FuncCallStoreMsg::PutWorkflow(workflow) => {
+                let dataObj = WorkflowObject(workflow)?;
+                client.Update(QUARK_WORKFLOW, &dataObj).await?;
+            }
+            FuncCallStoreMsg::JobFinished(jobId) => {
+                let opts = JobBlobListOption(jobId)?;
+                let list = client.List(QUARK_BLOB, "", &opts).await?;
+                for obj in &list.objs {
+                    // the node agents remove the local copies when the blob location is deleted
+                    if let Err(e) = client.Delete(QUARK_BLOB, &obj.namespace, &obj.name).await {
+                        info!("FuncCallStore remove blob {} of job {} fail with error {:?}", obj.Key(), jobId, e);
+                    }
+                }
+            }
+        }
+
+        return Ok(())
+    }
+
+    pub async fn Process(&self, rx: mpsc::UnboundedReceiver<FuncCallStoreMsg>) -> Result<()> {
+        let mut rx = rx;
+        let closeNotify = self.closeNotify.clone();
+        let mut cacheClient;
+        // the message failed to write and its write count
+        let mut pending: Option<(FuncCallStoreMsg, usize)> = None;
+        loop {
+            loop {
+                match CacherClient::New(self.qmetaSvcAddr.clone()).await {
+                    Ok(c) => {
+                        cacheClient = c;
+                        break;
+                    }
+                    Err(e) => {
+                        error!("FuncCallStore can't connect to qmeta service with error {:?}", e);
+                    }
+                }
+
+                let duration = Duration::from_secs(1);
+                tokio::time::sleep(duration).await;
+            }
+
+            if let Some((msg, count)) = pending.take() {
+                if let Err(e) = self.ProcessMsg(&cacheClient, &msg).await {
+                    // the later messages wait behind it to keep the records in order
+                    error!("FuncCallStore write fail {} times with error {:?}", count + 1, e);
+                    pending = Some((msg, count + 1));
+                    tokio::select! {
+                        _ = closeNotify.notified() => {
+                            return Ok(());
+                        }
+                        _ = tokio::time::sleep(RetryBackoff(count + 1)) => ()
+                    }
+                    continue;
+                }
+            }
+
+            loop {
+                tokio::select! {
+                    _ = closeNotify.notified() => {
+                        return Ok(());
+                    }
+                    msg = rx.recv() => {
+                        let msg = match msg {
+                            None => {
+                                info!("funccall store finish");
+                                return Ok(());
+                            }
+                            Some(msg) => msg,
+                        };
+
+                        match self.ProcessMsg(&cacheClient, &msg).await {
+                            Ok(()) => (),
+                            Err(e) => {
+                                error!("FuncCallStore write fail with error {:?}", e);
+                                pending = Some((msg, 1));
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn TestRetryBackoff() {
+        assert_eq!(RetryBackoff(0), Duration::from_secs(1));
+        assert_eq!(RetryBackoff(1), Duration::from_secs(1));
+        assert_eq!(RetryBackoff(2), Duration::from_secs(2));
+        assert_eq!(RetryBackoff(4), Duration::from_secs(8));
+        assert_eq!(RetryBackoff(6), FUNC_CALL_STORE_MAX_BACKOFF);
+        // a message is never dropped, it keeps retrying at the max interval
+        assert_eq!(RetryBackoff(1000), FUNC_CALL_STORE_MAX_BACKOFF);
+    }
+
+    #[test]
+    fn TestJobBlobListOption() {
+        let opts = JobBlobListOption("job1").unwrap();
+        let blob = |jobId: &str| {
+            let location = BlobLocation::New("ns1", "blob1", 10, "", jobId, 0);
+            let data = serde_json::to_string(&location).unwrap();
+            return DataObject::NewFromK8sObj(QUARK_BLOB, &location.metadata, data);
+        };
+
+        assert!(opts.predicate.label.Match(&blob("job1").Labels()));
+        assert!(!opts.predicate.label.Match(&blob("job2").Labels()));
+        // the blobs created outside a job are never collected
+        assert!(!opts.predicate.label.Match(&blob("").Labels()));
+    }
+
+    #[test]
+    fn TestWorkflowObject() {
+        let mut workflow = Workflow::default();
+        workflow.metadata.namespace = Some("ns1".to_owned());
+        workflow.metadata.name = Some("wf1".to_owned());
+        workflow.status.jobId = "job1".to_owned();
+
+        let obj = WorkflowObject(&workflow).unwrap();
+        assert_eq!(obj.kind, QUARK_WORKFLOW);
+        assert_eq!(obj.namespace, "ns1");
+        assert_eq!(obj.name, "wf1");
+        let stored: Workflow = serde_json::from_str(&obj.data).unwrap();
+        assert_eq!(stored.status.jobId, "job1");
+    }
+}
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::sync::Mutex;
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
@@ -20,7 +21,6 @@ use std::time::SystemTime;
 use core::ops::Deref;
 use qobjs::audit::func_audit::FuncStateFail;
 //use qobjs::audit::func_audit::FuncStateSuccess;
-use qobjs::utility::SystemTimeProto;
 use tokio::sync::Mutex as TMutex;
 use tonic::Streaming;
 use tokio::sync::mpsc;
@@ -31,19 +31,25 @@ use tokio::task::JoinHandle;
 use qobjs::common::*;
 use qobjs::types::*;
 use qobjs::func;
+use qobjs::k8s;
+use qobjs::informer::EventHandler;
+use qobjs::store::ThreadSafeStore;
+use qobjs::system_types::FuncCallStage;
 use tokio::sync::Notify;
 
 use crate::AUDIT_AGENT;
-//use crate::FUNC_CALL_MGR;
+use crate::FUNC_CALL_MGR;
+use crate::FUNC_CALL_STORE;
 use crate::FUNC_NODE_MGR;
 use crate::FUNC_POD_MGR;
 use crate::FUNC_SVC_MGR;
 use crate::PACKAGE_MGR;
 use crate::func_call::FuncCall;
-use crate::func_call::FuncCallInner;
+use crate::func_call::FuncCallResult;
 use crate::func_call::FuncCallState;
 use crate::func_pod::*;
 use crate::message::FuncNodeMsg;
+use crate::package::Package;
 
 #[derive(Debug, Clone)]
 pub enum FuncNodeState {
@@ -66,6 +72,14 @@ pub struct FuncNodeInner {
 
     pub processorHandler: Option<JoinHandle<()>>,
     pub resource: Resource,
+
+    // resource occupied by the pods placed on the node
+    pub allocated: Resource,
+    // pods placed on the node by scheduler but not connected yet
+    pub creatingPods: BTreeMap<String, Package>,
+    // images cached by the node, as reported in the node status, plus the images of
+    // the packages placed on the node since the last report
+    pub images: BTreeSet<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -93,6 +107,9 @@ impl FuncNode {
             internMsgRx: Some(rx),
             processorHandler: None,
             resource: Resource::default(),
+            allocated: Resource::default(),
+            creatingPods: BTreeMap::new(),
+            images: BTreeSet::new(),
         };
 
         return Self(Arc::new(Mutex::new(inner)));
@@ -130,151 +147,206 @@ impl FuncNode {
     // 1. When the nodeagent reconnect to the FuncService, todo: need to handle this scenario
     // 2. When the Master FuncService die, the nodeagent sent FuncAgentRegisterReq to another FuncService
     pub fn OnNodeRegister(&self, regReq: func::FuncAgentRegisterReq) -> Result<()> {  
-        let mut inner = self.lock().unwrap();
-        
-        /* 
-        for funccall in regReq.callee_calls {
-            let packageId = PackageId {
-                namespace: funccall.namespace.clone(),
-                packageName: funccall.package_name.clone(),
-            };
-            let funcCallId = funccall.func_name.clone();
-
-            let package = match PACKAGE_MGR.Get(&packageId) {
-                Err(_) => {
-                    error!("StartProcess get invalid package {:?}", packageId);
-                    continue;
+        let resource = match &regReq.resource {
+            None => Resource::default(),
+            Some(r) => {
+                Resource {
+                    mem: r.mem as _,
+                    cpu: r.cpu as _,
                 }
-                Ok(p) => p,
-            };
+            }
+        };
 
-            let funcCallInner = FuncCallInner {
-                jobId: funccall.job_id.clone(),
-                id: funcCallId.clone(),
-                package: package.clone(),
-                funcName: funccall.func_name.clone(),
-
-                callerNodeId: funccall.caller_node_id.clone(),
-                callerFuncPodId: funccall.caller_pod_id.clone(),
-                calleeNodeId: Mutex::new(funccall.caller_node_id.clone()),
-                calleeFuncPodId: Mutex::new(funccall.caller_pod_id.clone()),
-                state: Mutex::new(FuncCallState::Cancelling),
-                parameters: funccall.parameters.clone(),
-                callerFuncId: funccall.caller_func_id.clone(),
-                priority: funccall.priority as usize,
-                createTime: SystemTimeProto::FromTimestamp(funccall.createtime.as_ref().unwrap()).ToSystemTime(),
+        self.lock().unwrap().resource = resource.clone();
+        FUNC_SVC_MGR.lock().unwrap().OnNodeJoin(resource)?;
+
+        let nodeName = self.NodeName();
+
+        // the func calls processing in the node
+        for funccall in &regReq.callee_calls {
+            let package = match Self::GetPackage(&funccall.namespace, &funccall.package_name) {
+                None => continue,
+                Some(p) => p,
             };
 
-            let funcCall = FuncCall(Arc::new(funcCallInner));
-            
+            FUNC_CALL_MGR.TakeRecovered(&funccall.id);
+            let funcCall = FuncCall::NewFromGrpc(funccall, &package, FuncCallState::Scheduled);
             FUNC_CALL_MGR.RegisteCallee(&funcCall)?;
+            FUNC_CALL_STORE.Put(&funcCall, FuncCallStage::Running)?;
         }
 
-        
+        // the func calls assigned to the node but not reported by the node are lost, requeue them
+        for funcCall in FUNC_CALL_MGR.TakeLostCallees(&nodeName) {
+            info!("node {} lost funccall {}, requeue it", &nodeName, &funcCall.id);
+            funcCall.SetState(FuncCallState::Cancelling);
+            FUNC_SVC_MGR.lock().unwrap().RequeueFuncCall(&funcCall)?;
+        }
 
-        for funccall in regReq.caller_calls {
-            let packageId = PackageId {
-                namespace: funccall.namespace.clone(),
-                packageName: funccall.package_name.clone(),
-            };
-            let funcCallId = funccall.func_name.clone();
+        for podStatus in &regReq.func_pods {
+            if self.lock().unwrap().funcPods.contains_key(&podStatus.func_pod_id) {
+                continue;
+            }
 
-            let package = match PACKAGE_MGR.Get(&packageId) {
-                Err(_) => {
-                    error!("StartProcess get invalid package {:?}", packageId);
-                    continue;
+            let package = if !podStatus.client_mode {
+                match Self::GetPackage(&podStatus.namespace, &podStatus.package_name) {
+                    None => continue,
+                    Some(p) => Some(p),
                 }
-                Ok(p) => p,
-            };
-
-            let funcCallInner = FuncCallInner {
-                jobId: funccall.job_id.clone(),
-                id: funcCallId.clone(),
-                package: package.clone(),
-                funcName: funccall.func_name.clone(),
-
-                callerNodeId: funccall.caller_node_id.clone(),
-                callerFuncPodId: funccall.caller_pod_id.clone(),
-                calleeNodeId: Mutex::new(funccall.caller_node_id.clone()),
-                calleeFuncPodId: Mutex::new(funccall.caller_pod_id.clone()),
-                state: Mutex::new(FuncCallState::Cancelling),
-                parameters: funccall.parameters.clone(),
-                callerFuncId: funccall.caller_func_id.clone(),
-                priority: funccall.priority as usize,
-                createTime: SystemTimeProto::FromTimestamp(funccall.createtime.as_ref().unwrap()).ToSystemTime(),
-            };
-
-            let funcCall = FuncCall(Arc::new(funcCallInner));
-            
-            // todo: handle the situation when the result is ready
-            let _ret = FUNC_CALL_MGR.RegisteCaller(&funcCall)?;
-        }
-        
-        for podStatus in regReq.func_pods {
-            let package  = if !podStatus.client_mode {
-                let packageId = PackageId {
-                    namespace: podStatus.namespace.clone(),
-                    packageName: podStatus.package_name.clone(),
-                };
-                let package = match PACKAGE_MGR.Get(&packageId) {
-                    Err(_) => {
-                        error!("StartProcess get invalid package {:?}", packageId);
-                        continue;
-                    }
-                    Ok(p) => p,
-                };
-                Some(package)
             } else {
                 None
             };
-            
+
             let state = match podStatus.state {
                 n if func::FuncPodState::Idle as i32 == n => FuncPodState::Idle(SystemTime::now()),
                 n if func::FuncPodState::Running as i32 == n => {
-                    FuncPodState::Running(podStatus.func_call_id.clone())
+                    match FUNC_CALL_MGR.Get(&podStatus.func_call_id) {
+                        None => {
+                            error!("OnNodeRegister funcpod {} runs unknown funccall {}", &podStatus.func_pod_id, &podStatus.func_call_id);
+                            FuncPodState::Idle(SystemTime::now())
+                        }
+                        Some(funcCall) => FuncPodState::Running(funcCall),
+                    }
+                }
+                _ => {
+                    error!("OnNodeRegister get unexpected podStatus.state {}", podStatus.state);
+                    continue;
                 }
-                _ => panic!("get unexpected podStatus.state {}", podStatus.state)
             };
 
-            let funcPodId = podStatus.func_pod_id.clone();
+            let podInner = FuncPodInner {
+                namespace: podStatus.namespace.clone(),
+                podName: podStatus.func_pod_id.clone(),
+                package: package.clone(),
+                node: self.clone(),
+                state: Mutex::new(state),
+                clientMode: podStatus.client_mode,
+                callerFuncCalls: Mutex::new(BTreeMap::new()),
+            };
+            let funcPod = FuncPod(Arc::new(podInner));
+            if let Some(package) = &package {
+                self.OnPlacedPodConnected(&podStatus.func_pod_id, package)?;
+            }
+            self.lock().unwrap().funcPods.insert(podStatus.func_pod_id.clone(), funcPod.clone());
+            FUNC_POD_MGR.Add(&funcPod);
+            if !funcPod.clientMode {
+                FUNC_SVC_MGR.lock().unwrap().OnPodRecovered(&funcPod)?;
+            }
+        }
 
-            match inner.funcPods.get(&funcPodId) {
-                None => {
-                    let podInner = FuncPodInner {
-                        namespace: podStatus.namespace.clone(),
-                        podName: podStatus.func_pod_id.clone(),
-                        package: package,
-                        node: self.clone(),
-                        state: Mutex::new(state),
-                        clientMode: podStatus.client_mode,
-                        callerFuncCalls: Mutex::new(BTreeMap::new()),
-                    };
-                    let funcPod = FuncPod(Arc::new(podInner));
-                    inner.funcPods.insert(funcPodId, funcPod);
+        // the func calls sent from the node which are waiting for result
+        for funccall in &regReq.caller_calls {
+            self.OnCallerCallRegister(funccall)?;
+        }
 
-                }
-                Some(funcPod) => {
-                    *funcPod.state.lock().unwrap() = state;
-                }
+        FUNC_SVC_MGR.lock().unwrap().TryCreatePod()?;
+        return Ok(())
+    }
+
+    pub fn GetPackage(namespace: &str, packageName: &str) -> Option<Package> {
+        let packageId = PackageId {
+            namespace: namespace.to_owned(),
+            packageName: packageName.to_owned(),
+        };
+
+        match PACKAGE_MGR.Get(&packageId) {
+            Err(_) => {
+                error!("FuncNode get invalid package {:?}", packageId);
+                return None;
             }
+            Ok(p) => return Some(p),
         }
+    }
 
-        */
-        let resource = match &regReq.resource {
-            None => Resource::default(),
-            Some(r) => {
-                Resource {
-                    mem: r.mem as _,
-                    cpu: r.cpu as _,
+    // reconcile a func call reported by the caller node with the persisted state
+    pub fn OnCallerCallRegister(&self, funccall: &func::FuncSvcCallReq) -> Result<()> {
+        let record = FUNC_CALL_MGR.TakeRecovered(&funccall.id);
+        if let Some(record) = &record {
+            if let Some(res) = record.Result() {
+                // the callee has finished before the func service restart
+                let resp = func::FuncSvcCallResp {
+                    id: funccall.id.clone(),
+                    res: Some(res),
+                    caller_node_id: funccall.caller_node_id.clone(),
+                    caller_pod_id: funccall.caller_pod_id.clone(),
+                    callee_node_id: record.status.calleeNodeId.clone(),
+                    callee_pod_id: record.status.calleePodId.clone(),
+                };
+                return self.Send(FuncNodeMsg::FuncCallResp(resp));
+            }
+        }
+
+        let package = match Self::GetPackage(&funccall.namespace, &funccall.package_name) {
+            None => return Ok(()),
+            Some(p) => p,
+        };
+
+        let funcCall = FuncCall::NewFromGrpc(funccall, &package, FuncCallState::Scheduling(SystemTime::now()));
+        let needQueue = if FUNC_CALL_MGR.Get(&funccall.id).is_some() {
+            // the callee node has reported the func call
+            false
+        } else {
+            let (stage, calleeNodeId) = match &record {
+                Some(r) => (r.status.stage, r.status.calleeNodeId.clone()),
+                None => (FuncCallStage::Assigned, funccall.callee_node_id.clone()),
+            };
+
+            match stage {
+                FuncCallStage::Queued => true,
+                _ => {
+                    match FUNC_NODE_MGR.Get(&calleeNodeId) {
+                        // the callee node has registered without the func call
+                        Ok(node) => node.IsRunning(),
+                        // wait for the callee node
+                        Err(_) => calleeNodeId.len() == 0,
+                    }
                 }
             }
         };
 
-        inner.resource = resource.clone();
-        FUNC_SVC_MGR.lock().unwrap().OnNodeJoin(resource)?;
-        
-        FUNC_SVC_MGR.lock().unwrap().TryCreatePod()?;
-        return Ok(())
+        if needQueue {
+            *funcCall.calleeNodeId.lock().unwrap() = String::new();
+            *funcCall.calleeFuncPodId.lock().unwrap() = String::new();
+            self.AddCallerFuncCall(&funcCall)?;
+            FUNC_CALL_MGR.Add(&funcCall);
+            FUNC_CALL_STORE.Put(&funcCall, FuncCallStage::Queued)?;
+            return FUNC_SVC_MGR.lock().unwrap().OnNewFuncCall(&funcCall);
+        }
+
+        if let Some(result) = FUNC_CALL_MGR.RegisteCaller(&funcCall)? {
+            let funcRes = match result {
+                FuncCallResult::Ok(resp) => FuncRes::NewResponse(resp),
+                FuncCallResult::Err(e) => FuncRes::NewError(FuncErrSource::System, e),
+            };
+            let resp = func::FuncSvcCallResp {
+                id: funccall.id.clone(),
+                res: Some(funcRes.ToGrpc()),
+                caller_node_id: funccall.caller_node_id.clone(),
+                caller_pod_id: funccall.caller_pod_id.clone(),
+                callee_node_id: funccall.callee_node_id.clone(),
+                callee_pod_id: funccall.callee_pod_id.clone(),
+            };
+            return self.Send(FuncNodeMsg::FuncCallResp(resp));
+        }
+
+        let funcCall = FUNC_CALL_MGR.Get(&funccall.id).unwrap_or(funcCall);
+        return self.AddCallerFuncCall(&funcCall);
+    }
+
+    pub fn AddCallerFuncCall(&self, funcCall: &FuncCall) -> Result<()> {
+        if funcCall.callerFuncPodId.len() == 0 {
+            // it is a gateway funccall
+            self.lock().unwrap().callerFuncCalls.insert(funcCall.id.clone(), funcCall.clone());
+            return Ok(())
+        }
+
+        let pod = self.lock().unwrap().funcPods.get(&funcCall.callerFuncPodId).cloned();
+        match pod {
+            None => {
+                error!("funcNode {} miss callerfuncPod {:?}", self.NodeName(), &funcCall.callerFuncPodId);
+                return Ok(())
+            }
+            Some(pod) => return pod.OnFuncSvcCallReq(funcCall),
+        }
     }
 
     pub async fn CreateProcessor(&self, regReq: func::FuncAgentRegisterReq, rx: Streaming<func::FuncSvcMsg>, tx: mpsc::Sender<SResult<func::FuncSvcMsg, Status>>) -> Result<()> {
@@ -341,24 +413,9 @@ impl FuncNode {
                 return self.Send(FuncNodeMsg::FuncCallResp(resp));
             }
         };
-        let inner = FuncCallInner {
-            jobId: req.job_id.clone(),
-            id: req.id.clone(),
-            package: package,
-            funcName: req.func_name.clone(),
-            callerNodeId: req.caller_node_id.clone(),
-            callerFuncPodId: req.caller_pod_id.clone(),
-            calleeNodeId: Mutex::new(req.callee_node_id.clone()),
-            calleeFuncPodId: Mutex::new(req.callee_pod_id.clone()),
-            state: Mutex::new(FuncCallState::Scheduling(SystemTime::now())),
-            parameters: req.parameters.clone(),
-            callerFuncId: req.caller_func_id.clone(),
-            priority: req.priority as usize,
-            createTime: SystemTimeProto::FromTimestamp(&req.createtime.as_ref().unwrap()).ToSystemTime(),
-            callType: req.call_type,
-        };
-
-        let funcCall = FuncCall(Arc::new(inner));
+        let funcCall = FuncCall::NewFromGrpc(&req, &package, FuncCallState::Scheduling(SystemTime::now()));
+        FUNC_CALL_MGR.Add(&funcCall);
+        FUNC_CALL_STORE.Put(&funcCall, FuncCallStage::Queued)?;
         if req.caller_pod_id.len() == 0 {
             // it is a gateway funccall
             self.lock().unwrap().callerFuncCalls.insert(req.id.clone(), funcCall.clone());
@@ -458,6 +515,9 @@ impl FuncNode {
         };
 
         let funcPod = FuncPod(Arc::new(funcPodInner));
+        if let Some(package) = &package {
+            self.OnPlacedPodConnected(&req.func_pod_id, package)?;
+        }
         self.lock().unwrap().funcPods.insert(req.func_pod_id.clone(), funcPod.clone());
         FUNC_POD_MGR.Add(&funcPod);
         if !funcPod.clientMode {
@@ -487,6 +547,13 @@ impl FuncNode {
             }
         }
 
+        {
+            // the creating pods will be placed on another node by node manager
+            let mut inner = self.lock().unwrap();
+            inner.creatingPods.clear();
+            inner.allocated = Resource::default();
+        }
+
         let resource = self.lock().unwrap().resource.clone();
         FUNC_SVC_MGR.lock().unwrap().OnNodeLeave(resource)?;
         
@@ -503,6 +570,9 @@ impl FuncNode {
             Some(pod) => pod
         };
         FUNC_POD_MGR.Remove(funcPodId)?;
+        if let Some(package) = &funcPod.package {
+            self.FreeAllocated(&package.ReqResource());
+        }
                 
         funcPod.OnFuncPodDisconnReq()?;
         
@@ -577,22 +647,7 @@ impl FuncNode {
 
     // get funccall req from another func node
     pub fn OnFuncCall(&self, call: FuncCall, tx: &mpsc::Sender<SResult<func::FuncSvcMsg, Status>>) -> Result<()> {
-        let req = func::FuncSvcCallReq {
-            job_id: call.jobId.clone(),
-            id: call.id.clone(),
-            namespace: call.package.Namespace(),
-            package_name: call.package.Name(),
-            func_name: call.funcName.clone(),
-            parameters: call.parameters.clone(),
-            caller_func_id: call.callerFuncId.clone(),
-            priority: call.priority as u64,
-            createtime: Some(SystemTimeProto::FromSystemTime(call.createTime).ToTimeStamp()),
-            caller_node_id: call.callerNodeId.clone(),
-            caller_pod_id: call.callerFuncPodId.clone(),
-            callee_node_id: call.calleeNodeId.lock().unwrap().clone(),
-            callee_pod_id: call.calleeFuncPodId.lock().unwrap().clone(),
-            call_type: call.callType,
-        };
+        let req = call.ToGrpcType();
 
         self.SendToNodeAgent(func::FuncSvcMsg {
             event_body: Some(func::func_svc_msg::EventBody::FuncSvcCallReq(req))
@@ -603,6 +658,12 @@ impl FuncNode {
 
     // get funccall resp from another func node
     pub fn OnFuncCallResp(&self, resp: func::FuncSvcCallResp, tx: &mpsc::Sender<SResult<func::FuncSvcMsg, Status>>) -> Result<()> {
+        // the caller gets the result, the func call is done
+        if let Some(funcCall) = FUNC_CALL_MGR.Get(&resp.id) {
+            FUNC_CALL_STORE.Remove(&funcCall.package.Namespace(), &resp.id)?;
+            FUNC_CALL_MGR.Remove(&resp.id);
+        }
+
         if resp.caller_pod_id.len() != 0 {
             let pod = match FUNC_POD_MGR.Get(&resp.caller_pod_id) {
                 Err(Error::ENOENT(_)) => {
@@ -762,6 +823,182 @@ impl FuncNode {
     pub fn AddPod(&self, _pod: &FuncPod) -> Result<()> {
         unimplemented!();
     }
+
+    // the resource which is not allocated to pods placed on the node
+    pub fn FreeResource(&self) -> Resource {
+        let inner = self.lock().unwrap();
+        if !inner.resource.Fullfil(&inner.allocated) {
+            return Resource::default();
+        }
+        return inner.resource - inner.allocated;
+    }
+
+    // the number of pods of the package placed on the node, include the creating pods
+    pub fn PackagePodCount(&self, packageId: &PackageId) -> usize {
+        // collect the packages first, the package lock can't be taken with node lock
+        let mut packages : Vec<Package> = Vec::new();
+        {
+            let inner = self.lock().unwrap();
+            for (_, pod) in &inner.funcPods {
+                if let Some(p) = &pod.package {
+                    packages.push(p.clone());
+                }
+            }
+            packages.extend(inner.creatingPods.values().cloned());
+        }
+
+        let mut count = 0;
+        for p in &packages {
+            if &p.PackageId() == packageId {
+                count += 1;
+            }
+        }
+
+        return count;
+    }
+
+    // whether all the images of the package has been pulled by the node
+    pub fn HasImages(&self, images: &[String]) -> bool {
+        let inner = self.lock().unwrap();
+        if images.len() == 0 {
+            return false;
+        }
+
+        for image in images {
+            if !inner.images.contains(image) {
+                return false;
+            }
+        }
+
+        return true;
+    }
+
+    // the node agent reports its cached images in the node status, the report replaces
+    // the placed images as the gc may have removed some of them
+    pub fn SetReportedImages(&self, images: BTreeSet<String>) {
+        self.lock().unwrap().images = images;
+    }
+
+    // reserve resource for a pod which the scheduler places on the node
+    pub fn ReservePod(&self, podName: &str, package: &Package) {
+        let req = package.ReqResource();
+        let images = PackageImages(package);
+        let mut inner = self.lock().unwrap();
+        inner.allocated = inner.allocated + req;
+        inner.creatingPods.insert(podName.to_owned(), package.clone());
+        for image in images {
+            inner.images.insert(image);
+        }
+    }
+
+    // release the reservation when the pod creation fails
+    pub fn ReleasePod(&self, podName: &str) -> bool {
+        let package = match self.lock().unwrap().creatingPods.remove(podName) {
+            None => return false,
+            Some(p) => p,
+        };
+
+        self.FreeAllocated(&package.ReqResource());
+        return true;
+    }
+
+    pub fn FreeAllocated(&self, req: &Resource) {
+        let mut inner = self.lock().unwrap();
+        if inner.allocated.Fullfil(req) {
+            inner.allocated = inner.allocated - *req;
+        } else {
+            inner.allocated = Resource::default();
+        }
+    }
+
+    // a placed pod connects from the node. If the node manager started it on another node,
+    // move the reservation to the node
+    pub fn OnPlacedPodConnected(&self, podName: &str, package: &Package) -> Result<()> {
+        if self.lock().unwrap().creatingPods.remove(podName).is_some() {
+            return Ok(())
+        }
+
+        let nodeName = self.NodeName();
+        let nodes : Vec<FuncNode> = FUNC_NODE_MGR.nodes.lock().unwrap().values().cloned().collect();
+        for node in nodes {
+            if node.NodeName() != nodeName && node.ReleasePod(podName) {
+                info!("funcpod {} is placed on node {} but started on node {}", podName, node.NodeName(), &nodeName);
+                break;
+            }
+        }
+
+        let req = package.ReqResource();
+        let images = PackageImages(package);
+        let mut inner = self.lock().unwrap();
+        inner.allocated = inner.allocated + req;
+        for image in images {
+            inner.images.insert(image);
+        }
+
+        return Ok(())
+    }
+}
+
+pub fn PackageImages(package: &Package) -> Vec<String> {
+    let mut images = Vec::new();
+    for container in &package.PodSpec().containers {
+        if let Some(image) = &container.image {
+            images.push(DefaultImageTag(image));
+        }
+    }
+
+    return images;
+}
+
+// the node agent reports the image names with tag, e.g. "nginx" is reported as "nginx:latest"
+pub fn DefaultImageTag(image: &str) -> String {
+    let name = match image.rsplit_once('/') {
+        None => image,
+        Some((_, name)) => name,
+    };
+
+    if name.contains(':') || name.contains('@') {
+        return image.to_owned();
+    }
+
+    return format!("{}:latest", image);
+}
+
+// update the cached images of the func nodes from the node status reported by the node agents
+#[derive(Debug, Clone)]
+pub struct FuncNodeStatusHandler {}
+
+impl EventHandler for FuncNodeStatusHandler {
+    fn handle(&self, _store: &ThreadSafeStore, event: &DeltaEvent) {
+        match &event.type_ {
+            EventType::Added | EventType::Modified => (),
+            _ => return,
+        }
+
+        let node : k8s::Node = match serde_json::from_str(&event.obj.data) {
+            Err(e) => {
+                error!("FuncNodeStatusHandler parse node fail with error {:?}", e);
+                return;
+            }
+            Ok(n) => n,
+        };
+
+        let nodeName = node.metadata.name.as_deref().unwrap_or("");
+        let funcNode = match FUNC_NODE_MGR.Get(nodeName) {
+            Err(_) => return, // the node agent has not registered yet
+            Ok(n) => n,
+        };
+
+        let mut images = BTreeSet::new();
+        let reported = node.status.as_ref().and_then(|s| s.images.as_ref());
+        for image in reported.into_iter().flatten() {
+            for name in image.names.iter().flatten() {
+                images.insert(DefaultImageTag(name));
+            }
+        }
+
+        funcNode.SetReportedImages(images);
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -794,4 +1031,17 @@ impl FuncNodeMgr {
     pub fn Insert(&self, nodeName: &str, node: &FuncNode) {
         self.nodes.lock().unwrap().insert(nodeName.to_owned(), node.clone());
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn TestDefaultImageTag() {
+        assert_eq!(DefaultImageTag("nginx"), "nginx:latest");
+        assert_eq!(DefaultImageTag("nginx:1.25"), "nginx:1.25");
+        assert_eq!(DefaultImageTag("localhost:5000/app"), "localhost:5000/app:latest");
+        assert_eq!(DefaultImageTag("docker.io/library/app:v1"), "docker.io/library/app:v1");
+        assert_eq!(DefaultImageTag("app@sha256:abcd"), "app@sha256:abcd");
+    }
+}
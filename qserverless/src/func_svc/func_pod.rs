@@ -23,14 +23,20 @@ use qobjs::audit::func_audit::FuncStateSuccess;
 use qobjs::common::*;
 use qobjs::types::*;
 use qobjs::func;
+use qobjs::system_types::FuncCallStage;
 
 use crate::AUDIT_AGENT;
+use crate::FUNC_CALL_MGR;
+use crate::FUNC_CALL_STORE;
 use crate::FUNC_NODE_MGR;
 use crate::FUNC_SVC_MGR;
+use crate::WORKFLOW_MGR;
 use crate::func_call::FuncCall;
+use crate::func_call::FuncCallResult;
 use crate::package::*;
 use crate::func_node::*;
 use crate::message::FuncNodeMsg;
+use crate::workflow::WORKFLOW_CALLER_NODE;
 
 #[derive(Debug, Clone)]
 pub struct FuncPodId {
@@ -73,6 +79,8 @@ impl Eq for FuncPodId {}
 pub enum FuncPodState {
     Idle(SystemTime), // IdleTime
     Running(FuncCall), // the funcCallId
+    Preempting(FuncCall), // the pod is terminating for higher priority task, the funccall will be requeued
+    Cancelling, // the pod is terminating as its funccall is cancelled, the caller has got the error
     Exiting,
     Dead,
 }
@@ -88,6 +96,8 @@ impl FuncPodState {
     pub fn IsExiting(&self) -> bool {
         match self {
             Self::Exiting => return true,
+            Self::Preempting(_) => return true,
+            Self::Cancelling => return true,
             _ => return false,
         }
     }
@@ -124,6 +134,7 @@ impl FuncPod {
             &funcCall.id, 
             &self.node.NodeName(),
         )?;
+        FUNC_CALL_STORE.Put(funcCall, FuncCallStage::Assigned)?;
         self.node.Send(FuncNodeMsg::FuncCall(funcCall.clone()))?;
         return Ok(())
     }
@@ -150,6 +161,15 @@ impl FuncPod {
         return self.state.lock().unwrap().IsExiting();
     }
 
+    // the func call which the pod is running
+    pub fn RunningFuncCall(&self) -> Option<FuncCall> {
+        match &*self.state.lock().unwrap() {
+            FuncPodState::Running(funcCall) => return Some(funcCall.clone()),
+            FuncPodState::Preempting(funcCall) => return Some(funcCall.clone()),
+            _ => return None,
+        }
+    }
+
     pub fn OnFuncSvcCallReq(&self, funccall: &FuncCall) -> Result<()> {
         self.callerFuncCalls.lock().unwrap().insert(funccall.id.clone(), funccall.clone());
         return Ok(())
@@ -157,15 +177,36 @@ impl FuncPod {
 
     // get funccall response from nodeagent
     pub fn OnFuncSvcCallResp(&self, resp: func::FuncSvcCallResp) -> Result<()> {
+        {
+            let mut state = self.state.lock().unwrap();
+            if let FuncPodState::Cancelling = &*state {
+                // the func call finished before the pod is terminated, the caller has got the cancel error
+                *state = FuncPodState::Exiting;
+                return Ok(())
+            }
+        }
+
+        let funcCall = self.RunningFuncCall();
+        let mut result = None;
         match resp.res.as_ref() {
             None => {
                 error!("get none func res {:#?}", resp);
             }
             Some(res) => {
+                if let Some(funcCall) = &funcCall {
+                    FUNC_CALL_STORE.Done(funcCall, res)?;
+                }
+
                 let funcRes : FuncRes = res.clone().into();
-                let state = match funcRes {
-                    FuncRes::Resp(_) => FuncStateSuccess,
-                    FuncRes::Error(_) => FuncStateFail,
+                let state = match &funcRes {
+                    FuncRes::Resp(r) => {
+                        result = Some(FuncCallResult::Ok(r.clone()));
+                        FuncStateSuccess
+                    }
+                    FuncRes::Error(e) => {
+                        result = Some(FuncCallResult::Err(e.error.clone()));
+                        FuncStateFail
+                    }
                 };
         
                 AUDIT_AGENT.FinishFunc(
@@ -175,17 +216,55 @@ impl FuncPod {
             }
         };
         
+        // the workflow result is handled after the pod is freed as it might start new func calls
+        let mut workflowResp = None;
+        if resp.caller_node_id == WORKFLOW_CALLER_NODE {
+            workflowResp = Some(resp);
+        } else {
+            match FUNC_NODE_MGR.Get(&resp.caller_node_id) {
+                Ok(callerNode) => {
+                    callerNode.Send(FuncNodeMsg::FuncCallResp(resp))?;
+                }
+                Err(_) => {
+                    // the caller node has not registered after func service restart, 
+                    // keep the result until it registers
+                    if let (Some(funcCall), Some(result)) = (&funcCall, result) {
+                        FUNC_CALL_MGR.PendingResult(funcCall, result);
+                    }
+                }
+            }
+        }
+
+        let preempting = {
+            let mut state = self.state.lock().unwrap();
+            if let FuncPodState::Preempting(_) = &*state {
+                // the func call finished before the pod is terminated, no need to requeue it
+                *state = FuncPodState::Exiting;
+                true
+            } else {
+                *state = FuncPodState::Idle(SystemTime::now());
+                false
+            }
+        };
         
-        let callerNode = FUNC_NODE_MGR.Get(&resp.caller_node_id)?;
-        callerNode.Send(FuncNodeMsg::FuncCallResp(resp))?;
-        *self.state.lock().unwrap() = FuncPodState::Idle(SystemTime::now());
-        
-        FUNC_SVC_MGR.lock().unwrap().OnFreePod(self, false)?;
+        if !preempting {
+            FUNC_SVC_MGR.lock().unwrap().OnFreePod(self, false)?;
+        }
+
+        if let Some(resp) = workflowResp {
+            WORKFLOW_MGR.OnFuncCallResp(resp)?;
+        }
         return Ok(())
     } 
 
         // get funccall ack from nodeagent
         pub fn OnFuncSvcCallAck(&self, ack: func::FuncSvcCallAck) -> Result<()> {
+            if let Some(funcCall) = self.RunningFuncCall() {
+                FUNC_CALL_STORE.Put(&funcCall, FuncCallStage::Running)?;
+            }
+            if ack.caller_node_id == WORKFLOW_CALLER_NODE {
+                return Ok(())
+            }
             let callerNode = FUNC_NODE_MGR.Get(&ack.caller_node_id)?;
             callerNode.Send(FuncNodeMsg::FuncCallAck(ack))?;
             return Ok(())
@@ -199,24 +278,43 @@ impl FuncPod {
     
     // when a pod is disconnected
     pub fn OnFuncPodDisconnReq(&self) -> Result<()> {
-        match self.state.lock().unwrap().clone() {
+        let state = self.state.lock().unwrap().clone();
+        match state {
             FuncPodState::Idle(_) => (),
             FuncPodState::Running(funcCall) => {
-                let callerNode = FUNC_NODE_MGR.Get(&funcCall.callerNodeId)?;
-                let funcRes = FuncRes::NewError(
-                    FuncErrSource::System, 
-                    format!("funcpod {} disconnect ", &self.podName)
-                );
-
-                let resp = func::FuncSvcCallResp {
-                    id: funcCall.id.clone(),
-                    res: Some(funcRes.ToGrpc()),
-                    caller_node_id: funcCall.callerNodeId.clone(),
-                    caller_pod_id: funcCall.callerFuncPodId.clone(),
-                    callee_node_id: funcCall.calleeNodeId.lock().unwrap().clone(),
-                    callee_pod_id: funcCall.calleeFuncPodId.lock().unwrap().clone(),
-                };
-                callerNode.Send(FuncNodeMsg::FuncCallResp(resp))?;
+                // the func calls started by the failed attempt have no caller any more
+                FUNC_SVC_MGR.CancelCallees(&funcCall.id, &format!("caller funcpod {} disconnect", &self.podName))?;
+                match funcCall.NextRetry() {
+                    Some(backoff) => {
+                        info!("funcpod {} disconnect, retry funccall {} after {:?}", &self.podName, &funcCall.id, backoff);
+                        FUNC_SVC_MGR.RetryFuncCall(&funcCall, backoff);
+                    }
+                    None => {
+                        let funcRes = FuncRes::NewError(
+                            FuncErrSource::System, 
+                            format!("funcpod {} disconnect ", &self.podName)
+                        );
+
+                        let resp = func::FuncSvcCallResp {
+                            id: funcCall.id.clone(),
+                            res: Some(funcRes.ToGrpc()),
+                            caller_node_id: funcCall.callerNodeId.clone(),
+                            caller_pod_id: funcCall.callerFuncPodId.clone(),
+                            callee_node_id: funcCall.calleeNodeId.lock().unwrap().clone(),
+                            callee_pod_id: funcCall.calleeFuncPodId.lock().unwrap().clone(),
+                        };
+                        if funcCall.callerNodeId == WORKFLOW_CALLER_NODE {
+                            WORKFLOW_MGR.OnFuncCallResp(resp)?;
+                        } else {
+                            let callerNode = FUNC_NODE_MGR.Get(&funcCall.callerNodeId)?;
+                            callerNode.Send(FuncNodeMsg::FuncCallResp(resp))?;
+                        }
+                    }
+                }
+            }
+            FuncPodState::Preempting(funcCall) => {
+                FUNC_SVC_MGR.CancelCallees(&funcCall.id, &format!("caller funcpod {} is preempted", &self.podName))?;
+                FUNC_SVC_MGR.lock().unwrap().RequeueFuncCall(&funcCall)?;
             }
             _ => {}
         }
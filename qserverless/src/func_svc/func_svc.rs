@@ -14,7 +14,7 @@
 
 use std::collections::BTreeMap;
 use std::sync::Mutex;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 use std::result::Result as SResult;
 use core::ops::Deref;
 use tokio::sync::mpsc;
@@ -22,15 +22,28 @@ use tokio::sync::mpsc;
 use qobjs::common::*;
 use qobjs::func;
 use qobjs::types::*;
+use qobjs::system_types::FuncCallStage;
+use qobjs::audit::func_audit::FuncStateCancelled;
 
+use crate::AUDIT_AGENT;
+use crate::FUNC_CALL_MGR;
+use crate::FUNC_CALL_STORE;
 use crate::FUNC_NODE_MGR;
+use crate::FUNC_POD_MGR;
 use crate::PACKAGE_MGR;
 use crate::SCHEDULER;
+use crate::FUNC_SVC_MGR;
+use crate::WORKFLOW_MGR;
+use crate::message::FuncNodeMsg;
 use crate::func_node::FuncNode;
 use crate::task_queue::*;
 use crate::func_call::*;
 use crate::func_pod::*;
 use crate::package::*;
+use crate::workflow::WORKFLOW_CALLER_NODE;
+
+// the interval to check the func calls which pass the deadline and the idle jobs
+pub const DEADLINE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
 
 #[derive(Debug)]
 pub struct Func {
@@ -113,6 +126,117 @@ impl FuncSvc {
         node.CreateProcessor(req, stream, tx).await?;
         return Ok(())
     }
+
+    // cancel the func call and all the func calls started by it directly or indirectly
+    pub fn CancelFuncCall(&self, id: &str, reason: &str) -> Result<()> {
+        let funcCall = match FUNC_CALL_MGR.Get(id) {
+            None => return Ok(()),
+            Some(c) => c,
+        };
+
+        let mut calls = vec![funcCall];
+        calls.append(&mut FUNC_CALL_MGR.Descendants(id));
+        return self.CancelFuncCalls(&calls, reason);
+    }
+
+    // cancel the func calls started by the func call directly or indirectly
+    pub fn CancelCallees(&self, id: &str, reason: &str) -> Result<()> {
+        let calls = FUNC_CALL_MGR.Descendants(id);
+        return self.CancelFuncCalls(&calls, reason);
+    }
+
+    pub fn CancelFuncCalls(&self, calls: &[FuncCall], reason: &str) -> Result<()> {
+        if calls.len() == 0 {
+            return Ok(())
+        }
+
+        // one failed func call doesn't stop cancelling the others
+        {
+            let mut inner = self.lock().unwrap();
+            for funcCall in calls {
+                if let Err(e) = inner.StopFuncCall(funcCall) {
+                    error!("stop funccall {} fail with error {:?}", &funcCall.id, e);
+                }
+            }
+        }
+
+        // the callers are notified after the lock is released as the workflow might start new func calls
+        for funcCall in calls {
+            info!("cancel funccall {} as {}", &funcCall.id, reason);
+            if let Err(e) = Self::NotifyCancelled(funcCall, reason) {
+                error!("notify cancelled funccall {} fail with error {:?}", &funcCall.id, e);
+            }
+        }
+
+        return Ok(())
+    }
+
+    // send the cancel error to the caller of the func call
+    pub fn NotifyCancelled(funcCall: &FuncCall, reason: &str) -> Result<()> {
+        AUDIT_AGENT.FinishFunc(&funcCall.id, FuncStateCancelled)?;
+        let funcRes = FuncRes::NewError(
+            FuncErrSource::System, 
+            format!("funccall {} is cancelled as {}", &funcCall.id, reason)
+        );
+
+        if funcCall.callerNodeId == WORKFLOW_CALLER_NODE {
+            FUNC_CALL_STORE.Remove(&funcCall.package.Namespace(), &funcCall.id)?;
+            FUNC_CALL_MGR.Remove(&funcCall.id);
+            return WORKFLOW_MGR.OnFuncCallResult(&funcCall.id, funcRes);
+        }
+
+        let resp = func::FuncSvcCallResp {
+            id: funcCall.id.clone(),
+            res: Some(funcRes.ToGrpc()),
+            caller_node_id: funcCall.callerNodeId.clone(),
+            caller_pod_id: funcCall.callerFuncPodId.clone(),
+            callee_node_id: funcCall.calleeNodeId.lock().unwrap().clone(),
+            callee_pod_id: funcCall.calleeFuncPodId.lock().unwrap().clone(),
+        };
+
+        match FUNC_NODE_MGR.Get(&funcCall.callerNodeId) {
+            // the func call is removed when the caller node gets the response
+            Ok(callerNode) => callerNode.Send(FuncNodeMsg::FuncCallResp(resp))?,
+            Err(_) => {
+                FUNC_CALL_STORE.Remove(&funcCall.package.Namespace(), &funcCall.id)?;
+                FUNC_CALL_MGR.Remove(&funcCall.id);
+            }
+        }
+
+        return Ok(())
+    }
+
+    // requeue the func call after the backoff when its callee pod fails
+    pub fn RetryFuncCall(&self, funcCall: &FuncCall, backoff: Duration) {
+        funcCall.SetState(FuncCallState::Retrying(SystemTime::now()));
+        let funcCall = funcCall.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(backoff).await;
+            let mut inner = FUNC_SVC_MGR.lock().unwrap();
+            if let Err(e) = inner.RequeueRetryingFuncCall(&funcCall) {
+                error!("retry funccall {} fail with error {:?}", &funcCall.id, e);
+            }
+        });
+    }
+
+    // cancel the func calls which pass the deadline
+    pub async fn DeadlineProcess(&self) {
+        loop {
+            tokio::time::sleep(DEADLINE_CHECK_INTERVAL).await;
+            for funcCall in FUNC_CALL_MGR.Expired() {
+                // the func call might have been cancelled with its caller
+                if funcCall.state.lock().unwrap().IsCancelled() {
+                    continue;
+                }
+
+                if let Err(e) = self.CancelFuncCall(&funcCall.id, "deadline exceeded") {
+                    error!("cancel expired funccall {} fail with error {:?}", &funcCall.id, e);
+                }
+            }
+
+            FUNC_CALL_MGR.FinishIdleJobs();
+        }
+    }
 }
 impl FuncSvcInner {
     pub fn OnNodeJoin(&mut self, resource: Resource) -> Result<()> {
@@ -249,7 +373,8 @@ impl FuncSvcInner {
             };
 
             if !self.freeResource.Fullfil(&task.ReqResource()) {
-                return Ok(()); // no enough resource
+                // no enough resource
+                return self.PreemptForTask(&task);
             }
 
             let package = task.Package();
@@ -258,17 +383,23 @@ impl FuncSvcInner {
                 return Ok(())
             }
 
-            self.CreatePod(&package)?;
+            // the cluster has enough resource but no single node can host the pod
+            let (node, score) = match SCHEDULER.SelectNode(&package) {
+                None => return self.PreemptForTask(&task),
+                Some(n) => n,
+            };
+
+            self.CreatePod(&package, &node, score)?;
             self.waitResourceQueue.Remove(&task);
         }
     }
 
-    pub fn CreatePod(&mut self, package: &Package) -> Result<()> {
+    pub fn CreatePod(&mut self, package: &Package, node: &FuncNode, score: i64) -> Result<()> {
         assert!(self.freeResource.Fullfil(&package.ReqResource()));
         self.freeResource = self.freeResource - package.ReqResource();
         
         let podName = uuid::Uuid::new_v4().to_string();
-        SCHEDULER.SchedulePod(&podName, package)?;
+        SCHEDULER.SchedulePod(&podName, package, node, score)?;
         let res = package.lock().unwrap().OnNewPodCreating();
         match res {
             None => {
@@ -292,10 +423,262 @@ impl FuncSvcInner {
         return freemem * 100 / self.totalResource.mem < KEEP_BATCHTASK_THRESHOLD;
     }
 
+    // when the task can't get resource, terminate the pods running lower priority tasks
+    pub fn PreemptForTask(&mut self, task: &FuncCall) -> Result<()> {
+        let pri = task.Priority();
+        let reqResource = task.Package().ReqResource();
+
+        let nodes : Vec<FuncNode> = FUNC_NODE_MGR.nodes.lock().unwrap().values().cloned().collect();
+        let pods : Vec<FuncPod> = FUNC_POD_MGR.pods.lock().unwrap().values().cloned().collect();
+
+        // the resource which will be freed by the exiting pods and the preemptable pods, per node
+        let mut freeing : BTreeMap<String, Resource> = BTreeMap::new();
+        let mut victims : BTreeMap<String, Vec<(FuncPod, FuncCall)>> = BTreeMap::new();
+        for pod in pods {
+            if pod.clientMode {
+                continue;
+            }
+            let nodeName = pod.node.NodeName();
+            if pod.IsExiting() {
+                if let Some(package) = &pod.package {
+                    let resource = freeing.get(&nodeName).cloned().unwrap_or_default();
+                    freeing.insert(nodeName, resource + package.ReqResource());
+                }
+                continue;
+            }
+            let funcCall = match &*pod.state.lock().unwrap() {
+                FuncPodState::Running(funcCall) => funcCall.clone(),
+                _ => continue,
+            };
+            if funcCall.Priority() > pri {
+                victims.entry(nodeName).or_insert_with(Vec::new).push((pod.clone(), funcCall));
+            }
+        }
+
+        // the free resource and the victims in preemption order, per running node
+        let mut candidates : Vec<(Resource, Vec<((FuncPod, FuncCall), Resource)>)> = Vec::new();
+        for node in nodes {
+            if !node.IsRunning() {
+                continue;
+            }
+            let nodeName = node.NodeName();
+            let free = node.FreeResource() + freeing.get(&nodeName).cloned().unwrap_or_default();
+            let mut nodeVictims = victims.remove(&nodeName).unwrap_or_default();
+
+            // preempt the lowest priority and the latest started task first
+            nodeVictims.sort_by(|a, b| {
+                b.1.Priority().cmp(&a.1.Priority()).then(b.1.createTime.cmp(&a.1.createTime))
+            });
+
+            let nodeVictims = nodeVictims.into_iter().map(|(pod, funcCall)| {
+                let resource = pod.package.as_ref().unwrap().ReqResource();
+                ((pod, funcCall), resource)
+            }).collect();
+            candidates.push((free, nodeVictims));
+        }
+
+        let selected = SelectVictims(candidates, &reqResource);
+        let selected = match selected {
+            None => return Ok(()),
+            Some(s) => s,
+        };
+
+        for (pod, funcCall) in selected {
+            info!("preempt funcpod {} running funccall {} with priority {} for task {} with priority {}", 
+                &pod.podName, &funcCall.id, funcCall.Priority(), &task.id, pri);
+            self.PreemptPod(&pod, &funcCall)?;
+        }
+
+        return Ok(())
+    }
+
+    pub fn PreemptPod(&mut self, pod: &FuncPod, funcCall: &FuncCall) -> Result<()> {
+        let package = pod.package.clone().unwrap();
+        funcCall.SetState(FuncCallState::Cancelling);
+        *pod.state.lock().unwrap() = FuncPodState::Preempting(funcCall.clone());
+        package.lock().unwrap().OnPodPreempted();
+        self.freeingResource = self.freeingResource + package.ReqResource();
+        SCHEDULER.TerminatePod(&pod.namespace, &pod.podName)?;
+        return Ok(())
+    }
+
+    // remove the cancelled func call from the waiting queue or terminate the pod running it
+    pub fn StopFuncCall(&mut self, funcCall: &FuncCall) -> Result<()> {
+        let state = funcCall.state.lock().unwrap().clone();
+        funcCall.SetState(FuncCallState::Cancelled);
+        match state {
+            FuncCallState::Cancelled => (),
+            FuncCallState::Scheduling(_) => {
+                let package = funcCall.Package();
+                let task = package.lock().unwrap().RemoveFuncCall(funcCall);
+                if let Some(t) = task {
+                    self.waitResourceQueue.Remove(&t);
+                }
+            }
+            _ => {
+                let podName = funcCall.calleeFuncPodId.lock().unwrap().clone();
+                if let Ok(pod) = FUNC_POD_MGR.Get(&podName) {
+                    self.CancelPod(&pod, funcCall)?;
+                }
+            }
+        }
+
+        return Ok(())
+    }
+
+    // terminate the pod running the cancelled func call
+    pub fn CancelPod(&mut self, pod: &FuncPod, funcCall: &FuncCall) -> Result<()> {
+        if pod.clientMode {
+            return Ok(())
+        }
+
+        match pod.RunningFuncCall() {
+            Some(c) if c.id == funcCall.id => (),
+            _ => return Ok(()),
+        }
+
+        let package = pod.package.clone().unwrap();
+        let preempting = pod.IsExiting();
+        *pod.state.lock().unwrap() = FuncPodState::Cancelling;
+        if preempting {
+            // the pod is already terminating and its resource is counted as freeing
+            return Ok(())
+        }
+
+        package.lock().unwrap().OnPodPreempted();
+        self.freeingResource = self.freeingResource + package.ReqResource();
+        SCHEDULER.TerminatePod(&pod.namespace, &pod.podName)?;
+        return Ok(())
+    }
+
+    // the pod running the funccall is preempted, put the funccall back to waiting queue
+    pub fn RequeueFuncCall(&mut self, funcCall: &FuncCall) -> Result<()> {
+        if !funcCall.state.lock().unwrap().IsCancelling() {
+            return Ok(())
+        }
+
+        return self.Requeue(funcCall);
+    }
+
+    // the backoff of the failed funccall is over, put it back to waiting queue
+    pub fn RequeueRetryingFuncCall(&mut self, funcCall: &FuncCall) -> Result<()> {
+        // the func call might be cancelled during the backoff
+        if !funcCall.state.lock().unwrap().IsRetrying() {
+            return Ok(())
+        }
+
+        return self.Requeue(funcCall);
+    }
+
+    // put the funccall back to waiting queue
+    pub fn Requeue(&mut self, funcCall: &FuncCall) -> Result<()> {
+        // the caller has gone, drop the funccall
+        if funcCall.callerNodeId != WORKFLOW_CALLER_NODE {
+            match FUNC_NODE_MGR.Get(&funcCall.callerNodeId) {
+                Ok(node) if node.IsRunning() => (),
+                _ => return Ok(())
+            }
+        }
+
+        funcCall.SetState(FuncCallState::Scheduling(SystemTime::now()));
+        *funcCall.calleeNodeId.lock().unwrap() = String::new();
+        *funcCall.calleeFuncPodId.lock().unwrap() = String::new();
+        FUNC_CALL_STORE.Put(funcCall, FuncCallStage::Queued)?;
+        return self.OnNewFuncCall(funcCall);
+    }
+
+    // a pod reported by the node when it registers to the func service
+    pub fn OnPodRecovered(&mut self, pod: &FuncPod) -> Result<()> {
+        let package = pod.package.clone().unwrap();
+        let req = package.ReqResource();
+        if self.freeResource.Fullfil(&req) {
+            self.freeResource = self.freeResource - req;
+        }
+
+        let state = pod.state.lock().unwrap().clone();
+        match state {
+            FuncPodState::Running(_) => {
+                package.lock().unwrap().runningPodCnt += 1;
+                return Ok(())
+            }
+            _ => return self.OnFreePod(pod, true),
+        }
+    }
+
     pub fn EvictPod(&mut self, pod: &FuncPod, freeResource: &Resource) -> Result<()> {
         *pod.state.lock().unwrap() = FuncPodState::Exiting;
         self.freeingResource = self.freeingResource + *freeResource;
         SCHEDULER.TerminatePod(&pod.namespace, &pod.podName)?;
         return Ok(())
     }
-}
\ No newline at end of file
+}
+
+// the victims to preempt on the node which needs the fewest of them to host the request.
+// each node comes with its free resource and its victims in preemption order.
+// none when a node can already host the request or no node can host it after preemption
+pub fn SelectVictims<T>(nodes: Vec<(Resource, Vec<(T, Resource)>)>, req: &Resource) -> Option<Vec<T>> {
+    let mut selected : Option<Vec<T>> = None;
+    for (mut free, mut victims) in nodes {
+        if free.Fullfil(req) {
+            // the exiting pods on the node will free enough resource
+            return None;
+        }
+
+        let mut count = 0;
+        for (_, resource) in &victims {
+            if free.Fullfil(req) {
+                break;
+            }
+            free = free + *resource;
+            count += 1;
+        }
+
+        if !free.Fullfil(req) {
+            continue;
+        }
+
+        let fewer = match &selected {
+            None => true,
+            Some(s) => count < s.len(),
+        };
+        if fewer {
+            victims.truncate(count);
+            selected = Some(victims.into_iter().map(|(v, _)| v).collect());
+        }
+    }
+
+    return selected;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn TestSelectVictims() {
+        let req = Resource::New(4096, 4);
+        let victim = |name: &str, mem: u64, cpu: u32| (name.to_string(), Resource::New(mem, cpu));
+
+        // each node is only partly free, node a needs 3 victims and node b 2.
+        // a1 and b1 together would free enough but the victims must come from one node
+        let nodes = vec![
+            (Resource::New(1024, 1), vec![victim("a1", 1024, 1), victim("a2", 1024, 1), victim("a3", 1024, 1), victim("a4", 1024, 1)]),
+            (Resource::New(0, 0), vec![victim("b1", 2048, 2), victim("b2", 2048, 2), victim("b3", 2048, 2)]),
+        ];
+        assert_eq!(SelectVictims(nodes, &req), Some(vec!["b1".to_string(), "b2".to_string()]));
+
+        // each node only partly freeable, together they would fit the request
+        let nodes = vec![
+            (Resource::New(1024, 1), vec![victim("a1", 1024, 1)]),
+            (Resource::New(0, 0), vec![victim("b1", 2048, 2)]),
+        ];
+        assert_eq!(SelectVictims(nodes, &req), None);
+
+        // a node which can host the request without preemption
+        let nodes = vec![
+            (Resource::New(0, 0), vec![victim("a1", 4096, 4)]),
+            (Resource::New(4096, 4), vec![victim("b1", 4096, 4)]),
+        ];
+        assert_eq!(SelectVictims(nodes, &req), None);
+    }
+}
@@ -26,6 +26,7 @@ extern crate log;
 
 use func_call::FuncCallMgr;
 use func_node::FuncNodeMgr;
+use func_node::FuncNodeStatusHandler;
 use func_pod::FuncPodMgr;
 use func_svc::FuncSvc;
 use lazy_static::lazy_static;
@@ -33,6 +34,7 @@ use lazy_static::lazy_static;
 pub mod func_svc;
 pub mod func_conn;
 pub mod func_call;
+pub mod func_call_store;
 pub mod task_queue;
 pub mod scheduler;
 pub mod func_pod;
@@ -40,10 +42,13 @@ pub mod package;
 pub mod func_node;
 pub mod message;
 pub mod grpc_svc;
+pub mod workflow;
 
 use package::PackageMgr;
-use qobjs::{common::*, informer_factory::InformerFactory, selection_predicate::ListOption, audit::audit_agent::AuditAgent};
+use qobjs::{common::*, informer_factory::InformerFactory, selection_predicate::ListOption, audit::audit_agent::AuditAgent, audit::func_audit::AuditDbAddr};
 use scheduler::Scheduler;
+use func_call_store::FuncCallStore;
+use workflow::WorkflowMgr;
 use qobjs::types::*;
 
 lazy_static! {
@@ -71,8 +76,16 @@ lazy_static! {
         Scheduler::New(QMETASVC_ADDR)
     };
 
+    pub static ref FUNC_CALL_STORE: FuncCallStore = {
+        FuncCallStore::New(QMETASVC_ADDR)
+    };
+
     pub static ref AUDIT_AGENT: AuditAgent = {
-        AuditAgent::New(AUDITDB_ADDR)
+        AuditAgent::New(&AuditDbAddr())
+    };
+
+    pub static ref WORKFLOW_MGR: WorkflowMgr = {
+        WorkflowMgr::New()
     };
 }
 
@@ -92,6 +105,33 @@ async fn main() -> Result<()> {
     let informer = factory.GetInformer("package").await.unwrap();
     let _id1 = informer.AddEventHandler(Arc::new(PACKAGE_MGR.clone())).await.unwrap();
 
+    // the func calls of last func service instance will be reconciled when node agents register
+    match FUNC_CALL_STORE.Load().await {
+        Ok(records) => {
+            info!("func svc recover {} func calls", records.len());
+            FUNC_CALL_MGR.Recover(records);
+        }
+        Err(e) => {
+            error!("func svc load func calls fail with error {:?}", e);
+        }
+    }
+
+    // the node agents report their cached images in the node status to the node manager
+    let nodeMgrSvcAddr = &format!("http://{}", NODEMGRSVC_ADDR);
+    let nodeFactory = InformerFactory::New(nodeMgrSvcAddr, "").await.unwrap();
+    nodeFactory.AddInformer(QUARK_NODE, &ListOption::default()).await.unwrap();
+    let nodeInformer = nodeFactory.GetInformer(QUARK_NODE).await.unwrap();
+    let _id3 = nodeInformer.AddEventHandler(Arc::new(FuncNodeStatusHandler {})).await.unwrap();
+
+    // the workflows are started after the func calls are recovered so that their running steps can be reconciled
+    factory.AddInformer(QUARK_WORKFLOW, &ListOption::default()).await.unwrap();
+    let workflowInformer = factory.GetInformer(QUARK_WORKFLOW).await.unwrap();
+    let _id2 = workflowInformer.AddEventHandler(Arc::new(WORKFLOW_MGR.clone())).await.unwrap();
+
+    tokio::spawn(async {
+        FUNC_SVC_MGR.DeadlineProcess().await;
+    });
+
     grpc_svc::FuncSvcGrpcService().await.unwrap();
     Ok(())
 }
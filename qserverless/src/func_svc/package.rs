@@ -168,6 +168,29 @@ impl PackageInner {
         }
     }
 
+    // remove the cancelled task from waiting queue, return the task which needs removed from global task Queue
+    pub fn RemoveFuncCall(&mut self, funcCall: &FuncCall) -> Option<FuncCall> {
+        let idx = match self.waitingQueue.Remove(funcCall) {
+            None => return None,
+            Some(idx) => idx,
+        };
+
+        if idx >= self.creatingPodCnt {
+            // the task is waiting for resource to create pod
+            return Some(funcCall.clone());
+        }
+
+        // the creating pod will serve the next task which is waiting for resource
+        return self.waitingQueue.GetWaitingTask(self.creatingPodCnt - 1);
+    }
+
+    // a running pod of the package is preempted by higher priority task
+    pub fn OnPodPreempted(&mut self) {
+        if self.runningPodCnt > 0 {
+            self.runningPodCnt -= 1;
+        }
+    }
+
     pub fn TopPriority(&self) -> usize {
         return self.waitingQueue.TopPriority();
     }
@@ -22,10 +22,18 @@ use tokio::sync::mpsc;
 use qobjs::cacher_client::CacherClient;
 use qobjs::k8s;
 use qobjs::types::*;
+use qobjs::runtime_types::DefaultDomainName;
 use qobjs::common::*;
 
+use crate::AUDIT_AGENT;
+use crate::FUNC_NODE_MGR;
+use crate::func_node::*;
 use crate::package::Package;
 
+// the weights used when scoring node for a new pod
+pub const IMAGE_LOCALITY_SCORE: i64 = 50;
+pub const ANTI_AFFINITY_SCORE: i64 = 30;
+
 
 pub enum SchedulerMsg {
     CreatePod(CreatePod),
@@ -36,6 +44,7 @@ pub enum SchedulerMsg {
 pub struct CreatePod {
     pub podName: String,
     pub package: Package,
+    pub nodeName: String,
 }
 
 #[derive(Debug)]
@@ -89,10 +98,67 @@ impl Scheduler {
         self.stop.store(true, std::sync::atomic::Ordering::SeqCst);
     }
 
-    pub fn SchedulePod(&self, podName: &str, package: &Package) -> Result<()> {
+    // score a node for the package, the higher is better.
+    // return None when the node can't host the package
+    pub fn ScoreNode(node: &FuncNode, package: &Package, images: &[String]) -> Option<i64> {
+        if !node.IsRunning() {
+            return None;
+        }
+
+        let req = package.ReqResource();
+        let total = node.lock().unwrap().resource;
+        let free = node.FreeResource();
+        if !free.Fullfil(&req) || total.mem == 0 || total.cpu == 0 {
+            return None;
+        }
+
+        // prefer the node with more free resource left after the placement
+        let left = free - req;
+        let mut score = (left.mem * 100 / total.mem + left.cpu as i64 * 100 / total.cpu as i64) / 2;
+
+        // the node has pulled the images
+        if node.HasImages(images) {
+            score += IMAGE_LOCALITY_SCORE;
+        }
+
+        // spread the replicas of the package across nodes
+        score -= ANTI_AFFINITY_SCORE * node.PackagePodCount(&package.PackageId()) as i64;
+
+        return Some(score);
+    }
+
+    // pick the node with highest score for the package
+    pub fn SelectNode(&self, package: &Package) -> Option<(FuncNode, i64)> {
+        let images = PackageImages(package);
+        let nodes : Vec<FuncNode> = FUNC_NODE_MGR.nodes.lock().unwrap().values().cloned().collect();
+
+        let mut selected: Option<(FuncNode, i64)> = None;
+        for node in nodes {
+            let score = match Self::ScoreNode(&node, package, &images) {
+                None => continue,
+                Some(s) => s,
+            };
+
+            match &selected {
+                Some((_, s)) if *s >= score => (),
+                _ => selected = Some((node, score)),
+            }
+        }
+
+        return selected;
+    }
+
+    pub fn SchedulePod(&self, podName: &str, package: &Package, node: &FuncNode, score: i64) -> Result<()> {
+        let nodeName = node.NodeName();
+        node.ReservePod(podName, package);
+        info!("schedule pod {}/{} of package {} on node {} with score {}", 
+            package.Namespace(), podName, package.Name(), &nodeName, score);
+        AUDIT_AGENT.PlacePod(podName, &package.Namespace(), &package.Name(), &nodeName, score)?;
+
         let msg = CreatePod {
             podName: podName.to_string(),
             package: package.clone(),
+            nodeName: nodeName,
         };
         match self.agentChann.try_send(SchedulerMsg::CreatePod(msg)) {
             Ok(()) => return Ok(()),
@@ -111,13 +177,14 @@ impl Scheduler {
         }
     }
 
-    pub async fn CreatePod(&self, client: &CacherClient, podName: &str, package: &Package) -> Result<()> {
+    pub async fn CreatePod(&self, client: &CacherClient, podName: &str, package: &Package, nodeName: &str) -> Result<()> {
         let mut pod = k8s::Pod::default();
 
         pod.metadata.namespace = Some(package.Namespace());
         pod.metadata.name = Some(podName.to_owned());
         let mut annotations = package.lock().unwrap().Annotations();
         annotations.insert(AnnotationFuncPodPackageName.to_owned(), package.Name());
+        annotations.insert(AnnotationNodeMgrNode.to_owned(), format!("{}/{}", DefaultDomainName, nodeName));
         pod.metadata.annotations = Some(annotations);
         
         pod.spec = Some(package.PodSpec());
@@ -157,6 +224,9 @@ impl Scheduler {
             Ok(_) => (),
             Err(e) => {
                 error!("fail to create pod with error {:?}", e);
+                if let Ok(node) = FUNC_NODE_MGR.Get(nodeName) {
+                    node.ReleasePod(podName);
+                }
             }
         }
         return Ok(())
@@ -198,7 +268,7 @@ impl Scheduler {
 
                         match msg {
                             SchedulerMsg::CreatePod(msg) => {
-                                match self.CreatePod(&cacheClient, &msg.podName, &msg.package).await {
+                                match self.CreatePod(&cacheClient, &msg.podName, &msg.package, &msg.nodeName).await {
                                     Ok(()) => (),
                                     Err(_) => break,
                                 }
@@ -220,6 +220,26 @@ impl PackageTaskQueue {
         return Some(task);
     }
 
+    // remove the task from the queue, return its index in the waiting order
+    pub fn Remove(&mut self, task: &FuncCall) -> Option<usize> {
+        let mut n = 0;
+        for i in self.TopPriority()..self.waitingQueue.len() {
+            match self.waitingQueue[i].iter().position(|t| t.id == task.id) {
+                None => n += self.waitingQueue[i].len(),
+                Some(idx) => {
+                    self.waitingQueue[i].remove(idx);
+                    self.waitingTask -= 1;
+                    if self.waitingQueue[i].len() == 0 {
+                        self.existingMask &= !(1<<i);
+                    }
+                    return Some(n + idx);
+                }
+            }
+        }
+
+        return None;
+    }
+
     pub fn Push(&mut self, task: &FuncCall) {
         self.waitingTask += 1;
         let pri = task.Priority();
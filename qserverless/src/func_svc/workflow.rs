@@ -0,0 +1,623 @@
+// Copyright (c) 2021 Quark Container Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::SystemTime;
+use core::ops::Deref;
+
+use qobjs::common::*;
+use qobjs::func;
+use qobjs::informer::EventHandler;
+use qobjs::store::ThreadSafeStore;
+use qobjs::system_types::*;
+use qobjs::types::*;
+use qobjs::utility::SystemTimeProto;
+
+use crate::AUDIT_AGENT;
+use crate::FUNC_CALL_MGR;
+use crate::FUNC_CALL_STORE;
+use crate::FUNC_NODE_MGR;
+use crate::FUNC_SVC_MGR;
+use crate::WORKFLOW_MGR;
+use crate::func_call::*;
+use crate::func_node::FuncNode;
+use crate::task_queue::PRIORITY_COUNT;
+
+// the caller node id of the func calls started by workflow
+pub const WORKFLOW_CALLER_NODE: &str = "workflow.qserverless.quarksoft.io";
+
+#[derive(Debug, Clone)]
+pub struct WorkflowCall {
+    pub workflow: String, // namespace/name
+    pub step: String,
+    pub item: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct WorkflowMgrInner {
+    pub workflows: BTreeMap<String, Workflow>,
+    // from func call id to the workflow step
+    pub calls: BTreeMap<String, WorkflowCall>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct WorkflowMgr(Arc<Mutex<WorkflowMgrInner>>);
+
+impl Deref for WorkflowMgr {
+    type Target = Arc<Mutex<WorkflowMgrInner>>;
+
+    fn deref(&self) -> &Arc<Mutex<WorkflowMgrInner>> {
+        &self.0
+    }
+}
+
+impl WorkflowMgr {
+    pub fn New() -> Self {
+        return Self::default();
+    }
+
+    pub fn Key(namespace: &str, name: &str) -> String {
+        return format!("{}/{}", namespace, name);
+    }
+
+    pub fn Start(&self, workflow: Workflow) -> Result<()> {
+        let key = Self::Key(&workflow.Namespace(), &workflow.Name());
+        if workflow.status.phase.IsFinished() || self.lock().unwrap().workflows.contains_key(&key) {
+            return Ok(())
+        }
+
+        let mut workflow = workflow;
+        if let Err(e) = workflow.Validate() {
+            error!("workflow {} is invalid with error {:?}", &key, e);
+            workflow.status.phase = WorkflowPhase::Failed;
+            return FUNC_CALL_STORE.PutWorkflow(&workflow);
+        }
+
+        let mut calls = Vec::new();
+        if workflow.status.phase == WorkflowPhase::Running {
+            // the func service restarted when the workflow is running
+            if let Err(e) = workflow.ValidateStatus() {
+                error!("workflow {} status is invalid with error {:?}", &key, e);
+                workflow.status.phase = WorkflowPhase::Failed;
+                FUNC_CALL_STORE.PutWorkflow(&workflow)?;
+                return FUNC_CALL_STORE.JobFinished(&workflow.status.jobId);
+            }
+            calls = self.Recover(&key, &mut workflow);
+        } else {
+            workflow.status.phase = WorkflowPhase::Running;
+            workflow.status.jobId = uuid::Uuid::new_v4().to_string();
+            for step in &workflow.spec.steps {
+                workflow.status.steps.insert(step.name.clone(), WorkflowStepStatus::default());
+            }
+        }
+
+        info!("start workflow {} with job id {}", &key, &workflow.status.jobId);
+        self.lock().unwrap().workflows.insert(key.clone(), workflow);
+        for (req, record) in calls {
+            self.RecoverCall(req, record)?;
+        }
+        return self.Advance(&key);
+    }
+
+    // rebuild the mapping of the running func calls of the workflow,
+    // return the func calls with the state persisted by last func service instance
+    pub fn Recover(&self, key: &str, workflow: &mut Workflow) -> Vec<(func::FuncSvcCallReq, Option<FuncCallRecord>)> {
+        let mut calls = Vec::new();
+        let mut inner = self.lock().unwrap();
+        for step in &workflow.spec.steps {
+            let status = workflow.status.steps.entry(step.name.clone()).or_default().clone();
+            if status.phase != WorkflowPhase::Running {
+                continue;
+            }
+
+            for item in 0..status.calls.len() {
+                match status.results.get(item) {
+                    Some(None) => (),
+                    _ => continue,
+                }
+
+                let id = status.calls[item].clone();
+                inner.calls.insert(id.clone(), WorkflowCall {
+                    workflow: key.to_owned(),
+                    step: step.name.clone(),
+                    item: item,
+                });
+
+                let parameters = Self::Parameters(workflow, step, item);
+                let req = Self::NewCallReq(workflow, step, &id, &parameters);
+                calls.push((req, FUNC_CALL_MGR.TakeRecovered(&id)));
+            }
+        }
+
+        return calls;
+    }
+
+    // reconcile a func call of the workflow with the persisted state
+    pub fn RecoverCall(&self, req: func::FuncSvcCallReq, record: Option<FuncCallRecord>) -> Result<()> {
+        let record = match record {
+            // the func call has not been scheduled, start it again
+            None => return self.IssueCall(req),
+            Some(r) => r,
+        };
+
+        if let Some(res) = record.Result() {
+            // the callee has finished before the func service restart
+            FUNC_CALL_STORE.Remove(&record.Namespace(), &req.id)?;
+            return self.OnFuncCallResult(&req.id, res.into());
+        }
+
+        if record.status.stage == FuncCallStage::Queued {
+            return self.IssueCall(req);
+        }
+
+        let package = match FuncNode::GetPackage(&req.namespace, &req.package_name) {
+            None => return self.IssueCall(req),
+            Some(p) => p,
+        };
+
+        let funcCall = FuncCall::NewFromGrpc(&record.ToGrpcType(), &package, FuncCallState::Scheduling(SystemTime::now()));
+        if FUNC_CALL_MGR.Get(&req.id).is_none() {
+            let lost = match FUNC_NODE_MGR.Get(&record.status.calleeNodeId) {
+                // the callee node has registered without the func call
+                Ok(node) => node.IsRunning(),
+                // wait for the callee node
+                Err(_) => record.status.calleeNodeId.len() == 0,
+            };
+
+            if lost {
+                return self.IssueCall(req);
+            }
+        }
+
+        let result = match FUNC_CALL_MGR.RegisteCaller(&funcCall)? {
+            None => return Ok(()),
+            Some(r) => r,
+        };
+
+        FUNC_CALL_STORE.Remove(&record.Namespace(), &req.id)?;
+        FUNC_CALL_MGR.Remove(&req.id);
+        let res = match result {
+            FuncCallResult::Ok(resp) => FuncRes::NewResponse(resp),
+            FuncCallResult::Err(e) => FuncRes::NewError(FuncErrSource::System, e),
+        };
+        return self.OnFuncCallResult(&req.id, res);
+    }
+
+    pub fn Parameters(workflow: &Workflow, step: &WorkflowStep, item: usize) -> String {
+        let mut parameters = step.parameters.clone();
+        for dep in &step.dependsOn {
+            if let Some(status) = workflow.status.steps.get(dep) {
+                parameters = parameters.replace(&format!("{{{{steps.{}.result}}}}", dep), &status.Result());
+            }
+        }
+
+        if step.items.len() > item {
+            parameters = parameters.replace("{{item}}", &step.items[item]);
+        }
+
+        return parameters;
+    }
+
+    pub fn NewCallReq(workflow: &Workflow, step: &WorkflowStep, id: &str, parameters: &str) -> func::FuncSvcCallReq {
+        let priority = if step.priority as usize >= PRIORITY_COUNT {
+            PRIORITY_COUNT as u64 - 1
+        } else {
+            step.priority
+        };
+
+        return func::FuncSvcCallReq {
+            job_id: workflow.status.jobId.clone(),
+            id: id.to_owned(),
+            namespace: workflow.Namespace(),
+            package_name: step.packageName.clone(),
+            func_name: step.funcName.clone(),
+            parameters: parameters.to_owned(),
+            priority: priority,
+            createtime: Some(SystemTimeProto::FromSystemTime(SystemTime::now()).ToTimeStamp()),
+            caller_node_id: WORKFLOW_CALLER_NODE.to_owned(),
+            caller_pod_id: String::new(),
+            caller_func_id: String::new(),
+            callee_node_id: String::new(),
+            callee_pod_id: String::new(),
+            call_type: 1,
+            timeout: step.timeout,
+            // the workflow retries the failed step by its own retry policy
+            max_retry: 0,
+            retry_count: 0,
+        }
+    }
+
+    // start the steps whose dependencies are finished
+    pub fn Advance(&self, key: &str) -> Result<()> {
+        let invalid = match self.lock().unwrap().workflows.get(key) {
+            None => return Ok(()),
+            Some(w) => w.ValidateStatus().err(),
+        };
+
+        if let Some(e) = invalid {
+            return self.Fail(key, e);
+        }
+
+        let mut reqs = Vec::new();
+        let mut finished = false;
+        let workflow = {
+            let mut inner = self.lock().unwrap();
+            let mut workflow = match inner.workflows.remove(key) {
+                None => return Ok(()),
+                Some(w) => w,
+            };
+
+            loop {
+                let mut changed = false;
+                let steps = workflow.spec.steps.clone();
+                for step in &steps {
+                    let phase = |name: &str| workflow.status.steps.get(name).map(|s| s.phase);
+                    if phase(&step.name) != Some(WorkflowPhase::Pending) {
+                        continue;
+                    }
+
+                    if !step.dependsOn.iter().all(|d| phase(d).map_or(false, |p| p.IsFinished())) {
+                        continue;
+                    }
+
+                    let run = match &step.when {
+                        None => step.dependsOn.iter().all(|d| phase(d) == Some(WorkflowPhase::Succeeded)),
+                        Some(cond) => match workflow.status.steps.get(&cond.step) {
+                            None => false,
+                            Some(dep) => {
+                                let stateMatch = match cond.state {
+                                    WorkflowConditionState::Succeeded => dep.phase == WorkflowPhase::Succeeded,
+                                    WorkflowConditionState::Failed => dep.phase == WorkflowPhase::Failed,
+                                };
+                                let containsMatch = match &cond.contains {
+                                    None => true,
+                                    Some(c) => dep.Result().contains(c.as_str()),
+                                };
+                                stateMatch && containsMatch
+                            }
+                        },
+                    };
+
+                    changed = true;
+                    if !run {
+                        if let Some(status) = workflow.status.steps.get_mut(&step.name) {
+                            status.phase = WorkflowPhase::Skipped;
+                        }
+                        continue;
+                    }
+
+                    let count = step.ItemCount();
+                    let mut calls = Vec::new();
+                    for item in 0..count {
+                        let id = uuid::Uuid::new_v4().to_string();
+                        let parameters = Self::Parameters(&workflow, step, item);
+                        reqs.push(Self::NewCallReq(&workflow, step, &id, &parameters));
+                        inner.calls.insert(id.clone(), WorkflowCall {
+                            workflow: key.to_owned(),
+                            step: step.name.clone(),
+                            item: item,
+                        });
+                        calls.push(id);
+                    }
+
+                    if let Some(status) = workflow.status.steps.get_mut(&step.name) {
+                        status.phase = WorkflowPhase::Running;
+                        status.retries = vec![0; count];
+                        status.results = vec![None; count];
+                        status.calls = calls;
+                    }
+                }
+
+                if !changed {
+                    break;
+                }
+            }
+
+            if workflow.status.steps.values().all(|s| s.phase.IsFinished()) {
+                if workflow.status.steps.values().any(|s| s.phase == WorkflowPhase::Failed) {
+                    workflow.status.phase = WorkflowPhase::Failed;
+                } else {
+                    workflow.status.phase = WorkflowPhase::Succeeded;
+                }
+                info!("workflow {} finish with phase {:?}", key, workflow.status.phase);
+                finished = true;
+            } else {
+                inner.workflows.insert(key.to_owned(), workflow.clone());
+            }
+
+            workflow
+        };
+
+        FUNC_CALL_STORE.PutWorkflow(&workflow)?;
+        if finished {
+            FUNC_CALL_STORE.JobFinished(&workflow.status.jobId)?;
+        }
+
+        for req in reqs {
+            self.IssueCall(req)?;
+        }
+
+        return Ok(())
+    }
+
+    // whether the job is owned by a running workflow
+    pub fn HasJob(&self, jobId: &str) -> bool {
+        return self.lock().unwrap().workflows.values().any(|w| w.status.jobId == jobId);
+    }
+
+    pub fn IssueCall(&self, req: func::FuncSvcCallReq) -> Result<()> {
+        if !self.lock().unwrap().calls.contains_key(&req.id) {
+            // the workflow is removed before the retry
+            return Ok(())
+        }
+
+        let package = match FuncNode::GetPackage(&req.namespace, &req.package_name) {
+            None => {
+                let res = FuncRes::NewError(
+                    FuncErrSource::System,
+                    format!("FuncCall fail as package {}/{} doesn't exist", &req.namespace, &req.package_name)
+                );
+                return self.OnFuncCallResult(&req.id, res);
+            }
+            Some(p) => p,
+        };
+
+        AUDIT_AGENT.CreateFunc(
+            &req.id,
+            &req.job_id,
+            &req.namespace,
+            &req.package_name,
+            package.Revision(),
+            &req.func_name,
+            &req.caller_func_id
+        )?;
+
+        let funcCall = FuncCall::NewFromGrpc(&req, &package, FuncCallState::Scheduling(SystemTime::now()));
+        FUNC_CALL_MGR.Add(&funcCall);
+        FUNC_CALL_STORE.Put(&funcCall, FuncCallStage::Queued)?;
+        return FUNC_SVC_MGR.lock().unwrap().OnNewFuncCall(&funcCall);
+    }
+
+    // get the response of func call started by workflow
+    pub fn OnFuncCallResp(&self, resp: func::FuncSvcCallResp) -> Result<()> {
+        let res = match resp.res {
+            None => FuncRes::NewError(FuncErrSource::System, format!("get none func res")),
+            Some(res) => res.into(),
+        };
+
+        let funcCall = FUNC_CALL_MGR.Get(&resp.id);
+        if !self.lock().unwrap().calls.contains_key(&resp.id) {
+            // the workflow has not been started after func service restart,
+            // keep the result until it starts
+            if let Some(funcCall) = &funcCall {
+                let result = match &res {
+                    FuncRes::Resp(r) => FuncCallResult::Ok(r.clone()),
+                    FuncRes::Error(e) => FuncCallResult::Err(e.error.clone()),
+                };
+                FUNC_CALL_MGR.PendingResult(funcCall, result);
+            }
+            return Ok(())
+        }
+
+        if let Some(funcCall) = funcCall {
+            FUNC_CALL_STORE.Remove(&funcCall.package.Namespace(), &resp.id)?;
+            FUNC_CALL_MGR.Remove(&resp.id);
+        }
+
+        return self.OnFuncCallResult(&resp.id, res);
+    }
+
+    pub fn OnFuncCallResult(&self, id: &str, res: FuncRes) -> Result<()> {
+        let mut retry = None;
+        let mut cancels = Vec::new();
+        let (key, ret) = {
+            let mut inner = self.lock().unwrap();
+            let call = match inner.calls.remove(id) {
+                None => {
+                    // the workflow has been deleted
+                    return Ok(())
+                }
+                Some(c) => c,
+            };
+
+            if !inner.workflows.contains_key(&call.workflow) {
+                return Ok(())
+            }
+
+            let ret = Self::UpdateStep(&mut inner, &call, res, &mut retry, &mut cancels);
+            (call.workflow.clone(), ret)
+        };
+
+        if let Err(e) = ret {
+            return self.Fail(&key, e);
+        }
+
+        // the sibling waiting for retry is dropped by IssueCall as it is removed from the calls
+        for id in &cancels {
+            if let Err(e) = FUNC_SVC_MGR.CancelFuncCall(id, "workflow step fails") {
+                error!("workflow {} cancel func call {} fail with error {:?}", &key, id, e);
+            }
+        }
+
+        if let Some((req, backoffMs)) = retry {
+            let workflow = self.lock().unwrap().workflows.get(&key).cloned();
+            if let Some(workflow) = workflow {
+                FUNC_CALL_STORE.PutWorkflow(&workflow)?;
+            }
+
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(backoffMs)).await;
+                if let Err(e) = WORKFLOW_MGR.IssueCall(req) {
+                    error!("workflow retry func call fail with error {:?}", e);
+                }
+            });
+            return Ok(())
+        }
+
+        return self.Advance(&key);
+    }
+
+    // update the step status with the func call result, the status is checked as the persisted
+    // status of a recovered workflow might not match its spec
+    pub fn UpdateStep(
+        inner: &mut WorkflowMgrInner,
+        call: &WorkflowCall,
+        res: FuncRes,
+        retry: &mut Option<(func::FuncSvcCallReq, u64)>,
+        cancels: &mut Vec<String>
+    ) -> Result<()> {
+        let workflow = match inner.workflows.get_mut(&call.workflow) {
+            None => return Ok(()),
+            Some(w) => w,
+        };
+
+        let step = match workflow.Step(&call.step) {
+            None => return Err(Error::CommonError(format!("workflow step {} doesn't exist", &call.step))),
+            Some(s) => s.clone(),
+        };
+
+        let status = match workflow.status.steps.get_mut(&call.step) {
+            None => return Err(Error::CommonError(format!("workflow step {} has no status", &call.step))),
+            Some(s) => s,
+        };
+
+        let item = call.item;
+        if item >= status.calls.len() || item >= status.results.len() || item >= status.retries.len() {
+            return Err(Error::CommonError(format!("workflow step {} has no func call {}", &call.step, item)));
+        }
+
+        match res {
+            FuncRes::Resp(r) => {
+                status.results[item] = Some(r);
+                if status.results.iter().all(|r| r.is_some()) {
+                    status.phase = WorkflowPhase::Succeeded;
+                }
+            }
+            FuncRes::Error(e) => {
+                if status.retries[item] < step.retry.limit {
+                    status.retries[item] += 1;
+                    let id = uuid::Uuid::new_v4().to_string();
+                    status.calls[item] = id.clone();
+                    info!("workflow {} step {} retry {} with error {}",
+                        &call.workflow, &call.step, status.retries[item], &e.error);
+                    let parameters = Self::Parameters(workflow, &step, item);
+                    *retry = Some((Self::NewCallReq(workflow, &step, &id, &parameters), step.retry.backoffMs));
+                    inner.calls.insert(id, call.clone());
+                } else {
+                    status.error = Some(e.error.clone());
+                    status.phase = WorkflowPhase::Failed;
+                    // the step fails, the running fan out siblings are cancelled
+                    for i in 0..status.calls.len() {
+                        if i != item && status.results[i].is_none() {
+                            cancels.push(status.calls[i].clone());
+                        }
+                    }
+                    for id in cancels.iter() {
+                        inner.calls.remove(id);
+                    }
+                }
+            }
+        }
+
+        return Ok(())
+    }
+
+    // remove the func calls of the workflow, return their ids
+    pub fn RemoveCalls(inner: &mut WorkflowMgrInner, key: &str) -> Vec<String> {
+        let ids: Vec<String> = inner.calls.iter()
+            .filter(|(_, c)| c.workflow == key)
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in &ids {
+            inner.calls.remove(id);
+        }
+
+        return ids;
+    }
+
+    // fail the workflow whose status doesn't match its spec, only the workflow is affected
+    pub fn Fail(&self, key: &str, err: Error) -> Result<()> {
+        let (workflow, ids) = {
+            let mut inner = self.lock().unwrap();
+            let mut workflow = match inner.workflows.remove(key) {
+                None => return Ok(()),
+                Some(w) => w,
+            };
+
+            workflow.status.phase = WorkflowPhase::Failed;
+            (workflow, Self::RemoveCalls(&mut inner, key))
+        };
+
+        error!("workflow {} fails with error {:?}", key, err);
+        for id in &ids {
+            if let Err(e) = FUNC_SVC_MGR.CancelFuncCall(id, "workflow fails") {
+                error!("workflow {} cancel func call {} fail with error {:?}", key, id, e);
+            }
+        }
+
+        FUNC_CALL_STORE.PutWorkflow(&workflow)?;
+        return FUNC_CALL_STORE.JobFinished(&workflow.status.jobId);
+    }
+
+    pub fn Remove(&self, namespace: &str, name: &str) -> Result<()> {
+        let key = Self::Key(namespace, name);
+        let (jobId, ids) = {
+            let mut inner = self.lock().unwrap();
+            let workflow = match inner.workflows.remove(&key) {
+                None => return Ok(()),
+                Some(w) => w,
+            };
+
+            (workflow.status.jobId, Self::RemoveCalls(&mut inner, &key))
+        };
+
+        // the running func calls and their callees are cancelled, the results are dropped
+        for id in &ids {
+            FUNC_SVC_MGR.CancelFuncCall(id, "workflow is deleted")?;
+        }
+
+        return FUNC_CALL_STORE.JobFinished(&jobId);
+    }
+}
+
+impl EventHandler for WorkflowMgr {
+    fn handle(&self, _store: &ThreadSafeStore, event: &DeltaEvent) {
+        let obj = event.obj.clone();
+        let workflow : Workflow = match serde_json::from_str(&obj.data) {
+            Err(e) => {
+                error!("WorkflowMgr get invalid workflow {} with error {:?}", obj.Key(), e);
+                return;
+            }
+            Ok(w) => w,
+        };
+
+        match &event.type_{
+            EventType::Added => {
+                if let Err(e) = self.Start(workflow) {
+                    error!("WorkflowMgr start workflow {} fail with error {:?}", obj.Key(), e);
+                }
+            }
+            EventType::Deleted => {
+                if let Err(e) = self.Remove(&workflow.Namespace(), &workflow.Name()) {
+                    error!("WorkflowMgr remove workflow {} fail with error {:?}", obj.Key(), e);
+                }
+            }
+            // the status update is written by the func service itself
+            _ => (),
+        }
+    }
+}
@@ -25,6 +25,7 @@ use std::io::prelude::*;
 
 use qobjs::common::*;
 
+use crate::BLOB_REGISTRY;
 use crate::BLOB_SVC_CLIENT_MGR;
 
 use super::blob_store::BLOB_STORE;
@@ -44,6 +45,11 @@ pub struct BlobInner {
     pub state: BlobState,
     pub createTime: SystemTime,
     pub lastAccessTime: SystemTime,
+    // the job which owns the blob, empty if the blob is not removed with the job
+    #[serde(default)]
+    pub jobId: String,
+    #[serde(default)]
+    pub expireTime: Option<SystemTime>,
 }
 
 #[derive(Debug, Clone)]
@@ -64,7 +70,7 @@ impl From<BlobInner> for Blob {
 }
 
 impl Blob {
-    pub fn Create(namespace: &str, name: &str) -> Result<Self> {
+    pub fn Create(namespace: &str, name: &str, jobId: &str, expireTime: Option<SystemTime>) -> Result<Self> {
         let name = name.trim_start_matches("/").trim_end_matches("/");
         
         if namespace.contains("/") {
@@ -79,6 +85,8 @@ impl Blob {
             createTime: SystemTime::now(),
             lastAccessTime: SystemTime::now(),
             state: BlobState::Created,
+            jobId: jobId.to_string(),
+            expireTime: expireTime,
         };
 
         return Ok(Self(Arc::new(Mutex::new(inner))))
@@ -112,6 +120,13 @@ impl Blob {
         return inner.name.clone();
     }
 
+    pub fn IsExpired(&self, now: SystemTime) -> bool {
+        match self.lock().unwrap().expireTime {
+            None => return false,
+            Some(t) => return t <= now,
+        }
+    }
+
     pub fn Access(&self) {
         let mut inner = self.lock().unwrap();
         inner.lastAccessTime = SystemTime::now();
@@ -138,13 +153,15 @@ pub struct WriteBlob {
     pub file: File,
     pub size: usize,
     pub hasher: Option<Sha256>,
+    // the replica copied from other node has been counted in the namespace quota
+    pub checkQuota: bool,
 }
 
 impl Drop for WriteBlob {
     fn drop(&mut self) {
         if self.hasher.is_some() {
             error!("Blob {:?} dropped without seal", &self.blob);
-            BLOB_STORE.RemoveBlob(&self.blob.Namespace(), &self.blob.Name()).ok();
+            self.Discard();
         }  
     }
 }
@@ -157,10 +174,14 @@ impl WriteBlob {
             file: file,
             size: 0,
             hasher: Some(Sha256::new()),
+            checkQuota: true,
         }
     }
 
     pub fn Write(&mut self, buf: &[u8]) -> Result<()> {
+        if self.checkQuota {
+            BLOB_REGISTRY.CheckQuota(&self.blob.Namespace(), 0, (self.size + buf.len()) as u64)?;
+        }
         self.file.write_all(buf)?;
         match &mut self.hasher {
             None => return Err(Error::EINVAL(format!("WriteBlob::Write the blob has been sealled"))),
@@ -178,6 +199,32 @@ impl WriteBlob {
         self.blob.Seal(self.size, format!("{:?}", hash));
         return BLOB_STORE.Seal(self);
     }
+
+    // seal the blob only when its content matches the checksum, e.g. the replica copied
+    // from other node, so that a corrupted copy is never registered
+    pub fn SealWithChecksum(&mut self, checksum: &str) -> Result<()> {
+        let hash = match &self.hasher {
+            None => return Err(Error::EINVAL(format!("WriteBlob::SealWithChecksum the blob has been sealled"))),
+            Some(h) => format!("{:?}", h.clone().finalize()),
+        };
+
+        if &hash != checksum {
+            return Err(Error::CommonError(format!("WriteBlob blob {} checksum mismatch", self.blob.Address())));
+        }
+
+        return self.Seal();
+    }
+
+    // remove the unsealed blob which fails to be written
+    pub fn Discard(&mut self) {
+        if self.hasher.take().is_none() {
+            return;
+        }
+
+        if let Err(e) = BLOB_STORE.RemoveBlob(&self.blob.Namespace(), &self.blob.Name()) {
+            error!("WriteBlob discard blob {} fail with error {:?}", self.blob.Address(), e);
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -188,6 +188,8 @@ impl BlobSvcClient {
                     state: BlobState::Sealed,
                     createTime: SystemTimeProto::FromTimestamp(resp.create_time.as_ref().unwrap()).ToSystemTime(),
                     lastAccessTime: SystemTimeProto::FromTimestamp(resp.last_access_time.as_ref().unwrap()).ToSystemTime(),
+                    jobId: String::new(),
+                    expireTime: None,
                 };
 
                 let blob = Blob(Arc::new(Mutex::new(inner)));
@@ -0,0 +1,750 @@
+// Copyright (c) 2021 Quark Container Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+use std::time::SystemTime;
+use core::ops::Deref;
+use tokio::sync::Notify;
+use tokio::sync::mpsc;
+use tokio::sync::Mutex as TMutex;
+
+use qobjs::cacher_client::CacherClient;
+use qobjs::informer::EventHandler;
+use qobjs::informer_factory::InformerFactory;
+use qobjs::selection_predicate::ListOption;
+use qobjs::store::ThreadSafeStore;
+use qobjs::system_types::*;
+use qobjs::types::*;
+use qobjs::common::*;
+
+use crate::BLOB_REGISTRY;
+use crate::BLOB_SVC_CLIENT_MGR;
+use crate::blobstore::blob::{Blob, RemoteReadBlob, WriteBlob};
+use crate::blobstore::blob_store::BLOB_STORE;
+
+// the size of each read when copy a blob from another node
+pub const BLOB_REPLICATE_READ_SIZE: usize = 64 * 1024;
+// the interval to remove the expired blobs
+pub const BLOB_GC_INTERVAL: Duration = Duration::from_secs(60);
+// the retries of a location update which conflicts with the other nodes
+pub const BLOB_LOCATION_UPDATE_RETRY: usize = 10;
+// the interval to retry the registry messages which fail to write
+pub const BLOB_REGISTRY_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug)]
+pub enum BlobRegistryMsg {
+    Sealed(BlobLocation),
+    Removed(String, String), // namespace, name
+}
+
+impl BlobRegistryMsg {
+    // the blob of the message, a later message of the blob supersedes the earlier one
+    pub fn Key(&self) -> String {
+        match self {
+            Self::Sealed(location) => return format!("/{}/{}", location.Namespace(), &location.spec.name),
+            Self::Removed(namespace, name) => return format!("/{}/{}", namespace, name),
+        }
+    }
+}
+
+// the blob usage of a namespace in the cluster
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BlobUsage {
+    pub bytes: u64,
+    pub count: u64,
+}
+
+#[derive(Debug)]
+pub struct BlobRegistryInner {
+    pub closeNotify: Arc<Notify>,
+    pub stop: AtomicBool,
+
+    pub blobSvcAddr: String,
+    pub qmetaSvcAddr: String,
+    // unbounded as the blob store sends the messages after the blob is persisted, a full
+    // channel would lose the location update
+    pub agentChann: mpsc::UnboundedSender<BlobRegistryMsg>,
+
+    // the client for blob location lookup
+    pub client: TMutex<Option<CacherClient>>,
+    // the blobs being copied from other nodes
+    pub replicating: Mutex<BTreeSet<String>>,
+    // the blob policies of namespaces
+    pub policies: Mutex<BTreeMap<String, BlobPolicySpec>>,
+    // the blob usage of namespaces, calculated from the registered blob locations
+    pub usage: Mutex<BTreeMap<String, BlobUsage>>,
+    // the local blobs created but not registered yet, counted in the namespace count quota
+    pub reserved: Mutex<BTreeMap<String, BTreeSet<String>>>,
+}
+
+// register the location of the local blobs in the node manager so that the blobs
+// can be read by the func pods on other nodes, and copy the blobs to other nodes 
+// based on the replicas of the blob policy of the namespace
+#[derive(Debug, Clone)]
+pub struct BlobRegistry(Arc<BlobRegistryInner>);
+
+impl Deref for BlobRegistry {
+    type Target = Arc<BlobRegistryInner>;
+
+    fn deref(&self) -> &Arc<BlobRegistryInner> {
+        &self.0
+    }
+}
+
+impl BlobRegistry {
+    pub fn New(blobSvcAddr: &str, qmetaSvcAddr: &str) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let inner = BlobRegistryInner {
+            closeNotify: Arc::new(Notify::new()),
+            stop: AtomicBool::new(false),
+            blobSvcAddr: blobSvcAddr.to_string(),
+            qmetaSvcAddr: format!("http://{}", qmetaSvcAddr),
+            agentChann: tx,
+            client: TMutex::new(None),
+            replicating: Mutex::new(BTreeSet::new()),
+            policies: Mutex::new(BTreeMap::new()),
+            usage: Mutex::new(BTreeMap::new()),
+            reserved: Mutex::new(BTreeMap::new()),
+        };
+
+        let ret = Self(Arc::new(inner));
+        let clone = ret.clone();
+        tokio::spawn(async move {
+            clone.Process(rx).await.unwrap();
+        });
+
+        return ret;
+    }
+
+    pub fn Close(&self){
+        self.closeNotify.notify_waiters();
+        self.stop.store(true, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    pub fn Send(&self, msg: BlobRegistryMsg) -> Result<()> {
+        match self.agentChann.send(msg) {
+            Ok(()) => return Ok(()),
+            Err(_) => return Err(Error::MpscSendFail),
+        }
+    }
+
+    pub fn SealedLocation(blob: &Blob) -> BlobLocation {
+        let inner = blob.lock().unwrap();
+        let expireTimeSec = match inner.expireTime {
+            None => 0,
+            Some(t) => t.duration_since(SystemTime::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        };
+        return BlobLocation::New(
+            &inner.namespace, 
+            &inner.name, 
+            inner.size as u64, 
+            &inner.checksum, 
+            &inner.jobId, 
+            expireTimeSec
+        );
+    }
+
+    pub fn OnSealed(&self, blob: &Blob) -> Result<()> {
+        return self.Send(BlobRegistryMsg::Sealed(Self::SealedLocation(blob)));
+    }
+
+    pub fn OnRemoved(&self, namespace: &str, name: &str) -> Result<()> {
+        return self.Send(BlobRegistryMsg::Removed(namespace.to_owned(), name.to_owned()));
+    }
+
+    // reconcile the local blobs with the registry when node agent starts. The blob whose
+    // location was deleted when the node was down, by user or by the job gc, is removed.
+    // The blob which is not in its location is registered again
+    pub async fn Sync(&self) {
+        let client = loop {
+            match self.Client().await {
+                Ok(c) => break c,
+                Err(e) => {
+                    error!("BlobRegistry sync can't connect to qmeta service with error {:?}", e);
+                }
+            }
+
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        };
+
+        for blob in BLOB_STORE.SealedBlobs() {
+            let namespace = &blob.Namespace();
+            let name = &blob.Name();
+            let location = match Self::GetLocation(&client, namespace, name).await {
+                Err(e) => {
+                    error!("BlobRegistry sync get blob {} location fail with error {:?}", blob.Address(), e);
+                    continue;
+                }
+                Ok(l) => l,
+            };
+
+            match location {
+                None => {
+                    info!("BlobRegistry remove blob {} as its location is deleted", blob.Address());
+                    if let Err(e) = BLOB_STORE.RemoveBlob(namespace, name) {
+                        error!("BlobRegistry remove blob {} fail with error {:?}", blob.Address(), e);
+                    }
+                }
+                Some(l) => {
+                    if l.spec.locations.contains(&self.blobSvcAddr) {
+                        continue;
+                    }
+
+                    let msg = BlobRegistryMsg::Sealed(Self::SealedLocation(&blob));
+                    if let Err(e) = self.Send(msg) {
+                        error!("BlobRegistry sync blob {} fail with error {:?}", blob.Address(), e);
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    pub async fn Client(&self) -> Result<CacherClient> {
+        let mut client = self.client.lock().await;
+        if let Some(c) = &*client {
+            return Ok(c.clone());
+        }
+
+        let c = CacherClient::New(self.qmetaSvcAddr.clone()).await?;
+        *client = Some(c.clone());
+        return Ok(c)
+    }
+
+    pub async fn GetLocation(client: &CacherClient, namespace: &str, name: &str) -> Result<Option<BlobLocation>> {
+        match Self::GetLocationWithRev(client, namespace, name).await? {
+            None => return Ok(None),
+            Some((location, _)) => return Ok(Some(location)),
+        }
+    }
+
+    // the location and the revision of its object for the revision checked update
+    pub async fn GetLocationWithRev(client: &CacherClient, namespace: &str, name: &str) -> Result<Option<(BlobLocation, i64)>> {
+        let obj = match client.Get(QUARK_BLOB, namespace, &BlobLocation::ObjName(name), 0).await? {
+            None => return Ok(None),
+            Some(o) => o,
+        };
+
+        let location : BlobLocation = serde_json::from_str(&obj.data)?;
+        return Ok(Some((location, obj.Revision())));
+    }
+
+    // read-modify-write the blob location. The location is created when revision is 0, or
+    // updated only when its revision is not changed, so the concurrent updates of the other
+    // nodes are retried instead of overwritten. update returns false when there is nothing
+    // to write, and empty location is deleted
+    pub async fn UpdateLocation<F>(
+        &self,
+        client: &CacherClient,
+        namespace: &str,
+        name: &str,
+        mut update: F
+    ) -> Result<()>
+        where F: FnMut(Option<BlobLocation>) -> Option<BlobLocation>
+    {
+        let mut lastErr = Error::CommonError(format!("BlobRegistry update blob /{}/{} location fail", namespace, name));
+        for _ in 0..BLOB_LOCATION_UPDATE_RETRY {
+            let (location, revision) = match Self::GetLocationWithRev(client, namespace, name).await? {
+                None => (None, 0),
+                Some((l, rev)) => (Some(l), rev),
+            };
+
+            let location = match update(location) {
+                None => return Ok(()),
+                Some(l) => l,
+            };
+
+            let ret = if location.spec.locations.len() == 0 && location.spec.pending.len() == 0 {
+                if revision == 0 {
+                    return Ok(())
+                }
+                client.Delete(QUARK_BLOB, namespace, &BlobLocation::ObjName(name)).await.map(|_| ())
+            } else {
+                let data = serde_json::to_string(&location)?;
+                let dataObj = DataObject::NewFromK8sObj(QUARK_BLOB, &location.metadata, data);
+                if revision == 0 {
+                    client.Create(QUARK_BLOB, dataObj.Obj()).await.map(|_| ())
+                } else {
+                    client.UpdateWithRev(QUARK_BLOB, &dataObj, revision).await.map(|_| ())
+                }
+            };
+
+            match ret {
+                Ok(()) => return Ok(()),
+                Err(e) => {
+                    info!("BlobRegistry update blob /{}/{} location conflicts with error {:?}, retry", namespace, name, e);
+                    lastErr = e;
+                }
+            }
+        }
+
+        return Err(lastErr)
+    }
+
+    // the blob service addresses of the nodes which have the blob
+    pub async fn Lookup(&self, namespace: &str, name: &str) -> Result<Vec<String>> {
+        let name = name.trim_start_matches("/").trim_end_matches("/");
+        let client = self.Client().await?;
+        match Self::GetLocation(&client, namespace, name).await {
+            Err(e) => {
+                // the connection might be broken, reconnect next time
+                *self.client.lock().await = None;
+                return Err(e);
+            }
+            Ok(None) => {
+                return Err(Error::ENOENT(format!("BlobRegistry can't find blob /{}/{}", namespace, name)));
+            }
+            Ok(Some(location)) => return Ok(location.spec.locations),
+        }
+    }
+
+    pub async fn Replicas(client: &CacherClient, namespace: &str) -> Result<u32> {
+        match client.Get(QUARK_BLOB_POLICY, namespace, DEFAULT_BLOB_POLICY_NAME, 0).await? {
+            None => return Ok(BlobPolicySpec::DefaultReplicas()),
+            Some(obj) => {
+                let policy : BlobPolicy = serde_json::from_str(&obj.data)?;
+                return Ok(policy.spec.replicas)
+            }
+        }
+    }
+
+    pub fn Policy(&self, namespace: &str) -> BlobPolicySpec {
+        return self.policies.lock().unwrap().get(namespace).cloned().unwrap_or_default();
+    }
+
+    pub fn Usage(&self, namespace: &str) -> BlobUsage {
+        return self.usage.lock().unwrap().get(namespace).cloned().unwrap_or_default();
+    }
+
+    // reserve a slot of the namespace count quota for a new blob, the reservation is
+    // released when the blob location is registered or the blob is removed
+    pub fn ReserveBlob(&self, namespace: &str, name: &str) -> Result<()> {
+        let policy = self.Policy(namespace);
+        let mut reserved = self.reserved.lock().unwrap();
+        let names = reserved.entry(namespace.to_owned()).or_default();
+        if names.contains(name) {
+            return Ok(())
+        }
+
+        let count = self.Usage(namespace).count + names.len() as u64;
+        if policy.maxCount > 0 && count + 1 > policy.maxCount {
+            return Err(Error::CommonError(format!("namespace {} exceeds blob count quota {}", namespace, policy.maxCount)));
+        }
+
+        names.insert(name.to_owned());
+        return Ok(())
+    }
+
+    pub fn ReleaseBlob(&self, namespace: &str, name: &str) {
+        let mut reserved = self.reserved.lock().unwrap();
+        if let Some(names) = reserved.get_mut(namespace) {
+            names.remove(name);
+            if names.len() == 0 {
+                reserved.remove(namespace);
+            }
+        }
+    }
+
+    // check whether the namespace has quota for more blobs and bytes
+    pub fn CheckQuota(&self, namespace: &str, count: u64, bytes: u64) -> Result<()> {
+        let policy = self.Policy(namespace);
+        let usage = self.Usage(namespace);
+        if policy.maxCount > 0 && usage.count + count > policy.maxCount {
+            return Err(Error::CommonError(format!("namespace {} exceeds blob count quota {}", namespace, policy.maxCount)));
+        }
+
+        if policy.maxBytes > 0 && usage.bytes + bytes > policy.maxBytes {
+            return Err(Error::CommonError(format!("namespace {} exceeds blob bytes quota {}", namespace, policy.maxBytes)));
+        }
+
+        return Ok(())
+    }
+
+    // the blob service addresses of the other registered nodes
+    pub async fn Peers(&self, client: &CacherClient) -> Result<Vec<String>> {
+        let list = client.List(QUARK_NODE, "", &ListOption::default()).await?;
+        let mut peers = Vec::new();
+        for obj in &list.objs {
+            if let Some(addr) = obj.annotations.get(AnnotationNodeMgrBlobSvcAddr) {
+                if addr != &self.blobSvcAddr {
+                    peers.push(addr.clone());
+                }
+            }
+        }
+
+        return Ok(peers);
+    }
+
+    // select the nodes by rendezvous hashing so that the replicas of a blob are spread
+    pub fn SelectPeers(location: &BlobLocation, peers: Vec<String>, count: usize) -> Vec<String> {
+        let mut candidates = Vec::new();
+        for peer in peers {
+            if location.spec.locations.contains(&peer) || location.spec.pending.contains(&peer) {
+                continue;
+            }
+
+            let mut hasher = DefaultHasher::new();
+            location.Namespace().hash(&mut hasher);
+            location.spec.name.hash(&mut hasher);
+            peer.hash(&mut hasher);
+            candidates.push((hasher.finish(), peer));
+        }
+
+        candidates.sort();
+        return candidates.into_iter().rev().take(count).map(|(_, p)| p).collect();
+    }
+
+    pub async fn ProcessSealed(&self, client: &CacherClient, sealed: &BlobLocation) -> Result<()> {
+        let namespace = &sealed.Namespace();
+        let replicas = Self::Replicas(client, namespace).await? as usize;
+        let peers = self.Peers(client).await?;
+        return self.UpdateLocation(client, namespace, &sealed.spec.name, |location| {
+            let mut location = match location {
+                None => sealed.clone(),
+                Some(l) => l,
+            };
+
+            location.spec.pending.retain(|a| a != &self.blobSvcAddr);
+            if !location.spec.locations.contains(&self.blobSvcAddr) {
+                location.spec.locations.push(self.blobSvcAddr.clone());
+            }
+
+            let count = location.spec.locations.len() + location.spec.pending.len();
+            if replicas > count {
+                let mut selected = Self::SelectPeers(&location, peers.clone(), replicas - count);
+                location.spec.pending.append(&mut selected);
+            }
+
+            return Some(location);
+        }).await;
+    }
+
+    pub async fn ProcessRemoved(&self, client: &CacherClient, namespace: &str, name: &str) -> Result<()> {
+        return self.UpdateLocation(client, namespace, name, |location| {
+            let mut location = match location {
+                None => return None,
+                Some(l) => l,
+            };
+
+            location.spec.locations.retain(|a| a != &self.blobSvcAddr);
+            location.spec.pending.retain(|a| a != &self.blobSvcAddr);
+            return Some(location);
+        }).await;
+    }
+
+    pub async fn ProcessMsg(&self, client: &CacherClient, msg: &BlobRegistryMsg) -> Result<()> {
+        match msg {
+            BlobRegistryMsg::Sealed(location) => {
+                return self.ProcessSealed(client, location).await;
+            }
+            BlobRegistryMsg::Removed(namespace, name) => {
+                return self.ProcessRemoved(client, namespace, name).await;
+            }
+        }
+    }
+
+    pub async fn Connect(&self) -> CacherClient {
+        loop {
+            match CacherClient::New(self.qmetaSvcAddr.clone()).await {
+                Ok(c) => return c,
+                Err(e) => {
+                    error!("BlobRegistry can't connect to qmeta service with error {:?}", e);
+                }
+            }
+
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    }
+
+    pub async fn Process(&self, rx: mpsc::UnboundedReceiver<BlobRegistryMsg>) -> Result<()> {
+        let mut rx = rx;
+        let closeNotify = self.closeNotify.clone();
+        let mut cacheClient = self.Connect().await;
+        // the messages failed to write by blob, they are retried until written without
+        // blocking the messages of other blobs
+        let mut retries: BTreeMap<String, BlobRegistryMsg> = BTreeMap::new();
+        let mut interval = tokio::time::interval(BLOB_REGISTRY_RETRY_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = closeNotify.notified() => {
+                    return Ok(());
+                }
+                _ = interval.tick() => {
+                    if retries.len() == 0 {
+                        continue;
+                    }
+
+                    let keys: Vec<String> = retries.keys().cloned().collect();
+                    for key in keys {
+                        let msg = retries.remove(&key).unwrap();
+                        if let Err(e) = self.ProcessMsg(&cacheClient, &msg).await {
+                            error!("BlobRegistry retry {:?} fail with error {:?}", &msg, e);
+                            retries.insert(key, msg);
+                        }
+                    }
+
+                    if retries.len() > 0 {
+                        // the connection might be broken
+                        cacheClient = self.Connect().await;
+                    }
+                }
+                msg = rx.recv() => {
+                    let msg = match msg {
+                        None => {
+                            info!("blob registry finish");
+                            return Ok(());
+                        }
+                        Some(msg) => msg,
+                    };
+
+                    let key = msg.Key();
+                    retries.remove(&key);
+                    if let Err(e) = self.ProcessMsg(&cacheClient, &msg).await {
+                        error!("BlobRegistry process {:?} fail with error {:?}, retry", &msg, e);
+                        retries.insert(key, msg);
+                    }
+                }
+            }
+        }
+    }
+
+    // copy the blob from one of its locations to local blob store
+    pub async fn Replicate(&self, location: &BlobLocation) -> Result<()> {
+        let namespace = location.Namespace();
+        let name = location.Name();
+        let mut lastErr = Error::ENOENT(format!("BlobRegistry blob /{}/{} has no location", &namespace, &name));
+        for addr in &location.spec.locations {
+            match self.Copy(addr, location).await {
+                Ok(()) => {
+                    info!("BlobRegistry replicate blob /{}/{} from {}", &namespace, &name, addr);
+                    return Ok(())
+                }
+                Err(e) => {
+                    error!("BlobRegistry replicate blob /{}/{} from {} fail with error {:?}", &namespace, &name, addr, e);
+                    lastErr = e;
+                }
+            }
+        }
+
+        return Err(lastErr)
+    }
+
+    pub async fn Copy(&self, addr: &str, location: &BlobLocation) -> Result<()> {
+        let namespace = &location.Namespace();
+        let name = &location.Name();
+        let checksum = &location.spec.checksum;
+        let expireTime = if location.spec.expireTimeSec > 0 {
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(location.spec.expireTimeSec))
+        } else {
+            None
+        };
+
+        let mut remote = BLOB_SVC_CLIENT_MGR.Open(addr, namespace, name).await?;
+        let mut writeBlob = BLOB_STORE.CreateBlob(0, namespace, name, &location.spec.jobId, expireTime)?;
+        writeBlob.checkQuota = false;
+        let ret = Self::CopyData(addr, &mut remote, &mut writeBlob, checksum).await;
+        if ret.is_err() {
+            // remove the unsealed blob so that it can be copied again
+            writeBlob.Discard();
+        }
+
+        return ret;
+    }
+
+    pub async fn CopyData(addr: &str, remote: &mut RemoteReadBlob, writeBlob: &mut WriteBlob, checksum: &str) -> Result<()> {
+        loop {
+            let data = BLOB_SVC_CLIENT_MGR.Read(addr, remote.id, BLOB_REPLICATE_READ_SIZE).await?;
+            if data.len() == 0 {
+                break;
+            }
+            writeBlob.Write(&data)?;
+        }
+
+        remote.Close().await?;
+        return writeBlob.SealWithChecksum(checksum);
+    }
+
+    pub fn OnLocation(&self, location: BlobLocation) {
+        if !location.spec.pending.contains(&self.blobSvcAddr) {
+            return;
+        }
+
+        let key = format!("/{}/{}", location.Namespace(), location.Name());
+        if BLOB_STORE.Contains(&location.Namespace(), &location.Name()) {
+            // the blob has been copied
+            return;
+        }
+
+        if !self.replicating.lock().unwrap().insert(key.clone()) {
+            return;
+        }
+
+        let registry = self.clone();
+        tokio::spawn(async move {
+            if let Err(e) = registry.Replicate(&location).await {
+                error!("BlobRegistry replicate blob {} fail with error {:?}", &key, e);
+                // give up the replica so that the location won't wait for it
+                registry.OnRemoved(&location.Namespace(), &location.Name()).ok();
+            }
+            registry.replicating.lock().unwrap().remove(&key);
+        });
+    }
+}
+
+impl BlobRegistry {
+    pub fn OnBlobLocationEvent(&self, event: &DeltaEvent) -> Result<()> {
+        let location : BlobLocation = serde_json::from_str(&event.obj.data)?;
+        let namespace = location.Namespace();
+        match &event.type_ {
+            EventType::Added => {
+                {
+                    let mut usage = self.usage.lock().unwrap();
+                    let usage = usage.entry(namespace.clone()).or_default();
+                    usage.bytes += location.spec.size;
+                    usage.count += 1;
+                }
+
+                // the blob is counted in the usage now
+                self.ReleaseBlob(&namespace, &location.Name());
+            }
+            EventType::Modified => {
+                self.ReleaseBlob(&namespace, &location.Name());
+            }
+            EventType::Deleted => {
+                {
+                    let mut usage = self.usage.lock().unwrap();
+                    let usage = usage.entry(namespace.clone()).or_default();
+                    usage.bytes = usage.bytes.saturating_sub(location.spec.size);
+                    usage.count = usage.count.saturating_sub(1);
+                }
+
+                // the blob is removed by user or garbage collected with its job
+                if BLOB_STORE.Contains(&namespace, &location.Name()) {
+                    info!("BlobRegistry remove blob /{}/{} as its location is deleted", &namespace, &location.Name());
+                    BLOB_STORE.RemoveBlob(&namespace, &location.Name())?;
+                }
+                return Ok(())
+            }
+            _ => (),
+        }
+
+        self.OnLocation(location);
+        return Ok(())
+    }
+
+    pub fn OnBlobPolicyEvent(&self, event: &DeltaEvent) -> Result<()> {
+        let policy : BlobPolicy = serde_json::from_str(&event.obj.data)?;
+        if policy.metadata.name.as_deref() != Some(DEFAULT_BLOB_POLICY_NAME) {
+            return Ok(())
+        }
+
+        let namespace = policy.metadata.namespace.as_deref().unwrap_or("").to_string();
+        match &event.type_ {
+            EventType::Deleted => {
+                self.policies.lock().unwrap().remove(&namespace);
+            }
+            _ => {
+                self.policies.lock().unwrap().insert(namespace, policy.spec);
+            }
+        }
+
+        return Ok(())
+    }
+
+    // remove the expired local blobs
+    pub fn GcExpired(&self) {
+        for blob in BLOB_STORE.ExpiredBlobs() {
+            info!("BlobRegistry remove expired blob {}", blob.Address());
+            if let Err(e) = BLOB_STORE.RemoveBlob(&blob.Namespace(), &blob.Name()) {
+                error!("BlobRegistry remove expired blob {} fail with error {:?}", blob.Address(), e);
+            }
+        }
+    }
+}
+
+impl EventHandler for BlobRegistry {
+    fn handle(&self, _store: &ThreadSafeStore, event: &DeltaEvent) {
+        let ret = if event.obj.kind == QUARK_BLOB_POLICY {
+            self.OnBlobPolicyEvent(event)
+        } else {
+            self.OnBlobLocationEvent(event)
+        };
+
+        if let Err(e) = ret {
+            error!("BlobRegistry handle {} event fail with error {:?}", event.obj.Key(), e);
+        }
+    }
+}
+
+// watch the blob locations to copy the blobs assigned to this node, and the blob policies
+pub async fn BlobRegistryService() -> Result<()> {
+    // the sync waits for the registry queue, it doesn't block the startup
+    tokio::spawn(async move {
+        BLOB_REGISTRY.Sync().await;
+    });
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(BLOB_GC_INTERVAL);
+        loop {
+            interval.tick().await;
+            BLOB_REGISTRY.GcExpired();
+        }
+    });
+
+    let qmetaSvcAddr = BLOB_REGISTRY.qmetaSvcAddr.clone();
+    loop {
+        let factory = match InformerFactory::New(&qmetaSvcAddr, "").await {
+            Err(e) => {
+                error!("BlobRegistryService can't connect to qmeta service with error {:?}", e);
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                continue;
+            }
+            Ok(f) => f,
+        };
+
+        let mut informers = Vec::new();
+        for objType in [QUARK_BLOB_POLICY, QUARK_BLOB] {
+            let informer = match factory.AddInformer(objType, &ListOption::default()).await {
+                Err(e) => Err(e),
+                Ok(()) => factory.GetInformer(objType).await,
+            };
+
+            match informer {
+                Err(e) => {
+                    error!("BlobRegistryService can't watch {} with error {:?}", objType, e);
+                    break;
+                }
+                Ok(i) => informers.push(i),
+            }
+        }
+
+        if informers.len() < 2 {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            continue;
+        }
+
+        for informer in &informers {
+            let _id = informer.AddEventHandler(Arc::new(BLOB_REGISTRY.clone())).await?;
+        }
+        BLOB_REGISTRY.closeNotify.notified().await;
+        return Ok(())
+    }
+}
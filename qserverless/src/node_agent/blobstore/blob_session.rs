@@ -16,10 +16,13 @@ use std::collections::BTreeMap;
 use std::sync::Mutex;
 use std::sync::Arc;
 use std::ops::Deref;
+use std::time::Duration;
+use std::time::SystemTime;
 
 use qobjs::common::*;
 use qobjs::types::BLOB_LOCAL_HOST;
 
+use crate::BLOB_REGISTRY;
 use crate::BLOB_SVC_CLIENT_MGR;
 use crate::blobstore::blob::BlobHandler;
 use crate::blobstore::blob_store::BLOB_STORE;
@@ -61,9 +64,24 @@ impl BlobSession {
         return inner.lastBlobId;
     }
 
-    pub fn Create(&self, namespace: &str, name: &str) -> Result<u64> {
+    pub fn Create(&self, namespace: &str, name: &str, jobId: &str) -> Result<u64> {
+        BLOB_REGISTRY.ReserveBlob(namespace, name)?;
+        let policy = BLOB_REGISTRY.Policy(namespace);
+        let expireTime = if policy.ttlSec > 0 {
+            Some(SystemTime::now() + Duration::from_secs(policy.ttlSec))
+        } else {
+            None
+        };
+        let jobId = if policy.gcWithJob { jobId } else { "" };
+
         let id = self.NextBlobId();
-        let writeBlob = BLOB_STORE.CreateBlob(id, namespace, name)?;
+        let writeBlob = match BLOB_STORE.CreateBlob(id, namespace, name, jobId, expireTime) {
+            Err(e) => {
+                BLOB_REGISTRY.ReleaseBlob(namespace, name);
+                return Err(e);
+            }
+            Ok(b) => b,
+        };
         self.lock().unwrap().blobHandlers.insert(id, BlobHandler::NewWrite(writeBlob));
         return Ok(id)
     }
@@ -80,27 +98,99 @@ impl BlobSession {
     pub async fn Open(&self, svcAddr: &str, namespace: &str, name: &str) -> Result<(u64, Blob)> {
         let id = self.NextBlobId();
 
+        let mut tried = Vec::new();
         if svcAddr == BLOB_LOCAL_HOST || svcAddr == &self.BlobSvcAddr() {
-            let b = BLOB_STORE.Open(id, namespace, name)?;
-            let blob = b.blob.clone();
-            self.lock().unwrap().blobHandlers.insert(id, BlobHandler::NewRead(b));
-            return Ok((id, blob))
+            match BLOB_STORE.Open(id, namespace, name) {
+                Ok(b) => {
+                    let blob = b.blob.clone();
+                    self.lock().unwrap().blobHandlers.insert(id, BlobHandler::NewRead(b));
+                    return Ok((id, blob))
+                }
+                Err(e) => {
+                    // the blob might be written on another node
+                    info!("BlobSession open local blob /{}/{} fail with error {:?}, try remote", namespace, name, e);
+                }
+            }
         } else {
-            let b = BLOB_SVC_CLIENT_MGR.Open(svcAddr, namespace, name).await?;
-            let blob = b.blob.clone();
-            self.lock().unwrap().blobHandlers.insert(id, BlobHandler::NewRemoteRead(b));
-            return Ok((id, blob))
+            match self.OpenRemote(id, svcAddr, namespace, name).await {
+                Ok(ret) => return Ok(ret),
+                Err(e) => {
+                    // the node might be lost, try the replicas
+                    info!("BlobSession open blob /{}/{} on {} fail with error {:?}", namespace, name, svcAddr, e);
+                    tried.push(svcAddr.to_string());
+                }
+            }
         }
+
+        let mut lastErr = Error::ENOENT(format!("BlobSession blob /{}/{} doesn't exist", namespace, name));
+        for addr in BLOB_REGISTRY.Lookup(namespace, name).await? {
+            if addr == self.BlobSvcAddr() || tried.contains(&addr) {
+                continue;
+            }
+
+            match self.OpenRemote(id, &addr, namespace, name).await {
+                Ok(ret) => return Ok(ret),
+                Err(e) => lastErr = e,
+            }
+        }
+
+        return Err(lastErr)
     }
 
+    // open the blob in local blob store, used for the request from other nodes
+    pub fn OpenLocal(&self, namespace: &str, name: &str) -> Result<(u64, Blob)> {
+        let id = self.NextBlobId();
+        let b = BLOB_STORE.Open(id, namespace, name)?;
+        let blob = b.blob.clone();
+        self.lock().unwrap().blobHandlers.insert(id, BlobHandler::NewRead(b));
+        return Ok((id, blob))
+    }
+
+    pub async fn OpenRemote(&self, id: u64, svcAddr: &str, namespace: &str, name: &str) -> Result<(u64, Blob)> {
+        let b = BLOB_SVC_CLIENT_MGR.Open(svcAddr, namespace, name).await?;
+        let blob = b.blob.clone();
+        self.lock().unwrap().blobHandlers.insert(id, BlobHandler::NewRemoteRead(b));
+        return Ok((id, blob))
+    }
+
+    // delete the blob and all its replicas
     pub async fn Delete(&self, svcAddr: &str, namespace: &str, name: &str) -> Result<()> {
-        if svcAddr == BLOB_LOCAL_HOST || svcAddr == &self.BlobSvcAddr() {
-            BLOB_STORE.RemoveBlob(namespace, name)?;
-            return Ok(())
+        let mut addrs = match BLOB_REGISTRY.Lookup(namespace, name).await {
+            Err(_) => Vec::new(),
+            Ok(a) => a,
+        };
+
+        let svcAddr = if svcAddr == BLOB_LOCAL_HOST {
+            self.BlobSvcAddr()
         } else {
-            BLOB_SVC_CLIENT_MGR.Delete(svcAddr, namespace, name).await?;
-            return Ok(())
+            svcAddr.to_string()
+        };
+
+        if !addrs.contains(&svcAddr) {
+            addrs.push(svcAddr.clone());
+        }
+
+        for addr in &addrs {
+            let ret = if addr == &self.BlobSvcAddr() {
+                self.DeleteLocal(namespace, name)
+            } else {
+                BLOB_SVC_CLIENT_MGR.Delete(addr, namespace, name).await
+            };
+
+            if let Err(e) = ret {
+                if addr == &svcAddr {
+                    return Err(e);
+                }
+                // the replica might be lost with its node
+                error!("BlobSession delete blob /{}/{} replica on {} fail with error {:?}", namespace, name, addr, e);
+            }
         }
+
+        return Ok(())
+    }
+
+    pub fn DeleteLocal(&self, namespace: &str, name: &str) -> Result<()> {
+        return BLOB_STORE.RemoveBlob(namespace, name);
     }
 
     pub fn Get(&self, id: u64) -> Result<BlobHandler> {
@@ -16,12 +16,14 @@ use std::collections::BTreeMap;
 use std::sync::Mutex;
 use std::path::Path;
 use std::fs;
+use std::time::SystemTime;
 use rocksdb::{DB, Options, SingleThreaded, SliceTransform, IteratorMode};
 use rocksdb::DBWithThreadMode;
 
 use qobjs::common::*;
 
 use crate::NODEAGENT_CONFIG;
+use crate::BLOB_REGISTRY;
 use crate::blobstore::blob::{BlobState, BlobInner};
 use crate::blobstore::blob::{Blob, WriteBlob, ReadBlob};
 use crate::blobstore::blob_fs::BlobFs;
@@ -95,8 +97,8 @@ impl BlobStore {
         return Ok(store)
     }
 
-    pub fn CreateBlob(&self, id: u64, namespace: &str, name: &str) -> Result<WriteBlob> {
-        let blob = Blob::Create(namespace, name)?;
+    pub fn CreateBlob(&self, id: u64, namespace: &str, name: &str, jobId: &str, expireTime: Option<SystemTime>) -> Result<WriteBlob> {
+        let blob = Blob::Create(namespace, name, jobId, expireTime)?;
         match self.blobs.lock().unwrap().get(&blob.Address()) {
             None => (),
             Some(b) => {
@@ -130,9 +132,35 @@ impl BlobStore {
         //     return Err(Error::ENOENT(format!("Seal blob {} doesn't exist, it might be delete before seal", blob.Address())));
         // }
         self.db.lock().unwrap().put(blob.Address(), blob.ToString()?)?;
+        // the blob is sealed anyway, the send fails only when the registry is stopped and
+        // the blob is registered by the registry sync when the node agent restarts
+        if let Err(e) = BLOB_REGISTRY.OnSealed(blob) {
+            error!("BlobStore register sealed blob {} fail with error {:?}", blob.Address(), e);
+        }
         return Ok(())
     }
 
+    pub fn Contains(&self, namespace: &str, name: &str) -> bool {
+        let addr = match Blob::BuildAddr(namespace, name) {
+            Err(_) => return false,
+            Ok(a) => a,
+        };
+
+        return self.blobs.lock().unwrap().contains_key(&addr);
+    }
+
+    // the blobs whose ttl has passed
+    pub fn ExpiredBlobs(&self) -> Vec<Blob> {
+        let now = SystemTime::now();
+        let blobs = self.blobs.lock().unwrap();
+        return blobs.values().filter(|b| b.State() == BlobState::Sealed && b.IsExpired(now)).cloned().collect();
+    }
+
+    pub fn SealedBlobs(&self) -> Vec<Blob> {
+        let blobs = self.blobs.lock().unwrap();
+        return blobs.values().filter(|b| b.State() == BlobState::Sealed).cloned().collect();
+    }
+
     pub fn Open(&self, id: u64, namespace: &str, name: &str) -> Result<ReadBlob> {
         let addr = Blob::BuildAddr(namespace, name)?;
         let blob = match self.blobs.lock().unwrap().get(&addr) {
@@ -151,13 +179,19 @@ impl BlobStore {
     }
 
     pub fn RemoveBlob(&self, namespace: &str, name: &str) -> Result<()> {
+        BLOB_REGISTRY.ReleaseBlob(namespace, name);
         let addr = Blob::BuildAddr(namespace, name)?;
         if !self.db.lock().unwrap().key_may_exist(addr.clone()) {
             return Err(Error::ENOENT(format!("RemoveBlob blob {} doesn't exist", addr)));
         }
         self.db.lock().unwrap().delete(addr.clone())?;
+        self.blobs.lock().unwrap().remove(&addr);
         
         self.blobfs.Remove(&addr)?;
+        // the blob is removed anyway, the send fails only when the registry is stopped
+        if let Err(e) = BLOB_REGISTRY.OnRemoved(namespace, name) {
+            error!("BlobStore unregister removed blob {} fail with error {:?}", &addr, e);
+        }
         return Ok(());
     }
 }
\ No newline at end of file
@@ -76,7 +76,7 @@ impl BlogSvcSession {
         let msgId = msg.msg_id;
         let resp = match body {
             func::blob_svc_req::EventBody::BlobOpenReq(msg) => {
-                match self.blobSession.Open(&msg.svc_addr, &msg.namespace, &msg.name).await {
+                match self.blobSession.OpenLocal(&msg.namespace, &msg.name) {
                     Ok((id, b)) => {
                         let inner = b.lock().unwrap();
                         if &msg.svc_addr != &self.svcAddress {
@@ -118,7 +118,8 @@ impl BlogSvcSession {
                 }
             }
             func::blob_svc_req::EventBody::BlobDeleteReq(msg) => {
-                match self.blobSession.Delete(&msg.svc_addr, &msg.namespace, &msg.name).await {
+                // the replicas are deleted by the node which gets the request from func pod
+                match self.blobSession.DeleteLocal(&msg.namespace, &msg.name) {
                     Ok(()) => {
                         let resp = func::BlobDeleteResp {
                             ..Default::default()
@@ -18,3 +18,4 @@ pub mod blob_fs;
 pub mod blob_session;
 pub mod blob_svc;
 pub mod blob_client;
+pub mod blob_registry;
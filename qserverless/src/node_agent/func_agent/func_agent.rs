@@ -49,7 +49,9 @@ pub struct FuncCallInner {
     pub calleeNodeId: String,
     pub calleeFuncPodId: String,
     pub callType: i32,
-    
+    pub timeout: u64,
+    pub maxRetry: u32,
+    pub retryCount: u32,
 }
 
 impl FuncCallInner {
@@ -69,6 +71,9 @@ impl FuncCallInner {
             callee_node_id: self.calleeNodeId.clone(),
             callee_pod_id: self.calleeFuncPodId.clone(),
             call_type: self.callType,
+            timeout: self.timeout,
+            max_retry: self.maxRetry,
+            retry_count: self.retryCount,
         }
     }
 }
@@ -242,6 +247,9 @@ impl FuncAgent {
             priority: req.priority as usize,
             createTime: createTime,
             callType: req.call_type,
+            timeout: req.timeout,
+            maxRetry: req.max_retry,
+            retryCount: 0,
         };
 
         let funcCall = FuncCall(Arc::new(inner));
@@ -263,6 +271,9 @@ impl FuncAgent {
             callee_node_id: String::new(),
             callee_pod_id: String::new(),
             call_type: req.call_type,
+            timeout: req.timeout,
+            max_retry: req.max_retry,
+            retry_count: 0,
         };
 
         FUNC_SVC_CLIENT.get().unwrap().Send(func::FuncSvcMsg {
@@ -330,6 +341,9 @@ impl FuncAgent {
             priority: req.priority as usize,
             createTime: createTimeProto.ToSystemTime(),
             callType: req.call_type,
+            timeout: req.timeout,
+            maxRetry: req.max_retry,
+            retryCount: req.retry_count,
         }; 
 
         let funcCall = FuncCall(Arc::new(inner));
@@ -346,7 +360,9 @@ impl FuncAgent {
             caller_node_id: req.caller_node_id.clone(),
             caller_pod_id: req.caller_pod_id.clone(),
             caller_func_id: req.caller_func_id.clone(),
-            call_type: req.call_type
+            call_type: req.call_type,
+            timeout: req.timeout,
+            max_retry: req.max_retry,
         };
 
         funcPod.SetState(funcPodState::Running(funcCall));
@@ -51,6 +51,13 @@ impl funcPodState {
             Self::Running(funcCall) => funcCall.id.clone(),
         }
     }
+
+    pub fn JobId(&self) -> String {
+        match self {
+            Self::Idle => String::new(),
+            Self::Running(funcCall) => funcCall.jobId.clone(),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -136,6 +143,9 @@ impl FuncPod {
             priority: req.priority as usize,
             createTime: createTime,
             callType: req.call_type,
+            timeout: req.timeout,
+            maxRetry: req.max_retry,
+            retryCount: 0,
         };
 
         let funcCall = FuncCall(Arc::new(inner));
@@ -157,6 +167,9 @@ impl FuncPod {
             callee_node_id: String::new(),
             callee_pod_id: String::new(),
             call_type: req.call_type,
+            timeout: req.timeout,
+            max_retry: req.max_retry,
+            retry_count: 0,
         };
 
         FUNC_SVC_CLIENT.get().unwrap().Send(func::FuncSvcMsg {
@@ -350,7 +363,7 @@ impl FuncPod {
     }
 
     pub fn OnBlobCreateReq(&self, msgId: u64, msg: func::BlobCreateReq) -> Result<()> {
-        let resp = match self.blobSession.Create(&self.namespace, &msg.name) {
+        let resp = match self.blobSession.Create(&self.namespace, &msg.name, &self.State().JobId()) {
             Ok(id) => {
                 let resp = func::BlobCreateResp {
                     id: id,
@@ -65,6 +65,8 @@ use crate::runtime::network::*;
 use crate::cadvisor::client as CadvisorClient;
 use crate::cadvisor::provider::CadvisorInfoProvider;
 use crate::blobstore::blob_svc::BlobServiceGrpcService;
+use crate::blobstore::blob_registry::{BlobRegistry, BlobRegistryService};
+use crate::runtime::image_mgr::ImagePrefetchService;
 
 pub static RUNTIME_MGR: OnceCell<RuntimeMgr> = OnceCell::new();
 pub static IMAGE_MGR: OnceCell<ImageMgr> = OnceCell::new();
@@ -87,6 +89,10 @@ lazy_static! {
         BlobSvcClientMgr::default()
     };
 
+    pub static ref BLOB_REGISTRY : BlobRegistry = {
+        BlobRegistry::New(&NODEAGENT_CONFIG.BlobSvcAddr(), QMETASVC_ADDR)
+    };
+
     pub static ref NODE_CONFIG : NodeConfiguration = {
         NodeConfiguration::Default().unwrap()
     };
@@ -126,10 +132,14 @@ async fn main() -> QResult<()> {
     let funcAgentSvc = FuncAgentSvc();
     let nodeAgentSvc = NodeAgentSvc();
     let blobSvc = BlobServiceGrpcService(&blobSvcAddr);
+    let blobRegistrySvc = BlobRegistryService();
+    let imagePrefetchSvc = ImagePrefetchService();
     tokio::select! {
         _ = funcAgentSvc => (),
         _ = nodeAgentSvc => (),
-        _ = blobSvc => ()
+        _ = blobSvc => (),
+        _ = blobRegistrySvc => (),
+        _ = imagePrefetchSvc => ()
     }
 
     return Ok(())
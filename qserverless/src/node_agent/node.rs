@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::{Arc, RwLock};
 use std::sync::atomic::{AtomicBool, AtomicI64};
 use std::time::{SystemTime, Duration};
@@ -38,6 +38,8 @@ use crate::nm_svc::{NodeAgentMsg, PodCreate};
 use crate::node_status::{SetNodeStatus, IsNodeStatusReady};
 use crate::{pod::*, NODEAGENT_STORE, RUNTIME_MGR, ConfigName, NODEAGENT_CONFIG};
 use crate::NETWORK_PROVIDER;
+use crate::IMAGE_MGR;
+use crate::runtime::image_mgr::{IMAGE_GC_INTERVAL, ApplyDefaultImageTag};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum NodeAgentState {
@@ -174,6 +176,9 @@ impl NodeAgent {
 
     pub async fn Process(&self, mut rx: mpsc::Receiver<NodeAgentMsg>) -> Result<()> {
         let mut interval = time::interval(Duration::from_secs(5));
+        let mut imageGcInterval = time::interval(IMAGE_GC_INTERVAL);
+        // the image gc removes the images by cri calls, it runs out of the message loop
+        let mut imageGc: Option<tokio::task::JoinHandle<()>> = None;
         loop {
             tokio::select! {
                 _ = self.closeNotify.notified() => {
@@ -195,6 +200,28 @@ impl NodeAgent {
                         NODEAGENT_STORE.get().unwrap().UpdateNode(&self.node)?;
                     }
                 }
+                _ = imageGcInterval.tick() => {
+                    // the last gc is still removing the images
+                    if imageGc.as_ref().map(|gc| !gc.is_finished()).unwrap_or(false) {
+                        continue;
+                    }
+
+                    let mut inUse = BTreeSet::new();
+                    for pod in self.node.ActivePods() {
+                        for container in &pod.spec.as_ref().unwrap().containers {
+                            if let Some(image) = container.image.as_deref().and_then(ApplyDefaultImageTag) {
+                                inUse.insert(image);
+                            }
+                        }
+                    }
+
+                    let nodeConfig = self.node.nodeConfig.clone();
+                    imageGc = Some(tokio::spawn(async move {
+                        if let Err(e) = IMAGE_MGR.get().unwrap().GcImages(&inUse, &nodeConfig).await {
+                            error!("Node image gc fail with error {:?}", e);
+                        }
+                    }));
+                }
                 msg = rx.recv() => {
                     if let Some(msg) = msg {
                         self.NodeHandler(&msg).await?;
@@ -353,10 +380,31 @@ impl NodeAgent {
         return Ok(())
     }
 
-    pub fn ReadFuncLog(&self, namespace: &str, funcName: &str, _offset: usize, _len: usize) -> Result<String> {
+    // read the log from offset, at most FUNC_LOG_READ_MAX bytes, len 0 means the max
+    // the content is the lossy utf8 of the bytes read, the read byte count is returned
+    // for the next offset. an utf8 char cut by the end is left to the next read
+    pub fn ReadFuncLog(&self, namespace: &str, funcName: &str, offset: usize, len: usize) -> Result<(String, u64)> {
+        use std::io::{Read, Seek, SeekFrom};
+
+        let len = if len == 0 || len > FUNC_LOG_READ_MAX {
+            FUNC_LOG_READ_MAX
+        } else {
+            len
+        };
+
         let filename = format!("{}/func/{}/{}.log", DefaultNodeFuncLogFolder, namespace, funcName);
-        let content = std::fs::read_to_string(&filename)?;
-        return Ok(content)
+        let mut file = std::fs::File::open(&filename)?;
+        file.seek(SeekFrom::Start(offset as u64))?;
+        let mut buf = Vec::new();
+        file.take(len as u64).read_to_end(&mut buf)?;
+
+        if let Err(e) = std::str::from_utf8(&buf) {
+            if e.error_len().is_none() && e.valid_up_to() > 0 {
+                buf.truncate(e.valid_up_to());
+            }
+        }
+
+        return Ok((String::from_utf8_lossy(&buf).to_string(), buf.len() as u64))
     }
 
     pub fn BuildAQuarkPod(&self, state: PodState, pod: &k8s::Pod, configMap: &Option<k8s::ConfigMap>, isDaemon: bool) -> Result<QuarkPod> {
@@ -464,6 +512,12 @@ pub fn InitK8sNode(hostname: &str) -> Result<k8s::Node> {
 
     node.status.as_mut().unwrap().addresses = Some(NETWORK_PROVIDER.GetNetAddress());
 
+    // the peer node agents fetch the blob replicas through the address
+    node.metadata.annotations.as_mut().unwrap().insert(
+        AnnotationNodeMgrBlobSvcAddr.to_string(), 
+        NODEAGENT_CONFIG.BlobSvcAddr()
+    );
+
     return Ok(node)
 }
 
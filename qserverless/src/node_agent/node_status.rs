@@ -28,7 +28,7 @@ use qobjs::runtime_types;
 use crate::cadvisor::client::NodeCAdvisorInfo;
 use crate::node::QuarkNode;
 use crate::runtime::k8s_quantity::*;
-use crate::{NETWORK_PROVIDER, RUNTIME_MGR, CADVISOR_PROVIDER};
+use crate::{NETWORK_PROVIDER, RUNTIME_MGR, CADVISOR_PROVIDER, IMAGE_MGR};
 
 // NodeReady means kubelet is healthy and ready to accept pods.
 pub const NodeReady: &str = "Ready";
@@ -144,6 +144,12 @@ pub async fn SetNodeStatus(node: &QuarkNode) -> Result<()> {
 	conditions[condition.Type] = condition
     */
 
+    let imageMgr = IMAGE_MGR.get().unwrap();
+    let condition = UpdateNodeDiskPressureStatus(imageMgr.DiskPressure());
+    conditions.insert(condition.type_.clone(), condition);
+    let images = imageMgr.CachedImages().await;
+    node.node.lock().unwrap().status.as_mut().unwrap().images = Some(images);
+
     let currentTime = Time(Utc::now());
     conditions.insert(NodeReady.to_string(), k8s::NodeCondition {
         type_: NodeReady.to_string(),
@@ -159,6 +165,24 @@ pub async fn SetNodeStatus(node: &QuarkNode) -> Result<()> {
     return Ok(())
 }
 
+pub fn UpdateNodeDiskPressureStatus(diskPressure: bool) -> k8s::NodeCondition {
+    let currentTime = Time(Utc::now());
+    let (status, reason, message) = if diskPressure {
+        (ConditionTrue, "ImageCacheFull", "image usage exceeds the gc high threshold")
+    } else {
+        (ConditionFalse, "ImageCacheAvailable", "image usage is under the gc high threshold")
+    };
+
+    return k8s::NodeCondition {
+        type_: NodeDiskPressure.to_string(),
+        status: status.to_string(),
+        reason: Some(reason.to_owned()),
+        message: Some(message.to_owned()),
+        last_heartbeat_time: Some(currentTime.clone()),
+        last_transition_time: Some(currentTime),
+    };
+}
+
 pub fn UpdateNodeCapacity(nodeConfig: &NodeConfiguration, node: &mut k8s::Node) -> Result<()> {
     let info = CADVISOR_PROVIDER.get().unwrap().CAdvisorInfo();
     
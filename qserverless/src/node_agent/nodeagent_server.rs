@@ -289,9 +289,10 @@ impl NodeAgentServer {
                             ..Default::default()
                         }));
                     }
-                    Ok(content) => {
+                    Ok((content, readLen)) => {
                         return Ok(NmMsg::node_agent_resp::MessageBody::ReadFuncLogResp(NmMsg::ReadFuncLogResp{
                             content: content,
+                            read_len: readLen,
                             ..Default::default()
                         }));
                     }
@@ -12,7 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime};
 use tokio::sync::Mutex as TMutex;
 
 //use qobjs::core_types::{Container, PullPolicy};
@@ -20,14 +23,35 @@ use qobjs::k8s;
 use qobjs::crictl;
 use qobjs::common::*;
 use qobjs::k8s_util::*;
+use qobjs::types::*;
+use qobjs::config::NodeConfiguration;
+use qobjs::informer::EventHandler;
+use qobjs::informer_factory::InformerFactory;
+use qobjs::selection_predicate::ListOption;
+use qobjs::store::ThreadSafeStore;
+use qobjs::system_types::FuncPackage;
 
 use crate::cri::client::CriClient;
+use crate::IMAGE_MGR;
+
+// the interval to check the image usage and remove the least recently used images
+pub const IMAGE_GC_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone)]
+pub struct CachedImage {
+    pub image: crictl::Image,
+    pub lastUsed: SystemTime,
+}
 
 #[derive(Debug)]
 pub struct ImageMgr {
-    pub imageRefs: TMutex<BTreeMap<String, crictl::Image>>,
+    pub imageRefs: TMutex<BTreeMap<String, CachedImage>>,
     pub imageSvc: CriClient,
     pub authConfig: crictl::AuthConfig,
+    // package key to the images of the hot packages which are pre-pulled
+    pub hotPackages: Mutex<BTreeMap<String, Vec<String>>>,
+    // the image usage exceeds the gc high threshold
+    pub diskPressure: AtomicBool,
 }
 
 impl ImageMgr {
@@ -37,6 +61,8 @@ impl ImageMgr {
             imageRefs: TMutex::new(BTreeMap::new()),
             imageSvc: imageService,
             authConfig: authConfig,
+            hotPackages: Mutex::new(BTreeMap::new()),
+            diskPressure: AtomicBool::new(false),
         })
     }
 
@@ -48,16 +74,21 @@ impl ImageMgr {
             Some(l) => l,
         };
 
-        match self.imageRefs.lock().await.get(&imageWithTag) {
-            Some(image) => {
-                info!("Container image with tag {} already present on machine", &imageWithTag);
-                return Ok(image.clone());
+        return self.PullImage(&imageWithTag, Some(podSandboxConfig.clone())).await;
+    }
+
+    pub async fn PullImage(&self, imageWithTag: &str, podSandboxConfig: Option<crictl::PodSandboxConfig>) -> Result<crictl::Image> {
+        match self.imageRefs.lock().await.get_mut(imageWithTag) {
+            Some(cached) => {
+                info!("Container image with tag {} already present on machine", imageWithTag);
+                cached.lastUsed = SystemTime::now();
+                return Ok(cached.image.clone());
             }
             None => (),
         }
 
         let imageSpec = crictl::ImageSpec {
-            image: imageWithTag.clone(),
+            image: imageWithTag.to_string(),
             ..Default::default()
         };
 
@@ -70,7 +101,7 @@ impl ImageMgr {
         for img in images {
             let tags = &img.repo_tags;
             for t in tags {
-                if t.ends_with(&imageWithTag) {
+                if t.ends_with(imageWithTag) {
                     image = Some(img);
                     break;
                 }
@@ -78,19 +109,19 @@ impl ImageMgr {
         }
 
         if let Some(img) = image {
-            self.imageRefs.lock().await.insert(imageWithTag.clone(), img.clone());
+            self.AddImageRef(imageWithTag, &img).await;
             info!("Container image already present on machine image {:?} tag {}", &img, imageWithTag);
             return Ok(img)
         }
 
-        self.imageSvc.PullImage(Some(imageSpec), Some(self.authConfig.clone()), Some(podSandboxConfig.clone())).await?;
+        self.imageSvc.PullImage(Some(imageSpec), Some(self.authConfig.clone()), podSandboxConfig).await?;
 
         let mut image = None;
         let images = self.imageSvc.ListImages(Some(filter)).await?;
         for img in images {
             let tags = &img.repo_tags;
             for t in tags {
-                if t.starts_with(&imageWithTag) {
+                if t.starts_with(imageWithTag) {
                     image = Some(img);
                     break;
                 }
@@ -98,11 +129,211 @@ impl ImageMgr {
         }
 
         if let Some(img) = image {
-            self.imageRefs.lock().await.insert(imageWithTag.clone(), img.clone());
+            self.AddImageRef(imageWithTag, &img).await;
             return Ok(img)
         }
 
-        panic!("impossible")
+        return Err(Error::CommonError(format!("can't find image {} after pull", imageWithTag)));
+    }
+
+    pub async fn AddImageRef(&self, imageWithTag: &str, image: &crictl::Image) {
+        self.imageRefs.lock().await.insert(imageWithTag.to_string(), CachedImage {
+            image: image.clone(),
+            lastUsed: SystemTime::now(),
+        });
+    }
+
+    // the images cached on the node, reported in node status
+    pub async fn CachedImages(&self) -> Vec<k8s::ContainerImage> {
+        let refs = self.imageRefs.lock().await;
+        let mut images = Vec::new();
+        for (tag, cached) in refs.iter() {
+            let mut names = vec![tag.clone()];
+            for t in &cached.image.repo_tags {
+                if t != tag {
+                    names.push(t.clone());
+                }
+            }
+            images.push(k8s::ContainerImage {
+                names: Some(names),
+                size_bytes: Some(cached.image.size as i64),
+            });
+        }
+
+        return images;
+    }
+
+    pub fn DiskPressure(&self) -> bool {
+        return self.diskPressure.load(Ordering::SeqCst);
+    }
+
+    pub fn HotImages(&self) -> BTreeSet<String> {
+        let mut images = BTreeSet::new();
+        for list in self.hotPackages.lock().unwrap().values() {
+            for image in list {
+                images.insert(image.clone());
+            }
+        }
+
+        return images;
+    }
+
+    // the images of the package, with default tag applied
+    pub fn PackageImages(funcPackage: &FuncPackage) -> Vec<String> {
+        let mut images = Vec::new();
+        for container in &funcPackage.spec.template.containers {
+            if let Some(image) = container.image.as_deref().and_then(ApplyDefaultImageTag) {
+                images.push(image);
+            }
+        }
+
+        return images;
+    }
+
+    pub fn IsHot(funcPackage: &FuncPackage) -> bool {
+        match &funcPackage.metadata.annotations {
+            None => return false,
+            Some(annotations) => {
+                return annotations.get(AnnotationFuncPackageHot).map(|v| v == "true").unwrap_or(false);
+            }
+        }
+    }
+
+    // the images which can be removed, the least recently used first
+    // return: (last used time, image tag, image size)
+    pub fn GcCandidates(
+        imageRefs: &BTreeMap<String, CachedImage>,
+        inUse: &BTreeSet<String>,
+        hotImages: &BTreeSet<String>,
+    ) -> Vec<(SystemTime, String, u64)> {
+        let mut candidates : Vec<(SystemTime, String, u64)> = imageRefs.iter()
+            .filter(|(tag, _)| !inUse.contains(*tag) && !hotImages.contains(*tag))
+            .map(|(tag, cached)| (cached.lastUsed, tag.clone(), cached.image.size))
+            .collect();
+        candidates.sort();
+        return candidates;
+    }
+
+    pub fn OnPackageEvent(&self, event: &DeltaEvent) -> Result<()> {
+        let key = event.obj.Key();
+        let funcPackage : FuncPackage = serde_json::from_str(&event.obj.data)?;
+        let hot = match &event.type_ {
+            EventType::Added | EventType::Modified => Self::IsHot(&funcPackage),
+            _ => false,
+        };
+
+        if !hot {
+            self.hotPackages.lock().unwrap().remove(&key);
+            return Ok(())
+        }
+
+        let images = Self::PackageImages(&funcPackage);
+        self.hotPackages.lock().unwrap().insert(key.clone(), images.clone());
+        for image in images {
+            let key = key.clone();
+            tokio::spawn(async move {
+                info!("ImageMgr pre-pull image {} for hot package {}", &image, &key);
+                if let Err(e) = IMAGE_MGR.get().unwrap().PullImage(&image, None).await {
+                    error!("ImageMgr pre-pull image {} for package {} fail with error {:?}", &image, &key, e);
+                }
+            });
+        }
+
+        return Ok(())
+    }
+
+    // the bytes used by the images
+    pub async fn ImageUsage(&self) -> Result<u64> {
+        let mut used = 0;
+        for fs in self.imageSvc.ImageFsInfo().await? {
+            if let Some(bytes) = fs.used_bytes {
+                used += bytes.value;
+            }
+        }
+
+        return Ok(used);
+    }
+
+    // remove the least recently used images when the image usage exceeds the high threshold,
+    // the images in use by the pods on the node and the hot package images are kept.
+    // only the images pulled or looked up by the node agent are managed
+    pub async fn GcImages(&self, inUse: &BTreeSet<String>, config: &NodeConfiguration) -> Result<()> {
+        let mut used = self.ImageUsage().await?;
+        let high = config.ImageCacheLimit / 100 * config.ImageGCHighThresholdPercent;
+        let low = config.ImageCacheLimit / 100 * config.ImageGCLowThresholdPercent;
+        self.diskPressure.store(used > high, Ordering::SeqCst);
+        if used <= high {
+            return Ok(())
+        }
+
+        let hotImages = self.HotImages();
+        let candidates = Self::GcCandidates(&*self.imageRefs.lock().await, inUse, &hotImages);
+        for (_, tag, size) in candidates {
+            if used <= low {
+                break;
+            }
+
+            info!("ImageMgr gc image {} with size {}, image usage {} exceeds {}", &tag, size, used, low);
+            let imageSpec = crictl::ImageSpec {
+                image: tag.clone(),
+                ..Default::default()
+            };
+            if let Err(e) = self.imageSvc.RemoveImage(Some(imageSpec)).await {
+                error!("ImageMgr gc image {} fail with error {:?}", &tag, e);
+                continue;
+            }
+
+            self.imageRefs.lock().await.remove(&tag);
+            used = used.saturating_sub(size);
+        }
+
+        self.diskPressure.store(used > high, Ordering::SeqCst);
+        return Ok(())
+    }
+}
+
+// pre-pull the images of the hot packages
+pub struct PackageImageHandler {}
+
+impl EventHandler for PackageImageHandler {
+    fn handle(&self, _store: &ThreadSafeStore, event: &DeltaEvent) {
+        if let Err(e) = IMAGE_MGR.get().unwrap().OnPackageEvent(event) {
+            error!("ImageMgr handle package {} event fail with error {:?}", event.obj.Key(), e);
+        }
+    }
+}
+
+// watch the packages to pre-pull the images of the hot packages
+pub async fn ImagePrefetchService() -> Result<()> {
+    crate::NODE_READY_NOTIFY.notified().await;
+    let qmetaSvcAddr = format!("http://{}", QMETASVC_ADDR);
+    loop {
+        let factory = match InformerFactory::New(&qmetaSvcAddr, "").await {
+            Err(e) => {
+                error!("ImagePrefetchService can't connect to qmeta service with error {:?}", e);
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                continue;
+            }
+            Ok(f) => f,
+        };
+
+        let informer = match factory.AddInformer("package", &ListOption::default()).await {
+            Err(e) => Err(e),
+            Ok(()) => factory.GetInformer("package").await,
+        };
+
+        let informer = match informer {
+            Err(e) => {
+                error!("ImagePrefetchService can't watch package with error {:?}", e);
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                continue;
+            }
+            Ok(i) => i,
+        };
+
+        let _id = informer.AddEventHandler(std::sync::Arc::new(PackageImageHandler {})).await?;
+        // keep the informer running
+        std::future::pending::<()>().await;
     }
 }
 
@@ -185,3 +416,68 @@ pub fn splitDockerDomain(name: &str) -> (String, String) {
     return (domain.to_string(), reminder.to_string())
 }
 */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn Cached(size: u64, lastUsed: u64) -> CachedImage {
+        return CachedImage {
+            image: crictl::Image {
+                size: size,
+                ..Default::default()
+            },
+            lastUsed: SystemTime::UNIX_EPOCH + Duration::from_secs(lastUsed),
+        };
+    }
+
+    #[test]
+    fn TestGcCandidates() {
+        let mut refs = BTreeMap::new();
+        refs.insert("a:latest".to_string(), Cached(1, 30));
+        refs.insert("b:latest".to_string(), Cached(2, 10));
+        refs.insert("c:latest".to_string(), Cached(3, 20));
+        refs.insert("d:latest".to_string(), Cached(4, 5));
+
+        let inUse = BTreeSet::from(["c:latest".to_string()]);
+        let hot = BTreeSet::from(["d:latest".to_string()]);
+        let tags : Vec<String> = ImageMgr::GcCandidates(&refs, &inUse, &hot)
+            .into_iter()
+            .map(|(_, tag, _)| tag)
+            .collect();
+        assert_eq!(tags, vec!["b:latest".to_string(), "a:latest".to_string()]);
+    }
+
+    #[test]
+    fn TestHotPackageImages() {
+        let mut funcPackage = FuncPackage::default();
+        assert!(!ImageMgr::IsHot(&funcPackage));
+
+        funcPackage.metadata.annotations = Some(BTreeMap::from([
+            (AnnotationFuncPackageHot.to_string(), "true".to_string()),
+        ]));
+        assert!(ImageMgr::IsHot(&funcPackage));
+
+        funcPackage.spec.template.containers = vec![
+            k8s::Container {
+                image: Some("nginx".to_string()),
+                ..Default::default()
+            },
+            k8s::Container {
+                image: Some("docker.io/redis:7".to_string()),
+                ..Default::default()
+            },
+        ];
+        assert_eq!(
+            ImageMgr::PackageImages(&funcPackage),
+            vec!["nginx:latest".to_string(), "docker.io/redis:7".to_string()]
+        );
+    }
+
+    #[test]
+    fn TestApplyDefaultImageTag() {
+        assert_eq!(ApplyDefaultImageTag("nginx"), Some("nginx:latest".to_string()));
+        assert_eq!(ApplyDefaultImageTag("nginx:1.25"), Some("nginx:1.25".to_string()));
+        assert_eq!(ApplyDefaultImageTag("docker.io/library/nginx"), Some("docker.io/library/nginx:latest".to_string()));
+    }
+}
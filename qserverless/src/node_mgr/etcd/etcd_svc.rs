@@ -187,7 +187,12 @@ impl EtcdSvc {
             }
             Some(o) => {
                 let dataObj: DataObject = o.into();
-                match cacher.Update(&dataObj).await {
+                let res = if req.expect_rev > 0 {
+                    cacher.UpdateWithRev(&dataObj, req.expect_rev).await
+                } else {
+                    cacher.Update(&dataObj).await
+                };
+                match res {
                     Err(e) => {
                         return Ok(Response::new(UpdateResponseMessage {
                             error: format!("Fail: {:?}", e),
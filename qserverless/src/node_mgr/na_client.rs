@@ -242,11 +242,13 @@ impl NodeAgentClient {
         return Ok(())
     }
 
-    pub async fn ReadFuncLog(&self, namespace: &str, funcId: &str) -> Result<String> {
+    // return the log content and the bytes read
+    pub async fn ReadFuncLog(&self, namespace: &str, funcId: &str, offset: u64, len: u32) -> Result<(String, u64)> {
         let req = NmMsg::ReadFuncLogReq {
             namespace: namespace.to_owned(),
             func_name: funcId.to_owned(),
-            ..Default::default()
+            offset: offset,
+            len: len,
         };
 
         let ret = self.Call(NmMsg::node_agent_req::MessageBody::ReadFuncLogReq(req)).await?;
@@ -256,7 +258,7 @@ impl NodeAgentClient {
                     return Err(Error::CommonError(resp.error))
                 }
 
-                return Ok(resp.content)
+                return Ok((resp.content, resp.read_len))
             }
             _ => panic!("ReadFuncLog wrong response type"),
         }
@@ -21,13 +21,13 @@ extern crate reqwest;
 extern crate log;
 extern crate simple_logging;
 
+use std::sync::Arc;
 use once_cell::sync::OnceCell;
 use lazy_static::lazy_static;
 
 use nm_store::NodeMgrCache;
-use qobjs::audit::func_audit::SqlFuncAudit;
+use qobjs::audit::func_audit::*;
 use qobjs::common::Result as QResult;
-use qobjs::types::AUDITDB_ADDR;
 
 pub mod nm_svc;
 pub mod na_client;
@@ -40,13 +40,13 @@ use crate::nm_svc::*;
 use crate::etcd::svc_dir::*;
 
 pub static NM_CACHE : OnceCell<NodeMgrCache> = OnceCell::new();
-pub static FUNCAUDIT: OnceCell<SqlFuncAudit> = OnceCell::new();
+pub static FUNCAUDIT: OnceCell<Arc<dyn FuncAudit>> = OnceCell::new();
 
 pub const VERSION: &str = "0.1";
 
 lazy_static! {
     pub static ref SVC_DIR: SvcDir = SvcDir::default();
-    pub static ref CACHE_OBJ_TYPES: Vec<&'static str> = vec!["pod", "podset", "package"];
+    pub static ref CACHE_OBJ_TYPES: Vec<&'static str> = vec!["pod", "podset", "package", "funccall", "workflow", "blob", "blobpolicy"];
 }
 
 #[tokio::main]
@@ -54,7 +54,8 @@ async fn main() -> QResult<()> {
     log4rs::init_file("logging_config.yaml", Default::default()).unwrap();
     
     NM_CACHE.set(NodeMgrCache::New().await.unwrap()).unwrap();
-    FUNCAUDIT.set(SqlFuncAudit::New(AUDITDB_ADDR).await.unwrap()).unwrap();
+    // func svc writes the audits, node mgr only queries them
+    FUNCAUDIT.set(NewFuncAudit(&AuditDbAddr(), true).await.unwrap()).unwrap();
 
     SVC_DIR.write().await.Init("localhost:2379").await?;
     GrpcService().await.unwrap();
@@ -188,7 +188,18 @@ impl NodeMgrCache {
     }
 
     // workaround scheduler, schedule pod to node one by one
-    pub fn SchedulePod(&self, _pod: &k8s::Pod) -> Option<String> {
+    pub fn SchedulePod(&self, pod: &k8s::Pod) -> Option<String> {
+        // the func service has placed the pod, honour it when the node is ready
+        if let Some(annotations) = &pod.metadata.annotations {
+            if let Some(node) = annotations.get(AnnotationNodeMgrNode) {
+                if self.read().unwrap().nodeAgents.contains_key(node) {
+                    return Some(node.clone());
+                }
+
+                info!("SchedulePod placed node {} is not ready, fallback to default schedule", node);
+            }
+        }
+
         let pods = self.GetCacher(QUARK_POD).unwrap();
         let podCount = pods.Count();
         let nodes : Vec<String> = self.read().unwrap().nodeAgents.keys().cloned().collect();
@@ -326,7 +337,7 @@ impl NodeMgrCache {
         }
     }
 
-    pub async fn ReadFuncLog(&self, namespace: &str, funcId: &str) -> Result<String> {
+    pub async fn ReadFuncLog(&self, namespace: &str, funcId: &str, offset: u64, len: u32) -> Result<(String, u64)> {
         let nodeId = FUNCAUDIT.get().unwrap().GetNode(namespace, funcId).await?;
         let nodeId = format!("{}/{}", DefaultDomainName, nodeId);
         let nodeAgentClient = self.read().unwrap().nodeAgents.get(&nodeId).cloned();
@@ -335,7 +346,7 @@ impl NodeMgrCache {
                 return Err(Error::CommonError(format!("Can't find node with name {}", nodeId)));
             }
             Some(client) => {
-                return client.ReadFuncLog(namespace, funcId).await;
+                return client.ReadFuncLog(namespace, funcId, offset, len).await;
             }
         }
     }
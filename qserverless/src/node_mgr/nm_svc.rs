@@ -31,11 +31,13 @@ use qobjs::selection_predicate::*;
 use qobjs::selector::*;
 use qobjs::types::*;
 use qobjs::qmeta as qmeta;
+use qobjs::audit::func_audit::FuncAuditQuery;
 
 use crate::VERSION;
 use crate::na_client::*;
 use crate::etcd::etcd_svc::EtcdSvc;
 use crate::SVC_DIR;
+use crate::FUNCAUDIT;
 
 #[derive(Debug)]
 pub struct NodeMgrSvcInner {
@@ -554,16 +556,63 @@ impl qmeta::q_meta_service_server::QMetaService for NodeMgrSvc {
         request: tonic::Request<qmeta::ReadFuncLogReq>,
     ) -> SResult<tonic::Response<qmeta::ReadFuncLogResp>, tonic::Status> {
         let req = request.get_ref();
-        match crate::NM_CACHE.get().unwrap().ReadFuncLog(&req.namespace, &req.func_name).await {
+        match crate::NM_CACHE.get().unwrap().ReadFuncLog(&req.namespace, &req.func_name, req.offset, req.len).await {
             Err(e) => {
                 return Ok(Response::new(qmeta::ReadFuncLogResp {
                     error: format!("read_func_log fail with error {:?}", e),
                     ..Default::default()
                 }));
             }
-            Ok(content) =>  {
+            Ok((content, readLen)) =>  {
                 return Ok(Response::new(qmeta::ReadFuncLogResp {
                     content: content,
+                    read_len: readLen,
+                    ..Default::default()
+                }));
+            }
+        }
+    }
+
+    async fn read_func_audit(
+        &self,
+        request: tonic::Request<qmeta::ReadFuncAuditReq>,
+    ) -> SResult<tonic::Response<qmeta::ReadFuncAuditResp>, tonic::Status> {
+        let req = request.get_ref();
+        let query = FuncAuditQuery {
+            namespace: req.namespace.clone(),
+            jobId: req.job_id.clone(),
+            startTime: req.start_time,
+            endTime: req.end_time,
+            limit: req.limit as usize,
+        };
+
+        match FUNCAUDIT.get().unwrap().ReadFuncAudit(&query).await {
+            Err(e) => {
+                return Ok(Response::new(qmeta::ReadFuncAuditResp {
+                    error: format!("read_func_audit fail with error {:?}", e),
+                    ..Default::default()
+                }));
+            }
+            Ok(records) => {
+                let mut audits = Vec::new();
+                for r in records {
+                    audits.push(qmeta::FuncAudit {
+                        id: r.id,
+                        job_id: r.jobId,
+                        namespace: r.namespace,
+                        package_name: r.packageName,
+                        revision: r.revision,
+                        func_name: r.funcName,
+                        caller_func_id: r.callerFuncId,
+                        func_state: r.funcState,
+                        node_id: r.nodeId,
+                        create_time: r.createTime,
+                        assigned_time: r.assignedTime,
+                        finish_time: r.finishTime,
+                    });
+                }
+                return Ok(Response::new(qmeta::ReadFuncAuditResp {
+                    audits: audits,
                     ..Default::default()
                 }));
             }
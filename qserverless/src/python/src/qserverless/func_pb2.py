@@ -13,15 +13,15 @@ _sym_db = _symbol_database.Default()
 
 
 
-DESCRIPTOR = _descriptor_pool.Default().AddSerializedFile(b'\n\nfunc.proto\x12\x04\x66unc\"\x85\x02\n\nBlobSvcReq\x12\r\n\x05msgId\x18\x01 \x01(\x04\x12)\n\x0b\x42lobOpenReq\x18\xf5\x03 \x01(\x0b\x32\x11.func.BlobOpenReqH\x00\x12)\n\x0b\x42lobReadReq\x18\xf9\x03 \x01(\x0b\x32\x11.func.BlobReadReqH\x00\x12)\n\x0b\x42lobSeekReq\x18\xfb\x03 \x01(\x0b\x32\x11.func.BlobSeekReqH\x00\x12+\n\x0c\x42lobCloseReq\x18\x81\x04 \x01(\x0b\x32\x12.func.BlobCloseReqH\x00\x12-\n\rBlobDeleteReq\x18\x83\x04 \x01(\x0b\x32\x13.func.BlobDeleteReqH\x00\x42\x0b\n\tEventBody\"\x90\x02\n\x0b\x42lobSvcResp\x12\r\n\x05msgId\x18\x01 \x01(\x04\x12+\n\x0c\x42lobOpenResp\x18\xf6\x03 \x01(\x0b\x32\x12.func.BlobOpenRespH\x00\x12+\n\x0c\x42lobReadResp\x18\xfa\x03 \x01(\x0b\x32\x12.func.BlobReadRespH\x00\x12+\n\x0c\x42lobSeekResp\x18\xfc\x03 \x01(\x0b\x32\x12.func.BlobSeekRespH\x00\x12-\n\rBlobCloseResp\x18\x82\x04 \x01(\x0b\x32\x13.func.BlobCloseRespH\x00\x12/\n\x0e\x42lobDeleteResp\x18\x84\x04 \x01(\x0b\x32\x14.func.BlobDeleteRespH\x00\x42\x0b\n\tEventBody\"\xe1\x07\n\x0c\x46uncAgentMsg\x12\r\n\x05msgId\x18\x01 \x01(\x04\x12\x36\n\x12\x46uncPodRegisterReq\x18\x64 \x01(\x0b\x32\x18.func.FuncPodRegisterReqH\x00\x12\x39\n\x13\x46uncPodRegisterResp\x18\xc8\x01 \x01(\x0b\x32\x19.func.FuncPodRegisterRespH\x00\x12\x33\n\x10\x46uncAgentCallReq\x18\xac\x02 \x01(\x0b\x32\x16.func.FuncAgentCallReqH\x00\x12\x35\n\x11\x46uncAgentCallResp\x18\x90\x03 \x01(\x0b\x32\x17.func.FuncAgentCallRespH\x00\x12\x33\n\x10\x46uncAgentCallAck\x18\x91\x03 \x01(\x0b\x32\x16.func.FuncAgentCallAckH\x00\x12)\n\x0b\x42lobOpenReq\x18\xf5\x03 \x01(\x0b\x32\x11.func.BlobOpenReqH\x00\x12+\n\x0c\x42lobOpenResp\x18\xf6\x03 \x01(\x0b\x32\x12.func.BlobOpenRespH\x00\x12-\n\rBlobCreateReq\x18\xf7\x03 \x01(\x0b\x32\x13.func.BlobCreateReqH\x00\x12/\n\x0e\x42lobCreateResp\x18\xf8\x03 \x01(\x0b\x32\x14.func.BlobCreateRespH\x00\x12)\n\x0b\x42lobReadReq\x18\xf9\x03 \x01(\x0b\x32\x11.func.BlobReadReqH\x00\x12+\n\x0c\x42lobReadResp\x18\xfa\x03 \x01(\x0b\x32\x12.func.BlobReadRespH\x00\x12)\n\x0b\x42lobSeekReq\x18\xfb\x03 \x01(\x0b\x32\x11.func.BlobSeekReqH\x00\x12+\n\x0c\x42lobSeekResp\x18\xfc\x03 \x01(\x0b\x32\x12.func.BlobSeekRespH\x00\x12+\n\x0c\x42lobWriteReq\x18\xfd\x03 \x01(\x0b\x32\x12.func.BlobWriteReqH\x00\x12-\n\rBlobWriteResp\x18\xfe\x03 \x01(\x0b\x32\x13.func.BlobWriteRespH\x00\x12+\n\x0c\x42lobCloseReq\x18\x81\x04 \x01(\x0b\x32\x12.func.BlobCloseReqH\x00\x12-\n\rBlobCloseResp\x18\x82\x04 \x01(\x0b\x32\x13.func.BlobCloseRespH\x00\x12-\n\rBlobDeleteReq\x18\x83\x04 \x01(\x0b\x32\x13.func.BlobDeleteReqH\x00\x12/\n\x0e\x42lobDeleteResp\x18\x84\x04 \x01(\x0b\x32\x14.func.BlobDeleteRespH\x00\x12!\n\x07\x46uncMsg\x18\x85\x04 \x01(\x0b\x32\r.func.FuncMsgH\x00\x42\x0b\n\tEventBody\"?\n\x0b\x42lobOpenReq\x12\x0f\n\x07svcAddr\x18\x02 \x01(\t\x12\x11\n\tnamespace\x18\x03 \x01(\t\x12\x0c\n\x04name\x18\x04 \x01(\t\"\xb8\x01\n\x0c\x42lobOpenResp\x12\n\n\x02id\x18\x02 \x01(\x04\x12\x11\n\tnamespace\x18\x03 \x01(\t\x12\x0c\n\x04name\x18\x04 \x01(\t\x12\x0c\n\x04size\x18\x05 \x01(\x04\x12\x10\n\x08\x63hecksum\x18\x06 \x01(\t\x12#\n\ncreateTime\x18\x07 \x01(\x0b\x32\x0f.func.Timestamp\x12\'\n\x0elastAccessTime\x18\x08 \x01(\x0b\x32\x0f.func.Timestamp\x12\r\n\x05\x65rror\x18\t \x01(\t\"A\n\rBlobDeleteReq\x12\x0f\n\x07svcAddr\x18\x02 \x01(\t\x12\x11\n\tnamespace\x18\x03 \x01(\t\x12\x0c\n\x04name\x18\x04 \x01(\t\"\x1f\n\x0e\x42lobDeleteResp\x12\r\n\x05\x65rror\x18\x01 \x01(\t\"0\n\rBlobCreateReq\x12\x11\n\tnamespace\x18\x03 \x01(\t\x12\x0c\n\x04name\x18\x04 \x01(\t\"<\n\x0e\x42lobCreateResp\x12\n\n\x02id\x18\x02 \x01(\x04\x12\x0f\n\x07svcAddr\x18\x03 \x01(\t\x12\r\n\x05\x65rror\x18\t \x01(\t\"&\n\x0b\x42lobReadReq\x12\n\n\x02id\x18\x02 \x01(\x04\x12\x0b\n\x03len\x18\x03 \x01(\x04\"+\n\x0c\x42lobReadResp\x12\x0c\n\x04\x64\x61ta\x18\x03 \x01(\x0c\x12\r\n\x05\x65rror\x18\x04 \x01(\t\"8\n\x0b\x42lobSeekReq\x12\n\n\x02id\x18\x02 \x01(\x04\x12\x0b\n\x03pos\x18\x03 \x01(\x03\x12\x10\n\x08seekType\x18\x04 \x01(\r\"-\n\x0c\x42lobSeekResp\x12\x0e\n\x06offset\x18\x02 \x01(\x04\x12\r\n\x05\x65rror\x18\x03 \x01(\t\"\x1a\n\x0c\x42lobCloseReq\x12\n\n\x02id\x18\x02 \x01(\x04\"\x1e\n\rBlobCloseResp\x12\r\n\x05\x65rror\x18\x02 \x01(\t\"(\n\x0c\x42lobWriteReq\x12\n\n\x02id\x18\x02 \x01(\x04\x12\x0c\n\x04\x64\x61ta\x18\x03 \x01(\x0c\"\x1e\n\rBlobWriteResp\x12\r\n\x05\x65rror\x18\x02 \x01(\t\"\x19\n\x0b\x42lobSealReq\x12\n\n\x02id\x18\x02 \x01(\x04\"\x1d\n\x0c\x42lobSealResp\x12\r\n\x05\x65rror\x18\x02 \x01(\t\"c\n\x12\x46uncPodRegisterReq\x12\x11\n\tfuncPodId\x18\x01 \x01(\t\x12\x11\n\tnamespace\x18\x02 \x01(\t\x12\x13\n\x0bpackageName\x18\x03 \x01(\t\x12\x12\n\nclientMode\x18\x04 \x01(\x08\"$\n\x13\x46uncPodRegisterResp\x12\r\n\x05\x65rror\x18\x01 \x01(\t\"\xe0\x01\n\x10\x46uncAgentCallReq\x12\r\n\x05jobId\x18\x01 \x01(\t\x12\n\n\x02id\x18\x02 \x01(\t\x12\x11\n\tnamespace\x18\x03 \x01(\t\x12\x13\n\x0bpackageName\x18\x04 \x01(\t\x12\x10\n\x08\x66uncName\x18\x05 \x01(\t\x12\x12\n\nparameters\x18\x06 \x01(\t\x12\x10\n\x08priority\x18\x07 \x01(\x04\x12\x14\n\x0c\x63\x61llerFuncId\x18\x08 \x01(\t\x12\x14\n\x0c\x63\x61llerNodeId\x18\t \x01(\t\x12\x13\n\x0b\x63\x61llerPodId\x18\n \x01(\t\x12\x10\n\x08\x63\x61llType\x18\x0b \x01(\x05\";\n\x11\x46uncAgentCallResp\x12\n\n\x02id\x18\x01 \x01(\t\x12\x1a\n\x03res\x18\x02 \x01(\x0b\x32\r.func.FuncRes\"\x83\x01\n\x10\x46uncAgentCallAck\x12\n\n\x02id\x18\x01 \x01(\t\x12\r\n\x05\x65rror\x18\x02 \x01(\t\x12\x14\n\x0c\x63\x61lleeNodeId\x18\x03 \x01(\t\x12\x13\n\x0b\x63\x61lleePodId\x18\x04 \x01(\t\x12\x14\n\x0c\x63\x61llerNodeId\x18\x08 \x01(\t\x12\x13\n\x0b\x63\x61llerPodId\x18\t \x01(\t\"\x1e\n\x02KV\x12\x0b\n\x03key\x18\x01 \x01(\t\x12\x0b\n\x03val\x18\x02 \x01(\t\"\xe5\x01\n\x07\x46uncMsg\x12\r\n\x05msgId\x18\x01 \x01(\t\x12\x11\n\tsrcNodeId\x18\x02 \x01(\t\x12\x10\n\x08srcPodId\x18\x03 \x01(\t\x12\x11\n\tsrcFuncId\x18\x04 \x01(\t\x12\x11\n\tdstNodeId\x18\x05 \x01(\t\x12\x10\n\x08\x64stPodId\x18\x06 \x01(\t\x12\x11\n\tdstFuncId\x18\x07 \x01(\t\x12(\n\x0b\x46uncMsgBody\x18\x65 \x01(\x0b\x32\x11.func.FuncMsgBodyH\x00\x12&\n\nFuncMsgAck\x18\x66 \x01(\x0b\x32\x10.func.FuncMsgAckH\x00\x42\t\n\x07Payload\"\x1b\n\x0b\x46uncMsgBody\x12\x0c\n\x04\x64\x61ta\x18\x04 \x01(\t\"\x1b\n\nFuncMsgAck\x12\r\n\x05\x65rror\x18\x02 \x01(\t\"\xa0\x04\n\nFuncSvcMsg\x12:\n\x14\x46uncAgentRegisterReq\x18\x64 \x01(\x0b\x32\x1a.func.FuncAgentRegisterReqH\x00\x12=\n\x15\x46uncAgentRegisterResp\x18\xc8\x01 \x01(\x0b\x32\x1b.func.FuncAgentRegisterRespH\x00\x12/\n\x0e\x46uncPodConnReq\x18\xac\x02 \x01(\x0b\x32\x14.func.FuncPodConnReqH\x00\x12\x31\n\x0f\x46uncPodConnResp\x18\x90\x03 \x01(\x0b\x32\x15.func.FuncPodConnRespH\x00\x12\x35\n\x11\x46uncPodDisconnReq\x18\xf4\x03 \x01(\x0b\x32\x17.func.FuncPodDisconnReqH\x00\x12\x37\n\x12\x46uncPodDisconnResp\x18\xd8\x04 \x01(\x0b\x32\x18.func.FuncPodDisconnRespH\x00\x12/\n\x0e\x46uncSvcCallReq\x18\xbc\x05 \x01(\x0b\x32\x14.func.FuncSvcCallReqH\x00\x12\x31\n\x0f\x46uncSvcCallResp\x18\xa0\x06 \x01(\x0b\x32\x15.func.FuncSvcCallRespH\x00\x12/\n\x0e\x46uncSvcCallAck\x18\xa1\x06 \x01(\x0b\x32\x14.func.FuncSvcCallAckH\x00\x12!\n\x07\x46uncMsg\x18\x84\x07 \x01(\x0b\x32\r.func.FuncMsgH\x00\x42\x0b\n\tEventBody\"\xc5\x01\n\x14\x46uncAgentRegisterReq\x12\x0e\n\x06nodeId\x18\x01 \x01(\t\x12)\n\x0b\x63\x61llerCalls\x18\x02 \x03(\x0b\x32\x14.func.FuncSvcCallReq\x12)\n\x0b\x63\x61lleeCalls\x18\x03 \x03(\x0b\x32\x14.func.FuncSvcCallReq\x12%\n\x08\x66uncPods\x18\x04 \x03(\x0b\x32\x13.func.FuncPodStatus\x12 \n\x08resource\x18\x05 \x01(\x0b\x32\x0e.func.Resource\"$\n\x08Resource\x12\x0b\n\x03mem\x18\x01 \x01(\x04\x12\x0b\n\x03\x63pu\x18\x02 \x01(\r\"&\n\x15\x46uncAgentRegisterResp\x12\r\n\x05\x65rror\x18\x01 \x01(\t\"_\n\x0e\x46uncPodConnReq\x12\x11\n\tfuncPodId\x18\x02 \x01(\t\x12\x11\n\tnamespace\x18\x03 \x01(\t\x12\x13\n\x0bpackageName\x18\x04 \x01(\t\x12\x12\n\nclientMode\x18\x05 \x01(\x08\"3\n\x0f\x46uncPodConnResp\x12\x11\n\tfuncPodId\x18\x01 \x01(\t\x12\r\n\x05\x65rror\x18\x02 \x01(\t\"&\n\x11\x46uncPodDisconnReq\x12\x11\n\tfuncPodId\x18\x01 \x01(\t\"#\n\x12\x46uncPodDisconnResp\x12\r\n\x05\x65rror\x18\x01 \x01(\t\"\xae\x02\n\x0e\x46uncSvcCallReq\x12\r\n\x05jobId\x18\x01 \x01(\t\x12\n\n\x02id\x18\x02 \x01(\t\x12\x11\n\tnamespace\x18\x03 \x01(\t\x12\x13\n\x0bpackageName\x18\x04 \x01(\t\x12\x10\n\x08\x66uncName\x18\x05 \x01(\t\x12\x12\n\nparameters\x18\x06 \x01(\t\x12\x10\n\x08priority\x18\x07 \x01(\x04\x12#\n\ncreatetime\x18\t \x01(\x0b\x32\x0f.func.Timestamp\x12\x14\n\x0c\x63\x61llerNodeId\x18\n \x01(\t\x12\x13\n\x0b\x63\x61llerPodId\x18\x0b \x01(\t\x12\x14\n\x0c\x63\x61llerFuncId\x18\x08 \x01(\t\x12\x14\n\x0c\x63\x61lleeNodeId\x18\x0c \x01(\t\x12\x13\n\x0b\x63\x61lleePodId\x18\r \x01(\t\x12\x10\n\x08\x63\x61llType\x18\x0e \x01(\x05\"\x8f\x01\n\x0f\x46uncSvcCallResp\x12\n\n\x02id\x18\x01 \x01(\t\x12\x1a\n\x03res\x18\x02 \x01(\x0b\x32\r.func.FuncRes\x12\x14\n\x0c\x63\x61llerNodeId\x18\x08 \x01(\t\x12\x13\n\x0b\x63\x61llerPodId\x18\t \x01(\t\x12\x14\n\x0c\x63\x61lleeNodeId\x18\n \x01(\t\x12\x13\n\x0b\x63\x61lleePodId\x18\x0b \x01(\t\"&\n\x05\x45rror\x12\x0e\n\x06source\x18\x01 \x01(\x05\x12\r\n\x05\x65rror\x18\x02 \x01(\t\">\n\x07\x46uncRes\x12\x1c\n\x05\x65rror\x18\x02 \x01(\x0b\x32\x0b.func.ErrorH\x00\x12\x0e\n\x04resp\x18\x03 \x01(\tH\x00\x42\x05\n\x03res\"\x81\x01\n\x0e\x46uncSvcCallAck\x12\n\n\x02id\x18\x01 \x01(\t\x12\r\n\x05\x65rror\x18\x02 \x01(\t\x12\x14\n\x0c\x63\x61lleeNodeId\x18\x03 \x01(\t\x12\x13\n\x0b\x63\x61lleePodId\x18\x04 \x01(\t\x12\x14\n\x0c\x63\x61llerNodeId\x18\x08 \x01(\t\x12\x13\n\x0b\x63\x61llerPodId\x18\t \x01(\t\"\x95\x01\n\rFuncPodStatus\x12\x11\n\tfuncPodId\x18\x01 \x01(\t\x12\x11\n\tnamespace\x18\x02 \x01(\t\x12\x13\n\x0bpackageName\x18\x03 \x01(\t\x12!\n\x05state\x18\x04 \x01(\x0e\x32\x12.func.FuncPodState\x12\x12\n\nfuncCallId\x18\x05 \x01(\t\x12\x12\n\nclientMode\x18\x06 \x01(\x08\"+\n\tTimestamp\x12\x0f\n\x07seconds\x18\x01 \x01(\x04\x12\r\n\x05nanos\x18\x02 \x01(\r*%\n\x0c\x46uncPodState\x12\x08\n\x04Idle\x10\x00\x12\x0b\n\x07Running\x10\x01\x32G\n\x0b\x42lobService\x12\x38\n\rStreamProcess\x12\x10.func.BlobSvcReq\x1a\x11.func.BlobSvcResp(\x01\x30\x01\x32\x8c\x01\n\x10\x46uncAgentService\x12;\n\rStreamProcess\x12\x12.func.FuncAgentMsg\x1a\x12.func.FuncAgentMsg(\x01\x30\x01\x12;\n\x08\x46uncCall\x12\x16.func.FuncAgentCallReq\x1a\x17.func.FuncAgentCallResp2I\n\x0e\x46uncSvcService\x12\x37\n\rStreamProcess\x12\x10.func.FuncSvcMsg\x1a\x10.func.FuncSvcMsg(\x01\x30\x01\x62\x06proto3')
+DESCRIPTOR = _descriptor_pool.Default().AddSerializedFile(b'\n\nfunc.proto\x12\x04\x66unc\"\x85\x02\n\nBlobSvcReq\x12\r\n\x05msgId\x18\x01 \x01(\x04\x12)\n\x0b\x42lobOpenReq\x18\xf5\x03 \x01(\x0b\x32\x11.func.BlobOpenReqH\x00\x12)\n\x0b\x42lobReadReq\x18\xf9\x03 \x01(\x0b\x32\x11.func.BlobReadReqH\x00\x12)\n\x0b\x42lobSeekReq\x18\xfb\x03 \x01(\x0b\x32\x11.func.BlobSeekReqH\x00\x12+\n\x0c\x42lobCloseReq\x18\x81\x04 \x01(\x0b\x32\x12.func.BlobCloseReqH\x00\x12-\n\rBlobDeleteReq\x18\x83\x04 \x01(\x0b\x32\x13.func.BlobDeleteReqH\x00\x42\x0b\n\tEventBody\"\x90\x02\n\x0b\x42lobSvcResp\x12\r\n\x05msgId\x18\x01 \x01(\x04\x12+\n\x0c\x42lobOpenResp\x18\xf6\x03 \x01(\x0b\x32\x12.func.BlobOpenRespH\x00\x12+\n\x0c\x42lobReadResp\x18\xfa\x03 \x01(\x0b\x32\x12.func.BlobReadRespH\x00\x12+\n\x0c\x42lobSeekResp\x18\xfc\x03 \x01(\x0b\x32\x12.func.BlobSeekRespH\x00\x12-\n\rBlobCloseResp\x18\x82\x04 \x01(\x0b\x32\x13.func.BlobCloseRespH\x00\x12/\n\x0e\x42lobDeleteResp\x18\x84\x04 \x01(\x0b\x32\x14.func.BlobDeleteRespH\x00\x42\x0b\n\tEventBody\"\xe1\x07\n\x0c\x46uncAgentMsg\x12\r\n\x05msgId\x18\x01 \x01(\x04\x12\x36\n\x12\x46uncPodRegisterReq\x18\x64 \x01(\x0b\x32\x18.func.FuncPodRegisterReqH\x00\x12\x39\n\x13\x46uncPodRegisterResp\x18\xc8\x01 \x01(\x0b\x32\x19.func.FuncPodRegisterRespH\x00\x12\x33\n\x10\x46uncAgentCallReq\x18\xac\x02 \x01(\x0b\x32\x16.func.FuncAgentCallReqH\x00\x12\x35\n\x11\x46uncAgentCallResp\x18\x90\x03 \x01(\x0b\x32\x17.func.FuncAgentCallRespH\x00\x12\x33\n\x10\x46uncAgentCallAck\x18\x91\x03 \x01(\x0b\x32\x16.func.FuncAgentCallAckH\x00\x12)\n\x0b\x42lobOpenReq\x18\xf5\x03 \x01(\x0b\x32\x11.func.BlobOpenReqH\x00\x12+\n\x0c\x42lobOpenResp\x18\xf6\x03 \x01(\x0b\x32\x12.func.BlobOpenRespH\x00\x12-\n\rBlobCreateReq\x18\xf7\x03 \x01(\x0b\x32\x13.func.BlobCreateReqH\x00\x12/\n\x0e\x42lobCreateResp\x18\xf8\x03 \x01(\x0b\x32\x14.func.BlobCreateRespH\x00\x12)\n\x0b\x42lobReadReq\x18\xf9\x03 \x01(\x0b\x32\x11.func.BlobReadReqH\x00\x12+\n\x0c\x42lobReadResp\x18\xfa\x03 \x01(\x0b\x32\x12.func.BlobReadRespH\x00\x12)\n\x0b\x42lobSeekReq\x18\xfb\x03 \x01(\x0b\x32\x11.func.BlobSeekReqH\x00\x12+\n\x0c\x42lobSeekResp\x18\xfc\x03 \x01(\x0b\x32\x12.func.BlobSeekRespH\x00\x12+\n\x0c\x42lobWriteReq\x18\xfd\x03 \x01(\x0b\x32\x12.func.BlobWriteReqH\x00\x12-\n\rBlobWriteResp\x18\xfe\x03 \x01(\x0b\x32\x13.func.BlobWriteRespH\x00\x12+\n\x0c\x42lobCloseReq\x18\x81\x04 \x01(\x0b\x32\x12.func.BlobCloseReqH\x00\x12-\n\rBlobCloseResp\x18\x82\x04 \x01(\x0b\x32\x13.func.BlobCloseRespH\x00\x12-\n\rBlobDeleteReq\x18\x83\x04 \x01(\x0b\x32\x13.func.BlobDeleteReqH\x00\x12/\n\x0e\x42lobDeleteResp\x18\x84\x04 \x01(\x0b\x32\x14.func.BlobDeleteRespH\x00\x12!\n\x07\x46uncMsg\x18\x85\x04 \x01(\x0b\x32\r.func.FuncMsgH\x00\x42\x0b\n\tEventBody\"?\n\x0b\x42lobOpenReq\x12\x0f\n\x07svcAddr\x18\x02 \x01(\t\x12\x11\n\tnamespace\x18\x03 \x01(\t\x12\x0c\n\x04name\x18\x04 \x01(\t\"\xb8\x01\n\x0c\x42lobOpenResp\x12\n\n\x02id\x18\x02 \x01(\x04\x12\x11\n\tnamespace\x18\x03 \x01(\t\x12\x0c\n\x04name\x18\x04 \x01(\t\x12\x0c\n\x04size\x18\x05 \x01(\x04\x12\x10\n\x08\x63hecksum\x18\x06 \x01(\t\x12#\n\ncreateTime\x18\x07 \x01(\x0b\x32\x0f.func.Timestamp\x12\'\n\x0elastAccessTime\x18\x08 \x01(\x0b\x32\x0f.func.Timestamp\x12\r\n\x05\x65rror\x18\t \x01(\t\"A\n\rBlobDeleteReq\x12\x0f\n\x07svcAddr\x18\x02 \x01(\t\x12\x11\n\tnamespace\x18\x03 \x01(\t\x12\x0c\n\x04name\x18\x04 \x01(\t\"\x1f\n\x0e\x42lobDeleteResp\x12\r\n\x05\x65rror\x18\x01 \x01(\t\"0\n\rBlobCreateReq\x12\x11\n\tnamespace\x18\x03 \x01(\t\x12\x0c\n\x04name\x18\x04 \x01(\t\"<\n\x0e\x42lobCreateResp\x12\n\n\x02id\x18\x02 \x01(\x04\x12\x0f\n\x07svcAddr\x18\x03 \x01(\t\x12\r\n\x05\x65rror\x18\t \x01(\t\"&\n\x0b\x42lobReadReq\x12\n\n\x02id\x18\x02 \x01(\x04\x12\x0b\n\x03len\x18\x03 \x01(\x04\"+\n\x0c\x42lobReadResp\x12\x0c\n\x04\x64\x61ta\x18\x03 \x01(\x0c\x12\r\n\x05\x65rror\x18\x04 \x01(\t\"8\n\x0b\x42lobSeekReq\x12\n\n\x02id\x18\x02 \x01(\x04\x12\x0b\n\x03pos\x18\x03 \x01(\x03\x12\x10\n\x08seekType\x18\x04 \x01(\r\"-\n\x0c\x42lobSeekResp\x12\x0e\n\x06offset\x18\x02 \x01(\x04\x12\r\n\x05\x65rror\x18\x03 \x01(\t\"\x1a\n\x0c\x42lobCloseReq\x12\n\n\x02id\x18\x02 \x01(\x04\"\x1e\n\rBlobCloseResp\x12\r\n\x05\x65rror\x18\x02 \x01(\t\"(\n\x0c\x42lobWriteReq\x12\n\n\x02id\x18\x02 \x01(\x04\x12\x0c\n\x04\x64\x61ta\x18\x03 \x01(\x0c\"\x1e\n\rBlobWriteResp\x12\r\n\x05\x65rror\x18\x02 \x01(\t\"\x19\n\x0b\x42lobSealReq\x12\n\n\x02id\x18\x02 \x01(\x04\"\x1d\n\x0c\x42lobSealResp\x12\r\n\x05\x65rror\x18\x02 \x01(\t\"c\n\x12\x46uncPodRegisterReq\x12\x11\n\tfuncPodId\x18\x01 \x01(\t\x12\x11\n\tnamespace\x18\x02 \x01(\t\x12\x13\n\x0bpackageName\x18\x03 \x01(\t\x12\x12\n\nclientMode\x18\x04 \x01(\x08\"$\n\x13\x46uncPodRegisterResp\x12\r\n\x05\x65rror\x18\x01 \x01(\t\"\x83\x02\n\x10\x46uncAgentCallReq\x12\r\n\x05jobId\x18\x01 \x01(\t\x12\n\n\x02id\x18\x02 \x01(\t\x12\x11\n\tnamespace\x18\x03 \x01(\t\x12\x13\n\x0bpackageName\x18\x04 \x01(\t\x12\x10\n\x08\x66uncName\x18\x05 \x01(\t\x12\x12\n\nparameters\x18\x06 \x01(\t\x12\x10\n\x08priority\x18\x07 \x01(\x04\x12\x14\n\x0c\x63\x61llerFuncId\x18\x08 \x01(\t\x12\x14\n\x0c\x63\x61llerNodeId\x18\t \x01(\t\x12\x13\n\x0b\x63\x61llerPodId\x18\n \x01(\t\x12\x10\n\x08\x63\x61llType\x18\x0b \x01(\x05\x12\x0f\n\x07timeout\x18\x0c \x01(\x04\x12\x10\n\x08maxRetry\x18\r \x01(\r\";\n\x11\x46uncAgentCallResp\x12\n\n\x02id\x18\x01 \x01(\t\x12\x1a\n\x03res\x18\x02 \x01(\x0b\x32\r.func.FuncRes\"\x83\x01\n\x10\x46uncAgentCallAck\x12\n\n\x02id\x18\x01 \x01(\t\x12\r\n\x05\x65rror\x18\x02 \x01(\t\x12\x14\n\x0c\x63\x61lleeNodeId\x18\x03 \x01(\t\x12\x13\n\x0b\x63\x61lleePodId\x18\x04 \x01(\t\x12\x14\n\x0c\x63\x61llerNodeId\x18\x08 \x01(\t\x12\x13\n\x0b\x63\x61llerPodId\x18\t \x01(\t\"\x1e\n\x02KV\x12\x0b\n\x03key\x18\x01 \x01(\t\x12\x0b\n\x03val\x18\x02 \x01(\t\"\xe5\x01\n\x07\x46uncMsg\x12\r\n\x05msgId\x18\x01 \x01(\t\x12\x11\n\tsrcNodeId\x18\x02 \x01(\t\x12\x10\n\x08srcPodId\x18\x03 \x01(\t\x12\x11\n\tsrcFuncId\x18\x04 \x01(\t\x12\x11\n\tdstNodeId\x18\x05 \x01(\t\x12\x10\n\x08\x64stPodId\x18\x06 \x01(\t\x12\x11\n\tdstFuncId\x18\x07 \x01(\t\x12(\n\x0b\x46uncMsgBody\x18\x65 \x01(\x0b\x32\x11.func.FuncMsgBodyH\x00\x12&\n\nFuncMsgAck\x18\x66 \x01(\x0b\x32\x10.func.FuncMsgAckH\x00\x42\t\n\x07Payload\"\x1b\n\x0b\x46uncMsgBody\x12\x0c\n\x04\x64\x61ta\x18\x04 \x01(\t\"\x1b\n\nFuncMsgAck\x12\r\n\x05\x65rror\x18\x02 \x01(\t\"\xa0\x04\n\nFuncSvcMsg\x12:\n\x14\x46uncAgentRegisterReq\x18\x64 \x01(\x0b\x32\x1a.func.FuncAgentRegisterReqH\x00\x12=\n\x15\x46uncAgentRegisterResp\x18\xc8\x01 \x01(\x0b\x32\x1b.func.FuncAgentRegisterRespH\x00\x12/\n\x0e\x46uncPodConnReq\x18\xac\x02 \x01(\x0b\x32\x14.func.FuncPodConnReqH\x00\x12\x31\n\x0f\x46uncPodConnResp\x18\x90\x03 \x01(\x0b\x32\x15.func.FuncPodConnRespH\x00\x12\x35\n\x11\x46uncPodDisconnReq\x18\xf4\x03 \x01(\x0b\x32\x17.func.FuncPodDisconnReqH\x00\x12\x37\n\x12\x46uncPodDisconnResp\x18\xd8\x04 \x01(\x0b\x32\x18.func.FuncPodDisconnRespH\x00\x12/\n\x0e\x46uncSvcCallReq\x18\xbc\x05 \x01(\x0b\x32\x14.func.FuncSvcCallReqH\x00\x12\x31\n\x0f\x46uncSvcCallResp\x18\xa0\x06 \x01(\x0b\x32\x15.func.FuncSvcCallRespH\x00\x12/\n\x0e\x46uncSvcCallAck\x18\xa1\x06 \x01(\x0b\x32\x14.func.FuncSvcCallAckH\x00\x12!\n\x07\x46uncMsg\x18\x84\x07 \x01(\x0b\x32\r.func.FuncMsgH\x00\x42\x0b\n\tEventBody\"\xc5\x01\n\x14\x46uncAgentRegisterReq\x12\x0e\n\x06nodeId\x18\x01 \x01(\t\x12)\n\x0b\x63\x61llerCalls\x18\x02 \x03(\x0b\x32\x14.func.FuncSvcCallReq\x12)\n\x0b\x63\x61lleeCalls\x18\x03 \x03(\x0b\x32\x14.func.FuncSvcCallReq\x12%\n\x08\x66uncPods\x18\x04 \x03(\x0b\x32\x13.func.FuncPodStatus\x12 \n\x08resource\x18\x05 \x01(\x0b\x32\x0e.func.Resource\"$\n\x08Resource\x12\x0b\n\x03mem\x18\x01 \x01(\x04\x12\x0b\n\x03\x63pu\x18\x02 \x01(\r\"&\n\x15\x46uncAgentRegisterResp\x12\r\n\x05\x65rror\x18\x01 \x01(\t\"_\n\x0e\x46uncPodConnReq\x12\x11\n\tfuncPodId\x18\x02 \x01(\t\x12\x11\n\tnamespace\x18\x03 \x01(\t\x12\x13\n\x0bpackageName\x18\x04 \x01(\t\x12\x12\n\nclientMode\x18\x05 \x01(\x08\"3\n\x0f\x46uncPodConnResp\x12\x11\n\tfuncPodId\x18\x01 \x01(\t\x12\r\n\x05\x65rror\x18\x02 \x01(\t\"&\n\x11\x46uncPodDisconnReq\x12\x11\n\tfuncPodId\x18\x01 \x01(\t\"#\n\x12\x46uncPodDisconnResp\x12\r\n\x05\x65rror\x18\x01 \x01(\t\"\xe5\x02\n\x0e\x46uncSvcCallReq\x12\r\n\x05jobId\x18\x01 \x01(\t\x12\n\n\x02id\x18\x02 \x01(\t\x12\x11\n\tnamespace\x18\x03 \x01(\t\x12\x13\n\x0bpackageName\x18\x04 \x01(\t\x12\x10\n\x08\x66uncName\x18\x05 \x01(\t\x12\x12\n\nparameters\x18\x06 \x01(\t\x12\x10\n\x08priority\x18\x07 \x01(\x04\x12#\n\ncreatetime\x18\t \x01(\x0b\x32\x0f.func.Timestamp\x12\x14\n\x0c\x63\x61llerNodeId\x18\n \x01(\t\x12\x13\n\x0b\x63\x61llerPodId\x18\x0b \x01(\t\x12\x14\n\x0c\x63\x61llerFuncId\x18\x08 \x01(\t\x12\x14\n\x0c\x63\x61lleeNodeId\x18\x0c \x01(\t\x12\x13\n\x0b\x63\x61lleePodId\x18\r \x01(\t\x12\x10\n\x08\x63\x61llType\x18\x0e \x01(\x05\x12\x0f\n\x07timeout\x18\x0f \x01(\x04\x12\x10\n\x08maxRetry\x18\x10 \x01(\r\x12\x12\n\nretryCount\x18\x11 \x01(\r\"\x8f\x01\n\x0f\x46uncSvcCallResp\x12\n\n\x02id\x18\x01 \x01(\t\x12\x1a\n\x03res\x18\x02 \x01(\x0b\x32\r.func.FuncRes\x12\x14\n\x0c\x63\x61llerNodeId\x18\x08 \x01(\t\x12\x13\n\x0b\x63\x61llerPodId\x18\t \x01(\t\x12\x14\n\x0c\x63\x61lleeNodeId\x18\n \x01(\t\x12\x13\n\x0b\x63\x61lleePodId\x18\x0b \x01(\t\"&\n\x05\x45rror\x12\x0e\n\x06source\x18\x01 \x01(\x05\x12\r\n\x05\x65rror\x18\x02 \x01(\t\">\n\x07\x46uncRes\x12\x1c\n\x05\x65rror\x18\x02 \x01(\x0b\x32\x0b.func.ErrorH\x00\x12\x0e\n\x04resp\x18\x03 \x01(\tH\x00\x42\x05\n\x03res\"\x81\x01\n\x0e\x46uncSvcCallAck\x12\n\n\x02id\x18\x01 \x01(\t\x12\r\n\x05\x65rror\x18\x02 \x01(\t\x12\x14\n\x0c\x63\x61lleeNodeId\x18\x03 \x01(\t\x12\x13\n\x0b\x63\x61lleePodId\x18\x04 \x01(\t\x12\x14\n\x0c\x63\x61llerNodeId\x18\x08 \x01(\t\x12\x13\n\x0b\x63\x61llerPodId\x18\t \x01(\t\"\x95\x01\n\rFuncPodStatus\x12\x11\n\tfuncPodId\x18\x01 \x01(\t\x12\x11\n\tnamespace\x18\x02 \x01(\t\x12\x13\n\x0bpackageName\x18\x03 \x01(\t\x12!\n\x05state\x18\x04 \x01(\x0e\x32\x12.func.FuncPodState\x12\x12\n\nfuncCallId\x18\x05 \x01(\t\x12\x12\n\nclientMode\x18\x06 \x01(\x08\"+\n\tTimestamp\x12\x0f\n\x07seconds\x18\x01 \x01(\x04\x12\r\n\x05nanos\x18\x02 \x01(\r*%\n\x0c\x46uncPodState\x12\x08\n\x04Idle\x10\x00\x12\x0b\n\x07Running\x10\x01\x32G\n\x0b\x42lobService\x12\x38\n\rStreamProcess\x12\x10.func.BlobSvcReq\x1a\x11.func.BlobSvcResp(\x01\x30\x01\x32\x8c\x01\n\x10\x46uncAgentService\x12;\n\rStreamProcess\x12\x12.func.FuncAgentMsg\x1a\x12.func.FuncAgentMsg(\x01\x30\x01\x12;\n\x08\x46uncCall\x12\x16.func.FuncAgentCallReq\x1a\x17.func.FuncAgentCallResp2I\n\x0e\x46uncSvcService\x12\x37\n\rStreamProcess\x12\x10.func.FuncSvcMsg\x1a\x10.func.FuncSvcMsg(\x01\x30\x01\x62\x06proto3')
 
 _builder.BuildMessageAndEnumDescriptors(DESCRIPTOR, globals())
 _builder.BuildTopDescriptorsAndMessages(DESCRIPTOR, 'func_pb2', globals())
 if _descriptor._USE_C_DESCRIPTORS == False:
 
   DESCRIPTOR._options = None
-  _FUNCPODSTATE._serialized_start=5220
-  _FUNCPODSTATE._serialized_end=5257
+  _FUNCPODSTATE._serialized_start=5310
+  _FUNCPODSTATE._serialized_end=5347
   _BLOBSVCREQ._serialized_start=21
   _BLOBSVCREQ._serialized_end=282
   _BLOBSVCRESP._serialized_start=285
@@ -65,53 +65,53 @@ if _descriptor._USE_C_DESCRIPTORS == False:
   _FUNCPODREGISTERRESP._serialized_start=2502
   _FUNCPODREGISTERRESP._serialized_end=2538
   _FUNCAGENTCALLREQ._serialized_start=2541
-  _FUNCAGENTCALLREQ._serialized_end=2765
-  _FUNCAGENTCALLRESP._serialized_start=2767
-  _FUNCAGENTCALLRESP._serialized_end=2826
-  _FUNCAGENTCALLACK._serialized_start=2829
-  _FUNCAGENTCALLACK._serialized_end=2960
-  _KV._serialized_start=2962
-  _KV._serialized_end=2992
-  _FUNCMSG._serialized_start=2995
-  _FUNCMSG._serialized_end=3224
-  _FUNCMSGBODY._serialized_start=3226
-  _FUNCMSGBODY._serialized_end=3253
-  _FUNCMSGACK._serialized_start=3255
-  _FUNCMSGACK._serialized_end=3282
-  _FUNCSVCMSG._serialized_start=3285
-  _FUNCSVCMSG._serialized_end=3829
-  _FUNCAGENTREGISTERREQ._serialized_start=3832
-  _FUNCAGENTREGISTERREQ._serialized_end=4029
-  _RESOURCE._serialized_start=4031
-  _RESOURCE._serialized_end=4067
-  _FUNCAGENTREGISTERRESP._serialized_start=4069
-  _FUNCAGENTREGISTERRESP._serialized_end=4107
-  _FUNCPODCONNREQ._serialized_start=4109
-  _FUNCPODCONNREQ._serialized_end=4204
-  _FUNCPODCONNRESP._serialized_start=4206
-  _FUNCPODCONNRESP._serialized_end=4257
-  _FUNCPODDISCONNREQ._serialized_start=4259
-  _FUNCPODDISCONNREQ._serialized_end=4297
-  _FUNCPODDISCONNRESP._serialized_start=4299
-  _FUNCPODDISCONNRESP._serialized_end=4334
-  _FUNCSVCCALLREQ._serialized_start=4337
-  _FUNCSVCCALLREQ._serialized_end=4639
-  _FUNCSVCCALLRESP._serialized_start=4642
-  _FUNCSVCCALLRESP._serialized_end=4785
-  _ERROR._serialized_start=4787
-  _ERROR._serialized_end=4825
-  _FUNCRES._serialized_start=4827
-  _FUNCRES._serialized_end=4889
-  _FUNCSVCCALLACK._serialized_start=4892
-  _FUNCSVCCALLACK._serialized_end=5021
-  _FUNCPODSTATUS._serialized_start=5024
-  _FUNCPODSTATUS._serialized_end=5173
-  _TIMESTAMP._serialized_start=5175
-  _TIMESTAMP._serialized_end=5218
-  _BLOBSERVICE._serialized_start=5259
-  _BLOBSERVICE._serialized_end=5330
-  _FUNCAGENTSERVICE._serialized_start=5333
-  _FUNCAGENTSERVICE._serialized_end=5473
-  _FUNCSVCSERVICE._serialized_start=5475
-  _FUNCSVCSERVICE._serialized_end=5548
+  _FUNCAGENTCALLREQ._serialized_end=2800
+  _FUNCAGENTCALLRESP._serialized_start=2802
+  _FUNCAGENTCALLRESP._serialized_end=2861
+  _FUNCAGENTCALLACK._serialized_start=2864
+  _FUNCAGENTCALLACK._serialized_end=2995
+  _KV._serialized_start=2997
+  _KV._serialized_end=3027
+  _FUNCMSG._serialized_start=3030
+  _FUNCMSG._serialized_end=3259
+  _FUNCMSGBODY._serialized_start=3261
+  _FUNCMSGBODY._serialized_end=3288
+  _FUNCMSGACK._serialized_start=3290
+  _FUNCMSGACK._serialized_end=3317
+  _FUNCSVCMSG._serialized_start=3320
+  _FUNCSVCMSG._serialized_end=3864
+  _FUNCAGENTREGISTERREQ._serialized_start=3867
+  _FUNCAGENTREGISTERREQ._serialized_end=4064
+  _RESOURCE._serialized_start=4066
+  _RESOURCE._serialized_end=4102
+  _FUNCAGENTREGISTERRESP._serialized_start=4104
+  _FUNCAGENTREGISTERRESP._serialized_end=4142
+  _FUNCPODCONNREQ._serialized_start=4144
+  _FUNCPODCONNREQ._serialized_end=4239
+  _FUNCPODCONNRESP._serialized_start=4241
+  _FUNCPODCONNRESP._serialized_end=4292
+  _FUNCPODDISCONNREQ._serialized_start=4294
+  _FUNCPODDISCONNREQ._serialized_end=4332
+  _FUNCPODDISCONNRESP._serialized_start=4334
+  _FUNCPODDISCONNRESP._serialized_end=4369
+  _FUNCSVCCALLREQ._serialized_start=4372
+  _FUNCSVCCALLREQ._serialized_end=4729
+  _FUNCSVCCALLRESP._serialized_start=4732
+  _FUNCSVCCALLRESP._serialized_end=4875
+  _ERROR._serialized_start=4877
+  _ERROR._serialized_end=4915
+  _FUNCRES._serialized_start=4917
+  _FUNCRES._serialized_end=4979
+  _FUNCSVCCALLACK._serialized_start=4982
+  _FUNCSVCCALLACK._serialized_end=5111
+  _FUNCPODSTATUS._serialized_start=5114
+  _FUNCPODSTATUS._serialized_end=5263
+  _TIMESTAMP._serialized_start=5265
+  _TIMESTAMP._serialized_end=5308
+  _BLOBSERVICE._serialized_start=5349
+  _BLOBSERVICE._serialized_end=5420
+  _FUNCAGENTSERVICE._serialized_start=5423
+  _FUNCAGENTSERVICE._serialized_end=5563
+  _FUNCSVCSERVICE._serialized_start=5565
+  _FUNCSVCSERVICE._serialized_end=5638
 # @@protoc_insertion_point(module_scope)
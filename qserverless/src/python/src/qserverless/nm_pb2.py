@@ -13,15 +13,15 @@ _sym_db = _symbol_database.Default()
 
 
 
-DESCRIPTOR = _descriptor_pool.Default().AddSerializedFile(b'\n\x08nm.proto\x12\x02nm\"\x84\x01\n\x10NodeAgentRespMsg\x12*\n\rnodeAgentResp\x18\x64 \x01(\x0b\x32\x11.nm.NodeAgentRespH\x00\x12\x35\n\x12nodeAgentStreamMsg\x18\xc8\x01 \x01(\x0b\x32\x16.nm.NodeAgentStreamMsgH\x00\x42\r\n\x0bMessageBody\"\xe7\x01\n\x0cNodeAgentReq\x12\x11\n\trequestId\x18\x01 \x01(\x04\x12*\n\rnodeConfigReq\x18\x64 \x01(\x0b\x32\x11.nm.NodeConfigReqH\x00\x12)\n\x0c\x63reatePodReq\x18\xc8\x01 \x01(\x0b\x32\x10.nm.CreatePodReqH\x00\x12/\n\x0fterminatePodReq\x18\xac\x02 \x01(\x0b\x32\x13.nm.TerminatePodReqH\x00\x12-\n\x0ereadFuncLogReq\x18\x90\x03 \x01(\x0b\x32\x12.nm.ReadFuncLogReqH\x00\x42\r\n\x0bMessageBody\"\xff\x01\n\rNodeAgentResp\x12\x11\n\trequestId\x18\x01 \x01(\x04\x12\r\n\x05\x65rror\x18\x02 \x01(\t\x12,\n\x0enodeConfigResp\x18\x64 \x01(\x0b\x32\x12.nm.NodeConfigRespH\x00\x12+\n\rcreatePodResp\x18\xc8\x01 \x01(\x0b\x32\x11.nm.CreatePodRespH\x00\x12\x31\n\x10terminatePodResp\x18\xac\x02 \x01(\x0b\x32\x14.nm.TerminatePodRespH\x00\x12/\n\x0freadFuncLogResp\x18\x90\x03 \x01(\x0b\x32\x13.nm.ReadFuncLogRespH\x00\x42\r\n\x0bMessageBody\"R\n\x0eReadFuncLogReq\x12\x11\n\tnamespace\x18\x01 \x01(\t\x12\x10\n\x08\x66uncName\x18\x02 \x01(\t\x12\x0e\n\x06offset\x18\x03 \x01(\x04\x12\x0b\n\x03len\x18\x04 \x01(\r\"1\n\x0fReadFuncLogResp\x12\r\n\x05\x65rror\x18\x01 \x01(\t\x12\x0f\n\x07\x63ontent\x18\x02 \x01(\t\"4\n\rNodeConfigReq\x12\x15\n\rclusterDomain\x18\x01 \x01(\t\x12\x0c\n\x04node\x18\x02 \x01(\t\"\x10\n\x0eNodeConfigResp\".\n\x0c\x43reatePodReq\x12\x0b\n\x03pod\x18\x01 \x01(\t\x12\x11\n\tconfigMap\x18\x02 \x01(\t\"\x0f\n\rCreatePodResp\" \n\x0fTerminatePodReq\x12\r\n\x05podId\x18\x01 \x01(\t\"\x12\n\x10TerminatePodResp\"\x95\x01\n\x12NodeAgentStreamMsg\x12(\n\x0cnodeRegister\x18\x64 \x01(\x0b\x32\x10.nm.NodeRegisterH\x00\x12%\n\nnodeUpdate\x18\xc8\x01 \x01(\x0b\x32\x0e.nm.NodeUpdateH\x00\x12!\n\x08podEvent\x18\xac\x02 \x01(\x0b\x32\x0c.nm.PodEventH\x00\x42\x0b\n\tEventBody\"<\n\x0cNodeRegister\x12\x10\n\x08revision\x18\x02 \x01(\x03\x12\x0c\n\x04node\x18\x03 \x01(\t\x12\x0c\n\x04pods\x18\x04 \x03(\t\",\n\nNodeUpdate\x12\x10\n\x08revision\x18\x02 \x01(\x03\x12\x0c\n\x04node\x18\x03 \x01(\t\"K\n\x08PodEvent\x12 \n\teventType\x18\x01 \x01(\x0e\x32\r.nm.EventType\x12\x10\n\x08revision\x18\x02 \x01(\x03\x12\x0b\n\x03pod\x18\x03 \x01(\t*,\n\tEventType\x12\x07\n\x03\x41\x64\x64\x10\x00\x12\n\n\x06Update\x10\x02\x12\n\n\x06\x44\x65lete\x10\x03\x32O\n\x10NodeAgentService\x12;\n\rStreamProcess\x12\x14.nm.NodeAgentRespMsg\x1a\x10.nm.NodeAgentReq(\x01\x30\x01\x62\x06proto3')
+DESCRIPTOR = _descriptor_pool.Default().AddSerializedFile(b'\n\x08nm.proto\x12\x02nm\"\x84\x01\n\x10NodeAgentRespMsg\x12*\n\rnodeAgentResp\x18\x64 \x01(\x0b\x32\x11.nm.NodeAgentRespH\x00\x12\x35\n\x12nodeAgentStreamMsg\x18\xc8\x01 \x01(\x0b\x32\x16.nm.NodeAgentStreamMsgH\x00\x42\r\n\x0bMessageBody\"\xe7\x01\n\x0cNodeAgentReq\x12\x11\n\trequestId\x18\x01 \x01(\x04\x12*\n\rnodeConfigReq\x18\x64 \x01(\x0b\x32\x11.nm.NodeConfigReqH\x00\x12)\n\x0c\x63reatePodReq\x18\xc8\x01 \x01(\x0b\x32\x10.nm.CreatePodReqH\x00\x12/\n\x0fterminatePodReq\x18\xac\x02 \x01(\x0b\x32\x13.nm.TerminatePodReqH\x00\x12-\n\x0ereadFuncLogReq\x18\x90\x03 \x01(\x0b\x32\x12.nm.ReadFuncLogReqH\x00\x42\r\n\x0bMessageBody\"\xff\x01\n\rNodeAgentResp\x12\x11\n\trequestId\x18\x01 \x01(\x04\x12\r\n\x05\x65rror\x18\x02 \x01(\t\x12,\n\x0enodeConfigResp\x18\x64 \x01(\x0b\x32\x12.nm.NodeConfigRespH\x00\x12+\n\rcreatePodResp\x18\xc8\x01 \x01(\x0b\x32\x11.nm.CreatePodRespH\x00\x12\x31\n\x10terminatePodResp\x18\xac\x02 \x01(\x0b\x32\x14.nm.TerminatePodRespH\x00\x12/\n\x0freadFuncLogResp\x18\x90\x03 \x01(\x0b\x32\x13.nm.ReadFuncLogRespH\x00\x42\r\n\x0bMessageBody\"R\n\x0eReadFuncLogReq\x12\x11\n\tnamespace\x18\x01 \x01(\t\x12\x10\n\x08\x66uncName\x18\x02 \x01(\t\x12\x0e\n\x06offset\x18\x03 \x01(\x04\x12\x0b\n\x03len\x18\x04 \x01(\r\"B\n\x0fReadFuncLogResp\x12\r\n\x05\x65rror\x18\x01 \x01(\t\x12\x0f\n\x07\x63ontent\x18\x02 \x01(\t\x12\x0f\n\x07readLen\x18\x03 \x01(\x04\"4\n\rNodeConfigReq\x12\x15\n\rclusterDomain\x18\x01 \x01(\t\x12\x0c\n\x04node\x18\x02 \x01(\t\"\x10\n\x0eNodeConfigResp\".\n\x0c\x43reatePodReq\x12\x0b\n\x03pod\x18\x01 \x01(\t\x12\x11\n\tconfigMap\x18\x02 \x01(\t\"\x0f\n\rCreatePodResp\" \n\x0fTerminatePodReq\x12\r\n\x05podId\x18\x01 \x01(\t\"\x12\n\x10TerminatePodResp\"\x95\x01\n\x12NodeAgentStreamMsg\x12(\n\x0cnodeRegister\x18\x64 \x01(\x0b\x32\x10.nm.NodeRegisterH\x00\x12%\n\nnodeUpdate\x18\xc8\x01 \x01(\x0b\x32\x0e.nm.NodeUpdateH\x00\x12!\n\x08podEvent\x18\xac\x02 \x01(\x0b\x32\x0c.nm.PodEventH\x00\x42\x0b\n\tEventBody\"<\n\x0cNodeRegister\x12\x10\n\x08revision\x18\x02 \x01(\x03\x12\x0c\n\x04node\x18\x03 \x01(\t\x12\x0c\n\x04pods\x18\x04 \x03(\t\",\n\nNodeUpdate\x12\x10\n\x08revision\x18\x02 \x01(\x03\x12\x0c\n\x04node\x18\x03 \x01(\t\"K\n\x08PodEvent\x12 \n\teventType\x18\x01 \x01(\x0e\x32\r.nm.EventType\x12\x10\n\x08revision\x18\x02 \x01(\x03\x12\x0b\n\x03pod\x18\x03 \x01(\t*,\n\tEventType\x12\x07\n\x03\x41\x64\x64\x10\x00\x12\n\n\x06Update\x10\x02\x12\n\n\x06\x44\x65lete\x10\x03\x32O\n\x10NodeAgentService\x12;\n\rStreamProcess\x12\x14.nm.NodeAgentRespMsg\x1a\x10.nm.NodeAgentReq(\x01\x30\x01\x62\x06proto3')
 
 _builder.BuildMessageAndEnumDescriptors(DESCRIPTOR, globals())
 _builder.BuildTopDescriptorsAndMessages(DESCRIPTOR, 'nm_pb2', globals())
 if _descriptor._USE_C_DESCRIPTORS == False:
 
   DESCRIPTOR._options = None
-  _EVENTTYPE._serialized_start=1306
-  _EVENTTYPE._serialized_end=1350
+  _EVENTTYPE._serialized_start=1323
+  _EVENTTYPE._serialized_end=1367
   _NODEAGENTRESPMSG._serialized_start=17
   _NODEAGENTRESPMSG._serialized_end=149
   _NODEAGENTREQ._serialized_start=152
@@ -31,27 +31,27 @@ if _descriptor._USE_C_DESCRIPTORS == False:
   _READFUNCLOGREQ._serialized_start=643
   _READFUNCLOGREQ._serialized_end=725
   _READFUNCLOGRESP._serialized_start=727
-  _READFUNCLOGRESP._serialized_end=776
-  _NODECONFIGREQ._serialized_start=778
-  _NODECONFIGREQ._serialized_end=830
-  _NODECONFIGRESP._serialized_start=832
-  _NODECONFIGRESP._serialized_end=848
-  _CREATEPODREQ._serialized_start=850
-  _CREATEPODREQ._serialized_end=896
-  _CREATEPODRESP._serialized_start=898
-  _CREATEPODRESP._serialized_end=913
-  _TERMINATEPODREQ._serialized_start=915
-  _TERMINATEPODREQ._serialized_end=947
-  _TERMINATEPODRESP._serialized_start=949
-  _TERMINATEPODRESP._serialized_end=967
-  _NODEAGENTSTREAMMSG._serialized_start=970
-  _NODEAGENTSTREAMMSG._serialized_end=1119
-  _NODEREGISTER._serialized_start=1121
-  _NODEREGISTER._serialized_end=1181
-  _NODEUPDATE._serialized_start=1183
-  _NODEUPDATE._serialized_end=1227
-  _PODEVENT._serialized_start=1229
-  _PODEVENT._serialized_end=1304
-  _NODEAGENTSERVICE._serialized_start=1352
-  _NODEAGENTSERVICE._serialized_end=1431
+  _READFUNCLOGRESP._serialized_end=793
+  _NODECONFIGREQ._serialized_start=795
+  _NODECONFIGREQ._serialized_end=847
+  _NODECONFIGRESP._serialized_start=849
+  _NODECONFIGRESP._serialized_end=865
+  _CREATEPODREQ._serialized_start=867
+  _CREATEPODREQ._serialized_end=913
+  _CREATEPODRESP._serialized_start=915
+  _CREATEPODRESP._serialized_end=930
+  _TERMINATEPODREQ._serialized_start=932
+  _TERMINATEPODREQ._serialized_end=964
+  _TERMINATEPODRESP._serialized_start=966
+  _TERMINATEPODRESP._serialized_end=984
+  _NODEAGENTSTREAMMSG._serialized_start=987
+  _NODEAGENTSTREAMMSG._serialized_end=1136
+  _NODEREGISTER._serialized_start=1138
+  _NODEREGISTER._serialized_end=1198
+  _NODEUPDATE._serialized_start=1200
+  _NODEUPDATE._serialized_end=1244
+  _PODEVENT._serialized_start=1246
+  _PODEVENT._serialized_end=1321
+  _NODEAGENTSERVICE._serialized_start=1369
+  _NODEAGENTSERVICE._serialized_end=1448
 # @@protoc_insertion_point(module_scope)
@@ -0,0 +1,169 @@
+// Copyright (c) 2021 Quark Container Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap::{App, AppSettings, ArgMatches, SubCommand, Arg};
+
+use qobjs::{common::*, types::QMETASVC_ADDR, system_types::BlobPolicySpec};
+
+use crate::package_mgr::PackageMgr;
+
+#[derive(Debug)]
+pub enum BlobSubCmd {
+    List(String), // namespace
+    Remove(String, String), // namespace, name
+    Usage(String), // namespace
+}
+
+#[derive(Debug)]
+pub struct BlobCmd {
+    pub cmd: BlobSubCmd,
+}
+
+impl BlobCmd {
+    pub fn Init(cmd_matches: &ArgMatches) -> Result<Self> {
+        let cmd = match cmd_matches.subcommand() {
+            ("ls", Some(m)) => {
+                BlobSubCmd::List(m.value_of("namespace").unwrap().to_string())
+            }
+            ("rm", Some(m)) => {
+                BlobSubCmd::Remove(
+                    m.value_of("namespace").unwrap().to_string(),
+                    m.value_of("name").unwrap().to_string(),
+                )
+            }
+            ("du", Some(m)) => {
+                BlobSubCmd::Usage(m.value_of("namespace").unwrap().to_string())
+            }
+            x => return Err(Error::CommonError(format!("blob command not recognized {:?}", x))),
+        };
+
+        return Ok(Self {
+            cmd: cmd,
+        });
+    }
+
+    fn NamespaceArg<'a, 'b>() -> Arg<'a, 'b> {
+        return Arg::with_name("namespace")
+            .required(true)
+            .help("blob namespace")
+            .long("namespace")
+            .short("n")
+            .takes_value(true);
+    }
+
+    pub fn SubCommand<'a, 'b>() -> App<'a, 'b> {
+        return SubCommand::with_name("blob")
+            .setting(AppSettings::ColoredHelp)
+            .setting(AppSettings::SubcommandRequired)
+            .subcommand(
+                SubCommand::with_name("ls")
+                    .setting(AppSettings::ColoredHelp)
+                    .arg(Self::NamespaceArg())
+                    .about("List the blobs of a namespace")
+            )
+            .subcommand(
+                SubCommand::with_name("rm")
+                    .setting(AppSettings::ColoredHelp)
+                    .arg(Self::NamespaceArg())
+                    .arg(
+                        Arg::with_name("name")
+                            .required(true)
+                            .help("blob name")
+                            .takes_value(true),
+                    )
+                    .about("Remove a blob and all its replicas")
+            )
+            .subcommand(
+                SubCommand::with_name("du")
+                    .setting(AppSettings::ColoredHelp)
+                    .arg(Self::NamespaceArg())
+                    .about("Show the blob usage and quota of a namespace")
+            )
+            .about("List, remove blobs and show blob usage");
+    }
+
+    pub async fn Run(&self) -> Result<()> {
+        let addr = format!("http://{}", QMETASVC_ADDR);
+        let packageMgr = match PackageMgr::New(&addr).await {
+            Err(e) => {
+                println!("can't connect the qservereless service {} with error {:?}", &addr, e);
+                return Ok(())
+            }
+            Ok(m) => m
+        };
+
+        match &self.cmd {
+            BlobSubCmd::List(namespace) => {
+                let blobs = match packageMgr.ListBlobs(namespace).await {
+                    Err(e) => {
+                        println!("can't list blobs of namespace {} with error {:?}", namespace, e);
+                        return Ok(())
+                    }
+                    Ok(b) => b
+                };
+
+                println!("{:<40}{:<12}{:<10}{:<38}{}", "NAME", "SIZE", "REPLICAS", "JOB", "EXPIRE");
+                for blob in &blobs {
+                    let expire = if blob.spec.expireTimeSec == 0 {
+                        "never".to_string()
+                    } else {
+                        format!("{}", blob.spec.expireTimeSec)
+                    };
+                    println!("{:<40}{:<12}{:<10}{:<38}{}",
+                        &blob.spec.name,
+                        blob.spec.size,
+                        blob.spec.locations.len(),
+                        &blob.spec.jobId,
+                        expire
+                    );
+                }
+            }
+            BlobSubCmd::Remove(namespace, name) => {
+                match packageMgr.DeleteBlob(namespace, name).await {
+                    Err(e) => {
+                        println!("can't remove blob {}/{} with error {:?}", namespace, name, e);
+                    }
+                    Ok(()) => {
+                        println!("blob {}/{} removed", namespace, name);
+                    }
+                }
+            }
+            BlobSubCmd::Usage(namespace) => {
+                let blobs = match packageMgr.ListBlobs(namespace).await {
+                    Err(e) => {
+                        println!("can't list blobs of namespace {} with error {:?}", namespace, e);
+                        return Ok(())
+                    }
+                    Ok(b) => b
+                };
+
+                let policy = match packageMgr.GetBlobPolicy(namespace).await {
+                    Err(e) => {
+                        println!("can't get blob policy of namespace {} with error {:?}", namespace, e);
+                        return Ok(())
+                    }
+                    Ok(p) => p.map(|p| p.spec).unwrap_or(BlobPolicySpec::default())
+                };
+
+                let bytes : u64 = blobs.iter().map(|b| b.spec.size).sum();
+                let quota = |limit: u64| if limit == 0 { "unlimited".to_string() } else { format!("{}", limit) };
+                println!("{:<12}{:<16}{}", "", "USED", "QUOTA");
+                println!("{:<12}{:<16}{}", "bytes", bytes, quota(policy.maxBytes));
+                println!("{:<12}{:<16}{}", "count", blobs.len(), quota(policy.maxCount));
+            }
+        }
+
+        return Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+// Copyright (c) 2021 Quark Container Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap::{App, AppSettings, ArgMatches, SubCommand, Arg};
+
+use qobjs::{common::*, types::GATEWAY_ADDR, func_client::FuncClient};
+
+#[derive(Debug)]
+pub struct CallCmd {
+    pub namespace: String,
+    pub packageName: String,
+    pub funcName: String,
+    pub data: String,
+    pub priority: usize,
+    pub gateway: String,
+}
+
+impl CallCmd {
+    pub fn Init(cmd_matches: &ArgMatches) -> Result<Self> {
+        let data = match (cmd_matches.value_of("data"), cmd_matches.value_of("file")) {
+            (Some(d), _) => d.to_string(),
+            (None, Some(f)) => std::fs::read_to_string(f)?,
+            (None, None) => String::new(),
+        };
+
+        return Ok(Self {
+            namespace: cmd_matches.value_of("namespace").unwrap().to_string(),
+            packageName: cmd_matches.value_of("package").unwrap().to_string(),
+            funcName: cmd_matches.value_of("func").unwrap().to_string(),
+            data: data,
+            priority: cmd_matches.value_of("priority").unwrap().parse()?,
+            gateway: cmd_matches.value_of("gateway").unwrap().to_string(),
+        });
+    }
+
+    pub fn SubCommand<'a, 'b>() -> App<'a, 'b> {
+        return SubCommand::with_name("call")
+            .setting(AppSettings::ColoredHelp)
+            .arg(
+                Arg::with_name("namespace")
+                    .required(true)
+                    .help("package namespace")
+                    .long("namespace")
+                    .short("n")
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("package")
+                    .required(true)
+                    .help("package name")
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("func")
+                    .required(true)
+                    .help("function name")
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("data")
+                    .help("function parameters")
+                    .long("data")
+                    .short("d")
+                    .conflicts_with("file")
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("file")
+                    .help("file contains the function parameters")
+                    .long("file")
+                    .short("f")
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("priority")
+                    .help("function call priority")
+                    .long("priority")
+                    .short("p")
+                    .default_value("1")
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("gateway")
+                    .help("func agent gateway address")
+                    .long("gateway")
+                    .default_value(GATEWAY_ADDR)
+                    .takes_value(true),
+            )
+            .about("Call a function and wait for its result");
+    }
+
+    pub async fn Run(&self) -> Result<()> {
+        let addr = format!("http://{}", &self.gateway);
+        let mut client = match FuncClient::Connect(&addr).await {
+            Err(e) => {
+                println!("can't connect the func gateway {} with error {:?}", &addr, e);
+                return Ok(())
+            }
+            Ok(c) => c
+        };
+
+        let id = uuid::Uuid::new_v4().to_string();
+        println!("func id {}", &id);
+        match client.CallWithId(&id, &self.namespace, &self.packageName, &self.funcName, &self.data, self.priority).await {
+            Err(e) => {
+                println!("call {}/{}/{} fail with error {:?}", &self.namespace, &self.packageName, &self.funcName, e);
+            }
+            Ok(res) => {
+                println!("{}", res);
+            }
+        }
+
+        return Ok(())
+    }
+}
@@ -17,7 +17,8 @@ use clap::{App, AppSettings};
 
 use qobjs::common::*;
 
-use crate::{create_pypackage::CreatePyPackageCmd, list::ListCmd, get::GetCmd, get_object::GetObjectCmd, delete::DeleteCmd};
+use crate::{create_pypackage::CreatePyPackageCmd, list::ListCmd, get::GetCmd, get_object::GetObjectCmd, delete::DeleteCmd, workflow::WorkflowCmd, blob::BlobCmd};
+use crate::{call::CallCmd, logs::LogsCmd, watch::WatchCmd};
 
 lazy_static::lazy_static! {
     pub static ref SUPPORT_OBJ_TYPES : BTreeSet<String> = [
@@ -36,6 +37,11 @@ pub enum Command {
     Get(GetCmd),
     Delete(DeleteCmd),
     GetObject(GetObjectCmd),
+    Workflow(WorkflowCmd),
+    Blob(BlobCmd),
+    Call(CallCmd),
+    Logs(LogsCmd),
+    Watch(WatchCmd),
 }
 
 pub async fn Run(args: &mut Arguments) -> Result<()> {
@@ -45,6 +51,11 @@ pub async fn Run(args: &mut Arguments) -> Result<()> {
         Command::Get(cmd) => return cmd.Run().await,
         Command::Delete(cmd) => return cmd.Run().await,
         Command::GetObject(cmd) => return cmd.Run().await,
+        Command::Workflow(cmd) => return cmd.Run().await,
+        Command::Blob(cmd) => return cmd.Run().await,
+        Command::Call(cmd) => return cmd.Run().await,
+        Command::Logs(cmd) => return cmd.Run().await,
+        Command::Watch(cmd) => return cmd.Run().await,
     }
 }
 
@@ -66,6 +77,11 @@ pub fn Parse() -> Result<Arguments> {
     .subcommand(GetCmd::SubCommand())
     .subcommand(DeleteCmd::SubCommand())
     .subcommand(GetObjectCmd::SubCommand())
+    .subcommand(WorkflowCmd::SubCommand())
+    .subcommand(BlobCmd::SubCommand())
+    .subcommand(CallCmd::SubCommand())
+    .subcommand(LogsCmd::SubCommand())
+    .subcommand(WatchCmd::SubCommand())
     .get_matches_from(get_args());
         
     let args = match matches.subcommand() {
@@ -75,7 +91,7 @@ pub fn Parse() -> Result<Arguments> {
         ("get", Some(cmd_matches)) => Arguments {
             cmd: Command::List(ListCmd::Init(&cmd_matches)?),
         },
-        ("describle", Some(cmd_matches)) => Arguments {
+        ("describe", Some(cmd_matches)) => Arguments {
             cmd: Command::Get(GetCmd::Init(&cmd_matches)?),
         },
         ("delete", Some(cmd_matches)) => Arguments {
@@ -84,6 +100,21 @@ pub fn Parse() -> Result<Arguments> {
         ("download", Some(cmd_matches)) => Arguments {
             cmd: Command::GetObject(GetObjectCmd::Init(&cmd_matches)?),
         },
+        ("workflow", Some(cmd_matches)) => Arguments {
+            cmd: Command::Workflow(WorkflowCmd::Init(&cmd_matches)?),
+        },
+        ("blob", Some(cmd_matches)) => Arguments {
+            cmd: Command::Blob(BlobCmd::Init(&cmd_matches)?),
+        },
+        ("call", Some(cmd_matches)) => Arguments {
+            cmd: Command::Call(CallCmd::Init(&cmd_matches)?),
+        },
+        ("logs", Some(cmd_matches)) => Arguments {
+            cmd: Command::Logs(LogsCmd::Init(&cmd_matches)?),
+        },
+        ("watch", Some(cmd_matches)) => Arguments {
+            cmd: Command::Watch(WatchCmd::Init(&cmd_matches)?),
+        },
         // We should never reach here because clap already enforces this
         x => panic!("command not recognized {:?}", x),
     };
@@ -14,9 +14,10 @@
 
 use clap::{App, AppSettings, ArgMatches, SubCommand, Arg};
 
-use qobjs::{common::*, types::QMETASVC_ADDR};
+use qobjs::{common::*, types::*, k8s, ObjectMeta, cacher_client::CacherClient, selection_predicate::ListOption};
+use qobjs::runtime_types::DefaultDomainName;
 
-use crate::{package_mgr::PackageMgr, command::SUPPORT_OBJ_TYPES};
+use crate::{package_mgr::PackageMgr, watch::ObjType};
 
 #[derive(Debug)]
 pub struct GetCmd {
@@ -27,25 +28,33 @@ pub struct GetCmd {
 
 impl GetCmd {
     pub fn Init(cmd_matches: &ArgMatches) -> Result<Self> {
+        let objType = ObjType(cmd_matches.value_of("objectType").unwrap()).to_string();
+        // the nodes are all in the default domain
+        let namespace = match cmd_matches.value_of("namespace") {
+            Some(ns) => ns.to_string(),
+            None if objType == QUARK_NODE => DefaultDomainName.to_string(),
+            None => return Err(Error::CommonError(format!("namespace is required for {}", &objType))),
+        };
+
         return Ok(Self {
-            objType: cmd_matches.value_of("objectType").unwrap().to_string(),
-            namespace: cmd_matches.value_of("namespace").unwrap().to_string(),
+            objType: objType,
+            namespace: namespace,
             name: cmd_matches.value_of("name").unwrap().to_string(),
         });
     }
 
     pub fn SubCommand<'a, 'b>() -> App<'a, 'b> {
-        return SubCommand::with_name("describle")
+        return SubCommand::with_name("describe")
+            .alias("describle")
             .setting(AppSettings::ColoredHelp)
             .arg(
                 Arg::with_name("objectType")
                     .required(true)
-                    .help("object type")
+                    .help("object type, pod, node or package")
                     .takes_value(true),
             )              
             .arg(
                 Arg::with_name("namespace")
-                    .required(true)
                     .help("object namespace")
                     .long("namespace")
                     .short("n")
@@ -57,34 +66,243 @@ impl GetCmd {
                     .help("object name")
                     .takes_value(true),
             )  
-            .about("Create a python function package");
+            .about("Show the details, conditions and resource requests of an object");
     }
 
     pub async fn Run(&self) -> Result<()> {
-        println!("Getcmd is {:?}", self);
         let addr = format!("http://{}", QMETASVC_ADDR);
-        let packageMgr = match PackageMgr::New(&addr).await {
+        let client = match CacherClient::New(addr.clone()).await {
             Err(e) => {
                 println!("can't connect the qservereless service {} with error {:?}", &addr, e);
                 return Ok(())
             }
-            Ok(m) => m
+            Ok(c) => c
         };
 
-        if !SUPPORT_OBJ_TYPES.contains(&self.objType) {
-            println!("doesn't support object type {}, support list is {:?}", &self.objType, SUPPORT_OBJ_TYPES.iter().collect::<Vec<_>>());
-            return Ok(())
-        }
-        
-        let package = match packageMgr.GetPackage(&self.namespace, &self.name).await {
-            Err(e) => {
-                println!("can't open list packages for namespace {} with error {:?}", &self.namespace, e);
+        let res = match self.objType.as_str() {
+            QUARK_POD => self.DescribePod(&client).await,
+            QUARK_NODE => self.DescribeNode(&client).await,
+            "package" => self.DescribePackage(&client, &addr).await,
+            t => {
+                println!("doesn't support object type {}, support list is [pod, node, package]", t);
                 return Ok(())
             }
-            Ok(s) => s 
         };
 
-        println!("package is {}", serde_json::to_string_pretty(&package).unwrap());
+        match res {
+            Err(e) => {
+                println!("can't describe {} {}/{} with error {:?}", &self.objType, &self.namespace, &self.name, e);
+            }
+            Ok(()) => (),
+        }
+        return Ok(())
+    }
+
+    pub async fn GetObj(&self, client: &CacherClient) -> Result<DataObject> {
+        match client.Get(&self.objType, &self.namespace, &self.name, 0).await? {
+            None => return Err(Error::ENOENT(format!("{} {}/{} doesn't exist", &self.objType, &self.namespace, &self.name))),
+            Some(obj) => return Ok(obj),
+        }
+    }
+
+    // the pods which match the filter in all namespaces
+    pub async fn ListPods<F: Fn(&k8s::Pod) -> bool>(client: &CacherClient, filter: F) -> Result<Vec<k8s::Pod>> {
+        let list = client.List(QUARK_POD, "", &ListOption::default()).await?;
+        let mut pods = Vec::new();
+        for obj in &list.objs {
+            let pod : k8s::Pod = serde_json::from_str(&obj.data)?;
+            if filter(&pod) {
+                pods.push(pod);
+            }
+        }
+        return Ok(pods)
+    }
+
+    pub fn Annotation(meta: &ObjectMeta, key: &str) -> String {
+        match &meta.annotations {
+            None => return String::new(),
+            Some(a) => return a.get(key).cloned().unwrap_or_default(),
+        }
+    }
+
+    // the sum of the container requests of the pod, (cpu in milli core, memory in bytes)
+    pub fn PodRequests(pod: &k8s::Pod) -> (u64, u64) {
+        let mut cpu = 0;
+        let mut mem = 0;
+        let spec = match &pod.spec {
+            None => return (0, 0),
+            Some(s) => s,
+        };
+        for c in &spec.containers {
+            let requests = match c.resources.as_ref().and_then(|r| r.requests.as_ref()) {
+                None => continue,
+                Some(r) => r,
+            };
+            if let Some(q) = requests.get("cpu") {
+                cpu += Resource::ParseCpuStr(&q.0).unwrap_or(0) as u64;
+            }
+            if let Some(q) = requests.get("memory") {
+                mem += Resource::ParseMemoryString(&q.0).unwrap_or(0);
+            }
+        }
+        return (cpu, mem)
+    }
+
+    pub fn PrintConditions(conditions: &[(String, String, String, String)]) {
+        println!("Conditions:");
+        if conditions.len() == 0 {
+            println!("  <none>");
+            return;
+        }
+        println!("  {:<30}{:<10}{:<24}{}", "TYPE", "STATUS", "LAST TRANSITION", "MESSAGE");
+        for (type_, status, time, message) in conditions {
+            println!("  {:<30}{:<10}{:<24}{}", type_, status, time, message);
+        }
+    }
+
+    pub async fn DescribePod(&self, client: &CacherClient) -> Result<()> {
+        let obj = self.GetObj(client).await?;
+        let pod : k8s::Pod = serde_json::from_str(&obj.data)?;
+        let status = pod.status.clone().unwrap_or_default();
+
+        println!("Name:       {}", &self.name);
+        println!("Namespace:  {}", &self.namespace);
+        println!("Revision:   {}", obj.Revision());
+        println!("Node:       {}", Self::Annotation(&pod.metadata, AnnotationNodeMgrNode));
+        println!("Package:    {}", Self::Annotation(&pod.metadata, AnnotationFuncPodPackageName));
+        println!("Phase:      {}", status.phase.clone().unwrap_or_default());
+        println!("Pod IP:     {}", status.pod_ip.clone().unwrap_or_default());
+
+        println!("Containers:");
+        if let Some(spec) = &pod.spec {
+            for c in &spec.containers {
+                println!("  {}:", &c.name);
+                println!("    Image:     {}", c.image.clone().unwrap_or_default());
+                if let Some(resources) = &c.resources {
+                    for (name, quantities) in [("Requests", &resources.requests), ("Limits", &resources.limits)] {
+                        if let Some(quantities) = quantities {
+                            let list : Vec<String> = quantities.iter().map(|(k, v)| format!("{}={}", k, v.0)).collect();
+                            println!("    {:<11}{}", format!("{}:", name), list.join(", "));
+                        }
+                    }
+                }
+                let cs = status.container_statuses.as_ref()
+                    .and_then(|s| s.iter().find(|s| s.name == c.name));
+                if let Some(cs) = cs {
+                    let state = match &cs.state {
+                        Some(s) if s.running.is_some() => "Running".to_string(),
+                        Some(s) if s.terminated.is_some() => {
+                            let t = s.terminated.as_ref().unwrap();
+                            format!("Terminated (exit code {}, {})", t.exit_code, t.reason.clone().unwrap_or_default())
+                        }
+                        Some(s) if s.waiting.is_some() => {
+                            format!("Waiting ({})", s.waiting.as_ref().unwrap().reason.clone().unwrap_or_default())
+                        }
+                        _ => "Unknown".to_string(),
+                    };
+                    println!("    State:     {}", state);
+                    println!("    Ready:     {}", cs.ready);
+                    println!("    Restarts:  {}", cs.restart_count);
+                }
+            }
+        }
+
+        let conditions : Vec<_> = status.conditions.unwrap_or_default().into_iter().map(|c| (
+            c.type_,
+            c.status,
+            c.last_transition_time.map(|t| t.0.to_rfc3339()).unwrap_or_default(),
+            c.message.unwrap_or_default(),
+        )).collect();
+        Self::PrintConditions(&conditions);
+        return Ok(())
+    }
+
+    pub async fn DescribeNode(&self, client: &CacherClient) -> Result<()> {
+        let obj = self.GetObj(client).await?;
+        let node : k8s::Node = serde_json::from_str(&obj.data)?;
+        let status = node.status.clone().unwrap_or_default();
+
+        println!("Name:       {}", &self.name);
+        println!("Revision:   {}", obj.Revision());
+        if let Some(addresses) = &status.addresses {
+            for a in addresses {
+                println!("{:<12}{}", format!("{}:", &a.type_), &a.address);
+            }
+        }
+
+        for (name, quantities) in [("Capacity", &status.capacity), ("Allocatable", &status.allocatable)] {
+            println!("{}:", name);
+            if let Some(quantities) = quantities {
+                for (k, v) in quantities {
+                    println!("  {:<20}{}", k, &v.0);
+                }
+            }
+        }
+
+        let nodeKey = obj.Key();
+        let pods = Self::ListPods(client, |pod| Self::Annotation(&pod.metadata, AnnotationNodeMgrNode) == nodeKey).await?;
+        let mut cpu = 0;
+        let mut mem = 0;
+        println!("Pods ({}):", pods.len());
+        println!("  {:<20}{:<40}{:<12}{:<12}{}", "NAMESPACE", "NAME", "PHASE", "CPU(m)", "MEMORY(Mi)");
+        for pod in &pods {
+            let (podCpu, podMem) = Self::PodRequests(pod);
+            cpu += podCpu;
+            mem += podMem;
+            println!("  {:<20}{:<40}{:<12}{:<12}{}",
+                pod.metadata.namespace.clone().unwrap_or_default(),
+                pod.metadata.name.clone().unwrap_or_default(),
+                pod.status.as_ref().and_then(|s| s.phase.clone()).unwrap_or_default(),
+                podCpu,
+                podMem / 1024 / 1024
+            );
+        }
+
+        // the allocated resource compares with the allocatable resource
+        let allocatable = status.allocatable.clone().unwrap_or_default();
+        let allocatableCpu = allocatable.get("cpu").map(|q| Resource::ParseCpuStr(&q.0).unwrap_or(0) as u64).unwrap_or(0);
+        let allocatableMem = allocatable.get("memory").map(|q| Resource::ParseMemoryString(&q.0).unwrap_or(0)).unwrap_or(0);
+        let percent = |used: u64, total: u64| if total == 0 { 0 } else { used * 100 / total };
+        println!("Allocated resources:");
+        println!("  {:<10}{:<16}{}", "RESOURCE", "REQUESTS", "PERCENT");
+        println!("  {:<10}{:<16}{}%", "cpu", format!("{}m", cpu), percent(cpu, allocatableCpu));
+        println!("  {:<10}{:<16}{}%", "memory", format!("{}Mi", mem / 1024 / 1024), percent(mem, allocatableMem));
+
+        let conditions : Vec<_> = status.conditions.unwrap_or_default().into_iter().map(|c| (
+            c.type_,
+            c.status,
+            c.last_transition_time.map(|t| t.0.to_rfc3339()).unwrap_or_default(),
+            c.message.unwrap_or_default(),
+        )).collect();
+        Self::PrintConditions(&conditions);
         return Ok(())
     }
-}
\ No newline at end of file
+
+    pub async fn DescribePackage(&self, client: &CacherClient, addr: &str) -> Result<()> {
+        let packageMgr = PackageMgr::New(addr).await?;
+        let package = packageMgr.GetPackage(&self.namespace, &self.name).await?;
+        println!("package is {}", serde_json::to_string_pretty(&package)?);
+
+        let pods = Self::ListPods(client, |pod| {
+            pod.metadata.namespace.as_deref() == Some(self.namespace.as_str())
+                && Self::Annotation(&pod.metadata, AnnotationFuncPodPackageName) == self.name
+        }).await?;
+
+        let mut cpu = 0;
+        let mut mem = 0;
+        println!("Pods ({}):", pods.len());
+        println!("  {:<40}{:<40}{:<12}", "NAME", "NODE", "PHASE");
+        for pod in &pods {
+            let (podCpu, podMem) = Self::PodRequests(pod);
+            cpu += podCpu;
+            mem += podMem;
+            println!("  {:<40}{:<40}{:<12}",
+                pod.metadata.name.clone().unwrap_or_default(),
+                Self::Annotation(&pod.metadata, AnnotationNodeMgrNode),
+                pod.status.as_ref().and_then(|s| s.phase.clone()).unwrap_or_default()
+            );
+        }
+        println!("Resource requests: cpu {}m, memory {}Mi", cpu, mem / 1024 / 1024);
+        return Ok(())
+    }
+}
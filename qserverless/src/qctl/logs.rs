@@ -0,0 +1,106 @@
+// Copyright (c) 2021 Quark Container Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write;
+use std::time::Duration;
+use clap::{App, AppSettings, ArgMatches, SubCommand, Arg};
+
+use qobjs::{common::*, types::QMETASVC_ADDR, types::FUNC_LOG_READ_MAX, cacher_client::CacherClient};
+
+// the interval to poll the new log when follow the log
+pub const LOG_FOLLOW_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug)]
+pub struct LogsCmd {
+    pub namespace: String,
+    pub funcId: String,
+    pub follow: bool,
+}
+
+impl LogsCmd {
+    pub fn Init(cmd_matches: &ArgMatches) -> Result<Self> {
+        return Ok(Self {
+            namespace: cmd_matches.value_of("namespace").unwrap().to_string(),
+            funcId: cmd_matches.value_of("funcId").unwrap().to_string(),
+            follow: cmd_matches.is_present("follow"),
+        });
+    }
+
+    pub fn SubCommand<'a, 'b>() -> App<'a, 'b> {
+        return SubCommand::with_name("logs")
+            .setting(AppSettings::ColoredHelp)
+            .arg(
+                Arg::with_name("namespace")
+                    .required(true)
+                    .help("function namespace")
+                    .long("namespace")
+                    .short("n")
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("funcId")
+                    .required(true)
+                    .help("function call id")
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("follow")
+                    .help("keep printing the new log")
+                    .long("follow")
+                    .short("f"),
+            )
+            .about("Print the log of a function call");
+    }
+
+    pub async fn Run(&self) -> Result<()> {
+        let addr = format!("http://{}", QMETASVC_ADDR);
+        let client = match CacherClient::New(addr.clone()).await {
+            Err(e) => {
+                println!("can't connect the qservereless service {} with error {:?}", &addr, e);
+                return Ok(())
+            }
+            Ok(c) => c
+        };
+
+        let mut offset = 0;
+        loop {
+            match client.ReadFuncLog(&self.namespace, &self.funcId, offset, FUNC_LOG_READ_MAX as u32).await {
+                Err(e) => {
+                    // the func might not be assigned to a node yet
+                    if !self.follow || offset > 0 {
+                        println!("can't read log of func {} with error {:?}", &self.funcId, e);
+                        return Ok(())
+                    }
+                }
+                Ok((content, readLen)) => {
+                    // the content might not be the raw bytes, e.g. the invalid utf8 is replaced
+                    offset += readLen;
+                    print!("{}", content);
+                    std::io::stdout().flush()?;
+
+                    // the log is read in chunks, read on until the end
+                    if readLen > 0 {
+                        continue;
+                    }
+                }
+            }
+
+            if !self.follow {
+                return Ok(())
+            }
+
+            tokio::time::sleep(LOG_FOLLOW_INTERVAL).await;
+        }
+    }
+}
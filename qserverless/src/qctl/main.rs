@@ -30,6 +30,11 @@ pub mod list;
 pub mod get;
 pub mod get_object;
 pub mod delete;
+pub mod workflow;
+pub mod blob;
+pub mod call;
+pub mod logs;
+pub mod watch;
 
 use command::{Parse, Run};
 use qobjs::{common::*, zip::ZipMgr};
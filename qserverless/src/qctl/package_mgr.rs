@@ -18,6 +18,10 @@ use qobjs::cacher_client::CacherClient;
 use qobjs::common::*;
 use qobjs::selection_predicate::ListOption;
 use qobjs::system_types::FuncPackage;
+use qobjs::system_types::Workflow;
+use qobjs::system_types::BlobLocation;
+use qobjs::system_types::BlobPolicy;
+use qobjs::system_types::DEFAULT_BLOB_POLICY_NAME;
 use qobjs::zip::ZipMgr;
 use qobjs::types::*;
 
@@ -147,4 +151,49 @@ impl PackageMgr {
 
         return Ok(funcPackage)
     }
-}
\ No newline at end of file
+
+    pub async fn CreateWorkflow(&self, workflow: &Workflow) -> Result<()> {
+        workflow.Validate()?;
+        let workflowStr = serde_json::to_string(workflow)?;
+        let obj = DataObject::NewFromK8sObj(QUARK_WORKFLOW, &workflow.metadata, workflowStr);
+        self.client.Create(QUARK_WORKFLOW, obj.Obj()).await?;
+        return Ok(())
+    }
+
+    pub async fn GetWorkflow(&self, namespace: &str, name: &str) -> Result<Workflow> {
+        let obj = match self.client.Get(QUARK_WORKFLOW, namespace, name, 0).await? {
+            None => return Err(Error::ENOENT(format!("can't get workflow with name {}/{}", namespace, name))),
+            Some(o) => o,
+        };
+
+        let workflow : Workflow = serde_json::from_str(&obj.data)?;
+        return Ok(workflow)
+    }
+
+    pub async fn ListBlobs(&self, namespace: &str) -> Result<Vec<BlobLocation>> {
+        let list = self.client.List(QUARK_BLOB, namespace, &ListOption::default()).await?;
+        let mut blobs = Vec::new();
+        for obj in &list.objs {
+            let blob : BlobLocation = serde_json::from_str(&obj.data)?;
+            blobs.push(blob);
+        }
+
+        return Ok(blobs)
+    }
+
+    // the node agents remove the local copies after the blob location is deleted
+    pub async fn DeleteBlob(&self, namespace: &str, name: &str) -> Result<()> {
+        self.client.Delete(QUARK_BLOB, namespace, &BlobLocation::ObjName(name)).await?;
+        return Ok(())
+    }
+
+    pub async fn GetBlobPolicy(&self, namespace: &str) -> Result<Option<BlobPolicy>> {
+        let obj = match self.client.Get(QUARK_BLOB_POLICY, namespace, DEFAULT_BLOB_POLICY_NAME, 0).await? {
+            None => return Ok(None),
+            Some(o) => o,
+        };
+
+        let policy : BlobPolicy = serde_json::from_str(&obj.data)?;
+        return Ok(Some(policy))
+    }
+}
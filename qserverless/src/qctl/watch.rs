@@ -0,0 +1,113 @@
+// Copyright (c) 2021 Quark Container Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap::{App, AppSettings, ArgMatches, SubCommand, Arg};
+
+use qobjs::{common::*, types::*, cacher_client::CacherClient, selection_predicate::ListOption};
+
+// map the object type name used in command line to the stored object type
+pub fn ObjType(name: &str) -> &str {
+    match name {
+        "pod" | "pods" => return QUARK_POD,
+        "node" | "nodes" => return QUARK_NODE,
+        "package" | "packages" => return "package",
+        n => return n,
+    }
+}
+
+#[derive(Debug)]
+pub struct WatchCmd {
+    pub objType: String,
+    pub namespace: String,
+    pub showData: bool,
+}
+
+impl WatchCmd {
+    pub fn Init(cmd_matches: &ArgMatches) -> Result<Self> {
+        return Ok(Self {
+            objType: ObjType(cmd_matches.value_of("objectType").unwrap()).to_string(),
+            namespace: cmd_matches.value_of("namespace").unwrap_or("").to_string(),
+            showData: cmd_matches.is_present("data"),
+        });
+    }
+
+    pub fn SubCommand<'a, 'b>() -> App<'a, 'b> {
+        return SubCommand::with_name("watch")
+            .setting(AppSettings::ColoredHelp)
+            .arg(
+                Arg::with_name("objectType")
+                    .required(true)
+                    .help("object type, e.g. pod, node, package, funccall, workflow")
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("namespace")
+                    .help("object namespace, all namespaces if not set")
+                    .long("namespace")
+                    .short("n")
+                    .takes_value(true),
+            )
+            .arg(
+                Arg::with_name("data")
+                    .help("print the object data of each event")
+                    .long("data")
+                    .short("d"),
+            )
+            .about("Watch the changes of objects");
+    }
+
+    pub async fn Run(&self) -> Result<()> {
+        let addr = format!("http://{}", QMETASVC_ADDR);
+        let client = match CacherClient::New(addr.clone()).await {
+            Err(e) => {
+                println!("can't connect the qservereless service {} with error {:?}", &addr, e);
+                return Ok(())
+            }
+            Ok(c) => c
+        };
+
+        let mut ws = match client.Watch(&self.objType, &self.namespace, &ListOption::default()).await {
+            Err(e) => {
+                println!("can't watch {} with error {:?}", &self.objType, e);
+                return Ok(())
+            }
+            Ok(ws) => ws
+        };
+
+        println!("{:<10}{:<12}{:<60}{}", "EVENT", "KIND", "KEY", "REVISION");
+        loop {
+            let event = match ws.Next().await {
+                Err(e) => {
+                    println!("watch {} fail with error {:?}", &self.objType, e);
+                    return Ok(())
+                }
+                Ok(None) => return Ok(()),
+                Ok(Some(e)) => e,
+            };
+
+            println!("{:<10}{:<12}{:<60}{}",
+                format!("{:?}", event.type_),
+                &event.obj.kind,
+                event.obj.Key(),
+                event.obj.Revision()
+            );
+            if self.showData {
+                match serde_json::from_str::<serde_json::Value>(&event.obj.data) {
+                    Ok(v) => println!("{}", serde_json::to_string_pretty(&v)?),
+                    Err(_) => println!("{}", &event.obj.data),
+                }
+            }
+        }
+    }
+}
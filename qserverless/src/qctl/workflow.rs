@@ -0,0 +1,158 @@
+// Copyright (c) 2021 Quark Container Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap::{App, AppSettings, ArgMatches, SubCommand, Arg};
+
+use qobjs::{common::*, types::QMETASVC_ADDR, system_types::Workflow};
+
+use crate::package_mgr::PackageMgr;
+
+#[derive(Debug)]
+pub enum WorkflowSubCmd {
+    Submit(String), // manifest file
+    Status(String, String), // namespace, name
+}
+
+#[derive(Debug)]
+pub struct WorkflowCmd {
+    pub cmd: WorkflowSubCmd,
+}
+
+impl WorkflowCmd {
+    pub fn Init(cmd_matches: &ArgMatches) -> Result<Self> {
+        let cmd = match cmd_matches.subcommand() {
+            ("submit", Some(m)) => {
+                WorkflowSubCmd::Submit(m.value_of("manifest").unwrap().to_string())
+            }
+            ("status", Some(m)) => {
+                WorkflowSubCmd::Status(
+                    m.value_of("namespace").unwrap().to_string(),
+                    m.value_of("name").unwrap().to_string(),
+                )
+            }
+            x => return Err(Error::CommonError(format!("workflow command not recognized {:?}", x))),
+        };
+
+        return Ok(Self {
+            cmd: cmd,
+        });
+    }
+
+    pub fn SubCommand<'a, 'b>() -> App<'a, 'b> {
+        return SubCommand::with_name("workflow")
+            .setting(AppSettings::ColoredHelp)
+            .setting(AppSettings::SubcommandRequired)
+            .subcommand(
+                SubCommand::with_name("submit")
+                    .setting(AppSettings::ColoredHelp)
+                    .arg(
+                        Arg::with_name("manifest")
+                            .required(true)
+                            .help("workflow definition")
+                            .long("manifest")
+                            .short("f")
+                            .takes_value(true),
+                    )
+                    .about("Submit a workflow")
+            )
+            .subcommand(
+                SubCommand::with_name("status")
+                    .setting(AppSettings::ColoredHelp)
+                    .arg(
+                        Arg::with_name("namespace")
+                            .required(true)
+                            .help("workflow namespace")
+                            .long("namespace")
+                            .short("n")
+                            .takes_value(true),
+                    )
+                    .arg(
+                        Arg::with_name("name")
+                            .required(true)
+                            .help("workflow name")
+                            .takes_value(true),
+                    )
+                    .about("Show the status of a workflow")
+            )
+            .about("Submit workflow and show its status");
+    }
+
+    pub async fn Run(&self) -> Result<()> {
+        let addr = format!("http://{}", QMETASVC_ADDR);
+        let packageMgr = match PackageMgr::New(&addr).await {
+            Err(e) => {
+                println!("can't connect the qservereless service {} with error {:?}", &addr, e);
+                return Ok(())
+            }
+            Ok(m) => m
+        };
+
+        match &self.cmd {
+            WorkflowSubCmd::Submit(manifestfile) => {
+                let workflowStr = match std::fs::read(manifestfile) {
+                    Err(e) => {
+                        println!("can't open manifest file {} with error {:?}", manifestfile, e);
+                        return Ok(())
+                    }
+                    Ok(s) => s 
+                };
+
+                let workflow : Workflow = match serde_json::from_slice(&workflowStr) {
+                    Err(e) => {
+                        println!("can't deserialize manifest {} with error {:?}", manifestfile, e);
+                        return Ok(())
+                    }
+                    Ok(w) => w 
+                };
+
+                match packageMgr.CreateWorkflow(&workflow).await {
+                    Err(e) => {
+                        println!("can't submit workflow with error {:?}", e);
+                    }
+                    Ok(()) => {
+                        println!("workflow {}/{} submitted", workflow.Namespace(), workflow.Name());
+                    }
+                }
+            }
+            WorkflowSubCmd::Status(namespace, name) => {
+                let workflow = match packageMgr.GetWorkflow(namespace, name).await {
+                    Err(e) => {
+                        println!("can't get workflow {}/{} with error {:?}", namespace, name, e);
+                        return Ok(())
+                    }
+                    Ok(w) => w 
+                };
+
+                println!("workflow {}/{} phase {:?} job {}", namespace, name, workflow.status.phase, &workflow.status.jobId);
+                println!("{:<24}{:<12}{:<10}{}", "STEP", "PHASE", "RETRIES", "ERROR");
+                for step in &workflow.spec.steps {
+                    match workflow.status.steps.get(&step.name) {
+                        None => println!("{:<24}{:<12}{:<10}", &step.name, "Pending", 0),
+                        Some(s) => {
+                            let retries : u32 = s.retries.iter().sum();
+                            println!("{:<24}{:<12}{:<10}{}", 
+                                &step.name, 
+                                format!("{:?}", s.phase), 
+                                retries, 
+                                s.error.as_deref().unwrap_or("")
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        return Ok(())
+    }
+}
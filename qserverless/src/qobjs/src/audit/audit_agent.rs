@@ -44,11 +44,21 @@ pub struct FinishFunc {
     pub funcState: String,
 }
 
+#[derive(Debug)]
+pub struct PlacePod {
+    pub podName: String,
+    pub namespace: String,
+    pub packageName: String,
+    pub nodeId: String,
+    pub score: i64,
+}
+
 #[derive(Debug)]
 pub enum AuditEvent {
     CreateFunc(CreateFunc),
     AssignFunc(AssignFunc),
     FinishFunc(FinishFunc),
+    PlacePod(PlacePod),
 }
 
 pub struct AuditAgentInner {
@@ -56,7 +66,8 @@ pub struct AuditAgentInner {
     pub stop: AtomicBool,
 
     pub tx: mpsc::Sender<AuditEvent>,
-    pub sqlAddr: String,
+    // the audit backend address, see NewFuncAudit
+    pub auditAddr: String,
 }
 
 
@@ -72,13 +83,13 @@ impl Deref for AuditAgent {
 }
 
 impl AuditAgent {
-    pub fn New(sqlAddr: &str) -> Self {
+    pub fn New(auditAddr: &str) -> Self {
         let (tx, rx) = mpsc::channel::<AuditEvent>(100);
         let inner = AuditAgentInner {
             closeNotify: Arc::new(Notify::new()),
             stop: AtomicBool::new(false),
             tx: tx,
-            sqlAddr: sqlAddr.to_owned()
+            auditAddr: auditAddr.to_owned()
         };
 
         let ret = AuditAgent(Arc::new(inner));
@@ -143,6 +154,26 @@ impl AuditAgent {
         return Ok(())
     }
 
+    pub fn PlacePod(
+        &self,
+        podName: &str,
+        namespace: &str,
+        packageName: &str,
+        nodeId: &str,
+        score: i64
+    ) -> Result<()> {
+        let cf = PlacePod {
+            podName: podName.to_owned(),
+            namespace: namespace.to_owned(),
+            packageName: packageName.to_owned(),
+            nodeId: nodeId.to_owned(),
+            score: score,
+        };
+
+        self.Send(AuditEvent::PlacePod(cf))?;
+        return Ok(())
+    }
+
     pub fn Send(&self, msg: AuditEvent) -> Result<()> {
         match self.tx.try_send(msg) {
             Ok(()) => return Ok(()),
@@ -153,7 +184,7 @@ impl AuditAgent {
     }
 
     pub async fn Process(&self, rx: mpsc::Receiver<AuditEvent>) -> Result<()> {
-        let audit = SqlFuncAudit::New(&self.sqlAddr).await.unwrap();
+        let audit = NewFuncAudit(&self.auditAddr, false).await.unwrap();
         let mut rx = rx;
         loop {
             tokio::select! {
@@ -164,16 +195,25 @@ impl AuditAgent {
 
                 msg = rx.recv() => {
                     if let Some(msg) = msg {
-                        match msg {
+                        // one failed audit record, e.g. the func record which was dropped when
+                        // the queue was full, doesn't stop the following records
+                        let ret = match &msg {
                             AuditEvent::CreateFunc(c) => {
-                                audit.CreateFunc(&c.id, &c.jobId, &c.namespace, &c.packageName, c.revision, &c.funcName, &c.callerFuncId).await?;
+                                audit.CreateFunc(&c.id, &c.jobId, &c.namespace, &c.packageName, c.revision, &c.funcName, &c.callerFuncId).await
                             }
                             AuditEvent::AssignFunc(c) => {
-                                audit.AssignFunc(&c.id, &c.nodeId).await?;
+                                audit.AssignFunc(&c.id, &c.nodeId).await
                             }
                             AuditEvent::FinishFunc(c) => {
-                                audit.FinishFunc(&c.id, &c.funcState).await?;
+                                audit.FinishFunc(&c.id, &c.funcState).await
                             }
+                            AuditEvent::PlacePod(c) => {
+                                audit.PlacePod(&c.podName, &c.namespace, &c.packageName, &c.nodeId, c.score).await
+                            }
+                        };
+
+                        if let Err(e) = ret {
+                            error!("AuditAgent write {:?} fail with error {:?}", &msg, e);
                         }
                     } else {
                         break;
@@ -0,0 +1,411 @@
+// Copyright (c) 2021 Quark Container Authors / 2014 The Kubernetes Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+use serde::{Deserialize, Serialize};
+
+use crate::common::*;
+use crate::audit::func_audit::*;
+
+// one line of the audit file
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "event")]
+pub enum FileAuditEvent {
+    CreateFunc(FuncAuditRecord),
+    AssignFunc {
+        id: String,
+        nodeId: String,
+        time: i64,
+    },
+    FinishFunc {
+        id: String,
+        funcState: String,
+        time: i64,
+    },
+    PlacePod(PodAuditRecord),
+}
+
+// the func audits kept in memory, the oldest ones are dropped beyond it
+pub const FILE_AUDIT_MAX_RECORDS: usize = 100_000;
+
+// the func audits rebuilt from the audit file, up to offset
+#[derive(Debug)]
+pub struct FileAuditIndex {
+    pub offset: u64,
+    pub maxRecords: usize,
+    // func id to the audit
+    pub records: HashMap<String, FuncAuditRecord>,
+    // the func ids in create order, to drop the oldest audits
+    pub order: VecDeque<String>,
+    // namespace to the (createTime, func id) of its audits, for the queries
+    pub namespaces: BTreeMap<String, BTreeSet<(i64, String)>>,
+}
+
+impl Default for FileAuditIndex {
+    fn default() -> Self {
+        return Self::New(FILE_AUDIT_MAX_RECORDS);
+    }
+}
+
+impl FileAuditIndex {
+    pub fn New(maxRecords: usize) -> Self {
+        return Self {
+            offset: 0,
+            maxRecords: maxRecords,
+            records: HashMap::new(),
+            order: VecDeque::new(),
+            namespaces: BTreeMap::new(),
+        };
+    }
+
+    pub fn Insert(&mut self, record: FuncAuditRecord) {
+        // the recreated audit moves to the end
+        if self.Remove(&record.id) {
+            if let Some(idx) = self.order.iter().position(|id| id == &record.id) {
+                self.order.remove(idx);
+            }
+        }
+
+        self.namespaces
+            .entry(record.namespace.clone())
+            .or_default()
+            .insert((record.createTime, record.id.clone()));
+        self.order.push_back(record.id.clone());
+        self.records.insert(record.id.clone(), record);
+
+        while self.records.len() > self.maxRecords {
+            match self.order.pop_front() {
+                None => break,
+                Some(id) => {
+                    self.Remove(&id);
+                }
+            }
+        }
+    }
+
+    // remove the audit from records and namespaces, the caller updates order
+    pub fn Remove(&mut self, id: &str) -> bool {
+        let record = match self.records.remove(id) {
+            None => return false,
+            Some(r) => r,
+        };
+
+        if let Some(ids) = self.namespaces.get_mut(&record.namespace) {
+            ids.remove(&(record.createTime, record.id));
+            if ids.len() == 0 {
+                self.namespaces.remove(&record.namespace);
+            }
+        }
+
+        return true;
+    }
+
+    pub fn Apply(&mut self, event: FileAuditEvent) {
+        match event {
+            FileAuditEvent::CreateFunc(record) => {
+                self.Insert(record);
+            }
+            FileAuditEvent::AssignFunc { id, nodeId, time } => {
+                if let Some(record) = self.records.get_mut(&id) {
+                    record.funcState = FuncStateAssigned.to_owned();
+                    record.nodeId = nodeId;
+                    record.assignedTime = time;
+                }
+            }
+            FileAuditEvent::FinishFunc { id, funcState, time } => {
+                if let Some(record) = self.records.get_mut(&id) {
+                    record.funcState = funcState;
+                    record.finishTime = time;
+                }
+            }
+            FileAuditEvent::PlacePod(_) => (),
+        }
+    }
+
+    // the matched audits of the namespace with the latest first, up to the query limit
+    pub fn Query(&self, query: &FuncAuditQuery) -> Vec<FuncAuditRecord> {
+        let mut records = Vec::new();
+        let ids = match self.namespaces.get(&query.namespace) {
+            None => return records,
+            Some(ids) => ids,
+        };
+
+        for (_, id) in ids.iter().rev() {
+            let record = &self.records[id];
+            if query.Match(record) {
+                records.push(record.clone());
+                if records.len() >= query.Limit() {
+                    break;
+                }
+            }
+        }
+
+        return query.Finish(records);
+    }
+}
+
+// the audit backend which appends the audit events to a json lines file. The index of
+// the latest func audits is kept in memory and the queries only read the lines appended
+// since the last query, which may be written by another process, so it is for small
+// deployments. The read only audit, i.e. node mgr, doesn't open the file for write
+pub struct FileFuncAudit {
+    pub path: String,
+    pub file: Option<Mutex<File>>,
+    pub index: Mutex<FileAuditIndex>,
+}
+
+impl FileFuncAudit {
+    pub fn New(path: &str, readOnly: bool) -> Result<Self> {
+        let file = if readOnly {
+            None
+        } else {
+            if let Some(dir) = Path::new(path).parent() {
+                if !dir.exists() {
+                    fs::create_dir_all(dir)?;
+                }
+            }
+
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)?;
+            Some(Mutex::new(file))
+        };
+
+        return Ok(Self {
+            path: path.to_owned(),
+            file: file,
+            index: Mutex::new(FileAuditIndex::default()),
+        })
+    }
+
+    pub fn Append(&self, event: &FileAuditEvent) -> Result<()> {
+        let file = match &self.file {
+            None => return Err(Error::CommonError(format!("FileFuncAudit is opened as read only"))),
+            Some(f) => f,
+        };
+
+        let mut line = serde_json::to_string(event)?;
+        line.push('\n');
+        let mut file = file.lock().unwrap();
+        file.write_all(line.as_bytes())?;
+        file.flush()?;
+        return Ok(())
+    }
+
+    // apply the complete lines appended to the file since the last catch up
+    pub fn CatchUp(&self) -> Result<MutexGuard<FileAuditIndex>> {
+        let mut index = self.index.lock().unwrap();
+        let mut file = match File::open(&self.path) {
+            Ok(f) => f,
+            // func service hasn't written any audit yet
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && self.file.is_none() => {
+                return Ok(index)
+            }
+            Err(e) => return Err(e.into()),
+        };
+        file.seek(SeekFrom::Start(index.offset))?;
+        let mut reader = BufReader::new(file);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let cnt = reader.read_line(&mut line)?;
+            // a partial line is read again by the next catch up
+            if cnt == 0 || !line.ends_with('\n') {
+                break;
+            }
+
+            index.offset += cnt as u64;
+            let trimmed = line.trim_end();
+            if trimmed.len() == 0 {
+                continue;
+            }
+
+            match serde_json::from_str::<FileAuditEvent>(trimmed) {
+                Err(e) => {
+                    error!("FileFuncAudit skip invalid line {} with error {:?}", trimmed, e);
+                }
+                Ok(event) => index.Apply(event),
+            }
+        }
+
+        return Ok(index)
+    }
+}
+
+#[async_trait::async_trait]
+impl FuncAudit for FileFuncAudit {
+    async fn CreateFunc(
+        &self,
+        id: &str,
+        jobId: &str,
+        namespace: &str,
+        packageName: &str,
+        revision: i64,
+        funcName: &str,
+        callerFuncId: &str
+    ) -> Result<()> {
+        let record = FuncAuditRecord {
+            id: id.to_owned(),
+            jobId: jobId.to_owned(),
+            namespace: namespace.to_owned(),
+            packageName: packageName.to_owned(),
+            revision: revision,
+            funcName: funcName.to_owned(),
+            callerFuncId: callerFuncId.to_owned(),
+            funcState: FuncStateCreated.to_owned(),
+            createTime: NowMillis(),
+            ..Default::default()
+        };
+
+        return self.Append(&FileAuditEvent::CreateFunc(record));
+    }
+
+    async fn AssignFunc(&self, id: &str, nodeId: &str) -> Result<()> {
+        return self.Append(&FileAuditEvent::AssignFunc {
+            id: id.to_owned(),
+            nodeId: nodeId.to_owned(),
+            time: NowMillis(),
+        });
+    }
+
+    async fn FinishFunc(&self, id: &str, funcState: &str) -> Result<()> {
+        return self.Append(&FileAuditEvent::FinishFunc {
+            id: id.to_owned(),
+            funcState: funcState.to_owned(),
+            time: NowMillis(),
+        });
+    }
+
+    async fn PlacePod(&self, podName: &str, namespace: &str, packageName: &str, nodeId: &str, score: i64) -> Result<()> {
+        return self.Append(&FileAuditEvent::PlacePod(PodAuditRecord {
+            podName: podName.to_owned(),
+            namespace: namespace.to_owned(),
+            packageName: packageName.to_owned(),
+            nodeId: nodeId.to_owned(),
+            score: score,
+            placeTime: NowMillis(),
+        }));
+    }
+
+    async fn GetNode(&self, namespace: &str, funcId: &str) -> Result<String> {
+        let record = self.CatchUp()?.records.get(funcId).cloned();
+        return FuncAuditRecord::Node(record, namespace, funcId);
+    }
+
+    async fn ReadFuncAudit(&self, query: &FuncAuditQuery) -> Result<Vec<FuncAuditRecord>> {
+        return Ok(self.CatchUp()?.Query(query))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn Record(id: &str, namespace: &str, jobId: &str, createTime: i64) -> FuncAuditRecord {
+        return FuncAuditRecord {
+            id: id.to_owned(),
+            jobId: jobId.to_owned(),
+            namespace: namespace.to_owned(),
+            funcState: FuncStateCreated.to_owned(),
+            createTime: createTime,
+            ..Default::default()
+        };
+    }
+
+    fn Ids(records: &[FuncAuditRecord]) -> Vec<&str> {
+        return records.iter().map(|r| r.id.as_str()).collect();
+    }
+
+    #[test]
+    fn TestFileAuditIndexQuery() {
+        let mut index = FileAuditIndex::default();
+        index.Apply(FileAuditEvent::CreateFunc(Record("f1", "ns1", "j1", 10)));
+        index.Apply(FileAuditEvent::CreateFunc(Record("f2", "ns1", "j2", 20)));
+        index.Apply(FileAuditEvent::CreateFunc(Record("f3", "ns1", "j1", 30)));
+        index.Apply(FileAuditEvent::CreateFunc(Record("f4", "ns2", "j1", 40)));
+        index.Apply(FileAuditEvent::AssignFunc {
+            id: "f1".to_owned(),
+            nodeId: "n1".to_owned(),
+            time: 11,
+        });
+
+        let mut query = FuncAuditQuery {
+            namespace: "ns1".to_owned(),
+            ..Default::default()
+        };
+        assert_eq!(Ids(&index.Query(&query)), vec!["f3", "f2", "f1"]);
+        assert_eq!(index.Query(&query)[2].nodeId, "n1");
+
+        query.limit = 2;
+        assert_eq!(Ids(&index.Query(&query)), vec!["f3", "f2"]);
+
+        query.limit = 0;
+        query.jobId = "j1".to_owned();
+        query.endTime = 30;
+        assert_eq!(Ids(&index.Query(&query)), vec!["f1"]);
+
+        query.namespace = "ns3".to_owned();
+        assert_eq!(index.Query(&query).len(), 0);
+    }
+
+    #[test]
+    fn TestFileAuditIndexBound() {
+        let mut index = FileAuditIndex::New(2);
+        index.Apply(FileAuditEvent::CreateFunc(Record("f1", "ns1", "j1", 10)));
+        index.Apply(FileAuditEvent::CreateFunc(Record("f2", "ns2", "j1", 20)));
+        index.Apply(FileAuditEvent::CreateFunc(Record("f3", "ns1", "j1", 30)));
+
+        // the oldest audit is dropped with its namespace index
+        assert_eq!(index.records.len(), 2);
+        assert!(index.records.get("f1").is_none());
+        assert_eq!(index.order, vec!["f2".to_owned(), "f3".to_owned()]);
+        let query = FuncAuditQuery {
+            namespace: "ns1".to_owned(),
+            ..Default::default()
+        };
+        assert_eq!(Ids(&index.Query(&query)), vec!["f3"]);
+
+        // the recreated audit moves to the end
+        index.Apply(FileAuditEvent::CreateFunc(Record("f2", "ns2", "j1", 40)));
+        index.Apply(FileAuditEvent::CreateFunc(Record("f4", "ns2", "j1", 50)));
+        assert_eq!(index.order, vec!["f2".to_owned(), "f4".to_owned()]);
+        assert_eq!(index.namespaces.get("ns1"), None);
+        assert_eq!(index.namespaces["ns2"].len(), 2);
+    }
+
+    #[test]
+    fn TestFileFuncAuditReadOnly() {
+        let path = std::env::temp_dir().join(format!("file_audit_test_{}.jsonl", std::process::id()));
+        let path = path.to_str().unwrap().to_owned();
+        let _ = fs::remove_file(&path);
+
+        // the reader doesn't create the file
+        let reader = FileFuncAudit::New(&path, true).unwrap();
+        assert!(!Path::new(&path).exists());
+        assert_eq!(reader.CatchUp().unwrap().records.len(), 0);
+        assert!(reader.Append(&FileAuditEvent::CreateFunc(Record("f1", "ns1", "j1", 10))).is_err());
+
+        let writer = FileFuncAudit::New(&path, false).unwrap();
+        writer.Append(&FileAuditEvent::CreateFunc(Record("f1", "ns1", "j1", 10))).unwrap();
+        assert_eq!(reader.CatchUp().unwrap().records.len(), 1);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -13,4 +13,6 @@
 // limitations under the License.
 
 pub mod func_audit;
-pub mod audit_agent;
\ No newline at end of file
+pub mod audit_agent;
+pub mod rocksdb_audit;
+pub mod file_audit;
\ No newline at end of file
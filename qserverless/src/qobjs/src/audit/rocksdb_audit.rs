@@ -0,0 +1,281 @@
+// Copyright (c) 2021 Quark Container Authors / 2014 The Kubernetes Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use rocksdb::{DB, Direction, IteratorMode, Options, SingleThreaded, WriteBatch};
+use rocksdb::DBWithThreadMode;
+
+use crate::common::*;
+use crate::audit::func_audit::*;
+
+pub const FUNC_KEY_PREFIX: &str = "func/";
+pub const JOB_KEY_PREFIX: &str = "job/";
+pub const TIME_KEY_PREFIX: &str = "time/";
+pub const POD_KEY_PREFIX: &str = "pod/";
+
+// the embedded audit backend, the func audits are indexed by job id and by namespace and
+// create time. func service opens the db as primary, node mgr opens it as secondary once the
+// primary has created it and catches up with the primary before each query
+pub struct RocksdbFuncAudit {
+    pub path: String,
+    pub db: Mutex<Option<DBWithThreadMode<SingleThreaded>>>,
+    pub readOnly: bool,
+}
+
+impl RocksdbFuncAudit {
+    pub fn New(path: &str, readOnly: bool) -> Result<Self> {
+        let db = if readOnly {
+            None
+        } else {
+            if !Path::new(path).exists() {
+                fs::create_dir_all(path)?;
+            }
+
+            let mut opts = Options::default();
+            opts.create_if_missing(true);
+            Some(DB::open(&opts, path)?)
+        };
+
+        return Ok(Self {
+            path: path.to_owned(),
+            db: Mutex::new(db),
+            readOnly: readOnly,
+        })
+    }
+
+    // the secondary keeps its own info log and manifest in the directory, which is reused
+    // across the restarts
+    pub fn SecondaryPath(&self) -> String {
+        return format!("{}-secondary", &self.path);
+    }
+
+    pub fn Db(&self) -> Result<MutexGuard<Option<DBWithThreadMode<SingleThreaded>>>> {
+        let mut db = self.db.lock().unwrap();
+        if db.is_none() {
+            if !Path::new(&self.path).join("CURRENT").exists() {
+                return Err(Error::CommonError(format!("RocksdbFuncAudit audit db {} isn't created by func service yet", &self.path)));
+            }
+
+            let mut opts = Options::default();
+            opts.set_max_open_files(-1);
+            *db = Some(DB::open_as_secondary(&opts, &self.path, &self.SecondaryPath())?);
+        } else if self.readOnly {
+            db.as_ref().unwrap().try_catch_up_with_primary()?;
+        }
+
+        return Ok(db)
+    }
+
+    pub fn FuncKey(id: &str) -> String {
+        return format!("{}{}", FUNC_KEY_PREFIX, id);
+    }
+
+    pub fn JobKey(jobId: &str, id: &str) -> String {
+        return format!("{}{}/{}", JOB_KEY_PREFIX, jobId, id);
+    }
+
+    pub fn TimePrefix(namespace: &str) -> String {
+        return format!("{}{}/", TIME_KEY_PREFIX, namespace);
+    }
+
+    // zero padded so that the keys are ordered by time
+    pub fn TimeKey(namespace: &str, time: i64, id: &str) -> String {
+        return format!("{}{:020}/{}", Self::TimePrefix(namespace), time, id);
+    }
+
+    pub fn CheckWritable(&self) -> Result<()> {
+        if self.readOnly {
+            return Err(Error::CommonError(format!("RocksdbFuncAudit is opened as read only")));
+        }
+
+        return Ok(())
+    }
+
+    pub fn Get(db: &DBWithThreadMode<SingleThreaded>, id: &str) -> Result<Option<FuncAuditRecord>> {
+        let data = match db.get(Self::FuncKey(id))? {
+            None => return Ok(None),
+            Some(d) => d,
+        };
+
+        let record : FuncAuditRecord = serde_json::from_slice(&data)?;
+        return Ok(Some(record))
+    }
+
+    pub fn Update(&self, id: &str, update: impl FnOnce(&mut FuncAuditRecord)) -> Result<()> {
+        let db = self.Db()?;
+        let db = db.as_ref().unwrap();
+        let mut record = match Self::Get(db, id)? {
+            None => return Err(Error::ENOENT(format!("RocksdbFuncAudit has no audit for func {}", id))),
+            Some(r) => r,
+        };
+
+        update(&mut record);
+        db.put(Self::FuncKey(id), serde_json::to_string(&record)?)?;
+        return Ok(())
+    }
+
+    // the func ids of the index keys with the prefix in the range [start, end), the latest
+    // first when reverse is set, at most limit ids
+    pub fn Scan(
+        db: &DBWithThreadMode<SingleThreaded>,
+        prefix: &str,
+        start: &str,
+        end: &str,
+        reverse: bool,
+        limit: usize
+    ) -> Result<Vec<String>> {
+        let mode = if reverse {
+            IteratorMode::From(end.as_bytes(), Direction::Reverse)
+        } else {
+            IteratorMode::From(start.as_bytes(), Direction::Forward)
+        };
+
+        let mut ids = Vec::new();
+        for v in db.iterator(mode) {
+            let v = v?;
+            let key = String::from_utf8(v.0.to_vec())?;
+            if reverse && key.as_str() >= end {
+                continue;
+            }
+
+            if !key.starts_with(prefix) || key.as_str() < start || key.as_str() >= end {
+                break;
+            }
+
+            if let Some((_, id)) = key.rsplit_once("/") {
+                ids.push(id.to_string());
+            }
+
+            if ids.len() >= limit {
+                break;
+            }
+        }
+
+        return Ok(ids)
+    }
+}
+
+#[async_trait::async_trait]
+impl FuncAudit for RocksdbFuncAudit {
+    async fn CreateFunc(
+        &self,
+        id: &str,
+        jobId: &str,
+        namespace: &str,
+        packageName: &str,
+        revision: i64,
+        funcName: &str,
+        callerFuncId: &str
+    ) -> Result<()> {
+        self.CheckWritable()?;
+        let record = FuncAuditRecord {
+            id: id.to_owned(),
+            jobId: jobId.to_owned(),
+            namespace: namespace.to_owned(),
+            packageName: packageName.to_owned(),
+            revision: revision,
+            funcName: funcName.to_owned(),
+            callerFuncId: callerFuncId.to_owned(),
+            funcState: FuncStateCreated.to_owned(),
+            createTime: NowMillis(),
+            ..Default::default()
+        };
+
+        let mut batch = WriteBatch::default();
+        batch.put(Self::FuncKey(id), serde_json::to_string(&record)?);
+        batch.put(Self::JobKey(jobId, id), "");
+        batch.put(Self::TimeKey(namespace, record.createTime, id), "");
+        self.Db()?.as_ref().unwrap().write(batch)?;
+        return Ok(())
+    }
+
+    async fn AssignFunc(&self, id: &str, nodeId: &str) -> Result<()> {
+        self.CheckWritable()?;
+        return self.Update(id, |record| {
+            record.funcState = FuncStateAssigned.to_owned();
+            record.nodeId = nodeId.to_owned();
+            record.assignedTime = NowMillis();
+        });
+    }
+
+    async fn FinishFunc(&self, id: &str, funcState: &str) -> Result<()> {
+        self.CheckWritable()?;
+        return self.Update(id, |record| {
+            record.funcState = funcState.to_owned();
+            record.finishTime = NowMillis();
+        });
+    }
+
+    async fn PlacePod(&self, podName: &str, namespace: &str, packageName: &str, nodeId: &str, score: i64) -> Result<()> {
+        self.CheckWritable()?;
+        let record = PodAuditRecord {
+            podName: podName.to_owned(),
+            namespace: namespace.to_owned(),
+            packageName: packageName.to_owned(),
+            nodeId: nodeId.to_owned(),
+            score: score,
+            placeTime: NowMillis(),
+        };
+
+        let key = format!("{}{:020}/{}", POD_KEY_PREFIX, record.placeTime, podName);
+        self.Db()?.as_ref().unwrap().put(key, serde_json::to_string(&record)?)?;
+        return Ok(())
+    }
+
+    async fn GetNode(&self, namespace: &str, funcId: &str) -> Result<String> {
+        let db = self.Db()?;
+        return FuncAuditRecord::Node(Self::Get(db.as_ref().unwrap(), funcId)?, namespace, funcId);
+    }
+
+    async fn ReadFuncAudit(&self, query: &FuncAuditQuery) -> Result<Vec<FuncAuditRecord>> {
+        let db = self.Db()?;
+        let db = db.as_ref().unwrap();
+
+        // the job index isn't ordered by time, the job records are sorted after they are loaded.
+        // the time index is scanned from the end so that only the latest records are loaded
+        let (ids, limit) = if query.jobId.len() > 0 {
+            let prefix = format!("{}{}/", JOB_KEY_PREFIX, &query.jobId);
+            let end = format!("{}{}0", JOB_KEY_PREFIX, &query.jobId);
+            (Self::Scan(db, &prefix, &prefix, &end, false, usize::MAX)?, usize::MAX)
+        } else {
+            let prefix = Self::TimePrefix(&query.namespace);
+            let start = Self::TimeKey(&query.namespace, query.startTime, "");
+            let end = if query.endTime > 0 {
+                Self::TimeKey(&query.namespace, query.endTime, "")
+            } else {
+                // '~' is after the zero padded time
+                format!("{}~", &prefix)
+            };
+            (Self::Scan(db, &prefix, &start, &end, true, query.Limit())?, query.Limit())
+        };
+
+        let mut records = Vec::new();
+        for id in &ids {
+            if let Some(record) = Self::Get(db, id)? {
+                if query.Match(&record) {
+                    records.push(record);
+                }
+            }
+
+            if records.len() >= limit {
+                break;
+            }
+        }
+
+        return Ok(query.Finish(records))
+    }
+}
@@ -247,6 +247,13 @@ impl Cacher {
         }
     }
 
+    // update the object only when its stored revision is expectRev, so that a client's
+    // read-modify-write fails instead of overwriting a concurrent update
+    pub async fn UpdateWithRev(&self, obj: &DataObject, expectRev: i64) -> Result<DataObject> {
+        let store = self.Store();
+        return store.Update(expectRev, obj).await;
+    }
+
     pub fn Contains(&self, key: &str) -> bool {
         return self.read().unwrap().GetByKey(key).is_some();
     }
@@ -67,6 +67,11 @@ impl CacherClient {
         return inner.Update(objType, obj).await;
     }
 
+    pub async fn UpdateWithRev(&self, objType: &str, obj: &DataObject, expectRev: i64) -> Result<i64> {
+        let mut inner = self.lock().await;
+        return inner.UpdateWithRev(objType, obj, expectRev).await;
+    }
+
     pub async fn List(&self, objType: &str, namespace: &str, opts: &ListOption) -> Result<DataObjList> {
         let mut inner = self.lock().await;
         return inner.List(objType, namespace, opts).await;
@@ -96,6 +101,12 @@ impl CacherClient {
         let mut inner = self.lock().await;
         return inner.ListObjects(namespace, prefix).await;
     }
+
+    // return the log content and the bytes read from the log file
+    pub async fn ReadFuncLog(&self, namespace: &str, funcId: &str, offset: u64, len: u32) -> Result<(String, u64)> {
+        let mut inner = self.lock().await;
+        return inner.ReadFuncLog(namespace, funcId, offset, len).await;
+    }
 }
 
 #[derive(Debug)]
@@ -165,9 +176,15 @@ impl CacherClientInner {
     }
 
     pub async fn Update(&mut self, objType: &str, obj: &DataObject) -> Result<i64> {
+        return self.UpdateWithRev(objType, obj, 0).await;
+    }
+
+    // update the object only when its stored revision is expectRev, 0 means unconditional
+    pub async fn UpdateWithRev(&mut self, objType: &str, obj: &DataObject, expectRev: i64) -> Result<i64> {
         let req = UpdateRequestMessage {
             obj_type: objType.to_string(),
             obj: Some(obj.Obj()),
+            expect_rev: expectRev,
         };
 
         let response = self.client.update(Request::new(req)).await?;
@@ -290,6 +307,23 @@ impl CacherClientInner {
 
         return Err(Error::CommonError(resp.error.to_owned()))
     }
+
+    async fn ReadFuncLog(&mut self, namespace: &str, funcId: &str, offset: u64, len: u32) -> Result<(String, u64)> {
+        let req = ReadFuncLogReq {
+            namespace: namespace.to_owned(),
+            func_name: funcId.to_owned(),
+            offset: offset,
+            len: len,
+        };
+
+        let response = self.client.read_func_log(Request::new(req)).await?;
+        let resp = response.into_inner();
+        if resp.error.len() == 0 {
+            return Ok((resp.content, resp.read_len))
+        }
+
+        return Err(Error::CommonError(resp.error.to_owned()))
+    }
 }
 
 pub struct WatchStream {
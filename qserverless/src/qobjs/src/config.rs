@@ -184,6 +184,9 @@ pub const KubeletPluginsDirSELinuxLabel     : &str = "system_u:object_r:containe
 pub const DefaultPodCgroupName              : &str = "containers";
 pub const DefaultRuntimeHandler             : &str = "runc";
 pub const DefaultPodConcurrency             : i32 = 5;
+pub const DefaultImageCacheLimit            : u64 = 50 * 1024 * 1024 * 1024;
+pub const DefaultImageGCHighThresholdPercent: u64 = 85;
+pub const DefaultImageGCLowThresholdPercent : u64 = 80;
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum ResourceName {
@@ -240,6 +243,9 @@ pub struct NodeConfigurationInner {
 	pub NodePortStartingNo       : i32,
 	pub SessionServicePort       : i32,
 	pub PodConcurrency           : i32,
+	pub ImageCacheLimit          : u64, // the disk space in bytes the node agent pulled images can use
+	pub ImageGCHighThresholdPercent : u64, // image gc starts when the image usage exceeds the percent of ImageCacheLimit
+	pub ImageGCLowThresholdPercent  : u64, // image gc frees the images until the usage is below the percent
 }
 
 impl NodeConfigurationInner {
@@ -289,6 +295,9 @@ impl NodeConfigurationInner {
             EnforceNodeAllocatable:   BTreeSet::new(),
             NodeAgentReserved:        BTreeMap::new(),
             SystemReserved:           BTreeMap::new(),
+            ImageCacheLimit:          DefaultImageCacheLimit,
+            ImageGCHighThresholdPercent: DefaultImageGCHighThresholdPercent,
+            ImageGCLowThresholdPercent:  DefaultImageGCLowThresholdPercent,
         })
     }
 
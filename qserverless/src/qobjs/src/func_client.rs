@@ -83,6 +83,14 @@ impl FuncClient {
         })
     }
 
+    // connect to the func agent gateway of a node by tcp, e.g. http://127.0.0.1:8889
+    pub async fn Connect(gatewayAddr: &str) -> Result<Self> {
+        let client = func::func_agent_service_client::FuncAgentServiceClient::connect(gatewayAddr.to_string()).await?;
+        return Ok(Self {
+            client: client,
+        })
+    }
+
     pub async fn Call(
         &mut self, 
         namespace: &str, 
@@ -92,6 +100,20 @@ impl FuncClient {
         priority: usize,
     ) -> SResult<String, FuncErr> {
         let id = uuid::Uuid::new_v4().to_string();
+        return self.CallWithId(&id, namespace, packageName, funcName, parameters, priority).await;
+    }
+
+    // call the func with the given id so that the caller can read its log and audit
+    pub async fn CallWithId(
+        &mut self, 
+        id: &str,
+        namespace: &str, 
+        packageName: &str,
+        funcName: &str,
+        parameters: &str,
+        priority: usize,
+    ) -> SResult<String, FuncErr> {
+        let id = id.to_string();
         let req = func::FuncAgentCallReq {
             job_id: id.clone(),
             id: id,
@@ -104,6 +126,8 @@ impl FuncClient {
             caller_func_id: String::new(), // direct call has no caller func
             priority: priority as u64,
             call_type: 1,
+            timeout: 0,
+            max_retry: 0,
         };
 
         let res = match self.client.func_call(req).await {
@@ -312,6 +312,12 @@ pub struct FuncAgentCallReq {
     /// 1: Normal 2: Iterate
     #[prost(int32, tag = "11")]
     pub call_type: i32,
+    /// the func call timeout in milliseconds, 0 means no deadline
+    #[prost(uint64, tag = "12")]
+    pub timeout: u64,
+    /// the max retry count when the callee pod fails
+    #[prost(uint32, tag = "13")]
+    pub max_retry: u32,
 }
 #[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -538,6 +544,14 @@ pub struct FuncSvcCallReq {
     pub callee_pod_id: ::prost::alloc::string::String,
     #[prost(int32, tag = "14")]
     pub call_type: i32,
+    /// the func call timeout in milliseconds from createtime, 0 means no deadline
+    #[prost(uint64, tag = "15")]
+    pub timeout: u64,
+    #[prost(uint32, tag = "16")]
+    pub max_retry: u32,
+    /// how many times the func call has been requeued for callee pod failure
+    #[prost(uint32, tag = "17")]
+    pub retry_count: u32,
 }
 #[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -82,6 +82,9 @@ pub struct ReadFuncLogResp {
     pub error: ::prost::alloc::string::String,
     #[prost(string, tag = "2")]
     pub content: ::prost::alloc::string::String,
+    /// the bytes read from the log file, the next read starts at offset + readLen
+    #[prost(uint64, tag = "3")]
+    pub read_len: u64,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -17,6 +17,60 @@ pub struct ReadFuncLogResp {
     pub error: ::prost::alloc::string::String,
     #[prost(string, tag = "2")]
     pub content: ::prost::alloc::string::String,
+    /// the bytes read from the log file, the next read starts at offset + readLen
+    #[prost(uint64, tag = "3")]
+    pub read_len: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+/// empty jobId matches all jobs, the times are unix time in milliseconds and 0 means no limit
+pub struct ReadFuncAuditReq {
+    #[prost(string, tag = "1")]
+    pub namespace: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub job_id: ::prost::alloc::string::String,
+    #[prost(int64, tag = "3")]
+    pub start_time: i64,
+    #[prost(int64, tag = "4")]
+    pub end_time: i64,
+    #[prost(uint32, tag = "5")]
+    pub limit: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FuncAudit {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub job_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub namespace: ::prost::alloc::string::String,
+    #[prost(string, tag = "4")]
+    pub package_name: ::prost::alloc::string::String,
+    #[prost(int64, tag = "5")]
+    pub revision: i64,
+    #[prost(string, tag = "6")]
+    pub func_name: ::prost::alloc::string::String,
+    #[prost(string, tag = "7")]
+    pub caller_func_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "8")]
+    pub func_state: ::prost::alloc::string::String,
+    #[prost(string, tag = "9")]
+    pub node_id: ::prost::alloc::string::String,
+    #[prost(int64, tag = "10")]
+    pub create_time: i64,
+    #[prost(int64, tag = "11")]
+    pub assigned_time: i64,
+    #[prost(int64, tag = "12")]
+    pub finish_time: i64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReadFuncAuditResp {
+    #[prost(string, tag = "1")]
+    pub error: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag = "2")]
+    pub audits: ::prost::alloc::vec::Vec<FuncAudit>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -225,6 +279,9 @@ pub struct UpdateRequestMessage {
     pub obj_type: ::prost::alloc::string::String,
     #[prost(message, optional, tag = "2")]
     pub obj: ::core::option::Option<Obj>,
+    /// update only when the stored object revision is expect_rev, 0 means unconditional
+    #[prost(int64, tag = "3")]
+    pub expect_rev: i64,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -574,6 +631,25 @@ pub mod q_meta_service_client {
             );
             self.inner.unary(request.into_request(), path, codec).await
         }
+        pub async fn read_func_audit(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ReadFuncAuditReq>,
+        ) -> Result<tonic::Response<super::ReadFuncAuditResp>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/qmeta.QMetaService/ReadFuncAudit",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -637,6 +713,10 @@ pub mod q_meta_service_server {
             &self,
             request: tonic::Request<super::ReadFuncLogReq>,
         ) -> Result<tonic::Response<super::ReadFuncLogResp>, tonic::Status>;
+        async fn read_func_audit(
+            &self,
+            request: tonic::Request<super::ReadFuncAuditReq>,
+        ) -> Result<tonic::Response<super::ReadFuncAuditResp>, tonic::Status>;
     }
     #[derive(Debug)]
     pub struct QMetaServiceServer<T: QMetaService> {
@@ -1150,6 +1230,46 @@ pub mod q_meta_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/qmeta.QMetaService/ReadFuncAudit" => {
+                    #[allow(non_camel_case_types)]
+                    struct ReadFuncAuditSvc<T: QMetaService>(pub Arc<T>);
+                    impl<
+                        T: QMetaService,
+                    > tonic::server::UnaryService<super::ReadFuncAuditReq>
+                    for ReadFuncAuditSvc<T> {
+                        type Response = super::ReadFuncAuditResp;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ReadFuncAuditReq>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).read_func_audit(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ReadFuncAuditSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         Ok(
@@ -17,6 +17,16 @@ CREATE TABLE FuncAudit (
     finishTime      TIMESTAMP
 );
 
+DROP TABLE PodAudit;
+CREATE TABLE PodAudit (
+    podName         VARCHAR NOT NULL PRIMARY KEY,
+    namespace       VARCHAR NOT NULL,
+    packageName     VARCHAR NOT NULL,
+    nodeId          VARCHAR NOT NULL,
+    score           bigint,
+    placeTime       TIMESTAMP
+);
+
 CREATE USER audit_user WITH PASSWORD '123456';
 GRANT ALL ON ALL TABLES IN SCHEMA public to audit_user;
 
@@ -13,9 +13,14 @@
 // limitations under the License.
 
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 
 use crate::ObjectMeta;
 use crate::k8s;
+use crate::func;
+use crate::utility::SystemTimeProto;
+use crate::common::*;
+use crate::types::LabelBlobJobId;
 
 #[derive(Serialize, Deserialize, Debug, Default)]
 pub struct FuncPackage {
@@ -26,4 +31,579 @@ pub struct FuncPackage {
 #[derive(Serialize, Deserialize, Debug, Default)]
 pub struct FuncPackageSpec {
     pub template: k8s::PodSpec,
-}
\ No newline at end of file
+}
+// the stage of a func call persisted by func service
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FuncCallStage {
+    Queued, // waiting for a func pod
+    Assigned, // assigned to a func pod
+    Running, // the func pod has acked the call
+    Done, // the result is ready, waiting for caller to get it
+}
+
+impl Default for FuncCallStage {
+    fn default() -> Self {
+        return Self::Queued;
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct FuncCallRecordSpec {
+    pub jobId: String,
+    pub packageName: String,
+    pub funcName: String,
+    pub parameters: String,
+    pub priority: u64,
+    pub createTimeSec: u64,
+    pub createTimeNanos: u32,
+    pub callerNodeId: String,
+    pub callerPodId: String,
+    pub callerFuncId: String,
+    pub callType: i32,
+    // the timeout in milliseconds from the create time, 0 means no deadline
+    #[serde(default)]
+    pub timeout: u64,
+    #[serde(default)]
+    pub maxRetry: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct FuncCallRecordStatus {
+    pub stage: FuncCallStage,
+    pub calleeNodeId: String,
+    pub calleePodId: String,
+    // the func result when the stage is Done
+    pub resp: Option<String>,
+    pub error: Option<String>,
+    pub errorSource: i32,
+    // the times the func call has been requeued for callee pod failure
+    #[serde(default)]
+    pub retryCount: u32,
+}
+
+// func call state stored in qobjs store, the name is the func call id
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct FuncCallRecord {
+    pub metadata: ObjectMeta,
+    pub spec: FuncCallRecordSpec,
+    pub status: FuncCallRecordStatus,
+}
+
+impl FuncCallRecord {
+    pub fn NewFromGrpc(req: &func::FuncSvcCallReq, stage: FuncCallStage) -> Self {
+        let createTime = match &req.createtime {
+            None => SystemTimeProto { seconds: 0, nanos: 0 },
+            Some(t) => SystemTimeProto::FromTimestamp(t),
+        };
+        return Self {
+            metadata: ObjectMeta { 
+                namespace: Some(req.namespace.clone()),
+                name: Some(req.id.clone()),
+                ..Default::default()
+            },
+            spec: FuncCallRecordSpec {
+                jobId: req.job_id.clone(),
+                packageName: req.package_name.clone(),
+                funcName: req.func_name.clone(),
+                parameters: req.parameters.clone(),
+                priority: req.priority,
+                createTimeSec: createTime.seconds,
+                createTimeNanos: createTime.nanos,
+                callerNodeId: req.caller_node_id.clone(),
+                callerPodId: req.caller_pod_id.clone(),
+                callerFuncId: req.caller_func_id.clone(),
+                callType: req.call_type,
+                timeout: req.timeout,
+                maxRetry: req.max_retry,
+            },
+            status: FuncCallRecordStatus {
+                stage: stage,
+                calleeNodeId: req.callee_node_id.clone(),
+                calleePodId: req.callee_pod_id.clone(),
+                retryCount: req.retry_count,
+                ..Default::default()
+            }
+        }
+    }
+
+    pub fn Id(&self) -> String {
+        return self.metadata.name.as_deref().unwrap_or("").to_string();
+    }
+
+    pub fn Namespace(&self) -> String {
+        return self.metadata.namespace.as_deref().unwrap_or("").to_string();
+    }
+
+    pub fn ToGrpcType(&self) -> func::FuncSvcCallReq {
+        let createTime = SystemTimeProto {
+            seconds: self.spec.createTimeSec,
+            nanos: self.spec.createTimeNanos,
+        };
+        return func::FuncSvcCallReq {
+            job_id: self.spec.jobId.clone(),
+            id: self.Id(),
+            namespace: self.Namespace(),
+            package_name: self.spec.packageName.clone(),
+            func_name: self.spec.funcName.clone(),
+            parameters: self.spec.parameters.clone(),
+            priority: self.spec.priority,
+            createtime: Some(createTime.ToTimeStamp()),
+            caller_node_id: self.spec.callerNodeId.clone(),
+            caller_pod_id: self.spec.callerPodId.clone(),
+            caller_func_id: self.spec.callerFuncId.clone(),
+            callee_node_id: self.status.calleeNodeId.clone(),
+            callee_pod_id: self.status.calleePodId.clone(),
+            call_type: self.spec.callType,
+            timeout: self.spec.timeout,
+            max_retry: self.spec.maxRetry,
+            retry_count: self.status.retryCount,
+        }
+    }
+
+    pub fn Result(&self) -> Option<func::FuncRes> {
+        if let Some(resp) = &self.status.resp {
+            return Some(func::FuncRes {
+                res: Some(func::func_res::Res::Resp(resp.clone()))
+            });
+        }
+
+        if let Some(error) = &self.status.error {
+            return Some(func::FuncRes {
+                res: Some(func::func_res::Res::Error(func::Error {
+                    source: self.status.errorSource,
+                    error: error.clone(),
+                }))
+            });
+        }
+
+        return None;
+    }
+
+    pub fn SetResult(&mut self, res: &func::FuncRes) {
+        match &res.res {
+            None => (),
+            Some(func::func_res::Res::Resp(resp)) => {
+                self.status.resp = Some(resp.clone());
+            }
+            Some(func::func_res::Res::Error(e)) => {
+                self.status.error = Some(e.error.clone());
+                self.status.errorSource = e.source;
+            }
+        }
+        self.status.stage = FuncCallStage::Done;
+    }
+}
+
+// the retry policy of a workflow step when its func call fails
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct WorkflowRetryPolicy {
+    #[serde(default)]
+    pub limit: u32,
+    #[serde(default)]
+    pub backoffMs: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkflowConditionState {
+    Succeeded,
+    Failed,
+}
+
+// the step runs only when the referred step ends with the state,
+// and its result contains the string if it is set
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WorkflowCondition {
+    pub step: String,
+    pub state: WorkflowConditionState,
+    #[serde(default)]
+    pub contains: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct WorkflowStep {
+    pub name: String,
+    pub packageName: String,
+    pub funcName: String,
+    // the parameters can refer the dependent step result with {{steps.<name>.result}}
+    #[serde(default)]
+    pub parameters: String,
+    // fan out, start one func call for each item with {{item}} replaced in parameters
+    #[serde(default)]
+    pub items: Vec<String>,
+    // fan in, the step starts after all the dependent steps finish
+    #[serde(default)]
+    pub dependsOn: Vec<String>,
+    #[serde(default)]
+    pub when: Option<WorkflowCondition>,
+    #[serde(default)]
+    pub retry: WorkflowRetryPolicy,
+    #[serde(default)]
+    pub priority: u64,
+    // the timeout of each func call of the step in milliseconds, 0 means no deadline
+    #[serde(default)]
+    pub timeout: u64,
+}
+
+impl WorkflowStep {
+    // the func call count of the step
+    pub fn ItemCount(&self) -> usize {
+        if self.items.len() == 0 {
+            return 1;
+        }
+
+        return self.items.len();
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct WorkflowSpec {
+    pub steps: Vec<WorkflowStep>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkflowPhase {
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+    Skipped,
+}
+
+impl Default for WorkflowPhase {
+    fn default() -> Self {
+        return Self::Pending;
+    }
+}
+
+impl WorkflowPhase {
+    pub fn IsFinished(&self) -> bool {
+        match self {
+            Self::Succeeded | Self::Failed | Self::Skipped => return true,
+            _ => return false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct WorkflowStepStatus {
+    pub phase: WorkflowPhase,
+    // the func call ids, one for each item when fan out
+    #[serde(default)]
+    pub calls: Vec<String>,
+    // the retried count of each func call
+    #[serde(default)]
+    pub retries: Vec<u32>,
+    // the results of the func calls
+    #[serde(default)]
+    pub results: Vec<Option<String>>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl WorkflowStepStatus {
+    // the step result, a json array when the step fans out
+    pub fn Result(&self) -> String {
+        if self.results.len() == 1 {
+            return self.results[0].clone().unwrap_or_default();
+        }
+
+        let results : Vec<String> = self.results.iter().map(|r| r.clone().unwrap_or_default()).collect();
+        return serde_json::to_string(&results).unwrap_or_default();
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct WorkflowStatus {
+    pub phase: WorkflowPhase,
+    #[serde(default)]
+    pub jobId: String,
+    #[serde(default)]
+    pub steps: BTreeMap<String, WorkflowStepStatus>,
+}
+
+// a DAG of func calls executed by func service
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct Workflow {
+    pub metadata: ObjectMeta,
+    pub spec: WorkflowSpec,
+    #[serde(default)]
+    pub status: WorkflowStatus,
+}
+
+impl Workflow {
+    pub fn Namespace(&self) -> String {
+        return self.metadata.namespace.as_deref().unwrap_or("").to_string();
+    }
+
+    pub fn Name(&self) -> String {
+        return self.metadata.name.as_deref().unwrap_or("").to_string();
+    }
+
+    pub fn Step(&self, name: &str) -> Option<&WorkflowStep> {
+        for step in &self.spec.steps {
+            if &step.name == name {
+                return Some(step);
+            }
+        }
+
+        return None;
+    }
+
+    // check the step names are unique and the dependencies have no cycle
+    pub fn Validate(&self) -> Result<()> {
+        let mut names = BTreeSet::new();
+        for step in &self.spec.steps {
+            if !names.insert(step.name.clone()) {
+                return Err(Error::CommonError(format!("workflow has duplicate step {}", &step.name)));
+            }
+        }
+
+        for step in &self.spec.steps {
+            for dep in &step.dependsOn {
+                if !names.contains(dep) {
+                    return Err(Error::CommonError(format!("workflow step {} depends on unknown step {}", &step.name, dep)));
+                }
+            }
+
+            if let Some(cond) = &step.when {
+                if !step.dependsOn.contains(&cond.step) {
+                    return Err(Error::CommonError(format!("workflow step {} condition refers step {} which is not in dependsOn", &step.name, &cond.step)));
+                }
+            }
+        }
+
+        // topology sort
+        let mut done = BTreeSet::new();
+        loop {
+            let mut progress = false;
+            for step in &self.spec.steps {
+                if done.contains(&step.name) {
+                    continue;
+                }
+                if step.dependsOn.iter().all(|d| done.contains(d)) {
+                    done.insert(step.name.clone());
+                    progress = true;
+                }
+            }
+
+            if done.len() == self.spec.steps.len() {
+                return Ok(())
+            }
+
+            if !progress {
+                return Err(Error::CommonError(format!("workflow {}/{} has dependency cycle", self.Namespace(), self.Name())));
+            }
+        }
+    }
+
+    // check the status matches the steps, the persisted status of a running workflow
+    // might not match it after the workflow spec changes
+    pub fn ValidateStatus(&self) -> Result<()> {
+        for name in self.status.steps.keys() {
+            if self.Step(name).is_none() {
+                return Err(Error::CommonError(format!("workflow status has unknown step {}", name)));
+            }
+        }
+
+        for step in &self.spec.steps {
+            let status = match self.status.steps.get(&step.name) {
+                None => return Err(Error::CommonError(format!("workflow step {} has no status", &step.name))),
+                Some(s) => s,
+            };
+
+            let count = status.calls.len();
+            if status.retries.len() != count || status.results.len() != count {
+                return Err(Error::CommonError(format!("workflow step {} has {} calls, {} retries and {} results",
+                    &step.name, count, status.retries.len(), status.results.len())));
+            }
+
+            if status.phase == WorkflowPhase::Running && count != step.ItemCount() {
+                return Err(Error::CommonError(format!("workflow step {} has {} calls for {} items", &step.name, count, step.ItemCount())));
+            }
+        }
+
+        return Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct BlobLocationSpec {
+    // the blob name, the object name is escaped from it as blob name might contain '/'
+    pub name: String,
+    pub size: u64,
+    pub checksum: String,
+    // the blob service addresses of the nodes which have the blob
+    #[serde(default)]
+    pub locations: Vec<String>,
+    // the blob service addresses of the nodes which are copying the blob
+    #[serde(default)]
+    pub pending: Vec<String>,
+    // the job which owns the blob, the blob is removed when the job finishes
+    #[serde(default)]
+    pub jobId: String,
+    // the unix time in seconds when the blob expires, 0 means never
+    #[serde(default)]
+    pub expireTimeSec: u64,
+}
+
+// the nodes which store a blob, registered by node agents
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct BlobLocation {
+    pub metadata: ObjectMeta,
+    pub spec: BlobLocationSpec,
+}
+
+impl BlobLocation {
+    pub fn ObjName(name: &str) -> String {
+        return name.replace("%", "%25").replace("/", "%2F");
+    }
+
+    pub fn New(namespace: &str, name: &str, size: u64, checksum: &str, jobId: &str, expireTimeSec: u64) -> Self {
+        let mut labels = BTreeMap::new();
+        if jobId.len() > 0 {
+            labels.insert(LabelBlobJobId.to_string(), jobId.to_string());
+        }
+
+        return Self {
+            metadata: ObjectMeta {
+                namespace: Some(namespace.to_string()),
+                name: Some(Self::ObjName(name)),
+                labels: Some(labels),
+                ..Default::default()
+            },
+            spec: BlobLocationSpec {
+                name: name.to_string(),
+                size: size,
+                checksum: checksum.to_string(),
+                jobId: jobId.to_string(),
+                expireTimeSec: expireTimeSec,
+                ..Default::default()
+            }
+        }
+    }
+
+    pub fn Namespace(&self) -> String {
+        return self.metadata.namespace.as_deref().unwrap_or("").to_string();
+    }
+
+    pub fn Name(&self) -> String {
+        return self.spec.name.clone();
+    }
+}
+
+pub const DEFAULT_BLOB_POLICY_NAME : &str = "default";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BlobPolicySpec {
+    // how many nodes keep a copy of each blob of the namespace
+    #[serde(default = "BlobPolicySpec::DefaultReplicas")]
+    pub replicas: u32,
+    // the max total size of the blobs in the namespace, 0 means no limit
+    #[serde(default)]
+    pub maxBytes: u64,
+    // the max count of the blobs in the namespace, 0 means no limit
+    #[serde(default)]
+    pub maxCount: u64,
+    // the blob expires after the seconds since its creation, 0 means never
+    #[serde(default)]
+    pub ttlSec: u64,
+    // remove the blobs created by a job when the job finishes
+    #[serde(default)]
+    pub gcWithJob: bool,
+}
+
+impl Default for BlobPolicySpec {
+    fn default() -> Self {
+        return Self {
+            replicas: Self::DefaultReplicas(),
+            maxBytes: 0,
+            maxCount: 0,
+            ttlSec: 0,
+            gcWithJob: false,
+        }
+    }
+}
+
+impl BlobPolicySpec {
+    pub fn DefaultReplicas() -> u32 {
+        return 1;
+    }
+}
+
+// the blob policy of a namespace, the object name is DEFAULT_BLOB_POLICY_NAME
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct BlobPolicy {
+    pub metadata: ObjectMeta,
+    pub spec: BlobPolicySpec,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn NewWorkflow(steps: &[(&str, &[&str])]) -> Workflow {
+        let mut workflow = Workflow::default();
+        workflow.metadata.namespace = Some("ns1".to_owned());
+        workflow.metadata.name = Some("wf1".to_owned());
+        for (name, deps) in steps {
+            workflow.spec.steps.push(WorkflowStep {
+                name: name.to_string(),
+                dependsOn: deps.iter().map(|d| d.to_string()).collect(),
+                ..Default::default()
+            });
+        }
+        return workflow;
+    }
+
+    #[test]
+    fn TestWorkflowValidate() {
+        assert!(NewWorkflow(&[("a", &[]), ("b", &["a"]), ("c", &["a"]), ("d", &["b", "c"])]).Validate().is_ok());
+        // the steps are not required to be listed in the dependency order
+        assert!(NewWorkflow(&[("b", &["a"]), ("a", &[])]).Validate().is_ok());
+
+        assert!(NewWorkflow(&[("a", &[]), ("a", &[])]).Validate().is_err());
+        assert!(NewWorkflow(&[("a", &[]), ("b", &["x"])]).Validate().is_err());
+        assert!(NewWorkflow(&[("a", &["a"])]).Validate().is_err());
+        assert!(NewWorkflow(&[("a", &[]), ("b", &["a", "c"]), ("c", &["b"])]).Validate().is_err());
+
+        let mut workflow = NewWorkflow(&[("a", &[]), ("b", &[]), ("c", &["a"])]);
+        workflow.spec.steps[2].when = Some(WorkflowCondition {
+            step: "b".to_owned(),
+            state: WorkflowConditionState::Failed,
+            contains: None,
+        });
+        assert!(workflow.Validate().is_err());
+        workflow.spec.steps[2].dependsOn.push("b".to_owned());
+        assert!(workflow.Validate().is_ok());
+    }
+
+    #[test]
+    fn TestWorkflowValidateStatus() {
+        let mut workflow = NewWorkflow(&[("a", &[]), ("b", &["a"])]);
+        workflow.spec.steps[0].items = vec!["x".to_owned(), "y".to_owned()];
+        assert!(workflow.ValidateStatus().is_err());
+
+        workflow.status.steps.insert("b".to_owned(), WorkflowStepStatus::default());
+        workflow.status.steps.insert("a".to_owned(), WorkflowStepStatus {
+            phase: WorkflowPhase::Running,
+            calls: vec!["c1".to_owned(), "c2".to_owned()],
+            retries: vec![0, 0],
+            results: vec![None, Some("r2".to_owned())],
+            error: None,
+        });
+        assert!(workflow.ValidateStatus().is_ok());
+
+        // the step fans out to more items after the status is persisted
+        workflow.spec.steps[0].items.push("z".to_owned());
+        assert!(workflow.ValidateStatus().is_err());
+        workflow.spec.steps[0].items.pop();
+
+        workflow.status.steps.get_mut("a").unwrap().results.pop();
+        assert!(workflow.ValidateStatus().is_err());
+        workflow.status.steps.get_mut("a").unwrap().results.push(None);
+
+        // the step is removed from the spec
+        workflow.spec.steps.pop();
+        assert!(workflow.ValidateStatus().is_err());
+    }
+}
@@ -26,6 +26,7 @@ use serde::{Deserialize, Serialize};
 type Client = hyper_util::client::legacy::Client<HttpConnector, Body>;
 
 use qshare::common::*;
+use qshare::node::{NetworkPolicy, ServiceDef};
 
 use crate::namespace_mgr::NamespaceSpec;
 use crate::NAMESPACE_MGR;
@@ -56,6 +57,13 @@ impl HttpGateway {
                 get(GetFuncPackage),
             )
             .route("/funcpackages/:tenant/:namespace", get(GetFuncPackages))
+            .route("/services/", post(PostService))
+            .route("/services/:tenant/:namespace/:name", delete(DropService))
+            .route("/networkpolicies/", post(PostNetworkPolicy))
+            .route(
+                "/networkpolicies/:tenant/:namespace/:name",
+                delete(DropNetworkPolicy),
+            )
             .route("/funcpods/:tenant/:namespace/:name", get(GetFuncPods))
             .route("/funccall/", post(PostFuncCall))
             .with_state(client);
@@ -177,6 +185,70 @@ async fn GetFuncPackage(
     }
 }
 
+// create the service when the revision is 0, otherwise update it
+async fn PostService(Json(service): Json<ServiceDef>) -> impl IntoResponse {
+    let res = if service.revision == 0 {
+        NAMESPACE_STORE.get().unwrap().CreateService(&service).await
+    } else {
+        NAMESPACE_STORE.get().unwrap().UpdateService(&service).await
+    };
+
+    match res {
+        Err(e) => (StatusCode::BAD_REQUEST, Json(format!("{:?}", e))),
+        Ok(()) => (StatusCode::OK, Json(format!("ok"))),
+    }
+}
+
+async fn DropService(
+    Path((tenant, namespace, name)): Path<(String, String, String)>,
+) -> impl IntoResponse {
+    match NAMESPACE_STORE
+        .get()
+        .unwrap()
+        .DropService(&tenant, &namespace, &name)
+        .await
+    {
+        Err(e) => (StatusCode::BAD_REQUEST, Json(format!("{:?}", e))),
+        Ok(()) => (StatusCode::OK, Json(format!("ok"))),
+    }
+}
+
+// create the policy when the revision is 0, otherwise update it
+async fn PostNetworkPolicy(Json(policy): Json<NetworkPolicy>) -> impl IntoResponse {
+    let res = if policy.revision == 0 {
+        NAMESPACE_STORE
+            .get()
+            .unwrap()
+            .CreateNetworkPolicy(&policy)
+            .await
+    } else {
+        NAMESPACE_STORE
+            .get()
+            .unwrap()
+            .UpdateNetworkPolicy(&policy)
+            .await
+    };
+
+    match res {
+        Err(e) => (StatusCode::BAD_REQUEST, Json(format!("{:?}", e))),
+        Ok(()) => (StatusCode::OK, Json(format!("ok"))),
+    }
+}
+
+async fn DropNetworkPolicy(
+    Path((tenant, namespace, name)): Path<(String, String, String)>,
+) -> impl IntoResponse {
+    match NAMESPACE_STORE
+        .get()
+        .unwrap()
+        .DropNetworkPolicy(&tenant, &namespace, &name)
+        .await
+    {
+        Err(e) => (StatusCode::BAD_REQUEST, Json(format!("{:?}", e))),
+        Ok(()) => (StatusCode::OK, Json(format!("ok"))),
+    }
+}
+
 async fn GetFuncPackages(Path((tenant, namespace)): Path<(String, String)>) -> impl IntoResponse {
     match NAMESPACE_MGR
         .get()
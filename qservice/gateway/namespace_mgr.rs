@@ -23,7 +23,7 @@ use qshare::metastore::informer::Informer;
 use qshare::metastore::informer_factory::InformerFactory;
 use qshare::metastore::selection_predicate::ListOption;
 use qshare::metastore::store::ThreadSafeStore;
-use qshare::node::PodDef;
+use qshare::node::{NetworkPolicy, PodDef, ServiceDef};
 use serde::{Deserialize, Serialize};
 
 use qshare::common::*;
@@ -367,4 +367,40 @@ impl NamespaceStore {
         self.store.Delete(&key, revision).await?;
         return Ok(());
     }
+
+    pub async fn CreateNetworkPolicy(&self, policy: &NetworkPolicy) -> Result<()> {
+        let obj = policy.DataObject();
+        self.store.Create(&obj, 0).await?;
+        return Ok(());
+    }
+
+    pub async fn UpdateNetworkPolicy(&self, policy: &NetworkPolicy) -> Result<()> {
+        let obj = policy.DataObject();
+        self.store.Update(policy.revision, &obj).await?;
+        return Ok(());
+    }
+
+    pub async fn CreateService(&self, service: &ServiceDef) -> Result<()> {
+        let obj = service.DataObject();
+        self.store.Create(&obj, 0).await?;
+        return Ok(());
+    }
+
+    pub async fn UpdateService(&self, service: &ServiceDef) -> Result<()> {
+        let obj = service.DataObject();
+        self.store.Update(service.revision, &obj).await?;
+        return Ok(());
+    }
+
+    pub async fn DropService(&self, tenant: &str, namespace: &str, name: &str) -> Result<()> {
+        let key = format!("{}/{}/{}/{}", ServiceDef::KEY, tenant, namespace, name);
+        self.store.Delete(&key, 0).await?;
+        return Ok(());
+    }
+
+    pub async fn DropNetworkPolicy(&self, tenant: &str, namespace: &str, name: &str) -> Result<()> {
+        let key = format!("{}/{}/{}/{}", NetworkPolicy::KEY, tenant, namespace, name);
+        self.store.Delete(&key, 0).await?;
+        return Ok(());
+    }
 }
@@ -337,7 +337,7 @@ impl TsotClient {
         let mut req = GatewayConnectReq {
             reqId: reqId,
             podNamespace: [0; 64],
-            dstIp: IpAddress::New(&ipAddr).0,
+            dstIp: TsotAddr::FromIpv4(IpAddress::New(&ipAddr).0),
             dstPort: port,
             srcPort: 123,
         };
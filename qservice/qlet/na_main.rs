@@ -34,12 +34,15 @@ use std::sync::Arc;
 
 use crate::pod_mgr::cadvisor::client as CadvisorClient;
 use crate::pod_mgr::podMgr::PodMgrSvc;
+use crate::tsot::network_policy::NETWORK_POLICY_MGR;
+use crate::tsot::service_mgr::SERVICE_MGR;
 use crate::tsot::tsot_svc::TsotSvc;
 
 use pod_mgr::node_mgr::NodeMgr;
 use qshare::common::*;
 use qshare::metastore::informer_factory::InformerFactory;
 use qshare::metastore::selection_predicate::ListOption;
+use qshare::node::{NetworkPolicy, PodDef, ServiceDef};
 use qshare::qlet_config::QletConfig;
 use tokio::sync::Notify;
 
@@ -62,7 +65,12 @@ lazy_static::lazy_static! {
                 tsotCniPort: 1234,
                 tsotSvcPort: 1235,
                 stateSvcPort: 1236,
+                streamingPort: 1237,
                 cidr: "10.1.1.0/8".to_string(),
+                cidr6: "fd00:10::1:0/16".to_string(),
+                tlsCaDir: "".to_string(),
+                tlsNamespaces: Vec::new(),
+                containerdTaskDir: qshare::qlet_config::DefaultContainerdTaskDir(),
                 stateSvcAddr: vec![
                     "127.0.0.1:8890".to_string()
                 ],
@@ -103,6 +111,31 @@ async fn main() -> Result<()> {
             // todo: handle statesvc crash
             informer.Process(notify).await.ok();
         });
+
+        // the network policies, the services and the pods used by them
+        for objType in [NetworkPolicy::KEY, ServiceDef::KEY, PodDef::KEY] {
+            factory
+                .AddInformer(objType, &ListOption::default())
+                .await
+                .unwrap();
+            let informer = factory.GetInformer(objType).await.unwrap();
+            if objType != ServiceDef::KEY {
+                let _id = informer
+                    .AddEventHandler(Arc::new(NETWORK_POLICY_MGR.clone()))
+                    .await
+                    .unwrap();
+            }
+            if objType != NetworkPolicy::KEY {
+                let _id = informer
+                    .AddEventHandler(Arc::new(SERVICE_MGR.clone()))
+                    .await
+                    .unwrap();
+            }
+            let notify = Arc::new(Notify::new());
+            tokio::spawn(async move {
+                informer.Process(notify).await.ok();
+            });
+        }
     }
 
     let podMgrFuture = PodMgrSvc();
@@ -21,12 +21,12 @@ use qshare::common::*;
 
 #[derive(Debug)]
 pub struct CidrInner {
-    pub addr: u32,
-    pub mask: u32,
-    pub minAddr: u32,
-    pub maxAddr: u32,
-    pub nextAddr: u32,
-    pub allocated: BTreeSet<u32>,
+    pub addr: u128,
+    pub mask: u128,
+    pub minAddr: u128,
+    pub maxAddr: u128,
+    pub nextAddr: u128,
+    pub allocated: BTreeSet<u128>,
 }
 
 #[derive(Debug, Clone)]
@@ -43,7 +43,18 @@ impl Deref for Cidr {
 impl Cidr {
     // for cidr (10.2.0.0/16), the addr is 10.2.0.0, the maskbits is 16
     pub fn New(addr: u32, maskbits: usize) -> Self {
-        let mask: u32 = !((1 << maskbits) - 1);
+        assert!(maskbits < 32);
+        return Self::NewCidr(addr as u128, maskbits);
+    }
+
+    // the ipv6 cidr, the maskbits is the same as the ipv4 one
+    pub fn New6(addr: u128, maskbits: usize) -> Self {
+        assert!(maskbits < 128);
+        return Self::NewCidr(addr, maskbits);
+    }
+
+    fn NewCidr(addr: u128, maskbits: usize) -> Self {
+        let mask: u128 = !((1 << maskbits) - 1);
         assert!(addr & !mask == 0);
 
         let minAddr = addr + 1; // we don't use the first addr
@@ -62,8 +73,18 @@ impl Cidr {
     }
 
     pub fn Allocate(&self) -> Result<IpAddress> {
+        let addr = self.AllocateAddr()?;
+        return Ok(IpAddress(addr as u32));
+    }
+
+    pub fn Allocate6(&self) -> Result<Ipv6Address> {
+        let addr = self.AllocateAddr()?;
+        return Ok(Ipv6Address(addr.to_be_bytes()));
+    }
+
+    fn AllocateAddr(&self) -> Result<u128> {
         let mut inner = self.lock().unwrap();
-        if inner.allocated.len() == (inner.maxAddr - inner.minAddr + 1) as usize {
+        if inner.allocated.len() as u128 == inner.maxAddr - inner.minAddr + 1 {
             return Err(Error::CommonError(
                 "Cidr: the address are used up".to_owned(),
             ));
@@ -78,7 +99,7 @@ impl Cidr {
                     inner.nextAddr = current + 1;
                 }
 
-                return Ok(IpAddress(current));
+                return Ok(current);
             }
         }
 
@@ -87,7 +108,7 @@ impl Cidr {
                 inner.allocated.insert(current);
                 inner.nextAddr = current + 1;
 
-                return Ok(IpAddress(current));
+                return Ok(current);
             }
         }
 
@@ -97,8 +118,16 @@ impl Cidr {
     }
 
     pub fn Free(&self, addr: IpAddress) -> Result<()> {
+        return self.FreeAddr(addr.0 as u128);
+    }
+
+    pub fn Free6(&self, addr: Ipv6Address) -> Result<()> {
+        return self.FreeAddr(u128::from_be_bytes(addr.0));
+    }
+
+    fn FreeAddr(&self, addr: u128) -> Result<()> {
         let mut inner = self.lock().unwrap();
-        let exist = inner.allocated.remove(&addr.0);
+        let exist = inner.allocated.remove(&addr);
 
         if !exist {
             return Err(Error::CommonError(format!(
@@ -110,3 +139,37 @@ impl Cidr {
         return Ok(());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn TestCidrAllocate6() {
+        // fd00:10::1:0 with 2 host bits, only fd00:10::1:1 and fd00:10::1:2 are usable
+        let addr: u128 = (0xfd00_0010 << 96) | 0x1_0000;
+        let cidr = Cidr::New6(addr, 2);
+
+        let first = cidr.Allocate6().unwrap();
+        assert_eq!(
+            first.AsBytes(),
+            [0xfd, 0, 0, 0x10, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 1]
+        );
+        let second = cidr.Allocate6().unwrap();
+        assert_eq!(u128::from_be_bytes(second.AsBytes()), addr + 2);
+        assert!(cidr.Allocate6().is_err());
+
+        cidr.Free6(first).unwrap();
+        assert!(cidr.Free6(first).is_err());
+        assert_eq!(cidr.Allocate6().unwrap(), first);
+    }
+
+    #[test]
+    fn TestCidrAllocate() {
+        let cidr = Cidr::New(0x0a010100, 8);
+        assert_eq!(cidr.Allocate().unwrap().0, 0x0a010101);
+        assert_eq!(cidr.Allocate().unwrap().0, 0x0a010102);
+        cidr.Free(IpAddress(0x0a010101)).unwrap();
+        assert!(cidr.Free(IpAddress(0x0a010101)).is_err());
+    }
+}
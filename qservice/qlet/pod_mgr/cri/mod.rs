@@ -13,3 +13,6 @@
 // limitations under the License.
 
 pub mod client;
+pub mod server;
+pub mod streaming;
+pub mod websocket;
@@ -0,0 +1,332 @@
+// Copyright (c) 2021 Quark Container Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::result::Result as SResult;
+
+use qshare::crictl::*;
+
+use super::streaming::StreamReq;
+use super::streaming::STREAMING_SVC;
+
+pub const QLET_RUNTIME_NAME: &str = "qlet";
+pub const CRI_API_VERSION: &str = "v1";
+
+pub fn Unimplemented(rpc: &str) -> tonic::Status {
+    return tonic::Status::unimplemented(format!(
+        "the qlet cri only serves the streaming requests, {} is served by containerd",
+        rpc
+    ));
+}
+
+pub fn ValidateExec(req: &ExecRequest) -> SResult<(), tonic::Status> {
+    if !req.stdin && !req.stdout && !req.stderr {
+        return Err(tonic::Status::invalid_argument(
+            "one of stdin, stdout, or stderr must be set",
+        ));
+    }
+
+    if req.tty && req.stderr {
+        return Err(tonic::Status::invalid_argument(
+            "tty and stderr cannot both be true",
+        ));
+    }
+
+    return Ok(());
+}
+
+// the container stdio is owned by the containerd shim, the attach only follows the container
+// output from its log. The stdin and tty attach need the real stdio, they are not served
+pub fn ValidateAttach(req: &AttachRequest) -> SResult<(), tonic::Status> {
+    if !req.stdin && !req.stdout && !req.stderr {
+        return Err(tonic::Status::invalid_argument(
+            "one of stdin, stdout, or stderr must be set",
+        ));
+    }
+
+    if req.stdin || req.tty {
+        return Err(tonic::Status::unimplemented(
+            "the quark container attach only follows the container output, use exec for the stdin or tty",
+        ));
+    }
+
+    return Ok(());
+}
+
+// the cri streaming service of the qlet. The exec, attach and port forward requests are
+// served by the qlet streaming service, which gets into the quark sandbox through the ucall
+// exec path and the tsot sockets. The pods are managed by containerd, so the other runtime
+// requests are not served
+#[derive(Debug, Default)]
+pub struct QletCriSvc {}
+
+impl QletCriSvc {
+    pub fn StreamUrl(&self, req: StreamReq) -> SResult<String, tonic::Status> {
+        match STREAMING_SVC.Url(req) {
+            Ok(url) => return Ok(url),
+            Err(e) => return Err(tonic::Status::failed_precondition(format!("fail: {:?}", e))),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl runtime_service_server::RuntimeService for QletCriSvc {
+    async fn version(
+        &self,
+        _request: tonic::Request<VersionRequest>,
+    ) -> SResult<tonic::Response<VersionResponse>, tonic::Status> {
+        return Ok(tonic::Response::new(VersionResponse {
+            version: CRI_API_VERSION.to_owned(),
+            runtime_name: QLET_RUNTIME_NAME.to_owned(),
+            runtime_version: env!("CARGO_PKG_VERSION").to_owned(),
+            runtime_api_version: CRI_API_VERSION.to_owned(),
+        }));
+    }
+
+    async fn run_pod_sandbox(
+        &self,
+        _request: tonic::Request<RunPodSandboxRequest>,
+    ) -> SResult<tonic::Response<RunPodSandboxResponse>, tonic::Status> {
+        return Err(Unimplemented("run_pod_sandbox"));
+    }
+
+    async fn stop_pod_sandbox(
+        &self,
+        _request: tonic::Request<StopPodSandboxRequest>,
+    ) -> SResult<tonic::Response<StopPodSandboxResponse>, tonic::Status> {
+        return Err(Unimplemented("stop_pod_sandbox"));
+    }
+
+    async fn remove_pod_sandbox(
+        &self,
+        _request: tonic::Request<RemovePodSandboxRequest>,
+    ) -> SResult<tonic::Response<RemovePodSandboxResponse>, tonic::Status> {
+        return Err(Unimplemented("remove_pod_sandbox"));
+    }
+
+    async fn pod_sandbox_status(
+        &self,
+        _request: tonic::Request<PodSandboxStatusRequest>,
+    ) -> SResult<tonic::Response<PodSandboxStatusResponse>, tonic::Status> {
+        return Err(Unimplemented("pod_sandbox_status"));
+    }
+
+    async fn list_pod_sandbox(
+        &self,
+        _request: tonic::Request<ListPodSandboxRequest>,
+    ) -> SResult<tonic::Response<ListPodSandboxResponse>, tonic::Status> {
+        return Err(Unimplemented("list_pod_sandbox"));
+    }
+
+    async fn create_container(
+        &self,
+        _request: tonic::Request<CreateContainerRequest>,
+    ) -> SResult<tonic::Response<CreateContainerResponse>, tonic::Status> {
+        return Err(Unimplemented("create_container"));
+    }
+
+    async fn start_container(
+        &self,
+        _request: tonic::Request<StartContainerRequest>,
+    ) -> SResult<tonic::Response<StartContainerResponse>, tonic::Status> {
+        return Err(Unimplemented("start_container"));
+    }
+
+    async fn stop_container(
+        &self,
+        _request: tonic::Request<StopContainerRequest>,
+    ) -> SResult<tonic::Response<StopContainerResponse>, tonic::Status> {
+        return Err(Unimplemented("stop_container"));
+    }
+
+    async fn remove_container(
+        &self,
+        _request: tonic::Request<RemoveContainerRequest>,
+    ) -> SResult<tonic::Response<RemoveContainerResponse>, tonic::Status> {
+        return Err(Unimplemented("remove_container"));
+    }
+
+    async fn list_containers(
+        &self,
+        _request: tonic::Request<ListContainersRequest>,
+    ) -> SResult<tonic::Response<ListContainersResponse>, tonic::Status> {
+        return Err(Unimplemented("list_containers"));
+    }
+
+    async fn container_status(
+        &self,
+        _request: tonic::Request<ContainerStatusRequest>,
+    ) -> SResult<tonic::Response<ContainerStatusResponse>, tonic::Status> {
+        return Err(Unimplemented("container_status"));
+    }
+
+    async fn update_container_resources(
+        &self,
+        _request: tonic::Request<UpdateContainerResourcesRequest>,
+    ) -> SResult<tonic::Response<UpdateContainerResourcesResponse>, tonic::Status> {
+        return Err(Unimplemented("update_container_resources"));
+    }
+
+    async fn reopen_container_log(
+        &self,
+        _request: tonic::Request<ReopenContainerLogRequest>,
+    ) -> SResult<tonic::Response<ReopenContainerLogResponse>, tonic::Status> {
+        return Err(Unimplemented("reopen_container_log"));
+    }
+
+    async fn exec_sync(
+        &self,
+        _request: tonic::Request<ExecSyncRequest>,
+    ) -> SResult<tonic::Response<ExecSyncResponse>, tonic::Status> {
+        return Err(Unimplemented("exec_sync"));
+    }
+
+    async fn exec(
+        &self,
+        request: tonic::Request<ExecRequest>,
+    ) -> SResult<tonic::Response<ExecResponse>, tonic::Status> {
+        let req = request.into_inner();
+        ValidateExec(&req)?;
+        let url = self.StreamUrl(StreamReq::Exec(req))?;
+        return Ok(tonic::Response::new(ExecResponse { url: url }));
+    }
+
+    async fn attach(
+        &self,
+        request: tonic::Request<AttachRequest>,
+    ) -> SResult<tonic::Response<AttachResponse>, tonic::Status> {
+        let req = request.into_inner();
+        ValidateAttach(&req)?;
+        let url = self.StreamUrl(StreamReq::Attach(req))?;
+        return Ok(tonic::Response::new(AttachResponse { url: url }));
+    }
+
+    async fn port_forward(
+        &self,
+        request: tonic::Request<PortForwardRequest>,
+    ) -> SResult<tonic::Response<PortForwardResponse>, tonic::Status> {
+        let req = request.into_inner();
+        let url = self.StreamUrl(StreamReq::PortForward(req))?;
+        return Ok(tonic::Response::new(PortForwardResponse { url: url }));
+    }
+
+    async fn container_stats(
+        &self,
+        _request: tonic::Request<ContainerStatsRequest>,
+    ) -> SResult<tonic::Response<ContainerStatsResponse>, tonic::Status> {
+        return Err(Unimplemented("container_stats"));
+    }
+
+    async fn list_container_stats(
+        &self,
+        _request: tonic::Request<ListContainerStatsRequest>,
+    ) -> SResult<tonic::Response<ListContainerStatsResponse>, tonic::Status> {
+        return Err(Unimplemented("list_container_stats"));
+    }
+
+    async fn pod_sandbox_stats(
+        &self,
+        _request: tonic::Request<PodSandboxStatsRequest>,
+    ) -> SResult<tonic::Response<PodSandboxStatsResponse>, tonic::Status> {
+        return Err(Unimplemented("pod_sandbox_stats"));
+    }
+
+    async fn list_pod_sandbox_stats(
+        &self,
+        _request: tonic::Request<ListPodSandboxStatsRequest>,
+    ) -> SResult<tonic::Response<ListPodSandboxStatsResponse>, tonic::Status> {
+        return Err(Unimplemented("list_pod_sandbox_stats"));
+    }
+
+    async fn update_runtime_config(
+        &self,
+        _request: tonic::Request<UpdateRuntimeConfigRequest>,
+    ) -> SResult<tonic::Response<UpdateRuntimeConfigResponse>, tonic::Status> {
+        return Err(Unimplemented("update_runtime_config"));
+    }
+
+    async fn status(
+        &self,
+        _request: tonic::Request<StatusRequest>,
+    ) -> SResult<tonic::Response<StatusResponse>, tonic::Status> {
+        return Err(Unimplemented("status"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn TestValidateExec() {
+        let req = ExecRequest {
+            stdout: true,
+            ..Default::default()
+        };
+        assert!(ValidateExec(&req).is_ok());
+
+        let none = ExecRequest::default();
+        assert_eq!(
+            ValidateExec(&none).unwrap_err().code(),
+            tonic::Code::InvalidArgument
+        );
+
+        let tty = ExecRequest {
+            tty: true,
+            stdout: true,
+            stderr: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            ValidateExec(&tty).unwrap_err().code(),
+            tonic::Code::InvalidArgument
+        );
+    }
+
+    #[test]
+    fn TestValidateAttach() {
+        let req = AttachRequest {
+            stdout: true,
+            stderr: true,
+            ..Default::default()
+        };
+        assert!(ValidateAttach(&req).is_ok());
+
+        let none = AttachRequest::default();
+        assert_eq!(
+            ValidateAttach(&none).unwrap_err().code(),
+            tonic::Code::InvalidArgument
+        );
+
+        // the stdin and tty attach are not answered with the log
+        let stdin = AttachRequest {
+            stdin: true,
+            stdout: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            ValidateAttach(&stdin).unwrap_err().code(),
+            tonic::Code::Unimplemented
+        );
+
+        let tty = AttachRequest {
+            tty: true,
+            stdout: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            ValidateAttach(&tty).unwrap_err().code(),
+            tonic::Code::Unimplemented
+        );
+    }
+}
@@ -0,0 +1,894 @@
+// Copyright (c) 2021 Quark Container Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io::SeekFrom;
+use std::os::fd::AsRawFd;
+use std::os::fd::FromRawFd;
+use std::process::Stdio;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+use tokio::fs::File;
+use tokio::io::AsyncBufReadExt;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncSeekExt;
+use tokio::io::AsyncWrite;
+use tokio::io::AsyncWriteExt;
+use tokio::io::BufReader;
+use tokio::net::unix::OwnedWriteHalf;
+use tokio::net::TcpListener;
+use tokio::net::UnixStream;
+use tokio::process::Command;
+use tokio::sync::mpsc;
+
+use qshare::common::*;
+use qshare::crictl::AttachRequest;
+use qshare::crictl::ContainerFilter;
+use qshare::crictl::ContainerState;
+use qshare::crictl::ExecRequest;
+use qshare::crictl::PortForwardRequest;
+use qshare::tsot_msg::TsotAddr;
+
+use crate::pod_mgr::NAMESPACE_MGR;
+use crate::pod_mgr::RUNTIME_MGR;
+use crate::tsot::pod_broker_mgr::POD_BRORKER_MGRS;
+use crate::tsot::tls::TsotTls;
+use crate::tsot::tls::TSOT_TLS;
+use crate::QLET_CONFIG;
+
+use super::websocket;
+use super::websocket::RequestHead;
+use super::websocket::WsMessage;
+use super::websocket::WsReader;
+use super::websocket::WS_OP_BINARY;
+use super::websocket::WS_OP_PONG;
+
+// the tokens are used by one connection, the forwarded ports of a port forward request
+// share its connection
+pub const STREAM_TOKEN_TTL: Duration = Duration::from_secs(60);
+
+pub const QUARK_BIN: &str = "/usr/local/bin/quark";
+pub const CONTAINERD_NAMESPACE: &str = "k8s.io";
+
+// the kubernetes websocket protocols of the exec and attach streams, the first byte of
+// each binary message is the channel. v5 adds the close channel
+pub const STREAM_PROTOCOLS: &[&str] = &["v5.channel.k8s.io", "v4.channel.k8s.io"];
+pub const STREAM_STDIN: u8 = 0;
+pub const STREAM_STDOUT: u8 = 1;
+pub const STREAM_STDERR: u8 = 2;
+pub const STREAM_ERROR: u8 = 3;
+pub const STREAM_RESIZE: u8 = 4;
+pub const STREAM_CLOSE: u8 = 255;
+
+// the kubernetes websocket port forward protocol, the port in the query has the data channel
+// 2 * i and the error channel 2 * i + 1. The first message of each channel is the little
+// endian port
+pub const PORT_FORWARD_PROTOCOLS: &[&str] = &["v4.channel.k8s.io"];
+
+#[derive(Debug, Clone)]
+pub enum StreamReq {
+    Exec(ExecRequest),
+    Attach(AttachRequest),
+    PortForward(PortForwardRequest),
+}
+
+impl StreamReq {
+    pub fn Kind(&self) -> &'static str {
+        match self {
+            Self::Exec(_) => return "exec",
+            Self::Attach(_) => return "attach",
+            Self::PortForward(_) => return "portforward",
+        }
+    }
+}
+
+// the payload of the resize channel
+#[derive(Debug, Deserialize)]
+pub struct TerminalSize {
+    pub Width: u16,
+    pub Height: u16,
+}
+
+lazy_static::lazy_static! {
+    pub static ref STREAMING_SVC: StreamingSvc = StreamingSvc::default();
+}
+
+#[derive(Debug, Default)]
+pub struct StreamingSvc {
+    // token to the request and its expire time
+    pub tokens: Mutex<HashMap<String, (StreamReq, Instant)>>,
+}
+
+impl StreamingSvc {
+    pub fn Url(&self, req: StreamReq) -> Result<String> {
+        if QLET_CONFIG.streamingPort == 0 {
+            return Err(Error::CommonError(
+                "the qlet streaming is disabled".to_owned(),
+            ));
+        }
+
+        let kind = req.Kind();
+        let token = self.NewToken(req);
+        let scheme = if TSOT_TLS.Enabled() { "https" } else { "http" };
+        return Ok(format!(
+            "{}://{}:{}/{}/{}",
+            scheme,
+            TsotTls::NodeIp(),
+            QLET_CONFIG.streamingPort,
+            kind,
+            token
+        ));
+    }
+
+    // the expired tokens are removed when a new token is issued
+    pub fn NewToken(&self, req: StreamReq) -> String {
+        let token = uuid::Uuid::new_v4().to_string();
+        let now = Instant::now();
+        let mut tokens = self.tokens.lock().unwrap();
+        tokens.retain(|_, (_, expire)| *expire > now);
+        tokens.insert(token.clone(), (req, now + STREAM_TOKEN_TTL));
+        return token;
+    }
+
+    pub fn Take(&self, kind: &str, token: &str) -> Result<StreamReq> {
+        let mut tokens = self.tokens.lock().unwrap();
+        match tokens.remove(token) {
+            Some((req, expire)) if expire > Instant::now() && req.Kind() == kind => return Ok(req),
+            _ => {
+                return Err(Error::NotExist(format!(
+                    "the {} stream token {} not exist",
+                    kind, token
+                )))
+            }
+        }
+    }
+
+    // the streams are served with the node certificate when the tsot tls is enabled
+    pub async fn Process(&self) -> Result<()> {
+        if QLET_CONFIG.streamingPort == 0 {
+            std::future::pending::<()>().await;
+        }
+
+        let addr = format!("{}:{}", TsotTls::NodeIp(), QLET_CONFIG.streamingPort);
+        let listener = TcpListener::bind(&addr).await?;
+        loop {
+            let stream = match listener.accept().await {
+                Err(_) => continue,
+                Ok((stream, _peerAddr)) => stream,
+            };
+
+            tokio::spawn(async move {
+                let res = if TSOT_TLS.Enabled() {
+                    match TSOT_TLS.AcceptStreaming(stream).await {
+                        Err(e) => Err(e),
+                        Ok(stream) => StreamConnection::Process(stream).await,
+                    }
+                } else {
+                    StreamConnection::Process(stream).await
+                };
+
+                match res {
+                    Err(e) => error!("StreamingSvc::Process stream fail with error {:?}", e),
+                    Ok(()) => (),
+                }
+            });
+        }
+    }
+}
+
+// the binary message of a channel
+pub fn ChannelMessage(channel: u8, data: &[u8]) -> (u8, Vec<u8>) {
+    let mut msg = Vec::with_capacity(data.len() + 1);
+    msg.push(channel);
+    msg.extend_from_slice(data);
+    return (WS_OP_BINARY, msg);
+}
+
+pub async fn PumpOutput<R: AsyncRead + Unpin>(
+    mut reader: R,
+    channel: u8,
+    tx: mpsc::Sender<(u8, Vec<u8>)>,
+) {
+    let mut buf = vec![0; 16 * 1024];
+    loop {
+        // the pty master returns EIO after the slave is closed
+        let cnt = match reader.read(&mut buf).await {
+            Ok(0) | Err(_) => return,
+            Ok(cnt) => cnt,
+        };
+
+        if tx.send(ChannelMessage(channel, &buf[..cnt])).await.is_err() {
+            return;
+        }
+    }
+}
+
+// the status on the error channel, in the shape of the kubernetes metav1.Status
+pub fn ExitStatus(code: Option<i32>) -> Vec<u8> {
+    let status = match code {
+        Some(0) => serde_json::json!({ "status": "Success" }),
+        Some(code) => serde_json::json!({
+            "status": "Failure",
+            "reason": "NonZeroExitCode",
+            "message": format!("command terminated with non-zero exit code {}", code),
+            "details": { "causes": [{ "reason": "ExitCode", "message": code.to_string() }] },
+        }),
+        None => serde_json::json!({
+            "status": "Failure",
+            "message": "command terminated by signal",
+        }),
+    };
+
+    return status.to_string().into_bytes();
+}
+
+pub fn ErrorStatus(message: &str) -> Vec<u8> {
+    let status = serde_json::json!({ "status": "Failure", "message": message });
+    return status.to_string().into_bytes();
+}
+
+pub struct StreamConnection {}
+
+impl StreamConnection {
+    // /<kind>/<token>
+    // return: (kind, token)
+    pub fn ParsePath(path: &str) -> Result<(String, String)> {
+        let parts: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+        if parts.len() != 2 {
+            return Err(Error::CommonError(format!(
+                "StreamConnection invalid request path {}",
+                path
+            )));
+        }
+
+        return Ok((parts[0].to_owned(), parts[1].to_owned()));
+    }
+
+    pub async fn Process<S: AsyncRead + AsyncWrite + Unpin + Send + 'static>(
+        mut stream: S,
+    ) -> Result<()> {
+        let head = RequestHead::Read(&mut stream).await?;
+        let res = match Self::ParsePath(&head.path) {
+            Err(e) => Err(e),
+            Ok((kind, token)) => STREAMING_SVC.Take(&kind, &token),
+        };
+
+        let req = match res {
+            Err(e) => {
+                websocket::Reject(&mut stream, "404 Not Found").await?;
+                return Err(e);
+            }
+            Ok(req) => req,
+        };
+
+        let supported = match &req {
+            StreamReq::PortForward(_) => PORT_FORWARD_PROTOCOLS,
+            _ => STREAM_PROTOCOLS,
+        };
+
+        let protocol = match head.Protocol(supported) {
+            None => {
+                websocket::Reject(&mut stream, "400 Bad Request").await?;
+                return Err(Error::CommonError(format!(
+                    "StreamConnection unsupported websocket protocols {:?}",
+                    head.Header("sec-websocket-protocol")
+                )));
+            }
+            Some(protocol) => protocol,
+        };
+
+        let mut ports = Vec::new();
+        for port in head.QueryValues("port") {
+            match port.parse::<u16>() {
+                Ok(port) => ports.push(port),
+                Err(e) => {
+                    websocket::Reject(&mut stream, "400 Bad Request").await?;
+                    return Err(e.into());
+                }
+            }
+        }
+
+        websocket::Accept(&mut stream, &head, &protocol).await?;
+
+        match req {
+            StreamReq::Exec(req) => return Self::Exec(stream, req).await,
+            StreamReq::Attach(req) => return Self::Attach(stream, req).await,
+            StreamReq::PortForward(req) => return Self::PortForward(stream, req, ports).await,
+        }
+    }
+
+    // the quark container state directory of the containerd shim, "quark exec" of the
+    // container gets into the sandbox through the ucall exec path
+    pub async fn ContainerRoot(containerId: &str) -> Result<String> {
+        let filter = ContainerFilter {
+            id: containerId.to_owned(),
+            ..Default::default()
+        };
+
+        let containers = RUNTIME_MGR
+            .get()
+            .unwrap()
+            .runtimeService
+            .ListContainers(Some(filter))
+            .await?;
+        if containers.len() == 0 {
+            return Err(Error::NotExist(format!(
+                "the container {} not exist",
+                containerId
+            )));
+        }
+
+        return Ok(format!(
+            "{}/{}/{}",
+            &QLET_CONFIG.containerdTaskDir, &containers[0].pod_sandbox_id, CONTAINERD_NAMESPACE
+        ));
+    }
+
+    // the exit status or the failure is sent on the error channel before the stream is closed
+    pub async fn Exec<S: AsyncRead + AsyncWrite + Unpin + Send + 'static>(
+        stream: S,
+        req: ExecRequest,
+    ) -> Result<()> {
+        let (rd, wr) = tokio::io::split(stream);
+        let (tx, rx) = mpsc::channel::<(u8, Vec<u8>)>(30);
+        let writer = tokio::spawn(websocket::WriteMessages(wr, rx));
+
+        let res = Self::RunExec(WsReader::New(rd), req, tx.clone()).await;
+        let status = match &res {
+            Ok(code) => ExitStatus(*code),
+            Err(e) => ErrorStatus(&format!("{:?}", e)),
+        };
+
+        tx.send(ChannelMessage(STREAM_ERROR, &status)).await.ok();
+        drop(tx);
+        writer.await.ok();
+        return res.map(|_| ());
+    }
+
+    // return the exit code of the exec, none when it is killed by a signal
+    pub async fn RunExec<R: AsyncRead + Unpin + Send + 'static>(
+        reader: WsReader<R>,
+        req: ExecRequest,
+        tx: mpsc::Sender<(u8, Vec<u8>)>,
+    ) -> Result<Option<i32>> {
+        let root = Self::ContainerRoot(&req.container_id).await?;
+
+        let mut cmd = Command::new(QUARK_BIN);
+        cmd.arg("--root").arg(&root).arg("exec");
+        if req.tty {
+            cmd.arg("--tty");
+        }
+        cmd.arg(&req.container_id);
+        cmd.args(&req.cmd);
+        cmd.kill_on_drop(true);
+
+        let mut pty = None;
+        if req.tty {
+            let res = nix::pty::openpty(None, None)
+                .map_err(|e| Error::CommonError(format!("openpty fail with error {:?}", e)))?;
+            let master = unsafe { std::fs::File::from_raw_fd(res.master) };
+            let slave = unsafe { std::fs::File::from_raw_fd(res.slave) };
+            cmd.stdin(slave.try_clone()?);
+            cmd.stdout(slave.try_clone()?);
+            cmd.stderr(slave);
+            pty = Some(master);
+        } else {
+            cmd.stdin(if req.stdin {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            });
+            cmd.stdout(if req.stdout {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            });
+            cmd.stderr(if req.stderr {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            });
+        }
+
+        let mut child = cmd.spawn()?;
+
+        // close the pty slave of the qlet, so that the master gets EIO after the exec exits
+        drop(cmd);
+
+        let mut pumps = Vec::new();
+        let mut stdin: Option<Box<dyn AsyncWrite + Unpin + Send>> = None;
+        let mut resizer = None;
+        match pty {
+            Some(master) => {
+                if req.stdin {
+                    stdin = Some(Box::new(File::from_std(master.try_clone()?)));
+                }
+                resizer = Some(master.try_clone()?);
+                pumps.push(tokio::spawn(PumpOutput(
+                    File::from_std(master),
+                    STREAM_STDOUT,
+                    tx.clone(),
+                )));
+            }
+            None => {
+                if let Some(out) = child.stdout.take() {
+                    pumps.push(tokio::spawn(PumpOutput(out, STREAM_STDOUT, tx.clone())));
+                }
+                if let Some(err) = child.stderr.take() {
+                    pumps.push(tokio::spawn(PumpOutput(err, STREAM_STDERR, tx.clone())));
+                }
+                if let Some(input) = child.stdin.take() {
+                    stdin = Some(Box::new(input));
+                }
+            }
+        }
+
+        let input = tokio::spawn(Self::ProcessInput(reader, stdin, resizer, tx));
+        let status = child.wait().await?;
+        for pump in pumps {
+            pump.await.ok();
+        }
+
+        input.abort();
+        return Ok(status.code());
+    }
+
+    pub async fn ProcessInput<R: AsyncRead + Unpin>(
+        mut reader: WsReader<R>,
+        mut stdin: Option<Box<dyn AsyncWrite + Unpin + Send>>,
+        pty: Option<std::fs::File>,
+        tx: mpsc::Sender<(u8, Vec<u8>)>,
+    ) -> Result<()> {
+        loop {
+            let msg = match reader.Read().await? {
+                WsMessage::Close => return Ok(()),
+                WsMessage::Ping(data) => {
+                    tx.send((WS_OP_PONG, data)).await.ok();
+                    continue;
+                }
+                WsMessage::Pong => continue,
+                WsMessage::Data(msg) => msg,
+            };
+
+            if msg.len() == 0 {
+                continue;
+            }
+
+            let data = &msg[1..];
+            match msg[0] {
+                STREAM_STDIN => {
+                    if let Some(writer) = stdin.as_mut() {
+                        writer.write_all(data).await?;
+                        writer.flush().await?;
+                    }
+                }
+                STREAM_RESIZE => {
+                    if let Some(pty) = &pty {
+                        let size: TerminalSize = serde_json::from_slice(data)?;
+                        Self::Resize(pty, &size)?;
+                    }
+                }
+                STREAM_CLOSE => {
+                    // the client closes the stdin of the exec
+                    if data.first() == Some(&STREAM_STDIN) {
+                        stdin = None;
+                    }
+                }
+                channel => {
+                    error!("StreamConnection::ProcessInput unknown channel {}", channel);
+                }
+            }
+        }
+    }
+
+    pub fn Resize(pty: &std::fs::File, size: &TerminalSize) -> Result<()> {
+        let ws = libc::winsize {
+            ws_row: size.Height,
+            ws_col: size.Width,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        };
+
+        let ret = unsafe {
+            libc::ioctl(
+                pty.as_raw_fd(),
+                libc::TIOCSWINSZ,
+                &ws as *const libc::winsize,
+            )
+        };
+        if ret < 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+
+        return Ok(());
+    }
+
+    pub async fn ContainerRunning(containerId: &str) -> bool {
+        let resp = RUNTIME_MGR
+            .get()
+            .unwrap()
+            .runtimeService
+            .ContainerStatus(containerId, false)
+            .await;
+        match resp {
+            Ok(resp) => match resp.status {
+                Some(status) => return status.state == ContainerState::ContainerRunning as i32,
+                None => return false,
+            },
+            Err(_) => return false,
+        }
+    }
+
+    // the cri log line: <timestamp> <stdout|stderr> <P|F> <content>, P is a partial line
+    pub fn ParseLogLine(line: &str, tty: bool) -> Option<(u8, String)> {
+        let parts: Vec<&str> = line.splitn(4, ' ').collect();
+        if parts.len() != 4 {
+            return None;
+        }
+
+        let channel = if parts[1] == "stderr" && !tty {
+            STREAM_STDERR
+        } else {
+            STREAM_STDOUT
+        };
+
+        let mut content = parts[3].trim_end_matches('\n').to_owned();
+        if parts[2] == "F" {
+            content.push('\n');
+        }
+
+        return Some((channel, content));
+    }
+
+    // the container stdio is owned by the containerd shim, so the attach follows the container
+    // log from its end. The cri attach rejects the stdin and tty requests
+    pub async fn Attach<S: AsyncRead + AsyncWrite + Unpin + Send + 'static>(
+        stream: S,
+        req: AttachRequest,
+    ) -> Result<()> {
+        let (rd, wr) = tokio::io::split(stream);
+        let (tx, rx) = mpsc::channel::<(u8, Vec<u8>)>(30);
+        let writer = tokio::spawn(websocket::WriteMessages(wr, rx));
+
+        let res = Self::FollowLog(WsReader::New(rd), &req, tx.clone()).await;
+        if let Err(e) = &res {
+            let status = ErrorStatus(&format!("{:?}", e));
+            tx.send(ChannelMessage(STREAM_ERROR, &status)).await.ok();
+        }
+
+        drop(tx);
+        writer.await.ok();
+        return res;
+    }
+
+    pub async fn FollowLog<R: AsyncRead + Unpin + Send + 'static>(
+        mut reader: WsReader<R>,
+        req: &AttachRequest,
+        tx: mpsc::Sender<(u8, Vec<u8>)>,
+    ) -> Result<()> {
+        let status = RUNTIME_MGR
+            .get()
+            .unwrap()
+            .runtimeService
+            .ContainerStatus(&req.container_id, false)
+            .await?;
+        let logPath = match status.status {
+            None => {
+                return Err(Error::NotExist(format!(
+                    "the container {} not exist",
+                    &req.container_id
+                )))
+            }
+            Some(status) => status.log_path,
+        };
+
+        let mut log = BufReader::new(File::open(&logPath).await?);
+        log.seek(SeekFrom::End(0)).await?;
+
+        // the client closes the stream, the input other than the pings is ignored
+        let pong = tx.clone();
+        let mut closed = tokio::spawn(async move {
+            loop {
+                match reader.Read().await {
+                    Ok(WsMessage::Close) | Err(_) => return,
+                    Ok(WsMessage::Ping(data)) => {
+                        pong.send((WS_OP_PONG, data)).await.ok();
+                    }
+                    Ok(_) => (),
+                }
+            }
+        });
+
+        let mut line = String::new();
+        let mut idle = 0;
+        loop {
+            let cnt = tokio::select! {
+                res = log.read_line(&mut line) => res?,
+                _ = &mut closed => return Ok(()),
+            };
+
+            // wait for the rest of the line, check the container state every second
+            if cnt == 0 || !line.ends_with('\n') {
+                idle += 1;
+                if idle % 10 == 0 && !Self::ContainerRunning(&req.container_id).await {
+                    break;
+                }
+
+                tokio::select! {
+                    _ = tokio::time::sleep(Duration::from_millis(100)) => (),
+                    _ = &mut closed => return Ok(()),
+                }
+                continue;
+            }
+
+            idle = 0;
+            if let Some((channel, content)) = Self::ParseLogLine(&line, req.tty) {
+                let wanted = match channel {
+                    STREAM_STDERR => req.stderr,
+                    _ => req.stdout,
+                };
+
+                if wanted
+                    && tx
+                        .send(ChannelMessage(channel, content.as_bytes()))
+                        .await
+                        .is_err()
+                {
+                    break;
+                }
+            }
+            line.clear();
+        }
+
+        closed.abort();
+        return Ok(());
+    }
+
+    // return: (namespace, pod ip) of the pod sandbox
+    pub async fn PodAddr(podSandboxId: &str) -> Result<(String, u32)> {
+        let sandbox = match RUNTIME_MGR
+            .get()
+            .unwrap()
+            .GetPodSandbox(podSandboxId)
+            .await?
+        {
+            None => {
+                return Err(Error::NotExist(format!(
+                    "the pod sandbox {} not exist",
+                    podSandboxId
+                )))
+            }
+            Some(sandbox) => sandbox,
+        };
+
+        let uid = match &sandbox.metadata {
+            None => {
+                return Err(Error::NotExist(format!(
+                    "the pod sandbox {} has no metadata",
+                    podSandboxId
+                )))
+            }
+            Some(metadata) => metadata.uid.clone(),
+        };
+
+        let podSandbox = NAMESPACE_MGR.GetPodSandbox(&uid)?;
+        let inner = podSandbox.lock().unwrap();
+        return Ok((inner.namespace.clone(), inner.ip.0));
+    }
+
+    // a new connection to the port of the pod, handed over to the pod through the tsot pod
+    // broker as one side of a socket pair
+    pub fn ConnectPod(namespace: &str, podIp: u32, port: u16) -> Result<UnixStream> {
+        // the pod broker closes the socket after it is sent to the pod
+        let (socket, local) = TsotTls::SocketPair()?;
+        match POD_BRORKER_MGRS.HandlePeerConnect(
+            namespace,
+            TsotAddr::FromIpv4(podIp),
+            port,
+            TsotAddr::FromIpv4(u32::from(TsotTls::NodeIp())),
+            0,
+            socket,
+        ) {
+            Err(e) => {
+                nix::unistd::close(socket).ok();
+                return Err(e);
+            }
+            Ok(()) => return Ok(local),
+        }
+    }
+
+    // the ports of the query are forwarded through the connection, they have to be in the
+    // ports of the request when the request has any
+    pub async fn PortForward<S: AsyncRead + AsyncWrite + Unpin + Send + 'static>(
+        stream: S,
+        req: PortForwardRequest,
+        ports: Vec<u16>,
+    ) -> Result<()> {
+        let ports = if ports.len() > 0 {
+            ports
+        } else {
+            req.port.iter().map(|port| *port as u16).collect()
+        };
+
+        for port in &ports {
+            if *port == 0 || (req.port.len() > 0 && !req.port.contains(&(*port as i32))) {
+                return Err(Error::CommonError(format!(
+                    "the port {} is not forwarded by the request {:?}",
+                    port, &req
+                )));
+            }
+        }
+
+        let (namespace, podIp) = Self::PodAddr(&req.pod_sandbox_id).await?;
+
+        let (rd, wr) = tokio::io::split(stream);
+        let (tx, rx) = mpsc::channel::<(u8, Vec<u8>)>(30);
+        let writer = tokio::spawn(websocket::WriteMessages(wr, rx));
+
+        let mut conns = Vec::new();
+        let mut pumps = Vec::new();
+        for (i, port) in ports.iter().enumerate() {
+            let dataChannel = (2 * i) as u8;
+            let errorChannel = dataChannel + 1;
+            tx.send(ChannelMessage(dataChannel, &port.to_le_bytes()))
+                .await
+                .ok();
+            tx.send(ChannelMessage(errorChannel, &port.to_le_bytes()))
+                .await
+                .ok();
+
+            match Self::ConnectPod(&namespace, podIp, *port) {
+                Err(e) => {
+                    let msg = format!("fail to connect port {}: {:?}", port, e);
+                    tx.send(ChannelMessage(errorChannel, msg.as_bytes()))
+                        .await
+                        .ok();
+                    conns.push(None);
+                }
+                Ok(local) => {
+                    let (localRd, localWr) = local.into_split();
+                    pumps.push(tokio::spawn(PumpOutput(localRd, dataChannel, tx.clone())));
+                    conns.push(Some(localWr));
+                }
+            }
+        }
+
+        let res = Self::ForwardInput(WsReader::New(rd), &mut conns, &tx).await;
+        for pump in pumps {
+            pump.abort();
+        }
+
+        drop(tx);
+        writer.await.ok();
+        return res;
+    }
+
+    pub async fn ForwardInput<R: AsyncRead + Unpin>(
+        mut reader: WsReader<R>,
+        conns: &mut Vec<Option<OwnedWriteHalf>>,
+        tx: &mpsc::Sender<(u8, Vec<u8>)>,
+    ) -> Result<()> {
+        loop {
+            let msg = match reader.Read().await? {
+                WsMessage::Close => return Ok(()),
+                WsMessage::Ping(data) => {
+                    tx.send((WS_OP_PONG, data)).await.ok();
+                    continue;
+                }
+                WsMessage::Pong => continue,
+                WsMessage::Data(msg) => msg,
+            };
+
+            if msg.len() == 0 {
+                continue;
+            }
+
+            let channel = msg[0] as usize;
+            if channel % 2 != 0 || channel / 2 >= conns.len() {
+                error!("StreamConnection::ForwardInput unknown channel {}", channel);
+                continue;
+            }
+
+            if let Some(conn) = conns[channel / 2].as_mut() {
+                if let Err(e) = conn.write_all(&msg[1..]).await {
+                    let msg = format!("fail to write the forwarded port: {:?}", e);
+                    tx.send(ChannelMessage(channel as u8 + 1, msg.as_bytes()))
+                        .await
+                        .ok();
+                    conns[channel / 2] = None;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn Exec() -> StreamReq {
+        return StreamReq::Exec(ExecRequest {
+            container_id: "c1".to_owned(),
+            stdout: true,
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn TestStreamToken() {
+        let svc = StreamingSvc::default();
+        let token = svc.NewToken(Exec());
+
+        // the token is bound to its kind
+        assert!(svc.Take("attach", &token).is_err());
+
+        let token = svc.NewToken(Exec());
+        match svc.Take("exec", &token).unwrap() {
+            StreamReq::Exec(req) => assert_eq!(req.container_id, "c1"),
+            req => panic!("unexpected request {:?}", req),
+        }
+
+        // the token is used once
+        assert!(svc.Take("exec", &token).is_err());
+        assert!(svc.Take("exec", "unknown").is_err());
+    }
+
+    #[test]
+    fn TestStreamTokenExpire() {
+        let svc = StreamingSvc::default();
+        let token = svc.NewToken(Exec());
+        svc.tokens.lock().unwrap().get_mut(&token).unwrap().1 = Instant::now();
+        assert!(svc.Take("exec", &token).is_err());
+
+        // the expired token is removed with the next token
+        let expired = svc.NewToken(Exec());
+        svc.tokens.lock().unwrap().get_mut(&expired).unwrap().1 = Instant::now();
+        let attach = svc.NewToken(StreamReq::Attach(AttachRequest::default()));
+        let tokens = svc.tokens.lock().unwrap();
+        assert!(!tokens.contains_key(&expired));
+        assert!(tokens.contains_key(&attach));
+    }
+
+    #[test]
+    fn TestParsePath() {
+        let (kind, token) = StreamConnection::ParsePath("/exec/abc").unwrap();
+        assert_eq!(kind, "exec");
+        assert_eq!(token, "abc");
+        assert!(StreamConnection::ParsePath("/exec").is_err());
+        assert!(StreamConnection::ParsePath("/exec/abc/d").is_err());
+    }
+
+    #[test]
+    fn TestParseLogLine() {
+        let line = "2024-01-01T00:00:00Z stderr F hello\n";
+        assert_eq!(
+            StreamConnection::ParseLogLine(line, false),
+            Some((STREAM_STDERR, "hello\n".to_owned()))
+        );
+        assert_eq!(
+            StreamConnection::ParseLogLine(line, true),
+            Some((STREAM_STDOUT, "hello\n".to_owned()))
+        );
+
+        let partial = "2024-01-01T00:00:00Z stdout P hel";
+        assert_eq!(
+            StreamConnection::ParseLogLine(partial, false),
+            Some((STREAM_STDOUT, "hel".to_owned()))
+        );
+        assert_eq!(StreamConnection::ParseLogLine("bad", false), None);
+    }
+}
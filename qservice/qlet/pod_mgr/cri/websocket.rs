@@ -0,0 +1,339 @@
+// Copyright (c) 2021 Quark Container Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use openssl::base64;
+use openssl::sha::sha1;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWrite;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+
+use qshare::common::*;
+
+// the websocket server side of rfc 6455, enough for the kubernetes channel protocols
+
+pub const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+pub const WS_REQ_HEAD_MAX: usize = 8 * 1024;
+pub const WS_MESSAGE_MAX: usize = 1024 * 1024;
+
+pub const WS_FIN: u8 = 0x80;
+pub const WS_MASK: u8 = 0x80;
+pub const WS_OP_CONTINUATION: u8 = 0x0;
+pub const WS_OP_TEXT: u8 = 0x1;
+pub const WS_OP_BINARY: u8 = 0x2;
+pub const WS_OP_CLOSE: u8 = 0x8;
+pub const WS_OP_PING: u8 = 0x9;
+pub const WS_OP_PONG: u8 = 0xa;
+
+pub const WS_CLOSE_NORMAL: u16 = 1000;
+
+pub fn AcceptKey(key: &str) -> String {
+    let data = format!("{}{}", key.trim(), WEBSOCKET_GUID);
+    return base64::encode_block(&sha1(data.as_bytes()));
+}
+
+// the http request of the websocket handshake
+#[derive(Debug, Default)]
+pub struct RequestHead {
+    pub path: String,
+    pub query: Vec<(String, String)>,
+    // the header names are in lower case
+    pub headers: HashMap<String, String>,
+}
+
+impl RequestHead {
+    // the head is read byte by byte to leave the websocket frames to the handler
+    pub async fn Read<S: AsyncRead + Unpin>(stream: &mut S) -> Result<Self> {
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            if head.len() >= WS_REQ_HEAD_MAX {
+                return Err(Error::CommonError(
+                    "RequestHead request head is too long".to_owned(),
+                ));
+            }
+
+            head.push(stream.read_u8().await?);
+        }
+
+        return Self::Parse(&String::from_utf8(head)?);
+    }
+
+    // GET <path>[?<query>] HTTP/1.1
+    pub fn Parse(head: &str) -> Result<Self> {
+        let mut lines = head.split("\r\n");
+        let line = lines.next().unwrap_or("");
+        let parts: Vec<&str> = line.split(' ').collect();
+        if parts.len() != 3 || parts[0] != "GET" {
+            return Err(Error::CommonError(format!(
+                "RequestHead invalid request line {}",
+                line
+            )));
+        }
+
+        let (path, query) = match parts[1].split_once('?') {
+            None => (parts[1], ""),
+            Some((path, query)) => (path, query),
+        };
+
+        let mut req = Self {
+            path: path.to_owned(),
+            ..Default::default()
+        };
+
+        for kv in query.split('&') {
+            if kv.len() == 0 {
+                continue;
+            }
+
+            match kv.split_once('=') {
+                None => req.query.push((kv.to_owned(), String::new())),
+                Some((k, v)) => req.query.push((k.to_owned(), v.to_owned())),
+            }
+        }
+
+        for line in lines {
+            if let Some((name, value)) = line.split_once(':') {
+                req.headers
+                    .insert(name.trim().to_lowercase(), value.trim().to_owned());
+            }
+        }
+
+        return Ok(req);
+    }
+
+    pub fn Header(&self, name: &str) -> &str {
+        match self.headers.get(name) {
+            None => return "",
+            Some(value) => return value,
+        }
+    }
+
+    pub fn QueryValues(&self, name: &str) -> Vec<&str> {
+        return self
+            .query
+            .iter()
+            .filter(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+            .collect();
+    }
+
+    // the first subprotocol offered by the client which is supported
+    pub fn Protocol(&self, supported: &[&str]) -> Option<String> {
+        for protocol in self.Header("sec-websocket-protocol").split(',') {
+            let protocol = protocol.trim();
+            if supported.contains(&protocol) {
+                return Some(protocol.to_owned());
+            }
+        }
+
+        return None;
+    }
+}
+
+pub async fn Reject<S: AsyncWrite + Unpin>(stream: &mut S, status: &str) -> Result<()> {
+    let resp = format!(
+        "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        status
+    );
+    stream.write_all(resp.as_bytes()).await?;
+    return Ok(());
+}
+
+pub async fn Accept<S: AsyncWrite + Unpin>(
+    stream: &mut S,
+    head: &RequestHead,
+    protocol: &str,
+) -> Result<()> {
+    let key = head.Header("sec-websocket-key");
+    if !head.Header("upgrade").to_lowercase().contains("websocket") || key.len() == 0 {
+        Reject(stream, "400 Bad Request").await?;
+        return Err(Error::CommonError(format!(
+            "websocket::Accept {} is not a websocket request",
+            &head.path
+        )));
+    }
+
+    let resp = format!(
+        "HTTP/1.1 101 Switching Protocols\r\n\
+         Upgrade: websocket\r\n\
+         Connection: Upgrade\r\n\
+         Sec-WebSocket-Accept: {}\r\n\
+         Sec-WebSocket-Protocol: {}\r\n\r\n",
+        AcceptKey(key),
+        protocol
+    );
+    stream.write_all(resp.as_bytes()).await?;
+    return Ok(());
+}
+
+#[derive(Debug)]
+pub enum WsMessage {
+    // the payload of a text or binary message
+    Data(Vec<u8>),
+    Ping(Vec<u8>),
+    Pong,
+    Close,
+}
+
+pub struct WsReader<R: AsyncRead + Unpin> {
+    pub reader: R,
+    // the payload of the fragmented message
+    pub partial: Option<Vec<u8>>,
+}
+
+impl<R: AsyncRead + Unpin> WsReader<R> {
+    pub fn New(reader: R) -> Self {
+        return Self {
+            reader: reader,
+            partial: None,
+        };
+    }
+
+    // return Close when the client closes the connection
+    pub async fn Read(&mut self) -> Result<WsMessage> {
+        loop {
+            let b0 = match self.reader.read_u8().await {
+                Ok(b) => b,
+                Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                    return Ok(WsMessage::Close)
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            let b1 = self.reader.read_u8().await?;
+            if b1 & WS_MASK == 0 {
+                return Err(Error::CommonError(
+                    "WsReader the client frame is not masked".to_owned(),
+                ));
+            }
+
+            let len = match b1 & 0x7f {
+                126 => self.reader.read_u16().await? as u64,
+                127 => self.reader.read_u64().await?,
+                len => len as u64,
+            };
+
+            let partialLen = match &self.partial {
+                None => 0,
+                Some(partial) => partial.len() as u64,
+            };
+
+            if len + partialLen > WS_MESSAGE_MAX as u64 {
+                return Err(Error::CommonError(format!(
+                    "WsReader invalid message len {}",
+                    len + partialLen
+                )));
+            }
+
+            let mut mask = [0; 4];
+            self.reader.read_exact(&mut mask).await?;
+            let mut payload = vec![0; len as usize];
+            self.reader.read_exact(&mut payload).await?;
+            for i in 0..payload.len() {
+                payload[i] ^= mask[i % 4];
+            }
+
+            let fin = b0 & WS_FIN != 0;
+            match b0 & 0x0f {
+                WS_OP_CLOSE => return Ok(WsMessage::Close),
+                WS_OP_PING => return Ok(WsMessage::Ping(payload)),
+                WS_OP_PONG => return Ok(WsMessage::Pong),
+                WS_OP_TEXT | WS_OP_BINARY => {
+                    if self.partial.is_some() {
+                        return Err(Error::CommonError(
+                            "WsReader new message in a fragmented message".to_owned(),
+                        ));
+                    }
+
+                    if fin {
+                        return Ok(WsMessage::Data(payload));
+                    }
+
+                    self.partial = Some(payload);
+                }
+                WS_OP_CONTINUATION => {
+                    let mut partial = match self.partial.take() {
+                        None => {
+                            return Err(Error::CommonError(
+                                "WsReader continuation without a fragmented message".to_owned(),
+                            ))
+                        }
+                        Some(partial) => partial,
+                    };
+
+                    partial.extend_from_slice(&payload);
+                    if fin {
+                        return Ok(WsMessage::Data(partial));
+                    }
+
+                    self.partial = Some(partial);
+                }
+                opcode => {
+                    return Err(Error::CommonError(format!(
+                        "WsReader unknown opcode {}",
+                        opcode
+                    )))
+                }
+            }
+        }
+    }
+}
+
+// the server frames are not masked
+pub async fn WriteFrame<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    opcode: u8,
+    data: &[u8],
+) -> Result<()> {
+    let mut head = Vec::with_capacity(10);
+    head.push(WS_FIN | opcode);
+    if data.len() < 126 {
+        head.push(data.len() as u8);
+    } else if data.len() <= u16::MAX as usize {
+        head.push(126);
+        head.extend_from_slice(&(data.len() as u16).to_be_bytes());
+    } else {
+        head.push(127);
+        head.extend_from_slice(&(data.len() as u64).to_be_bytes());
+    }
+
+    writer.write_all(&head).await?;
+    writer.write_all(data).await?;
+    return Ok(());
+}
+
+// the messages of a connection are written by one task, it closes the connection after
+// all the senders are dropped
+pub async fn WriteMessages<W: AsyncWrite + Unpin>(
+    mut writer: W,
+    mut rx: mpsc::Receiver<(u8, Vec<u8>)>,
+) {
+    while let Some((opcode, data)) = rx.recv().await {
+        match WriteFrame(&mut writer, opcode, &data).await {
+            Ok(()) => (),
+            Err(e) => {
+                error!("WriteMessages fail with error {:?}", e);
+                return;
+            }
+        }
+    }
+
+    WriteFrame(&mut writer, WS_OP_CLOSE, &WS_CLOSE_NORMAL.to_be_bytes())
+        .await
+        .ok();
+    writer.shutdown().await.ok();
+}
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::ops::Deref;
 use std::str::FromStr;
@@ -19,6 +20,7 @@ use std::sync::Arc;
 use std::sync::Mutex;
 
 use qshare::common::*;
+use qshare::tsot_msg::TsotAddr;
 
 use crate::QLET_CONFIG;
 
@@ -30,6 +32,8 @@ pub struct NamespaceInner {
     pub namespace: String,
 
     pub cidr: Cidr,
+    // none for the ipv4 only pod network
+    pub cidr6: Option<Cidr>,
 }
 
 #[derive(Debug, Clone)]
@@ -44,25 +48,40 @@ impl Deref for Namespace {
 }
 
 impl Namespace {
-    pub fn New(namespace: &str, addr: u32, maskbits: usize) -> Self {
+    pub fn New(namespace: &str, addr: u32, maskbits: usize, cidr6: Option<(u128, usize)>) -> Self {
         let inner = NamespaceInner {
             namespace: namespace.to_owned(),
             cidr: Cidr::New(addr, maskbits),
+            cidr6: cidr6.map(|(addr, maskbits)| Cidr::New6(addr, maskbits)),
         };
 
         return Self(Arc::new(Mutex::new(inner)));
     }
 
-    pub fn NewPodSandboxAddr(&self) -> Result<IpAddress> {
+    // the ipv6 address is unspecified for the ipv4 only pod network
+    pub fn NewPodSandboxAddr(&self) -> Result<(IpAddress, Ipv6Address)> {
         let inner = self.lock().unwrap();
         let addr = inner.cidr.Allocate()?;
+        let ipv6 = match &inner.cidr6 {
+            None => Ipv6Address::default(),
+            Some(cidr6) => match cidr6.Allocate6() {
+                Ok(ipv6) => ipv6,
+                Err(e) => {
+                    inner.cidr.Free(addr)?;
+                    return Err(e);
+                }
+            },
+        };
 
-        return Ok(addr);
+        return Ok((addr, ipv6));
     }
 
-    pub fn RemovePodSandboxAddr(&self, addr: IpAddress) -> Result<()> {
+    pub fn RemovePodSandboxAddr(&self, addr: IpAddress, ipv6: Ipv6Address) -> Result<()> {
         let inner = self.lock().unwrap();
         inner.cidr.Free(addr)?;
+        if let Some(cidr6) = &inner.cidr6 {
+            cidr6.Free6(ipv6)?;
+        }
         return Ok(());
     }
 }
@@ -72,6 +91,8 @@ pub struct NamespaceMgrInner {
     pub namespaces: HashMap<String, Namespace>,
     pub addr: u32,
     pub maskbits: usize,
+    // the (addr, maskbits) of the ipv6 pod cidr, none for the ipv4 only pod network
+    pub cidr6: Option<(u128, usize)>,
 
     pub podSandboxes: HashMap<String, PodSandbox>,
 }
@@ -87,8 +108,9 @@ impl NamespaceMgrInner {
             Some(sandbox) => {
                 let ns = sandbox.lock().unwrap().namespace.clone();
                 let addr = sandbox.lock().unwrap().ip.clone();
+                let ipv6 = sandbox.lock().unwrap().ipv6.clone();
                 let namespace = self.GetNamespace(&ns)?;
-                namespace.RemovePodSandboxAddr(addr)?;
+                namespace.RemovePodSandboxAddr(addr, ipv6)?;
 
                 return Ok(());
             }
@@ -125,10 +147,18 @@ impl NamespaceMgr {
         let cidrStr = QLET_CONFIG.cidr.clone();
         let ipv4 = ipnetwork::Ipv4Network::from_str(&cidrStr).unwrap();
 
+        let cidr6 = if QLET_CONFIG.cidr6.len() == 0 {
+            None
+        } else {
+            let ipv6 = ipnetwork::Ipv6Network::from_str(&QLET_CONFIG.cidr6).unwrap();
+            Some((ipv6.ip().into(), ipv6.prefix() as usize))
+        };
+
         let inner = NamespaceMgrInner {
             namespaces: HashMap::new(),
             addr: ipv4.ip().into(),
             maskbits: ipv4.prefix() as _,
+            cidr6: cidr6,
             podSandboxes: HashMap::new(),
         };
 
@@ -139,7 +169,7 @@ impl NamespaceMgr {
         let mut inner = self.lock().unwrap();
         match inner.namespaces.get(namespace) {
             None => {
-                let ns = Namespace::New(namespace, inner.addr, inner.maskbits);
+                let ns = Namespace::New(namespace, inner.addr, inner.maskbits, inner.cidr6);
                 inner.namespaces.insert(namespace.to_owned(), ns.clone());
                 return ns;
             }
@@ -153,12 +183,18 @@ impl NamespaceMgr {
         return self.lock().unwrap().GetNamespace(namespace);
     }
 
-    pub fn NewPodSandbox(&self, namespace: &str, uid: &str, name: &str) -> Result<IpAddress> {
+    pub fn NewPodSandbox(
+        &self,
+        namespace: &str,
+        uid: &str,
+        name: &str,
+        labels: &BTreeMap<String, String>,
+    ) -> Result<(IpAddress, Ipv6Address)> {
         let ns = self.GetOrCreateNamespace(namespace);
-        let addr = ns.NewPodSandboxAddr()?;
+        let (addr, ipv6) = ns.NewPodSandboxAddr()?;
 
         let mut inner = self.lock().unwrap();
-        let podsandbox = PodSandbox::New(uid, namespace, name, addr);
+        let podsandbox = PodSandbox::New(uid, namespace, name, addr, ipv6, labels);
         match inner.podSandboxes.insert(uid.to_owned(), podsandbox) {
             None => (),
             Some(_) => {
@@ -168,7 +204,7 @@ impl NamespaceMgr {
             }
         }
 
-        return Ok(addr);
+        return Ok((addr, ipv6));
     }
 
     pub fn RemovePodSandbox(&self, uid: &str) -> Result<()> {
@@ -188,6 +224,19 @@ impl NamespaceMgr {
         }
     }
 
+    // the local pod sandbox with the ipv4 or ipv6 address in the namespace
+    pub fn GetPodSandboxByAddr(&self, namespace: &str, addr: TsotAddr) -> Option<PodSandbox> {
+        let inner = self.lock().unwrap();
+        for (_, podsandbox) in &inner.podSandboxes {
+            let sandbox = podsandbox.lock().unwrap();
+            if sandbox.namespace == namespace && sandbox.Addrs().contains(&addr) {
+                return Some(podsandbox.clone());
+            }
+        }
+
+        return None;
+    }
+
     pub fn GetPodSandboxAddr(&self, uid: &str) -> Result<IpAddress> {
         let ns = self.GetPodSandbox(uid)?;
 
@@ -40,8 +40,14 @@ impl EventHandler for NodeMgr {
                     .into();
                 let peerPort: u16 = nodeInfo.tsotSvcPort;
                 let cidr = ipnetwork::Ipv4Network::from_str(&nodeInfo.cidr).unwrap();
+                let cidr6 = if nodeInfo.cidr6.len() == 0 {
+                    None
+                } else {
+                    let cidr6 = ipnetwork::Ipv6Network::from_str(&nodeInfo.cidr6).unwrap();
+                    Some(cidr6.ip().into())
+                };
                 PEER_MGR
-                    .AddPeer(peerIp, peerPort, cidr.ip().into())
+                    .AddPeer(peerIp, peerPort, cidr.ip().into(), cidr6)
                     .unwrap();
             }
             EventType::Deleted => {
@@ -34,6 +34,7 @@ pub struct NodeRegister {
     pub tsotSvcPort: u16,
     pub stateSvcPort: u16,
     pub cidr: String,
+    pub cidr6: String,
 }
 
 impl NodeRegister {
@@ -45,6 +46,7 @@ impl NodeRegister {
         tsotSvcPort: u16,
         stateSvcPort: u16,
         cidr: &str,
+        cidr6: &str,
     ) -> Self {
         let mut etcdAddresses = Vec::new();
         for addr in addresses {
@@ -61,6 +63,7 @@ impl NodeRegister {
             tsotSvcPort: tsotSvcPort,
             stateSvcPort: stateSvcPort,
             cidr: cidr.to_owned(),
+            cidr6: cidr6.to_owned(),
         };
     }
 
@@ -77,6 +80,7 @@ impl NodeRegister {
             tsotSvcPort: self.tsotSvcPort,
             stateSvcPort: self.stateSvcPort,
             cidr: self.cidr.clone(),
+            cidr6: self.cidr6.clone(),
         };
     }
 
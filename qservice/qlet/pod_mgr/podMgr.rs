@@ -35,6 +35,8 @@ use crate::pod_mgr::*;
 use crate::QLET_CONFIG;
 
 use super::cadvisor::provider::CadvisorInfoProvider;
+use super::cri::server::QletCriSvc;
+use super::cri::streaming::STREAMING_SVC;
 use super::qnode::QuarkNode;
 use super::{CADVISOR_PROVIDER, QLET_STORE, RUNTIME_MGR};
 
@@ -277,6 +279,9 @@ pub async fn PodMgrSvc() -> Result<()> {
         .add_service(na::node_agent_service_server::NodeAgentServiceServer::new(
             podMgr,
         ))
+        .add_service(crictl::runtime_service_server::RuntimeServiceServer::new(
+            QletCriSvc::default(),
+        ))
         .serve(podMgrAddr.parse().unwrap());
 
     let nodeRegister = NodeRegister::New(
@@ -287,12 +292,15 @@ pub async fn PodMgrSvc() -> Result<()> {
         QLET_CONFIG.tsotSvcPort,
         QLET_CONFIG.stateSvcPort,
         &QLET_CONFIG.cidr,
+        &QLET_CONFIG.cidr6,
     );
 
     let nodeRegisterFuture = nodeRegister.Process();
 
     let qletStateSvcFuture = QletStateService();
 
+    let streamingSvcFuture = STREAMING_SVC.Process();
+
     info!("pod manager start ...");
     tokio::select! {
         _ = podMgrSvcFuture => {
@@ -304,6 +312,9 @@ pub async fn PodMgrSvc() -> Result<()> {
         _ = qletStateSvcFuture => {
             error!("qletStateSvcFuture finish");
         }
+        res = streamingSvcFuture => {
+            error!("streamingSvcFuture finish {:?}", res);
+        }
     }
     info!("pod manager finish ...");
 
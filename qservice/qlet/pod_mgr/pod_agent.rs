@@ -38,6 +38,8 @@ use qshare::config::*;
 use qshare::crictl;
 use qshare::types::*;
 
+use crate::tsot::dns_proxy::SearchDomains;
+
 use super::IMAGE_MGR;
 use super::RUNTIME_MGR;
 
@@ -735,8 +737,6 @@ impl PodAgent {
         let pod = self.pod.Pod();
         let pod = pod.read().unwrap();
 
-        let namespaceSearch = format!("{}.{}.svc.cluster.local", &pod.namespace, &pod.tenant);
-
         let podUID = pod.uid.clone();
         let mut podSandboxConfig = crictl::PodSandboxConfig {
             metadata: Some(crictl::PodSandboxMetadata {
@@ -749,7 +749,8 @@ impl PodAgent {
             annotations: NewPodAnnotations(&pod),
             dns_config: Some(DnsConfig {
                 servers: vec!["127.0.0.53".to_string()],
-                searches: vec![namespaceSearch],
+                // the guest resolver expands the names with the search domains
+                searches: SearchDomains(&pod.PodNamespace()),
                 options: vec!["ndots:2".to_string(), "edns0".to_string()],
             }),
             ..Default::default()
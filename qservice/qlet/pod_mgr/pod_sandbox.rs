@@ -13,10 +13,12 @@
 // limitations under the License.
 
 use core::ops::Deref;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use std::sync::Mutex;
 
 use qshare::common::*;
+use qshare::tsot_msg::TsotAddr;
 
 #[derive(Debug, Default)]
 pub struct PodSandboxInner {
@@ -24,6 +26,9 @@ pub struct PodSandboxInner {
     pub namespace: String,
     pub name: String,
     pub ip: IpAddress,
+    // unspecified when the pod network is ipv4 only
+    pub ipv6: Ipv6Address,
+    pub labels: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Clone)]
@@ -38,14 +43,45 @@ impl Deref for PodSandbox {
 }
 
 impl PodSandbox {
-    pub fn New(uid: &str, namespace: &str, name: &str, addr: IpAddress) -> Self {
+    pub fn New(
+        uid: &str,
+        namespace: &str,
+        name: &str,
+        addr: IpAddress,
+        ipv6: Ipv6Address,
+        labels: &BTreeMap<String, String>,
+    ) -> Self {
         let inner = PodSandboxInner {
             podUid: uid.to_owned(),
             namespace: namespace.to_owned(),
             name: name.to_owned(),
             ip: addr,
+            ipv6: ipv6,
+            labels: labels.clone(),
         };
 
         return Self(Arc::new(Mutex::new(inner)));
     }
 }
+
+impl PodSandboxInner {
+    // the tsot addresses of the pod, the ipv6 one is only there for the dual stack pod network
+    pub fn Addrs(&self) -> Vec<TsotAddr> {
+        let mut addrs = vec![TsotAddr::FromIpv4(self.ip.0)];
+        if !self.ipv6.IsUnspecified() {
+            addrs.push(TsotAddr::FromIpv6(self.ipv6.AsBytes()));
+        }
+
+        return addrs;
+    }
+
+    // the pod address in the family of the peer address, so that an ipv6 peer sees the pod ipv6
+    // address. the ipv4 address is used when the pod has no ipv6 address
+    pub fn AddrFor(&self, peer: &TsotAddr) -> TsotAddr {
+        if !peer.IsIpv4() && !self.ipv6.IsUnspecified() {
+            return TsotAddr::FromIpv6(self.ipv6.AsBytes());
+        }
+
+        return TsotAddr::FromIpv4(self.ip.0);
+    }
+}
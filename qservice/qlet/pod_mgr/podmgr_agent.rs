@@ -356,11 +356,13 @@ impl PmAgent {
             let namespace = pod.PodNamespace();
             let podname = pod.name.clone();
 
-            let addr = NAMESPACE_MGR.NewPodSandbox(&namespace, &uid, &podname)?;
+            let (addr, ipv6) =
+                NAMESPACE_MGR.NewPodSandbox(&namespace, &uid, &podname, &pod.labels)?;
 
             podAgent.Start()?;
             let qpod = podAgent.pod.clone();
             qpod.Pod().write().unwrap().ipAddr = addr.0;
+            qpod.Pod().write().unwrap().ipv6Addr = ipv6.AsBytes();
             NODEAGENT_STORE.CreatePod(&qpod)?;
             QLET_STORE.get().unwrap().CreatePod(&qpod)?;
             podAgent.Send(NodeAgentMsg::PodCreate(PodCreate { pod: qpod }))?;
@@ -16,21 +16,30 @@ use std::net::Ipv4Addr;
 use std::net::SocketAddrV4;
 use std::os::fd::AsRawFd;
 use std::os::fd::FromRawFd;
-use std::os::fd::IntoRawFd;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWrite;
+use tokio::io::AsyncWriteExt;
 use tokio::net::TcpListener;
 use tokio::net::TcpSocket;
 use tokio::net::TcpStream;
 use tokio::sync::Notify;
+use tokio_openssl::SslStream;
 
 use qshare::common::*;
 use qshare::tsot_msg::ErrCode;
+use qshare::tsot_msg::TsotAddr;
+use qshare::tsot_msg::UDP_MAX_PAYLOAD_SIZE;
 
+use super::network_policy::NETWORK_POLICY_MGR;
 use super::peer_mgr::PEER_MGR;
 use super::pod_broker::PodBroker;
 use super::pod_broker_mgr::POD_BRORKER_MGRS;
+use super::tls::TsotTls;
+use super::tls::TSOT_TLS;
 
 #[repr(u32)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -39,29 +48,91 @@ pub enum TsotErrCode {
     Reject = 1,
 }
 
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TsotConnType {
+    // the connection is handed over to the target pod
+    Stream = 0,
+    // the connection carries the pod datagrams to the peer node, each one is framed by TsotDatagramHdr
+    Datagram = 1,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct TsotConnReq {
     pub podNamespace: [u8; 64],
-    pub dstIp: u32,
+    pub dstIp: TsotAddr,
     pub dstPort: u16,
-    pub srcIp: u32,
+    pub srcIp: TsotAddr,
     pub srcPort: u16,
+    pub connType: u32,
 }
 
-impl TsotConnReq {
-    pub fn GetNamespace(&self) -> Result<String> {
-        for i in 0..self.podNamespace.len() {
-            if self.podNamespace[i] == 0 {
-                if i == 0 {
-                    return Ok("Default".to_owned());
-                }
-                let str = std::str::from_utf8(&self.podNamespace[0..i])?;
-                return Ok(str.to_owned());
+pub fn GetNamespace(podNamespace: &[u8; 64]) -> Result<String> {
+    for i in 0..podNamespace.len() {
+        if podNamespace[i] == 0 {
+            if i == 0 {
+                return Ok("Default".to_owned());
             }
+            let str = std::str::from_utf8(&podNamespace[0..i])?;
+            return Ok(str.to_owned());
         }
+    }
+
+    let str = std::str::from_utf8(podNamespace)?;
+    return Ok(str.to_owned());
+}
+
+impl TsotConnReq {
+    pub fn GetNamespace(&self) -> Result<String> {
+        return GetNamespace(&self.podNamespace);
+    }
+}
+
+// the header of a datagram on the datagram link, followed by len bytes of payload
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TsotDatagramHdr {
+    pub podNamespace: [u8; 64],
+    pub dstIp: TsotAddr,
+    pub srcIp: TsotAddr,
+    pub dstPort: u16,
+    pub srcPort: u16,
+    pub len: u32,
+}
+
+pub const TSOT_DATAGRAM_HDR_SIZE: usize = std::mem::size_of::<TsotDatagramHdr>();
+
+impl TsotDatagramHdr {
+    pub fn New(
+        namespace: &str,
+        dstIp: TsotAddr,
+        dstPort: u16,
+        srcIp: TsotAddr,
+        srcPort: u16,
+        len: usize,
+    ) -> Self {
+        let mut hdr = Self {
+            podNamespace: [0; 64],
+            dstIp: dstIp,
+            srcIp: srcIp,
+            dstPort: dstPort,
+            srcPort: srcPort,
+            len: len as u32,
+        };
+
+        let bytes = namespace.as_bytes();
+        let len = bytes.len().min(hdr.podNamespace.len() - 1);
+        hdr.podNamespace[..len].copy_from_slice(&bytes[..len]);
+        return hdr;
+    }
+
+    pub fn GetNamespace(&self) -> Result<String> {
+        return GetNamespace(&self.podNamespace);
+    }
 
-        let str = std::str::from_utf8(&self.podNamespace)?;
-        return Ok(str.to_owned());
+    pub fn AsBytes(&self) -> &[u8] {
+        let addr = self as *const _ as u64 as *const u8;
+        return unsafe { std::slice::from_raw_parts(addr, TSOT_DATAGRAM_HDR_SIZE) };
     }
 }
 
@@ -150,27 +221,85 @@ impl TcpSvcConnection {
         return Self { stream: stream };
     }
 
-    pub async fn ProcessConnectionInner(&self) -> Result<()> {
-        error!("TcpSvcConnection ProcessConnectionInner 1");
-        let connReq = self.ReadConnReq().await?;
-
-        error!("TcpSvcConnection ProcessConnectionInner 2 {:?}", &connReq);
+    // check whether the peer connection from the node at hostIp can be accepted, the connection
+    // response is sent to the peer before the socket is handed over to the pod
+    pub fn CheckConnection(connReq: &TsotConnReq, encrypted: bool, hostIp: u32) -> Result<()> {
         let namespace = connReq.GetNamespace()?;
-        let socket = self.stream.as_raw_fd();
+        if !encrypted && TSOT_TLS.RequireEncryption(&namespace) {
+            return Err(Error::CommonError(format!(
+                "TcpSvcConnection namespace {} requires encrypted connection",
+                namespace
+            )));
+        }
+
+        let res = NETWORK_POLICY_MGR
+            .RemoteSource(&namespace, hostIp, connReq.srcIp)
+            .and_then(|source| {
+                NETWORK_POLICY_MGR.CheckAccept(&namespace, connReq.dstIp, connReq.dstPort, &source)
+            });
+        match res {
+            Ok(()) => (),
+            Err(e) => {
+                error!("TcpSvcConnection reject connection {:?}", e);
+                return Err(e);
+            }
+        }
 
-        POD_BRORKER_MGRS.HandlePeerConnect(
+        POD_BRORKER_MGRS.CheckPeerConnect(&namespace, connReq.dstIp, connReq.dstPort)?;
+        return Ok(());
+    }
+
+    // hand the socket over to the pod, the pod broker owns and closes the socket afterwards
+    pub fn HandOver(connReq: &TsotConnReq, socket: i32) -> Result<()> {
+        let namespace = connReq.GetNamespace()?;
+        match POD_BRORKER_MGRS.HandlePeerConnect(
             &namespace,
             connReq.dstIp,
             connReq.dstPort,
             connReq.srcIp,
             connReq.srcPort,
             socket,
-        )?;
-        return Ok(());
+        ) {
+            Ok(()) => return Ok(()),
+            Err(e) => {
+                nix::unistd::close(socket).ok();
+                return Err(e);
+            }
+        }
     }
 
-    pub async fn Process(self) -> Result<()> {
-        match self.ProcessConnectionInner().await {
+    pub async fn Process(mut self) -> Result<()> {
+        if TSOT_TLS.Enabled() {
+            if !TsotTls::IsTlsStream(&self.stream).await? {
+                return Err(Error::CommonError(format!(
+                    "TcpSvcConnection reject plain connection from {:?}",
+                    self.stream.peer_addr()?
+                )));
+            }
+
+            let stream = TSOT_TLS.Accept(self.stream).await?;
+            return Self::ProcessTls(stream).await;
+        }
+
+        let peerIp = TsotTls::PeerIp(&self.stream)?;
+        if !PEER_MGR.IsPeerHost(peerIp.into()) {
+            return Err(Error::CommonError(format!(
+                "TcpSvcConnection reject connection from unknown host {:?}",
+                peerIp
+            )));
+        }
+
+        let connReq = self.ReadConnReq().await?;
+        if connReq.connType == TsotConnType::Datagram as u32 {
+            let resp = TsotConnResp {
+                errcode: TsotErrCode::Ok as _,
+            };
+
+            self.WriteConnResp(resp).await?;
+            return Self::ProcessDatagrams(&mut self.stream, false, peerIp.into()).await;
+        }
+
+        match Self::CheckConnection(&connReq, false, peerIp.into()) {
             Err(_e) => {
                 let resp = TsotConnResp {
                     errcode: TsotErrCode::Reject as _,
@@ -185,15 +314,117 @@ impl TcpSvcConnection {
                 };
 
                 self.WriteConnResp(resp).await?;
-                let stdStream = self.stream.into_std().unwrap();
 
-                // take the ownership of the TcpStream to avoid fd close
-                let _fd = stdStream.into_raw_fd();
+                // the pod gets its own fd of the connection, self.stream is closed as usual
+                let socket = nix::unistd::dup(self.stream.as_raw_fd())
+                    .map_err(|e| Error::SysError(e as i32))?;
+                return Self::HandOver(&connReq, socket);
+            }
+        }
+    }
+
+    // the encrypted connection from the peer node, the pod gets one side of a socket pair
+    // and the other side is relayed to the peer node
+    pub async fn ProcessTls(mut stream: SslStream<TcpStream>) -> Result<()> {
+        let peerIp: u32 = TsotTls::PeerIp(stream.get_ref())?.into();
+        let mut reqBuf = [0; std::mem::size_of::<TsotConnReq>()];
+        stream.read_exact(&mut reqBuf).await?;
+        let connReq = unsafe { *(&reqBuf[0] as *const _ as u64 as *const TsotConnReq) };
+        if connReq.connType == TsotConnType::Datagram as u32 {
+            Self::WriteConnRespTo(&mut stream, TsotErrCode::Ok).await?;
+            return Self::ProcessDatagrams(&mut stream, true, peerIp).await;
+        }
+
+        match Self::CheckConnection(&connReq, true, peerIp) {
+            Err(_e) => {
+                Self::WriteConnRespTo(&mut stream, TsotErrCode::Reject).await?;
+                return Ok(());
+            }
+            Ok(()) => {
+                Self::WriteConnRespTo(&mut stream, TsotErrCode::Ok).await?;
+                let (socket, local) = TsotTls::SocketPair()?;
+                Self::HandOver(&connReq, socket)?;
+                TsotTls::Relay(stream, local).await;
                 return Ok(());
             }
         }
     }
 
+    pub async fn WriteConnRespTo<S: AsyncWrite + Unpin>(
+        stream: &mut S,
+        errcode: TsotErrCode,
+    ) -> Result<()> {
+        let resp = TsotConnResp {
+            errcode: errcode as _,
+        };
+
+        let addr = &resp as *const _ as u64 as *const u8;
+        let buf = unsafe { std::slice::from_raw_parts(addr, std::mem::size_of::<TsotConnResp>()) };
+        stream.write_all(buf).await?;
+        return Ok(());
+    }
+
+    // the datagrams from the peer node, a rejected datagram is dropped
+    pub async fn ProcessDatagrams<S: AsyncRead + Unpin>(
+        stream: &mut S,
+        encrypted: bool,
+        hostIp: u32,
+    ) -> Result<()> {
+        let mut hdrBuf = [0; TSOT_DATAGRAM_HDR_SIZE];
+        let mut payload = vec![0; UDP_MAX_PAYLOAD_SIZE];
+        loop {
+            match stream.read_exact(&mut hdrBuf).await {
+                Ok(_) => (),
+                Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(()),
+                Err(e) => return Err(e.into()),
+            }
+
+            let hdr =
+                unsafe { std::ptr::read_unaligned(hdrBuf.as_ptr() as *const TsotDatagramHdr) };
+            let len = hdr.len as usize;
+            if len > UDP_MAX_PAYLOAD_SIZE {
+                return Err(Error::CommonError(format!(
+                    "TcpSvcConnection::ProcessDatagrams invalid datagram len {}",
+                    len
+                )));
+            }
+
+            stream.read_exact(&mut payload[..len]).await?;
+            match Self::ProcessDatagram(&hdr, &payload[..len], encrypted, hostIp) {
+                Ok(()) => (),
+                Err(e) => {
+                    error!("TcpSvcConnection::ProcessDatagrams drop datagram {:?}", e);
+                }
+            }
+        }
+    }
+
+    pub fn ProcessDatagram(
+        hdr: &TsotDatagramHdr,
+        payload: &[u8],
+        encrypted: bool,
+        hostIp: u32,
+    ) -> Result<()> {
+        let namespace = hdr.GetNamespace()?;
+        if !encrypted && TSOT_TLS.RequireEncryption(&namespace) {
+            return Err(Error::CommonError(format!(
+                "TcpSvcConnection namespace {} requires encrypted datagram",
+                namespace
+            )));
+        }
+
+        let source = NETWORK_POLICY_MGR.RemoteSource(&namespace, hostIp, hdr.srcIp)?;
+        NETWORK_POLICY_MGR.CheckAccept(&namespace, hdr.dstIp, hdr.dstPort, &source)?;
+        return POD_BRORKER_MGRS.HandleDatagram(
+            &namespace,
+            hdr.dstIp,
+            hdr.dstPort,
+            hdr.srcIp,
+            hdr.srcPort,
+            payload,
+        );
+    }
+
     pub async fn ReadConnReq(&self) -> Result<TsotConnReq> {
         const REQ_SIZE: usize = std::mem::size_of::<TsotConnReq>();
         let mut readBuf = [0; REQ_SIZE];
@@ -234,9 +465,9 @@ pub struct TcpClientConnection {
 
     pub reqId: u32,
     pub podNamespace: String,
-    pub dstIp: u32,
+    pub dstIp: TsotAddr,
     pub dstPort: u16,
-    pub srcIp: u32,
+    pub srcIp: TsotAddr,
     pub srcPort: u16,
 }
 
@@ -279,21 +510,32 @@ impl TcpClientConnection {
         }
     }
 
-    pub async fn ProcessConnection(&self) -> Result<TcpStream> {
-        error!("ProcessConnection 1");
-        let stream = self.Connect().await?;
+    pub fn ConnReq(&self) -> TsotConnReq {
         let mut req = TsotConnReq {
             podNamespace: [0; 64],
             dstIp: self.dstIp,
             dstPort: self.dstPort,
             srcIp: self.srcIp,
             srcPort: self.srcPort,
+            connType: TsotConnType::Stream as _,
         };
 
         for i in 0..self.podNamespace.as_bytes().len() {
             req.podNamespace[i] = self.podNamespace.as_bytes()[i];
         }
 
+        return req;
+    }
+
+    pub async fn ProcessConnection(&self) -> Result<TcpStream> {
+        if TSOT_TLS.Enabled() {
+            return self.ProcessTlsConnection().await;
+        }
+
+        error!("ProcessConnection 1");
+        let stream = self.Connect().await?;
+        let req = self.ConnReq();
+
         self.WriteConnReq(&stream, req).await?;
 
         let resp = self.ReadConnResp(&stream).await?;
@@ -308,6 +550,36 @@ impl TcpClientConnection {
         return Ok(stream);
     }
 
+    // the pod socket is connected to a loopback relay of the encrypted connection to the peer node
+    pub async fn ProcessTlsConnection(&self) -> Result<TcpStream> {
+        let socket = unsafe { TcpSocket::from_raw_fd(self.socket) };
+        let peer = PEER_MGR.LookforPeer(self.dstIp)?;
+        let addr = SocketAddrV4::new(Ipv4Addr::from(peer.hostIp), peer.port);
+        let stream = TcpStream::connect(addr).await?;
+        stream.set_nodelay(true)?;
+        let mut stream = TSOT_TLS.Connect(stream, peer.hostIp).await?;
+
+        let req = self.ConnReq();
+        let reqAddr = &req as *const _ as u64 as *const u8;
+        let reqBuf =
+            unsafe { std::slice::from_raw_parts(reqAddr, std::mem::size_of::<TsotConnReq>()) };
+        stream.write_all(reqBuf).await?;
+
+        let mut respBuf = [0; std::mem::size_of::<TsotConnResp>()];
+        stream.read_exact(&mut respBuf).await?;
+        let resp = unsafe { *(&respBuf[0] as *const _ as u64 as *const TsotConnResp) };
+        if resp.errcode != TsotErrCode::Ok as u32 {
+            return Err(Error::CommonError(format!(
+                "TcpClientConnection connect fail with error {:?}",
+                resp.errcode
+            )));
+        }
+
+        let (podStream, local) = TsotTls::LoopbackConnect(socket).await?;
+        tokio::spawn(TsotTls::Relay(stream, local));
+        return Ok(podStream);
+    }
+
     pub async fn Connect(&self) -> Result<TcpStream> {
         let peer = PEER_MGR.LookforPeer(self.dstIp)?;
         let ip = Ipv4Addr::from(peer.hostIp);
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use dns_lookup::{AddrInfoHints, LookupErrorKind};
 use rand::Rng;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
@@ -20,14 +21,131 @@ use std::time::Duration;
 use tokio::sync::{mpsc, Notify};
 
 use crate::QLET_CONFIG;
+use qshare::common::*;
 use qshare::metastore::cacher_client::CacherClient;
 use qshare::tsot_msg::*;
 
 use super::pod_broker::PodBroker;
+use super::service_mgr::SERVICE_MGR;
 use qshare::node::PodDef;
 
 pub const DNS_POSTFIX: &'static str = "svc.cluster.local";
 
+// the cluster addresses change with the pods, keep them short in the guest cache
+pub const DNS_CLUSTER_TTL: u32 = 5;
+pub const DNS_EXTERNAL_TTL: u32 = 30;
+pub const DNS_NEGATIVE_TTL: u32 = 5;
+
+// the resolv.conf search domains of the pod, the podNamespace is "tenant/namespace"
+pub fn SearchDomains(podNamespace: &str) -> Vec<String> {
+    let split: Vec<&str> = podNamespace.split("/").collect();
+    if split.len() != 2 {
+        return Vec::new();
+    }
+
+    let tenant = split[0];
+    let namespace = split[1];
+    return vec![
+        format!("{}.{}.{}", namespace, tenant, DNS_POSTFIX),
+        format!("{}.{}", tenant, DNS_POSTFIX),
+    ];
+}
+
+#[derive(Debug, Default)]
+pub struct DnsLookupResult {
+    pub rcode: u8,
+    pub ip: u32,
+    // unspecified when there is no AAAA answer, only the cluster domains have one
+    pub ipv6: Ipv6Address,
+    // the canonical name, empty when the domain is canonical
+    pub cname: String,
+    pub ttl: u32,
+}
+
+impl DnsLookupResult {
+    pub fn NxDomain() -> Self {
+        return Self {
+            rcode: DNS_RCODE_NXDOMAIN,
+            ttl: DNS_NEGATIVE_TTL,
+            ..Default::default()
+        };
+    }
+
+    pub fn ServFail() -> Self {
+        return Self {
+            rcode: DNS_RCODE_SERVFAIL,
+            ..Default::default()
+        };
+    }
+
+    // the services only have the ipv4 virtual ip, their ipv6 is unspecified
+    pub fn Cluster(ip: u32, ipv6: Ipv6Address) -> Self {
+        return Self {
+            rcode: DNS_RCODE_NOERROR,
+            ip: ip,
+            ipv6: ipv6,
+            ttl: DNS_CLUSTER_TTL,
+            ..Default::default()
+        };
+    }
+}
+
+pub fn LookupExternalDomain(domain: &str) -> DnsLookupResult {
+    if domain.len() == 0 {
+        return DnsLookupResult::NxDomain();
+    }
+
+    let hints = AddrInfoHints {
+        flags: libc::AI_CANONNAME,
+        socktype: libc::SOCK_STREAM,
+        ..Default::default()
+    };
+
+    let addrs = match dns_lookup::getaddrinfo(Some(domain), None, Some(hints)) {
+        Err(e) => match e.kind() {
+            LookupErrorKind::NoName | LookupErrorKind::NoData => {
+                return DnsLookupResult::NxDomain()
+            }
+            _ => return DnsLookupResult::ServFail(),
+        },
+        Ok(addrs) => addrs,
+    };
+
+    let mut result = DnsLookupResult {
+        rcode: DNS_RCODE_NOERROR,
+        ttl: DNS_EXTERNAL_TTL,
+        ..Default::default()
+    };
+    let mut hasIpv4 = false;
+    for addr in addrs {
+        let addr = match addr {
+            Err(_) => continue,
+            Ok(a) => a,
+        };
+
+        if let Some(canonname) = addr.canonname {
+            if result.cname.len() == 0 && canonname != domain {
+                result.cname = canonname;
+            }
+        }
+
+        match addr.sockaddr.ip() {
+            std::net::IpAddr::V4(addr) => {
+                if hasIpv4 {
+                    continue;
+                }
+                result.ip = u32::from(addr);
+                hasIpv4 = true;
+            }
+            // tsot only routes the ipv6 addresses of the pods, the external AAAA answers are
+            // dropped so that the clients connect by ipv4
+            std::net::IpAddr::V6(_) => (),
+        }
+    }
+
+    return result;
+}
+
 lazy_static::lazy_static! {
     pub static ref DNS_PROXY: DnsProxy = DnsProxy::New();
 }
@@ -116,6 +234,41 @@ impl DnsProxy {
         }
     }
 
+    // return None when the dns proxy is closed
+    pub async fn LookupClusterDomain(
+        &self,
+        client: &mut CacherClient,
+        domain: &str,
+    ) -> Option<DnsLookupResult> {
+        let left = domain.strip_suffix(DNS_POSTFIX).unwrap();
+        let split: Vec<&str> = left.split(".").collect();
+        if split.len() != 4 {
+            return Some(DnsLookupResult::NxDomain());
+        }
+        let tenant = split[2];
+        let namespace = split[1];
+        let name = split[0];
+        // the service names are resolved to the virtual ips
+        if let Some(vip) = SERVICE_MGR.Lookup(tenant, namespace, name) {
+            return Some(DnsLookupResult::Cluster(vip, Ipv6Address::default()));
+        }
+        match client.Get("pod", tenant, namespace, name, 0).await {
+            Err(e) => {
+                error!("DnsProxy::Process fail {:?}", e);
+                *client = self.GetClient().await?;
+                return Some(DnsLookupResult::ServFail());
+            }
+            Ok(None) => return Some(DnsLookupResult::NxDomain()),
+            Ok(Some(obj)) => {
+                let pod: PodDef = serde_json::from_str(&obj.data).expect(&format!(
+                    "NodeMgr::handle deserialize fail for {}",
+                    &obj.data
+                ));
+                return Some(DnsLookupResult::Cluster(pod.ipAddr, Ipv6Address(pod.ipv6Addr)));
+            }
+        }
+    }
+
     pub async fn Process(&self) {
         let mut rx = self.inputRx.lock().unwrap().take().unwrap();
         let mut client = match self.GetClient().await {
@@ -134,85 +287,38 @@ impl DnsProxy {
                         None => (),
                         Some(m) => {
                             let m: DnsProxyReq = m;
-                            let mut ips = Vec::new();
-                            for domain in m.domains {
-                                if domain.ends_with(DNS_POSTFIX) {
-                                    let left = domain.strip_suffix(DNS_POSTFIX).unwrap();
-                                    let split : Vec<&str> = left.split(".").collect();
-                                    if split.len() != 4 {
-                                        error!("get invalid domain {}/ {:?}/ {}", domain, &split, left);
-                                        ips.push(0);
-                                        continue;
-                                    }
-                                    let tenant = split[2];
-                                    let namespace = split[1];
-                                    let name = split[0];
-                                    match client.Get("pod", tenant, namespace, name, 0).await {
-                                        Err(e) => {
-                                            error!("DnsProxy::Process fail {:?}", e);
-                                            client = match self.GetClient().await {
-                                                None => return,
-                                                Some(c) => c
-                                            };
-                                        }
-                                        Ok(r) => {
-                                            match r {
-                                                None => ips.push(0),
-                                                Some(obj) => {
-                                                    let pod: PodDef = serde_json::from_str(&obj.data)
-                                                    .expect(&format!("NodeMgr::handle deserialize fail for {}", &obj.data));
-                                                    ips.push(pod.ipAddr);
-                                                }
-                                            }
-                                        }
+                            let mut results = Vec::new();
+                            for domain in &m.domains {
+                                let result = if domain.ends_with(DNS_POSTFIX) {
+                                    match self.LookupClusterDomain(&mut client, domain).await {
+                                        None => return,
+                                        Some(r) => r,
                                     }
                                 } else {
-                                    if domain.len() == 0 {
-                                        ips.push(0);
-                                        continue;
-                                    }
-                                    match dns_lookup::lookup_host(&domain) {
-                                        Err(_) => {
-                                            ips.push(0);
-                                        }
-                                        Ok(ipAddresses) =>  {
-                                            let mut hasIpv4 = false;
-                                            for ip in ipAddresses {
-                                                match ip {
-                                                    std::net::IpAddr::V4(addr) => {
-                                                        let octets = addr.octets();
-                                                        let addr = ((octets[0] as u32) << 24) |
-                                                            ((octets[1] as u32) << 16) |
-                                                            ((octets[2] as u32) << 8) |
-                                                            ((octets[3] as u32) << 0);
-
-                                                            ips.push(addr);
-                                                            hasIpv4 = true;
-                                                            break;
-                                                    }
-                                                    _ => ()
-                                                }
-                                            }
-
-                                            if !hasIpv4 {
-                                                ips.push(0);
-                                            }
-                                        }
-                                    };
-                                }
+                                    LookupExternalDomain(domain)
+                                };
+                                results.push(result);
                             }
 
                             let mut dnsResp = DnsResp {
                                 reqId: m.reqId,
-                                count: ips.len(),
+                                count: results.len(),
                                 ..Default::default()
                             };
 
-                            assert!(ips.len() <= 4);
-                            for i in 0..ips.len() {
-                                dnsResp.ips[i] = ips[i];
+                            assert!(results.len() <= 4);
+                            let mut cnames = Vec::new();
+                            for i in 0..results.len() {
+                                dnsResp.ips[i] = results[i].ip;
+                                dnsResp.ipv6s[i] = results[i].ipv6.AsBytes();
+                                dnsResp.rcodes[i] = results[i].rcode;
+                                dnsResp.ttls[i] = results[i].ttl;
+                                cnames.push(results[i].cname.clone());
                             }
 
+                            if !dnsResp.SetCnames(&cnames) {
+                                error!("DnsProxy::Process cnames {:?} are too long", &cnames);
+                            }
 
                             m.podBroker.EnqMsg(TsotMsg::DnsResp(dnsResp).into()).unwrap();
                         }
@@ -14,8 +14,12 @@
 
 pub mod conn_svc;
 pub mod dns_proxy;
+pub mod network_policy;
 pub mod peer_mgr;
 pub mod pod_broker;
 pub mod pod_broker_mgr;
+pub mod service_mgr;
 mod tsot_agent;
+pub mod tls;
 pub mod tsot_svc;
+pub mod udp_relay;
@@ -0,0 +1,376 @@
+// Copyright (c) 2023 Quark Container Authors / 2018 The gVisor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use core::ops::Deref;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::RwLock;
+
+use qshare::common::*;
+use qshare::metastore::data_obj::*;
+use qshare::metastore::informer::EventHandler;
+use qshare::metastore::store::ThreadSafeStore;
+use qshare::node::NetworkPolicy;
+use qshare::node::PodDef;
+use qshare::tsot_msg::TsotAddr;
+
+use super::peer_mgr::PEER_MGR;
+use crate::pod_mgr::NAMESPACE_MGR;
+
+lazy_static::lazy_static! {
+    pub static ref NETWORK_POLICY_MGR: NetworkPolicyMgr = {
+        NetworkPolicyMgr::default()
+    };
+}
+
+// the gateway pod sandbox namespace and address, see PodBroker::Init
+pub const GATEWAY_NAMESPACE: &str = "system";
+pub const GATEWAY_ADDR: u32 = (127 << 24) | (1 << 16) | (2 << 8) | 3;
+
+// the source of an accepted connection or datagram. it is derived from where the connection
+// comes from: the pod registered to the local pod broker, or the pod cidr of the peer node
+#[derive(Debug, Clone, Default)]
+pub struct ConnSource {
+    pub namespace: String,
+    pub ip: TsotAddr,
+    pub labels: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Default)]
+pub struct NetworkPolicyMgrInner {
+    // policy key to NetworkPolicy
+    pub policies: HashMap<String, NetworkPolicy>,
+
+    // (namespace, pod ip) to the labels of the pods in the cluster, the dual stack pod has one
+    // entry for each of its addresses
+    pub pods: HashMap<(String, TsotAddr), BTreeMap<String, String>>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NetworkPolicyMgr(Arc<RwLock<NetworkPolicyMgrInner>>);
+
+impl Deref for NetworkPolicyMgr {
+    type Target = Arc<RwLock<NetworkPolicyMgrInner>>;
+
+    fn deref(&self) -> &Arc<RwLock<NetworkPolicyMgrInner>> {
+        &self.0
+    }
+}
+
+impl NetworkPolicyMgr {
+    pub fn AddPolicy(&self, policy: NetworkPolicy) {
+        self.write().unwrap().policies.insert(policy.Key(), policy);
+    }
+
+    pub fn RemovePolicy(&self, key: &str) {
+        self.write().unwrap().policies.remove(key);
+    }
+
+    // the ipv4 and ipv6 addresses of the pod
+    pub fn PodAddrs(pod: &PodDef) -> Vec<TsotAddr> {
+        let mut addrs = vec![TsotAddr::FromIpv4(pod.ipAddr)];
+        if pod.ipv6Addr != [0; 16] {
+            addrs.push(TsotAddr::FromIpv6(pod.ipv6Addr));
+        }
+
+        return addrs;
+    }
+
+    pub fn AddPod(&self, pod: &PodDef) {
+        let mut inner = self.write().unwrap();
+        for addr in Self::PodAddrs(pod) {
+            inner
+                .pods
+                .insert((pod.PodNamespace(), addr), pod.labels.clone());
+        }
+    }
+
+    pub fn RemovePod(&self, pod: &PodDef) {
+        let mut inner = self.write().unwrap();
+        for addr in Self::PodAddrs(pod) {
+            inner.pods.remove(&(pod.PodNamespace(), addr));
+        }
+    }
+
+    // the labels of the pod with the address, the local pod sandboxes are checked first
+    pub fn PodLabels(&self, namespace: &str, addr: TsotAddr) -> BTreeMap<String, String> {
+        if let Some(sandbox) = NAMESPACE_MGR.GetPodSandboxByAddr(namespace, addr) {
+            return sandbox.lock().unwrap().labels.clone();
+        }
+
+        match self.read().unwrap().pods.get(&(namespace.to_owned(), addr)) {
+            None => return BTreeMap::new(),
+            Some(labels) => return labels.clone(),
+        }
+    }
+
+    // whether the pod (namespace, labels) can connect to the peer (peerNamespace, peerLabels, port)
+    pub fn AllowEgress(
+        &self,
+        namespace: &str,
+        labels: &BTreeMap<String, String>,
+        peerNamespace: &str,
+        peerLabels: &BTreeMap<String, String>,
+        port: u16,
+    ) -> bool {
+        let inner = self.read().unwrap();
+        let mut isolated = false;
+        for (_, policy) in &inner.policies {
+            if !policy.Select(namespace, labels) {
+                continue;
+            }
+
+            let rules = match &policy.spec.egress {
+                None => continue,
+                Some(rules) => rules,
+            };
+
+            isolated = true;
+            for rule in rules {
+                if rule.Allow(&policy.PodNamespace(), peerNamespace, peerLabels, port) {
+                    return true;
+                }
+            }
+        }
+
+        return !isolated;
+    }
+
+    // whether the pod (namespace, labels) accepts the connection from the peer (peerNamespace, peerLabels) at port
+    pub fn AllowIngress(
+        &self,
+        namespace: &str,
+        labels: &BTreeMap<String, String>,
+        port: u16,
+        peerNamespace: &str,
+        peerLabels: &BTreeMap<String, String>,
+    ) -> bool {
+        let inner = self.read().unwrap();
+        let mut isolated = false;
+        for (_, policy) in &inner.policies {
+            if !policy.Select(namespace, labels) {
+                continue;
+            }
+
+            let rules = match &policy.spec.ingress {
+                None => continue,
+                Some(rules) => rules,
+            };
+
+            isolated = true;
+            for rule in rules {
+                if rule.Allow(&policy.PodNamespace(), peerNamespace, peerLabels, port) {
+                    return true;
+                }
+            }
+        }
+
+        return !isolated;
+    }
+
+    // check the egress policies of the local pod with the labels, called when the pod connects
+    pub fn CheckConnect(
+        &self,
+        namespace: &str,
+        labels: &BTreeMap<String, String>,
+        dstIp: TsotAddr,
+        dstPort: u16,
+    ) -> Result<()> {
+        let peerLabels = self.PodLabels(namespace, dstIp);
+        if !self.AllowEgress(namespace, labels, namespace, &peerLabels, dstPort) {
+            return Err(Error::CommonError(format!(
+                "network policy denies egress connection in namespace {} from {:?} to {}:{}",
+                namespace, labels, dstIp, dstPort
+            )));
+        }
+
+        return Ok(());
+    }
+
+    // the source of the connection from the peer node at hostIp, which is authenticated by
+    // the PeerMgr and the node certificate. the source ip is set by the qlet of the peer node,
+    // it has to be in the pod cidr of the node or the gateway of the node
+    pub fn RemoteSource(
+        &self,
+        namespace: &str,
+        hostIp: u32,
+        srcIp: TsotAddr,
+    ) -> Result<ConnSource> {
+        if !PEER_MGR.IsPeerHost(hostIp) {
+            return Err(Error::CommonError(format!(
+                "NetworkPolicyMgr::RemoteSource {:x} is not a peer node",
+                hostIp
+            )));
+        }
+
+        if srcIp == TsotAddr::FromIpv4(GATEWAY_ADDR) {
+            return Ok(ConnSource {
+                namespace: GATEWAY_NAMESPACE.to_owned(),
+                ip: srcIp,
+                labels: BTreeMap::new(),
+            });
+        }
+
+        if !PEER_MGR.IsHostPod(hostIp, srcIp) {
+            return Err(Error::CommonError(format!(
+                "NetworkPolicyMgr::RemoteSource source {} is not a pod of the node {:x}",
+                srcIp, hostIp
+            )));
+        }
+
+        return Ok(ConnSource {
+            namespace: namespace.to_owned(),
+            ip: srcIp,
+            labels: self.PodLabels(namespace, srcIp),
+        });
+    }
+
+    // check the ingress policies of the target pod, called when the peer connection is accepted
+    pub fn CheckAccept(
+        &self,
+        namespace: &str,
+        dstIp: TsotAddr,
+        dstPort: u16,
+        source: &ConnSource,
+    ) -> Result<()> {
+        let labels = self.PodLabels(namespace, dstIp);
+        if !self.AllowIngress(
+            namespace,
+            &labels,
+            dstPort,
+            &source.namespace,
+            &source.labels,
+        ) {
+            return Err(Error::CommonError(format!(
+                "network policy denies ingress connection in namespace {} from {}/{} to {}:{}",
+                namespace, &source.namespace, source.ip, dstIp, dstPort
+            )));
+        }
+
+        return Ok(());
+    }
+
+    pub fn ProcessDeltaEvent(&self, event: &DeltaEvent) -> Result<()> {
+        let obj = &event.obj;
+        match &event.type_ {
+            EventType::Added | EventType::Modified => {
+                if obj.kind == NetworkPolicy::KEY {
+                    self.AddPolicy(NetworkPolicy::FromDataObject(obj.clone())?);
+                } else {
+                    if let Some(oldObj) = &event.oldObj {
+                        self.RemovePod(&PodDef::FromDataObject(oldObj.clone())?);
+                    }
+                    self.AddPod(&PodDef::FromDataObject(obj.clone())?);
+                }
+            }
+            EventType::Deleted => {
+                let obj = event.oldObj.as_ref().unwrap_or(obj);
+                if obj.kind == NetworkPolicy::KEY {
+                    let policy = NetworkPolicy::FromDataObject(obj.clone())?;
+                    self.RemovePolicy(&policy.Key());
+                } else {
+                    self.RemovePod(&PodDef::FromDataObject(obj.clone())?);
+                }
+            }
+            _ => (),
+        }
+
+        return Ok(());
+    }
+}
+
+impl EventHandler for NetworkPolicyMgr {
+    fn handle(&self, _store: &ThreadSafeStore, event: &DeltaEvent) {
+        match self.ProcessDeltaEvent(event) {
+            Ok(()) => (),
+            Err(e) => {
+                error!(
+                    "NetworkPolicyMgr::handle {} fail with error {:?}",
+                    event.obj.Key(),
+                    e
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use qshare::node::NetworkPolicyPeer;
+    use qshare::node::NetworkPolicyRule;
+    use qshare::node::NetworkPolicySpec;
+
+    fn Labels(kvs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        return kvs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+    }
+
+    fn Policy(ingress: Option<Vec<NetworkPolicyRule>>) -> NetworkPolicy {
+        return NetworkPolicy {
+            tenant: "t1".to_owned(),
+            namespace: "ns1".to_owned(),
+            name: "db".to_owned(),
+            spec: NetworkPolicySpec {
+                podSelector: Labels(&[("app", "db")]),
+                ingress: ingress,
+                egress: None,
+            },
+            ..Default::default()
+        };
+    }
+
+    #[test]
+    fn TestIngressSelector() {
+        let mgr = NetworkPolicyMgr::default();
+        mgr.AddPolicy(Policy(Some(vec![NetworkPolicyRule {
+            peers: vec![NetworkPolicyPeer {
+                namespace: None,
+                podSelector: Labels(&[("app", "web")]),
+            }],
+            ports: vec![5432],
+        }])));
+
+        let db = Labels(&[("app", "db"), ("tier", "backend")]);
+        let web = Labels(&[("app", "web")]);
+        let other = Labels(&[("app", "other")]);
+
+        assert!(mgr.AllowIngress("t1/ns1", &db, 5432, "t1/ns1", &web));
+        // the port is not allowed
+        assert!(!mgr.AllowIngress("t1/ns1", &db, 80, "t1/ns1", &web));
+        // the peer doesn't match the selector
+        assert!(!mgr.AllowIngress("t1/ns1", &db, 5432, "t1/ns1", &other));
+        // the peer in another namespace
+        assert!(!mgr.AllowIngress("t1/ns1", &db, 5432, "t1/ns2", &web));
+        // the gateway is not allowed unless the rule has its namespace
+        assert!(!mgr.AllowIngress("t1/ns1", &db, 5432, GATEWAY_NAMESPACE, &BTreeMap::new()));
+        // the pods not selected by the policy are not isolated
+        assert!(mgr.AllowIngress("t1/ns1", &web, 80, "t1/ns1", &other));
+        assert!(mgr.AllowIngress("t1/ns2", &db, 80, "t1/ns2", &other));
+    }
+
+    #[test]
+    fn TestIngressIsolated() {
+        let mgr = NetworkPolicyMgr::default();
+        mgr.AddPolicy(Policy(Some(Vec::new())));
+        let db = Labels(&[("app", "db")]);
+        assert!(!mgr.AllowIngress("t1/ns1", &db, 5432, "t1/ns1", &db));
+
+        mgr.RemovePolicy("t1/ns1/db");
+        assert!(mgr.AllowIngress("t1/ns1", &db, 5432, "t1/ns1", &db));
+    }
+}
@@ -19,6 +19,7 @@ use std::sync::Arc;
 use std::sync::RwLock;
 
 use qshare::common::*;
+use qshare::tsot_msg::TsotAddr;
 
 use crate::QLET_CONFIG;
 
@@ -26,13 +27,18 @@ lazy_static::lazy_static! {
     pub static ref PEER_MGR: PeerMgr = {
         let cidrStr = QLET_CONFIG.cidr.clone();
         let ipv4 = ipnetwork::Ipv4Network::from_str(&cidrStr).unwrap();
+        let ipv6 = if QLET_CONFIG.cidr6.len() == 0 {
+            None
+        } else {
+            Some(ipnetwork::Ipv6Network::from_str(&QLET_CONFIG.cidr6).unwrap())
+        };
         //let localIp = local_ip_address::local_ip().unwrap();
-        let pm = PeerMgr::New(ipv4.prefix() as _);
+        let pm = PeerMgr::New(ipv4.prefix() as _, ipv6.map(|n| n.prefix() as _).unwrap_or(0));
 
         if QLET_CONFIG.singleNodeModel {
             let localIp : u32 = ipnetwork::Ipv4Network::from_str(&QLET_CONFIG.nodeIp).unwrap().ip().into();
             let localPort = QLET_CONFIG.tsotSvcPort;
-            pm.AddPeer(localIp, localPort, ipv4.ip().into()).unwrap();
+            pm.AddPeer(localIp, localPort, ipv4.ip().into(), ipv6.map(|n| n.ip().into())).unwrap();
         }
         pm
     };
@@ -43,17 +49,20 @@ pub struct PeerInner {
     pub hostIp: u32,
     pub port: u16,
     pub cidrAddr: u32,
+    // none when the peer has no ipv6 pod cidr
+    pub cidr6Addr: Option<u128>,
 }
 
 #[derive(Debug, Clone)]
 pub struct Peer(Arc<PeerInner>);
 
 impl Peer {
-    pub fn New(hostIp: u32, port: u16, cidrAddr: u32) -> Self {
+    pub fn New(hostIp: u32, port: u16, cidrAddr: u32, cidr6Addr: Option<u128>) -> Self {
         let inner = PeerInner {
             hostIp: hostIp,
             port: port,
             cidrAddr: cidrAddr,
+            cidr6Addr: cidr6Addr,
         };
 
         return Self(Arc::new(inner));
@@ -72,8 +81,11 @@ impl Deref for Peer {
 pub struct PeerMgrInner {
     // map cidrAddr --> Peer
     pub peers: HashMap<u32, Peer>,
+    // map cidr6Addr --> Peer
+    pub peers6: HashMap<u128, Peer>,
     pub maskbits: usize,
     pub mask: u32,
+    pub mask6: u128,
 }
 
 #[derive(Debug, Clone)]
@@ -88,30 +100,48 @@ impl Deref for PeerMgr {
 }
 
 impl PeerMgr {
-    pub fn New(maskbits: usize) -> Self {
+    // the maskbits6 is the maskbits of the ipv6 pod cidrs, all the nodes use the same maskbits
+    pub fn New(maskbits: usize, maskbits6: usize) -> Self {
         assert!(maskbits < 32);
+        assert!(maskbits6 < 128);
         let mask = !((1 << maskbits) - 1);
+        let mask6 = !((1 << maskbits6) - 1);
         let inner = PeerMgrInner {
             peers: HashMap::new(),
+            peers6: HashMap::new(),
             maskbits: maskbits,
             mask: mask,
+            mask6: mask6,
         };
 
         let mgr = Self(Arc::new(RwLock::new(inner)));
         return mgr;
     }
 
-    pub fn AddPeer(&self, hostIp: u32, port: u16, cidrAddr: u32) -> Result<()> {
-        let peer = Peer::New(hostIp, port, cidrAddr);
+    pub fn AddPeer(
+        &self,
+        hostIp: u32,
+        port: u16,
+        cidrAddr: u32,
+        cidr6Addr: Option<u128>,
+    ) -> Result<()> {
+        let peer = Peer::New(hostIp, port, cidrAddr, cidr6Addr);
         let mut inner = self.write().unwrap();
-        if inner.peers.contains_key(&cidrAddr) {
+        let exist = match cidr6Addr {
+            None => false,
+            Some(addr) => inner.peers6.contains_key(&addr),
+        };
+        if exist || inner.peers.contains_key(&cidrAddr) {
             return Err(Error::Exist(format!(
                 "PeerMgr::AddPeer get existing peer {:?}",
                 peer
             )));
         }
 
-        inner.peers.insert(cidrAddr, peer);
+        inner.peers.insert(cidrAddr, peer.clone());
+        if let Some(addr) = cidr6Addr {
+            inner.peers6.insert(addr, peer);
+        }
         return Ok(());
     }
 
@@ -124,17 +154,41 @@ impl PeerMgr {
                     cidrAddr
                 )))
             }
-            Some(_peer) => return Ok(()),
+            Some(peer) => {
+                if let Some(addr) = peer.cidr6Addr {
+                    inner.peers6.remove(&addr);
+                }
+                return Ok(());
+            }
+        }
+    }
+
+    // whether the host is the node of one of the peers
+    pub fn IsPeerHost(&self, hostIp: u32) -> bool {
+        let inner = self.read().unwrap();
+        return inner.peers.values().any(|peer| peer.hostIp == hostIp);
+    }
+
+    // whether the pod ip is in the pod cidrs of the peer at the host
+    pub fn IsHostPod(&self, hostIp: u32, podIp: TsotAddr) -> bool {
+        match self.LookforPeer(podIp) {
+            Err(_) => return false,
+            Ok(peer) => return peer.hostIp == hostIp,
         }
     }
 
-    pub fn LookforPeer(&self, ip: u32) -> Result<Peer> {
+    pub fn LookforPeer(&self, ip: TsotAddr) -> Result<Peer> {
         let inner = self.read().unwrap();
-        let cidrAddr = ip & inner.mask;
-        match inner.peers.get(&cidrAddr).cloned() {
+        let peer = match ip.Ipv4() {
+            Some(addr) => inner.peers.get(&(addr & inner.mask)),
+            None => inner
+                .peers6
+                .get(&(u128::from_be_bytes(ip.0) & inner.mask6)),
+        };
+        match peer {
             None => {
                 return Err(Error::NotExist(format!(
-                    "PeerMgr::LookforPeer peer {:x} doesn't exist",
+                    "PeerMgr::LookforPeer peer {} doesn't exist",
                     ip
                 )))
             }
@@ -142,3 +196,35 @@ impl PeerMgr {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn TestIsHostPod() {
+        let pm = PeerMgr::New(8, 16);
+        let host1 = (192 << 24) | (168 << 16) | 1;
+        let host2 = (192 << 24) | (168 << 16) | 2;
+        let cidr6: u128 = 0xfd00_0010 << 96;
+        pm.AddPeer(host1, 1235, (10 << 24) | (1 << 16) | (1 << 8), Some(cidr6 | 0x1_0000))
+            .unwrap();
+        pm.AddPeer(host2, 1235, (10 << 24) | (1 << 16) | (2 << 8), None)
+            .unwrap();
+
+        let pod = |addr: u32| TsotAddr::FromIpv4(addr);
+        assert!(pm.IsHostPod(host1, pod((10 << 24) | (1 << 16) | (1 << 8) | 5)));
+        assert!(!pm.IsHostPod(host2, pod((10 << 24) | (1 << 16) | (1 << 8) | 5)));
+        assert!(!pm.IsHostPod(host1, pod((10 << 24) | (1 << 16) | (3 << 8) | 5)));
+        assert!(pm.IsPeerHost(host2));
+        assert!(!pm.IsPeerHost((192 << 24) | (168 << 16) | 3));
+
+        // the ipv6 pod addresses
+        let pod6 = |addr: u128| TsotAddr::FromIpv6(addr.to_be_bytes());
+        assert!(pm.IsHostPod(host1, pod6(cidr6 | 0x1_0005)));
+        assert!(!pm.IsHostPod(host1, pod6(cidr6 | 0x2_0005)));
+
+        pm.RemovePeer((10 << 24) | (1 << 16) | (1 << 8)).unwrap();
+        assert!(pm.LookforPeer(pod6(cidr6 | 0x1_0005)).is_err());
+    }
+}
@@ -14,8 +14,9 @@
 
 use core::ops::Deref;
 use nix::sys::socket::ControlMessageOwned;
-use nix::sys::socket::{recvmsg, MsgFlags};
+use nix::sys::socket::{recvmsg, socketpair, AddressFamily, MsgFlags, SockFlag, SockType};
 use nix::sys::uio::IoVec;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::io::IoSlice;
 use std::os::fd::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
@@ -38,7 +39,11 @@ use crate::pod_mgr::NAMESPACE_MGR;
 use super::conn_svc::TcpClientConnection;
 use super::dns_proxy::DnsProxyReq;
 use super::dns_proxy::DNS_PROXY;
+use super::network_policy::{ConnSource, GATEWAY_ADDR, GATEWAY_NAMESPACE, NETWORK_POLICY_MGR};
 use super::pod_broker_mgr::POD_BRORKER_MGRS;
+use super::service_mgr::SERVICE_MGR;
+use super::udp_relay::UdpRelaySocket;
+use super::udp_relay::UDP_LINK_MGR;
 
 #[derive(Debug, Default)]
 pub struct PodIdentity {
@@ -66,6 +71,12 @@ pub struct PodBrokerInner {
 
     // reqId to ConnectReq
     pub connecting: Mutex<HashMap<u32, ConnectReq>>,
+
+    // socketId to the udp sockets of the pod
+    pub udpSockets: Mutex<HashMap<u32, Arc<UdpRelaySocket>>>,
+    // udp port to socketId
+    pub udpPorts: Mutex<HashMap<u16, u32>>,
+    pub nextUdpSocketId: AtomicU32,
 }
 
 #[derive(Debug, Clone)]
@@ -98,6 +109,10 @@ impl PodBroker {
 
             listeningPorts: Mutex::new(HashMap::new()),
             connecting: Mutex::new(HashMap::new()),
+
+            udpSockets: Mutex::new(HashMap::new()),
+            udpPorts: Mutex::new(HashMap::new()),
+            nextUdpSocketId: AtomicU32::new(1),
         };
 
         return Self(Arc::new(inner));
@@ -107,7 +122,7 @@ impl PodBroker {
         match self.podSandbox.lock().unwrap().take() {
             Some(podSandbox) => {
                 let inner = podSandbox.lock().unwrap();
-                POD_BRORKER_MGRS.RemoveBroker(&inner.namespace, inner.ip.0)?;
+                POD_BRORKER_MGRS.RemoveBroker(&inner.namespace, &inner.Addrs())?;
             }
             None => (),
         }
@@ -132,6 +147,7 @@ impl PodBroker {
                 if gatewayRegister {
                     let resp = PodRegisterResp {
                         containerIp: 0,
+                        containerIpv6: [0; 16],
                         errorCode: ErrCode::ECONNREFUSED as _,
                     };
 
@@ -146,10 +162,11 @@ impl PodBroker {
                 *self.podSandbox.lock().unwrap() = Some(podSandbox.clone());
 
                 let inner = podSandbox.lock().unwrap();
-                POD_BRORKER_MGRS.AddPodBroker(&inner.namespace, inner.ip.0, self.clone())?;
+                POD_BRORKER_MGRS.AddPodBroker(&inner.namespace, &inner.Addrs(), self.clone())?;
 
                 let resp = PodRegisterResp {
                     containerIp: inner.ip.0,
+                    containerIpv6: inner.ipv6.AsBytes(),
                     errorCode: ErrCode::None as _,
                 };
 
@@ -170,15 +187,17 @@ impl PodBroker {
                 let gatewayUid = uuid::Uuid::from_bytes(register.gatewayUid).to_string();
                 let podSandbox = PodSandbox::New(
                     &gatewayUid,
-                    "system",
+                    GATEWAY_NAMESPACE,
                     "gateway",
-                    IpAddress::New(&[127, 1, 2, 3]),
+                    IpAddress(GATEWAY_ADDR),
+                    Ipv6Address::default(),
+                    &BTreeMap::new(),
                 );
 
                 *self.podSandbox.lock().unwrap() = Some(podSandbox.clone());
 
                 let inner = podSandbox.lock().unwrap();
-                POD_BRORKER_MGRS.AddPodBroker(&inner.namespace, inner.ip.0, self.clone())?;
+                POD_BRORKER_MGRS.AddPodBroker(&inner.namespace, &inner.Addrs(), self.clone())?;
 
                 let resp = GatewayRegisterResp {
                     errorCode: ErrCode::None as _,
@@ -324,6 +343,15 @@ impl PodBroker {
 
         assert!(size == BUFF_SIZE);
 
+        // the pod owns its side of the udp socket pair and the peer connection now, the node
+        // agent closes its copy so that it sees the pod closing the socket
+        match msg.msg {
+            TsotMsg::CreateUdpSocketResp(_) | TsotMsg::PeerConnectNotify(_) => {
+                nix::unistd::close(socket).ok();
+            }
+            _ => (),
+        }
+
         return Ok(());
     }
 
@@ -388,10 +416,12 @@ impl PodBroker {
         let resp = match NAMESPACE_MGR.GetPodSandbox(&podUid) {
             Err(_e) => PodRegisterResp {
                 containerIp: 0,
+                containerIpv6: [0; 16],
                 errorCode: ErrCode::PodUidDonotExisit as _,
             },
             Ok(podSandbox) => PodRegisterResp {
                 containerIp: podSandbox.lock().unwrap().ip.0,
+                containerIpv6: podSandbox.lock().unwrap().ipv6.AsBytes(),
                 errorCode: ErrCode::PodUidDonotExisit as _,
             },
         };
@@ -451,19 +481,30 @@ impl PodBroker {
         }
     }
 
-    pub fn ProcessConnectReq(&self, req: PodConnectReq, socket: i32) -> Result<()> {
+    // the (namespace, ip, labels) of the pod, the ip is the pod address in the family of the peer
+    pub fn PodInfo(&self, peer: &TsotAddr) -> (String, TsotAddr, BTreeMap<String, String>) {
         let sandbox = self.podSandbox.lock().unwrap();
         let sandbox = sandbox.as_ref().unwrap();
         let sandbox = sandbox.lock().unwrap();
-        let connection = TcpClientConnection {
+        return (
+            sandbox.namespace.clone(),
+            sandbox.AddrFor(peer),
+            sandbox.labels.clone(),
+        );
+    }
+
+    pub fn ProcessConnectReq(&self, req: PodConnectReq, socket: i32) -> Result<()> {
+        let (namespace, srcIp, labels) = self.PodInfo(&req.dstIp);
+
+        let mut connection = TcpClientConnection {
             podBroker: self.clone(),
             isPodConnection: true,
             socket: socket,
             reqId: req.reqId,
-            podNamespace: sandbox.namespace.clone(),
+            podNamespace: namespace,
             dstIp: req.dstIp,
             dstPort: req.dstPort,
-            srcIp: sandbox.ip.0,
+            srcIp: srcIp,
             srcPort: req.srcPort,
         };
 
@@ -479,6 +520,32 @@ impl PodBroker {
             None => (),
         }
 
+        // map the service virtual ip to a backend pod
+        connection.dstIp =
+            match SERVICE_MGR.ResolveDstIp(&connection.podNamespace, req.dstIp, req.dstPort) {
+                Ok(ip) => ip,
+                Err(e) => {
+                    error!("PodBroker::ProcessConnectReq reject connection {:?}", e);
+                    nix::unistd::close(socket).ok();
+                    return self.HandlePodConnectResp(req.reqId, ErrCode::ECONNREFUSED as i32);
+                }
+            };
+
+        match NETWORK_POLICY_MGR.CheckConnect(
+            &connection.podNamespace,
+            &labels,
+            connection.dstIp,
+            connection.dstPort,
+        ) {
+            Ok(()) => (),
+            Err(e) => {
+                error!("PodBroker::ProcessConnectReq reject connection {:?}", e);
+                // close the socket sent by the pod
+                nix::unistd::close(socket).ok();
+                return self.HandlePodConnectResp(req.reqId, ErrCode::ECONNREFUSED as i32);
+            }
+        }
+
         tokio::spawn(async move {
             connection.PodConnectProcess().await;
         });
@@ -500,7 +567,7 @@ impl PodBroker {
         let namespace = String::from_utf8(req.podNamespace[..namespacelen].to_vec())?;
         let sandbox = sandbox.as_ref().unwrap();
         let sandbox = sandbox.lock().unwrap();
-        let connection = TcpClientConnection {
+        let mut connection = TcpClientConnection {
             podBroker: self.clone(),
             isPodConnection: false,
             socket: socket,
@@ -508,7 +575,7 @@ impl PodBroker {
             podNamespace: namespace,
             dstIp: req.dstIp,
             dstPort: req.dstPort,
-            srcIp: sandbox.ip.0,
+            srcIp: sandbox.AddrFor(&req.dstIp),
             srcPort: req.srcPort,
         };
 
@@ -524,6 +591,20 @@ impl PodBroker {
             None => (),
         }
 
+        // map the service virtual ip to a backend pod
+        connection.dstIp =
+            match SERVICE_MGR.ResolveDstIp(&connection.podNamespace, req.dstIp, req.dstPort) {
+                Ok(ip) => ip,
+                Err(e) => {
+                    error!(
+                        "PodBroker::ProcessGatewayConnectReq reject connection {:?}",
+                        e
+                    );
+                    nix::unistd::close(socket).ok();
+                    return self.HandleGatewayConnectResp(req.reqId, ErrCode::ECONNREFUSED as i32);
+                }
+            };
+
         tokio::spawn(async move {
             connection.GatewayConnectProcess().await;
         });
@@ -541,6 +622,128 @@ impl PodBroker {
         return Ok(());
     }
 
+    pub fn ProcessCreateUdpSocketReq(&self, _req: CreateUdpSocketReq) -> Result<()> {
+        let (podSide, brokerSide) = match socketpair(
+            AddressFamily::Unix,
+            SockType::SeqPacket,
+            None,
+            SockFlag::SOCK_CLOEXEC | SockFlag::SOCK_NONBLOCK,
+        ) {
+            Ok(pair) => pair,
+            Err(errno) => return Err(Error::SysError(errno as i32)),
+        };
+
+        let socketId = self.nextUdpSocketId.fetch_add(1, Ordering::SeqCst);
+        let socket = UdpRelaySocket::New(socketId, brokerSide)?;
+        self.udpSockets
+            .lock()
+            .unwrap()
+            .insert(socketId, socket.clone());
+
+        let broker = self.clone();
+        tokio::spawn(async move {
+            broker.ProcessUdpSocket(socket).await;
+        });
+
+        let message = TsotMessage {
+            socket: podSide,
+            msg: TsotMsg::CreateUdpSocketResp(CreateUdpSocketResp { socketId: socketId }),
+        };
+
+        return self.EnqMsg(message);
+    }
+
+    pub fn ProcessUdpBindReq(&self, req: UdpBindReq) -> Result<()> {
+        let socket = match self.udpSockets.lock().unwrap().get(&req.socketId) {
+            None => {
+                error!("ProcessUdpBindReq not existing socket {}", req.socketId);
+                return Ok(());
+            }
+            Some(socket) => socket.clone(),
+        };
+
+        socket.port.store(req.port, Ordering::SeqCst);
+        self.udpPorts.lock().unwrap().insert(req.port, req.socketId);
+        return Ok(());
+    }
+
+    // relay the datagrams sent by the pod until the pod closes the socket
+    pub async fn ProcessUdpSocket(&self, socket: Arc<UdpRelaySocket>) {
+        let mut buf = vec![0; UDP_DATAGRAM_HDR_SIZE + UDP_MAX_PAYLOAD_SIZE];
+        loop {
+            let cnt = match socket.Recv(&mut buf).await {
+                Ok(0) => break,
+                Ok(cnt) => cnt,
+                Err(e) => {
+                    error!(
+                        "PodBroker::ProcessUdpSocket {} fail with error {:?}",
+                        socket.socketId, e
+                    );
+                    break;
+                }
+            };
+
+            if cnt < UDP_DATAGRAM_HDR_SIZE {
+                error!("PodBroker::ProcessUdpSocket invalid datagram size {}", cnt);
+                continue;
+            }
+
+            let hdr = UdpDatagramHdr::FromBytes(&buf[..UDP_DATAGRAM_HDR_SIZE]);
+            match self.SendDatagram(&socket, &hdr, &buf[UDP_DATAGRAM_HDR_SIZE..cnt]) {
+                Ok(()) => (),
+                Err(e) => {
+                    error!("PodBroker::ProcessUdpSocket drop datagram {:?}", e);
+                }
+            }
+        }
+
+        self.udpSockets.lock().unwrap().remove(&socket.socketId);
+        let port = socket.Port();
+        let mut udpPorts = self.udpPorts.lock().unwrap();
+        if udpPorts.get(&port) == Some(&socket.socketId) {
+            udpPorts.remove(&port);
+        }
+    }
+
+    pub fn SendDatagram(
+        &self,
+        socket: &UdpRelaySocket,
+        hdr: &UdpDatagramHdr,
+        payload: &[u8],
+    ) -> Result<()> {
+        // the source port is the local port of the header, it has to be bound by the socket
+        let srcPort = hdr.localPort;
+        if srcPort == 0 || self.udpPorts.lock().unwrap().get(&srcPort) != Some(&socket.socketId) {
+            return Err(Error::CommonError(format!(
+                "PodBroker::SendDatagram udp socket {} doesn't bind the port {}",
+                socket.socketId, srcPort
+            )));
+        }
+
+        let (namespace, srcIp, labels) = self.PodInfo(&hdr.peerIp);
+        let dstPort = hdr.peerPort;
+
+        // map the service virtual ip to a backend pod
+        let dstIp = socket.ResolveDstIp(&namespace, hdr.peerIp, dstPort)?;
+        NETWORK_POLICY_MGR.CheckConnect(&namespace, &labels, dstIp, dstPort)?;
+
+        match POD_BRORKER_MGRS.GetBroker(&namespace, dstIp) {
+            Ok(broker) => {
+                // the source is the pod registered to this broker
+                let source = ConnSource {
+                    namespace: namespace.clone(),
+                    ip: srcIp,
+                    labels: labels,
+                };
+                NETWORK_POLICY_MGR.CheckAccept(&namespace, dstIp, dstPort, &source)?;
+                return broker.HandleDatagram(srcIp, srcPort, dstPort, payload);
+            }
+            Err(_) => {
+                return UDP_LINK_MGR.Send(&namespace, dstIp, dstPort, srcIp, srcPort, payload);
+            }
+        }
+    }
+
     pub fn ProcessMsg(&self, msg: TsotMsg, socket: Option<RawFd>) -> Result<()> {
         match msg {
             TsotMsg::PodRegisterReq(m) => {
@@ -571,6 +774,12 @@ impl PodBroker {
             TsotMsg::DnsReq(m) => {
                 self.ProcessDnsReq(m)?;
             }
+            TsotMsg::CreateUdpSocketReq(m) => {
+                self.ProcessCreateUdpSocketReq(m)?;
+            }
+            TsotMsg::UdpBindReq(m) => {
+                self.ProcessUdpBindReq(m)?;
+            }
 
             m => {
                 error!("ProcessMsg get unimplement msg {:?}", &m);
@@ -581,18 +790,31 @@ impl PodBroker {
         return Ok(());
     }
 
-    pub fn HandlePodRegisterResp(&self, containerIp: u32, errorCode: u32) -> Result<()> {
+    pub fn HandlePodRegisterResp(
+        &self,
+        containerIp: u32,
+        containerIpv6: Ipv6Address,
+        errorCode: u32,
+    ) -> Result<()> {
         let msg = PodRegisterResp {
             containerIp: containerIp,
+            containerIpv6: containerIpv6.AsBytes(),
             errorCode: errorCode,
         };
 
         return self.EnqMsg(TsotMsg::PodRegisterResp(msg).into());
     }
 
+    pub fn IsListening(&self, port: u16) -> bool {
+        match self.listeningPorts.lock().unwrap().get(&port) {
+            Some(count) => return *count > 0,
+            None => return false,
+        }
+    }
+
     pub fn HandleNewPeerConnection(
         &self,
-        peerIp: u32,
+        peerIp: TsotAddr,
         peerPort: u16,
         dstPort: u16,
         socket: i32,
@@ -628,6 +850,42 @@ impl PodBroker {
         return self.EnqMsg(message);
     }
 
+    pub fn HandleDatagram(
+        &self,
+        peerIp: TsotAddr,
+        peerPort: u16,
+        dstPort: u16,
+        payload: &[u8],
+    ) -> Result<()> {
+        let socket = {
+            let socketId = match self.udpPorts.lock().unwrap().get(&dstPort) {
+                None => {
+                    return Err(Error::NotExist(format!(
+                        "target container doesn't bind the udp port {dstPort}"
+                    )));
+                }
+                Some(socketId) => *socketId,
+            };
+
+            match self.udpSockets.lock().unwrap().get(&socketId) {
+                None => {
+                    return Err(Error::NotExist(format!(
+                        "target container udp socket {socketId} doesn't exist"
+                    )));
+                }
+                Some(socket) => socket.clone(),
+            }
+        };
+
+        let hdr = UdpDatagramHdr {
+            peerIp: peerIp,
+            peerPort: peerPort,
+            localPort: dstPort,
+        };
+
+        return socket.Send(&hdr, payload);
+    }
+
     pub fn HandlePodConnectResp(&self, reqId: u32, errorCode: i32) -> Result<()> {
         match self.connecting.lock().unwrap().remove(&reqId) {
             None => {
@@ -19,6 +19,7 @@ use std::{
 };
 
 use qshare::common::*;
+use qshare::tsot_msg::TsotAddr;
 
 use super::pod_broker::PodBroker;
 
@@ -30,8 +31,8 @@ lazy_static::lazy_static! {
 
 #[derive(Debug, Default)]
 pub struct PodBrokerMgrInner {
-    // addr to PodBroker
-    pub brokers: HashMap<u32, PodBroker>,
+    // addr to PodBroker, the dual stack pod has one entry for each of its addresses
+    pub brokers: HashMap<TsotAddr, PodBroker>,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -46,24 +47,28 @@ impl Deref for PodBrokerMgr {
 }
 
 impl PodBrokerMgr {
-    pub fn AddPodBroker(&self, addr: u32, podBroker: PodBroker) -> Result<()> {
+    pub fn AddPodBroker(&self, addrs: &[TsotAddr], podBroker: PodBroker) -> Result<()> {
         let mut inner = self.write().unwrap();
-        if inner.brokers.contains_key(&addr) {
-            return Err(Error::Exist(format!(
-                "PodBrokerMgr::AddPodBroker addr {:x?} existing",
-                addr
-            )));
+        for addr in addrs {
+            if inner.brokers.contains_key(addr) {
+                return Err(Error::Exist(format!(
+                    "PodBrokerMgr::AddPodBroker addr {} existing",
+                    addr
+                )));
+            }
         }
 
-        inner.brokers.insert(addr, podBroker);
+        for addr in addrs {
+            inner.brokers.insert(*addr, podBroker.clone());
+        }
         return Ok(());
     }
 
-    fn GetBroker(&self, addr: u32) -> Result<PodBroker> {
+    fn GetBroker(&self, addr: TsotAddr) -> Result<PodBroker> {
         match self.read().unwrap().brokers.get(&addr) {
             None => {
                 return Err(Error::NotExist(format!(
-                    "PodBrokerMgr::GetBroker fail with addr {:x?}",
+                    "PodBrokerMgr::GetBroker fail with addr {}",
                     addr
                 )))
             }
@@ -71,16 +76,20 @@ impl PodBrokerMgr {
         }
     }
 
-    pub fn RemoveBroker(&self, addr: u32) -> Result<()> {
-        match self.write().unwrap().brokers.remove(&addr) {
-            None => {
-                return Err(Error::NotExist(format!(
-                    "PodBrokerMgr::RemoveBroker broker with addr {:x} doesn't exist",
-                    addr
-                )));
+    pub fn RemoveBroker(&self, addrs: &[TsotAddr]) -> Result<()> {
+        let mut inner = self.write().unwrap();
+        for addr in addrs {
+            match inner.brokers.remove(addr) {
+                None => {
+                    return Err(Error::NotExist(format!(
+                        "PodBrokerMgr::RemoveBroker broker with addr {} doesn't exist",
+                        addr
+                    )));
+                }
+                Some(_) => (),
             }
-            Some(_) => return Ok(()),
         }
+        return Ok(());
     }
 }
 
@@ -102,9 +111,14 @@ impl Deref for PodBrokerMgrs {
 }
 
 impl PodBrokerMgrs {
-    pub fn AddPodBroker(&self, namespace: &str, addr: u32, broker: PodBroker) -> Result<()> {
+    pub fn AddPodBroker(
+        &self,
+        namespace: &str,
+        addrs: &[TsotAddr],
+        broker: PodBroker,
+    ) -> Result<()> {
         let mgr = self.GetOrCreateBrokerMgr(namespace)?;
-        mgr.AddPodBroker(addr, broker)?;
+        mgr.AddPodBroker(addrs, broker)?;
         return Ok(());
     }
 
@@ -122,12 +136,24 @@ impl PodBrokerMgrs {
         return Ok(mgr);
     }
 
+    // whether the target pod listens the port, checked before the peer connection is accepted
+    pub fn CheckPeerConnect(&self, namespace: &str, dstIp: TsotAddr, dstPort: u16) -> Result<()> {
+        let broker = self.GetBroker(namespace, dstIp)?;
+        if !broker.IsListening(dstPort) {
+            return Err(Error::NotExist(format!(
+                "target container doesn't listening the port {dstPort}"
+            )));
+        }
+
+        return Ok(());
+    }
+
     pub fn HandlePeerConnect(
         &self,
         namespace: &str,
-        dstIp: u32,
+        dstIp: TsotAddr,
         dstPort: u16,
-        peerIp: u32,
+        peerIp: TsotAddr,
         peerPort: u16,
         socket: i32,
     ) -> Result<()> {
@@ -137,6 +163,21 @@ impl PodBrokerMgrs {
         return Ok(());
     }
 
+    pub fn HandleDatagram(
+        &self,
+        namespace: &str,
+        dstIp: TsotAddr,
+        dstPort: u16,
+        peerIp: TsotAddr,
+        peerPort: u16,
+        payload: &[u8],
+    ) -> Result<()> {
+        let broker = self.GetBroker(namespace, dstIp)?;
+        broker.HandleDatagram(peerIp, peerPort, dstPort, payload)?;
+
+        return Ok(());
+    }
+
     pub fn GetBrokerMgr(&self, namespace: &str) -> Result<PodBrokerMgr> {
         match self.read().unwrap().mgrs.get(namespace) {
             None => {
@@ -149,14 +190,14 @@ impl PodBrokerMgrs {
         }
     }
 
-    pub fn GetBroker(&self, namespace: &str, addr: u32) -> Result<PodBroker> {
+    pub fn GetBroker(&self, namespace: &str, addr: TsotAddr) -> Result<PodBroker> {
         let mgr = self.GetBrokerMgr(namespace)?;
         let broker = mgr.GetBroker(addr)?;
         return Ok(broker);
     }
 
-    pub fn RemoveBroker(&self, namespace: &str, addr: u32) -> Result<()> {
+    pub fn RemoveBroker(&self, namespace: &str, addrs: &[TsotAddr]) -> Result<()> {
         let mgr = self.GetBrokerMgr(namespace)?;
-        return mgr.RemoveBroker(addr);
+        return mgr.RemoveBroker(addrs);
     }
 }
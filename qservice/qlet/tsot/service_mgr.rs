@@ -0,0 +1,577 @@
+// Copyright (c) 2023 Quark Container Authors / 2018 The gVisor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use core::ops::Deref;
+use std::collections::HashMap;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::RwLock;
+
+use qshare::common::*;
+use qshare::consts::ConditionTrue;
+use qshare::metastore::data_obj::*;
+use qshare::metastore::informer::EventHandler;
+use qshare::metastore::store::ThreadSafeStore;
+use qshare::node::LabelsMatch;
+use qshare::node::LoadBalancePolicy;
+use qshare::node::PodDef;
+use qshare::node::ServiceDef;
+use qshare::node::SERVICE_VIP_ADDR;
+use qshare::node::SERVICE_VIP_MASKBITS;
+use qshare::tsot_msg::TsotAddr;
+
+use crate::pod_mgr::qpod::PodReady;
+use crate::pod_mgr::qpod::PodRunning;
+
+lazy_static::lazy_static! {
+    pub static ref SERVICE_MGR: ServiceMgr = {
+        ServiceMgr::default()
+    };
+}
+
+// the endpoints of a service, they are updated by the pod and service events so that the
+// connect path doesn't scan the pods
+#[derive(Debug, Default)]
+pub struct ServiceBackend {
+    pub ip: u32,
+    // the connections assigned to the backend since it joins the service
+    pub conns: AtomicU64,
+}
+
+#[derive(Debug, Default)]
+pub struct ServiceEndpoints {
+    // the ready pods selected by the service, ordered by ip so that the round robin
+    // order doesn't change when other backends come and go
+    pub backends: Vec<ServiceBackend>,
+    // the next endpoint index for round robin
+    pub next: AtomicUsize,
+}
+
+impl ServiceEndpoints {
+    pub fn Add(&mut self, ip: u32) {
+        if let Err(idx) = self.backends.binary_search_by_key(&ip, |b| b.ip) {
+            let backend = ServiceBackend {
+                ip: ip,
+                conns: AtomicU64::new(0),
+            };
+            self.backends.insert(idx, backend);
+        }
+    }
+
+    pub fn Remove(&mut self, ip: u32) {
+        if let Ok(idx) = self.backends.binary_search_by_key(&ip, |b| b.ip) {
+            self.backends.remove(idx);
+        }
+    }
+
+    pub fn Contains(&self, ip: u32) -> bool {
+        return self.backends.binary_search_by_key(&ip, |b| b.ip).is_ok();
+    }
+
+    pub fn Ips(&self) -> Vec<u32> {
+        return self.backends.iter().map(|b| b.ip).collect();
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ServiceMgrInner {
+    // service key to ServiceDef
+    pub services: HashMap<String, ServiceDef>,
+    // service key to the virtual ip assigned to it
+    pub serviceVips: HashMap<String, u32>,
+    // (namespace, vip) to service key
+    pub vips: HashMap<(String, u32), String>,
+    // service key to its endpoints
+    pub endpoints: HashMap<String, ServiceEndpoints>,
+
+    // pod id to PodDef
+    pub pods: HashMap<String, PodDef>,
+}
+
+impl ServiceMgrInner {
+    // whether the pod is a healthy backend of the service, i.e. it is running and passes
+    // its readiness probes
+    pub fn IsEndpoint(service: &ServiceDef, pod: &PodDef) -> bool {
+        return pod.PodNamespace() == service.PodNamespace()
+            && pod.ipAddr != 0
+            && pod.status.phase == PodRunning
+            && Self::IsReady(pod)
+            && pod.deletion_timestamp.is_none()
+            && LabelsMatch(&service.selector, &pod.labels);
+    }
+
+    pub fn IsReady(pod: &PodDef) -> bool {
+        for condition in &pod.status.conditions {
+            if condition.type_ == PodReady {
+                return condition.status == ConditionTrue;
+            }
+        }
+
+        return false;
+    }
+
+    // the virtual ip of the new service. An explicit virtual ip which is taken by another
+    // service is rejected, a derived one is moved to the next free virtual ip
+    pub fn AssignVip(&self, service: &ServiceDef) -> Result<u32> {
+        let key = service.Key();
+        let namespace = service.PodNamespace();
+        let free = |vip: u32| match self.vips.get(&(namespace.clone(), vip)) {
+            None => true,
+            Some(other) => other == &key,
+        };
+
+        if service.vip != 0 {
+            if !free(service.vip) {
+                return Err(Error::CommonError(format!(
+                    "ServiceMgr::AssignVip service {} vip {:x} is taken by service {}",
+                    &key,
+                    service.vip,
+                    self.vips.get(&(namespace, service.vip)).unwrap()
+                )));
+            }
+
+            return Ok(service.vip);
+        }
+
+        // a modified service keeps its virtual ip
+        if let Some(vip) = self.serviceVips.get(&key) {
+            return Ok(*vip);
+        }
+
+        let hostMask: u32 = (1 << (32 - SERVICE_VIP_MASKBITS)) - 1;
+        let mut vip = service.VirtualIp();
+        for _ in 0..hostMask {
+            if free(vip) {
+                return Ok(vip);
+            }
+
+            // skip the network and broadcast address
+            let mut host = ((vip & hostMask) + 1) & hostMask;
+            if host == 0 || host == hostMask {
+                host = 1;
+            }
+            vip = SERVICE_VIP_ADDR | host;
+        }
+
+        return Err(Error::CommonError(format!(
+            "ServiceMgr::AssignVip no free vip for service {}",
+            &key
+        )));
+    }
+
+    pub fn RemoveService(&mut self, key: &str) {
+        let service = match self.services.remove(key) {
+            None => return,
+            Some(service) => service,
+        };
+
+        self.endpoints.remove(key);
+        if let Some(vip) = self.serviceVips.remove(key) {
+            self.vips.remove(&(service.PodNamespace(), vip));
+        }
+    }
+
+    // move the pod between the service endpoints after the pod changes, the backend which
+    // stays keeps its connection count
+    pub fn UpdatePodEndpoints(&mut self, old: Option<&PodDef>, pod: Option<&PodDef>) {
+        for (key, service) in &self.services {
+            let oldIp = old
+                .filter(|old| Self::IsEndpoint(service, old))
+                .map(|old| old.ipAddr);
+            let newIp = pod
+                .filter(|pod| Self::IsEndpoint(service, pod))
+                .map(|pod| pod.ipAddr);
+            if oldIp == newIp {
+                continue;
+            }
+
+            let endpoints = self.endpoints.entry(key.clone()).or_default();
+            if let Some(ip) = oldIp {
+                endpoints.Remove(ip);
+            }
+            if let Some(ip) = newIp {
+                endpoints.Add(ip);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ServiceMgr(Arc<RwLock<ServiceMgrInner>>);
+
+impl Deref for ServiceMgr {
+    type Target = Arc<RwLock<ServiceMgrInner>>;
+
+    fn deref(&self) -> &Arc<RwLock<ServiceMgrInner>> {
+        &self.0
+    }
+}
+
+impl ServiceMgr {
+    pub fn AddService(&self, service: ServiceDef) -> Result<()> {
+        let mut inner = self.write().unwrap();
+        let key = service.Key();
+        let vip = inner.AssignVip(&service)?;
+
+        inner.RemoveService(&key);
+        let mut endpoints = ServiceEndpoints::default();
+        for (_, pod) in &inner.pods {
+            if ServiceMgrInner::IsEndpoint(&service, pod) {
+                endpoints.Add(pod.ipAddr);
+            }
+        }
+
+        inner.endpoints.insert(key.clone(), endpoints);
+        inner
+            .vips
+            .insert((service.PodNamespace(), vip), key.clone());
+        inner.serviceVips.insert(key.clone(), vip);
+        inner.services.insert(key, service);
+        return Ok(());
+    }
+
+    pub fn RemoveService(&self, service: &ServiceDef) {
+        self.write().unwrap().RemoveService(&service.Key());
+    }
+
+    pub fn AddPod(&self, pod: PodDef) {
+        let mut inner = self.write().unwrap();
+        let old = inner.pods.remove(&pod.PodId());
+        inner.UpdatePodEndpoints(old.as_ref(), Some(&pod));
+        inner.pods.insert(pod.PodId(), pod);
+    }
+
+    pub fn RemovePod(&self, pod: &PodDef) {
+        let mut inner = self.write().unwrap();
+        if let Some(old) = inner.pods.remove(&pod.PodId()) {
+            inner.UpdatePodEndpoints(Some(&old), None);
+        }
+    }
+
+    // the virtual ip of the service, used by the dns proxy
+    pub fn Lookup(&self, tenant: &str, namespace: &str, name: &str) -> Option<u32> {
+        let key = format!("{}/{}/{}", tenant, namespace, name);
+        return self.read().unwrap().serviceVips.get(&key).cloned();
+    }
+
+    pub fn Endpoints(&self, key: &str) -> Vec<u32> {
+        match self.read().unwrap().endpoints.get(key) {
+            None => return Vec::new(),
+            Some(endpoints) => return endpoints.Ips(),
+        }
+    }
+
+    // the target pod ip of a connection, a service virtual ip is mapped to one of its backends.
+    // the services only have ipv4 virtual ips
+    pub fn ResolveDstIp(&self, namespace: &str, dstIp: TsotAddr, dstPort: u16) -> Result<TsotAddr> {
+        let vip = match dstIp.Ipv4() {
+            Some(ip) if ServiceDef::IsVirtualIp(ip) => ip,
+            _ => return Ok(dstIp),
+        };
+
+        let backend = self.PickBackend(namespace, vip, dstPort)?;
+        return Ok(TsotAddr::FromIpv4(backend));
+    }
+
+    // whether the ip is still a running backend of the service with the virtual ip
+    pub fn IsBackend(&self, namespace: &str, vip: u32, ip: u32) -> bool {
+        let inner = self.read().unwrap();
+        let key = match inner.vips.get(&(namespace.to_owned(), vip)) {
+            None => return false,
+            Some(key) => key,
+        };
+
+        match inner.endpoints.get(key) {
+            None => return false,
+            Some(endpoints) => return endpoints.Contains(ip),
+        }
+    }
+
+    pub fn PickBackend(&self, namespace: &str, vip: u32, port: u16) -> Result<u32> {
+        let inner = self.read().unwrap();
+        let key = match inner.vips.get(&(namespace.to_owned(), vip)) {
+            None => {
+                return Err(Error::NotExist(format!(
+                    "ServiceMgr::PickBackend no service with vip {:x} in namespace {}",
+                    vip, namespace
+                )))
+            }
+            Some(key) => key,
+        };
+
+        let service = inner.services.get(key).unwrap();
+        if !service.ExposePort(port) {
+            return Err(Error::NotExist(format!(
+                "ServiceMgr::PickBackend service {} doesn't expose port {}",
+                key, port
+            )));
+        }
+
+        let endpoints = match inner.endpoints.get(key) {
+            Some(endpoints) if endpoints.backends.len() > 0 => endpoints,
+            _ => {
+                return Err(Error::NotExist(format!(
+                    "ServiceMgr::PickBackend service {} has no running backend",
+                    key
+                )))
+            }
+        };
+
+        let backends = &endpoints.backends;
+        let backend = match service.lbPolicy {
+            LoadBalancePolicy::RoundRobin => {
+                let next = endpoints.next.fetch_add(1, Ordering::Relaxed);
+                &backends[next % backends.len()]
+            }
+            LoadBalancePolicy::LeastConnections => backends
+                .iter()
+                .min_by_key(|b| b.conns.load(Ordering::Relaxed))
+                .unwrap(),
+        };
+
+        backend.conns.fetch_add(1, Ordering::Relaxed);
+        return Ok(backend.ip);
+    }
+
+    pub fn ProcessDeltaEvent(&self, event: &DeltaEvent) -> Result<()> {
+        let obj = &event.obj;
+        match &event.type_ {
+            EventType::Added | EventType::Modified => {
+                if obj.kind == ServiceDef::KEY {
+                    self.AddService(ServiceDef::FromDataObject(obj.clone())?)?;
+                } else {
+                    self.AddPod(PodDef::FromDataObject(obj.clone())?);
+                }
+            }
+            EventType::Deleted => {
+                let obj = event.oldObj.as_ref().unwrap_or(obj);
+                if obj.kind == ServiceDef::KEY {
+                    self.RemoveService(&ServiceDef::FromDataObject(obj.clone())?);
+                } else {
+                    self.RemovePod(&PodDef::FromDataObject(obj.clone())?);
+                }
+            }
+            _ => (),
+        }
+
+        return Ok(());
+    }
+}
+
+impl EventHandler for ServiceMgr {
+    fn handle(&self, _store: &ThreadSafeStore, event: &DeltaEvent) {
+        match self.ProcessDeltaEvent(event) {
+            Ok(()) => (),
+            Err(e) => {
+                error!(
+                    "ServiceMgr::handle {} fail with error {:?}",
+                    event.obj.Key(),
+                    e
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::time::SystemTime;
+
+    use qshare::node::PodCondition;
+
+    fn Service(name: &str, vip: u32) -> ServiceDef {
+        let mut selector = BTreeMap::new();
+        selector.insert("app".to_owned(), name.to_owned());
+        return ServiceDef {
+            tenant: "t1".to_owned(),
+            namespace: "ns1".to_owned(),
+            name: name.to_owned(),
+            selector: selector,
+            vip: vip,
+            ..Default::default()
+        };
+    }
+
+    fn Pod(name: &str, app: &str, ip: u32, phase: &str) -> PodDef {
+        let mut pod = PodDef {
+            tenant: "t1".to_owned(),
+            namespace: "ns1".to_owned(),
+            name: name.to_owned(),
+            ipAddr: ip,
+            ..Default::default()
+        };
+        pod.labels.insert("app".to_owned(), app.to_owned());
+        pod.status.phase = phase.to_owned();
+        SetReady(&mut pod, ConditionTrue);
+        return pod;
+    }
+
+    fn SetReady(pod: &mut PodDef, status: &str) {
+        pod.status.conditions = vec![PodCondition {
+            last_probe_time: SystemTime::UNIX_EPOCH,
+            last_transition_time: SystemTime::UNIX_EPOCH,
+            message: String::new(),
+            reason: String::new(),
+            status: status.to_owned(),
+            type_: PodReady.to_owned(),
+        }];
+    }
+
+    #[test]
+    fn TestExplicitVipCollision() {
+        let mgr = ServiceMgr::default();
+        let vip = SERVICE_VIP_ADDR | 10;
+        mgr.AddService(Service("a", vip)).unwrap();
+        assert!(mgr.AddService(Service("b", vip)).is_err());
+        assert_eq!(mgr.Lookup("t1", "ns1", "a"), Some(vip));
+        assert_eq!(mgr.Lookup("t1", "ns1", "b"), None);
+
+        // the modified service keeps its vip
+        mgr.AddService(Service("a", vip)).unwrap();
+        assert_eq!(mgr.Lookup("t1", "ns1", "a"), Some(vip));
+    }
+
+    #[test]
+    fn TestDerivedVipCollision() {
+        let mgr = ServiceMgr::default();
+        let derived = Service("b", 0).VirtualIp();
+        mgr.AddService(Service("a", derived)).unwrap();
+        mgr.AddService(Service("b", 0)).unwrap();
+
+        let vip = mgr.Lookup("t1", "ns1", "b").unwrap();
+        assert_ne!(vip, derived);
+        assert!(ServiceDef::IsVirtualIp(vip));
+
+        // the vip doesn't move back after the collision is gone
+        mgr.RemoveService(&Service("a", derived));
+        mgr.AddService(Service("b", 0)).unwrap();
+        assert_eq!(mgr.Lookup("t1", "ns1", "b"), Some(vip));
+    }
+
+    #[test]
+    fn TestEndpoints() {
+        let mgr = ServiceMgr::default();
+        mgr.AddPod(Pod("p1", "a", 1, PodRunning));
+        mgr.AddPod(Pod("p2", "a", 2, "Pending"));
+        mgr.AddPod(Pod("p3", "b", 3, PodRunning));
+        mgr.AddService(Service("a", 0)).unwrap();
+        assert_eq!(mgr.Endpoints("t1/ns1/a"), vec![1]);
+
+        mgr.AddPod(Pod("p2", "a", 2, PodRunning));
+        assert_eq!(mgr.Endpoints("t1/ns1/a"), vec![1, 2]);
+
+        // the pod labels change
+        mgr.AddPod(Pod("p1", "b", 1, PodRunning));
+        assert_eq!(mgr.Endpoints("t1/ns1/a"), vec![2]);
+
+        mgr.RemovePod(&Pod("p2", "a", 2, PodRunning));
+        assert_eq!(mgr.Endpoints("t1/ns1/a"), Vec::<u32>::new());
+
+        // the running pod which fails its readiness probe is not a backend
+        let mut pod = Pod("p4", "a", 4, PodRunning);
+        SetReady(&mut pod, "False");
+        mgr.AddPod(pod.clone());
+        assert_eq!(mgr.Endpoints("t1/ns1/a"), Vec::<u32>::new());
+        pod.status.conditions.clear();
+        mgr.AddPod(pod);
+        assert_eq!(mgr.Endpoints("t1/ns1/a"), Vec::<u32>::new());
+        mgr.AddPod(Pod("p4", "a", 4, PodRunning));
+        assert_eq!(mgr.Endpoints("t1/ns1/a"), vec![4]);
+    }
+
+    #[test]
+    fn TestPickBackend() {
+        let mgr = ServiceMgr::default();
+        mgr.AddService(Service("a", 0)).unwrap();
+        let vip = mgr.Lookup("t1", "ns1", "a").unwrap();
+        assert!(mgr.PickBackend("t1/ns1", vip, 80).is_err());
+
+        mgr.AddPod(Pod("p1", "a", 1, PodRunning));
+        mgr.AddPod(Pod("p2", "a", 2, PodRunning));
+        let mut picked = Vec::new();
+        for _ in 0..4 {
+            picked.push(mgr.PickBackend("t1/ns1", vip, 80).unwrap());
+        }
+        assert_eq!(picked, vec![1, 2, 1, 2]);
+
+        let pod = TsotAddr::FromIpv4(5);
+        assert_eq!(mgr.ResolveDstIp("t1/ns1", pod, 80).unwrap(), pod);
+        assert_eq!(
+            mgr.ResolveDstIp("t1/ns1", TsotAddr::FromIpv4(vip), 80).unwrap(),
+            TsotAddr::FromIpv4(1)
+        );
+        assert!(mgr.PickBackend("t1/ns2", vip, 80).is_err());
+
+        assert!(mgr.IsBackend("t1/ns1", vip, 2));
+        assert!(!mgr.IsBackend("t1/ns1", vip, 5));
+        assert!(!mgr.IsBackend("t1/ns2", vip, 2));
+        mgr.RemovePod(&Pod("p2", "a", 2, PodRunning));
+        assert!(!mgr.IsBackend("t1/ns1", vip, 2));
+    }
+    #[test]
+    fn TestRoundRobinOrder() {
+        let mgr = ServiceMgr::default();
+        mgr.AddService(Service("a", 0)).unwrap();
+        let vip = mgr.Lookup("t1", "ns1", "a").unwrap();
+        mgr.AddPod(Pod("p3", "a", 3, PodRunning));
+        mgr.AddPod(Pod("p1", "a", 1, PodRunning));
+        assert_eq!(mgr.Endpoints("t1/ns1/a"), vec![1, 3]);
+
+        // the new backend takes its place in the ip order
+        assert_eq!(mgr.PickBackend("t1/ns1", vip, 80).unwrap(), 1);
+        mgr.AddPod(Pod("p2", "a", 2, PodRunning));
+        let mut picked = Vec::new();
+        for _ in 0..3 {
+            picked.push(mgr.PickBackend("t1/ns1", vip, 80).unwrap());
+        }
+        assert_eq!(picked, vec![2, 3, 1]);
+    }
+
+    #[test]
+    fn TestLeastConnections() {
+        let mgr = ServiceMgr::default();
+        let mut service = Service("a", 0);
+        service.lbPolicy = LoadBalancePolicy::LeastConnections;
+        mgr.AddService(service).unwrap();
+        let vip = mgr.Lookup("t1", "ns1", "a").unwrap();
+
+        mgr.AddPod(Pod("p1", "a", 1, PodRunning));
+        for _ in 0..3 {
+            assert_eq!(mgr.PickBackend("t1/ns1", vip, 80).unwrap(), 1);
+        }
+
+        // the new backend takes the connections until it catches up
+        mgr.AddPod(Pod("p2", "a", 2, PodRunning));
+        let mut picked = Vec::new();
+        for _ in 0..5 {
+            picked.push(mgr.PickBackend("t1/ns1", vip, 80).unwrap());
+        }
+        assert_eq!(picked, vec![2, 2, 2, 1, 2]);
+
+        // the pod update which keeps it a backend keeps its connection count
+        let mut pod = Pod("p2", "a", 2, PodRunning);
+        pod.annotations.insert("k".to_owned(), "v".to_owned());
+        mgr.AddPod(pod);
+        assert_eq!(mgr.PickBackend("t1/ns1", vip, 80).unwrap(), 1);
+
+        // the backend which comes back starts from 0
+        mgr.RemovePod(&Pod("p1", "a", 1, PodRunning));
+        mgr.AddPod(Pod("p1", "a", 1, PodRunning));
+        assert_eq!(mgr.PickBackend("t1/ns1", vip, 80).unwrap(), 1);
+    }
+}
@@ -0,0 +1,479 @@
+// Copyright (c) 2023 Quark Container Authors / 2018 The gVisor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+use std::fs;
+use std::net::IpAddr;
+use std::net::Ipv4Addr;
+use std::net::SocketAddr;
+use std::os::fd::IntoRawFd;
+use std::os::fd::RawFd;
+use std::os::unix::net::UnixStream as StdUnixStream;
+use std::path::Path;
+use std::pin::Pin;
+use std::str::FromStr;
+
+use openssl::pkey::PKey;
+use openssl::pkey::Private;
+use openssl::ssl::Ssl;
+use openssl::ssl::SslAcceptor;
+use openssl::ssl::SslConnector;
+use openssl::ssl::SslMethod;
+use openssl::ssl::SslVerifyMode;
+use openssl::ssl::SslVersion;
+use openssl::x509::X509Ref;
+use openssl::x509::X509;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncWrite;
+use tokio::net::TcpListener;
+use tokio::net::TcpSocket;
+use tokio::net::TcpStream;
+use tokio::net::UnixStream;
+use tokio_openssl::SslStream;
+
+use qshare::common::*;
+
+use super::peer_mgr::PEER_MGR;
+use crate::QLET_CONFIG;
+
+lazy_static::lazy_static! {
+    pub static ref TSOT_TLS: TsotTls = {
+        TsotTls::New().expect("TsotTls: fail to load the node certificate")
+    };
+}
+
+// the tsot ca certificate in the ca directory, the node certificate is issued by it
+pub const TSOT_CA_CERT: &str = "ca.crt";
+
+// the first byte of a tls connection is a handshake record, the first byte of a
+// plain connection is the TsotConnReq namespace which is printable or zero
+pub const TLS_HANDSHAKE_RECORD: u8 = 0x16;
+
+pub fn SslErr<E: std::fmt::Debug>(e: E) -> Error {
+    return Error::CommonError(format!("TsotTls ssl error {:?}", e));
+}
+
+// the mutual tls of the node to node connections
+pub struct TsotTls {
+    // none when the node to node connections are not encrypted
+    pub acceptor: Option<SslAcceptor>,
+    pub connector: Option<SslConnector>,
+    // the cri streaming clients have no node certificate, the streaming server only
+    // presents the node certificate
+    pub streamingAcceptor: Option<SslAcceptor>,
+
+    // the namespaces whose connections must be encrypted
+    pub namespaces: HashSet<String>,
+}
+
+impl TsotTls {
+    pub fn New() -> Result<Self> {
+        let namespaces: HashSet<String> = QLET_CONFIG.tlsNamespaces.iter().cloned().collect();
+        if QLET_CONFIG.tlsCaDir.len() == 0 {
+            if namespaces.len() > 0 {
+                return Err(Error::CommonError(format!(
+                    "TsotTls: namespaces {:?} require encryption without tls ca directory",
+                    &namespaces
+                )));
+            }
+
+            return Ok(Self {
+                acceptor: None,
+                connector: None,
+                streamingAcceptor: None,
+                namespaces: namespaces,
+            });
+        }
+
+        let (caCert, cert, key) = Self::LoadNodeCert(&QLET_CONFIG.tlsCaDir)?;
+        return Self::NewFromCert(caCert, &cert, &key, namespaces);
+    }
+
+    pub fn NewFromCert(
+        caCert: X509,
+        cert: &X509,
+        key: &PKey<Private>,
+        namespaces: HashSet<String>,
+    ) -> Result<Self> {
+        let mut acceptor =
+            SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).map_err(SslErr)?;
+        acceptor.set_certificate(cert).map_err(SslErr)?;
+        acceptor.set_private_key(key).map_err(SslErr)?;
+        acceptor.check_private_key().map_err(SslErr)?;
+        acceptor
+            .cert_store_mut()
+            .add_cert(caCert.clone())
+            .map_err(SslErr)?;
+        acceptor.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
+
+        let mut streamingAcceptor =
+            SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).map_err(SslErr)?;
+        streamingAcceptor.set_certificate(cert).map_err(SslErr)?;
+        streamingAcceptor.set_private_key(key).map_err(SslErr)?;
+        streamingAcceptor.check_private_key().map_err(SslErr)?;
+
+        let mut connector = SslConnector::builder(SslMethod::tls()).map_err(SslErr)?;
+        connector
+            .set_min_proto_version(Some(SslVersion::TLS1_2))
+            .map_err(SslErr)?;
+        connector.set_certificate(cert).map_err(SslErr)?;
+        connector.set_private_key(key).map_err(SslErr)?;
+        connector.check_private_key().map_err(SslErr)?;
+        connector
+            .cert_store_mut()
+            .add_cert(caCert)
+            .map_err(SslErr)?;
+        connector.set_verify(SslVerifyMode::PEER);
+
+        return Ok(Self {
+            acceptor: Some(acceptor.build()),
+            connector: Some(connector.build()),
+            streamingAcceptor: Some(streamingAcceptor.build()),
+            namespaces: namespaces,
+        });
+    }
+
+    pub fn Enabled(&self) -> bool {
+        return self.acceptor.is_some();
+    }
+
+    pub fn RequireEncryption(&self, namespace: &str) -> bool {
+        return self.namespaces.contains(namespace);
+    }
+
+    pub fn NodeIp() -> Ipv4Addr {
+        return ipnetwork::Ipv4Network::from_str(&QLET_CONFIG.nodeIp)
+            .unwrap()
+            .ip();
+    }
+
+    // the node certificate and key are issued by the tsot ca out of the node and put in the ca
+    // directory as <nodeName>.crt and <nodeName>.key, the ca key is not on the node. The subject
+    // alternative name of the certificate is the node ip, e.g.
+    //   openssl x509 -req -CA ca.crt -CAkey ca.key -in node1.csr -out node1.crt \
+    //       -extfile <(printf "subjectAltName=IP:10.0.0.1\nextendedKeyUsage=serverAuth,clientAuth")
+    // return: (ca certificate, node certificate, node key)
+    pub fn LoadNodeCert(caDir: &str) -> Result<(X509, X509, PKey<Private>)> {
+        let caCert =
+            X509::from_pem(&fs::read(Path::new(caDir).join(TSOT_CA_CERT))?).map_err(SslErr)?;
+
+        let certPath = Path::new(caDir).join(format!("{}.crt", &QLET_CONFIG.nodeName));
+        let keyPath = Path::new(caDir).join(format!("{}.key", &QLET_CONFIG.nodeName));
+        let cert = X509::from_pem(&fs::read(&certPath)?).map_err(SslErr)?;
+        let key = PKey::private_key_from_pem(&fs::read(&keyPath)?).map_err(SslErr)?;
+
+        let caKey = caCert.public_key().map_err(SslErr)?;
+        if !cert.verify(&caKey).map_err(SslErr)? {
+            return Err(Error::CommonError(format!(
+                "TsotTls::LoadNodeCert {:?} is not issued by the tsot ca",
+                &certPath
+            )));
+        }
+
+        let nodeIp = Self::NodeIp();
+        if !Self::IssuedFor(&cert, nodeIp) {
+            return Err(Error::CommonError(format!(
+                "TsotTls::LoadNodeCert {:?} is not issued for the node ip {:?}",
+                &certPath, nodeIp
+            )));
+        }
+
+        return Ok((caCert, cert, key));
+    }
+
+    // whether the subject alternative names of the certificate has the ip
+    pub fn IssuedFor(cert: &X509Ref, ip: Ipv4Addr) -> bool {
+        match cert.subject_alt_names() {
+            None => return false,
+            Some(names) => {
+                return names
+                    .iter()
+                    .any(|name| name.ipaddress() == Some(&ip.octets()[..]))
+            }
+        }
+    }
+
+    pub fn PeerIp(stream: &TcpStream) -> Result<Ipv4Addr> {
+        match stream.peer_addr()? {
+            SocketAddr::V4(addr) => return Ok(*addr.ip()),
+            SocketAddr::V6(addr) => match addr.ip().to_ipv4_mapped() {
+                None => {
+                    return Err(Error::CommonError(format!(
+                        "TsotTls::PeerIp unknown peer {:?}",
+                        addr
+                    )))
+                }
+                Some(ip) => return Ok(ip),
+            },
+        }
+    }
+
+    // whether the accepted connection starts with a tls handshake
+    pub async fn IsTlsStream(stream: &TcpStream) -> Result<bool> {
+        let mut buf = [0; 1];
+        let cnt = stream.peek(&mut buf).await?;
+        return Ok(cnt == 1 && buf[0] == TLS_HANDSHAKE_RECORD);
+    }
+
+    // the peer node has to be a PeerMgr host and its certificate has to be issued for the host ip
+    pub async fn Accept(&self, stream: TcpStream) -> Result<SslStream<TcpStream>> {
+        let peerIp = Self::PeerIp(&stream)?;
+
+        if !PEER_MGR.IsPeerHost(peerIp.into()) {
+            return Err(Error::CommonError(format!(
+                "TsotTls::Accept {:?} is not a peer node",
+                peerIp
+            )));
+        }
+
+        return self.AcceptPeer(stream, peerIp).await;
+    }
+
+    // the handshake with the peer node, its certificate has to be issued for the peer ip
+    pub async fn AcceptPeer(
+        &self,
+        stream: TcpStream,
+        peerIp: Ipv4Addr,
+    ) -> Result<SslStream<TcpStream>> {
+        let acceptor = self.acceptor.as_ref().unwrap();
+        let ssl = Ssl::new(acceptor.context()).map_err(SslErr)?;
+        let mut stream = SslStream::new(ssl, stream).map_err(SslErr)?;
+        Pin::new(&mut stream).accept().await.map_err(SslErr)?;
+
+        let cert = match stream.ssl().peer_certificate() {
+            None => {
+                return Err(Error::CommonError(format!(
+                    "TsotTls::Accept peer {:?} has no certificate",
+                    peerIp
+                )))
+            }
+            Some(cert) => cert,
+        };
+
+        if !Self::IssuedFor(&cert, peerIp) {
+            return Err(Error::CommonError(format!(
+                "TsotTls::Accept the certificate of peer {:?} is not issued for it",
+                peerIp
+            )));
+        }
+
+        return Ok(stream);
+    }
+
+    pub async fn AcceptStreaming(&self, stream: TcpStream) -> Result<SslStream<TcpStream>> {
+        let acceptor = self.streamingAcceptor.as_ref().unwrap();
+        let ssl = Ssl::new(acceptor.context()).map_err(SslErr)?;
+        let mut stream = SslStream::new(ssl, stream).map_err(SslErr)?;
+        Pin::new(&mut stream).accept().await.map_err(SslErr)?;
+        return Ok(stream);
+    }
+
+    // the peer certificate is verified against the PeerMgr host ip
+    pub async fn Connect(&self, stream: TcpStream, hostIp: u32) -> Result<SslStream<TcpStream>> {
+        let connector = self.connector.as_ref().unwrap();
+        let mut config = connector.configure().map_err(SslErr)?;
+        config.set_verify_hostname(false);
+        config.set_use_server_name_indication(false);
+        config
+            .param_mut()
+            .set_ip(IpAddr::V4(Ipv4Addr::from(hostIp)))
+            .map_err(SslErr)?;
+
+        let ssl = config.into_ssl("").map_err(SslErr)?;
+        let mut stream = SslStream::new(ssl, stream).map_err(SslErr)?;
+        Pin::new(&mut stream).connect().await.map_err(SslErr)?;
+        return Ok(stream);
+    }
+
+    // the accepted peer connection is handed over to the pod as one side of a socket pair,
+    // the node agent relays the other side to the encrypted connection
+    // return: (the pod socket, the relay side)
+    pub fn SocketPair() -> Result<(RawFd, UnixStream)> {
+        let (pod, local) = StdUnixStream::pair()?;
+        local.set_nonblocking(true)?;
+        return Ok((pod.into_raw_fd(), UnixStream::from_std(local)?));
+    }
+
+    // the pod creates the tcp socket of the outgoing connection, so it is connected to a
+    // loopback listener. The accepted connection is checked against the pod socket address,
+    // so that another local process can't take over the relay
+    // return: (the pod stream, the relay side)
+    pub async fn LoopbackConnect(socket: TcpSocket) -> Result<(TcpStream, TcpStream)> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let podStream = socket.connect(listener.local_addr()?).await?;
+        let podAddr = podStream.local_addr()?;
+        loop {
+            let (local, peerAddr) = listener.accept().await?;
+            if peerAddr == podAddr {
+                return Ok((podStream, local));
+            }
+
+            error!(
+                "TsotTls::LoopbackConnect drop the connection from {:?}",
+                peerAddr
+            );
+        }
+    }
+
+    pub async fn Relay<S: AsyncRead + AsyncWrite + Unpin>(
+        mut tls: SslStream<TcpStream>,
+        mut local: S,
+    ) {
+        match tokio::io::copy_bidirectional(&mut tls, &mut local).await {
+            Ok(_) => (),
+            Err(e) => {
+                error!("TsotTls::Relay fail with error {:?}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use openssl::asn1::Asn1Time;
+    use openssl::bn::BigNum;
+    use openssl::ec::EcGroup;
+    use openssl::ec::EcKey;
+    use openssl::hash::MessageDigest;
+    use openssl::nid::Nid;
+    use openssl::x509::extension::BasicConstraints;
+    use openssl::x509::extension::ExtendedKeyUsage;
+    use openssl::x509::extension::KeyUsage;
+    use openssl::x509::extension::SubjectAlternativeName;
+    use openssl::x509::X509NameBuilder;
+
+    // the certificate signed by the issuer, or the self signed ca certificate without issuer
+    fn NewCert(
+        serial: u32,
+        ip: Option<Ipv4Addr>,
+        issuer: Option<(&X509, &PKey<Private>)>,
+    ) -> (X509, PKey<Private>) {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+
+        let mut name = X509NameBuilder::new().unwrap();
+        name.append_entry_by_text("CN", &format!("tsot{}", serial))
+            .unwrap();
+        let name = name.build();
+
+        let mut builder = X509::builder().unwrap();
+        builder.set_version(2).unwrap();
+        let serial = BigNum::from_u32(serial).unwrap().to_asn1_integer().unwrap();
+        builder.set_serial_number(&serial).unwrap();
+        builder.set_subject_name(&name).unwrap();
+        builder.set_pubkey(&key).unwrap();
+        builder
+            .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+            .unwrap();
+        builder
+            .set_not_after(&Asn1Time::days_from_now(1).unwrap())
+            .unwrap();
+
+        let (caCert, caKey) = match issuer {
+            None => {
+                builder.set_issuer_name(&name).unwrap();
+                let ca = BasicConstraints::new().critical().ca().build().unwrap();
+                builder.append_extension(ca).unwrap();
+                let usage = KeyUsage::new().key_cert_sign().crl_sign().build().unwrap();
+                builder.append_extension(usage).unwrap();
+                builder.sign(&key, MessageDigest::sha256()).unwrap();
+                return (builder.build(), key);
+            }
+            Some(issuer) => issuer,
+        };
+
+        builder.set_issuer_name(caCert.subject_name()).unwrap();
+        if let Some(ip) = ip {
+            let san = SubjectAlternativeName::new()
+                .ip(&ip.to_string())
+                .build(&builder.x509v3_context(Some(&**caCert), None))
+                .unwrap();
+            builder.append_extension(san).unwrap();
+        }
+        let usage = ExtendedKeyUsage::new()
+            .server_auth()
+            .client_auth()
+            .build()
+            .unwrap();
+        builder.append_extension(usage).unwrap();
+        builder.sign(caKey, MessageDigest::sha256()).unwrap();
+        return (builder.build(), key);
+    }
+
+    fn NewTls(caCert: &X509, caKey: &PKey<Private>, serial: u32, ip: Ipv4Addr) -> TsotTls {
+        let (cert, key) = NewCert(serial, Some(ip), Some((caCert, caKey)));
+        return TsotTls::NewFromCert(caCert.clone(), &cert, &key, HashSet::new()).unwrap();
+    }
+
+    // the client connects to the server over loopback and expects the server certificate
+    // issued for serverIp
+    // return: (the client result, the server result)
+    async fn Handshake(
+        client: &TsotTls,
+        server: &TsotTls,
+        serverIp: Ipv4Addr,
+    ) -> (Result<SslStream<TcpStream>>, Result<SslStream<TcpStream>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let connect = async {
+            let stream = TcpStream::connect(addr).await.unwrap();
+            client.Connect(stream, serverIp.into()).await
+        };
+        let accept = async {
+            let (stream, _) = listener.accept().await.unwrap();
+            let peerIp = TsotTls::PeerIp(&stream).unwrap();
+            server.AcceptPeer(stream, peerIp).await
+        };
+
+        return tokio::join!(connect, accept);
+    }
+
+    #[test]
+    fn TestIssuedFor() {
+        let (caCert, caKey) = NewCert(1, None, None);
+        let ip = Ipv4Addr::new(10, 0, 0, 1);
+        let (cert, _) = NewCert(2, Some(ip), Some((&caCert, &caKey)));
+        assert!(TsotTls::IssuedFor(&cert, ip));
+        assert!(!TsotTls::IssuedFor(&cert, Ipv4Addr::new(10, 0, 0, 2)));
+
+        let (cert, _) = NewCert(3, None, Some((&caCert, &caKey)));
+        assert!(!TsotTls::IssuedFor(&cert, ip));
+    }
+
+    #[tokio::test]
+    async fn TestAcceptPeer() {
+        let (caCert, caKey) = NewCert(1, None, None);
+        let loopback = Ipv4Addr::new(127, 0, 0, 1);
+        let server = NewTls(&caCert, &caKey, 2, loopback);
+
+        // the certificate of the peer is issued for the peer ip
+        let client = NewTls(&caCert, &caKey, 3, loopback);
+        let (connected, accepted) = Handshake(&client, &server, loopback).await;
+        assert!(connected.is_ok());
+        assert!(accepted.is_ok());
+
+        // the client pins the server certificate to the host ip it connects to
+        let (connected, accepted) = Handshake(&client, &server, Ipv4Addr::new(10, 0, 0, 1)).await;
+        assert!(connected.is_err());
+        assert!(accepted.is_err());
+
+        // the certificate is issued by the tsot ca but for another node
+        let client = NewTls(&caCert, &caKey, 4, Ipv4Addr::new(10, 0, 0, 2));
+        let (_, accepted) = Handshake(&client, &server, loopback).await;
+        assert!(accepted.is_err());
+    }
+}
@@ -31,6 +31,7 @@ use qshare::tsot_msg::*;
 use crate::pod_mgr::NAMESPACE_MGR;
 use crate::tsot::conn_svc::ConnectionSvc;
 use crate::tsot::dns_proxy::DNS_PROXY;
+use crate::tsot::tls::TSOT_TLS;
 use crate::QLET_CONFIG;
 
 pub struct TsotSvc {
@@ -181,6 +182,8 @@ impl tsot_cni::tsot_cni_service_server::TsotCniService for TostCniSvc {
 pub async fn TsotSvc() -> Result<()> {
     info!("Tsot service start ...");
     let tsotSvc = TsotSvc::New()?;
+    // load or issue the node certificate before serving the peer connections
+    lazy_static::initialize(&TSOT_TLS);
     let tsotSvcFuture = tsotSvc.Process();
 
     let tsotCniSvc = TostCniSvc {};
@@ -0,0 +1,407 @@
+// Copyright (c) 2023 Quark Container Authors / 2018 The gVisor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::net::Ipv4Addr;
+use std::net::SocketAddrV4;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::sync::atomic::AtomicU16;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use tokio::io::unix::AsyncFd;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWrite;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+
+use nix::sys::socket::{recv, sendmsg, MsgFlags};
+use nix::sys::uio::IoVec;
+
+use qshare::common::*;
+use qshare::tsot_msg::*;
+
+use super::conn_svc::*;
+use super::peer_mgr::PEER_MGR;
+use super::service_mgr::ServiceMgr;
+use super::service_mgr::SERVICE_MGR;
+use super::tls::TSOT_TLS;
+
+lazy_static::lazy_static! {
+    pub static ref UDP_LINK_MGR: UdpLinkMgr = {
+        UdpLinkMgr::default()
+    };
+}
+
+// the datagrams waiting for the link to the peer node, more are dropped
+pub const UDP_LINK_QUEUE_SIZE: usize = 1024;
+
+// the node agent side of a pod udp socket, a SOCK_SEQPACKET socket pair with the pod
+#[derive(Debug)]
+pub struct UdpRelaySocket {
+    pub socketId: u32,
+    pub fd: AsyncFd<OwnedFd>,
+    // the pod port bound by the socket, 0 means not bound
+    pub port: AtomicU16,
+
+    // (service vip, port) to the backend picked for the socket, so that
+    // the datagrams of one flow go to the same backend
+    pub backends: Mutex<HashMap<(TsotAddr, u16), TsotAddr>>,
+}
+
+impl UdpRelaySocket {
+    pub fn New(socketId: u32, fd: RawFd) -> Result<Arc<Self>> {
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+        let socket = Self {
+            socketId: socketId,
+            fd: AsyncFd::new(fd)?,
+            port: AtomicU16::new(0),
+            backends: Mutex::new(HashMap::new()),
+        };
+
+        return Ok(Arc::new(socket));
+    }
+
+    pub fn Port(&self) -> u16 {
+        return self.port.load(Ordering::SeqCst);
+    }
+
+    pub fn ResolveDstIp(&self, namespace: &str, dstIp: TsotAddr, dstPort: u16) -> Result<TsotAddr> {
+        return self.ResolveBackend(&SERVICE_MGR, namespace, dstIp, dstPort);
+    }
+
+    // the cached backend is dropped once it is no longer an endpoint of the service,
+    // e.g. the backend pod is gone, and another one is picked
+    pub fn ResolveBackend(
+        &self,
+        serviceMgr: &ServiceMgr,
+        namespace: &str,
+        dstIp: TsotAddr,
+        dstPort: u16,
+    ) -> Result<TsotAddr> {
+        let mut backends = self.backends.lock().unwrap();
+        if let Some(backend) = backends.get(&(dstIp, dstPort)).cloned() {
+            match (dstIp.Ipv4(), backend.Ipv4()) {
+                (Some(vip), Some(ip)) if serviceMgr.IsBackend(namespace, vip, ip) => {
+                    return Ok(backend);
+                }
+                _ => {
+                    backends.remove(&(dstIp, dstPort));
+                }
+            }
+        }
+
+        let backend = serviceMgr.ResolveDstIp(namespace, dstIp, dstPort)?;
+        if backend != dstIp {
+            backends.insert((dstIp, dstPort), backend);
+        }
+
+        return Ok(backend);
+    }
+
+    // read one datagram from the pod, return 0 when the pod closes the socket
+    pub async fn Recv(&self, buf: &mut [u8]) -> Result<usize> {
+        loop {
+            let mut guard = self.fd.readable().await?;
+            match guard.try_io(|fd| {
+                recv(fd.as_raw_fd(), buf, MsgFlags::MSG_DONTWAIT)
+                    .map_err(|e| std::io::Error::from_raw_os_error(e as i32))
+            }) {
+                Ok(res) => return Ok(res?),
+                Err(_wouldBlock) => continue,
+            }
+        }
+    }
+
+    // the datagram is dropped when the pod doesn't read fast enough
+    pub fn Send(&self, hdr: &UdpDatagramHdr, payload: &[u8]) -> Result<()> {
+        let iov = [IoVec::from_slice(hdr.AsBytes()), IoVec::from_slice(payload)];
+        match sendmsg(self.fd.as_raw_fd(), &iov, &[], MsgFlags::MSG_DONTWAIT, None) {
+            Ok(_) => return Ok(()),
+            Err(errno) => return Err(Error::SysError(errno as i32)),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct UdpLinkMgr {
+    // (peer host ip, peer port) to the datagram queue of the link to the peer node
+    pub links: Mutex<HashMap<(u32, u16), mpsc::Sender<Vec<u8>>>>,
+}
+
+impl UdpLinkMgr {
+    pub fn Send(
+        &self,
+        namespace: &str,
+        dstIp: TsotAddr,
+        dstPort: u16,
+        srcIp: TsotAddr,
+        srcPort: u16,
+        payload: &[u8],
+    ) -> Result<()> {
+        let peer = PEER_MGR.LookforPeer(dstIp)?;
+        let hdr = TsotDatagramHdr::New(namespace, dstIp, dstPort, srcIp, srcPort, payload.len());
+        let mut frame = Vec::with_capacity(TSOT_DATAGRAM_HDR_SIZE + payload.len());
+        frame.extend_from_slice(hdr.AsBytes());
+        frame.extend_from_slice(payload);
+
+        let link = self.GetLink(peer.hostIp, peer.port);
+        match link.try_send(frame) {
+            Ok(()) => return Ok(()),
+            Err(_e) => {
+                return Err(Error::MpscSendFull(format!(
+                    "UdpLinkMgr::Send link to {:x}:{} is full",
+                    peer.hostIp, peer.port
+                )));
+            }
+        }
+    }
+
+    // the link is created on demand and recreated after it fails
+    pub fn GetLink(&self, hostIp: u32, port: u16) -> mpsc::Sender<Vec<u8>> {
+        let mut links = self.links.lock().unwrap();
+        match links.get(&(hostIp, port)) {
+            Some(link) if !link.is_closed() => return link.clone(),
+            _ => (),
+        }
+
+        let (tx, rx) = mpsc::channel::<Vec<u8>>(UDP_LINK_QUEUE_SIZE);
+        links.insert((hostIp, port), tx.clone());
+        tokio::spawn(async move {
+            match Self::ProcessLink(hostIp, port, rx).await {
+                Ok(()) => (),
+                Err(e) => {
+                    error!(
+                        "UdpLinkMgr link to {:x}:{} fail with error {:?}",
+                        hostIp, port, e
+                    );
+                }
+            }
+        });
+
+        return tx;
+    }
+
+    pub async fn ProcessLink(hostIp: u32, port: u16, rx: mpsc::Receiver<Vec<u8>>) -> Result<()> {
+        let addr = SocketAddrV4::new(Ipv4Addr::from(hostIp), port);
+        let stream = TcpStream::connect(addr).await?;
+        stream.set_nodelay(true)?;
+        if TSOT_TLS.Enabled() {
+            let mut stream = TSOT_TLS.Connect(stream, hostIp).await?;
+            return Self::ForwardFrames(&mut stream, rx).await;
+        }
+
+        let mut stream = stream;
+        return Self::ForwardFrames(&mut stream, rx).await;
+    }
+
+    pub async fn ForwardFrames<S: AsyncRead + AsyncWrite + Unpin>(
+        stream: &mut S,
+        mut rx: mpsc::Receiver<Vec<u8>>,
+    ) -> Result<()> {
+        let req = TsotConnReq {
+            podNamespace: [0; 64],
+            dstIp: TsotAddr::default(),
+            dstPort: 0,
+            srcIp: TsotAddr::default(),
+            srcPort: 0,
+            connType: TsotConnType::Datagram as _,
+        };
+
+        let reqAddr = &req as *const _ as u64 as *const u8;
+        let reqBuf =
+            unsafe { std::slice::from_raw_parts(reqAddr, std::mem::size_of::<TsotConnReq>()) };
+        stream.write_all(reqBuf).await?;
+
+        let mut respBuf = [0; std::mem::size_of::<TsotConnResp>()];
+        stream.read_exact(&mut respBuf).await?;
+        let resp = unsafe { *(&respBuf[0] as *const _ as u64 as *const TsotConnResp) };
+        if resp.errcode != TsotErrCode::Ok as u32 {
+            return Err(Error::CommonError(format!(
+                "UdpLinkMgr::ProcessLink rejected with error {:?}",
+                resp.errcode
+            )));
+        }
+
+        while let Some(frame) = rx.recv().await {
+            stream.write_all(&frame).await?;
+        }
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nix::sys::socket::{send, socketpair, AddressFamily, SockFlag, SockType};
+    use std::collections::BTreeMap;
+    use std::time::SystemTime;
+
+    use qshare::consts::ConditionTrue;
+    use qshare::node::{PodCondition, PodDef, ServiceDef};
+
+    use crate::pod_mgr::qpod::{PodReady, PodRunning};
+
+    fn Backend(name: &str, ip: u32) -> PodDef {
+        let mut pod = PodDef {
+            tenant: "t1".to_owned(),
+            namespace: "ns1".to_owned(),
+            name: name.to_owned(),
+            ipAddr: ip,
+            ..Default::default()
+        };
+        pod.labels.insert("app".to_owned(), "a".to_owned());
+        pod.status.phase = PodRunning.to_owned();
+        pod.status.conditions.push(PodCondition {
+            last_probe_time: SystemTime::UNIX_EPOCH,
+            last_transition_time: SystemTime::UNIX_EPOCH,
+            message: String::new(),
+            reason: String::new(),
+            status: ConditionTrue.to_owned(),
+            type_: PodReady.to_owned(),
+        });
+        return pod;
+    }
+
+    fn RelaySocket() -> (Arc<UdpRelaySocket>, OwnedFd) {
+        let (podSide, brokerSide) = socketpair(
+            AddressFamily::Unix,
+            SockType::SeqPacket,
+            None,
+            SockFlag::SOCK_CLOEXEC | SockFlag::SOCK_NONBLOCK,
+        )
+        .unwrap();
+        let socket = UdpRelaySocket::New(1, brokerSide).unwrap();
+        return (socket, unsafe { OwnedFd::from_raw_fd(podSide) });
+    }
+
+    #[tokio::test]
+    async fn TestResolveBackend() {
+        let mgr = ServiceMgr::default();
+        let mut selector = BTreeMap::new();
+        selector.insert("app".to_owned(), "a".to_owned());
+        mgr.AddService(ServiceDef {
+            tenant: "t1".to_owned(),
+            namespace: "ns1".to_owned(),
+            name: "a".to_owned(),
+            selector: selector,
+            ..Default::default()
+        })
+        .unwrap();
+        let vip = TsotAddr::FromIpv4(mgr.Lookup("t1", "ns1", "a").unwrap());
+        mgr.AddPod(Backend("p1", 1));
+        mgr.AddPod(Backend("p2", 2));
+
+        // the flow sticks to the first picked backend
+        let (socket, _pod) = RelaySocket();
+        let backend = socket.ResolveBackend(&mgr, "t1/ns1", vip, 53).unwrap();
+        for _ in 0..3 {
+            assert_eq!(socket.ResolveBackend(&mgr, "t1/ns1", vip, 53).unwrap(), backend);
+        }
+
+        // another backend is picked after the backend pod is gone
+        let gone = backend.Ipv4().unwrap();
+        mgr.RemovePod(&Backend(if gone == 1 { "p1" } else { "p2" }, gone));
+        let other = socket.ResolveBackend(&mgr, "t1/ns1", vip, 53).unwrap();
+        assert_ne!(other, backend);
+        assert_eq!(socket.backends.lock().unwrap().get(&(vip, 53)), Some(&other));
+
+        // the pod ip is not cached
+        let pod = TsotAddr::FromIpv4(5);
+        assert_eq!(socket.ResolveBackend(&mgr, "t1/ns1", pod, 53).unwrap(), pod);
+        assert_eq!(socket.backends.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn TestRelaySocketDatagram() {
+        let (socket, pod) = RelaySocket();
+
+        // from the pod: the header and the payload arrive as one datagram
+        let hdr = UdpDatagramHdr {
+            peerIp: TsotAddr::FromIpv4(0x0a000001),
+            peerPort: 53,
+            localPort: 1000,
+        };
+        let mut datagram = hdr.AsBytes().to_vec();
+        datagram.extend_from_slice(b"query");
+        send(pod.as_raw_fd(), &datagram, MsgFlags::empty()).unwrap();
+
+        let mut buf = vec![0; UDP_DATAGRAM_HDR_SIZE + UDP_MAX_PAYLOAD_SIZE];
+        let cnt = socket.Recv(&mut buf).await.unwrap();
+        assert_eq!(cnt, datagram.len());
+        let got = UdpDatagramHdr::FromBytes(&buf[..UDP_DATAGRAM_HDR_SIZE]);
+        assert_eq!(got.peerIp, hdr.peerIp);
+        assert_eq!((got.peerPort, got.localPort), (53, 1000));
+        assert_eq!(&buf[UDP_DATAGRAM_HDR_SIZE..cnt], b"query");
+
+        // to the pod
+        socket.Send(&hdr, b"answer").unwrap();
+        let cnt = recv(pod.as_raw_fd(), &mut buf, MsgFlags::empty()).unwrap();
+        assert_eq!(cnt, UDP_DATAGRAM_HDR_SIZE + 6);
+        assert_eq!(&buf[UDP_DATAGRAM_HDR_SIZE..cnt], b"answer");
+
+        // the pod closes the socket
+        drop(pod);
+        assert_eq!(socket.Recv(&mut buf).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn TestForwardFrames() {
+        let (mut link, mut peer) = tokio::io::duplex(4096);
+        let (tx, rx) = mpsc::channel(UDP_LINK_QUEUE_SIZE);
+        let hdr = TsotDatagramHdr::New(
+            "t1/ns1",
+            TsotAddr::FromIpv4(2),
+            53,
+            TsotAddr::FromIpv4(1),
+            1000,
+            5,
+        );
+        let mut frame = hdr.AsBytes().to_vec();
+        frame.extend_from_slice(b"query");
+        tx.send(frame).await.unwrap();
+        drop(tx);
+
+        let forward =
+            tokio::spawn(async move { UdpLinkMgr::ForwardFrames(&mut link, rx).await });
+
+        // the link starts with a datagram connection request
+        let mut reqBuf = [0; std::mem::size_of::<TsotConnReq>()];
+        peer.read_exact(&mut reqBuf).await.unwrap();
+        let req = unsafe { std::ptr::read_unaligned(reqBuf.as_ptr() as *const TsotConnReq) };
+        assert_eq!(req.connType, TsotConnType::Datagram as u32);
+        let resp = TsotConnResp {
+            errcode: TsotErrCode::Ok as u32,
+        };
+        let respAddr = &resp as *const _ as u64 as *const u8;
+        let respBuf =
+            unsafe { std::slice::from_raw_parts(respAddr, std::mem::size_of::<TsotConnResp>()) };
+        peer.write_all(respBuf).await.unwrap();
+
+        let mut hdrBuf = [0; TSOT_DATAGRAM_HDR_SIZE];
+        peer.read_exact(&mut hdrBuf).await.unwrap();
+        let got = unsafe { std::ptr::read_unaligned(hdrBuf.as_ptr() as *const TsotDatagramHdr) };
+        assert_eq!(got.GetNamespace().unwrap(), "t1/ns1");
+        assert_eq!((got.dstIp, got.dstPort), (TsotAddr::FromIpv4(2), 53));
+        assert_eq!((got.srcIp, got.srcPort), (TsotAddr::FromIpv4(1), 1000));
+        let mut payload = vec![0; got.len as usize];
+        peer.read_exact(&mut payload).await.unwrap();
+        assert_eq!(payload, b"query");
+
+        forward.await.unwrap().unwrap();
+    }
+}
@@ -209,6 +209,19 @@ impl IpAddress {
     }
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Ipv6Address(pub [u8; 16]);
+
+impl Ipv6Address {
+    pub fn AsBytes(&self) -> [u8; 16] {
+        return self.0;
+    }
+
+    pub fn IsUnspecified(&self) -> bool {
+        return self.0 == [0; 16];
+    }
+}
+
 #[derive(Debug)]
 pub struct MinRevsionErr {
     pub minRevision: i64,
@@ -326,6 +326,9 @@ pub struct PodDef {
     pub runtime_class_name: Option<String>,
     pub security_context: Option<k8s::PodSecurityContext>,
     pub ipAddr: u32,
+    // all zero for the ipv4 only pod network
+    #[serde(default)]
+    pub ipv6Addr: [u8; 16],
 
     pub status: PodStatus,
 }
@@ -389,6 +392,256 @@ impl From<PodDef> for PodDefBox {
     }
 }
 
+// the pods allowed by a network policy rule, an empty podSelector selects all pods
+#[derive(Default, Debug, Serialize, Deserialize, Clone)]
+pub struct NetworkPolicyPeer {
+    // the peer's "tenant/namespace", the policy's namespace if it is None.
+    // the gateway connects from the "system" namespace
+    #[serde(default)]
+    pub namespace: Option<String>,
+    #[serde(default)]
+    pub podSelector: BTreeMap<String, String>,
+}
+
+impl NetworkPolicyPeer {
+    pub fn Match(
+        &self,
+        policyNamespace: &str,
+        namespace: &str,
+        labels: &BTreeMap<String, String>,
+    ) -> bool {
+        let peerNamespace = self.namespace.as_deref().unwrap_or(policyNamespace);
+        if peerNamespace != namespace {
+            return false;
+        }
+
+        return LabelsMatch(&self.podSelector, labels);
+    }
+}
+
+// empty peers allow all peers and empty ports allow all ports
+#[derive(Default, Debug, Serialize, Deserialize, Clone)]
+pub struct NetworkPolicyRule {
+    #[serde(default)]
+    pub peers: Vec<NetworkPolicyPeer>,
+    #[serde(default)]
+    pub ports: Vec<u16>,
+}
+
+impl NetworkPolicyRule {
+    pub fn Allow(
+        &self,
+        policyNamespace: &str,
+        namespace: &str,
+        labels: &BTreeMap<String, String>,
+        port: u16,
+    ) -> bool {
+        if self.ports.len() > 0 && !self.ports.contains(&port) {
+            return false;
+        }
+
+        if self.peers.len() == 0 {
+            return true;
+        }
+
+        for peer in &self.peers {
+            if peer.Match(policyNamespace, namespace, labels) {
+                return true;
+            }
+        }
+
+        return false;
+    }
+}
+
+#[derive(Default, Debug, Serialize, Deserialize, Clone)]
+pub struct NetworkPolicySpec {
+    // the pods in the policy's namespace the policy applies to
+    #[serde(default)]
+    pub podSelector: BTreeMap<String, String>,
+    // the selected pods only accept the connections allowed by the rules when it is Some
+    #[serde(default)]
+    pub ingress: Option<Vec<NetworkPolicyRule>>,
+    // the selected pods only connect to the peers allowed by the rules when it is Some
+    #[serde(default)]
+    pub egress: Option<Vec<NetworkPolicyRule>>,
+}
+
+#[derive(Default, Debug, Serialize, Deserialize, Clone)]
+pub struct NetworkPolicy {
+    #[serde(default)]
+    pub tenant: String,
+    pub namespace: String,
+    pub name: String,
+    #[serde(default)]
+    pub revision: i64,
+
+    pub spec: NetworkPolicySpec,
+}
+
+impl NetworkPolicy {
+    pub const KEY: &'static str = "network_policy";
+
+    // the namespace used by the pod network, i.e. PodDef::PodNamespace
+    pub fn PodNamespace(&self) -> String {
+        return format!("{}/{}", &self.tenant, &self.namespace);
+    }
+
+    pub fn Key(&self) -> String {
+        return format!("{}/{}/{}", &self.tenant, &self.namespace, &self.name);
+    }
+
+    pub fn Select(&self, namespace: &str, labels: &BTreeMap<String, String>) -> bool {
+        return self.PodNamespace() == namespace && LabelsMatch(&self.spec.podSelector, labels);
+    }
+
+    pub fn FromDataObject(obj: DataObject) -> Result<Self> {
+        let policy = match serde_json::from_str::<Self>(&obj.data) {
+            Err(e) => {
+                return Err(Error::CommonError(format!(
+                    "NetworkPolicy::FromDataObject {:?}",
+                    e
+                )))
+            }
+            Ok(s) => s,
+        };
+        return Ok(policy);
+    }
+
+    pub fn DataObject(&self) -> DataObject {
+        let inner = DataObjectInner {
+            kind: Self::KEY.to_owned(),
+            tenant: self.tenant.clone(),
+            namespace: self.namespace.clone(),
+            name: self.name.clone(),
+            data: serde_json::to_string_pretty(&self).unwrap(),
+            ..Default::default()
+        };
+
+        return inner.into();
+    }
+}
+
+// all the selector labels exist in the labels with the same values
+pub fn LabelsMatch(selector: &BTreeMap<String, String>, labels: &BTreeMap<String, String>) -> bool {
+    for (k, v) in selector {
+        if labels.get(k) != Some(v) {
+            return false;
+        }
+    }
+
+    return true;
+}
+
+// the virtual ips of the services are in 172.16.0.0/12, out of the pod cidr
+pub const SERVICE_VIP_ADDR: u32 = (172 << 24) | (16 << 16);
+pub const SERVICE_VIP_MASKBITS: usize = 12;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum LoadBalancePolicy {
+    RoundRobin,
+    // the qlet doesn't see the connection closes, so it counts the connections assigned
+    // to the backend since the backend joins the service
+    LeastConnections,
+}
+
+impl Default for LoadBalancePolicy {
+    fn default() -> Self {
+        return Self::RoundRobin;
+    }
+}
+
+// a service is a stable virtual ip and dns name for the pods selected by the selector
+#[derive(Default, Debug, Serialize, Deserialize, Clone)]
+pub struct ServiceDef {
+    #[serde(default)]
+    pub tenant: String,
+    pub namespace: String,
+    pub name: String,
+    #[serde(default)]
+    pub revision: i64,
+
+    pub selector: BTreeMap<String, String>,
+    // the ports exposed by the service, all ports are exposed when it is empty
+    #[serde(default)]
+    pub ports: Vec<u16>,
+    #[serde(default)]
+    pub lbPolicy: LoadBalancePolicy,
+    // the virtual ip, it is derived from the service name when it is 0. A derived virtual ip
+    // which is taken by another service is moved to the next free one
+    #[serde(default)]
+    pub vip: u32,
+}
+
+impl ServiceDef {
+    pub const KEY: &'static str = "service";
+
+    pub fn PodNamespace(&self) -> String {
+        return format!("{}/{}", &self.tenant, &self.namespace);
+    }
+
+    pub fn Key(&self) -> String {
+        return format!("{}/{}/{}", &self.tenant, &self.namespace, &self.name);
+    }
+
+    // the virtual ip is derived from the service key with fnv-1a so that all the qlets get the same one
+    pub fn VirtualIp(&self) -> u32 {
+        if self.vip != 0 {
+            return self.vip;
+        }
+
+        let mut hash: u32 = 0x811c9dc5;
+        for b in self.Key().as_bytes() {
+            hash ^= *b as u32;
+            hash = hash.wrapping_mul(0x01000193);
+        }
+
+        let hostMask: u32 = (1 << (32 - SERVICE_VIP_MASKBITS)) - 1;
+        let mut host = hash & hostMask;
+        // skip the network and broadcast address
+        if host == 0 || host == hostMask {
+            host = 1;
+        }
+
+        return SERVICE_VIP_ADDR | host;
+    }
+
+    pub fn IsVirtualIp(addr: u32) -> bool {
+        let mask: u32 = !((1 << (32 - SERVICE_VIP_MASKBITS)) - 1);
+        return addr & mask == SERVICE_VIP_ADDR;
+    }
+
+    pub fn ExposePort(&self, port: u16) -> bool {
+        return self.ports.len() == 0 || self.ports.contains(&port);
+    }
+
+    pub fn FromDataObject(obj: DataObject) -> Result<Self> {
+        let service = match serde_json::from_str::<Self>(&obj.data) {
+            Err(e) => {
+                return Err(Error::CommonError(format!(
+                    "ServiceDef::FromDataObject {:?}",
+                    e
+                )))
+            }
+            Ok(s) => s,
+        };
+        return Ok(service);
+    }
+
+    pub fn DataObject(&self) -> DataObject {
+        let inner = DataObjectInner {
+            kind: Self::KEY.to_owned(),
+            tenant: self.tenant.clone(),
+            namespace: self.namespace.clone(),
+            name: self.name.clone(),
+            data: serde_json::to_string_pretty(&self).unwrap(),
+            ..Default::default()
+        };
+
+        return inner.into();
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct PodCondition {
     /// Last time we probed the condition.
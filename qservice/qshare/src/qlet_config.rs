@@ -27,12 +27,37 @@ pub struct QletConfig {
     pub tsotCniPort: u16,
     pub tsotSvcPort: u16,
     pub stateSvcPort: u16,
+    // the port of the cri exec, attach and port forward streams, 0 means the streaming is disabled
+    #[serde(default)]
+    pub streamingPort: u16,
 
     pub cidr: String,
+    // the ipv6 pod cidr of the node for the dual stack pod network, in the same form as the cidr,
+    // e.g. "fd00:10::1:0/16" for the 16 bits pod addresses. empty means the ipv4 only pod network
+    #[serde(default)]
+    pub cidr6: String,
+
+    // the directory of the tsot ca certificate (ca.crt) and the node certificate issued by it
+    // (<nodeName>.crt, <nodeName>.key) for the mutual tls between the nodes,
+    // empty means the node to node connections are not encrypted
+    #[serde(default)]
+    pub tlsCaDir: String,
+    // the namespaces whose node to node connections must be encrypted
+    #[serde(default)]
+    pub tlsNamespaces: Vec<String>,
+
+    // the containerd shim keeps the quark container states in the task directory of the sandbox
+    #[serde(default = "DefaultContainerdTaskDir")]
+    pub containerdTaskDir: String,
+
     pub stateSvcAddr: Vec<String>,
     pub singleNodeModel: bool,
 }
 
+pub fn DefaultContainerdTaskDir() -> String {
+    return "/run/containerd/io.containerd.runtime.v2.task/k8s.io".to_owned();
+}
+
 impl QletConfig {
     pub fn Load(path: &str) -> Result<Self> {
         let data = fs::read_to_string(path)?;
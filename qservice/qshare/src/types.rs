@@ -25,6 +25,9 @@ pub struct NodeInfo {
     pub tsotSvcPort: u16,
     pub stateSvcPort: u16,
     pub cidr: String,
+    // empty for the ipv4 only pod network
+    #[serde(default)]
+    pub cidr6: String,
 }
 
 pub enum PodType {
@@ -31,7 +31,7 @@ use qshare::qmeta;
 use crate::QletAggrStore::QletAggrStore;
 
 lazy_static::lazy_static! {
-    pub static ref ETCD_OBJECTS: Vec<&'static str> = vec!["node_info", "namespace_info", "funcpackage"];
+    pub static ref ETCD_OBJECTS: Vec<&'static str> = vec!["node_info", "namespace_info", "funcpackage", "network_policy", "service"];
 }
 
 pub const VERSION: &str = "0.1";
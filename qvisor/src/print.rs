@@ -19,16 +19,22 @@ use core::sync::atomic::AtomicI32;
 use core::sync::atomic::AtomicU64;
 use core::sync::atomic::Ordering;
 use lazy_static::lazy_static;
+use spin::Mutex;
 use std::fs::OpenOptions;
 use std::os::unix::io::IntoRawFd;
 
+use super::qlib::config::LogRecord;
 use super::qlib::kernel::Timestamp;
 use super::qlib::kernel::IOURING;
 use super::qlib::kernel::SHARESPACE;
+use super::LocalVcpu;
 use super::ThreadId;
+use super::ROOT_CONTAINER_ID;
 
 lazy_static! {
     pub static ref LOG: Log = Log::New();
+    // the container which the qvisor command runs for
+    pub static ref LOG_CONTAINER_ID: Mutex<String> = Mutex::new(String::new());
 }
 
 pub struct Log {
@@ -36,6 +42,7 @@ pub struct Log {
     pub rawfd: AtomicI32,
     pub lineNum: AtomicU64,
     pub syncPrint: AtomicBool,
+    pub jsonFormat: AtomicBool,
     pub processid: AtomicI32,
 }
 
@@ -43,6 +50,40 @@ pub fn SetSyncPrint(syncPrint: bool) {
     LOG.SetSyncPrint(syncPrint);
 }
 
+pub fn SetJsonFormat(jsonFormat: bool) {
+    LOG.jsonFormat.store(jsonFormat, Ordering::SeqCst);
+}
+
+pub fn SetLogContainer(id: &str) {
+    *LOG_CONTAINER_ID.lock() = id.to_string();
+}
+
+// the json log record of the host side. It has the container and the vcpu fields as the guest
+// records, so that the records of both sides can be correlated
+pub fn HostLogRecord(ts: i64, level: &str, msg: &str) -> String {
+    // the log might be printed with the container id locked
+    let sandbox = match ROOT_CONTAINER_ID.try_lock() {
+        None => String::new(),
+        Some(id) => id.clone(),
+    };
+    let container = match LOG_CONTAINER_ID.try_lock() {
+        None => String::new(),
+        Some(id) => id.clone(),
+    };
+
+    let record = LogRecord {
+        ts: ts,
+        level: level,
+        sandbox: &sandbox,
+        container: &container,
+        vcpu: LocalVcpu().map(|vcpu| vcpu.id),
+        task: None,
+        thread: Some(ThreadId()),
+        msg: msg,
+    };
+    return record.ToJson();
+}
+
 pub const LOG_FILE_DEFAULT: &str = "/var/log/quark/quark.log";
 pub const RAWLOG_FILE_DEFAULT: &str = "/var/log/quark/raw.log";
 pub const LOG_FILE_FORMAT: &str = "/var/log/quark/{}.log";
@@ -77,6 +118,7 @@ impl Log {
             rawfd: AtomicI32::new(rawfile.into_raw_fd()),
             lineNum: AtomicU64::new(1),
             syncPrint: AtomicBool::new(true),
+            jsonFormat: AtomicBool::new(false),
             processid: AtomicI32::new(std::process::id() as _),
         };
     }
@@ -195,7 +237,9 @@ impl Log {
     pub fn Print(&self, level: &str, str: &str) {
         let now = Timestamp();
         //let now = RawTimestamp();
-        if MEMORY_LEAK_LOG {
+        if self.jsonFormat.load(Ordering::Relaxed) {
+            self.Write(&format!("{}\n", HostLogRecord(now, level, str)));
+        } else if MEMORY_LEAK_LOG {
             self.Write(&format!(
                 "{:?} [{}] [{}/{}] {}\n",
                 self.processid,
@@ -222,6 +266,29 @@ impl Log {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn TestHostLogRecord() {
+        *ROOT_CONTAINER_ID.lock() = "sb1".to_string();
+        SetLogContainer("c1");
+
+        // the vcpu is only set on the vcpu threads
+        assert_eq!(
+            HostLogRecord(5, "INFO", "hello"),
+            r#"{"ts":5,"level":"INFO","sandbox":"sb1","container":"c1","thread":0,"msg":"hello"}"#
+        );
+
+        SetLogContainer("");
+        assert_eq!(
+            HostLogRecord(6, "ERROR", "e"),
+            r#"{"ts":6,"level":"ERROR","sandbox":"sb1","thread":0,"msg":"e"}"#
+        );
+    }
+}
+
 #[macro_export]
 macro_rules! raw {
     // macth like arm for macro
@@ -126,6 +126,9 @@ impl KVMVcpu {
             Msg::UnmapGuestMemRange(msg) => {
                 ret = super::VMSpace::UnmapGuestMemRange(msg.start, msg.len) as u64;
             }
+            Msg::RemoveGuestMemRange(msg) => {
+                ret = super::VMSpace::RemoveGuestMemRange(msg.start, msg.len) as u64;
+            }
             Msg::NividiaDriverVersion(msg) => {
                 ret = super::VMSpace::NividiaDriverVersion(msg.ioctlParamsAddr) as u64;
             }
@@ -28,6 +28,7 @@ use super::super::super::qlib::linux_def::*;
 use super::super::super::qlib::path::*;
 use super::super::oci::*;
 use super::super::specutils::specutils::MkdirAll;
+use super::cgroup_v2::*;
 
 pub const CONTROLLERS: [(&str, fn(spec: &LinuxResources, path: &str) -> Result<()>); 11] = [
     ("blkio", BlockIO),
@@ -255,24 +256,55 @@ impl<'a> Drop for CgroupCleanup<'a> {
     }
 }
 
+// RemoveCgroupDir removes one cgroup directory. If we try to remove the cgroup
+// too soon after killing the sandbox we might get EBUSY, so we retry for a few
+// seconds until it succeeds.
+pub fn RemoveCgroupDir(path: &str) {
+    for i in 0..7 {
+        match fs::remove_dir(path) {
+            Ok(()) => break,
+            Err(e) => {
+                if let Some(errno) = e.raw_os_error() {
+                    if errno == SysErr::ENOENT {
+                        continue;
+                    }
+
+                    error!("can't uninstall ({:?}) failed: {:?}", path, e);
+                    break;
+                }
+            }
+        }
+
+        //sleep 2^i * 100 ms
+        let millies = time::Duration::from_millis(100 << i);
+        thread::sleep(millies);
+    }
+}
+
 // Cgroup represents a group inside all controllers. For example: Name='/foo/bar'
-// maps to /sys/fs/cgroup/<controller>/foo/bar on all controllers.
+// maps to /sys/fs/cgroup/<controller>/foo/bar on all controllers, or to
+// /sys/fs/cgroup/foo/bar on the cgroup v2 unified hierarchy.
 #[derive(Serialize, Deserialize, Debug, Default)]
 pub struct Cgroup {
     pub Name: String,
     pub Parents: BTreeMap<String, String>,
     pub Own: bool,
+    #[serde(default)]
+    pub V2: bool,
 }
 
 impl Cgroup {
-    pub fn New(spec: &Spec) -> Result<Option<Self>> {
+    // New returns the cgroup of the spec. A rootless sandbox can only manage the
+    // cgroup subtree delegated to the user, so its cgroup is always nested under
+    // the cgroup of the current process.
+    pub fn New(spec: &Spec, rootless: bool) -> Result<Option<Self>> {
         if spec.linux.is_none() || spec.linux.as_ref().unwrap().cgroups_path.len() == 0 {
             return Ok(None);
         }
 
         let cgroupsPath = spec.linux.as_ref().unwrap().cgroups_path.to_string();
 
-        let parents = if !IsAbs(&cgroupsPath) {
+        let parents = if !IsAbs(&cgroupsPath) || rootless {
             LoadPaths("self")?
         } else {
             BTreeMap::new()
@@ -282,6 +314,7 @@ impl Cgroup {
             Name: cgroupsPath,
             Parents: parents,
             Own: false,
+            V2: IsCgroupV2(),
         }));
     }
 
@@ -289,6 +322,10 @@ impl Cgroup {
     // already exists, it means that the caller has already provided a
     // pre-configured cgroups, and 'res' is ignored.
     pub fn Install(&mut self, res: &Option<LinuxResources>) -> Result<()> {
+        if self.V2 {
+            return self.InstallV2(res);
+        }
+
         if Path::new(&self.MakePath("memory")).exists() {
             info!("Using pre-created cgroup {}", &self.Name);
             return Ok(());
@@ -326,32 +363,15 @@ impl Cgroup {
         }
 
         info!("Deleting cgroup {}", &self.Name);
+        if self.V2 {
+            RemoveCgroupDir(&self.MakePath(CGROUP2_KEY));
+            return;
+        }
+
         for c in &CONTROLLERS {
             let path = self.MakePath(c.0);
             info!("Removing cgroup controller for key={} path={}", &c.0, &path);
-
-            // If we try to remove the cgroup too soon after killing the
-            // sandbox we might get EBUSY, so we retry for a few seconds
-            // until it succeeds.
-            for i in 0..7 {
-                match fs::remove_dir(&path) {
-                    Ok(()) => break,
-                    Err(e) => {
-                        if let Some(errno) = e.raw_os_error() {
-                            if errno == SysErr::ENOENT {
-                                continue;
-                            }
-
-                            error!("can't uninstall ({:?}) failed: {:?}", path, e);
-                            break;
-                        }
-                    }
-                }
-
-                //sleep 2^i * 100 ms
-                let millies = time::Duration::from_millis(100 << i);
-                thread::sleep(millies);
-            }
+            RemoveCgroupDir(&path);
         }
     }
 
@@ -364,16 +384,29 @@ impl Cgroup {
         };
 
         let mut undoPaths = Vec::new();
-        //'outer:
-        for (ctrlr, path) in &paths {
-            for c in &CONTROLLERS {
-                if ctrlr == c.0 {
-                    let fullpath = Join(&Join(CGROUP_ROOT, ctrlr), path);
-                    undoPaths.push(fullpath);
-
-                    //break 'outer;
+        let mut joinPaths = Vec::new();
+        if self.V2 {
+            // the unified hierarchy has only one entry, with an empty controller list
+            if let Some(path) = paths.get("") {
+                undoPaths.push(Join(CGROUP_ROOT, path));
+            }
+            joinPaths.push(self.MakePath(CGROUP2_KEY));
+        } else {
+            //'outer:
+            for (ctrlr, path) in &paths {
+                for c in &CONTROLLERS {
+                    if ctrlr == c.0 {
+                        let fullpath = Join(&Join(CGROUP_ROOT, ctrlr), path);
+                        undoPaths.push(fullpath);
+
+                        //break 'outer;
+                    }
                 }
             }
+
+            for c in &CONTROLLERS {
+                joinPaths.push(self.MakePath(&c.0));
+            }
         }
 
         // Replace empty undo with the real thing before changes are made to cgroups.
@@ -388,9 +421,8 @@ impl Cgroup {
         };
 
         // Now join the cgroups.
-        for c in &CONTROLLERS {
-            let path = self.MakePath(&c.0);
-            info!("Joining cgroup {}", &path);
+        for path in &joinPaths {
+            info!("Joining cgroup {}", path);
 
             match SetValue(&path, "cgroup.procs", "0") {
                 Ok(()) => (),
@@ -406,6 +438,11 @@ impl Cgroup {
 
     // NumCPU returns the number of CPUs configured in 'cpuset/cpuset.cpus'.
     pub fn NumCPU(&self) -> Result<usize> {
+        if self.V2 {
+            let cpuset = GetValue(&self.MakePath(CGROUP2_KEY), "cpuset.cpus.effective")?;
+            return CountCpuset(cpuset.trim());
+        }
+
         let path = self.MakePath("cpuset");
         let cpuset = GetValue(&path, "cpuset.cpus")?;
         return CountCpuset(&cpuset);
@@ -413,6 +450,18 @@ impl Cgroup {
 
     // MemoryLimit returns the memory limit.
     pub fn MemoryLimit(&self) -> Result<u64> {
+        if self.V2 {
+            let limStr = GetValue(&self.MakePath(CGROUP2_KEY), "memory.max")?;
+            let limStr = limStr.trim();
+            if limStr == "max" {
+                return Ok(u64::MAX);
+            }
+
+            return limStr
+                .parse::<u64>()
+                .map_err(|_| Error::Common(format!("MemoryLimit: can't parse {}", limStr)));
+        }
+
         let path = self.MakePath("memory");
         let limStr = GetValue(&path, "memory.limit_in_bytes")?;
         let limStr = limStr.trim();
@@ -423,6 +472,16 @@ impl Cgroup {
     }
 
     pub fn MakePath(&self, controllerName: &str) -> String {
+        if self.V2 {
+            // all the controllers share the same directory in the unified hierarchy
+            let path = match self.Parents.get("") {
+                None => self.Name.to_string(),
+                Some(parent) => Join(parent, &self.Name),
+            };
+
+            return Join(CGROUP_ROOT, &path);
+        }
+
         let mut path = self.Name.to_string();
         match self.Parents.get(controllerName) {
             None => (),
@@ -15,19 +15,130 @@
 use std::fs::File;
 use std::io::prelude::*;
 use std::io::SeekFrom;
+use std::path::Path;
 
 use crate::qlib::common::*;
+use crate::qlib::path::*;
 use crate::runc::oci::*;
+use crate::runc::specutils::specutils::MkdirAll;
 
 use super::cgroup::*;
 
 pub const SUBTREE_CONTROL: &str = "cgroup.subtree_control";
 pub const CONTROLLERS_FILE: &str = "cgroup.controllers";
+pub const PROCS_FILE: &str = "cgroup.procs";
 pub const CGROUP2_KEY: &str = "cgroup2";
 
+// leaf child the processes of a delegated cgroup are moved into before
+// the cgroup enables controllers for its children
+pub const LEAF_CGROUP: &str = "init";
+
 // https://www.kernel.org/doc/html/latest/admin-guide/cgroup-v2.html
 pub const DEFAULT_PERIOD: u64 = 100000;
 
+// controllers the sandbox cgroup enables in the unified hierarchy
+pub const V2_CONTROLLERS: [&str; 3] = ["cpu", "cpuset", "memory"];
+
+// IsCgroupV2 returns whether the host mounts the cgroup v2 unified hierarchy
+pub fn IsCgroupV2() -> bool {
+    return Path::new(&Join(CGROUP_ROOT, CONTROLLERS_FILE)).exists();
+}
+
+// AvailableControllers returns the controllers listed in cgroup.controllers of path
+pub fn AvailableControllers(path: &str) -> Result<Vec<String>> {
+    let controllers = GetValue(path, CONTROLLERS_FILE)?;
+    return Ok(controllers
+        .split_whitespace()
+        .map(|c| c.to_string())
+        .collect());
+}
+
+// SubtreeControl returns the cgroup.subtree_control value enabling the sandbox
+// controllers in 'available', empty if none of them is available.
+pub fn SubtreeControl(available: &[String]) -> String {
+    let mut enable = Vec::new();
+    for c in &V2_CONTROLLERS {
+        if available.iter().any(|a| a == c) {
+            enable.push(format!("+{}", c));
+        }
+    }
+
+    return enable.join(" ");
+}
+
+// ParseProcs returns the pids listed in a cgroup.procs content
+pub fn ParseProcs(content: &str) -> Vec<String> {
+    return content.split_whitespace().map(|p| p.to_string()).collect();
+}
+
+// MoveProcsToLeaf moves the processes of path into its leaf child. Because of
+// the no internal process rule, a cgroup holding processes can't enable
+// controllers for its children, e.g. the caller's own cgroup in rootless mode.
+pub fn MoveProcsToLeaf(path: &str) -> Result<()> {
+    let procs = ParseProcs(&GetValue(path, PROCS_FILE)?);
+    if procs.len() == 0 {
+        return Ok(());
+    }
+
+    let leaf = Join(path, LEAF_CGROUP);
+    MkdirAll(&leaf)?;
+    // the kernel takes one pid per write
+    for pid in &procs {
+        SetValue(&leaf, PROCS_FILE, pid)?;
+    }
+
+    return Ok(());
+}
+
+// EnableControllers enables the sandbox controllers for the children of path.
+// Only the controllers delegated to path can be enabled.
+pub fn EnableControllers(path: &str) -> Result<()> {
+    let enable = SubtreeControl(&AvailableControllers(path)?);
+    if enable.len() == 0 {
+        return Ok(());
+    }
+
+    MoveProcsToLeaf(path)?;
+    return SetValue(path, SUBTREE_CONTROL, &enable);
+}
+
+impl Cgroup {
+    // InstallV2 creates the cgroup in the unified hierarchy and applies 'res' to the
+    // controllers enabled for it. A pre-created cgroup is used as is.
+    pub fn InstallV2(&mut self, res: &Option<LinuxResources>) -> Result<()> {
+        let path = self.MakePath(CGROUP2_KEY);
+        if Path::new(&path).exists() {
+            info!("Using pre-created cgroup {}", &self.Name);
+            return Ok(());
+        }
+
+        info!("Creating cgroup v2 {}", &path);
+        EnableControllers(&Dir(&path))?;
+        MkdirAll(&path)?;
+        self.Own = true;
+
+        let mut cgroupCleanup = CgroupCleanup {
+            cgroup: self,
+            enable: true,
+        };
+
+        let available = AvailableControllers(&path)?;
+        let controllers: [(&str, &dyn Controller); 3] =
+            [("cpu", &Cpu2 {}), ("cpuset", &CpuSet2 {}), ("memory", &Memory2 {})];
+        for (name, controller) in &controllers {
+            if !available.iter().any(|a| a == name) {
+                warn!("cgroup controller {} is not available in {}", name, &path);
+                continue;
+            }
+
+            controller.Set(res, &path)?;
+        }
+
+        cgroupCleanup.enable = false;
+        return Ok(());
+    }
+}
+
 /* pub trait Controllerv2 : Controller {
     fn generateProperties(spec: &LinuxResources)
 } */
@@ -336,3 +447,54 @@ pub fn ParseUint(s: &str, base: u32, bitSize: u32) -> Result<u64> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn TestSubtreeControl() {
+        let available: Vec<String> = ["cpuset", "cpu", "io", "memory", "pids"]
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        assert_eq!(SubtreeControl(&available), "+cpu +cpuset +memory");
+
+        let available = vec!["memory".to_string(), "io".to_string()];
+        assert_eq!(SubtreeControl(&available), "+memory");
+
+        let available = vec!["io".to_string(), "pids".to_string()];
+        assert_eq!(SubtreeControl(&available), "");
+    }
+
+    #[test]
+    fn TestParseProcs() {
+        assert_eq!(ParseProcs("12\n345\n"), vec!["12", "345"]);
+        assert_eq!(ParseProcs(""), Vec::<String>::new());
+    }
+
+    #[test]
+    fn TestMoveProcsToLeaf() {
+        let dir = Join(
+            &std::env::temp_dir().to_string_lossy(),
+            &format!("quark-cgroup-test-{}", std::process::id()),
+        );
+
+        // a cgroup without processes keeps no leaf child
+        let empty = Join(&dir, "empty");
+        MkdirAll(&empty).unwrap();
+        SetValue(&empty, PROCS_FILE, "").unwrap();
+        MoveProcsToLeaf(&empty).unwrap();
+        assert!(!Path::new(&Join(&empty, LEAF_CGROUP)).exists());
+
+        // the processes of a busy cgroup go to its leaf child
+        let busy = Join(&dir, "busy");
+        MkdirAll(&busy).unwrap();
+        SetValue(&busy, PROCS_FILE, "42\n").unwrap();
+        MoveProcsToLeaf(&busy).unwrap();
+        let leaf = Join(&busy, LEAF_CGROUP);
+        assert_eq!(GetValue(&leaf, PROCS_FILE).unwrap(), "42");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,71 @@
+// Copyright (c) 2021 Quark Container Authors / 2018 The gVisor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::string::String;
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+
+use super::super::super::qlib::common::*;
+use super::super::super::qlib::linux_def::*;
+use super::super::cmd::config::*;
+use super::super::container::container::*;
+use super::command::*;
+
+#[derive(Debug)]
+pub struct BalloonCmd {
+    pub id: String,
+    // the target guest memory size in MB
+    pub size: u64,
+}
+
+impl BalloonCmd {
+    pub fn Init(cmd_matches: &ArgMatches) -> Result<Self> {
+        let size = match cmd_matches.value_of("size").unwrap().parse::<u64>() {
+            Err(e) => return Err(Error::Common(format!("invalid size: {:?}", e))),
+            Ok(size) => size,
+        };
+
+        return Ok(Self {
+            id: cmd_matches.value_of("id").unwrap().to_string(),
+            size: size,
+        });
+    }
+
+    pub fn SubCommand<'a, 'b>(common: &CommonArgs<'a, 'b>) -> App<'a, 'b> {
+        return SubCommand::with_name("balloon")
+            .setting(AppSettings::ColoredHelp)
+            .arg(&common.id_arg)
+            .arg(
+                Arg::with_name("size")
+                    .help("target guest memory size in MB, 0 only shows the balloon info")
+                    .required(true)
+                    .takes_value(true)
+                    .long("size")
+                    .short("s"),
+            )
+            .about("balloon shrinks or grows the memory usable by a sandbox");
+    }
+
+    pub fn Run(&self, gCfg: &GlobalConfig) -> Result<()> {
+        let container = Container::Load(&gCfg.RootDir, &self.id)?;
+        let info = container.MemBalloon(self.size * MemoryDef::ONE_MB)?;
+
+        println!(
+            "total {} MB, balloon {} MB, free {} MB",
+            info.TotalSize / MemoryDef::ONE_MB,
+            info.BalloonSize / MemoryDef::ONE_MB,
+            info.FreeSize / MemoryDef::ONE_MB
+        );
+        return Ok(());
+    }
+}
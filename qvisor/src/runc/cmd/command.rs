@@ -16,12 +16,15 @@ use std::env;
 
 use clap::{App, AppSettings, Arg};
 
+use super::super::super::print::SetLogContainer;
 use super::super::super::qlib::common::*;
+use super::balloon::*;
 use super::boot::*;
 use super::cmd::*;
 use super::config;
 use super::config::*;
 use super::create::*;
+use super::debug::*;
 use super::delete::*;
 use super::exec::*;
 use super::kill::*;
@@ -35,6 +38,8 @@ use super::start::*;
 use super::state::*;
 use super::wait::*;
 
+pub const DEFAULT_ROOT_DIR: &str = "/run/qvisor";
+
 fn id_validator(val: String) -> core::result::Result<(), String> {
     if val.contains("..") || val.contains('/') {
         return Err(format!("id {} may cannot contain '..' or '/'", val));
@@ -161,9 +166,17 @@ pub fn Parse() -> Result<Arguments> {
                 .long("log-format")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("rootless")
+                .help("run the sandbox as an unprivileged user (auto, true or false)")
+                .default_value("auto")
+                .possible_values(&["auto", "true", "false"])
+                .long("rootless")
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("r")
-                .default_value("/run/qvisor")
+                .default_value(DEFAULT_ROOT_DIR)
                 .help("Dir for state")
                 .long("root")
                 .short("r")
@@ -184,6 +197,8 @@ pub fn Parse() -> Result<Arguments> {
         .subcommand(DeleteCmd::SubCommand(&common))
         .subcommand(StateCmd::SubCommand(&common))
         .subcommand(SandboxCmd::SubCommand(&common))
+        .subcommand(BalloonCmd::SubCommand(&common))
+        .subcommand(DebugCmd::SubCommand(&common))
         .get_matches_from(get_args());
 
     let level = match matches.occurrences_of("v") {
@@ -200,7 +215,19 @@ pub fn Parse() -> Result<Arguments> {
     // create empty log file to avoid warning
     let logFile = matches.value_of("log").unwrap_or_default();
 
-    let rootDir = matches.value_of("r").unwrap().to_string();
+    let rootless = match matches.value_of("rootless").unwrap() {
+        "true" => true,
+        "false" => false,
+        _ => unsafe { libc::geteuid() != 0 },
+    };
+
+    let mut rootDir = matches.value_of("r").unwrap().to_string();
+    // an unprivileged user can't write /run, keep the state under the user runtime dir
+    if rootless && matches.occurrences_of("r") == 0 {
+        if let Ok(dir) = env::var("XDG_RUNTIME_DIR") {
+            rootDir = format!("{}/qvisor", dir);
+        }
+    }
     /*debug!("ensuring railcar state dir {}", &state_dir);
     let chain = || format!("ensuring railcar state dir {} failed", &state_dir);
     create_dir_all(&state_dir).chain_err(chain)?;*/
@@ -212,8 +239,16 @@ pub fn Parse() -> Result<Arguments> {
         FileAccess: config::FileAccessType::default(),
         Network: config::NetworkType::default(),
         SystemdCgroup: systemdCgroup,
+        Rootless: rootless,
     };
 
+    // the host log records carry the container which the command runs for
+    if let (_, Some(cmd_matches)) = matches.subcommand() {
+        if let Some(id) = cmd_matches.value_of("id") {
+            SetLogContainer(id);
+        }
+    }
+
     let args = match matches.subcommand() {
         ("run", Some(cmd_matches)) => Arguments {
             config: gConfig,
@@ -275,6 +310,14 @@ pub fn Parse() -> Result<Arguments> {
             config: gConfig,
             cmd: Command::SandboxCmd(SandboxCmd::Init(&cmd_matches)?),
         },
+        ("balloon", Some(cmd_matches)) => Arguments {
+            config: gConfig,
+            cmd: Command::BalloonCmd(BalloonCmd::Init(&cmd_matches)?),
+        },
+        ("debug", Some(cmd_matches)) => Arguments {
+            config: gConfig,
+            cmd: Command::DebugCmd(DebugCmd::Init(&cmd_matches)?),
+        },
         // We should never reach here because clap already enforces this
         _ => panic!("command not recognized"),
     };
@@ -305,6 +348,8 @@ pub enum Command {
     DeleteCmd(DeleteCmd),
     StateCmd(StateCmd),
     SandboxCmd(SandboxCmd),
+    BalloonCmd(BalloonCmd),
+    DebugCmd(DebugCmd),
 }
 
 pub fn Run(args: &mut Arguments) -> Result<()> {
@@ -324,5 +369,7 @@ pub fn Run(args: &mut Arguments) -> Result<()> {
         Command::DeleteCmd(cmd) => return cmd.Run(&mut args.config),
         Command::StateCmd(cmd) => return cmd.Run(&mut args.config),
         Command::SandboxCmd(cmd) => return cmd.Run(&mut args.config),
+        Command::BalloonCmd(cmd) => return cmd.Run(&mut args.config),
+        Command::DebugCmd(cmd) => return cmd.Run(&mut args.config),
     }
 }
@@ -57,6 +57,10 @@ pub struct GlobalConfig {
     pub Network: NetworkType,
 
     pub SystemdCgroup: bool,
+
+    // Rootless runs the sandbox as an unprivileged user inside a user namespace.
+    #[serde(default)]
+    pub Rootless: bool,
 }
 
 impl Default for GlobalConfig {
@@ -68,6 +72,7 @@ impl Default for GlobalConfig {
             FileAccess: FileAccessType::default(),
             Network: NetworkType::default(),
             SystemdCgroup: false,
+            Rootless: false,
         };
     }
 }
@@ -81,6 +86,7 @@ impl GlobalConfig {
             FileAccess: self.FileAccess,
             Network: self.Network,
             SystemdCgroup: self.SystemdCgroup,
+            Rootless: self.Rootless,
         };
     }
 }
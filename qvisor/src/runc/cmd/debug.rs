@@ -0,0 +1,90 @@
+// Copyright (c) 2021 Quark Container Authors / 2018 The gVisor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::string::String;
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+
+use super::super::super::qlib::common::*;
+use super::super::super::qlib::config::{DebugLevel, LogFormat};
+use super::super::super::qlib::control_msg::*;
+use super::super::cmd::config::GlobalConfig;
+use super::super::container::container::*;
+use super::command::*;
+
+#[derive(Debug)]
+pub struct DebugCmd {
+    pub id: String,
+    pub level: Option<DebugLevel>,
+    pub format: Option<LogFormat>,
+}
+
+impl DebugCmd {
+    pub fn Init(cmd_matches: &ArgMatches) -> Result<Self> {
+        let level = match cmd_matches.value_of("log-level") {
+            None => None,
+            Some("off") => Some(DebugLevel::Off),
+            Some("error") => Some(DebugLevel::Error),
+            Some("warn") => Some(DebugLevel::Warn),
+            Some("info") => Some(DebugLevel::Info),
+            Some("debug") => Some(DebugLevel::Debug),
+            Some("trace") => Some(DebugLevel::Trace),
+            Some(l) => return Err(Error::Common(format!("invalid log level {}", l))),
+        };
+
+        let format = match cmd_matches.value_of("log-format") {
+            None => None,
+            Some("text") => Some(LogFormat::Text),
+            Some("json") => Some(LogFormat::Json),
+            Some(f) => return Err(Error::Common(format!("invalid log format {}", f))),
+        };
+
+        return Ok(Self {
+            id: cmd_matches.value_of("id").unwrap().to_string(),
+            level: level,
+            format: format,
+        });
+    }
+
+    pub fn SubCommand<'a, 'b>(common: &CommonArgs<'a, 'b>) -> App<'a, 'b> {
+        return SubCommand::with_name("debug")
+            .setting(AppSettings::ColoredHelp)
+            .arg(&common.id_arg)
+            .arg(
+                Arg::with_name("log-level")
+                    .help("debug level of the sandbox log")
+                    .possible_values(&["off", "error", "warn", "info", "debug", "trace"])
+                    .takes_value(true)
+                    .long("log-level"),
+            )
+            .arg(
+                Arg::with_name("log-format")
+                    .help("format of the sandbox log records")
+                    .possible_values(&["text", "json"])
+                    .takes_value(true)
+                    .long("log-format"),
+            )
+            .about("debug shows or changes the log settings of a running sandbox");
+    }
+
+    pub fn Run(&self, gCfg: &GlobalConfig) -> Result<()> {
+        let container = Container::Load(&gCfg.RootDir, &self.id)?;
+        let info = container.SetLogLevel(LogLevelArgs {
+            Level: self.level,
+            Format: self.format,
+        })?;
+
+        println!("log level {:?}, log format {:?}", info.Level, info.Format);
+        return Ok(());
+    }
+}
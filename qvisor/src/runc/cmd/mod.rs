@@ -12,11 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod balloon;
 pub mod boot;
 pub mod cmd;
 pub mod command;
 pub mod config;
 pub mod create;
+pub mod debug;
 pub mod delete;
 pub mod exec;
 pub mod kill;
@@ -517,37 +517,12 @@ impl Container {
                 // Create and join cgroup before processes are created to ensure they are
                 // part of the cgroup from the start (and all children processes).
 
-                let mut cg: Option<Cgroup> = if crate::QUARK_CONFIG.lock().DisableCgroup {
-                    None
-                } else {
-                    match Cgroup::New(&c.Spec) {
-                        Err(e) => {
-                            c.Destroy()?;
-                            return Err(e);
-                        }
-                        Ok(cg) => cg,
-                    }
-                };
-                if cg.is_some() {
-                    let ret = cg
-                        .as_mut()
-                        .unwrap()
-                        .Install(&c.Spec.linux.as_ref().unwrap().resources);
-                    match ret {
-                        Err(e) => {
-                            c.Destroy()?;
-                            return Err(e);
-                        }
-                        Ok(_) => (),
-                    }
-                }
-
-                let restore = match &cg {
-                    Some(ref cgroup) => {
-                        let restore = cgroup.Join()?;
-                        Some(restore)
+                let (cg, restore) = match SetupCgroup(&c.Spec, conf.Rootless) {
+                    Err(e) => {
+                        c.Destroy()?;
+                        return Err(e);
                     }
-                    None => None,
+                    Ok(r) => r,
                 };
 
                 let ret = Sandbox::New(
@@ -732,37 +707,12 @@ impl Container {
 
                 // Create and join cgroup before processes are created to ensure they are
                 // part of the cgroup from the start (and all children processes).
-                let mut cg: Option<Cgroup> = if crate::QUARK_CONFIG.lock().DisableCgroup {
-                    None
-                } else {
-                    match Cgroup::New(&c.Spec) {
-                        Err(e) => {
-                            c.Destroy()?;
-                            return Err(e);
-                        }
-                        Ok(cg) => cg,
-                    }
-                };
-                if cg.is_some() {
-                    let ret = cg
-                        .as_mut()
-                        .unwrap()
-                        .Install(&c.Spec.linux.as_ref().unwrap().resources);
-                    match ret {
-                        Err(e) => {
-                            c.Destroy()?;
-                            return Err(e);
-                        }
-                        Ok(_) => (),
-                    }
-                }
-
-                let restore = match &cg {
-                    Some(ref cgroup) => {
-                        let restore = cgroup.Join()?;
-                        Some(restore)
+                let (cg, restore) = match SetupCgroup(&c.Spec, conf.Rootless) {
+                    Err(e) => {
+                        c.Destroy()?;
+                        return Err(e);
                     }
-                    None => None,
+                    Ok(r) => r,
                 };
 
                 let ret = Sandbox::New1(id, action, conf, bundleDir, io, userlog, cg, pivot);
@@ -923,6 +873,36 @@ impl Container {
         return self.Sandbox.as_ref().unwrap().Processes(&self.ID);
     }
 
+    // MemBalloon shrinks or grows the memory usable by the sandbox to targetSize bytes.
+    pub fn MemBalloon(&self, targetSize: u64) -> Result<MemBalloonInfo> {
+        self.RequireStatus("resize memory of", &[Status::Running, Status::Paused])?;
+        return self
+            .Sandbox
+            .as_ref()
+            .unwrap()
+            .MemBalloon(&self.ID, targetSize);
+    }
+
+    // SetLogLevel changes the debug level and the log format of the running sandbox.
+    pub fn SetLogLevel(&self, args: LogLevelArgs) -> Result<LogLevelInfo> {
+        self.RequireStatus("change log level of", &[Status::Running, Status::Paused])?;
+        return self
+            .Sandbox
+            .as_ref()
+            .unwrap()
+            .SetLogLevel(&self.ID, args);
+    }
+
+    // VcpuResize changes the count of vcpus taking tasks in the sandbox.
+    pub fn VcpuResize(&self, count: usize) -> Result<VcpuInfo> {
+        self.RequireStatus("resize vcpus of", &[Status::Running, Status::Paused])?;
+        return self
+            .Sandbox
+            .as_ref()
+            .unwrap()
+            .VcpuResize(&self.ID, count);
+    }
+
     // Start starts running the containerized process inside the sandbox.
     pub fn Start(&mut self) -> Result<()> {
         info!("Start container {}", &self.ID);
@@ -1166,6 +1146,47 @@ impl Container {
     }
 }
 
+// SetupCgroup creates and joins the cgroup of the sandbox, it returns the cgroup
+// and the function to restore the original cgroup of the current process.
+// A rootless sandbox can only use the cgroup when the subtree is delegated to the
+// user, otherwise it runs without cgroup as with DisableCgroup.
+pub fn SetupCgroup(
+    spec: &Spec,
+    rootless: bool,
+) -> Result<(Option<Cgroup>, Option<Box<dyn Fn()>>)> {
+    if crate::QUARK_CONFIG.lock().DisableCgroup {
+        return Ok((None, None));
+    }
+
+    let mut cg = match Cgroup::New(spec, rootless)? {
+        None => return Ok((None, None)),
+        Some(cg) => cg,
+    };
+
+    let ret = cg
+        .Install(&spec.linux.as_ref().unwrap().resources)
+        .and_then(|_| cg.Join());
+    match ret {
+        Ok(restore) => {
+            let restore: Box<dyn Fn()> = Box::new(restore);
+            return Ok((Some(cg), Some(restore)));
+        }
+        Err(e) => {
+            if !rootless {
+                cg.Uninstall();
+                return Err(e);
+            }
+
+            warn!(
+                "cgroup {} is not delegated to the user, run without cgroup: {:?}",
+                &cg.Name, e
+            );
+            cg.Uninstall();
+            return Ok((None, None));
+        }
+    }
+}
+
 pub fn runInCgroup(cg: &Option<Cgroup>, mut f: impl FnMut() -> Result<()>) -> Result<()> {
     if cg.is_none() {
         return f();
@@ -59,22 +59,88 @@ impl Args {
                 None => return 0,
                 Some(resources) => match &resources.cpu {
                     None => return 0,
-                    Some(cpu) => {
-                        let quota = match cpu.quota {
-                            None => return 0,
-                            Some(q) => q,
-                        };
-
-                        let period = match cpu.period {
-                            None => return 0,
-                            Some(p) => p,
-                        };
-
-                        let count = (quota as u64 + period - 1) / period;
-                        return count as usize;
-                    }
+                    Some(cpu) => return CpuCountFromQuota(cpu),
                 },
             },
         };
     }
 }
+
+// CpuCountFromQuota returns the cpu count needed by the cpu quota, 0 means no limit
+pub fn CpuCountFromQuota(cpu: &LinuxCPU) -> usize {
+    let quota = match cpu.quota {
+        None => return 0,
+        Some(q) => q,
+    };
+
+    let period = match cpu.period {
+        None => return 0,
+        Some(p) => p,
+    };
+
+    // -1 is no limit
+    if quota <= 0 || period == 0 {
+        return 0;
+    }
+
+    let count = (quota as u64 + period - 1) / period;
+    return count as usize;
+}
+
+// MemoryLimitFromSpec returns the container memory limit in bytes, 0 means no limit
+pub fn MemoryLimitFromSpec(spec: &Spec) -> u64 {
+    let resources = match &spec.linux {
+        None => return 0,
+        Some(linux) => match &linux.resources {
+            None => return 0,
+            Some(resources) => resources,
+        },
+    };
+
+    if resources.disable_oom_killer {
+        return 0;
+    }
+
+    match &resources.memory {
+        None => return 0,
+        Some(memory) => match memory.limit {
+            Some(limit) if limit > 0 => return limit as u64,
+            _ => return 0,
+        },
+    }
+}
+
+pub fn OOMScoreAdjFromSpec(spec: &Spec) -> i32 {
+    match &spec.linux {
+        None => return 0,
+        Some(linux) => match &linux.resources {
+            None => return 0,
+            Some(resources) => return resources.oom_score_adj.unwrap_or(0),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn Cpu(quota: Option<i64>, period: Option<u64>) -> LinuxCPU {
+        return LinuxCPU {
+            quota: quota,
+            period: period,
+            ..Default::default()
+        };
+    }
+
+    #[test]
+    fn TestCpuCountFromQuota() {
+        assert_eq!(CpuCountFromQuota(&Cpu(None, Some(100000))), 0);
+        assert_eq!(CpuCountFromQuota(&Cpu(Some(100000), None)), 0);
+        assert_eq!(CpuCountFromQuota(&Cpu(Some(-1), Some(100000))), 0);
+        assert_eq!(CpuCountFromQuota(&Cpu(Some(100000), Some(0))), 0);
+        assert_eq!(CpuCountFromQuota(&Cpu(Some(100000), Some(100000))), 1);
+        // a partial cpu needs a whole vcpu
+        assert_eq!(CpuCountFromQuota(&Cpu(Some(50000), Some(100000))), 1);
+        assert_eq!(CpuCountFromQuota(&Cpu(Some(250000), Some(100000))), 3);
+    }
+}
@@ -70,6 +70,16 @@ use super::vm::*;
 
 pub const QUARK_SANDBOX_ROOT_PATH: &str = "/var/lib/quark/";
 
+// SandboxRootPath returns the dir holding the sandbox root dirs. An unprivileged
+// user can't write /var/lib, a rootless sandbox keeps it under the state root dir.
+pub fn SandboxRootPath(gCfg: &GlobalConfig) -> String {
+    if gCfg.Rootless {
+        return Join(&gCfg.RootDir, "sandbox");
+    }
+
+    return QUARK_SANDBOX_ROOT_PATH.to_string();
+}
+
 pub struct NSRestore {
     pub fd: i32,
     pub flag: i32,
@@ -156,7 +166,7 @@ impl SandboxProcess {
             CCond: Cond::New()?,
             PCond: Cond::New()?,
             Rootfs: "".to_string(),
-            SandboxRootDir: Join(QUARK_SANDBOX_ROOT_PATH, id),
+            SandboxRootDir: Join(&SandboxRootPath(gCfg), id),
             TaskSocket: None,
         };
 
@@ -186,7 +196,18 @@ impl SandboxProcess {
 
         PrepareHandler().unwrap();
 
-        let kvmfd = Kvm::open_with_cloexec(false).expect("can't open kvm");
+        let kvmfd = match Kvm::open_with_cloexec(false) {
+            Ok(fd) => fd,
+            Err(e) => {
+                if self.conf.Rootless {
+                    panic!(
+                        "can't open /dev/kvm: {:?}, a rootless sandbox needs the user in the kvm group",
+                        e
+                    );
+                }
+                panic!("can't open kvm: {:?}", e);
+            }
+        };
         let mut args = Args::default();
         args.ID = id.to_string();
         args.KvmFd = kvmfd;
@@ -194,7 +215,7 @@ impl SandboxProcess {
         args.AutoStart = self.action == RunAction::Run;
         args.BundleDir = self.bundleDir.to_string();
         args.Pivot = self.pivot;
-        args.Rootfs = Join(QUARK_SANDBOX_ROOT_PATH, id.as_str());
+        args.Rootfs = self.SandboxRootDir.to_string();
         args.ControlSock = controlSock;
         args.RDMASvcCliSock = rdmaSvcCliSock;
         if taskSockFd > 0 {
@@ -231,6 +252,19 @@ impl SandboxProcess {
             Ok(()) => (),
             Err(_e) => return Err(Error::Common(String::from("failed creating directory"))),
         }
+
+        if self.conf.Rootless {
+            if self.TaskSocket.is_some() {
+                return Err(Error::Common(
+                    "rootless sandbox doesn't support the task service".to_string(),
+                ));
+            }
+
+            // an unprivileged user can't mount in the host mount namespace, the bind
+            // mounts are done by InitRootfs in the mount namespace of the sandbox.
+            return Ok(());
+        }
+
         let rbindFlags = libc::MS_REC | libc::MS_BIND;
 
         // convert sandbox Root Dir to a mount point
@@ -328,8 +362,34 @@ impl SandboxProcess {
         //todo: handle mount ns separated, to avoid crash OS when pivot root
         cf |= LinuxNamespaceType::mount as i32;
 
+        // a rootless sandbox gets the privilege to mount and create namespaces from
+        // its own user namespace
+        if self.conf.Rootless {
+            cf |= LinuxNamespaceType::user as i32;
+        }
+
         if cf & LinuxNamespaceType::user as i32 != 0 {
             self.UserNS = true;
+            let linux = spec.linux.as_ref().unwrap();
+            self.UidMappings = linux.uid_mappings.to_vec();
+            self.GidMappings = linux.gid_mappings.to_vec();
+
+            // an unprivileged user can only map its own uid/gid, to root in the sandbox
+            if self.conf.Rootless && self.UidMappings.len() == 0 {
+                self.UidMappings.push(LinuxIDMapping {
+                    host_id: unsafe { libc::geteuid() },
+                    container_id: 0,
+                    size: 1,
+                });
+            }
+
+            if self.conf.Rootless && self.GidMappings.len() == 0 {
+                self.GidMappings.push(LinuxIDMapping {
+                    host_id: unsafe { libc::getegid() },
+                    container_id: 0,
+                    size: 1,
+                });
+            }
         }
 
         self.CloneFlags = cf;
@@ -431,7 +491,11 @@ impl SandboxProcess {
                 return Err(Error::Common(msg));
             }
             let (flags, data) = parse_mount(m);
-            if m.typ == "cgroup" {
+            if self.conf.Rootless && (m.typ == "proc" || m.typ == "mqueue") {
+                // the sandbox doesn't own a pid or ipc namespace, qkernel provides
+                // them for the containers
+                continue;
+            } else if m.typ == "cgroup" {
                 //mount_cgroups(m, rootfs, flags, &data, &linux.mount_label, cpath)?;
                 // won't mount cgroup
                 continue;
@@ -458,7 +522,8 @@ impl SandboxProcess {
         }
 
         //default_symlinks()?;
-        create_devices(&linux.devices, false)?;
+        // mknod is not allowed in a user namespace, bind the host devices instead
+        create_devices(&linux.devices, self.conf.Rootless)?;
         //ensure_ptmx()?;
 
         if Util::Chdir(olddir.as_path().to_str().unwrap()) == -1 {
@@ -878,14 +943,18 @@ impl SandboxProcess {
     }
 
     pub fn Parent(&self, child: i32) -> Result<()> {
-        let linux = self.spec.linux.as_ref().unwrap();
-
         self.CCond.Wait()?;
 
         if self.UserNS {
             // write uid/gid map
-            WriteIDMapping(&format!("/proc/{}/uid_map", child), &linux.uid_mappings)?;
-            WriteIDMapping(&format!("/proc/{}/gid_map", child), &linux.gid_mappings)?;
+            WriteIDMapping(&format!("/proc/{}/uid_map", child), &self.UidMappings)?;
+            if self.conf.Rootless {
+                // an unprivileged process can write gid_map only after setgroups is denied
+                let path = format!("/proc/{}/setgroups", child);
+                fs::write(&path, "deny")
+                    .map_err(|e| Error::IOError(format!("write {} fail {:?}", path, e)))?;
+            }
+            WriteIDMapping(&format!("/proc/{}/gid_map", child), &self.GidMappings)?;
         }
 
         self.PCond.Notify()?;
@@ -15,6 +15,7 @@
 //use kvm_bindings::{kvm_userspace_memory_region, KVM_CAP_X86_DISABLE_EXITS, kvm_enable_cap, KVM_X86_DISABLE_EXITS_HLT, KVM_X86_DISABLE_EXITS_MWAIT};
 use alloc::sync::Arc;
 use core::sync::atomic::{AtomicI32, AtomicU64, Ordering};
+use std::collections::HashMap;
 use std::os::unix::io::AsRawFd;
 use std::os::unix::io::FromRawFd;
 use std::thread;
@@ -35,6 +36,7 @@ use super::super::super::kvm_vcpu_aarch64::KVMVcpuInit;
 use super::super::super::print::LOG;
 use super::super::super::qlib::addr;
 use super::super::super::qlib::common::*;
+use super::super::super::qlib::config::LogFormat;
 use super::super::super::qlib::kernel::kernel::futex;
 use super::super::super::qlib::kernel::kernel::timer;
 use super::super::super::qlib::kernel::task;
@@ -61,6 +63,52 @@ use super::super::super::{
 };
 
 pub const SANDBOX_UID_NAME : &str = "io.kubernetes.cri.sandbox-uid";
+pub const INGRESS_BANDWIDTH_ANNOTATION: &str = "kubernetes.io/ingress-bandwidth";
+pub const EGRESS_BANDWIDTH_ANNOTATION: &str = "kubernetes.io/egress-bandwidth";
+
+// parse the kubernetes bandwidth quantity such as "10M" or "1Gi" in bits per second,
+// return the bytes per second
+pub fn ParseBandwidth(quantity: &str) -> Option<u64> {
+    let quantity = quantity.trim();
+    let idx = quantity
+        .find(|c: char| c.is_ascii_alphabetic())
+        .unwrap_or(quantity.len());
+    let (num, suffix) = quantity.split_at(idx);
+    let num: f64 = num.parse().ok()?;
+    let multiplier: f64 = match suffix {
+        "" => 1.0,
+        "k" => 1e3,
+        "M" => 1e6,
+        "G" => 1e9,
+        "T" => 1e12,
+        "Ki" => 1024.0,
+        "Mi" => 1024.0 * 1024.0,
+        "Gi" => 1024.0 * 1024.0 * 1024.0,
+        "Ti" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+
+    if num <= 0.0 {
+        return None;
+    }
+
+    return Some((num * multiplier / 8.0) as u64);
+}
+
+// the (ingress, egress) bytes per second of the pod annotations, 0 for no limit
+pub fn PodBandwidthLimit(annotations: &HashMap<String, String>) -> (u64, u64) {
+    let mut limits = [0; 2];
+    for (i, name) in [INGRESS_BANDWIDTH_ANNOTATION, EGRESS_BANDWIDTH_ANNOTATION].iter().enumerate() {
+        if let Some(quantity) = annotations.get(*name) {
+            match ParseBandwidth(quantity) {
+                None => error!("invalid pod annotation {}: {}", name, quantity),
+                Some(limit) => limits[i] = limit,
+            }
+        }
+    }
+
+    return (limits[0], limits[1]);
+}
 
 lazy_static! {
     static ref EXIT_STATUS: AtomicI32 = AtomicI32::new(-1);
@@ -231,6 +279,8 @@ impl VirtualMachine {
         
         let syncPrint = sharespace.config.read().SyncPrint();
         super::super::super::print::SetSyncPrint(syncPrint);
+        let jsonFormat = sharespace.config.read().LogFormat == LogFormat::Json;
+        super::super::super::print::SetJsonFormat(jsonFormat);
     }
 
 
@@ -274,8 +324,18 @@ impl VirtualMachine {
             }
         }
 
+        let (ingressBandwidth, egressBandwidth) = PodBandwidthLimit(&args.Spec.annotations);
+
         let cpuCount = cpuCount.max(2); // minimal 2 cpus
 
+        // with vcpu hotplug, all the vcpus are created and the ones above cpu quota are parked
+        let onlineCpuCount = cpuCount;
+        let cpuCount = if QUARK_CONFIG.lock().VcpuHotplug {
+            (VMSpace::VCPUCount() - reserveCpuCount).max(cpuCount)
+        } else {
+            cpuCount
+        };
+
         VMS.lock().vcpuCount = cpuCount; //VMSpace::VCPUCount();
         VMS.lock().RandomVcpuMapping();
         let kernelMemRegionSize = QUARK_CONFIG.lock().KernelMemSize;
@@ -385,6 +445,11 @@ impl VirtualMachine {
         }
 
         Self::InitShareSpace(cpuCount, controlSock, rdmaSvcCliSock, podId);
+        *SHARESPACE.sandboxId.lock() = ROOT_CONTAINER_ID.lock().clone();
+        SHARESPACE
+            .podBandwidth
+            .SetLimit(ingressBandwidth, egressBandwidth);
+        SHARE_SPACE.scheduler.SetOnlineVcpuCnt(onlineCpuCount);
 
         let entry = elf.LoadKernel(Self::KERNEL_IMAGE)?;
         //let vdsoMap = VDSOMemMap::Init(&"/home/brad/rust/quark/vdso/vdso.so".to_string()).unwrap();
@@ -548,3 +613,22 @@ extern "C" fn handleSigusr1(_signal: i32) {
     }
     drop(vms);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn TestParseBandwidth() {
+        assert_eq!(ParseBandwidth("8"), Some(1));
+        assert_eq!(ParseBandwidth("10M"), Some(1_250_000));
+        assert_eq!(ParseBandwidth(" 1.5k "), Some(187));
+        assert_eq!(ParseBandwidth("1Gi"), Some(134_217_728));
+        assert_eq!(ParseBandwidth("2Ti"), Some(274_877_906_944));
+        assert_eq!(ParseBandwidth("10m"), None);
+        assert_eq!(ParseBandwidth("M"), None);
+        assert_eq!(ParseBandwidth("0"), None);
+        assert_eq!(ParseBandwidth("-1M"), None);
+        assert_eq!(ParseBandwidth(""), None);
+    }
+}
@@ -0,0 +1,216 @@
+// Copyright (c) 2021 Quark Container Authors / 2018 The gVisor Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::string::String;
+use std::fs;
+use std::{thread, time};
+
+use super::super::super::qlib::common::*;
+use super::super::super::qlib::linux_def::*;
+use super::sandbox::*;
+
+// the interval the balloon driver checks the memory pressure of the sandbox cgroup
+pub const BALLOON_DRIVER_INTERVAL: time::Duration = time::Duration::from_secs(5);
+// the max memory moved by the balloon in one check
+pub const BALLOON_DRIVER_STEP: u64 = 256 * MemoryDef::ONE_MB;
+pub const BALLOON_DRIVER_CHUNK: u64 = MemoryDef::PAGE_SIZE_2M;
+
+// BalloonDriver inflates the memory balloon of the sandbox when its cgroup is under memory
+// pressure, so that the free guest memory is given back to the host, and deflates it when
+// the pressure is gone and the cgroup has room under its memory limit.
+pub struct BalloonDriver {
+    pub sandbox: Sandbox,
+    // the cgroup v2 directory of the sandbox process
+    pub cgroupDir: String,
+    // the "some avg10" percent of the cgroup memory.pressure above which the balloon inflates
+    pub threshold: f64,
+}
+
+impl BalloonDriver {
+    pub fn Start(sandboxId: &str, pid: i32, threshold: u32) {
+        let cgroupDir = match Self::CgroupDir(pid) {
+            Err(e) => {
+                info!(
+                    "BalloonDriver sandbox {} has no cgroup v2 directory: {:?}",
+                    sandboxId, e
+                );
+                return;
+            }
+            Ok(dir) => dir,
+        };
+
+        let driver = Self {
+            sandbox: Sandbox {
+                ID: sandboxId.to_string(),
+                Pid: pid,
+                ..Default::default()
+            },
+            cgroupDir: cgroupDir,
+            threshold: threshold as f64,
+        };
+
+        thread::spawn(move || driver.Run());
+    }
+
+    // the cgroup v2 directory of the process, from the "0::<path>" line of /proc/<pid>/cgroup
+    pub fn CgroupDir(pid: i32) -> Result<String> {
+        let content = Self::ReadFile(&format!("/proc/{}/cgroup", pid))?;
+        for line in content.lines() {
+            if let Some(path) = line.strip_prefix("0::") {
+                return Ok(format!("/sys/fs/cgroup{}", path));
+            }
+        }
+
+        return Err(Error::Common(format!(
+            "process {} is not in a cgroup v2",
+            pid
+        )));
+    }
+
+    pub fn ReadFile(path: &str) -> Result<String> {
+        return fs::read_to_string(path)
+            .map_err(|e| Error::IOError(format!("read {} fail {:?}", path, e)));
+    }
+
+    // the "some avg10" of the memory.pressure:
+    // some avg10=0.00 avg60=0.00 avg300=0.00 total=0
+    pub fn ParsePressure(content: &str) -> Result<f64> {
+        for line in content.lines() {
+            let mut fields = line.split_whitespace();
+            if fields.next() != Some("some") {
+                continue;
+            }
+
+            for field in fields {
+                if let Some(avg10) = field.strip_prefix("avg10=") {
+                    return avg10
+                        .parse::<f64>()
+                        .map_err(|e| Error::Common(format!("invalid pressure {:?}", e)));
+                }
+            }
+        }
+
+        return Err(Error::Common(format!(
+            "invalid memory.pressure {}",
+            content
+        )));
+    }
+
+    pub fn Pressure(&self) -> Result<f64> {
+        let content = Self::ReadFile(&format!("{}/memory.pressure", &self.cgroupDir))?;
+        return Self::ParsePressure(&content);
+    }
+
+    // the memory the cgroup can still use under memory.high and memory.max,
+    // none when the cgroup has no memory limit
+    pub fn Room(&self) -> Result<Option<u64>> {
+        let mut limit = None;
+        for file in ["memory.high", "memory.max"] {
+            let content = Self::ReadFile(&format!("{}/{}", &self.cgroupDir, file))?;
+            let content = content.trim();
+            if content == "max" {
+                continue;
+            }
+
+            let value = content
+                .parse::<u64>()
+                .map_err(|e| Error::Common(format!("invalid {} {:?}", file, e)))?;
+            limit = Some(limit.map_or(value, |l: u64| l.min(value)));
+        }
+
+        let limit = match limit {
+            None => return Ok(None),
+            Some(limit) => limit,
+        };
+
+        let current = Self::ReadFile(&format!("{}/memory.current", &self.cgroupDir))?;
+        let current = current
+            .trim()
+            .parse::<u64>()
+            .map_err(|e| Error::Common(format!("invalid memory.current {:?}", e)))?;
+        return Ok(Some(limit.saturating_sub(current)));
+    }
+
+    pub fn Check(&self) -> Result<()> {
+        let pressure = self.Pressure()?;
+        let id = self.sandbox.ID.clone();
+        let info = self.sandbox.MemBalloon(&id, 0)?;
+
+        if pressure >= self.threshold {
+            // keep half of the free guest memory for the sandbox
+            let step = BALLOON_DRIVER_STEP.min(info.FreeSize / 2) & !(BALLOON_DRIVER_CHUNK - 1);
+            if step == 0 {
+                return Ok(());
+            }
+
+            let info = self.sandbox.MemBalloon(&id, info.TotalSize - step)?;
+            info!(
+                "BalloonDriver sandbox {} pressure {} inflate, balloon {} MB",
+                &id,
+                pressure,
+                info.BalloonSize / MemoryDef::ONE_MB
+            );
+            return Ok(());
+        }
+
+        // deflate below half of the threshold so that the balloon doesn't swing around it
+        if pressure >= self.threshold / 2.0 || info.BalloonSize == 0 {
+            return Ok(());
+        }
+
+        let step = BALLOON_DRIVER_STEP.min(info.BalloonSize);
+        match self.Room()? {
+            Some(room) if room < 2 * step => return Ok(()),
+            _ => (),
+        }
+
+        let info = self.sandbox.MemBalloon(&id, info.TotalSize + step)?;
+        info!(
+            "BalloonDriver sandbox {} pressure {} deflate, balloon {} MB",
+            &id,
+            pressure,
+            info.BalloonSize / MemoryDef::ONE_MB
+        );
+        return Ok(());
+    }
+
+    pub fn Run(&self) {
+        while self.sandbox.IsRunning() {
+            match self.Check() {
+                Ok(()) => (),
+                Err(e) => {
+                    error!(
+                        "BalloonDriver sandbox {} check fail with error {:?}",
+                        &self.sandbox.ID, e
+                    );
+                }
+            }
+
+            thread::sleep(BALLOON_DRIVER_INTERVAL);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn TestParsePressure() {
+        let content = "some avg10=12.50 avg60=3.00 avg300=0.00 total=100\n\
+                       full avg10=1.00 avg60=0.00 avg300=0.00 total=10\n";
+        assert_eq!(BalloonDriver::ParsePressure(content).unwrap(), 12.5);
+        assert!(BalloonDriver::ParsePressure("full avg10=1.00").is_err());
+    }
+}
@@ -40,6 +40,7 @@ use super::super::oci;
 use super::super::oci::*;
 use super::super::runtime::console::*;
 use super::super::runtime::fs::FsImageMounter;
+use super::super::runtime::loader::{MemoryLimitFromSpec, OOMScoreAdjFromSpec};
 use super::super::runtime::sandbox_process::*;
 use super::super::specutils::specutils;
 
@@ -344,6 +345,65 @@ impl Sandbox {
         }
     }
 
+    pub fn MemBalloon(&self, cid: &str, targetSize: u64) -> Result<MemBalloonInfo> {
+        info!(
+            "Resize memory of sandbox {} to {:x} for container {}",
+            self.ID, targetSize, cid
+        );
+        let client = self.SandboxConnect()?;
+
+        let req = UCallReq::MemBalloon(MemBalloonArgs {
+            TargetSize: targetSize,
+        });
+
+        let resp = client.Call(&req)?;
+        match resp {
+            UCallResp::MemBalloonResp(info) => Ok(info),
+            UCallResp::UCallRespErr(e) => Err(Error::Common(e)),
+            resp => {
+                panic!("MemBalloon get unknow resp {:?}", resp);
+            }
+        }
+    }
+
+    pub fn SetLogLevel(&self, cid: &str, args: LogLevelArgs) -> Result<LogLevelInfo> {
+        info!(
+            "Set log level of sandbox {} to {:?} for container {}",
+            self.ID, &args, cid
+        );
+        let client = self.SandboxConnect()?;
+
+        let req = UCallReq::SetLogLevel(args);
+
+        let resp = client.Call(&req)?;
+        match resp {
+            UCallResp::SetLogLevelResp(info) => Ok(info),
+            UCallResp::UCallRespErr(e) => Err(Error::Common(e)),
+            resp => {
+                panic!("SetLogLevel get unknow resp {:?}", resp);
+            }
+        }
+    }
+
+    pub fn VcpuResize(&self, cid: &str, count: usize) -> Result<VcpuInfo> {
+        info!(
+            "Resize vcpus of sandbox {} to {} for container {}",
+            self.ID, count, cid
+        );
+        let client = self.SandboxConnect()?;
+
+        let req = UCallReq::VcpuResize(VcpuResizeArgs { Count: count });
+
+        let resp = client.Call(&req)?;
+        match resp {
+            UCallResp::VcpuResizeResp(info) => Ok(info),
+            UCallResp::UCallRespErr(e) => Err(Error::Common(e)),
+            resp => {
+                panic!("VcpuResize get unknow resp {:?}", resp);
+            }
+        }
+    }
+
     pub fn StartRootContainer(&self) -> Result<()> {
         let client = self.SandboxConnect()?;
 
@@ -423,6 +483,8 @@ impl Sandbox {
             ID: id.to_string(),
             Caps: specutils::Capabilities(false, &spec.process.capabilities),
             Root: container_root,
+            MemoryLimit: MemoryLimitFromSpec(spec),
+            OOMScoreAdj: OOMScoreAdjFromSpec(spec),
             ..Default::default()
         };
 
@@ -37,6 +37,8 @@ use super::super::super::qlib::common::*;
 use super::super::super::runc::oci::LinuxResources;
 use super::super::cmd::config::*;
 use super::super::container::container::*;
+use super::super::runtime::loader::CpuCountFromQuota;
+use super::super::sandbox::balloon_driver::BalloonDriver;
 use super::container_io::*;
 use super::process::*;
 
@@ -123,6 +125,7 @@ impl ContainerFactory {
             FileAccess: FileAccessType::default(),
             Network: NetworkType::default(),
             SystemdCgroup: false,
+            Rootless: false,
         };
 
         let container = init
@@ -373,97 +376,22 @@ impl CommonContainer {
         Ok(metrics)*/
     }
 
-    pub fn update(&mut self, _resources: &LinuxResources) -> Result<()> {
-        return Err(Error::Unimplemented("CommonContainer::pids".to_string()));
-        /*// get container main process cgroup
-        let path = get_cgroups_relative_paths_by_pid(self.common.init.pid() as u32)?;
-        let cgroup = Cgroup::load_with_relative_paths(hierarchies::auto(), Path::new("."), path);
-
-        for sub_system in Cgroup::subsystems(&cgroup) {
-            match sub_system {
-                Subsystem::Pid(pid_ctr) => {
-                    // set maximum number of PIDs
-                    if let Some(pids) = resources.pids() {
-                        pid_ctr
-                            .set_pid_max(MaxValue::Value(pids.limit()))
-                            .map_err(other_error!(e, "set pid max"))?;
-                    }
-                }
-                Subsystem::Mem(mem_ctr) => {
-                    if let Some(memory) = resources.memory() {
-                        // set memory limit in bytes
-                        if let Some(limit) = memory.limit() {
-                            mem_ctr
-                                .set_limit(limit)
-                                .map_err(other_error!(e, "set mem limit"))?;
-                        }
-
-                        // set memory swap limit in bytes
-                        if let Some(swap) = memory.swap() {
-                            mem_ctr
-                                .set_memswap_limit(swap)
-                                .map_err(other_error!(e, "set memsw limit"))?;
-                        }
-                    }
-                }
-                Subsystem::CpuSet(cpuset_ctr) => {
-                    if let Some(cpu) = resources.cpu() {
-                        // set CPUs to use within the cpuset
-                        if let Some(cpus) = cpu.cpus() {
-                            cpuset_ctr
-                                .set_cpus(cpus)
-                                .map_err(other_error!(e, "set CPU sets"))?;
-                        }
-
-                        // set list of memory nodes in the cpuset
-                        if let Some(mems) = cpu.mems() {
-                            cpuset_ctr
-                                .set_mems(mems)
-                                .map_err(other_error!(e, "set CPU memes"))?;
-                        }
-                    }
-                }
-                Subsystem::Cpu(cpu_ctr) => {
-                    if let Some(cpu) = resources.cpu() {
-                        // set CPU shares
-                        if let Some(shares) = cpu.shares() {
-                            cpu_ctr
-                                .set_shares(shares)
-                                .map_err(other_error!(e, "set CPU share"))?;
-                        }
-
-                        // set CPU hardcap limit
-                        if let Some(quota) = cpu.quota() {
-                            cpu_ctr
-                                .set_cfs_quota(quota)
-                                .map_err(other_error!(e, "set CPU quota"))?;
-                        }
-
-                        // set CPU hardcap period
-                        if let Some(period) = cpu.period() {
-                            cpu_ctr
-                                .set_cfs_period(period)
-                                .map_err(other_error!(e, "set CPU period"))?;
-                        }
-                    }
-                }
-                Subsystem::HugeTlb(ht_ctr) => {
-                    // set the limit if "pagesize" hugetlb usage
-                    if let Some(hp_limits) = resources.hugepage_limits() {
-                        for limit in hp_limits {
-                            ht_ctr
-                                .set_limit_in_bytes(
-                                    limit.page_size().as_str(),
-                                    limit.limit() as u64,
-                                )
-                                .map_err(other_error!(e, "set huge page limit"))?;
-                        }
-                    }
-                }
-                _ => {}
+    pub fn update(&mut self, resources: &LinuxResources) -> Result<()> {
+        CheckUpdateResources(resources)?;
+
+        // the cpu quota decides how many vcpus take tasks in the sandbox
+        if let Some(cpu) = &resources.cpu {
+            let count = CpuCountFromQuota(cpu);
+            if count > 0 {
+                let info = self.container.VcpuResize(count)?;
+                info!(
+                    "update container {}: {}/{} vcpus online",
+                    self.container.ID, info.Online, info.Total
+                );
             }
         }
-        Ok(())*/
+
+        return Ok(());
     }
 
     pub fn start(&mut self, exec_id: Option<&str>) -> Result<i32> {
@@ -492,6 +420,12 @@ impl CommonContainer {
             }
             None => {
                 self.container.Start()?;
+                let threshold = crate::QUARK_CONFIG.lock().BalloonPressure;
+                if threshold > 0 && IsRoot(&self.container.Spec) {
+                    let sandbox = self.container.Sandbox.as_ref().unwrap();
+                    BalloonDriver::Start(&sandbox.ID, sandbox.Pid, threshold);
+                }
+
                 self.init.common.set_status(Status::RUNNING);
                 Ok(self.init.common.pid())
             }
@@ -502,3 +436,75 @@ impl CommonContainer {
         self.init.pid()
     }
 }
+
+// the resources which can be updated on the running container: the cpu quota, which
+// decides the online vcpus, and the cpu shares, which are meaningless as the vcpus are
+// not shared with other containers. The others are rejected instead of being ignored
+pub fn CheckUpdateResources(resources: &LinuxResources) -> Result<()> {
+    let mut unsupported = Vec::new();
+    if resources.devices.len() > 0 {
+        unsupported.push("devices");
+    }
+    if resources.memory.is_some() {
+        unsupported.push("memory");
+    }
+    if resources.pids.is_some() {
+        unsupported.push("pids");
+    }
+    if resources.block_io.is_some() {
+        unsupported.push("blockIO");
+    }
+    if resources.hugepage_limits.len() > 0 {
+        unsupported.push("hugepageLimits");
+    }
+    if resources.network.is_some() {
+        unsupported.push("network");
+    }
+    if let Some(cpu) = &resources.cpu {
+        if cpu.realtime_runtime.is_some() || cpu.realtime_period.is_some() {
+            unsupported.push("cpu realtime");
+        }
+        if cpu.cpus.len() > 0 || cpu.mems.len() > 0 {
+            unsupported.push("cpuset");
+        }
+    }
+
+    if unsupported.len() > 0 {
+        return Err(Error::Unimplemented(format!(
+            "CommonContainer::update doesn't support {}",
+            unsupported.join(", ")
+        )));
+    }
+
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::oci::{LinuxCPU, LinuxMemory};
+    use super::*;
+
+    #[test]
+    fn TestCheckUpdateResources() {
+        let mut resources = LinuxResources::default();
+        assert!(CheckUpdateResources(&resources).is_ok());
+
+        resources.cpu = Some(LinuxCPU {
+            shares: Some(1024),
+            quota: Some(200000),
+            period: Some(100000),
+            ..Default::default()
+        });
+        assert!(CheckUpdateResources(&resources).is_ok());
+
+        resources.cpu.as_mut().unwrap().cpus = "0-1".to_string();
+        resources.memory = Some(LinuxMemory {
+            limit: Some(1 << 30),
+            ..Default::default()
+        });
+        match CheckUpdateResources(&resources) {
+            Err(Error::Unimplemented(msg)) => assert!(msg.ends_with("memory, cpuset"), "{}", msg),
+            ret => panic!("unexpected {:?}", ret),
+        }
+    }
+}
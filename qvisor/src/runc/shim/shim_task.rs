@@ -29,7 +29,7 @@ use containerd_shim::api::*;
 use containerd_shim::event::Event;
 use containerd_shim::protos::cgroups::metrics::Metrics;
 use containerd_shim::protos::events::task::{
-    TaskCreate, TaskDelete, TaskExecAdded, TaskExecStarted, TaskExit, TaskIO, TaskStart,
+    TaskCreate, TaskDelete, TaskExecAdded, TaskExecStarted, TaskExit, TaskIO, TaskOOM, TaskStart,
 };
 use containerd_shim::protos::protobuf::well_known_types::{Any, Timestamp};
 use containerd_shim::protos::protobuf::{Message, SingularPtrField};
@@ -96,6 +96,18 @@ impl ShimTask {
 
                 error!("shim WaitAll {:?}", resp);
 
+                // the oom event has to arrive before the exit event so that the container
+                // is reported as OOMKilled
+                if resp.oomKilled {
+                    Self::SendEvent(
+                        &tx,
+                        TaskOOM {
+                            container_id: resp.cid.clone(),
+                            ..Default::default()
+                        },
+                    );
+                }
+
                 Self::Exit(&tx, &containers, resp.cid, resp.execId, resp.status as i32)
             }
         });
@@ -39,6 +39,9 @@ pub enum UCallReq {
     CreateSubContainer(CreateArgs),
     StartSubContainer(StartArgs),
     WaitAll,
+    MemBalloon(MemBalloonArgs),
+    VcpuResize(VcpuResizeArgs),
+    SetLogLevel(LogLevelArgs),
 }
 
 impl FileDescriptors for UCallReq {
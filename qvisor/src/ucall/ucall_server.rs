@@ -14,12 +14,15 @@
 
 use crate::qlib::kernel::GlobalIOMgr;
 
+use super::super::print::SetJsonFormat;
 use super::super::qlib::common::*;
+use super::super::qlib::config::LogFormat;
 use super::super::qlib::control_msg::*;
 use super::super::qlib::linux_def::*;
 use super::super::qlib::loader;
 use super::super::runc::container::container::*;
 use super::super::vmspace::*;
+use super::super::QUARK_CONFIG;
 use super::super::URING_MGR;
 use super::ucall::*;
 use super::usocket::*;
@@ -105,6 +108,31 @@ pub fn WaitAll() -> Result<ControlMsg> {
     return Ok(msg);
 }
 
+pub fn MemBalloonHandler(args: &MemBalloonArgs) -> Result<ControlMsg> {
+    let msg = ControlMsg::New(Payload::MemBalloon(args.clone()));
+    return Ok(msg);
+}
+
+pub fn VcpuResizeHandler(args: &VcpuResizeArgs) -> Result<ControlMsg> {
+    let msg = ControlMsg::New(Payload::VcpuResize(args.clone()));
+    return Ok(msg);
+}
+
+pub fn SetLogLevelHandler(args: &LogLevelArgs) -> Result<ControlMsg> {
+    // the host side log of the sandbox follows the guest
+    if let Some(level) = args.Level {
+        QUARK_CONFIG.lock().DebugLevel = level;
+    }
+
+    if let Some(format) = args.Format {
+        QUARK_CONFIG.lock().LogFormat = format;
+        SetJsonFormat(format == LogFormat::Json);
+    }
+
+    let msg = ControlMsg::New(Payload::SetLogLevel(args.clone()));
+    return Ok(msg);
+}
+
 pub fn WaitPidHandler(waitpid: &WaitPid) -> Result<ControlMsg> {
     let msg = ControlMsg::New(Payload::WaitPid(waitpid.clone()));
     return Ok(msg);
@@ -171,6 +199,9 @@ pub fn ProcessReqHandler(req: &mut UCallReq, fds: &[i32]) -> Result<ControlMsg>
         UCallReq::CreateSubContainer(args) => CreateSubContainerHandler(args, fds)?,
         UCallReq::StartSubContainer(args) => StartSubContainerHandler(args)?,
         UCallReq::WaitAll => WaitAll()?,
+        UCallReq::MemBalloon(args) => MemBalloonHandler(args)?,
+        UCallReq::VcpuResize(args) => VcpuResizeHandler(args)?,
+        UCallReq::SetLogLevel(args) => SetLogLevelHandler(args)?,
     };
 
     return Ok(msg);
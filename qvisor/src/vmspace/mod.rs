@@ -252,6 +252,8 @@ impl VMSpace {
             .expect("load limitSet fail")
             .GetInternalCopy();
         process.Caps = Capabilities(false, &spec.process.capabilities);
+        process.MemoryLimit = MemoryLimitFromSpec(spec);
+        process.OOMScoreAdj = OOMScoreAdjFromSpec(spec);
 
         process.HostName = spec.hostname.to_string();
 
@@ -699,6 +701,19 @@ impl VMSpace {
         return Self::GetRet(ret as i64);
     }
 
+    // free the host memory of the guest memory range and keep the mapping, e.g. when memory
+    // balloon inflates. The range reads as zero when it is touched again. MADV_REMOVE frees
+    // the shared memory of a shared mapping, a private mapping only supports MADV_DONTNEED
+    pub fn RemoveGuestMemRange(start: u64, len: u64) -> i64 {
+        let ret = unsafe { libc::madvise(start as _, len as usize, libc::MADV_REMOVE) as i64 };
+        if ret == 0 || errno::errno().0 != libc::EINVAL {
+            return Self::GetRet(ret);
+        }
+
+        let ret = unsafe { libc::madvise(start as _, len as usize, libc::MADV_DONTNEED) as i64 };
+        return Self::GetRet(ret);
+    }
+
     pub fn CreateAt(
         dirfd: i32,
         fileName: u64,
@@ -73,6 +73,7 @@ impl TsotAgent {
                 }
 
                 SHARESPACE.tsotSocketMgr.SetLocalIpAddr(m.containerIp);
+                SHARESPACE.tsotSocketMgr.SetLocalIpv6Addr(m.containerIpv6);
 
                 //info!("TsotAgent::Register success with ip {:?}", QIPv4Addr::from(m.containerIp).ToBytes());
             }
@@ -0,0 +1,81 @@
+// Copyright (c) 2021 Quark Container Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![allow(dead_code)]
+#![allow(non_snake_case)]
+
+extern crate clap;
+
+pub mod constants;
+
+use clap::{App, Arg, SubCommand};
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::process;
+
+use crate::constants::*;
+
+fn main() {
+    let defaultSocket = format!("{}{}", RDMA_SRV_SOCKET, RDMA_SRV_STATS_SOCKET_SUFFIX);
+    let matches = App::new("rdma_cli")
+        .about("TSoR rdma_srv client")
+        .subcommand(
+            SubCommand::with_name("stats")
+                .about("show the connections, channels and pods of rdma_srv")
+                .arg(
+                    Arg::with_name("section")
+                        .possible_values(&[
+                            STATS_SECTION_ALL,
+                            STATS_SECTION_CONNS,
+                            STATS_SECTION_CHANNELS,
+                            STATS_SECTION_PODS,
+                        ])
+                        .default_value(STATS_SECTION_ALL),
+                )
+                .arg(
+                    Arg::with_name("socket")
+                        .long("socket")
+                        .takes_value(true)
+                        .default_value(&defaultSocket)
+                        .help("the rdma_srv stats socket"),
+                ),
+        )
+        .get_matches();
+
+    match matches.subcommand() {
+        ("stats", Some(m)) => {
+            let section = m.value_of("section").unwrap();
+            let socket = m.value_of("socket").unwrap();
+            match Stats(socket, section) {
+                Ok(report) => print!("{}", report),
+                Err(e) => {
+                    eprintln!("rdma_cli stats from {} fail with error {:?}", socket, e);
+                    process::exit(1);
+                }
+            }
+        }
+        _ => {
+            eprintln!("{}", matches.usage());
+            process::exit(1);
+        }
+    }
+}
+
+fn Stats(socket: &str, section: &str) -> std::io::Result<String> {
+    let mut stream = UnixStream::connect(socket)?;
+    stream.write_all(format!("{}\n", section).as_bytes())?;
+    let mut report = String::new();
+    stream.read_to_string(&mut report)?;
+    return Ok(report);
+}
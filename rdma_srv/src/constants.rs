@@ -24,5 +24,14 @@ pub const SO_ORIGINAL_DST: i32 = 80;
 pub const SOL_IP: i32 = 0;
 pub const INCLUSTER_INGRESS_PORT: u16 = 7981;
 
+pub const RDMA_SRV_SOCKET: &str = "/var/quarkrdma/rdma_srv_socket";
+// the stats socket path is the rdma_srv socket path with the suffix
+pub const RDMA_SRV_STATS_SOCKET_SUFFIX: &str = "_stats";
+
+pub const STATS_SECTION_CONNS: &str = "conns";
+pub const STATS_SECTION_CHANNELS: &str = "channels";
+pub const STATS_SECTION_PODS: &str = "pods";
+pub const STATS_SECTION_ALL: &str = "all";
+
 pub const PROTOCOL_TCP: &str = "TCP";
 pub const PROTOCOL_UDP: &str = "UDP";
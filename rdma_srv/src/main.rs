@@ -77,6 +77,8 @@ pub mod rdma_conn;
 pub mod rdma_ctrlconn;
 pub mod rdma_def;
 pub mod rdma_srv;
+pub mod rdma_stats;
+pub mod rdma_transport;
 pub mod unix_socket_def;
 
 pub mod common;
@@ -106,6 +108,7 @@ use crate::qlib::rdma_share::*;
 use crate::rdma::RDMA;
 use common::*;
 use configmap_informer::ConfigMapInformer;
+use constants::*;
 use endpoints_informer::EndpointsInformer;
 use id_mgr::IdMgr;
 use local_ip_address::list_afinet_netifas;
@@ -121,6 +124,7 @@ use rdma_channel::RDMAChannel;
 use rdma_conn::*;
 use rdma_ctrlconn::Node;
 use rdma_ctrlconn::Pod;
+use rdma_transport::RDMATransport;
 use service_informer::ServiceInformer;
 use spin::Mutex;
 use std::io::Error;
@@ -345,7 +349,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // unix domain socket
 
-    let mut unix_sock_path = "/var/quarkrdma/rdma_srv_socket";
+    let mut unix_sock_path = RDMA_SRV_SOCKET;
     if args.len() > 1 {
         unix_sock_path = args.get(1).unwrap(); //"/tmp/rdma_srv1";
     }
@@ -363,6 +367,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     );
 
     println!("srv_unix_sock: {}", srv_unix_sock_fd);
+    rdma_stats::StartStatsService(&format!(
+        "{}{}",
+        unix_sock_path, RDMA_SRV_STATS_SOCKET_SUFFIX
+    ));
     unblock_fd(srv_unix_sock_fd);
 
     epoll_add(
@@ -494,7 +502,7 @@ fn HandleEvents(epoll_fd: i32, events: &Vec<EpollEvent>, hostname: &String) -> R
                     stream_fd,
                     sockBuf.clone(),
                     RDMA_SRV.keys[controlRegionId / 1024][1],
-                    RDMA_SRV.udpQP.qpNum(),
+                    RDMA_SRV.UdpQpNum(),
                 );
                 let rdmaChannel = RDMAChannel::New(
                     0,
@@ -849,7 +857,7 @@ fn SetupConnection(ip: &u32) {
         sock_fd,
         sockBuf.clone(),
         RDMA_SRV.keys[controlRegionId / 16][1],
-        RDMA_SRV.udpQP.qpNum(),
+        RDMA_SRV.UdpQpNum(),
     );
     let rdmaChannel = RDMAChannel::New(
         0,
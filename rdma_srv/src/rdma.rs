@@ -11,6 +11,7 @@ use std::ptr;
 use super::qlib::common::*;
 use super::qlib::linux_def::*;
 use super::rdma_srv::RDMA_SRV;
+use super::rdma_transport::*;
 // use super::qlib::kernel::TSC;
 //use super::super::super::IO_MGR;
 
@@ -379,6 +380,11 @@ pub const MAX_RECV_SGE: u32 = 1;
 
 impl RDMAContext {
     pub fn Init(&self, deviceName: &str, ibPort: u8) {
+        // no RDMA device is needed by the tcp transport
+        if TransportType::IsTCP() {
+            return;
+        }
+
         *self.0.lock() = RDMAContextIntern::New(deviceName, ibPort);
     }
 
@@ -465,6 +471,10 @@ impl RDMAContext {
     }
 
     pub fn CreateMemoryRegion(&self, addr: u64, size: usize) -> Result<MemoryRegion> {
+        if TransportType::IsTCP() {
+            return Ok(TCP_TRANSPORT.RegMemoryRegion(addr, size));
+        }
+
         let context = self.lock();
         let access = rdmaffi::ibv_access_flags::IBV_ACCESS_LOCAL_WRITE
             | rdmaffi::ibv_access_flags::IBV_ACCESS_REMOTE_WRITE
@@ -496,6 +506,10 @@ impl RDMAContext {
     }
 
     pub fn CompleteChannelFd(&self) -> i32 {
+        if TransportType::IsTCP() {
+            return TCP_TRANSPORT.eventfd;
+        }
+
         let fd = self.lock().ccfd;
         // println!("XXXX, fd: {} ", fd);
         return fd;
@@ -527,6 +541,15 @@ impl RDMAContext {
 
         let mut count = 0;
 
+        if TransportType::IsTCP() {
+            while let Some(wc) = TCP_TRANSPORT.PollCompletion() {
+                count += 1;
+                self.ProcessWC(&wc, channels);
+            }
+
+            return count;
+        }
+
         loop {
             // let poll_result = unsafe { rdmaffi::ibv_poll_cq(self.CompleteQueue(), 2, &mut wc) };
             // let wc_ptr: *const rdmaffi::ibv_wc = &wc;
@@ -570,6 +593,11 @@ impl RDMAContext {
     // }
 
     pub fn HandleCQEvent(&self) -> Result<()> {
+        if TransportType::IsTCP() {
+            TCP_TRANSPORT.HandleEvent();
+            return Ok(());
+        }
+
         let mut cq_ptr: *mut rdmaffi::ibv_cq = ptr::null_mut();
         let mut cq_context: *mut std::os::raw::c_void = ptr::null_mut();
         let ret = unsafe {
@@ -725,6 +753,8 @@ impl RDMAContext {
                 "ProcessWC::1, work reqeust failed with status: {}, id: {}",
                 wc.status, wc.wr_id
             );
+            // the opcode of the failed work request is undefined
+            return;
         }
         if wc.opcode == rdmaffi::ibv_wc_opcode::IBV_WC_RDMA_WRITE {
             // debug!(
@@ -1604,7 +1634,11 @@ impl Drop for MemoryRegion {
     fn drop(&mut self) {
         unsafe {
             if self.0 as *const _ as u64 != 0 {
-                let _ret = rdmaffi::ibv_dereg_mr(self.0);
+                if TransportType::IsTCP() {
+                    TCP_TRANSPORT.DeregMemoryRegion(self.0);
+                } else {
+                    let _ret = rdmaffi::ibv_dereg_mr(self.0);
+                }
             }
         }
     }
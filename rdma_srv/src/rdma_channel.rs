@@ -96,6 +96,10 @@ pub struct RDMAChannelIntern {
     pub closeRequestedByClient: Mutex<bool>,
     pub pendingShutdown: Mutex<bool>,
     pub finReceived: Mutex<bool>,
+
+    // the bytes written to and received from the remote channel
+    pub bytesSent: AtomicU64,
+    pub bytesRecv: AtomicU64,
 }
 
 impl Drop for RDMAChannelIntern {
@@ -137,6 +141,7 @@ impl RDMAChannelIntern {
         )?;
 
         self.writeCount.store(writeCount, QOrdering::RELEASE);
+        self.bytesSent.fetch_add(writeCount as u64, Ordering::Relaxed);
         return Ok(());
     }
 
@@ -339,6 +344,7 @@ impl RDMAChannelIntern {
             .PostRecv(qpNum, self.localId as u64, self.raddr, self.rkey);
 
         if recvCount > 0 {
+            self.bytesRecv.fetch_add(recvCount, Ordering::Relaxed);
             // debug!("ProcessRDMARecvWriteImm::1, channelId: {}, recvCount: {}", self.localId, recvCount);
             let (trigger, _addr, _len) = self.sockBuf.ProduceAndGetFreeReadBuf(recvCount as usize);
             // debug!("ProcessRDMARecvWriteImm::2, trigger {}", trigger);
@@ -566,6 +572,8 @@ impl RDMAChannel {
             closeRequestedByClient: Mutex::new(false),
             pendingShutdown: Mutex::new(false),
             finReceived: Mutex::new(false),
+            bytesSent: AtomicU64::new(0),
+            bytesRecv: AtomicU64::new(0),
         }))
     }
 
@@ -611,6 +619,8 @@ impl RDMAChannel {
             closeRequestedByClient: Mutex::new(false),
             pendingShutdown: Mutex::new(false),
             finReceived: Mutex::new(false),
+            bytesSent: AtomicU64::new(0),
+            bytesRecv: AtomicU64::new(0),
         }))
     }
 
@@ -649,6 +659,8 @@ impl RDMAChannel {
             closeRequestedByClient: Mutex::new(false),
             pendingShutdown: Mutex::new(false),
             finReceived: Mutex::new(false),
+            bytesSent: AtomicU64::new(0),
+            bytesRecv: AtomicU64::new(0),
         }))
     }
 
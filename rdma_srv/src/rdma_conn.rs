@@ -29,6 +29,7 @@ use super::rdma::*;
 use super::rdma_agent::*;
 use super::rdma_channel::*;
 use super::rdma_ctrlconn::*;
+use super::rdma_transport::*;
 
 use super::qlib::linux_def::*;
 use super::qlib::rdma_share::*;
@@ -74,7 +75,7 @@ impl RDMAInfo {
 // RDMA connections between 2 nodes
 pub struct RDMAConnInternal {
     pub fd: i32,
-    pub qps: Vec<QueuePair>,
+    pub qps: Vec<Arc<dyn RDMATransport>>,
     //pub ctrlChan: Mutex<RDMAControlChannel>,
     pub ctrlChan: Mutex<RDMAControlChannel>,
     pub socketState: AtomicU64,
@@ -112,7 +113,7 @@ impl Drop for RDMAConn {
 
 impl RDMAConn {
     pub fn New(fd: i32, sockBuf: SocketBuff, controlRKey: u32, udpQPNum: u32) -> Self {
-        let rc_qp = RDMA.CreateRCQueuePair().expect("RDMA create RC QP fail");
+        let rc_qp = NewTransport(fd);
         println!("after create RC qp");
         let (addr, len) = sockBuf.ReadBuf();
         let localRDMAInfo = RDMAInfo {
@@ -177,7 +178,7 @@ impl RDMAConn {
     pub fn SetupRDMA(&self) {
         let remoteInfo = self.remoteRDMAInfo.lock();
         self.qps[0]
-            .SetupRCQP(&RDMA, remoteInfo.rc_qp_num, remoteInfo.lid, remoteInfo.gid)
+            .Setup(remoteInfo.rc_qp_num, remoteInfo.lid, remoteInfo.gid)
             .expect("SetupRCQP fail...");
         for _i in 0..RECV_REQUEST_COUNT {
             self.qps[0]
@@ -189,12 +190,18 @@ impl RDMAConn {
                 )
                 .expect("SetupRDMA PostRecv fail");
         }
+
+        // the tcp transport sends the datagram over the connection, no address handler
+        if TransportType::IsTCP() {
+            return;
+        }
+
         *self.addressHandler.lock() = RDMA
             .CreateAddressHandler(1, remoteInfo.lid, remoteInfo.gid) //TODO: port_num is local port num, should be configurable in the future
             .expect("Create AddressHandler fail...");
     }
 
-    pub fn GetQueuePairs(&self) -> &Vec<QueuePair> {
+    pub fn GetQueuePairs(&self) -> &Vec<Arc<dyn RDMATransport>> {
         &self.qps
     }
 
@@ -256,6 +263,12 @@ impl RDMAConn {
             }
             SocketState::Ready => {
                 // println!("Write::Ready, fd:{} ", self.fd);
+                // the tcp transport sends the frames queued while the socket buffer was full
+                for qp in &self.qps {
+                    if let Err(e) = qp.Flush() {
+                        error!("RDMAConn::Write flush fd {} fail with error {:?}", self.fd, e);
+                    }
+                }
             }
             _ => {
                 panic!(
@@ -279,6 +292,7 @@ impl RDMAConn {
 
     pub fn SetReady(&self) {
         self.SetSocketState(SocketState::Ready);
+        self.qps[0].Start();
         // println!("Ready!!!");
         //self.ctrlChan.lock().SendData();
         // let laddr = RDMA_SRV.udpMemRegion.addr + 10 * (mem::size_of::<UDPPacket>() + 40) as u64 + 40;
@@ -460,7 +474,7 @@ impl RDMAConn {
 
     // Send via unrelabile datagram queue pair
     pub fn RDMAUDQPSend(&self, laddr: u64, len: u32, wrId: u64, lkey: u32) -> Result<()> {
-        RDMA_SRV.udpQP.PostSendUDQP(
+        self.qps[0].SendUD(
             &self.addressHandler.lock(),
             self.remoteRDMAInfo.lock().ud_qp_num,
             wrId,
@@ -688,6 +702,9 @@ impl RDMAControlChannel {
         let (_trigger, _addr, _len) = rdmaChannel
             .sockBuf
             .ProduceAndGetFreeReadBuf(recvCount as usize);
+        rdmaChannel
+            .bytesRecv
+            .fetch_add(recvCount, Ordering::Relaxed);
 
         //TODO: handle postRecv error
         let mut readBuf = rdmaChannel.sockBuf.readBuf.lock();
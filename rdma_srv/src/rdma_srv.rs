@@ -19,6 +19,7 @@ use super::rdma_agent::*;
 use super::rdma_channel::*;
 use super::rdma_conn::*;
 use super::rdma_ctrlconn::*;
+use super::rdma_transport::*;
 use core::sync::atomic::Ordering;
 use lazy_static::lazy_static;
 use spin::Mutex;
@@ -254,11 +255,21 @@ impl RDMASrv {
         let udpMR = RDMA
             .CreateMemoryRegion(udpBufferAddr as u64, udpBufferSize)
             .unwrap();
-        let udpQP = RDMA.CreateUDQueuePair().expect("Create UD QP failed...");
-        udpQP.SetupUDQP(&RDMA).expect("SetupUDQP fail...");
+        let udpQP = if TransportType::IsTCP() {
+            QueuePair::default()
+        } else {
+            let udpQP = RDMA.CreateUDQueuePair().expect("Create UD QP failed...");
+            udpQP.SetupUDQP(&RDMA).expect("SetupUDQP fail...");
+            udpQP
+        };
 
         for i in 0..RECV_UDP_COUNT {
             let addr = udpBufferAddr as u64 + (i * udpPacketExtendedSize as u32) as u64;
+            if TransportType::IsTCP() {
+                TCP_TRANSPORT.PostUDRecv(i as u64, addr, udpPacketExtendedSize as u32);
+                continue;
+            }
+
             udpQP
                 .PostRecv(i as u64, addr, udpMR.LKey(), udpPacketExtendedSize as u32)
                 .expect("SetupUDQP PostRecv fail");
@@ -318,6 +329,15 @@ impl RDMASrv {
         };
     }
 
+    // the tcp transport has no UD queue pair
+    pub fn UdpQpNum(&self) -> u32 {
+        if TransportType::IsTCP() {
+            return 0;
+        }
+
+        return self.udpQP.qpNum();
+    }
+
     pub fn getRDMAChannel(&self, channelId: u32) -> Option<RDMAChannel> {
         match self.channels.lock().get(&channelId) {
             None => None,
@@ -0,0 +1,346 @@
+// Copyright (c) 2021 Quark Container Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use core::sync::atomic::Ordering;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::Ipv4Addr;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+use tabwriter::TabWriter;
+
+use super::constants::*;
+use super::rdma_channel::*;
+use super::rdma_conn::*;
+use super::rdma_srv::RDMA_SRV;
+use super::rdma_transport::*;
+
+// the client which doesn't send its request or read the report in time is dropped
+pub const STATS_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+// the introspection endpoint of rdma_srv. The client writes one line with the section,
+// "conns", "channels", "pods" or "all", and reads the report until the socket is closed
+pub fn StartStatsService(path: &str) {
+    // rdma_srv keeps working without the introspection endpoint
+    if Path::new(path).exists() {
+        if let Err(e) = fs::remove_file(path) {
+            error!(
+                "stats socket {} delete fail with error {:?}, stats is disabled",
+                path, e
+            );
+            return;
+        }
+    }
+
+    let listener = match UnixListener::bind(path) {
+        Ok(l) => l,
+        Err(e) => {
+            error!(
+                "stats socket {} bind fail with error {:?}, stats is disabled",
+                path, e
+            );
+            return;
+        }
+    };
+    info!("stats socket: {}", path);
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                // each client is served on its own thread, so that a slow client doesn't
+                // block the others
+                Ok(stream) => {
+                    thread::spawn(move || {
+                        if let Err(e) = HandleStatsRequest(stream, STATS_REQUEST_TIMEOUT) {
+                            error!("HandleStatsRequest fail with error {:?}", e);
+                        }
+                    });
+                }
+                Err(e) => {
+                    error!("stats socket accept fail with error {:?}", e);
+                }
+            }
+        }
+    });
+}
+
+fn HandleStatsRequest(stream: UnixStream, timeout: Duration) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+
+    let mut section = String::new();
+    BufReader::new(&stream).read_line(&mut section)?;
+    let report = SectionReport(section.trim());
+
+    let mut stream = stream;
+    stream.write_all(report.as_bytes())?;
+    return Ok(());
+}
+
+fn SectionReport(section: &str) -> String {
+    match section {
+        STATS_SECTION_CONNS => return ConnsReport(),
+        STATS_SECTION_CHANNELS => return ChannelsReport(),
+        STATS_SECTION_PODS => return PodsReport(),
+        "" | STATS_SECTION_ALL => {
+            return format!("{}\n{}\n{}", ConnsReport(), ChannelsReport(), PodsReport())
+        }
+        other => return format!("unknown stats section {}\n", other),
+    }
+}
+
+fn Tabulate(rows: Vec<String>) -> String {
+    let mut tw = TabWriter::new(vec![]);
+    for row in rows {
+        let _ = writeln!(tw, "{}", row);
+    }
+
+    let _ = tw.flush();
+    return String::from_utf8(tw.into_inner().unwrap_or_default()).unwrap_or_default();
+}
+
+fn PodName(podId: &[u8]) -> String {
+    let len = podId.iter().position(|b| *b == 0).unwrap_or(podId.len());
+    return String::from_utf8_lossy(&podId[..len]).to_string();
+}
+
+// the channel ips are in host byte order
+fn Endpoint(ip: u32, port: u16) -> String {
+    return format!("{}:{}", Ipv4Addr::from(ip), port);
+}
+
+#[derive(Debug, Default)]
+pub struct ChannelStats {
+    pub localId: u32,
+    pub remoteId: u32,
+    pub pod: String,
+    pub vpcId: u32,
+    pub local: String,
+    pub remote: String,
+    pub status: String,
+    pub remoteFreeSpace: u32,
+    pub sending: bool,
+    // the requests waiting for the remote receive request credit
+    pub pending: usize,
+    pub writeBuffered: usize,
+    pub readBuffered: usize,
+    pub bytesSent: u64,
+    pub bytesRecv: u64,
+}
+
+impl ChannelStats {
+    // the locks are taken one by one, so that the stats doesn't block the data path
+    pub fn New(channel: &RDMAChannel) -> Self {
+        let remoteInfo = channel.remoteChannelRDMAInfo.lock().clone();
+        let pending = channel
+            .conn
+            .requestsQueue
+            .lock()
+            .iter()
+            .filter(|id| **id == channel.localId)
+            .count();
+
+        return Self {
+            localId: channel.localId,
+            remoteId: remoteInfo.remoteId,
+            pod: PodName(&channel.agent.podId),
+            vpcId: channel.vpcId,
+            local: Endpoint(channel.srcIpAddr, channel.srcPort),
+            remote: Endpoint(channel.dstIpAddr, channel.dstPort),
+            status: format!("{:?}", *channel.status.lock()),
+            remoteFreeSpace: remoteInfo.freespace,
+            sending: remoteInfo.sending,
+            pending: pending,
+            writeBuffered: channel.sockBuf.writeBuf.lock().AvailableDataSize(),
+            readBuffered: channel.sockBuf.readBuf.lock().AvailableDataSize(),
+            bytesSent: channel.bytesSent.load(Ordering::Relaxed),
+            bytesRecv: channel.bytesRecv.load(Ordering::Relaxed),
+        };
+    }
+}
+
+pub fn Channels() -> Vec<ChannelStats> {
+    let mut channels: Vec<RDMAChannel> = RDMA_SRV.channels.lock().values().cloned().collect();
+    channels.sort_by_key(|c| c.localId);
+    return channels.iter().map(|c| ChannelStats::New(c)).collect();
+}
+
+pub fn ConnsReport() -> String {
+    let mut conns: Vec<(u32, RDMAConn)> = RDMA_SRV
+        .conns
+        .lock()
+        .iter()
+        .map(|(ip, conn)| (*ip, conn.clone()))
+        .collect();
+    conns.sort_by_key(|(ip, _)| *ip);
+
+    let mut rows = vec![
+        "NODE\tQPNUM\tTRANSPORT\tSTATE\tREMOTE_CREDITS\tLOCAL_RECV_POSTED\tPENDING\tPENDING_CONTROL"
+            .to_string(),
+    ];
+    for (ip, conn) in conns {
+        // 0 is the connection to the local node, the conn ip is in network byte order
+        let node = if ip == 0 {
+            "local".to_string()
+        } else {
+            Ipv4Addr::from(u32::from_be(ip)).to_string()
+        };
+
+        rows.push(format!(
+            "{}\t{}\t{:?}\t{:?}\t{}\t{}\t{}\t{}",
+            node,
+            conn.GetQueuePairs()[0].qpNum(),
+            *TRANSPORT_TYPE,
+            conn.SocketState(),
+            *conn.remoteRecvRequestCount.lock(),
+            conn.localInsertedRecvRequestCount.load(Ordering::Relaxed),
+            conn.requestsQueue.lock().len(),
+            conn.controlRequestsQueue.lock().len(),
+        ));
+    }
+
+    return Tabulate(rows);
+}
+
+pub fn ChannelsReport() -> String {
+    let mut rows = vec![
+        "ID\tREMOTE_ID\tPOD\tVPC\tLOCAL\tREMOTE\tSTATE\tREMOTE_FREE\tSENDING\tPENDING\tWRITE_BUF\tREAD_BUF\tSENT\tRECV"
+            .to_string(),
+    ];
+    for c in Channels() {
+        rows.push(format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            c.localId,
+            c.remoteId,
+            c.pod,
+            c.vpcId,
+            c.local,
+            c.remote,
+            c.status,
+            c.remoteFreeSpace,
+            c.sending,
+            c.pending,
+            c.writeBuffered,
+            c.readBuffered,
+            c.bytesSent,
+            c.bytesRecv,
+        ));
+    }
+
+    return Tabulate(rows);
+}
+
+#[derive(Debug, Default)]
+pub struct PodStats {
+    pub channels: usize,
+    pub established: usize,
+    pub pending: usize,
+    pub bytesSent: u64,
+    pub bytesRecv: u64,
+}
+
+pub fn PodsReport() -> String {
+    return PodsTable(&Channels());
+}
+
+// the channel stats summed by pod
+pub fn PodsTable(channels: &[ChannelStats]) -> String {
+    let mut pods: BTreeMap<String, PodStats> = BTreeMap::new();
+    for c in channels {
+        let pod = pods.entry(c.pod.clone()).or_default();
+        pod.channels += 1;
+        if c.status == format!("{:?}", ChannelStatus::ESTABLISHED) {
+            pod.established += 1;
+        }
+        pod.pending += c.pending;
+        pod.bytesSent += c.bytesSent;
+        pod.bytesRecv += c.bytesRecv;
+    }
+
+    let mut rows = vec!["POD\tCHANNELS\tESTABLISHED\tPENDING\tSENT\tRECV".to_string()];
+    for (name, pod) in pods {
+        rows.push(format!(
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            name, pod.channels, pod.established, pod.pending, pod.bytesSent, pod.bytesRecv
+        ));
+    }
+
+    return Tabulate(rows);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::time::Instant;
+
+    fn Channel(pod: &str, status: ChannelStatus, pending: usize, sent: u64) -> ChannelStats {
+        return ChannelStats {
+            pod: pod.to_string(),
+            status: format!("{:?}", status),
+            pending: pending,
+            bytesSent: sent,
+            ..Default::default()
+        };
+    }
+
+    #[test]
+    fn TestPodsTable() {
+        let channels = vec![
+            Channel("p1", ChannelStatus::ESTABLISHED, 1, 100),
+            Channel("p1", ChannelStatus::CLOSED, 2, 50),
+            Channel("p2", ChannelStatus::ESTABLISHED, 0, 7),
+        ];
+
+        let table = PodsTable(&channels);
+        let rows: Vec<Vec<&str>> = table
+            .lines()
+            .map(|l| l.split_whitespace().collect())
+            .collect();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0], vec!["POD", "CHANNELS", "ESTABLISHED", "PENDING", "SENT", "RECV"]);
+        assert_eq!(rows[1], vec!["p1", "2", "1", "3", "150", "0"]);
+        assert_eq!(rows[2], vec!["p2", "1", "1", "0", "7", "0"]);
+    }
+
+    #[test]
+    fn TestPodNameEndpoint() {
+        let mut podId = [0u8; 16];
+        podId[..3].copy_from_slice(b"pod");
+        assert_eq!(PodName(&podId), "pod");
+        assert_eq!(Endpoint(0x0a000001, 80), "10.0.0.1:80");
+    }
+
+    #[test]
+    fn TestStatsRequest() {
+        let (mut client, server) = UnixStream::pair().unwrap();
+        client.write_all(b"unknown\n").unwrap();
+        HandleStatsRequest(server, STATS_REQUEST_TIMEOUT).unwrap();
+
+        let mut report = String::new();
+        client.read_to_string(&mut report).unwrap();
+        assert_eq!(report, "unknown stats section unknown\n");
+    }
+
+    #[test]
+    fn TestStatsRequestTimeout() {
+        // the idle client is dropped after the timeout
+        let (_client, server) = UnixStream::pair().unwrap();
+        let start = Instant::now();
+        assert!(HandleStatsRequest(server, Duration::from_millis(100)).is_err());
+        assert!(start.elapsed() < STATS_REQUEST_TIMEOUT);
+    }
+}
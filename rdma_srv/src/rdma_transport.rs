@@ -0,0 +1,766 @@
+// Copyright (c) 2021 Quark Container Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicU32, Ordering};
+use lazy_static::lazy_static;
+use rdmaffi;
+use spin::Mutex;
+use std::collections::{HashMap, VecDeque};
+use std::mem::MaybeUninit;
+use std::{env, mem, thread};
+
+use super::qlib::common::*;
+use super::qlib::linux_def::*;
+use super::rdma::*;
+use super::rdma_srv::RDMA_SRV;
+
+// the transport between 2 nodes is picked by QUARK_TSOR_TRANSPORT, "rdma" (default) or "tcp".
+// The tcp transport emulates the RC write with immediate and the UD send over the node
+// handshake connection, so that TSoR runs on the hosts without RDMA NIC
+pub const TSOR_TRANSPORT_ENV: &str = "QUARK_TSOR_TRANSPORT";
+
+// the global routing header at the start of a UD receive buffer
+pub const UD_GRH_SIZE: u64 = 40;
+// the buffer size to read and drop the datagram which has no receive buffer
+pub const DISCARD_BUF_SIZE: usize = 4096;
+
+lazy_static! {
+    pub static ref TRANSPORT_TYPE: TransportType = TransportType::FromEnv();
+    pub static ref TCP_TRANSPORT: TcpTransport = TcpTransport::New();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransportType {
+    RDMA,
+    TCP,
+}
+
+impl TransportType {
+    pub fn FromEnv() -> Self {
+        match env::var(TSOR_TRANSPORT_ENV) {
+            Ok(val) if val.eq_ignore_ascii_case("tcp") => {
+                info!("TSoR uses tcp transport");
+                return Self::TCP;
+            }
+            _ => return Self::RDMA,
+        }
+    }
+
+    pub fn IsTCP() -> bool {
+        return *TRANSPORT_TYPE == Self::TCP;
+    }
+}
+
+// the queue pair of the connection between 2 nodes
+pub trait RDMATransport: Send + Sync {
+    fn qpNum(&self) -> u32;
+
+    // connect to the remote queue pair, called after the RDMAInfo exchange
+    fn Setup(&self, remoteQpNum: u32, lid: u16, gid: Gid) -> Result<()>;
+
+    // start to receive, called after the remote side is ready
+    fn Start(&self) {}
+
+    // send the pending data when the connection is writable again
+    fn Flush(&self) -> Result<()> {
+        return Ok(());
+    }
+
+    fn WriteImm(
+        &self,
+        wrId: u64,
+        laddr: u64,
+        len: u32,
+        lkey: u32,
+        raddr: u64,
+        rkey: u32,
+        imm: u32,
+    ) -> Result<()>;
+
+    fn PostRecv(&self, wrId: u64, addr: u64, lkey: u32, length: u32) -> Result<()>;
+
+    // send the datagram to the remote UD queue pair
+    fn SendUD(
+        &self,
+        ah: &AddressHandler,
+        remoteQpNum: u32,
+        wrId: u64,
+        laddr: u64,
+        len: u32,
+        lkey: u32,
+    ) -> Result<()>;
+}
+
+impl RDMATransport for QueuePair {
+    fn qpNum(&self) -> u32 {
+        return QueuePair::qpNum(self);
+    }
+
+    fn Setup(&self, remoteQpNum: u32, lid: u16, gid: Gid) -> Result<()> {
+        return self.SetupRCQP(&RDMA, remoteQpNum, lid, gid);
+    }
+
+    fn WriteImm(
+        &self,
+        wrId: u64,
+        laddr: u64,
+        len: u32,
+        lkey: u32,
+        raddr: u64,
+        rkey: u32,
+        imm: u32,
+    ) -> Result<()> {
+        return QueuePair::WriteImm(self, wrId, laddr, len, lkey, raddr, rkey, imm);
+    }
+
+    fn PostRecv(&self, wrId: u64, addr: u64, lkey: u32, length: u32) -> Result<()> {
+        return QueuePair::PostRecv(self, wrId, addr, lkey, length);
+    }
+
+    fn SendUD(
+        &self,
+        ah: &AddressHandler,
+        remoteQpNum: u32,
+        wrId: u64,
+        laddr: u64,
+        len: u32,
+        lkey: u32,
+    ) -> Result<()> {
+        return RDMA_SRV
+            .udpQP
+            .PostSendUDQP(ah, remoteQpNum, wrId, laddr, len, lkey);
+    }
+}
+
+pub fn NewTransport(fd: i32) -> Arc<dyn RDMATransport> {
+    match *TRANSPORT_TYPE {
+        TransportType::RDMA => {
+            let qp = RDMA.CreateRCQueuePair().expect("RDMA create RC QP fail");
+            return Arc::new(qp);
+        }
+        TransportType::TCP => return Arc::new(TcpQueuePair::New(fd)),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum TcpFrameType {
+    WriteImm = 1,
+    SendUD,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+#[repr(C)]
+pub struct TcpFrameHdr {
+    pub frameType: u32,
+    pub len: u32,
+    pub imm: u32,
+    pub rkey: u32,
+    pub raddr: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct UDRecv {
+    pub wrId: u64,
+    pub addr: u64,
+    pub len: u32,
+}
+
+// the memory regions, completion queue and UD queue pair shared by the tcp queue pairs
+pub struct TcpTransport {
+    // eventfd to wake up the srv when there is new completion, it replaces the complete channel fd
+    pub eventfd: i32,
+    pub completions: Mutex<VecDeque<rdmaffi::ibv_wc>>,
+    // key -> (addr, len)
+    pub regions: Mutex<HashMap<u32, (u64, u64)>>,
+    pub udRecvs: Mutex<VecDeque<UDRecv>>,
+    pub nextKey: AtomicU32,
+    pub nextQpNum: AtomicU32,
+}
+
+impl TcpTransport {
+    pub fn New() -> Self {
+        let eventfd = unsafe { libc::eventfd(0, libc::EFD_NONBLOCK) };
+        if eventfd < 0 {
+            panic!("TcpTransport eventfd fail, errno is {}", errno::errno().0);
+        }
+
+        return Self {
+            eventfd: eventfd,
+            completions: Mutex::new(VecDeque::new()),
+            regions: Mutex::new(HashMap::new()),
+            udRecvs: Mutex::new(VecDeque::new()),
+            nextKey: AtomicU32::new(1),
+            nextQpNum: AtomicU32::new(1),
+        };
+    }
+
+    pub fn RegMemoryRegion(&self, addr: u64, size: usize) -> MemoryRegion {
+        let key = self.nextKey.fetch_add(1, Ordering::SeqCst);
+        self.regions.lock().insert(key, (addr, size as u64));
+        let mut mr: Box<rdmaffi::ibv_mr> =
+            Box::new(unsafe { MaybeUninit::zeroed().assume_init() });
+        mr.addr = addr as _;
+        mr.length = size;
+        mr.lkey = key;
+        mr.rkey = key;
+        return MemoryRegion(Box::into_raw(mr));
+    }
+
+    // free the ibv_mr allocated by RegMemoryRegion
+    pub fn DeregMemoryRegion(&self, mr: *mut rdmaffi::ibv_mr) {
+        let mr = unsafe { Box::from_raw(mr) };
+        self.regions.lock().remove(&mr.lkey);
+    }
+
+    // the access is rejected when the range is out of the registered region, same as RDMA NIC
+    pub fn CheckAccess(&self, key: u32, addr: u64, len: u32) -> Result<()> {
+        match self.regions.lock().get(&key) {
+            Some((start, size)) if addr >= *start && addr + len as u64 <= *start + *size => {
+                return Ok(())
+            }
+            _ => {
+                error!(
+                    "TcpTransport::CheckAccess fail, key: {}, addr: 0x{:x}, len: {}",
+                    key, addr, len
+                );
+                return Err(Error::SysError(SysErr::EFAULT));
+            }
+        }
+    }
+
+    pub fn PostUDRecv(&self, wrId: u64, addr: u64, len: u32) {
+        self.udRecvs.lock().push_back(UDRecv { wrId, addr, len });
+    }
+
+    pub fn Complete(&self, wrId: u64, opcode: u32, byteLen: u32, imm: u32, qpNum: u32) {
+        self.PushCompletion(
+            wrId,
+            rdmaffi::ibv_wc_status::IBV_WC_SUCCESS,
+            opcode,
+            byteLen,
+            imm,
+            qpNum,
+        );
+    }
+
+    // the receive request is flushed when its queue pair fails
+    pub fn CompleteFlushErr(&self, wrId: u64, qpNum: u32) {
+        self.PushCompletion(
+            wrId,
+            rdmaffi::ibv_wc_status::IBV_WC_WR_FLUSH_ERR,
+            rdmaffi::ibv_wc_opcode::IBV_WC_RECV_RDMA_WITH_IMM,
+            0,
+            0,
+            qpNum,
+        );
+    }
+
+    pub fn PushCompletion(
+        &self,
+        wrId: u64,
+        status: u32,
+        opcode: u32,
+        byteLen: u32,
+        imm: u32,
+        qpNum: u32,
+    ) {
+        let mut wc: rdmaffi::ibv_wc = unsafe { MaybeUninit::zeroed().assume_init() };
+        wc.wr_id = wrId;
+        wc.status = status;
+        wc.opcode = opcode;
+        wc.byte_len = byteLen;
+        wc.imm_data_invalidated_rkey_union = rdmaffi::imm_data_invalidated_rkey_union_t {
+            imm_data: imm,
+        };
+        wc.qp_num = qpNum;
+        self.completions.lock().push_back(wc);
+
+        let data: u64 = 1;
+        unsafe {
+            libc::write(self.eventfd, &data as *const _ as *const libc::c_void, 8);
+        }
+    }
+
+    pub fn PollCompletion(&self) -> Option<rdmaffi::ibv_wc> {
+        return self.completions.lock().pop_front();
+    }
+
+    pub fn HandleEvent(&self) {
+        let mut data: u64 = 0;
+        unsafe {
+            libc::read(self.eventfd, &mut data as *mut _ as *mut libc::c_void, 8);
+        }
+    }
+}
+
+// the emulated RC queue pair over the node handshake tcp connection
+pub struct TcpQueuePair {
+    pub qpNum: u32,
+    pub fd: i32,
+    // the posted receive requests, one is consumed by each write with immediate
+    pub recvs: Arc<Mutex<VecDeque<u64>>>,
+    // the frame bytes the connection doesn't take yet, they are sent on EPOLLOUT so that the
+    // srv event loop doesn't block on a full socket buffer
+    pub pending: Mutex<VecDeque<u8>>,
+}
+
+impl TcpQueuePair {
+    pub fn New(fd: i32) -> Self {
+        return Self {
+            qpNum: TCP_TRANSPORT.nextQpNum.fetch_add(1, Ordering::SeqCst),
+            fd: fd,
+            recvs: Arc::new(Mutex::new(VecDeque::new())),
+            pending: Mutex::new(VecDeque::new()),
+        };
+    }
+
+    // the frame is sent without blocking, the rest is queued after the pending bytes. The
+    // local buffer is copied, so the write completes when the frame is queued
+    pub fn WriteFrame(&self, hdr: &TcpFrameHdr, laddr: u64) -> Result<()> {
+        let hdrBytes = unsafe {
+            std::slice::from_raw_parts(
+                hdr as *const _ as *const u8,
+                mem::size_of::<TcpFrameHdr>(),
+            )
+        };
+        let payload = unsafe { std::slice::from_raw_parts(laddr as *const u8, hdr.len as usize) };
+
+        let mut pending = self.pending.lock();
+        if pending.len() > 0 {
+            pending.extend(hdrBytes);
+            pending.extend(payload);
+            return Ok(());
+        }
+
+        let sent = SendSome(self.fd, hdrBytes)?;
+        if sent < hdrBytes.len() {
+            pending.extend(&hdrBytes[sent..]);
+            pending.extend(payload);
+            return Ok(());
+        }
+
+        let sent = SendSome(self.fd, payload)?;
+        pending.extend(&payload[sent..]);
+        return Ok(());
+    }
+
+    pub fn PendingLen(&self) -> usize {
+        return self.pending.lock().len();
+    }
+
+    pub fn ProcessRecv(qpNum: u32, fd: i32, recvs: &Mutex<VecDeque<u64>>) -> Result<()> {
+        let mut hdr = TcpFrameHdr::default();
+        loop {
+            ReadAll(fd, &mut hdr as *mut _ as u64, mem::size_of::<TcpFrameHdr>())?;
+            if hdr.frameType == TcpFrameType::WriteImm as u32 {
+                if hdr.len > 0 {
+                    TCP_TRANSPORT.CheckAccess(hdr.rkey, hdr.raddr, hdr.len)?;
+                    ReadAll(fd, hdr.raddr, hdr.len as usize)?;
+                }
+
+                // the sender's flow control keeps it under the remote receive request count
+                let wrId = match recvs.lock().pop_front() {
+                    None => {
+                        return Err(Error::Common(format!(
+                            "TcpQueuePair {} receives write without receive request",
+                            qpNum
+                        )))
+                    }
+                    Some(wrId) => wrId,
+                };
+
+                TCP_TRANSPORT.Complete(
+                    wrId,
+                    rdmaffi::ibv_wc_opcode::IBV_WC_RECV_RDMA_WITH_IMM,
+                    hdr.len,
+                    hdr.imm,
+                    qpNum,
+                );
+            } else if hdr.frameType == TcpFrameType::SendUD as u32 {
+                let recv = TCP_TRANSPORT.udRecvs.lock().pop_front();
+                match recv {
+                    Some(recv) if hdr.len as u64 + UD_GRH_SIZE <= recv.len as u64 => {
+                        ReadAll(fd, recv.addr + UD_GRH_SIZE, hdr.len as usize)?;
+                        TCP_TRANSPORT.Complete(
+                            recv.wrId,
+                            rdmaffi::ibv_wc_opcode::IBV_WC_RECV,
+                            hdr.len + UD_GRH_SIZE as u32,
+                            0,
+                            0,
+                        );
+                    }
+                    _ => {
+                        // same as UD, the datagram is dropped when there is no receive buffer
+                        if let Some(recv) = recv {
+                            TCP_TRANSPORT.udRecvs.lock().push_front(recv);
+                        }
+                        Discard(fd, hdr.len as usize)?;
+                    }
+                }
+            } else {
+                return Err(Error::Common(format!(
+                    "TcpQueuePair {} receives unknown frame type {}",
+                    qpNum, hdr.frameType
+                )));
+            }
+        }
+    }
+
+    // the queue pair goes to the error state as a RDMA queue pair: the connection is shut down
+    // so that the remote side fails too, and the posted receive requests are flushed with error
+    pub fn Fail(qpNum: u32, fd: i32, recvs: &Mutex<VecDeque<u64>>) {
+        unsafe {
+            libc::shutdown(fd, libc::SHUT_RDWR);
+        }
+
+        let wrIds: Vec<u64> = recvs.lock().drain(..).collect();
+        for wrId in wrIds {
+            TCP_TRANSPORT.CompleteFlushErr(wrId, qpNum);
+        }
+    }
+}
+
+impl RDMATransport for TcpQueuePair {
+    fn qpNum(&self) -> u32 {
+        return self.qpNum;
+    }
+
+    fn Setup(&self, _remoteQpNum: u32, _lid: u16, _gid: Gid) -> Result<()> {
+        return Ok(());
+    }
+
+    fn Start(&self) {
+        let qpNum = self.qpNum;
+        let fd = self.fd;
+        let recvs = self.recvs.clone();
+        thread::spawn(move || match Self::ProcessRecv(qpNum, fd, &recvs) {
+            Ok(()) => (),
+            Err(e) => {
+                error!("TcpQueuePair {} recv fail with error {:?}", qpNum, e);
+                Self::Fail(qpNum, fd, &recvs);
+            }
+        });
+    }
+
+    fn Flush(&self) -> Result<()> {
+        let mut pending = self.pending.lock();
+        while pending.len() > 0 {
+            let sent = {
+                let (data, _) = pending.as_slices();
+                SendSome(self.fd, data)?
+            };
+
+            if sent == 0 {
+                break;
+            }
+            pending.drain(..sent);
+        }
+
+        return Ok(());
+    }
+
+    fn WriteImm(
+        &self,
+        wrId: u64,
+        laddr: u64,
+        len: u32,
+        lkey: u32,
+        raddr: u64,
+        rkey: u32,
+        imm: u32,
+    ) -> Result<()> {
+        if len > 0 {
+            TCP_TRANSPORT.CheckAccess(lkey, laddr, len)?;
+        }
+
+        let hdr = TcpFrameHdr {
+            frameType: TcpFrameType::WriteImm as u32,
+            len: len,
+            imm: imm,
+            rkey: rkey,
+            raddr: raddr,
+        };
+
+        self.WriteFrame(&hdr, laddr)?;
+        TCP_TRANSPORT.Complete(
+            wrId,
+            rdmaffi::ibv_wc_opcode::IBV_WC_RDMA_WRITE,
+            len,
+            0,
+            self.qpNum,
+        );
+        return Ok(());
+    }
+
+    fn PostRecv(&self, wrId: u64, _addr: u64, _lkey: u32, _length: u32) -> Result<()> {
+        self.recvs.lock().push_back(wrId);
+        return Ok(());
+    }
+
+    fn SendUD(
+        &self,
+        _ah: &AddressHandler,
+        _remoteQpNum: u32,
+        wrId: u64,
+        laddr: u64,
+        len: u32,
+        lkey: u32,
+    ) -> Result<()> {
+        TCP_TRANSPORT.CheckAccess(lkey, laddr, len)?;
+        let hdr = TcpFrameHdr {
+            frameType: TcpFrameType::SendUD as u32,
+            len: len,
+            ..Default::default()
+        };
+
+        self.WriteFrame(&hdr, laddr)?;
+        TCP_TRANSPORT.Complete(wrId, rdmaffi::ibv_wc_opcode::IBV_WC_SEND, len, 0, 0);
+        return Ok(());
+    }
+}
+
+// the handshake socket is nonblocking, the recv thread waits until it is readable
+fn WaitFd(fd: i32, events: i16) -> Result<()> {
+    let mut pollfd = libc::pollfd {
+        fd: fd,
+        events: events,
+        revents: 0,
+    };
+
+    let ret = unsafe { libc::poll(&mut pollfd, 1, -1) };
+    if ret < 0 && errno::errno().0 != SysErr::EINTR {
+        return Err(Error::SysError(errno::errno().0));
+    }
+
+    return Ok(());
+}
+
+fn ReadAll(fd: i32, addr: u64, len: usize) -> Result<()> {
+    let mut offset = 0;
+    while offset < len {
+        let ret = unsafe { libc::read(fd, (addr + offset as u64) as _, len - offset) };
+        if ret > 0 {
+            offset += ret as usize;
+            continue;
+        }
+
+        if ret == 0 {
+            return Err(Error::SysError(SysErr::ECONNRESET));
+        }
+
+        let errno = errno::errno().0;
+        if errno == SysErr::EAGAIN {
+            WaitFd(fd, libc::POLLIN)?;
+        } else if errno != SysErr::EINTR {
+            return Err(Error::SysError(errno));
+        }
+    }
+
+    return Ok(());
+}
+
+// read and drop the len bytes
+fn Discard(fd: i32, len: usize) -> Result<()> {
+    let mut buf = [0u8; DISCARD_BUF_SIZE];
+    let mut left = len;
+    while left > 0 {
+        let count = left.min(DISCARD_BUF_SIZE);
+        ReadAll(fd, buf.as_mut_ptr() as u64, count)?;
+        left -= count;
+    }
+
+    return Ok(());
+}
+
+// send the data until the socket buffer is full, the write to the failed connection returns
+// EPIPE instead of raising SIGPIPE
+// return: the sent byte count
+fn SendSome(fd: i32, data: &[u8]) -> Result<usize> {
+    let mut offset = 0;
+    while offset < data.len() {
+        let ret = unsafe {
+            libc::send(
+                fd,
+                data[offset..].as_ptr() as _,
+                data.len() - offset,
+                libc::MSG_NOSIGNAL,
+            )
+        };
+        if ret >= 0 {
+            offset += ret as usize;
+            continue;
+        }
+
+        let errno = errno::errno().0;
+        if errno == SysErr::EAGAIN {
+            break;
+        } else if errno != SysErr::EINTR {
+            return Err(Error::SysError(errno));
+        }
+    }
+
+    return Ok(offset);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    // the region is registered to the transport without the ibv_mr
+    fn Region(buf: &Vec<u8>) -> u32 {
+        let key = TCP_TRANSPORT.nextKey.fetch_add(1, Ordering::SeqCst);
+        TCP_TRANSPORT
+            .regions
+            .lock()
+            .insert(key, (buf.as_ptr() as u64, buf.len() as u64));
+        return key;
+    }
+
+    fn SocketPair(sndbuf: i32) -> (i32, i32) {
+        let mut fds = [0; 2];
+        let ret = unsafe {
+            libc::socketpair(
+                libc::AF_UNIX,
+                libc::SOCK_STREAM | libc::SOCK_NONBLOCK,
+                0,
+                fds.as_mut_ptr(),
+            )
+        };
+        assert_eq!(ret, 0);
+
+        if sndbuf > 0 {
+            for fd in &fds {
+                unsafe {
+                    libc::setsockopt(
+                        *fd,
+                        libc::SOL_SOCKET,
+                        libc::SO_SNDBUF,
+                        &sndbuf as *const _ as *const libc::c_void,
+                        mem::size_of::<i32>() as u32,
+                    );
+                }
+            }
+        }
+
+        return (fds[0], fds[1]);
+    }
+
+    // wait for the completion of the queue pair, the completion queue is shared by the tests
+    fn WaitCompletion(qpNum: u32, opcode: u32) -> rdmaffi::ibv_wc {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        loop {
+            {
+                let mut completions = TCP_TRANSPORT.completions.lock();
+                let idx = completions
+                    .iter()
+                    .position(|wc| wc.qp_num == qpNum && wc.opcode == opcode);
+                if let Some(idx) = idx {
+                    return completions.remove(idx).unwrap();
+                }
+            }
+
+            assert!(Instant::now() < deadline, "no completion for qp {}", qpNum);
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn TestTcpQueuePairWriteImm() {
+        let (fd1, fd2) = SocketPair(0);
+        let sender = TcpQueuePair::New(fd1);
+        let receiver = TcpQueuePair::New(fd2);
+
+        let src: Vec<u8> = (0..4096).map(|i| i as u8).collect();
+        let dst = vec![0u8; 4096];
+        let lkey = Region(&src);
+        let rkey = Region(&dst);
+
+        receiver.PostRecv(7, 0, 0, 0).unwrap();
+        receiver.Start();
+
+        sender
+            .WriteImm(
+                1,
+                src.as_ptr() as u64,
+                src.len() as u32,
+                lkey,
+                dst.as_ptr() as u64,
+                rkey,
+                42,
+            )
+            .unwrap();
+
+        let wc = WaitCompletion(
+            receiver.qpNum,
+            rdmaffi::ibv_wc_opcode::IBV_WC_RECV_RDMA_WITH_IMM,
+        );
+        assert_eq!(wc.wr_id, 7);
+        assert_eq!(wc.byte_len, 4096);
+        assert_eq!(unsafe { wc.imm_data_invalidated_rkey_union.imm_data }, 42);
+        assert_eq!(src, dst);
+
+        // the write out of the registered region is rejected
+        assert!(sender
+            .WriteImm(2, src.as_ptr() as u64, 8192, lkey, dst.as_ptr() as u64, rkey, 0)
+            .is_err());
+    }
+
+    #[test]
+    fn TestTcpQueuePairPendingFlush() {
+        let (fd1, fd2) = SocketPair(4096);
+        let sender = TcpQueuePair::New(fd1);
+        let receiver = TcpQueuePair::New(fd2);
+
+        let len = 1 << 20;
+        let src: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+        let dst = vec![0u8; len];
+        let lkey = Region(&src);
+        let rkey = Region(&dst);
+
+        // nobody reads the connection yet, the write returns with the data queued
+        sender
+            .WriteImm(
+                1,
+                src.as_ptr() as u64,
+                len as u32,
+                lkey,
+                dst.as_ptr() as u64,
+                rkey,
+                0,
+            )
+            .unwrap();
+        assert!(sender.PendingLen() > 0);
+
+        receiver.PostRecv(8, 0, 0, 0).unwrap();
+        receiver.Start();
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while sender.PendingLen() > 0 {
+            assert!(Instant::now() < deadline, "the pending data is not flushed");
+            sender.Flush().unwrap();
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        let wc = WaitCompletion(
+            receiver.qpNum,
+            rdmaffi::ibv_wc_opcode::IBV_WC_RECV_RDMA_WITH_IMM,
+        );
+        assert_eq!(wc.wr_id, 8);
+        assert_eq!(wc.byte_len as usize, len);
+        assert_eq!(src, dst);
+    }
+}